@@ -13,19 +13,22 @@ mod transaction;
 use self::access::gen_state_access_trace;
 use crate::error::Error;
 use crate::evm::opcodes::{gen_associated_ops, gen_begin_tx_ops, gen_end_tx_ops};
-use crate::operation::{CallContextField, Operation, RWCounter, StartOp, RW};
-use crate::rpc::GethClient;
+use crate::operation::{
+    AccountField, CallContextField, Operation, RWCounter, StartOp, TxLogField, RW,
+};
 use crate::state_db::{self, CodeDB, StateDB};
+use crate::state_provider::{StateProvider, StateProviderError};
 pub use access::{Access, AccessSet, AccessValue, CodeSource};
 pub use block::{Block, BlockContext};
 pub use call::{Call, CallContext, CallKind};
 use core::fmt::Debug;
+use eth_types::evm_types::Hardfork;
 use eth_types::sign_types::{pk_bytes_le, pk_bytes_swap_endianness, SignData};
-use eth_types::ToWord;
 use eth_types::{self, geth_types, Address, GethExecStep, GethExecTrace, Word};
-use ethers_providers::JsonRpcClient;
+use eth_types::{ToAddress, ToBigEndian, ToWord, H256};
 pub use execution::{
     CopyDataType, CopyEvent, CopyStep, ExecState, ExecStep, ExpEvent, ExpStep, NumberOrHash,
+    PrecompileEvent,
 };
 pub use input_state_ref::CircuitInputStateRef;
 use itertools::Itertools;
@@ -45,12 +48,26 @@ pub struct CircuitsParams {
     pub max_txs: usize,
     /// Maximum number of bytes from all txs calldata in the Tx Circuit
     pub max_calldata: usize,
+    /// Maximum number of addresses and storage keys from all txs access lists
+    /// in the Tx Circuit
+    pub max_access_list: usize,
     /// Maximum number of bytes supported in the Bytecode Circuit
     pub max_bytecode: usize,
+    /// Maximum number of calls to ecrecover whose signature is verified in the
+    /// Tx Circuit
+    pub max_ecrecover: usize,
+    /// Maximum number of bytes of the encoding of the receipts of all txs,
+    /// hashed into the receipts root in the PI Circuit
+    pub max_receipts_len: usize,
     // TODO: Rename for consistency
     /// Pad the keccak circuit with this number of invocations to a static
     /// capacity.  Number of keccak_f that the Keccak circuit will support.
     pub keccak_padding: Option<usize>,
+    /// Reward in wei paid to the coinbase at the end of the block, also when
+    /// it has no txs.  No reward is paid when it's 0.
+    pub block_reward: Word,
+    /// Hardfork whose opcodes and gas schedule are used to execute the block.
+    pub hardfork: Hardfork,
 }
 
 impl Default for CircuitsParams {
@@ -60,8 +77,13 @@ impl Default for CircuitsParams {
             max_rws: 1000,
             max_txs: 1,
             max_calldata: 256,
+            max_access_list: 16,
             max_bytecode: 512,
+            max_ecrecover: 0,
+            max_receipts_len: 512,
             keccak_padding: None,
+            block_reward: Word::zero(),
+            hardfork: Hardfork::default(),
         }
     }
 }
@@ -182,21 +204,42 @@ impl<'a> CircuitInputBuilder {
             self.handle_tx(tx, geth_trace, tx_index + 1 == eth_block.transactions.len())?;
         }
         self.set_value_ops_call_context_rwc_eor();
-        self.set_end_block();
+        self.set_end_block()?;
         Ok(())
     }
 
-    fn set_end_block(&mut self) {
+    fn set_end_block(&mut self) -> Result<(), Error> {
         let max_rws = self.block.circuits_params.max_rws;
+        let mut block_reward_step = self.block.block_steps.block_reward.clone();
         let mut end_block_not_last = self.block.block_steps.end_block_not_last.clone();
         let mut end_block_last = self.block.block_steps.end_block_last.clone();
-        end_block_not_last.rwc = self.block_ctx.rwc;
-        end_block_last.rwc = self.block_ctx.rwc;
+        block_reward_step.rwc = self.block_ctx.rwc;
 
         let mut dummy_tx = Transaction::dummy();
         let mut dummy_tx_ctx = TransactionContext::default();
         let mut state = self.state_ref(&mut dummy_tx, &mut dummy_tx_ctx);
 
+        let block_reward = state.block.circuits_params.block_reward;
+        if !block_reward.is_zero() {
+            let coinbase = state.block.coinbase;
+            let (found, coinbase_account) = state.sdb.get_account_mut(&coinbase);
+            if !found {
+                return Err(Error::AccountNotFound(coinbase));
+            }
+            let coinbase_balance_prev = coinbase_account.balance;
+            let coinbase_balance = coinbase_balance_prev + block_reward;
+            coinbase_account.balance = coinbase_balance;
+            state.account_write(
+                &mut block_reward_step,
+                coinbase,
+                AccountField::Balance,
+                coinbase_balance,
+                coinbase_balance_prev,
+            )?;
+        }
+        end_block_not_last.rwc = state.block_ctx.rwc;
+        end_block_last.rwc = state.block_ctx.rwc;
+
         if let Some(call_id) = state.block.txs.last().map(|tx| tx.calls[0].call_id) {
             state.call_context_read(
                 &mut end_block_last,
@@ -230,8 +273,10 @@ impl<'a> CircuitInputBuilder {
             StartOp {},
         );
 
+        self.block.block_steps.block_reward = block_reward_step;
         self.block.block_steps.end_block_not_last = end_block_not_last;
         self.block.block_steps.end_block_last = end_block_last;
+        Ok(())
     }
 
     /// Handle a transaction with its corresponding execution trace to generate
@@ -252,8 +297,8 @@ impl<'a> CircuitInputBuilder {
         // - execution_state: BeginTx
         // - op: None
         // Generate BeginTx step
-        let begin_tx_step = gen_begin_tx_ops(&mut self.state_ref(&mut tx, &mut tx_ctx))?;
-        tx.steps_mut().push(begin_tx_step);
+        let begin_tx_steps = gen_begin_tx_ops(&mut self.state_ref(&mut tx, &mut tx_ctx))?;
+        tx.steps_mut().extend(begin_tx_steps);
 
         for (index, geth_step) in geth_trace.struct_logs.iter().enumerate() {
             let mut state_ref = self.state_ref(&mut tx, &mut tx_ctx);
@@ -287,17 +332,41 @@ pub fn keccak_inputs(block: &Block, code_db: &CodeDB) -> Result<Vec<Vec<u8>>, Er
     // Tx Circuit
     let txs: Vec<geth_types::Transaction> = block.txs.iter().map(|tx| tx.into()).collect();
     keccak_inputs.extend_from_slice(&keccak_inputs_tx_circuit(&txs, block.chain_id.as_u64())?);
+    // Tx Circuit, whose SignVerify chip also verifies the calls to ecrecover
+    let ecrecover_sign_datas: Vec<SignData> = block
+        .precompile_events
+        .iter()
+        .filter_map(|event| event.ecrecover_sign_data())
+        .collect();
+    keccak_inputs.extend_from_slice(&keccak_inputs_sign_verify(&ecrecover_sign_datas));
     // Bytecode Circuit
     for bytecode in code_db.0.values() {
         keccak_inputs.push(bytecode.clone());
     }
     // EVM Circuit
     keccak_inputs.extend_from_slice(&block.sha3_inputs);
+    // Bloom Circuit
+    keccak_inputs.extend_from_slice(&keccak_inputs_bloom_circuit(block));
     // MPT Circuit
     // TODO https://github.com/privacy-scaling-explorations/zkevm-circuits/issues/696
     Ok(keccak_inputs)
 }
 
+/// Generate the keccak inputs required by the Bloom Circuit from the address
+/// and topics of the logs.
+pub fn keccak_inputs_bloom_circuit(block: &Block) -> Vec<Vec<u8>> {
+    block
+        .container
+        .tx_log
+        .iter()
+        .filter_map(|op| match op.op().field {
+            TxLogField::Address => Some(op.op().value.to_address().as_bytes().to_vec()),
+            TxLogField::Topic => Some(op.op().value.to_be_bytes().to_vec()),
+            _ => None,
+        })
+        .collect()
+}
+
 /// Generate the keccak inputs required by the SignVerify Chip from the
 /// signature datas.
 pub fn keccak_inputs_sign_verify(sigs: &[SignData]) -> Vec<Vec<u8>> {
@@ -352,6 +421,37 @@ pub fn get_create_init_code<'a, 'b>(
         [offset.low_u64() as usize..(offset.low_u64() + length.low_u64()) as usize])
 }
 
+/// Returns the keccak preimage of a CREATE address, which is the RLP encoding
+/// of the list `[sender, nonce]`.
+pub fn get_create_address_preimage(sender: Address, nonce: u64) -> Vec<u8> {
+    let nonce_rlp = if nonce == 0 {
+        vec![0x80]
+    } else if nonce < 0x80 {
+        vec![nonce as u8]
+    } else {
+        let nonce_bytes = nonce.to_be_bytes();
+        let nonce_bytes = &nonce_bytes[(nonce.leading_zeros() / 8) as usize..];
+        std::iter::once(0x80 + nonce_bytes.len() as u8)
+            .chain(nonce_bytes.iter().copied())
+            .collect()
+    };
+    std::iter::once(0xc0 + 21 + nonce_rlp.len() as u8)
+        .chain(std::iter::once(0x80 + 20))
+        .chain(sender.to_fixed_bytes())
+        .chain(nonce_rlp)
+        .collect()
+}
+
+/// Returns the keccak preimage of a CREATE2 address, which is
+/// `0xff ++ sender ++ salt ++ keccak256(init_code)`.
+pub fn get_create2_address_preimage(sender: Address, salt: Word, code_hash: H256) -> Vec<u8> {
+    std::iter::once(0xff)
+        .chain(sender.to_fixed_bytes())
+        .chain(salt.to_be_bytes())
+        .chain(code_hash.to_fixed_bytes())
+        .collect()
+}
+
 /// Retrieve the memory offset and length of call.
 pub fn get_call_memory_offset_length(step: &GethExecStep, nth: usize) -> Result<(u64, u64), Error> {
     let offset = step.stack.nth_last(nth)?;
@@ -365,72 +465,61 @@ pub fn get_call_memory_offset_length(step: &GethExecStep, nth: usize) -> Result<
 
 type EthBlock = eth_types::Block<eth_types::Transaction>;
 
-/// Struct that wraps a GethClient and contains methods to perform all the steps
-/// necessary to generate the circuit inputs for a block by querying geth for
-/// the necessary information and using the CircuitInputBuilder.
-pub struct BuilderClient<P: JsonRpcClient> {
-    cli: GethClient<P>,
+/// Struct that wraps a [`StateProvider`] and contains methods to perform all
+/// the steps necessary to generate the circuit inputs for a block by querying
+/// the provider for the necessary information and using the
+/// CircuitInputBuilder.
+pub struct BuilderClient<S: StateProvider> {
+    provider: S,
     chain_id: Word,
     circuits_params: CircuitsParams,
 }
 
-impl<P: JsonRpcClient> BuilderClient<P> {
+impl<S: StateProvider> BuilderClient<S> {
     /// Create a new BuilderClient
-    pub async fn new(
-        client: GethClient<P>,
-        circuits_params: CircuitsParams,
-    ) -> Result<Self, Error> {
-        let chain_id = client.get_chain_id().await?;
+    pub async fn new(provider: S, circuits_params: CircuitsParams) -> Result<Self, Error> {
+        let chain_id = provider.chain_id().await?;
 
         Ok(Self {
-            cli: client,
+            provider,
             chain_id: chain_id.into(),
             circuits_params,
         })
     }
 
-    /// Step 1. Query geth for Block, Txs, TxExecTraces, history block hashes
-    /// and previous state root.
+    /// Step 1. Query the provider for Block, Txs, TxExecTraces, history block
+    /// hashes and previous state root.
     pub async fn get_block(
         &self,
         block_num: u64,
     ) -> Result<(EthBlock, Vec<eth_types::GethExecTrace>, Vec<Word>, Word), Error> {
-        let eth_block = self.cli.get_block_by_number(block_num.into()).await?;
-        let geth_traces = self.cli.trace_block_by_number(block_num.into()).await?;
-
-        // fetch up to 256 blocks
-        let mut n_blocks = std::cmp::min(256, block_num as usize);
-        let mut next_hash = eth_block.parent_hash;
-        let mut prev_state_root: Option<Word> = None;
-        let mut history_hashes = vec![Word::default(); n_blocks];
-        while n_blocks > 0 {
-            n_blocks -= 1;
-
-            // TODO: consider replacing it with `eth_getHeaderByHash`, it's faster
-            let header = self.cli.get_block_by_hash(next_hash).await?;
-
-            // set the previous state root
-            if prev_state_root.is_none() {
-                prev_state_root = Some(header.state_root.to_word());
-            }
+        // fetch up to 256 blocks, the latest one being the last
+        let block_nums: Vec<u64> = (block_num.saturating_sub(256)..block_num).collect();
+        let (eth_block, geth_traces, headers) = futures::try_join!(
+            self.provider.block(block_num),
+            self.provider.traces(block_num),
+            self.provider.headers(&block_nums),
+        )?;
 
-            // latest block hash is the last item
+        // check that the headers are the ancestors of the block
+        let mut history_hashes = vec![Word::default(); headers.len()];
+        let mut parent_hash = eth_block.parent_hash;
+        for (index, header) in headers.iter().enumerate().rev() {
             let block_hash = header
                 .hash
-                .ok_or(Error::EthTypeError(eth_types::Error::IncompleteBlock))?
-                .to_word();
-            history_hashes[n_blocks] = block_hash;
-
-            // continue
-            next_hash = header.parent_hash;
+                .ok_or(Error::EthTypeError(eth_types::Error::IncompleteBlock))?;
+            if block_hash != parent_hash {
+                return Err(StateProviderError::HeaderMismatch(block_nums[index]).into());
+            }
+            history_hashes[index] = block_hash.to_word();
+            parent_hash = header.parent_hash;
         }
+        let prev_state_root = headers
+            .last()
+            .map(|header| header.state_root.to_word())
+            .unwrap_or_default();
 
-        Ok((
-            eth_block,
-            geth_traces,
-            history_hashes,
-            prev_state_root.unwrap_or_default(),
-        ))
+        Ok((eth_block, geth_traces, history_hashes, prev_state_root))
     }
 
     /// Step 2. Get State Accesses from TxExecTraces
@@ -457,8 +546,8 @@ impl<P: JsonRpcClient> BuilderClient<P> {
         Ok(AccessSet::from(block_access_trace))
     }
 
-    /// Step 3. Query geth for all accounts, storage keys, and codes from
-    /// Accesses
+    /// Step 3. Query the provider for all accounts, storage keys, and codes
+    /// from Accesses
     pub async fn get_state(
         &self,
         block_num: u64,
@@ -470,27 +559,21 @@ impl<P: JsonRpcClient> BuilderClient<P> {
         ),
         Error,
     > {
-        let mut proofs = Vec::new();
-        for (address, key_set) in access_set.state {
-            let mut keys: Vec<Word> = key_set.iter().cloned().collect();
-            keys.sort();
-            let proof = self
-                .cli
-                .get_proof(address, keys, (block_num - 1).into())
-                .await
-                .unwrap();
-            proofs.push(proof);
-        }
-        let mut codes: HashMap<Address, Vec<u8>> = HashMap::new();
-        for address in access_set.code {
-            let code = self
-                .cli
-                .get_code(address, (block_num - 1).into())
-                .await
-                .unwrap();
-            codes.insert(address, code);
-        }
-        Ok((proofs, codes))
+        let accounts: Vec<(Address, Vec<Word>)> = access_set
+            .state
+            .into_iter()
+            .map(|(address, key_set)| {
+                let mut keys: Vec<Word> = key_set.into_iter().collect();
+                keys.sort();
+                (address, keys)
+            })
+            .collect();
+        let addresses: Vec<Address> = access_set.code.into_iter().collect();
+        let (proofs, codes) = futures::try_join!(
+            self.provider.proofs(block_num - 1, &accounts),
+            self.provider.codes(block_num - 1, &addresses),
+        )?;
+        Ok((proofs, addresses.into_iter().zip(codes).collect()))
     }
 
     /// Step 4. Build a partial StateDB from step 3
@@ -561,8 +644,8 @@ impl<P: JsonRpcClient> BuilderClient<P> {
             self.get_block(block_num).await?;
         let access_set = self.get_state_accesses(&eth_block, &geth_traces)?;
         let (proofs, codes) = self.get_state(block_num, access_set).await?;
-        let (state_db, code_db) = self.build_state_code_db(proofs, codes);
-        let builder = self.gen_inputs_from_state(
+        let (state_db, code_db) = self.build_state_code_db(proofs.clone(), codes);
+        let mut builder = self.gen_inputs_from_state(
             state_db,
             code_db,
             &eth_block,
@@ -570,6 +653,17 @@ impl<P: JsonRpcClient> BuilderClient<P> {
             history_hashes,
             prev_state_root,
         )?;
+        // The remaining child of a branch collapsed by a deletion is only in the
+        // proofs against the state after the block, merged into its parent.
+        let accounts: Vec<(Address, Vec<Word>)> = proofs
+            .iter()
+            .map(|proof| {
+                let keys = proof.storage_proof.iter().map(|proof| proof.key).collect();
+                (proof.address, keys)
+            })
+            .collect();
+        builder.block.post_state_proofs = self.provider.proofs(block_num, &accounts).await?;
+        builder.block.state_proofs = proofs;
         Ok((builder, eth_block))
     }
 }
@@ -2,6 +2,7 @@
 
 use super::{
     execution::ExecState, transaction::Transaction, CircuitsParams, CopyEvent, ExecStep, ExpEvent,
+    PrecompileEvent,
 };
 use crate::{
     operation::{OperationContainer, RWCounter},
@@ -44,6 +45,9 @@ impl BlockContext {
 /// Block-wise execution steps that don't belong to any Transaction.
 #[derive(Debug)]
 pub struct BlockSteps {
+    /// BlockReward step paying the block reward to the coinbase after the
+    /// last transaction, also in blocks without transactions.
+    pub block_reward: ExecStep,
     /// EndBlock step that is repeated after the last transaction and before
     /// reaching the last EVM row.
     pub end_block_not_last: ExecStep,
@@ -86,11 +90,21 @@ pub struct Block {
     pub sha3_inputs: Vec<Vec<u8>>,
     /// Exponentiation events in the block.
     pub exp_events: Vec<ExpEvent>,
+    /// Precompiled contract call events in the block.
+    pub precompile_events: Vec<PrecompileEvent>,
     code: HashMap<Hash, Vec<u8>>,
     /// Circuits Setup Paramteres
     pub circuits_params: CircuitsParams,
     /// Original block from geth
     pub eth_block: eth_types::Block<eth_types::Transaction>,
+    /// Merkle proofs of the accounts and storage slots accessed by the block,
+    /// made against `prev_state_root` with `eth_getProof`. Empty for blocks
+    /// built from mock data.
+    pub state_proofs: Vec<eth_types::EIP1186ProofResponse>,
+    /// Merkle proofs of the same accounts and storage slots made against the
+    /// state root of the block, which hold the nodes merged by the deletions
+    /// of the block. Empty for blocks built from mock data.
+    pub post_state_proofs: Vec<eth_types::EIP1186ProofResponse>,
 }
 
 impl Block {
@@ -128,6 +142,10 @@ impl Block {
             container: OperationContainer::new(),
             txs: Vec::new(),
             block_steps: BlockSteps {
+                block_reward: ExecStep {
+                    exec_state: ExecState::BlockReward,
+                    ..ExecStep::default()
+                },
                 end_block_not_last: ExecStep {
                     exec_state: ExecState::EndBlock,
                     ..ExecStep::default()
@@ -139,10 +157,13 @@ impl Block {
             },
             copy_events: Vec::new(),
             exp_events: Vec::new(),
+            precompile_events: Vec::new(),
             code: HashMap::new(),
             sha3_inputs: Vec::new(),
             circuits_params,
             eth_block: eth_block.clone(),
+            state_proofs: Vec::new(),
+            post_state_proofs: Vec::new(),
         })
     }
 
@@ -166,4 +187,8 @@ impl Block {
     pub fn add_exp_event(&mut self, event: ExpEvent) {
         self.exp_events.push(event);
     }
+    /// Push a precompiled contract call event to the block.
+    pub fn add_precompile_event(&mut self, event: PrecompileEvent) {
+        self.precompile_events.push(event);
+    }
 }
@@ -1,11 +1,15 @@
 //! Execution step related module.
 
 use crate::{
-    circuit_input_builder::CallContext, error::ExecError, exec_trace::OperationRef,
+    circuit_input_builder::CallContext,
+    error::ExecError,
+    exec_trace::OperationRef,
     operation::RWCounter,
+    precompile::{self, PrecompileCalls},
 };
 use eth_types::{
     evm_types::{Gas, GasCost, OpcodeId, ProgramCounter},
+    sign_types::SignData,
     GethExecStep, Word, H256,
 };
 use gadgets::impl_expr;
@@ -108,10 +112,18 @@ pub enum ExecState {
     Op(OpcodeId),
     /// Virtual step Begin Tx
     BeginTx,
+    /// Virtual step warming up an entry of the access list of the tx, right
+    /// after Begin Tx
+    BeginTxAccessList,
     /// Virtual step End Tx
     EndTx,
+    /// Virtual step paying the block reward to the coinbase, after the last
+    /// tx of the block
+    BlockReward,
     /// Virtual step End Block
     EndBlock,
+    /// Virtual step Precompile, executing the precompiled contract of a call
+    Precompile(PrecompileCalls),
 }
 
 impl ExecState {
@@ -167,6 +179,10 @@ pub enum CopyDataType {
     /// scenario where we wish to accumulate the value (RLC) over all rows.
     /// This is used for Copy Lookup from SHA3 opcode verification.
     RlcAcc,
+    /// When the source for the copy event is the output of a precompiled
+    /// contract call, which is bound to the call by reading it back into an
+    /// RLC.
+    PrecompileOutput,
 }
 
 impl From<CopyDataType> for usize {
@@ -245,19 +261,21 @@ impl CopyEvent {
     // increase in rw counter from the start of the copy event to step index
     fn rw_counter_increase(&self, step_index: usize) -> u64 {
         let source_rw_increase = match self.src_type {
-            CopyDataType::Bytecode | CopyDataType::TxCalldata => 0,
+            CopyDataType::Bytecode | CopyDataType::TxCalldata | CopyDataType::PrecompileOutput => 0,
             CopyDataType::Memory => std::cmp::min(
                 u64::try_from(step_index + 1).unwrap() / 2,
                 self.src_addr_end
                     .checked_sub(self.src_addr)
                     .unwrap_or_default(),
             ),
-            CopyDataType::RlcAcc | CopyDataType::TxLog => unreachable!(),
+            CopyDataType::RlcAcc | CopyDataType::TxLog => {
+                unreachable!()
+            }
         };
         let destination_rw_increase = match self.dst_type {
             CopyDataType::RlcAcc | CopyDataType::Bytecode => 0,
             CopyDataType::TxLog | CopyDataType::Memory => u64::try_from(step_index).unwrap() / 2,
-            CopyDataType::TxCalldata => unreachable!(),
+            CopyDataType::TxCalldata | CopyDataType::PrecompileOutput => unreachable!(),
         };
         source_rw_increase + destination_rw_increase
     }
@@ -298,3 +316,46 @@ pub struct ExpEvent {
     /// Intermediate multiplication results.
     pub steps: Vec<ExpStep>,
 }
+
+/// Event representing a call to a precompiled contract, which is verified
+/// through the sig table for ecrecover and the precompile table for the other
+/// precompiled contracts but identity.
+#[derive(Clone, Debug)]
+pub struct PrecompileEvent {
+    /// Call ID of the precompiled contract call.
+    pub call_id: usize,
+    /// The precompiled contract which is called.
+    pub precompile: PrecompileCalls,
+    /// Input bytes of the call.
+    pub input: Vec<u8>,
+    /// Output bytes of the precompiled contract for the input, empty when the
+    /// input is malformed.  They're returned only when the call succeeds.
+    pub output: Vec<u8>,
+    /// Gas required by the precompiled contract for the input.
+    pub required_gas: u64,
+    /// Whether the input is well formed for the precompiled contract.
+    pub is_valid: bool,
+    /// Whether the call succeeds, which is when the input is well formed and
+    /// the call is given the required gas.
+    pub is_success: bool,
+}
+
+impl PrecompileEvent {
+    /// Return the signature verified by the SignVerify chip for a call to
+    /// ecrecover which recovers an address.  The recovery reduces the message
+    /// hash modulo the order of secp256k1, so a message hash which isn't
+    /// reduced yet can't be bound to the input bytes, and the call is looked up
+    /// from the precompile table instead.
+    pub fn ecrecover_sign_data(&self) -> Option<SignData> {
+        if self.precompile != PrecompileCalls::ECRecover
+            || !self.is_success
+            || self.output.is_empty()
+        {
+            return None;
+        }
+        let sign_data = precompile::ecrecover_sign_data(&self.input)?;
+        let msg_hash_be = sign_data.msg_hash.to_bytes().into_iter().rev();
+        let input_msg_hash = self.input.iter().copied().chain(std::iter::repeat(0));
+        input_msg_hash.take(32).eq(msg_hash_be).then_some(sign_data)
+    }
+}
@@ -13,12 +13,14 @@ use crate::{
         StackOp, Target, TxAccessListAccountOp, TxLogField, TxLogOp, TxReceiptField, TxReceiptOp,
         RW,
     },
+    precompile,
     state_db::{CodeDB, StateDB},
     Error,
 };
 use eth_types::{
     evm_types::{
         gas_utils::memory_expansion_gas_cost, Gas, GasCost, MemoryAddress, OpcodeId, StackAddress,
+        MAX_CODE_SIZE,
     },
     Address, GethExecStep, ToAddress, ToBigEndian, ToWord, Word, H256,
 };
@@ -573,7 +575,7 @@ impl<'a> CircuitInputStateRef<'a> {
 
     /// Check if address is a precompiled or not.
     pub fn is_precompiled(&self, address: &Address) -> bool {
-        address.0[0..19] == [0u8; 19] && (1..=9).contains(&address.0[19])
+        precompile::is_precompiled(address)
     }
 
     // TODO: Remove unwrap() and add err handling.
@@ -623,7 +625,8 @@ impl<'a> CircuitInputStateRef<'a> {
                     _ => address,
                 };
                 let (found, account) = self.sdb.get_account(&code_address);
-                if !found {
+                // Precompiled contracts don't need to exist in the state.
+                if !found && !self.is_precompiled(&code_address) {
                     return Err(Error::AccountNotFound(code_address));
                 }
                 (CodeSource::Address(code_address), account.code_hash)
@@ -765,13 +768,22 @@ impl<'a> CircuitInputStateRef<'a> {
             OpEnum::TxRefund(op) => {
                 self.sdb.set_refund(op.value);
             }
-            OpEnum::AccountDestructed(_) => unimplemented!(),
+            OpEnum::AccountDestructed(op) => {
+                if !op.is_destructed_prev && op.is_destructed {
+                    self.sdb.destruct_account(op.address);
+                }
+                if op.is_destructed_prev && !op.is_destructed {
+                    self.sdb.undestruct_account(&op.address);
+                }
+            }
             _ => unreachable!(),
         };
     }
 
-    /// Handle a reversion group
-    fn handle_reversion(&mut self) {
+    /// Handle a reversion group.  The reversion ops are pushed into the step
+    /// of the original op, which is taken from `current_exec_steps` if it's
+    /// not pushed into the transaction yet.
+    fn handle_reversion(&mut self, current_exec_steps: &mut [&mut ExecStep]) {
         let reversion_group = self
             .tx_ctx
             .reversion_groups
@@ -788,9 +800,16 @@ impl<'a> CircuitInputStateRef<'a> {
                     false,
                     op,
                 );
-                self.tx.steps_mut()[step_index]
-                    .bus_mapping_instance
-                    .push(rev_op_ref);
+                let step_count = self.tx.steps().len();
+                if step_index < step_count {
+                    self.tx.steps_mut()[step_index]
+                        .bus_mapping_instance
+                        .push(rev_op_ref);
+                } else {
+                    current_exec_steps[step_index - step_count]
+                        .bus_mapping_instance
+                        .push(rev_op_ref);
+                }
             }
         }
 
@@ -856,7 +875,7 @@ impl<'a> CircuitInputStateRef<'a> {
 
         // Handle reversion if this call doesn't end successfully
         if !call.is_success {
-            self.handle_reversion();
+            self.handle_reversion(&mut []);
         }
 
         // If current call has caller.
@@ -871,7 +890,38 @@ impl<'a> CircuitInputStateRef<'a> {
         Ok(())
     }
 
-    /// Bus mapping for the RestoreContextGadget as used in RETURN.
+    /// Handle the return from a call to a precompiled contract.  The caller's
+    /// CALL step (absent for a root call) and the precompile step are passed
+    /// in `current_exec_steps` since they are not pushed into the transaction
+    /// yet.
+    pub(crate) fn handle_precompile_return(
+        &mut self,
+        current_exec_steps: &mut [&mut ExecStep],
+        output: &[u8],
+    ) -> Result<(), Error> {
+        let call = self.call()?.clone();
+
+        // Handle reversion if this call doesn't end successfully
+        if !call.is_success {
+            self.handle_reversion(current_exec_steps);
+        }
+
+        // If current call has caller.
+        if !call.is_root {
+            self.caller_ctx_mut()?.return_data = output.to_vec();
+            let caller = self.caller_mut()?;
+            caller.last_callee_id = call.call_id;
+            caller.last_callee_return_data_offset = 0;
+            caller.last_callee_return_data_length = output.len() as u64;
+        }
+
+        self.tx_ctx.pop_call_ctx();
+
+        Ok(())
+    }
+
+    /// Bus mapping for the RestoreContextGadget as used in RETURN and
+    /// SELFDESTRUCT.
     // TODO: unify this with restore context bus mapping for STOP.
     // TODO: unify this with the `handle return function above.`
     pub fn handle_restore_context(
@@ -892,7 +942,7 @@ impl<'a> CircuitInputStateRef<'a> {
         let geth_step_next = &steps[1];
 
         let [last_callee_return_data_offset, last_callee_return_data_length] = match geth_step.op {
-            OpcodeId::STOP => [Word::zero(); 2],
+            OpcodeId::STOP | OpcodeId::SELFDESTRUCT => [Word::zero(); 2],
             OpcodeId::REVERT | OpcodeId::RETURN => {
                 let offset = geth_step.stack.nth_last(0)?;
                 let length = geth_step.stack.nth_last(1)?;
@@ -925,7 +975,15 @@ impl<'a> CircuitInputStateRef<'a> {
         } else {
             0
         };
-        let gas_refund = geth_step.gas.0 - memory_expansion_gas_cost - code_deposit_cost;
+        // SELFDESTRUCT charges its own gas cost before handing the remaining gas
+        // back to the caller.
+        let selfdestruct_gas_cost = if geth_step.op == OpcodeId::SELFDESTRUCT {
+            geth_step.gas_cost.0
+        } else {
+            0
+        };
+        let gas_refund =
+            geth_step.gas.0 - memory_expansion_gas_cost - code_deposit_cost - selfdestruct_gas_cost;
 
         let caller_gas_left = geth_step_next.gas.0 - gas_refund;
 
@@ -981,6 +1039,38 @@ impl<'a> CircuitInputStateRef<'a> {
         self.block.add_exp_event(event)
     }
 
+    /// Return the error of storing the code returned by RETURN in a
+    /// {CREATE, CREATE2} call or in a creation transaction, if any.
+    fn get_code_store_err(&self, step: &GethExecStep) -> Result<Option<ExecError>, Error> {
+        let call_ctx = self.call_ctx()?;
+        let offset = step.stack.nth_last(0)?;
+        let length = step.stack.nth_last(1)?;
+        if length > Word::from(MAX_CODE_SIZE) {
+            return Ok(Some(ExecError::MaxCodeSizeExceeded));
+        } else if self.block.circuits_params.hardfork.is_eip3541_enabled()
+            && length > Word::zero()
+            && !call_ctx.memory.is_empty()
+            && call_ctx.memory.0.get(offset.low_u64() as usize) == Some(&0xef)
+        {
+            return Ok(Some(ExecError::InvalidCreationCode));
+        }
+        // The code is stored after RETURN charges its memory expansion.
+        let curr_memory_word_size = call_ctx.memory.word_size() as u64;
+        let next_memory_word_size = if length.is_zero() {
+            curr_memory_word_size
+        } else {
+            max((offset + length + 31).as_u64() / 32, curr_memory_word_size)
+        };
+        let memory_expansion_gas_cost =
+            memory_expansion_gas_cost(curr_memory_word_size, next_memory_word_size);
+        if GasCost::CODE_DEPOSIT_BYTE_COST.as_u64() * length.as_u64() + memory_expansion_gas_cost
+            > step.gas.0
+        {
+            return Ok(Some(ExecError::CodeStoreOutOfGas));
+        }
+        Ok(None)
+    }
+
     pub(crate) fn get_step_err(
         &self,
         step: &GethExecStep,
@@ -990,17 +1080,25 @@ impl<'a> CircuitInputStateRef<'a> {
             return Ok(Some(get_step_reported_error(&step.op, error)));
         }
 
-        if matches!(step.op, OpcodeId::INVALID(_)) {
+        // Opcodes which aren't enabled yet in the hardfork are invalid.
+        let hardfork = self.block.circuits_params.hardfork;
+        if !hardfork.is_opcode_enabled(step.op) {
             return Ok(Some(ExecError::InvalidOpcode));
         }
 
-        // When last step has opcodes that halt, there's no error.
+        // When last step has opcodes that halt, there's no error, except for
+        // the code returned by a creation transaction, which is stored after
+        // the RETURN step.
         if matches!(next_step, None)
             && matches!(
                 step.op,
                 OpcodeId::STOP | OpcodeId::RETURN | OpcodeId::REVERT | OpcodeId::SELFDESTRUCT
             )
         {
+            let call = self.call()?;
+            if step.op == OpcodeId::RETURN && call.is_root && call.is_create() {
+                return self.get_code_store_err(step);
+            }
             return Ok(None);
         }
 
@@ -1010,7 +1108,6 @@ impl<'a> CircuitInputStateRef<'a> {
             .unwrap_or_else(Word::zero);
 
         let call = self.call()?;
-        let call_ctx = self.call_ctx()?;
         // get value first if call/create
         let value = match step.op {
             OpcodeId::CALL | OpcodeId::CALLCODE => step.stack.nth_last(2)?,
@@ -1054,23 +1151,13 @@ impl<'a> CircuitInputStateRef<'a> {
             } else {
                 // Return from a {CREATE, CREATE2} with a failure, via RETURN
                 if !call.is_root && call.is_create() {
-                    let offset = step.stack.nth_last(0)?;
-                    let length = step.stack.nth_last(1)?;
-                    if length > Word::from(0x6000u64) {
-                        return Ok(Some(ExecError::MaxCodeSizeExceeded));
-                    } else if length > Word::zero()
-                        && !call_ctx.memory.is_empty()
-                        && call_ctx.memory.0.get(offset.low_u64() as usize) == Some(&0xef)
-                    {
-                        return Ok(Some(ExecError::InvalidCreationCode));
-                    } else if Word::from(200u64) * length > Word::from(step.gas.0) {
-                        return Ok(Some(ExecError::CodeStoreOutOfGas));
-                    } else {
-                        return Err(Error::UnexpectedExecStepError(
+                    return match self.get_code_store_err(step)? {
+                        Some(error) => Ok(Some(error)),
+                        None => Err(Error::UnexpectedExecStepError(
                             "failure in RETURN from {CREATE, CREATE2}",
                             step.clone(),
-                        ));
-                    }
+                        )),
+                    };
                 } else {
                     return Err(Error::UnexpectedExecStepError(
                         "failure in RETURN",
@@ -1124,6 +1211,15 @@ impl<'a> CircuitInputStateRef<'a> {
                 return Ok(Some(ExecError::InsufficientBalance));
             }
 
+            // A failing call to a precompiled contract doesn't execute any code.
+            if matches!(
+                step.op,
+                OpcodeId::CALL | OpcodeId::CALLCODE | OpcodeId::DELEGATECALL | OpcodeId::STATICCALL
+            ) && self.is_precompiled(&step.stack.nth_last(1)?.to_address())
+            {
+                return Ok(None);
+            }
+
             // Address collision
             if matches!(step.op, OpcodeId::CREATE | OpcodeId::CREATE2) {
                 let address = match step.op {
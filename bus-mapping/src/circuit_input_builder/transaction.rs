@@ -4,7 +4,7 @@ use std::collections::BTreeMap;
 
 use eth_types::evm_types::Memory;
 use eth_types::Signature;
-use eth_types::{geth_types, Address, GethExecTrace, Word};
+use eth_types::{geth_types, AccessList, Address, GethExecTrace, Word};
 use ethers_core::utils::get_contract_address;
 
 use crate::{
@@ -185,8 +185,14 @@ pub struct Transaction {
     pub nonce: u64,
     /// Gas
     pub gas: u64,
-    /// Gas price
+    /// Gas price, which is the effective gas price for EIP-1559 transactions
     pub gas_price: Word,
+    /// Gas fee cap, which equals the gas price for legacy transactions
+    pub gas_fee_cap: Word,
+    /// Gas tip cap, which equals the gas price for legacy transactions
+    pub gas_tip_cap: Word,
+    /// Transaction type (EIP-2718)
+    pub tx_type: u64,
     /// From / Caller Address
     pub from: Address,
     /// To / Callee Address
@@ -195,6 +201,8 @@ pub struct Transaction {
     pub value: Word,
     /// Input / Call Data
     pub input: Vec<u8>,
+    /// Access list (EIP-2930)
+    pub access_list: AccessList,
     /// Signature
     pub signature: Signature,
     /// Calls made in the transaction
@@ -207,12 +215,16 @@ impl From<&Transaction> for geth_types::Transaction {
     fn from(tx: &Transaction) -> geth_types::Transaction {
         geth_types::Transaction {
             from: tx.from,
-            to: Some(tx.to),
+            to: (!tx.is_create()).then_some(tx.to),
             nonce: Word::from(tx.nonce),
             gas_limit: Word::from(tx.gas),
             value: tx.value,
             gas_price: tx.gas_price,
+            gas_fee_cap: tx.gas_fee_cap,
+            gas_tip_cap: tx.gas_tip_cap,
+            transaction_type: tx.tx_type,
             call_data: tx.input.clone().into(),
+            access_list: Some(tx.access_list.clone()),
             v: tx.signature.v,
             r: tx.signature.r,
             s: tx.signature.s,
@@ -228,10 +240,14 @@ impl Transaction {
             nonce: 0,
             gas: 0,
             gas_price: Word::zero(),
+            gas_fee_cap: Word::zero(),
+            gas_tip_cap: Word::zero(),
+            tx_type: 0,
             from: Address::zero(),
             to: Address::zero(),
             value: Word::zero(),
             input: Vec::new(),
+            access_list: AccessList::default(),
             signature: Signature {
                 r: Word::zero(),
                 s: Word::zero(),
@@ -292,21 +308,28 @@ impl Transaction {
                 code_hash,
                 depth: 1,
                 value: eth_tx.value,
-                call_data_length: eth_tx.input.len().try_into().unwrap(),
+                // The tx calldata is the initialization code, not the
+                // calldata of the creation call.
+                call_data_length: 0,
                 ..Default::default()
             }
         };
 
+        let geth_tx = geth_types::Transaction::from(eth_tx);
         Ok(Self {
             nonce: eth_tx.nonce.as_u64(),
             gas: eth_tx.gas.as_u64(),
-            gas_price: eth_tx.gas_price.unwrap_or_default(),
+            gas_price: geth_tx.gas_price,
+            gas_fee_cap: geth_tx.gas_fee_cap,
+            gas_tip_cap: geth_tx.gas_tip_cap,
+            tx_type: geth_tx.transaction_type,
             from: eth_tx.from,
             to: eth_tx
                 .to
                 .unwrap_or_else(|| get_contract_address(eth_tx.from, eth_tx.nonce)),
             value: eth_tx.value,
             input: eth_tx.input.to_vec(),
+            access_list: eth_tx.access_list.clone().unwrap_or_default(),
             calls: vec![call],
             steps: Vec::new(),
             signature: Signature {
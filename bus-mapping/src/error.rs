@@ -9,6 +9,7 @@ use crate::geth_errors::{
     GETH_ERR_GAS_UINT_OVERFLOW, GETH_ERR_OUT_OF_GAS, GETH_ERR_STACK_OVERFLOW,
     GETH_ERR_STACK_UNDERFLOW,
 };
+use crate::state_provider::StateProviderError;
 
 /// Error type for any BusMapping related failure.
 #[derive(Debug)]
@@ -17,6 +18,8 @@ pub enum Error {
     SerdeError(serde_json::error::Error),
     /// JSON-RPC related error.
     JSONRpcError(ProviderError),
+    /// Error of the [`StateProvider`](crate::state_provider::StateProvider).
+    StateProviderError(StateProviderError),
     /// OpcodeId is not a call type.
     OpcodeIdNotCallType,
     /// Account not found in the StateDB
@@ -54,6 +57,12 @@ impl From<ProviderError> for Error {
     }
 }
 
+impl From<StateProviderError> for Error {
+    fn from(err: StateProviderError) -> Self {
+        Error::StateProviderError(err)
+    }
+}
+
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
         write!(f, "{:?}", self)
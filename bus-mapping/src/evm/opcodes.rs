@@ -1,20 +1,27 @@
 //! Definition of each opcode of the EVM.
 use crate::{
-    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    circuit_input_builder::{
+        get_create_address_preimage, CircuitInputStateRef, CopyDataType, CopyEvent, ExecState,
+        ExecStep, NumberOrHash,
+    },
     error::{ExecError, OogError},
     evm::OpcodeId,
     operation::{
-        AccountField, CallContextField, TxAccessListAccountOp, TxReceiptField, TxRefundOp, RW,
+        AccountField, AccountOp, CallContextField, TxAccessListAccountStorageOp, TxReceiptField,
+        TxRefundOp, RW,
     },
+    precompile::PrecompileCalls,
     Error,
 };
 use core::fmt::Debug;
 use eth_types::{
-    evm_types::{GasCost, MAX_REFUND_QUOTIENT_OF_GAS_USED},
-    GethExecStep, ToAddress, ToWord, Word,
+    evm_types::{Gas, GasCost},
+    geth_types::access_list_entries,
+    AccessList, Bytecode, GethExecStep, ToWord, Word,
 };
 use keccak256::EMPTY_HASH;
 use log::warn;
+use std::iter;
 
 #[cfg(any(feature = "test", test))]
 pub use self::sha3::sha3_tests::{gen_sha3_code, MemoryKind};
@@ -42,10 +49,12 @@ mod mload;
 mod mstore;
 mod number;
 mod origin;
+mod precompiles;
 mod return_revert;
 mod returndatacopy;
 mod returndatasize;
 mod selfbalance;
+mod selfdestruct;
 mod sha3;
 mod sload;
 mod sstore;
@@ -53,8 +62,19 @@ mod stackonlyop;
 mod stop;
 mod swap;
 
+mod error_code_store;
+mod error_contract_address_collision;
 mod error_invalid_jump;
+mod error_invalid_opcode;
+mod error_oog_account_access;
 mod error_oog_call;
+mod error_oog_code_store;
+mod error_oog_self_destruct;
+mod error_oog_sload_sstore;
+mod error_oog_stack_only;
+mod error_precheck;
+mod error_return_data_oob;
+mod error_write_protection;
 
 #[cfg(test)]
 mod memory_expansion_test;
@@ -70,10 +90,21 @@ use callop::CallOpcode;
 use callvalue::Callvalue;
 use codecopy::Codecopy;
 use codesize::Codesize;
-use create::DummyCreate;
+use create::Create;
 use dup::Dup;
+use error_code_store::ErrorCodeStore;
+use error_contract_address_collision::ErrorContractAddressCollision;
 use error_invalid_jump::ErrorInvalidJump;
+use error_invalid_opcode::ErrorInvalidOpcode;
+use error_oog_account_access::OOGAccountAccess;
 use error_oog_call::OOGCall;
+use error_oog_code_store::OOGCodeStore;
+use error_oog_self_destruct::OOGSelfDestruct;
+use error_oog_sload_sstore::OOGSloadSstore;
+use error_oog_stack_only::OOGStackOnly;
+use error_precheck::ErrorPrecheck;
+use error_return_data_oob::ErrorReturnDataOutOfBound;
+use error_write_protection::ErrorWriteProtection;
 use exp::Exponentiation;
 use extcodecopy::Extcodecopy;
 use extcodehash::Extcodehash;
@@ -87,6 +118,7 @@ use return_revert::ReturnRevert;
 use returndatacopy::Returndatacopy;
 use returndatasize::Returndatasize;
 use selfbalance::Selfbalance;
+use selfdestruct::Selfdestruct;
 use sload::Sload;
 use sstore::Sstore;
 use stackonlyop::StackOnlyOpcode;
@@ -195,6 +227,7 @@ fn fn_gen_associated_ops(opcode_id: &OpcodeId) -> FnGenAssociatedOps {
         OpcodeId::MSIZE => StackOnlyOpcode::<0, 1>::gen_associated_ops,
         OpcodeId::GAS => StackOnlyOpcode::<0, 1>::gen_associated_ops,
         OpcodeId::JUMPDEST => Dummy::gen_associated_ops,
+        OpcodeId::PUSH0 => StackOnlyOpcode::<0, 1>::gen_associated_ops,
         OpcodeId::DUP1 => Dup::<1>::gen_associated_ops,
         OpcodeId::DUP2 => Dup::<2>::gen_associated_ops,
         OpcodeId::DUP3 => Dup::<3>::gen_associated_ops,
@@ -235,18 +268,9 @@ fn fn_gen_associated_ops(opcode_id: &OpcodeId) -> FnGenAssociatedOps {
         OpcodeId::CALL | OpcodeId::CALLCODE => CallOpcode::<7>::gen_associated_ops,
         OpcodeId::DELEGATECALL | OpcodeId::STATICCALL => CallOpcode::<6>::gen_associated_ops,
         OpcodeId::RETURN | OpcodeId::REVERT => ReturnRevert::gen_associated_ops,
-        OpcodeId::SELFDESTRUCT => {
-            warn!("Using dummy gen_selfdestruct_ops for opcode SELFDESTRUCT");
-            DummySelfDestruct::gen_associated_ops
-        }
-        OpcodeId::CREATE => {
-            warn!("Using dummy gen_create_ops for opcode {:?}", opcode_id);
-            DummyCreate::<false>::gen_associated_ops
-        }
-        OpcodeId::CREATE2 => {
-            warn!("Using dummy gen_create_ops for opcode {:?}", opcode_id);
-            DummyCreate::<true>::gen_associated_ops
-        }
+        OpcodeId::SELFDESTRUCT => Selfdestruct::gen_associated_ops,
+        OpcodeId::CREATE => Create::<false>::gen_associated_ops,
+        OpcodeId::CREATE2 => Create::<true>::gen_associated_ops,
         _ => {
             warn!("Using dummy gen_associated_ops for opcode {:?}", opcode_id);
             Dummy::gen_associated_ops
@@ -257,7 +281,43 @@ fn fn_gen_associated_ops(opcode_id: &OpcodeId) -> FnGenAssociatedOps {
 fn fn_gen_error_state_associated_ops(error: &ExecError) -> Option<FnGenAssociatedOps> {
     match error {
         ExecError::InvalidJump => Some(ErrorInvalidJump::gen_associated_ops),
-        ExecError::OutOfGas(OogError::Call) => Some(OOGCall::gen_associated_ops),
+        ExecError::OutOfGas(OogError::Call | OogError::CallCode) => {
+            Some(OOGCall::<7>::gen_associated_ops)
+        }
+        ExecError::OutOfGas(OogError::DelegateCall | OogError::StaticCall) => {
+            Some(OOGCall::<6>::gen_associated_ops)
+        }
+        ExecError::OutOfGas(OogError::Sload | OogError::Sstore) => {
+            Some(OOGSloadSstore::gen_associated_ops)
+        }
+        ExecError::OutOfGas(OogError::Log | OogError::Sha3 | OogError::Exp) => {
+            Some(OOGStackOnly::<2>::gen_associated_ops)
+        }
+        ExecError::OutOfGas(OogError::MemoryCopy | OogError::Create2) => {
+            Some(OOGStackOnly::<3>::gen_associated_ops)
+        }
+        ExecError::OutOfGas(OogError::CodeStore) | ExecError::CodeStoreOutOfGas => {
+            Some(OOGCodeStore::gen_associated_ops)
+        }
+        ExecError::OutOfGas(OogError::AccountAccess) => {
+            Some(OOGAccountAccess::<1>::gen_associated_ops)
+        }
+        ExecError::OutOfGas(OogError::ExtCodeCopy) => {
+            Some(OOGAccountAccess::<4>::gen_associated_ops)
+        }
+        ExecError::OutOfGas(OogError::SelfDestruct) => Some(OOGSelfDestruct::gen_associated_ops),
+        ExecError::InvalidOpcode => Some(ErrorInvalidOpcode::gen_associated_ops),
+        ExecError::WriteProtection => Some(ErrorWriteProtection::gen_associated_ops),
+        ExecError::Depth | ExecError::InsufficientBalance => {
+            Some(ErrorPrecheck::gen_associated_ops)
+        }
+        ExecError::ContractAddressCollision => {
+            Some(ErrorContractAddressCollision::gen_associated_ops)
+        }
+        ExecError::InvalidCreationCode | ExecError::MaxCodeSizeExceeded => {
+            Some(ErrorCodeStore::gen_associated_ops)
+        }
+        ExecError::ReturnDataOutOfBounds => Some(ErrorReturnDataOutOfBound::gen_associated_ops),
         // more future errors place here
         _ => {
             warn!("TODO: error state {:?} not implemented", error);
@@ -325,7 +385,15 @@ pub fn gen_associated_ops(
     fn_gen_associated_ops(state, geth_steps)
 }
 
-pub fn gen_begin_tx_ops(state: &mut CircuitInputStateRef) -> Result<ExecStep, Error> {
+/// Intrinsic gas charged for the access list of a transaction (EIP-2930).
+fn access_list_gas_cost(access_list: &AccessList) -> u64 {
+    access_list.0.iter().fold(0, |acc, item| {
+        acc + GasCost::ACCESS_LIST_ADDRESS.as_u64()
+            + GasCost::ACCESS_LIST_STORAGE_KEY.as_u64() * item.storage_keys.len() as u64
+    })
+}
+
+pub fn gen_begin_tx_ops(state: &mut CircuitInputStateRef) -> Result<Vec<ExecStep>, Error> {
     let mut exec_step = state.new_begin_tx_step();
     let call = state.call()?.clone();
 
@@ -367,6 +435,21 @@ pub fn gen_begin_tx_ops(state: &mut CircuitInputStateRef) -> Result<ExecStep, Er
         )?;
     }
 
+    // Add the coinbase into access list, as specified in
+    // [EIP-3651](https://eips.ethereum.org/EIPS/eip-3651).
+    let hardfork = state.block.circuits_params.hardfork;
+    if hardfork.is_eip3651_enabled() {
+        let coinbase = state.block.coinbase;
+        let is_warm_prev = !state.sdb.add_account_to_access_list(coinbase);
+        state.tx_accesslist_account_write(
+            &mut exec_step,
+            state.tx_ctx.id(),
+            coinbase,
+            true,
+            is_warm_prev,
+        )?;
+    }
+
     // Calculate intrinsic gas cost
     let call_data_gas_cost = state
         .tx
@@ -374,10 +457,11 @@ pub fn gen_begin_tx_ops(state: &mut CircuitInputStateRef) -> Result<ExecStep, Er
         .iter()
         .fold(0, |acc, byte| acc + if *byte == 0 { 4 } else { 16 });
     let intrinsic_gas_cost = if state.tx.is_create() {
-        GasCost::CREATION_TX.as_u64()
+        GasCost::CREATION_TX.as_u64() + hardfork.init_code_gas_cost(state.tx.input.len() as u64)
     } else {
         GasCost::TX.as_u64()
-    } + call_data_gas_cost;
+    } + call_data_gas_cost
+        + access_list_gas_cost(&state.tx.access_list);
     exec_step.gas_cost = GasCost(intrinsic_gas_cost);
 
     // Transfer with fee
@@ -389,103 +473,177 @@ pub fn gen_begin_tx_ops(state: &mut CircuitInputStateRef) -> Result<ExecStep, Er
         state.tx.gas_price * state.tx.gas,
     )?;
 
-    // Get code_hash of callee
-    let (_, callee_account) = state.sdb.get_account(&call.address);
-    let code_hash = callee_account.code_hash;
-
-    // There are 4 branches from here.
-    match (
-        call.is_create(),
-        state.is_precompiled(&call.address),
-        code_hash.to_fixed_bytes() == *EMPTY_HASH,
-    ) {
-        // 1. Creation transaction.
-        (true, _, _) => {
-            state.account_read(
-                &mut exec_step,
-                call.address,
-                AccountField::CodeHash,
-                call.code_hash.to_word(),
-                call.code_hash.to_word(),
-            )?;
-            for (field, value) in [
-                (CallContextField::Depth, call.depth.into()),
-                (
-                    CallContextField::CallerAddress,
-                    call.caller_address.to_word(),
-                ),
-                (CallContextField::CalleeAddress, call.address.to_word()),
-                (
-                    CallContextField::CallDataOffset,
-                    call.call_data_offset.into(),
-                ),
-                (
-                    CallContextField::CallDataLength,
-                    state.tx.input.len().into(),
-                ),
-                (CallContextField::Value, call.value),
-                (CallContextField::IsStatic, (call.is_static as usize).into()),
-                (CallContextField::LastCalleeId, 0.into()),
-                (CallContextField::LastCalleeReturnDataOffset, 0.into()),
-                (CallContextField::LastCalleeReturnDataLength, 0.into()),
-                (CallContextField::IsRoot, 1.into()),
-                (CallContextField::IsCreate, 1.into()),
-                (CallContextField::CodeHash, call.code_hash.to_word()),
-            ] {
-                state.call_context_write(&mut exec_step, call.call_id, field, value);
-            }
-            Ok(exec_step)
-        }
-        // 2. Call to precompiled.
-        (_, true, _) => {
-            warn!("Call to precompiled is left unimplemented");
-            Ok(exec_step)
+    let (code_hash, precompile) = if call.is_create() {
+        // Increase callee's nonce, as specified in
+        // [EIP-161](https://eips.ethereum.org/EIPS/eip-161).
+        let nonce_prev = state.sdb.get_nonce(&call.address);
+        debug_assert!(nonce_prev == 0);
+        state.push_op_reversible(
+            &mut exec_step,
+            RW::WRITE,
+            AccountOp {
+                address: call.address,
+                field: AccountField::Nonce,
+                value: 1.into(),
+                value_prev: 0.into(),
+            },
+        )?;
+
+        // The contract address is derived from the caller's address and nonce.
+        state.block.sha3_inputs.push(get_create_address_preimage(
+            call.caller_address,
+            state.tx.nonce,
+        ));
+
+        // Copy the initialization code from the tx calldata into the bytecode
+        // table, where it's identified by its code hash.
+        if !state.tx.input.is_empty() {
+            let bytes: Vec<_> = Bytecode::from(state.tx.input.clone())
+                .code
+                .iter()
+                .map(|element| (element.value, element.is_code))
+                .collect();
+            state.push_copy(CopyEvent {
+                rw_counter_start: state.block_ctx.rwc,
+                src_type: CopyDataType::TxCalldata,
+                src_id: NumberOrHash::Number(state.tx_ctx.id()),
+                src_addr: 0,
+                src_addr_end: state.tx.input.len() as u64,
+                dst_type: CopyDataType::Bytecode,
+                dst_id: NumberOrHash::Hash(call.code_hash),
+                dst_addr: 0,
+                log_id: None,
+                bytes,
+            });
         }
-        (_, _, is_empty_code_hash) => {
-            state.account_read(
-                &mut exec_step,
-                call.address,
-                AccountField::CodeHash,
-                code_hash.to_word(),
-                code_hash.to_word(),
-            )?;
-
-            // 3. Call to account with empty code.
-            if is_empty_code_hash {
-                return Ok(exec_step);
-            }
 
-            // 4. Call to account with non-empty code.
-            for (field, value) in [
-                (CallContextField::Depth, call.depth.into()),
-                (
-                    CallContextField::CallerAddress,
-                    call.caller_address.to_word(),
-                ),
-                (CallContextField::CalleeAddress, call.address.to_word()),
-                (
-                    CallContextField::CallDataOffset,
-                    call.call_data_offset.into(),
-                ),
-                (
-                    CallContextField::CallDataLength,
-                    call.call_data_length.into(),
-                ),
-                (CallContextField::Value, call.value),
-                (CallContextField::IsStatic, (call.is_static as usize).into()),
-                (CallContextField::LastCalleeId, 0.into()),
-                (CallContextField::LastCalleeReturnDataOffset, 0.into()),
-                (CallContextField::LastCalleeReturnDataLength, 0.into()),
-                (CallContextField::IsRoot, 1.into()),
-                (CallContextField::IsCreate, 0.into()),
-                (CallContextField::CodeHash, code_hash.to_word()),
-            ] {
-                state.call_context_write(&mut exec_step, call.call_id, field, value);
-            }
+        (call.code_hash, None)
+    } else {
+        // Get code_hash of callee
+        let (_, callee_account) = state.sdb.get_account(&call.address);
+        let code_hash = callee_account.code_hash;
+        state.account_read(
+            &mut exec_step,
+            call.address,
+            AccountField::CodeHash,
+            code_hash.to_word(),
+            code_hash.to_word(),
+        )?;
+
+        (code_hash, PrecompileCalls::try_from(&call.address).ok())
+    };
+
+    // 1. Call to account with empty code, or creation with empty
+    // initialization code.
+    if code_hash.to_fixed_bytes() == *EMPTY_HASH && precompile.is_none() {
+        let access_list_steps = gen_begin_tx_access_list_ops(state, &exec_step)?;
+        return Ok(iter::once(exec_step).chain(access_list_steps).collect());
+    }
+
+    // 2. Creation, or call to precompiled or account with non-empty code.
+    for (field, value) in [
+        (CallContextField::Depth, call.depth.into()),
+        (
+            CallContextField::CallerAddress,
+            call.caller_address.to_word(),
+        ),
+        (CallContextField::CalleeAddress, call.address.to_word()),
+        (
+            CallContextField::CallDataOffset,
+            call.call_data_offset.into(),
+        ),
+        (
+            CallContextField::CallDataLength,
+            call.call_data_length.into(),
+        ),
+        (CallContextField::Value, call.value),
+        (CallContextField::IsStatic, (call.is_static as usize).into()),
+        (CallContextField::LastCalleeId, 0.into()),
+        (CallContextField::LastCalleeReturnDataOffset, 0.into()),
+        (CallContextField::LastCalleeReturnDataLength, 0.into()),
+        (CallContextField::IsRoot, 1.into()),
+        (CallContextField::IsCreate, call.is_create().to_word()),
+        (CallContextField::CodeHash, code_hash.to_word()),
+    ] {
+        state.call_context_write(&mut exec_step, call.call_id, field, value);
+    }
+
+    let access_list_steps = gen_begin_tx_access_list_ops(state, &exec_step)?;
+
+    // The precompiled contract is executed in a step of its own.
+    if let Some(precompile) = precompile {
+        let gas_left = exec_step.gas_left.0 - exec_step.gas_cost.0;
+        let precompile_step = precompiles::gen_associated_ops(
+            state,
+            Some(&mut exec_step),
+            precompile,
+            gas_left,
+            &[],
+        )?;
+        return Ok(iter::once(exec_step)
+            .chain(access_list_steps)
+            .chain(iter::once(precompile_step))
+            .collect());
+    }
+
+    Ok(iter::once(exec_step).chain(access_list_steps).collect())
+}
 
-            Ok(exec_step)
+/// Generate one step per entry of the access list of the tx (EIP-2930), which
+/// adds its address or storage key to the access list of the tx.  These steps
+/// follow the BeginTx step and keep its resulting state, so that the step after
+/// them starts as if it followed the BeginTx step.
+fn gen_begin_tx_access_list_ops(
+    state: &mut CircuitInputStateRef,
+    begin_tx_step: &ExecStep,
+) -> Result<Vec<ExecStep>, Error> {
+    let call = state.call()?.clone();
+    let mut exec_steps = Vec::new();
+    for (address, key) in access_list_entries(&state.tx.access_list) {
+        let mut exec_step = ExecStep {
+            exec_state: ExecState::BeginTxAccessList,
+            gas_left: Gas(begin_tx_step.gas_left.0 - begin_tx_step.gas_cost.0),
+            call_index: begin_tx_step.call_index,
+            rwc: state.block_ctx.rwc,
+            reversible_write_counter: state.call_ctx()?.reversible_write_counter,
+            log_id: state.tx_ctx.log_id,
+            ..Default::default()
+        };
+        state.call_context_read(
+            &mut exec_step,
+            call.call_id,
+            CallContextField::TxId,
+            state.tx_ctx.id().into(),
+        );
+        match key {
+            None => {
+                let is_warm_prev = !state.sdb.add_account_to_access_list(address);
+                state.tx_accesslist_account_write(
+                    &mut exec_step,
+                    state.tx_ctx.id(),
+                    address,
+                    true,
+                    is_warm_prev,
+                )?;
+            }
+            Some(key) => {
+                let is_warm_prev = !state.sdb.add_account_storage_to_access_list((address, key));
+                state.push_op(
+                    &mut exec_step,
+                    RW::WRITE,
+                    TxAccessListAccountStorageOp {
+                        tx_id: state.tx_ctx.id(),
+                        address,
+                        key,
+                        is_warm: true,
+                        is_warm_prev,
+                    },
+                );
+            }
         }
+        exec_steps.push(exec_step);
     }
+    Ok(exec_steps)
 }
 
 pub fn gen_end_tx_ops(state: &mut CircuitInputStateRef) -> Result<ExecStep, Error> {
@@ -516,8 +674,8 @@ pub fn gen_end_tx_ops(state: &mut CircuitInputStateRef) -> Result<ExecStep, Erro
         },
     );
 
-    let effective_refund =
-        refund.min((state.tx.gas - exec_step.gas_left.0) / MAX_REFUND_QUOTIENT_OF_GAS_USED as u64);
+    let max_refund_quotient = state.block.circuits_params.hardfork.max_refund_quotient();
+    let effective_refund = refund.min((state.tx.gas - exec_step.gas_left.0) / max_refund_quotient);
     let (found, caller_account) = state.sdb.get_account_mut(&call.caller_address);
     if !found {
         return Err(Error::AccountNotFound(call.caller_address));
@@ -535,6 +693,7 @@ pub fn gen_end_tx_ops(state: &mut CircuitInputStateRef) -> Result<ExecStep, Erro
         caller_balance_prev,
     )?;
 
+    // The base fee is burned, so the coinbase only gets the priority fee.
     let effective_tip = state.tx.gas_price - state.block.base_fee;
     let (found, coinbase_account) = state.sdb.get_account_mut(&state.block.coinbase);
     if !found {
@@ -597,54 +756,3 @@ pub fn gen_end_tx_ops(state: &mut CircuitInputStateRef) -> Result<ExecStep, Erro
 
     Ok(exec_step)
 }
-
-#[derive(Debug, Copy, Clone)]
-struct DummySelfDestruct;
-
-impl Opcode for DummySelfDestruct {
-    fn gen_associated_ops(
-        state: &mut CircuitInputStateRef,
-        geth_steps: &[GethExecStep],
-    ) -> Result<Vec<ExecStep>, Error> {
-        dummy_gen_selfdestruct_ops(state, geth_steps)
-    }
-}
-fn dummy_gen_selfdestruct_ops(
-    state: &mut CircuitInputStateRef,
-    geth_steps: &[GethExecStep],
-) -> Result<Vec<ExecStep>, Error> {
-    let geth_step = &geth_steps[0];
-    let mut exec_step = state.new_step(geth_step)?;
-    let sender = state.call()?.address;
-    let receiver = geth_step.stack.last()?.to_address();
-
-    let is_warm = state.sdb.check_account_in_access_list(&receiver);
-    state.push_op_reversible(
-        &mut exec_step,
-        RW::WRITE,
-        TxAccessListAccountOp {
-            tx_id: state.tx_ctx.id(),
-            address: receiver,
-            is_warm: true,
-            is_warm_prev: is_warm,
-        },
-    )?;
-
-    let (found, _) = state.sdb.get_account(&receiver);
-    if !found {
-        return Err(Error::AccountNotFound(receiver));
-    }
-    let (found, sender_account) = state.sdb.get_account(&sender);
-    if !found {
-        return Err(Error::AccountNotFound(sender));
-    }
-    let value = sender_account.balance;
-    state.transfer(&mut exec_step, sender, receiver, value)?;
-
-    if state.call()?.is_persistent {
-        state.sdb.destruct_account(sender);
-    }
-
-    state.handle_return(geth_step)?;
-    Ok(vec![exec_step])
-}
@@ -1,12 +1,12 @@
-use super::Opcode;
+use super::{precompiles, Opcode};
 use crate::circuit_input_builder::{CallKind, CircuitInputStateRef, CodeSource, ExecStep};
 use crate::operation::{AccountField, CallContextField, TxAccessListAccountOp, RW};
+use crate::precompile::PrecompileCalls;
 use crate::Error;
 use eth_types::evm_types::gas_utils::{eip150_gas, memory_expansion_gas_cost};
-use eth_types::evm_types::GasCost;
+use eth_types::evm_types::{GasCost, GAS_STIPEND_CALL_WITH_VALUE};
 use eth_types::{GethExecStep, ToWord, Word};
 use keccak256::EMPTY_HASH;
-use log::warn;
 
 /// Placeholder structure used to implement [`Opcode`] trait over it
 /// corresponding to the `OpcodeId::CALL`, `OpcodeId::CALLCODE`,
@@ -196,18 +196,12 @@ impl<const N_ARGS: usize> Opcode for CallOpcode<N_ARGS> {
         let gas_specified = geth_step.stack.last()?;
         let callee_gas_left = eip150_gas(geth_step.gas.0 - gas_cost, gas_specified);
 
-        // There are 3 branches from here.
-        match (
-            state.is_precompiled(&call.address),
-            callee_code_hash.to_fixed_bytes() == *EMPTY_HASH,
-        ) {
-            // 1. Call to precompiled.
-            (true, _) => {
-                warn!("Call to precompiled is left unimplemented");
-                Ok(vec![exec_step])
-            }
-            // 2. Call to account with empty code.
-            (_, true) => {
+        let precompile = PrecompileCalls::try_from(&callee_address).ok();
+
+        // There are 2 branches from here.
+        match (precompile, callee_code_hash.to_fixed_bytes() == *EMPTY_HASH) {
+            // 1. Call to account with empty code.
+            (None, true) => {
                 for (field, value) in [
                     (CallContextField::LastCalleeId, 0.into()),
                     (CallContextField::LastCalleeReturnDataOffset, 0.into()),
@@ -218,9 +212,9 @@ impl<const N_ARGS: usize> Opcode for CallOpcode<N_ARGS> {
                 state.handle_return(geth_step)?;
                 Ok(vec![exec_step])
             }
-            // 3. Call to account with non-empty code.
-            (_, false) => {
-                for (field, value) in [
+            // 2. Call to precompiled or account with non-empty code.
+            _ => {
+                let caller_context: [(CallContextField, Word); 5] = [
                     (
                         CallContextField::ProgramCounter,
                         (geth_step.pc.0 + 1).into(),
@@ -238,7 +232,8 @@ impl<const N_ARGS: usize> Opcode for CallOpcode<N_ARGS> {
                         CallContextField::ReversibleWriteCounter,
                         (exec_step.reversible_write_counter + 1).into(),
                     ),
-                ] {
+                ];
+                for (field, value) in caller_context.clone() {
                     state.call_context_write(&mut exec_step, current_call.call_id, field, value);
                 }
 
@@ -280,6 +275,23 @@ impl<const N_ARGS: usize> Opcode for CallOpcode<N_ARGS> {
                     state.call_context_write(&mut exec_step, call.call_id, field, value);
                 }
 
+                // The precompiled contract is executed in a step of its own.
+                if let Some(precompile) = precompile {
+                    let stipend = if has_value {
+                        GAS_STIPEND_CALL_WITH_VALUE
+                    } else {
+                        0
+                    };
+                    let precompile_step = precompiles::gen_associated_ops(
+                        state,
+                        Some(&mut exec_step),
+                        precompile,
+                        callee_gas_left + stipend,
+                        &caller_context,
+                    )?;
+                    return Ok(vec![exec_step, precompile_step]);
+                }
+
                 Ok(vec![exec_step])
             }
         }
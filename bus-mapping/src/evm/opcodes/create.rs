@@ -1,25 +1,37 @@
-use crate::circuit_input_builder::{CircuitInputStateRef, ExecStep};
+use crate::circuit_input_builder::{
+    get_create2_address_preimage, get_create_address_preimage, CircuitInputStateRef, CopyDataType,
+    CopyEvent, ExecStep, NumberOrHash,
+};
 use crate::evm::Opcode;
 use crate::operation::{AccountField, AccountOp, CallContextField, TxAccessListAccountOp, RW};
 use crate::Error;
-use eth_types::{evm_types::gas_utils::memory_expansion_gas_cost, GethExecStep, ToWord, Word};
+use eth_types::{
+    evm_types::{
+        gas_utils::{eip150_gas, memory_expansion_gas_cost},
+        GasCost,
+    },
+    Bytecode, GethExecStep, ToWord, Word, H256,
+};
 use keccak256::EMPTY_HASH;
 
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the [`OpcodeId::CREATE`](crate::evm::OpcodeId::CREATE) and
+/// [`OpcodeId::CREATE2`](crate::evm::OpcodeId::CREATE2) `OpcodeId`s.
 #[derive(Debug, Copy, Clone)]
-pub struct DummyCreate<const IS_CREATE2: bool>;
+pub struct Create<const IS_CREATE2: bool>;
 
-impl<const IS_CREATE2: bool> Opcode for DummyCreate<IS_CREATE2> {
+impl<const IS_CREATE2: bool> Opcode for Create<IS_CREATE2> {
     fn gen_associated_ops(
         state: &mut CircuitInputStateRef,
         geth_steps: &[GethExecStep],
     ) -> Result<Vec<ExecStep>, Error> {
-        // TODO: replace dummy create here
         let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
 
         let offset = geth_step.stack.nth_last(1)?.as_usize();
         let length = geth_step.stack.nth_last(2)?.as_usize();
 
-        let curr_memory_word_size = (state.call_ctx()?.memory.len() as u64) / 32;
+        let curr_memory_word_size = (exec_step.memory_size as u64) / 32;
         if length != 0 {
             state
                 .call_ctx_mut()?
@@ -28,7 +40,30 @@ impl<const IS_CREATE2: bool> Opcode for DummyCreate<IS_CREATE2> {
         }
         let next_memory_word_size = (state.call_ctx()?.memory.len() as u64) / 32;
 
-        let mut exec_step = state.new_step(geth_step)?;
+        let tx_id = state.tx_ctx.id();
+        let current_call = state.call()?.clone();
+        // The nonce of the caller must be read before it's increased, since
+        // the CREATE address is derived from it.
+        let caller_nonce = state.sdb.get_nonce(&current_call.address);
+
+        // Use rw_counter of the step which triggers next call as its call_id.
+        let call = state.parse_call(geth_step)?;
+
+        for (field, value) in [
+            (CallContextField::TxId, tx_id.into()),
+            (CallContextField::RwCounterEndOfReversion, 0.into()),
+            (
+                CallContextField::IsPersistent,
+                current_call.is_persistent.to_word(),
+            ),
+            (CallContextField::Depth, current_call.depth.into()),
+            (
+                CallContextField::CalleeAddress,
+                current_call.address.to_word(),
+            ),
+        ] {
+            state.call_context_read(&mut exec_step, current_call.call_id, field, value);
+        }
 
         let n_pop = if IS_CREATE2 { 4 } else { 3 };
         for i in 0..n_pop {
@@ -39,59 +74,34 @@ impl<const IS_CREATE2: bool> Opcode for DummyCreate<IS_CREATE2> {
             )?;
         }
 
-        let address = if IS_CREATE2 {
-            state.create2_address(&geth_steps[0])?
-        } else {
-            state.create_address()?
-        };
-
-        // TODO: rename this to initialization call?
-        let call = state.parse_call(geth_step)?;
         state.stack_write(
             &mut exec_step,
             geth_step.stack.nth_last_filled(n_pop - 1),
             if call.is_success {
-                address.to_word()
+                call.address.to_word()
             } else {
                 Word::zero()
             },
         )?;
 
-        let tx_id = state.tx_ctx.id();
-        let current_call = state.call()?.clone();
-
-        // Quote from [EIP-2929](https://eips.ethereum.org/EIPS/eip-2929)
-        // > When a CREATE or CREATE2 opcode is called,
-        // > immediately (i.e. before checks are done to determine
-        // > whether or not the address is unclaimed)
-        // > add the address being created to accessed_addresses,
-        // > but gas costs of CREATE and CREATE2 are unchanged
-        let is_warm = state.sdb.check_account_in_access_list(&address);
-        state.push_op_reversible(
-            &mut exec_step,
-            RW::WRITE,
-            TxAccessListAccountOp {
-                tx_id: state.tx_ctx.id(),
-                address,
-                is_warm: true,
-                is_warm_prev: is_warm,
-            },
-        )?;
-
         // Increase caller's nonce
-        let nonce_prev = state.sdb.get_nonce(&call.caller_address);
         state.push_op_reversible(
             &mut exec_step,
             RW::WRITE,
             AccountOp {
                 address: call.caller_address,
                 field: AccountField::Nonce,
-                value: (nonce_prev + 1).into(),
-                value_prev: nonce_prev.into(),
+                value: (caller_nonce + 1).into(),
+                value_prev: caller_nonce.into(),
             },
         )?;
 
-        // Add callee into access list
+        // Quote from [EIP-2929](https://eips.ethereum.org/EIPS/eip-2929)
+        // > When a CREATE or CREATE2 opcode is called,
+        // > immediately (i.e. before checks are done to determine
+        // > whether or not the address is unclaimed)
+        // > add the address being created to accessed_addresses,
+        // > but gas costs of CREATE and CREATE2 are unchanged
         let is_warm = state.sdb.check_account_in_access_list(&call.address);
         state.push_op_reversible(
             &mut exec_step,
@@ -104,9 +114,32 @@ impl<const IS_CREATE2: bool> Opcode for DummyCreate<IS_CREATE2> {
             },
         )?;
 
+        // Copy the initialization code from the caller's memory into the
+        // bytecode table, identified by its code hash.
+        let init_code = state.call_ctx()?.memory.0[offset..offset + length].to_vec();
+        if length > 0 {
+            handle_copy(state, &mut exec_step, offset, &init_code, call.code_hash)?;
+        }
+
+        // Record the preimage of the contract address for the keccak table.
+        state.block.sha3_inputs.push(if IS_CREATE2 {
+            let salt = geth_step.stack.nth_last(3)?;
+            get_create2_address_preimage(current_call.address, salt, call.code_hash)
+        } else {
+            get_create_address_preimage(current_call.address, caller_nonce)
+        });
+
         state.push_call(call.clone());
 
-        // Increase callee's nonce
+        for (field, value) in [
+            (CallContextField::RwCounterEndOfReversion, 0.into()),
+            (CallContextField::IsPersistent, call.is_persistent.to_word()),
+        ] {
+            state.call_context_write(&mut exec_step, call.call_id, field, value);
+        }
+
+        // Increase callee's nonce, as specified in
+        // [EIP-161](https://eips.ethereum.org/EIPS/eip-161).
         let nonce_prev = state.sdb.get_nonce(&call.address);
         debug_assert!(nonce_prev == 0);
         state.push_op_reversible(
@@ -129,11 +162,36 @@ impl<const IS_CREATE2: bool> Opcode for DummyCreate<IS_CREATE2> {
 
         let memory_expansion_gas_cost =
             memory_expansion_gas_cost(curr_memory_word_size, next_memory_word_size);
-
+        let init_code_hash_gas_cost = if IS_CREATE2 {
+            GasCost::COPY_SHA3.as_u64() * ((length as u64 + 31) / 32)
+        } else {
+            0
+        };
+        let gas_cost = GasCost::CREATE.as_u64()
+            + memory_expansion_gas_cost
+            + init_code_hash_gas_cost
+            + state
+                .block
+                .circuits_params
+                .hardfork
+                .init_code_gas_cost(length as u64);
         // EIP-150: all but one 64th of the caller's gas is sent to the callee.
-        let caller_gas_left =
-            (geth_step.gas.0 - geth_step.gas_cost.0 - memory_expansion_gas_cost) / 64;
+        let callee_gas_left = eip150_gas(geth_step.gas.0 - gas_cost, Word::MAX);
 
+        if call.code_hash.to_fixed_bytes() == *EMPTY_HASH {
+            // 1. Create with empty initcode.
+            for (field, value) in [
+                (CallContextField::LastCalleeId, 0.into()),
+                (CallContextField::LastCalleeReturnDataOffset, 0.into()),
+                (CallContextField::LastCalleeReturnDataLength, 0.into()),
+            ] {
+                state.call_context_write(&mut exec_step, current_call.call_id, field, value);
+            }
+            state.handle_return(geth_step)?;
+            return Ok(vec![exec_step]);
+        }
+
+        // 2. Create with non-empty initcode.
         for (field, value) in [
             (
                 CallContextField::ProgramCounter,
@@ -143,13 +201,15 @@ impl<const IS_CREATE2: bool> Opcode for DummyCreate<IS_CREATE2> {
                 CallContextField::StackPointer,
                 geth_step.stack.nth_last_filled(n_pop - 1).0.into(),
             ),
-            (CallContextField::GasLeft, caller_gas_left.into()),
+            (
+                CallContextField::GasLeft,
+                (geth_step.gas.0 - gas_cost - callee_gas_left).into(),
+            ),
             (CallContextField::MemorySize, next_memory_word_size.into()),
             (
                 CallContextField::ReversibleWriteCounter,
-                // +3 is because we do some transfers after pushing the call. can be just push the
-                // call later?
-                (exec_step.reversible_write_counter + 3).into(),
+                // The caller's nonce update and the access list write.
+                (exec_step.reversible_write_counter + 2).into(),
             ),
         ] {
             state.call_context_write(&mut exec_step, current_call.call_id, field, value);
@@ -157,30 +217,96 @@ impl<const IS_CREATE2: bool> Opcode for DummyCreate<IS_CREATE2> {
 
         for (field, value) in [
             (CallContextField::CallerId, current_call.call_id.into()),
-            (CallContextField::IsSuccess, call.is_success.to_word()),
-            (CallContextField::IsPersistent, call.is_persistent.to_word()),
-            (CallContextField::TxId, state.tx_ctx.id().into()),
+            (CallContextField::TxId, tx_id.into()),
+            (CallContextField::Depth, call.depth.into()),
             (
                 CallContextField::CallerAddress,
-                current_call.address.to_word(),
+                call.caller_address.to_word(),
             ),
             (CallContextField::CalleeAddress, call.address.to_word()),
-            (
-                CallContextField::RwCounterEndOfReversion,
-                call.rw_counter_end_of_reversion.to_word(),
-            ),
-            (CallContextField::IsPersistent, call.is_persistent.to_word()),
+            (CallContextField::CallDataOffset, 0.into()),
+            (CallContextField::CallDataLength, 0.into()),
+            (CallContextField::ReturnDataOffset, 0.into()),
+            (CallContextField::ReturnDataLength, 0.into()),
+            (CallContextField::Value, call.value),
+            (CallContextField::IsSuccess, call.is_success.to_word()),
+            (CallContextField::IsStatic, 0.into()),
+            (CallContextField::LastCalleeId, 0.into()),
+            (CallContextField::LastCalleeReturnDataOffset, 0.into()),
+            (CallContextField::LastCalleeReturnDataLength, 0.into()),
+            (CallContextField::IsRoot, 0.into()),
+            (CallContextField::IsCreate, 1.into()),
+            (CallContextField::CodeHash, call.code_hash.to_word()),
         ] {
             state.call_context_write(&mut exec_step, call.call_id, field, value);
         }
 
-        if call.code_hash.to_fixed_bytes() == *EMPTY_HASH {
-            // 1. Create with empty initcode.
-            state.handle_return(geth_step)?;
-            Ok(vec![exec_step])
-        } else {
-            // 2. Create with non-empty initcode.
-            Ok(vec![exec_step])
+        Ok(vec![exec_step])
+    }
+}
+
+pub(crate) fn handle_copy(
+    state: &mut CircuitInputStateRef,
+    step: &mut ExecStep,
+    offset: usize,
+    init_code: &[u8],
+    code_hash: H256,
+) -> Result<(), Error> {
+    let bytes: Vec<_> = Bytecode::from(init_code.to_vec())
+        .code
+        .iter()
+        .map(|element| (element.value, element.is_code))
+        .collect();
+
+    let rw_counter_start = state.block_ctx.rwc;
+    for (i, (byte, _)) in bytes.iter().enumerate() {
+        state.memory_read(step, (offset + i).into(), *byte)?;
+    }
+
+    state.push_copy(CopyEvent {
+        rw_counter_start,
+        src_type: CopyDataType::Memory,
+        src_id: NumberOrHash::Number(state.call()?.call_id),
+        src_addr: offset.try_into().unwrap(),
+        src_addr_end: (offset + init_code.len()).try_into().unwrap(),
+        dst_type: CopyDataType::Bytecode,
+        dst_id: NumberOrHash::Hash(code_hash),
+        dst_addr: 0,
+        log_id: None,
+        bytes,
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod create_tests {
+    use super::*;
+    use eth_types::{Address, ToBigEndian};
+    use ethers_core::utils::{get_contract_address, get_create2_address, keccak256};
+
+    #[test]
+    fn create_address_preimage() {
+        let sender = Address::repeat_byte(0xfe);
+        for nonce in [0, 1, 0x7f, 0x80, 0xff, 0x100, 0xdead_beef, u64::MAX - 1] {
+            let address =
+                Address::from_slice(&keccak256(get_create_address_preimage(sender, nonce))[12..]);
+            assert_eq!(address, get_contract_address(sender, nonce));
         }
     }
+
+    #[test]
+    fn create2_address_preimage() {
+        let sender = Address::repeat_byte(0xfe);
+        let salt = Word::from(0x1234_5678u64);
+        let init_code = vec![0x60, 0x00, 0x60, 0x00, 0xf3];
+        let code_hash = H256(keccak256(&init_code));
+        let address = Address::from_slice(
+            &keccak256(get_create2_address_preimage(sender, salt, code_hash))[12..],
+        );
+        assert_eq!(
+            address,
+            get_create2_address(sender, salt.to_be_bytes().to_vec(), init_code)
+        );
+    }
 }
@@ -0,0 +1,67 @@
+use super::Opcode;
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    error::ExecError,
+    evm::OpcodeId,
+    operation::CallContextField,
+    Error,
+};
+use eth_types::GethExecStep;
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the code returned by
+/// [`OpcodeId::RETURN`](crate::evm::OpcodeId::RETURN) in a contract creation
+/// being rejected, either for exceeding the maximum code size or starting with
+/// the byte 0xEF.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct ErrorCodeStore;
+
+impl Opcode for ErrorCodeStore {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+        let next_step = if geth_steps.len() > 1 {
+            Some(&geth_steps[1])
+        } else {
+            None
+        };
+        exec_step.error = state.get_step_err(geth_step, next_step).unwrap();
+        // assert op code can only be RETURN
+        assert!(geth_step.op == OpcodeId::RETURN);
+
+        let offset = geth_step.stack.nth_last(0)?;
+        for i in 0..2 {
+            state.stack_read(
+                &mut exec_step,
+                geth_step.stack.nth_last_filled(i),
+                geth_step.stack.nth_last(i)?,
+            )?;
+        }
+
+        let call = state.call()?.clone();
+        state.call_context_read(
+            &mut exec_step,
+            call.call_id,
+            CallContextField::IsCreate,
+            (call.is_create() as u64).into(),
+        );
+
+        if exec_step.error == Some(ExecError::InvalidCreationCode) {
+            let first_byte = state
+                .call_ctx()?
+                .memory
+                .0
+                .get(offset.as_usize())
+                .copied()
+                .unwrap_or_default();
+            state.memory_read(&mut exec_step, offset.as_usize().into(), first_byte)?;
+        }
+
+        state.gen_restore_context_ops(&mut exec_step, geth_steps)?;
+        state.handle_return(geth_step)?;
+        Ok(vec![exec_step])
+    }
+}
@@ -0,0 +1,149 @@
+use super::create::handle_copy;
+use super::Opcode;
+use crate::{
+    circuit_input_builder::{
+        get_create2_address_preimage, get_create_address_preimage, CircuitInputStateRef, ExecStep,
+    },
+    evm::OpcodeId,
+    operation::{AccountField, AccountOp, CallContextField, TxAccessListAccountOp, RW},
+    Error,
+};
+use eth_types::{GethExecStep, ToWord, Word};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to [`OpcodeId::CREATE`] and [`OpcodeId::CREATE2`] deriving a
+/// contract address which already has a non-zero nonce or non-empty code.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct ErrorContractAddressCollision;
+
+impl Opcode for ErrorContractAddressCollision {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+        let next_step = if geth_steps.len() > 1 {
+            Some(&geth_steps[1])
+        } else {
+            None
+        };
+        exec_step.error = state.get_step_err(geth_step, next_step).unwrap();
+        // assert op code can only be CREATE or CREATE2
+        assert!(geth_step.op.is_create());
+        let is_create2 = geth_step.op == OpcodeId::CREATE2;
+
+        let offset = geth_step.stack.nth_last(1)?.as_usize();
+        let length = geth_step.stack.nth_last(2)?.as_usize();
+        if length != 0 {
+            state
+                .call_ctx_mut()?
+                .memory
+                .extend_at_least(offset + length);
+        }
+
+        let tx_id = state.tx_ctx.id();
+        let current_call = state.call()?.clone();
+        // The nonce of the caller must be read before it's increased, since
+        // the CREATE address is derived from it.
+        let caller_nonce = state.sdb.get_nonce(&current_call.address);
+
+        let call = state.parse_call(geth_step)?;
+
+        for (field, value) in [
+            (CallContextField::TxId, tx_id.into()),
+            (CallContextField::RwCounterEndOfReversion, 0.into()),
+            (
+                CallContextField::IsPersistent,
+                current_call.is_persistent.to_word(),
+            ),
+            (CallContextField::Depth, current_call.depth.into()),
+            (
+                CallContextField::CalleeAddress,
+                current_call.address.to_word(),
+            ),
+        ] {
+            state.call_context_read(&mut exec_step, current_call.call_id, field, value);
+        }
+
+        let n_pop = if is_create2 { 4 } else { 3 };
+        for i in 0..n_pop {
+            state.stack_read(
+                &mut exec_step,
+                geth_step.stack.nth_last_filled(i),
+                geth_step.stack.nth_last(i)?,
+            )?;
+        }
+        state.stack_write(
+            &mut exec_step,
+            geth_step.stack.nth_last_filled(n_pop - 1),
+            Word::zero(),
+        )?;
+
+        // The caller's nonce is increased and the contract address is added
+        // into the access list before the collision is detected.
+        state.push_op_reversible(
+            &mut exec_step,
+            RW::WRITE,
+            AccountOp {
+                address: call.caller_address,
+                field: AccountField::Nonce,
+                value: (caller_nonce + 1).into(),
+                value_prev: caller_nonce.into(),
+            },
+        )?;
+        let is_warm = state.sdb.check_account_in_access_list(&call.address);
+        state.push_op_reversible(
+            &mut exec_step,
+            RW::WRITE,
+            TxAccessListAccountOp {
+                tx_id,
+                address: call.address,
+                is_warm: true,
+                is_warm_prev: is_warm,
+            },
+        )?;
+
+        let init_code = state.call_ctx()?.memory.0[offset..offset + length].to_vec();
+        if length > 0 {
+            handle_copy(state, &mut exec_step, offset, &init_code, call.code_hash)?;
+        }
+
+        // Record the preimage of the contract address for the keccak table.
+        state.block.sha3_inputs.push(if is_create2 {
+            let salt = geth_step.stack.nth_last(3)?;
+            get_create2_address_preimage(current_call.address, salt, call.code_hash)
+        } else {
+            get_create_address_preimage(current_call.address, caller_nonce)
+        });
+
+        let (_, callee_account) = state.sdb.get_account(&call.address);
+        let (callee_nonce, callee_code_hash) =
+            (callee_account.nonce, callee_account.code_hash.to_word());
+        for (field, value) in [
+            (AccountField::Nonce, callee_nonce),
+            (AccountField::CodeHash, callee_code_hash),
+        ] {
+            state.account_read(&mut exec_step, call.address, field, value, value)?;
+        }
+
+        for (field, value) in [
+            (CallContextField::LastCalleeId, 0.into()),
+            (CallContextField::LastCalleeReturnDataOffset, 0.into()),
+            (CallContextField::LastCalleeReturnDataLength, 0.into()),
+        ] {
+            state.call_context_write(&mut exec_step, current_call.call_id, field, value);
+        }
+
+        state.push_call(call);
+        state.handle_return(geth_step)?;
+
+        // The failed creation leaves no return data to the caller.
+        let caller = state.call_mut()?;
+        caller.last_callee_id = 0;
+        caller.last_callee_return_data_offset = 0;
+        caller.last_callee_return_data_length = 0;
+
+        Ok(vec![exec_step])
+    }
+}
@@ -0,0 +1,64 @@
+use super::Opcode;
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    operation::{CallContextField, TxAccessListAccountOp, RW},
+    Error,
+};
+use eth_types::{GethExecStep, ToAddress};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the out of gas errors of the opcodes which access the
+/// account whose address is the first of their `N_POP` stack items: BALANCE,
+/// EXTCODESIZE, EXTCODEHASH and EXTCODECOPY.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct OOGAccountAccess<const N_POP: usize>;
+
+impl<const N_POP: usize> Opcode for OOGAccountAccess<N_POP> {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+        let next_step = if geth_steps.len() > 1 {
+            Some(&geth_steps[1])
+        } else {
+            None
+        };
+        exec_step.error = state.get_step_err(geth_step, next_step).unwrap();
+
+        for i in 0..N_POP {
+            state.stack_read(
+                &mut exec_step,
+                geth_step.stack.nth_last_filled(i),
+                geth_step.stack.nth_last(i)?,
+            )?;
+        }
+
+        let tx_id = state.tx_ctx.id();
+        let call_id = state.call()?.call_id;
+        state.call_context_read(
+            &mut exec_step,
+            call_id,
+            CallContextField::TxId,
+            tx_id.into(),
+        );
+
+        let address = geth_step.stack.last()?.to_address();
+        let is_warm = state.sdb.check_account_in_access_list(&address);
+        state.push_op(
+            &mut exec_step,
+            RW::READ,
+            TxAccessListAccountOp {
+                tx_id,
+                address,
+                is_warm,
+                is_warm_prev: is_warm,
+            },
+        );
+
+        state.gen_restore_context_ops(&mut exec_step, geth_steps)?;
+        state.handle_return(geth_step)?;
+        Ok(vec![exec_step])
+    }
+}
@@ -7,11 +7,13 @@ use crate::{
 use eth_types::{GethExecStep, ToAddress, ToWord};
 
 /// Placeholder structure used to implement [`Opcode`] trait over it
-/// corresponding to the `OpcodeId::CALL` `OpcodeId`.
+/// corresponding to the out of gas errors of `OpcodeId::CALL`,
+/// `OpcodeId::CALLCODE`, `OpcodeId::DELEGATECALL` and `OpcodeId::STATICCALL`.
+/// `N_ARGS` is 7 for the opcodes taking a value and 6 for the others.
 #[derive(Debug, Copy, Clone)]
-pub(crate) struct OOGCall;
+pub(crate) struct OOGCall<const N_ARGS: usize>;
 
-impl Opcode for OOGCall {
+impl<const N_ARGS: usize> Opcode for OOGCall<N_ARGS> {
     fn gen_associated_ops(
         state: &mut CircuitInputStateRef,
         geth_steps: &[GethExecStep],
@@ -25,10 +27,10 @@ impl Opcode for OOGCall {
         };
         exec_step.error = state.get_step_err(geth_step, next_step).unwrap();
 
-        let args_offset = geth_step.stack.nth_last(3)?.as_usize();
-        let args_length = geth_step.stack.nth_last(4)?.as_usize();
-        let ret_offset = geth_step.stack.nth_last(5)?.as_usize();
-        let ret_length = geth_step.stack.nth_last(6)?.as_usize();
+        let args_offset = geth_step.stack.nth_last(N_ARGS - 4)?.as_usize();
+        let args_length = geth_step.stack.nth_last(N_ARGS - 3)?.as_usize();
+        let ret_offset = geth_step.stack.nth_last(N_ARGS - 2)?.as_usize();
+        let ret_length = geth_step.stack.nth_last(N_ARGS - 1)?.as_usize();
 
         state.call_expand_memory(args_offset, args_length, ret_offset, ret_length)?;
 
@@ -46,7 +48,7 @@ impl Opcode for OOGCall {
             state.call_context_read(&mut exec_step, current_call.call_id, field, value);
         }
 
-        for i in 0..7 {
+        for i in 0..N_ARGS {
             state.stack_read(
                 &mut exec_step,
                 geth_step.stack.nth_last_filled(i),
@@ -56,7 +58,7 @@ impl Opcode for OOGCall {
 
         state.stack_write(
             &mut exec_step,
-            geth_step.stack.nth_last_filled(6),
+            geth_step.stack.nth_last_filled(N_ARGS - 1),
             (0u64).into(), // must fail
         )?;
 
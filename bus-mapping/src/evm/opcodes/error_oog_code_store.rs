@@ -0,0 +1,52 @@
+use super::Opcode;
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    evm::OpcodeId,
+    operation::CallContextField,
+    Error,
+};
+use eth_types::GethExecStep;
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the out of gas error of storing the code returned by
+/// [`OpcodeId::RETURN`](crate::evm::OpcodeId::RETURN) in a contract creation.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct OOGCodeStore;
+
+impl Opcode for OOGCodeStore {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+        let next_step = if geth_steps.len() > 1 {
+            Some(&geth_steps[1])
+        } else {
+            None
+        };
+        exec_step.error = state.get_step_err(geth_step, next_step).unwrap();
+        // assert op code can only be RETURN
+        assert!(geth_step.op == OpcodeId::RETURN);
+
+        for i in 0..2 {
+            state.stack_read(
+                &mut exec_step,
+                geth_step.stack.nth_last_filled(i),
+                geth_step.stack.nth_last(i)?,
+            )?;
+        }
+
+        let call = state.call()?.clone();
+        state.call_context_read(
+            &mut exec_step,
+            call.call_id,
+            CallContextField::IsCreate,
+            (call.is_create() as u64).into(),
+        );
+
+        state.gen_restore_context_ops(&mut exec_step, geth_steps)?;
+        state.handle_return(geth_step)?;
+        Ok(vec![exec_step])
+    }
+}
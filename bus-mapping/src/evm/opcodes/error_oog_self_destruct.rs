@@ -0,0 +1,90 @@
+use super::Opcode;
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    operation::{AccountField, CallContextField, TxAccessListAccountOp, RW},
+    Error,
+};
+use eth_types::{GethExecStep, ToAddress, ToWord, Word};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the out of gas error of
+/// [`OpcodeId::SELFDESTRUCT`](crate::evm::OpcodeId::SELFDESTRUCT).
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct OOGSelfDestruct;
+
+impl Opcode for OOGSelfDestruct {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+        let next_step = if geth_steps.len() > 1 {
+            Some(&geth_steps[1])
+        } else {
+            None
+        };
+        exec_step.error = state.get_step_err(geth_step, next_step).unwrap();
+
+        let beneficiary = geth_step.stack.last()?.to_address();
+        state.stack_read(
+            &mut exec_step,
+            geth_step.stack.last_filled(),
+            beneficiary.to_word(),
+        )?;
+
+        let tx_id = state.tx_ctx.id();
+        let call = state.call()?.clone();
+        for (field, value) in [
+            (CallContextField::TxId, tx_id.into()),
+            (CallContextField::CalleeAddress, call.address.to_word()),
+        ] {
+            state.call_context_read(&mut exec_step, call.call_id, field, value);
+        }
+
+        let is_warm = state.sdb.check_account_in_access_list(&beneficiary);
+        state.push_op(
+            &mut exec_step,
+            RW::READ,
+            TxAccessListAccountOp {
+                tx_id,
+                address: beneficiary,
+                is_warm,
+                is_warm_prev: is_warm,
+            },
+        );
+
+        let (_, beneficiary_account) = state.sdb.get_account(&beneficiary);
+        if beneficiary_account.is_empty() {
+            state.account_read(
+                &mut exec_step,
+                beneficiary,
+                AccountField::NonExisting,
+                Word::zero(),
+                Word::zero(),
+            )?;
+        } else {
+            let code_hash = beneficiary_account.code_hash.to_word();
+            state.account_read(
+                &mut exec_step,
+                beneficiary,
+                AccountField::CodeHash,
+                code_hash,
+                code_hash,
+            )?;
+        }
+
+        let balance = state.sdb.get_account(&call.address).1.balance;
+        state.account_read(
+            &mut exec_step,
+            call.address,
+            AccountField::Balance,
+            balance,
+            balance,
+        )?;
+
+        state.gen_restore_context_ops(&mut exec_step, geth_steps)?;
+        state.handle_return(geth_step)?;
+        Ok(vec![exec_step])
+    }
+}
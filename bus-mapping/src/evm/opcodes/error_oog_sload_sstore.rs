@@ -0,0 +1,85 @@
+use super::Opcode;
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    evm::OpcodeId,
+    operation::{CallContextField, StorageOp, TxAccessListAccountStorageOp, RW},
+    Error,
+};
+use eth_types::{GethExecStep, ToWord};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the out of gas errors of
+/// [`OpcodeId::SLOAD`](crate::evm::OpcodeId::SLOAD) and
+/// [`OpcodeId::SSTORE`](crate::evm::OpcodeId::SSTORE).
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct OOGSloadSstore;
+
+impl Opcode for OOGSloadSstore {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+        let next_step = if geth_steps.len() > 1 {
+            Some(&geth_steps[1])
+        } else {
+            None
+        };
+        exec_step.error = state.get_step_err(geth_step, next_step).unwrap();
+        // assert op code can only be SLOAD or SSTORE
+        assert!(geth_step.op == OpcodeId::SLOAD || geth_step.op == OpcodeId::SSTORE);
+        let is_sstore = geth_step.op == OpcodeId::SSTORE;
+
+        let tx_id = state.tx_ctx.id();
+        let call = state.call()?.clone();
+        for (field, value) in [
+            (CallContextField::TxId, tx_id.into()),
+            (CallContextField::CalleeAddress, call.address.to_word()),
+        ] {
+            state.call_context_read(&mut exec_step, call.call_id, field, value);
+        }
+
+        let key = geth_step.stack.last()?;
+        state.stack_read(&mut exec_step, geth_step.stack.last_filled(), key)?;
+
+        // The gas cost of SSTORE depends on the current and the committed value
+        // of the slot.
+        if is_sstore {
+            state.stack_read(
+                &mut exec_step,
+                geth_step.stack.nth_last_filled(1),
+                geth_step.stack.nth_last(1)?,
+            )?;
+
+            let (_, value) = state.sdb.get_storage(&call.address, &key);
+            let value = *value;
+            let (_, committed_value) = state.sdb.get_committed_storage(&call.address, &key);
+            let committed_value = *committed_value;
+            state.push_op(
+                &mut exec_step,
+                RW::READ,
+                StorageOp::new(call.address, key, value, value, tx_id, committed_value),
+            );
+        }
+
+        let is_warm = state
+            .sdb
+            .check_account_storage_in_access_list(&(call.address, key));
+        state.push_op(
+            &mut exec_step,
+            RW::READ,
+            TxAccessListAccountStorageOp {
+                tx_id,
+                address: call.address,
+                key,
+                is_warm,
+                is_warm_prev: is_warm,
+            },
+        );
+
+        state.gen_restore_context_ops(&mut exec_step, geth_steps)?;
+        state.handle_return(geth_step)?;
+        Ok(vec![exec_step])
+    }
+}
@@ -0,0 +1,151 @@
+use super::Opcode;
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    error::ExecError,
+    evm::OpcodeId,
+    operation::{AccountField, CallContextField, TxAccessListAccountOp, RW},
+    Error,
+};
+use eth_types::{GethExecStep, ToAddress, ToWord, Word};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the *CALL* and CREATE* opcodes failing their precheck,
+/// which is either exceeding the call depth limit or transferring more than the
+/// caller's balance. The callee is never executed, but the call is still parsed
+/// and pushed to keep the calls of the transaction aligned with the trace.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct ErrorPrecheck;
+
+impl Opcode for ErrorPrecheck {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+        let next_step = if geth_steps.len() > 1 {
+            Some(&geth_steps[1])
+        } else {
+            None
+        };
+        exec_step.error = state.get_step_err(geth_step, next_step).unwrap();
+        assert!(geth_step.op.is_call_or_create());
+
+        let is_create_op = geth_step.op.is_create();
+        let n_pop = match geth_step.op {
+            OpcodeId::CALL | OpcodeId::CALLCODE => 7,
+            OpcodeId::DELEGATECALL | OpcodeId::STATICCALL => 6,
+            OpcodeId::CREATE => 3,
+            _ => 4,
+        };
+
+        // The memory is expanded even though the callee isn't executed.
+        if is_create_op {
+            let offset = geth_step.stack.nth_last(1)?.as_usize();
+            let length = geth_step.stack.nth_last(2)?.as_usize();
+            if length != 0 {
+                state
+                    .call_ctx_mut()?
+                    .memory
+                    .extend_at_least(offset + length);
+            }
+        } else {
+            let args_offset = geth_step.stack.nth_last(n_pop - 4)?.as_usize();
+            let args_length = geth_step.stack.nth_last(n_pop - 3)?.as_usize();
+            let ret_offset = geth_step.stack.nth_last(n_pop - 2)?.as_usize();
+            let ret_length = geth_step.stack.nth_last(n_pop - 1)?.as_usize();
+            state.call_expand_memory(args_offset, args_length, ret_offset, ret_length)?;
+        }
+
+        let tx_id = state.tx_ctx.id();
+        let call = state.parse_call(geth_step)?;
+        let current_call = state.call()?.clone();
+
+        for (field, value) in [
+            (CallContextField::TxId, tx_id.into()),
+            // NOTE: For `RwCounterEndOfReversion` we use the `0` value as a
+            // placeholder, and later set the proper value in
+            // `CircuitInputBuilder::set_value_ops_call_context_rwc_eor`
+            (CallContextField::RwCounterEndOfReversion, 0.into()),
+            (
+                CallContextField::IsPersistent,
+                current_call.is_persistent.to_word(),
+            ),
+            (CallContextField::Depth, current_call.depth.into()),
+            (
+                CallContextField::CalleeAddress,
+                current_call.address.to_word(),
+            ),
+        ] {
+            state.call_context_read(&mut exec_step, current_call.call_id, field, value);
+        }
+
+        for i in 0..n_pop {
+            state.stack_read(
+                &mut exec_step,
+                geth_step.stack.nth_last_filled(i),
+                geth_step.stack.nth_last(i)?,
+            )?;
+        }
+        state.stack_write(
+            &mut exec_step,
+            geth_step.stack.nth_last_filled(n_pop - 1),
+            Word::zero(),
+        )?;
+
+        // The callee of *CALL* is still added into the access list.
+        if !is_create_op {
+            let callee_address = geth_step.stack.nth_last(1)?.to_address();
+            let is_warm = state.sdb.check_account_in_access_list(&callee_address);
+            state.push_op_reversible(
+                &mut exec_step,
+                RW::WRITE,
+                TxAccessListAccountOp {
+                    tx_id,
+                    address: callee_address,
+                    is_warm: true,
+                    is_warm_prev: is_warm,
+                },
+            )?;
+
+            let (_, callee_account) = state.sdb.get_account(&callee_address);
+            let (field, value) = if callee_account.is_empty() {
+                (AccountField::NonExisting, Word::zero())
+            } else {
+                (AccountField::CodeHash, callee_account.code_hash.to_word())
+            };
+            state.account_read(&mut exec_step, callee_address, field, value, value)?;
+        }
+
+        if exec_step.error == Some(ExecError::InsufficientBalance) {
+            let (_, caller_account) = state.sdb.get_account(&current_call.address);
+            let caller_balance = caller_account.balance;
+            state.account_read(
+                &mut exec_step,
+                current_call.address,
+                AccountField::Balance,
+                caller_balance,
+                caller_balance,
+            )?;
+        }
+
+        for (field, value) in [
+            (CallContextField::LastCalleeId, 0.into()),
+            (CallContextField::LastCalleeReturnDataOffset, 0.into()),
+            (CallContextField::LastCalleeReturnDataLength, 0.into()),
+        ] {
+            state.call_context_write(&mut exec_step, current_call.call_id, field, value);
+        }
+
+        state.push_call(call);
+        state.handle_return(geth_step)?;
+
+        // The failed callee leaves no return data to the caller.
+        let caller = state.call_mut()?;
+        caller.last_callee_id = 0;
+        caller.last_callee_return_data_offset = 0;
+        caller.last_callee_return_data_length = 0;
+
+        Ok(vec![exec_step])
+    }
+}
@@ -1,8 +1,10 @@
 use super::Opcode;
-use crate::circuit_input_builder::{CircuitInputStateRef, ExecStep};
-use crate::operation::{TxAccessListAccountOp, RW};
+use crate::circuit_input_builder::{
+    CircuitInputStateRef, CopyDataType, CopyEvent, ExecStep, NumberOrHash,
+};
+use crate::operation::{AccountField, CallContextField, TxAccessListAccountOp, RW};
 use crate::Error;
-use eth_types::{GethExecStep, ToAddress};
+use eth_types::{Bytecode, GethExecStep, ToAddress, ToWord, Word, H256};
 
 #[derive(Clone, Copy, Debug)]
 pub(crate) struct Extcodecopy;
@@ -13,7 +15,7 @@ impl Opcode for Extcodecopy {
         geth_steps: &[GethExecStep],
     ) -> Result<Vec<ExecStep>, Error> {
         let geth_step = &geth_steps[0];
-        let exec_steps = vec![gen_extcodecopy_step(state, geth_step)?];
+        let mut exec_step = gen_extcodecopy_step(state, geth_step)?;
 
         // reconstruction
         let address = geth_steps[0].stack.nth_last(0)?.to_address();
@@ -21,9 +23,12 @@ impl Opcode for Extcodecopy {
         let code_offset = geth_steps[0].stack.nth_last(2)?.as_u64();
         let length = geth_steps[0].stack.nth_last(3)?.as_u64();
 
-        let (exist, account) = state.sdb.get_account(&address);
-        assert!(exist, "target account does not exist");
-        let code = state.code(account.code_hash)?;
+        let (_, account) = state.sdb.get_account(&address);
+        let code = if account.is_empty() {
+            vec![]
+        } else {
+            state.code(account.code_hash)?
+        };
 
         let call_ctx = state.call_ctx_mut()?;
         let memory = &mut call_ctx.memory;
@@ -45,8 +50,9 @@ impl Opcode for Extcodecopy {
             }
         }
 
-        // TODO: implement copy event
-        Ok(exec_steps)
+        let copy_event = gen_copy_event(state, geth_step, &mut exec_step)?;
+        state.push_copy(copy_event);
+        Ok(vec![exec_step])
     }
 }
 
@@ -61,35 +67,129 @@ fn gen_extcodecopy_step(
     let offset = geth_step.stack.nth_last(2)?;
     let length = geth_step.stack.nth_last(3)?;
 
-    let is_warm = state
-        .sdb
-        .check_account_in_access_list(&address.to_address());
+    // stack reads
+    state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(0), address)?;
+    state.stack_read(
+        &mut exec_step,
+        geth_step.stack.nth_last_filled(1),
+        dest_offset,
+    )?;
+    state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(2), offset)?;
+    state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(3), length)?;
+
+    for (field, value) in [
+        (CallContextField::TxId, state.tx_ctx.id().to_word()),
+        (
+            CallContextField::RwCounterEndOfReversion,
+            state.call()?.rw_counter_end_of_reversion.to_word(),
+        ),
+        (
+            CallContextField::IsPersistent,
+            state.call()?.is_persistent.to_word(),
+        ),
+    ] {
+        state.call_context_read(&mut exec_step, state.call()?.call_id, field, value);
+    }
+
+    let address = address.to_address();
+    let is_warm = state.sdb.check_account_in_access_list(&address);
     state.push_op_reversible(
         &mut exec_step,
         RW::WRITE,
         TxAccessListAccountOp {
             tx_id: state.tx_ctx.id(),
-            address: address.to_address(),
+            address,
             is_warm: true,
             is_warm_prev: is_warm,
         },
     )?;
 
-    // stack reads
-    state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(0), address)?;
-    state.stack_read(
-        &mut exec_step,
-        geth_step.stack.nth_last_filled(1),
-        dest_offset,
-    )?;
-    state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(2), offset)?;
-    state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(3), length)?;
+    let account = state.sdb.get_account(&address).1;
+    if !account.is_empty() {
+        let code_hash_word = account.code_hash.to_word();
+        state.account_read(
+            &mut exec_step,
+            address,
+            AccountField::CodeHash,
+            code_hash_word,
+            code_hash_word,
+        )?;
+    } else {
+        state.account_read(
+            &mut exec_step,
+            address,
+            AccountField::NonExisting,
+            Word::zero(),
+            Word::zero(),
+        )?;
+    }
+
     Ok(exec_step)
 }
 
+fn gen_copy_steps(
+    state: &mut CircuitInputStateRef,
+    exec_step: &mut ExecStep,
+    src_addr: u64,
+    dst_addr: u64,
+    bytes_left: u64,
+    bytecode: &Bytecode,
+) -> Result<Vec<(u8, bool)>, Error> {
+    let mut steps = Vec::with_capacity(bytes_left as usize);
+    for idx in 0..bytes_left {
+        let addr = src_addr + idx;
+        let bytecode_element = bytecode.get(addr as usize).unwrap_or_default();
+        steps.push((bytecode_element.value, bytecode_element.is_code));
+        state.memory_write(exec_step, (dst_addr + idx).into(), bytecode_element.value)?;
+    }
+    Ok(steps)
+}
+
+fn gen_copy_event(
+    state: &mut CircuitInputStateRef,
+    geth_step: &GethExecStep,
+    exec_step: &mut ExecStep,
+) -> Result<CopyEvent, Error> {
+    let rw_counter_start = state.block_ctx.rwc;
+
+    let external_address = geth_step.stack.nth_last(0)?.to_address();
+    let dst_offset = geth_step.stack.nth_last(1)?.as_u64();
+    let code_offset = geth_step.stack.nth_last(2)?.as_u64();
+    let length = geth_step.stack.nth_last(3)?.as_u64();
+
+    // A non-existing account has no code, so only zeros are copied. Its code
+    // hash is read as 0 by the circuit.
+    let (_, account) = state.sdb.get_account(&external_address);
+    let (code_hash, bytecode): (_, Bytecode) = if account.is_empty() {
+        (H256::zero(), Bytecode::default())
+    } else {
+        let code_hash = account.code_hash;
+        (code_hash, state.code(code_hash)?.into())
+    };
+    let src_addr_end = bytecode.to_vec().len() as u64;
+
+    let copy_steps = gen_copy_steps(state, exec_step, code_offset, dst_offset, length, &bytecode)?;
+
+    Ok(CopyEvent {
+        src_type: CopyDataType::Bytecode,
+        src_id: NumberOrHash::Hash(code_hash),
+        src_addr: code_offset,
+        src_addr_end,
+        dst_type: CopyDataType::Memory,
+        dst_id: NumberOrHash::Number(state.call()?.call_id),
+        dst_addr: dst_offset,
+        log_id: None,
+        rw_counter_start,
+        bytes: copy_steps,
+    })
+}
+
 #[cfg(test)]
 mod extcodecopy_tests {
+    use crate::circuit_input_builder::{CopyDataType, ExecState};
     use crate::mock::BlockData;
+    use crate::operation::RW;
+    use eth_types::evm_types::OpcodeId;
     use eth_types::geth_types::GethData;
     use eth_types::{bytecode, word};
     use mock::test_ctx::helpers::{account_0_code_account_1_no_code, tx_from_1_to_0};
@@ -149,5 +249,36 @@ mod extcodecopy_tests {
         builder
             .handle_block(&block.eth_block, &block.geth_traces)
             .unwrap();
+
+        let step = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::EXTCODECOPY))
+            .unwrap();
+        // The created contract is already in the access list.
+        let operation = &builder.block.container.tx_access_list_account
+            [step.bus_mapping_instance[7].as_usize()];
+        assert_eq!(operation.rw(), RW::WRITE);
+        assert!(operation.op().is_warm_prev);
+
+        // The deployed code is 12 bytes long, so the rest of the 32 bytes are
+        // padded with zeros.
+        let copy_event = builder.block.copy_events.last().unwrap();
+        assert_eq!(copy_event.src_type, CopyDataType::Bytecode);
+        assert_eq!(copy_event.dst_type, CopyDataType::Memory);
+        assert_eq!(copy_event.src_addr, 0);
+        assert_eq!(copy_event.src_addr_end, 12);
+        assert_eq!(copy_event.dst_addr, 0x20);
+        assert_eq!(copy_event.bytes.len(), 0x20);
+        assert_eq!(
+            copy_event
+                .bytes
+                .iter()
+                .take(12)
+                .map(|(byte, _)| *byte)
+                .collect::<Vec<_>>(),
+            hex::decode("6020600060003760206000F3").unwrap()
+        );
+        assert!(copy_event.bytes[12..].iter().all(|(byte, _)| *byte == 0));
     }
 }
@@ -124,6 +124,25 @@ fn gen_log_step(
         }
     }
 
+    if state.call()?.is_persistent {
+        state.tx_log_write(
+            &mut exec_step,
+            state.tx_ctx.id(),
+            state.tx_ctx.log_id + 1,
+            TxLogField::TopicLength,
+            0,
+            topic_count.into(),
+        )?;
+        state.tx_log_write(
+            &mut exec_step,
+            state.tx_ctx.id(),
+            state.tx_ctx.log_id + 1,
+            TxLogField::DataLength,
+            0,
+            msize,
+        )?;
+    }
+
     Ok(exec_step)
 }
 
@@ -211,23 +230,47 @@ mod log_tests {
     #[test]
     fn logs_opcode_ok() {
         // zero topics
-        test_logs_opcode(&[]);
+        test_logs_opcode(&[], true);
         // one topics
-        test_logs_opcode(&[Word::from(0xA0)]);
+        test_logs_opcode(&[Word::from(0xA0)], true);
         // two topics
-        test_logs_opcode(&[Word::from(0xA0), Word::from(0xef)]);
+        test_logs_opcode(&[Word::from(0xA0), Word::from(0xef)], true);
         // three topics
-        test_logs_opcode(&[Word::from(0xA0), Word::from(0xef), Word::from(0xb0)]);
+        test_logs_opcode(
+            &[Word::from(0xA0), Word::from(0xef), Word::from(0xb0)],
+            true,
+        );
         // four topics
-        test_logs_opcode(&[
-            Word::from(0xA0),
-            Word::from(0xef),
-            Word::from(0xb0),
-            Word::from(0x37),
-        ]);
+        test_logs_opcode(
+            &[
+                Word::from(0xA0),
+                Word::from(0xef),
+                Word::from(0xb0),
+                Word::from(0x37),
+            ],
+            true,
+        );
     }
 
-    fn test_logs_opcode(topics: &[Word]) {
+    #[test]
+    fn logs_opcode_reverted() {
+        // zero topics
+        test_logs_opcode(&[], false);
+        // one topics
+        test_logs_opcode(&[Word::from(0xA0)], false);
+        // four topics
+        test_logs_opcode(
+            &[
+                Word::from(0xA0),
+                Word::from(0xef),
+                Word::from(0xb0),
+                Word::from(0x37),
+            ],
+            false,
+        );
+    }
+
+    fn test_logs_opcode(topics: &[Word], is_persistent: bool) {
         let log_codes = [
             OpcodeId::LOG0,
             OpcodeId::LOG1,
@@ -250,7 +293,16 @@ mod log_tests {
         code.push(32, Word::from(msize));
         code.push(32, Word::from(mstart));
         code.write_op(cur_op_code);
-        code.write_op(OpcodeId::STOP);
+        if is_persistent {
+            code.write_op(OpcodeId::STOP);
+        } else {
+            // revert the call, so the log is not persistent
+            code.append(&bytecode! {
+                PUSH1(0x00)
+                PUSH1(0x00)
+                REVERT
+            });
+        }
 
         // prepare memory data
         let pushdata = hex::decode("1234567890abcdef1234567890abcdef").unwrap();
@@ -288,7 +340,10 @@ mod log_tests {
             .handle_block(&block.eth_block, &block.geth_traces)
             .unwrap();
 
-        let is_persistent = builder.block.txs()[0].calls()[0].is_persistent;
+        assert_eq!(
+            builder.block.txs()[0].calls()[0].is_persistent,
+            is_persistent
+        );
         let callee_address = builder.block.txs()[0].to;
 
         let step = builder.block.txs()[0]
@@ -351,31 +406,36 @@ mod log_tests {
                     &CallContextOp {
                         call_id: 1,
                         field: CallContextField::IsPersistent,
-                        value: Word::from(1),
+                        value: Word::from(is_persistent as u8),
                     },
                 ),
             ]
         );
 
-        // TODO: handle is_persistent = false conditions
-        if is_persistent {
-            assert_eq!(
-                [6].map(|idx| &builder.block.container.tx_log
-                    [step.bus_mapping_instance[idx].as_usize()])
-                    .map(|operation| (operation.rw(), operation.op())),
-                [(
-                    RW::WRITE,
-                    &TxLogOp {
-                        tx_id: 1,
-                        log_id: step.log_id + 1,
-                        field: TxLogField::Address,
-                        index: 0,
-                        value: callee_address.to_word(),
-                    }
-                ),]
-            );
+        // logs of reverted calls are not written to the TxLog
+        if !is_persistent {
+            assert!(builder.block.container.tx_log.is_empty());
+            assert!(builder.block.copy_events.is_empty());
+            return;
         }
 
+        assert_eq!(
+            [6].map(
+                |idx| &builder.block.container.tx_log[step.bus_mapping_instance[idx].as_usize()]
+            )
+            .map(|operation| (operation.rw(), operation.op())),
+            [(
+                RW::WRITE,
+                &TxLogOp {
+                    tx_id: 1,
+                    log_id: step.log_id + 1,
+                    field: TxLogField::Address,
+                    index: 0,
+                    value: callee_address.to_word(),
+                }
+            ),]
+        );
+
         // log topic writes
         let mut log_topic_ops = Vec::with_capacity(topic_count);
         for (idx, topic) in topics.iter().rev().enumerate() {
@@ -392,6 +452,36 @@ mod log_tests {
             { log_topic_ops },
         );
 
+        // log topic and data lengths
+        assert_eq!(
+            (1 + topic_count..3 + topic_count)
+                .map(|idx| &builder.block.container.tx_log[idx])
+                .map(|op| (op.rw(), op.op().clone()))
+                .collect::<Vec<(RW, TxLogOp)>>(),
+            [
+                (
+                    RW::WRITE,
+                    TxLogOp::new(
+                        1,
+                        step.log_id + 1,
+                        TxLogField::TopicLength,
+                        0,
+                        Word::from(topic_count)
+                    )
+                ),
+                (
+                    RW::WRITE,
+                    TxLogOp::new(
+                        1,
+                        step.log_id + 1,
+                        TxLogField::DataLength,
+                        0,
+                        Word::from(msize)
+                    )
+                ),
+            ]
+        );
+
         // memory reads.
         let mut log_data_ops = Vec::with_capacity(msize);
         assert_eq!(
@@ -424,7 +514,7 @@ mod log_tests {
             },
         );
         assert_eq!(
-            ((1 + topic_count)..msize + 1 + topic_count)
+            ((3 + topic_count)..msize + 3 + topic_count)
                 .map(|idx| &builder.block.container.tx_log[idx])
                 .map(|op| (op.rw(), op.op().clone()))
                 .collect::<Vec<(RW, TxLogOp)>>(),
@@ -0,0 +1,336 @@
+use crate::circuit_input_builder::{
+    CircuitInputStateRef, CopyDataType, CopyEvent, ExecState, ExecStep, NumberOrHash,
+    PrecompileEvent,
+};
+use crate::operation::{CallContextField, MemoryOp, RW};
+use crate::precompile::{execute_precompiled, PrecompileCalls};
+use crate::Error;
+use eth_types::evm_types::{Gas, GasCost};
+use eth_types::{ToWord, Word};
+use std::iter;
+
+/// Length of the input of ecrecover: the message hash, `v`, `r` and `s`.
+const ECRECOVER_INPUT_LENGTH: usize = 128;
+
+/// Generate the [`ExecStep`] which executes the precompiled contract of the
+/// current call, right after it's entered by a `*CALL` opcode (whose step is
+/// passed in `call_step`) or by the `BeginTx` of a transaction to a
+/// precompiled contract.
+///
+/// The input of identity is copied into the callee memory.  The input of a
+/// call to ecrecover which recovers an address is accumulated, right padded
+/// with zeros, into the RLC looked up from the sig table.  The input of the
+/// other calls is accumulated into the RLC looked up from the precompile
+/// table, except for a call to ecrecover which runs out of gas.  The output is
+/// written into the callee memory, and read back into an RLC unless it's the
+/// output of identity.
+///
+/// For a non root call, `caller_context` holds the caller's context fields
+/// written by the `*CALL` step, which are read back to restore the caller's
+/// context.
+pub(crate) fn gen_associated_ops(
+    state: &mut CircuitInputStateRef,
+    call_step: Option<&mut ExecStep>,
+    precompile: PrecompileCalls,
+    gas_left: u64,
+    caller_context: &[(CallContextField, Word)],
+) -> Result<ExecStep, Error> {
+    let call = state.call()?.clone();
+    let input = state.call_ctx()?.call_data.clone();
+    let result = execute_precompiled(&precompile.into(), &input, gas_left);
+    if result.is_success != call.is_success {
+        return Err(Error::InternalError(
+            "precompiled contract result differs from the trace",
+        ));
+    }
+
+    let mut exec_step = ExecStep {
+        exec_state: ExecState::Precompile(precompile),
+        gas_left: Gas(gas_left),
+        gas_cost: GasCost(result.gas_cost),
+        call_index: state.call_ctx()?.index,
+        rwc: state.block_ctx.rwc,
+        reversible_write_counter: state.call_ctx()?.reversible_write_counter,
+        log_id: state.tx_ctx.log_id,
+        ..Default::default()
+    };
+
+    for (field, value) in [
+        (CallContextField::IsSuccess, (call.is_success as u64).into()),
+        (CallContextField::IsRoot, (call.is_root as u64).into()),
+        (
+            CallContextField::CallDataOffset,
+            call.call_data_offset.into(),
+        ),
+        (
+            CallContextField::CallDataLength,
+            call.call_data_length.into(),
+        ),
+    ] {
+        state.call_context_read(&mut exec_step, call.call_id, field, value);
+    }
+    if call.is_root {
+        state.call_context_read(
+            &mut exec_step,
+            call.call_id,
+            CallContextField::TxId,
+            state.tx_ctx.id().into(),
+        );
+    } else {
+        for (field, value) in [
+            (
+                CallContextField::ReturnDataOffset,
+                call.return_data_offset.into(),
+            ),
+            (
+                CallContextField::ReturnDataLength,
+                call.return_data_length.into(),
+            ),
+        ] {
+            state.call_context_read(&mut exec_step, call.call_id, field, value);
+        }
+    }
+
+    let event = PrecompileEvent {
+        call_id: call.call_id,
+        precompile,
+        input: input.clone(),
+        output: precompile.execute(&input).unwrap_or_default(),
+        required_gas: result.required_gas,
+        is_valid: result.is_valid,
+        is_success: result.is_success,
+    };
+
+    let (src_type, src_id) = if call.is_root {
+        (
+            CopyDataType::TxCalldata,
+            NumberOrHash::Number(state.tx_ctx.id()),
+        )
+    } else {
+        (CopyDataType::Memory, NumberOrHash::Number(call.caller_id))
+    };
+    let length = input.len();
+    if precompile == PrecompileCalls::Identity {
+        // Copy the input into the callee memory.
+        if length != 0 {
+            let rw_counter_start = state.block_ctx.rwc;
+            for (i, byte) in input.iter().enumerate() {
+                if !call.is_root {
+                    state.push_op(
+                        &mut exec_step,
+                        RW::READ,
+                        MemoryOp::new(
+                            call.caller_id,
+                            (call.call_data_offset as usize + i).into(),
+                            *byte,
+                        ),
+                    );
+                }
+                state.memory_write(&mut exec_step, i.into(), *byte)?;
+            }
+            let memory = &mut state.call_ctx_mut()?.memory;
+            memory.extend_at_least(length);
+            memory.0[..length].copy_from_slice(&input);
+            state.push_copy(CopyEvent {
+                src_addr: call.call_data_offset,
+                src_addr_end: call.call_data_offset + call.call_data_length,
+                src_type,
+                src_id,
+                dst_addr: 0,
+                dst_type: CopyDataType::Memory,
+                dst_id: NumberOrHash::Number(call.call_id),
+                log_id: None,
+                rw_counter_start,
+                bytes: input.iter().map(|byte| (*byte, false)).collect(),
+            });
+        }
+    } else if event.ecrecover_sign_data().is_some() {
+        // Accumulate the input of ecrecover, right padded with zeros, into the
+        // RLC looked up from the sig table.
+        let rw_counter_start = state.block_ctx.rwc;
+        if !call.is_root {
+            for (i, byte) in input.iter().take(ECRECOVER_INPUT_LENGTH).enumerate() {
+                state.push_op(
+                    &mut exec_step,
+                    RW::READ,
+                    MemoryOp::new(
+                        call.caller_id,
+                        (call.call_data_offset as usize + i).into(),
+                        *byte,
+                    ),
+                );
+            }
+        }
+        state.push_copy(CopyEvent {
+            src_addr: call.call_data_offset,
+            src_addr_end: call.call_data_offset + call.call_data_length,
+            src_type,
+            src_id,
+            dst_addr: 0,
+            dst_type: CopyDataType::RlcAcc,
+            dst_id: NumberOrHash::Number(call.call_id),
+            log_id: None,
+            rw_counter_start,
+            bytes: input
+                .iter()
+                .copied()
+                .chain(iter::repeat(0))
+                .take(ECRECOVER_INPUT_LENGTH)
+                .map(|byte| (byte, false))
+                .collect(),
+        });
+    } else if (precompile != PrecompileCalls::ECRecover || call.is_success) && length != 0 {
+        // Accumulate the input into the RLC looked up from the precompile table.
+        let rw_counter_start = state.block_ctx.rwc;
+        if !call.is_root {
+            for (i, byte) in input.iter().enumerate() {
+                state.push_op(
+                    &mut exec_step,
+                    RW::READ,
+                    MemoryOp::new(
+                        call.caller_id,
+                        (call.call_data_offset as usize + i).into(),
+                        *byte,
+                    ),
+                );
+            }
+        }
+        state.push_copy(CopyEvent {
+            src_addr: call.call_data_offset,
+            src_addr_end: call.call_data_offset + call.call_data_length,
+            src_type,
+            src_id,
+            dst_addr: 0,
+            dst_type: CopyDataType::RlcAcc,
+            dst_id: NumberOrHash::Number(call.call_id),
+            log_id: None,
+            rw_counter_start,
+            bytes: input.iter().map(|byte| (*byte, false)).collect(),
+        });
+    }
+
+    // Write the output into the callee memory, where it's returned from.
+    let output = result.output.clone();
+    if !output.is_empty() && precompile != PrecompileCalls::Identity {
+        let rw_counter_start = state.block_ctx.rwc;
+        for (i, byte) in output.iter().enumerate() {
+            state.memory_write(&mut exec_step, i.into(), *byte)?;
+        }
+        let memory = &mut state.call_ctx_mut()?.memory;
+        memory.extend_at_least(output.len());
+        memory.0[..output.len()].copy_from_slice(&output);
+        state.push_copy(CopyEvent {
+            src_addr: 0,
+            src_addr_end: output.len() as u64,
+            src_type: CopyDataType::PrecompileOutput,
+            src_id: NumberOrHash::Number(call.call_id),
+            dst_addr: 0,
+            dst_type: CopyDataType::Memory,
+            dst_id: NumberOrHash::Number(call.call_id),
+            log_id: None,
+            rw_counter_start,
+            bytes: output.iter().map(|byte| (*byte, false)).collect(),
+        });
+
+        // Read the output back from the callee memory into the RLC looked up
+        // from the sig table or the precompile table, which binds it to the
+        // input.
+        let rw_counter_start = state.block_ctx.rwc;
+        for (i, byte) in output.iter().enumerate() {
+            state.push_op(
+                &mut exec_step,
+                RW::READ,
+                MemoryOp::new(call.call_id, i.into(), *byte),
+            );
+        }
+        state.push_copy(CopyEvent {
+            src_addr: 0,
+            src_addr_end: output.len() as u64,
+            src_type: CopyDataType::Memory,
+            src_id: NumberOrHash::Number(call.call_id),
+            dst_addr: 0,
+            dst_type: CopyDataType::RlcAcc,
+            dst_id: NumberOrHash::Number(call.call_id),
+            log_id: None,
+            rw_counter_start,
+            bytes: output.iter().map(|byte| (*byte, false)).collect(),
+        });
+    }
+
+    if !call.is_root {
+        // Copy the return data into the caller memory.
+        let copy_length = std::cmp::min(call.return_data_length as usize, output.len());
+        if copy_length > 0 {
+            let return_offset = call.return_data_offset as usize;
+            let rw_counter_start = state.block_ctx.rwc;
+            for (i, byte) in output[..copy_length].iter().enumerate() {
+                state.push_op(
+                    &mut exec_step,
+                    RW::READ,
+                    MemoryOp::new(call.call_id, i.into(), *byte),
+                );
+                state.push_op(
+                    &mut exec_step,
+                    RW::WRITE,
+                    MemoryOp::new(call.caller_id, (return_offset + i).into(), *byte),
+                );
+            }
+            state.caller_ctx_mut()?.memory.0[return_offset..return_offset + copy_length]
+                .copy_from_slice(&output[..copy_length]);
+            state.push_copy(CopyEvent {
+                src_addr: 0,
+                src_addr_end: output.len() as u64,
+                src_type: CopyDataType::Memory,
+                src_id: NumberOrHash::Number(call.call_id),
+                dst_addr: call.return_data_offset,
+                dst_type: CopyDataType::Memory,
+                dst_id: NumberOrHash::Number(call.caller_id),
+                log_id: None,
+                rw_counter_start,
+                bytes: output[..copy_length]
+                    .iter()
+                    .map(|byte| (*byte, false))
+                    .collect(),
+            });
+        }
+
+        // Restore the caller's context.
+        let caller = state.caller()?.clone();
+        state.call_context_read(
+            &mut exec_step,
+            call.call_id,
+            CallContextField::CallerId,
+            caller.call_id.into(),
+        );
+        for (field, value) in [
+            (CallContextField::IsRoot, (caller.is_root as u64).into()),
+            (
+                CallContextField::IsCreate,
+                (caller.is_create() as u64).into(),
+            ),
+            (CallContextField::CodeHash, caller.code_hash.to_word()),
+        ]
+        .into_iter()
+        .chain(caller_context.iter().cloned())
+        {
+            state.call_context_read(&mut exec_step, caller.call_id, field, value);
+        }
+        for (field, value) in [
+            (CallContextField::LastCalleeId, call.call_id.into()),
+            (CallContextField::LastCalleeReturnDataOffset, Word::zero()),
+            (
+                CallContextField::LastCalleeReturnDataLength,
+                output.len().into(),
+            ),
+        ] {
+            state.call_context_write(&mut exec_step, caller.call_id, field, value);
+        }
+    }
+
+    state.block.add_precompile_event(event);
+
+    let mut current_exec_steps: Vec<&mut ExecStep> = call_step.into_iter().collect();
+    state.handle_precompile_return(&mut current_exec_steps, &output)?;
+
+    Ok(exec_step)
+}
@@ -8,7 +8,7 @@ use crate::{
     operation::{AccountField, CallContextField, RW},
     Error,
 };
-use eth_types::{Bytecode, GethExecStep, ToWord, Word, H256};
+use eth_types::{evm_types::GasCost, Bytecode, GethExecStep, ToWord, Word, H256};
 use ethers_core::utils::keccak256;
 use keccak256::EMPTY_HASH_LE;
 
@@ -73,6 +73,14 @@ impl Opcode for ReturnRevert {
                 state.call_context_read(&mut exec_step, state.call()?.call_id, field, value);
             }
 
+            // The code deposit cost of a root call is charged in this step,
+            // since there's no caller to charge it when restoring the context.
+            if call.is_root {
+                exec_step.gas_cost = GasCost(
+                    exec_step.gas_cost.0 + GasCost::CODE_DEPOSIT_BYTE_COST.0 * length as u64,
+                );
+            }
+
             state.push_op_reversible(
                 &mut exec_step,
                 RW::WRITE,
@@ -0,0 +1,352 @@
+use super::Opcode;
+use crate::circuit_input_builder::{CircuitInputStateRef, ExecStep};
+use crate::operation::{
+    AccountDestructedOp, AccountField, AccountOp, CallContextField, TxAccessListAccountOp,
+    TxRefundOp, RW,
+};
+use crate::Error;
+use eth_types::{GethExecStep, ToAddress, ToWord, Word};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the
+/// [`OpcodeId::SELFDESTRUCT`](crate::evm::OpcodeId::SELFDESTRUCT) `OpcodeId`.
+/// The whole balance of the current account is moved to the beneficiary, and
+/// the current account is marked as destructed, which takes effect at the end
+/// of the transaction.  Before London, the first destruction of an account in a
+/// transaction is refunded.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Selfdestruct;
+
+impl Opcode for Selfdestruct {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+
+        let tx_id = state.tx_ctx.id();
+        let call = state.call()?.clone();
+        let beneficiary = geth_step.stack.last()?.to_address();
+
+        for (field, value) in [
+            (CallContextField::TxId, tx_id.into()),
+            (
+                CallContextField::RwCounterEndOfReversion,
+                call.rw_counter_end_of_reversion.into(),
+            ),
+            (CallContextField::IsPersistent, call.is_persistent.to_word()),
+            (CallContextField::CalleeAddress, call.address.to_word()),
+            // A call halted by SELFDESTRUCT is always successful.
+            (CallContextField::IsSuccess, 1.into()),
+        ] {
+            state.call_context_read(&mut exec_step, call.call_id, field, value);
+        }
+
+        state.stack_read(
+            &mut exec_step,
+            geth_step.stack.last_filled(),
+            beneficiary.to_word(),
+        )?;
+
+        let is_warm = state.sdb.check_account_in_access_list(&beneficiary);
+        state.push_op_reversible(
+            &mut exec_step,
+            RW::WRITE,
+            TxAccessListAccountOp {
+                tx_id,
+                address: beneficiary,
+                is_warm: true,
+                is_warm_prev: is_warm,
+            },
+        )?;
+
+        let (_, beneficiary_account) = state.sdb.get_account(&beneficiary);
+        if beneficiary_account.is_empty() {
+            state.account_read(
+                &mut exec_step,
+                beneficiary,
+                AccountField::NonExisting,
+                Word::zero(),
+                Word::zero(),
+            )?;
+        } else {
+            let code_hash = beneficiary_account.code_hash.to_word();
+            state.account_read(
+                &mut exec_step,
+                beneficiary,
+                AccountField::CodeHash,
+                code_hash,
+                code_hash,
+            )?;
+        }
+
+        // The beneficiary is credited before the current account is cleared, so
+        // the balance is burnt when the beneficiary is the current account
+        // itself.
+        let (found, account) = state.sdb.get_account(&call.address);
+        if !found {
+            return Err(Error::AccountNotFound(call.address));
+        }
+        let value = account.balance;
+        let (_, beneficiary_account) = state.sdb.get_account(&beneficiary);
+        let beneficiary_balance_prev = beneficiary_account.balance;
+        state.push_op_reversible(
+            &mut exec_step,
+            RW::WRITE,
+            AccountOp {
+                address: beneficiary,
+                field: AccountField::Balance,
+                value: beneficiary_balance_prev + value,
+                value_prev: beneficiary_balance_prev,
+            },
+        )?;
+        let balance_prev = state.sdb.get_account(&call.address).1.balance;
+        state.push_op_reversible(
+            &mut exec_step,
+            RW::WRITE,
+            AccountOp {
+                address: call.address,
+                field: AccountField::Balance,
+                value: Word::zero(),
+                value_prev: balance_prev,
+            },
+        )?;
+
+        let is_destructed = state.sdb.check_account_destructed(&call.address);
+        state.push_op_reversible(
+            &mut exec_step,
+            RW::WRITE,
+            AccountDestructedOp {
+                tx_id,
+                address: call.address,
+                is_destructed: true,
+                is_destructed_prev: is_destructed,
+            },
+        )?;
+
+        let selfdestruct_refund = state
+            .block
+            .circuits_params
+            .hardfork
+            .selfdestruct_refund()
+            .as_u64();
+        if selfdestruct_refund > 0 {
+            let refund_prev = state.sdb.refund();
+            state.push_op_reversible(
+                &mut exec_step,
+                RW::WRITE,
+                TxRefundOp {
+                    tx_id,
+                    value_prev: refund_prev,
+                    value: if is_destructed {
+                        refund_prev
+                    } else {
+                        refund_prev + selfdestruct_refund
+                    },
+                },
+            )?;
+        }
+
+        if !call.is_root {
+            state.handle_restore_context(geth_steps, &mut exec_step)?;
+        }
+
+        state.handle_return(geth_step)?;
+        Ok(vec![exec_step])
+    }
+}
+
+#[cfg(test)]
+mod selfdestruct_tests {
+    use super::*;
+    use crate::{circuit_input_builder::ExecState, mock::BlockData, operation::StackOp};
+    use eth_types::{
+        bytecode,
+        evm_types::{OpcodeId, StackAddress},
+        geth_types::GethData,
+        Address,
+    };
+    use mock::{
+        eth,
+        test_ctx::{helpers::*, TestContext},
+    };
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn selfdestruct_opcode_impl() {
+        let beneficiary = Address::repeat_byte(0xbe);
+        let code = bytecode! {
+            PUSH20(beneficiary.to_word())
+            SELFDESTRUCT
+        };
+
+        // Get the execution steps from the external tracer
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(Address::repeat_byte(0xff))
+                    .balance(eth(1))
+                    .code(code);
+                accs[1].address(Address::repeat_byte(0xfe)).balance(eth(10));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[1].address);
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let step = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::SELFDESTRUCT))
+            .unwrap();
+
+        let container = &builder.block.container;
+        let op = |idx: usize| step.bus_mapping_instance[idx].as_usize();
+
+        assert_eq!(
+            {
+                let operation = &container.stack[op(5)];
+                (operation.rw(), operation.op())
+            },
+            (
+                RW::READ,
+                &StackOp::new(1, StackAddress::from(1023), beneficiary.to_word())
+            )
+        );
+        assert_eq!(
+            {
+                let operation = &container.tx_access_list_account[op(6)];
+                (operation.rw(), operation.op())
+            },
+            (
+                RW::WRITE,
+                &TxAccessListAccountOp {
+                    tx_id: 1,
+                    address: beneficiary,
+                    is_warm: true,
+                    is_warm_prev: false,
+                }
+            )
+        );
+        assert_eq!(
+            {
+                let operation = &container.account[op(7)];
+                (operation.rw(), operation.op())
+            },
+            (
+                RW::READ,
+                &AccountOp {
+                    address: beneficiary,
+                    field: AccountField::NonExisting,
+                    value: Word::zero(),
+                    value_prev: Word::zero(),
+                }
+            )
+        );
+        assert_eq!(
+            [op(8), op(9)].map(|idx| {
+                let operation = &container.account[idx];
+                (operation.rw(), operation.op().clone())
+            }),
+            [
+                (
+                    RW::WRITE,
+                    AccountOp {
+                        address: beneficiary,
+                        field: AccountField::Balance,
+                        value: eth(1),
+                        value_prev: Word::zero(),
+                    }
+                ),
+                (
+                    RW::WRITE,
+                    AccountOp {
+                        address: Address::repeat_byte(0xff),
+                        field: AccountField::Balance,
+                        value: Word::zero(),
+                        value_prev: eth(1),
+                    }
+                ),
+            ]
+        );
+        assert_eq!(
+            {
+                let operation = &container.account_destructed[op(10)];
+                (operation.rw(), operation.op())
+            },
+            (
+                RW::WRITE,
+                &AccountDestructedOp {
+                    tx_id: 1,
+                    address: Address::repeat_byte(0xff),
+                    is_destructed: true,
+                    is_destructed_prev: false,
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn selfdestruct_reverted() {
+        let callee = Address::repeat_byte(0xcc);
+        let callee_code = bytecode! {
+            PUSH20(Address::repeat_byte(0xbe).to_word())
+            SELFDESTRUCT
+        };
+        // Call the callee, and then revert the caller so the destruction is
+        // reverted too.
+        let caller_code = bytecode! {
+            PUSH1(0) // retLength
+            PUSH1(0) // retOffset
+            PUSH1(0) // argsLength
+            PUSH1(0) // argsOffset
+            PUSH1(0) // value
+            PUSH20(callee.to_word()) // address
+            PUSH2(0xffff) // gas
+            CALL
+            PUSH1(0)
+            PUSH1(0)
+            REVERT
+        };
+
+        let block: GethData = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(Address::repeat_byte(0xff))
+                    .code(caller_code);
+                accs[1].address(callee).balance(eth(1)).code(callee_code);
+                accs[2].address(Address::repeat_byte(0xfe)).balance(eth(10));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[2].address);
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        // The destruction is written once by SELFDESTRUCT and once more when the
+        // caller reverts, which also gives the balance back to the callee.
+        let account_destructed = &builder.block.container.account_destructed;
+        assert_eq!(account_destructed.len(), 2);
+        assert!(account_destructed[0].op().is_destructed);
+        assert!(!account_destructed[1].op().is_destructed);
+        assert_eq!(builder.sdb.get_account(&callee).1.balance, eth(1));
+    }
+}
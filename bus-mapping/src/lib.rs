@@ -236,4 +236,7 @@ pub mod mock;
 pub mod operation;
 pub mod rpc;
 pub mod state_db;
+pub mod state_provider;
+
 pub use error::Error;
+pub use eth_types::precompile;
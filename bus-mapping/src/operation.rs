@@ -795,8 +795,11 @@ pub enum TxLogField {
     Topic,
     /// data of log entry
     Data,
-    /* TODO: Add `TopicLength` and `DataLength`, which will be used for the RLP encoding of the
-     * Tx Receipt */
+    /// number of topics of log entry, used for the RLP encoding of the receipt
+    TopicLength,
+    /// length of the data of log entry, used for the RLP encoding of the
+    /// receipt
+    DataLength,
 }
 
 /// Represents TxLog read/write operation.
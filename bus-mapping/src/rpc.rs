@@ -1,14 +1,19 @@
 //! Module which contains all the RPC calls that are needed at any point to
 //! query a Geth node in order to get a Block, Tx or Trace info.
 
+use crate::state_provider::{StateProvider, StateProviderError};
 use crate::Error;
+use async_trait::async_trait;
 use eth_types::{
     Address, Block, Bytes, EIP1186ProofResponse, GethExecTrace, Hash, ResultGethExecTraces,
-    Transaction, Word, U64,
+    Transaction, Word, H256, U64,
 };
 pub use ethers_core::types::BlockNumber;
 use ethers_providers::JsonRpcClient;
-use serde::Serialize;
+use futures::{stream, StreamExt, TryStreamExt};
+use reqwest::Url;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
 
 /// Serialize a type.
 ///
@@ -185,4 +190,205 @@ impl<P: JsonRpcClient> GethClient<P> {
     }
 }
 
+#[derive(Serialize)]
+struct JsonRpcRequest<'a, T> {
+    jsonrpc: &'static str,
+    id: u64,
+    method: &'a str,
+    params: &'a T,
+}
+
+#[derive(Deserialize)]
+struct JsonRpcResponse {
+    id: u64,
+    result: Option<serde_json::Value>,
+    error: Option<JsonRpcErrorObject>,
+}
+
+#[derive(Deserialize)]
+struct JsonRpcErrorObject {
+    code: i64,
+    message: String,
+}
+
+/// [`StateProvider`] which queries a Geth node over HTTP.  The requests for
+/// several items are sent as JSON-RPC batches, several of which are in flight
+/// at the same time.
+#[derive(Debug)]
+pub struct GethStateProvider {
+    client: reqwest::Client,
+    url: Url,
+    next_id: AtomicU64,
+    max_batch_size: usize,
+    max_concurrency: usize,
+}
+
+impl GethStateProvider {
+    /// Default maximum number of requests in a batch.
+    pub const DEFAULT_MAX_BATCH_SIZE: usize = 100;
+    /// Default maximum number of batches in flight.
+    pub const DEFAULT_MAX_CONCURRENCY: usize = 8;
+
+    /// Generates a new `GethStateProvider` querying the node at `url`.
+    pub fn new(url: Url) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url,
+            next_id: AtomicU64::new(0),
+            max_batch_size: Self::DEFAULT_MAX_BATCH_SIZE,
+            max_concurrency: Self::DEFAULT_MAX_CONCURRENCY,
+        }
+    }
+
+    /// Sets the maximum number of requests in a batch and of batches in
+    /// flight.
+    pub fn with_limits(mut self, max_batch_size: usize, max_concurrency: usize) -> Self {
+        assert!(max_batch_size > 0 && max_concurrency > 0);
+        self.max_batch_size = max_batch_size;
+        self.max_concurrency = max_concurrency;
+        self
+    }
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, StateProviderError>
+    where
+        T: Serialize + Send + Sync,
+        R: DeserializeOwned,
+    {
+        let mut results = self.send_batch(method, &[params]).await?;
+        Ok(results.remove(0))
+    }
+
+    /// Calls `method` once for each of the `params`, returning the results in
+    /// the same order.
+    async fn request_batch<T, R>(
+        &self,
+        method: &str,
+        params: &[T],
+    ) -> Result<Vec<R>, StateProviderError>
+    where
+        T: Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        stream::iter(params.chunks(self.max_batch_size))
+            .map(|batch| self.send_batch(method, batch))
+            .buffered(self.max_concurrency)
+            .try_concat()
+            .await
+    }
+
+    async fn send_batch<T, R>(
+        &self,
+        method: &str,
+        params: &[T],
+    ) -> Result<Vec<R>, StateProviderError>
+    where
+        T: Serialize + Send + Sync,
+        R: DeserializeOwned,
+    {
+        let first_id = self
+            .next_id
+            .fetch_add(params.len() as u64, Ordering::Relaxed);
+        let requests: Vec<_> = params
+            .iter()
+            .enumerate()
+            .map(|(index, params)| JsonRpcRequest {
+                jsonrpc: "2.0",
+                id: first_id + index as u64,
+                method,
+                params,
+            })
+            .collect();
+        let responses: Vec<JsonRpcResponse> = self
+            .client
+            .post(self.url.clone())
+            .json(&requests)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        let mut results = vec![None; params.len()];
+        for response in responses {
+            if let Some(error) = response.error {
+                return Err(StateProviderError::JsonRpc {
+                    code: error.code,
+                    message: error.message,
+                });
+            }
+            let result = response
+                .id
+                .checked_sub(first_id)
+                .and_then(|index| results.get_mut(index as usize))
+                .ok_or(StateProviderError::UnexpectedResponse(response.id))?;
+            *result = Some(response.result.unwrap_or_default());
+        }
+        results
+            .into_iter()
+            .enumerate()
+            .map(|(index, result)| {
+                let result =
+                    result.ok_or(StateProviderError::MissingResponse(first_id + index as u64))?;
+                Ok(serde_json::from_value(result)?)
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl StateProvider for GethStateProvider {
+    async fn chain_id(&self) -> Result<u64, StateProviderError> {
+        let chain_id: U64 = self.request("eth_chainId", ()).await?;
+        Ok(chain_id.as_u64())
+    }
+
+    async fn block(&self, block_num: u64) -> Result<Block<Transaction>, StateProviderError> {
+        self.request("eth_getBlockByNumber", (BlockNumber::from(block_num), true))
+            .await
+    }
+
+    async fn traces(&self, block_num: u64) -> Result<Vec<GethExecTrace>, StateProviderError> {
+        let resp: ResultGethExecTraces = self
+            .request(
+                "debug_traceBlockByNumber",
+                (BlockNumber::from(block_num), GethLoggerConfig::default()),
+            )
+            .await?;
+        Ok(resp.0.into_iter().map(|step| step.result).collect())
+    }
+
+    async fn headers(&self, block_nums: &[u64]) -> Result<Vec<Block<H256>>, StateProviderError> {
+        let params: Vec<_> = block_nums
+            .iter()
+            .map(|block_num| (BlockNumber::from(*block_num), false))
+            .collect();
+        self.request_batch("eth_getBlockByNumber", &params).await
+    }
+
+    async fn proofs(
+        &self,
+        block_num: u64,
+        accounts: &[(Address, Vec<Word>)],
+    ) -> Result<Vec<EIP1186ProofResponse>, StateProviderError> {
+        let params: Vec<_> = accounts
+            .iter()
+            .map(|(address, keys)| (address, keys, BlockNumber::from(block_num)))
+            .collect();
+        self.request_batch("eth_getProof", &params).await
+    }
+
+    async fn codes(
+        &self,
+        block_num: u64,
+        addresses: &[Address],
+    ) -> Result<Vec<Vec<u8>>, StateProviderError> {
+        let params: Vec<_> = addresses
+            .iter()
+            .map(|address| (address, BlockNumber::from(block_num)))
+            .collect();
+        let codes: Vec<Bytes> = self.request_batch("eth_getCode", &params).await?;
+        Ok(codes.into_iter().map(|code| code.to_vec()).collect())
+    }
+}
+
 // Integration tests found in `integration-tests/tests/rpc.rs`.
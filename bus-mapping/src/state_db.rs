@@ -225,9 +225,23 @@ impl StateDB {
         debug_assert!(exist);
     }
 
-    /// Set account as self destructed.
-    pub fn destruct_account(&mut self, addr: Address) {
-        self.destructed_account.insert(addr);
+    /// Check whether `addr` has been self destructed in the current
+    /// transaction.
+    pub fn check_account_destructed(&self, addr: &Address) -> bool {
+        self.destructed_account.contains(addr)
+    }
+
+    /// Set account as self destructed. Returns `true` if it's not destructed
+    /// before.
+    pub fn destruct_account(&mut self, addr: Address) -> bool {
+        self.destructed_account.insert(addr)
+    }
+
+    /// Unset account as self destructed, used when the `SELFDESTRUCT` is
+    /// reverted.
+    pub fn undestruct_account(&mut self, addr: &Address) {
+        let exist = self.destructed_account.remove(addr);
+        debug_assert!(exist);
     }
 
     /// Retrieve refund.
@@ -240,7 +254,8 @@ impl StateDB {
         self.refund = value;
     }
 
-    /// Clear access list and refund, and commit dirty storage.
+    /// Clear access list and refund, commit dirty storage and reset the
+    /// destructed accounts.
     /// It should be invoked before processing
     /// with new transaction with the same [`StateDB`].
     pub fn commit_tx(&mut self) {
@@ -255,6 +270,7 @@ impl StateDB {
             let (_, account) = self.get_account_mut(&addr);
             *account = ACCOUNT_ZERO.clone();
         }
+        self.destructed_account = HashSet::new();
         self.refund = 0;
     }
 }
@@ -0,0 +1,297 @@
+//! Module which contains the [`StateProvider`] trait, which abstracts the
+//! source of the chain data needed to build the circuit inputs of a block, and
+//! a provider reading that data from a directory of JSON fixtures.
+
+use async_trait::async_trait;
+use eth_types::{
+    Address, Block, Bytes, EIP1186ProofResponse, GethExecTrace, Transaction, Word, H256,
+};
+use serde::de::DeserializeOwned;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::path::{Path, PathBuf};
+
+/// Error returned by a [`StateProvider`].
+#[derive(Debug)]
+pub enum StateProviderError {
+    /// HTTP transport error.
+    Http(reqwest::Error),
+    /// Error object returned by a JSON-RPC call.
+    JsonRpc {
+        /// Error code
+        code: i64,
+        /// Error message
+        message: String,
+    },
+    /// JSON-RPC batch response without the response of a request.
+    MissingResponse(u64),
+    /// JSON-RPC response to an unknown request.
+    UnexpectedResponse(u64),
+    /// I/O error when reading a fixture.
+    Io(std::io::Error),
+    /// Response or fixture which doesn't deserialize to the expected type.
+    Serde(serde_json::Error),
+    /// Ancestor header whose hash isn't the parent hash of the next block.
+    HeaderMismatch(u64),
+}
+
+impl From<reqwest::Error> for StateProviderError {
+    fn from(err: reqwest::Error) -> Self {
+        StateProviderError::Http(err)
+    }
+}
+
+impl From<std::io::Error> for StateProviderError {
+    fn from(err: std::io::Error) -> Self {
+        StateProviderError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for StateProviderError {
+    fn from(err: serde_json::Error) -> Self {
+        StateProviderError::Serde(err)
+    }
+}
+
+impl Display for StateProviderError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl std::error::Error for StateProviderError {}
+
+/// Source of the blocks, traces and state needed by the
+/// [`BuilderClient`](crate::circuit_input_builder::BuilderClient).  The
+/// methods querying several items return them in the order of the query.
+#[async_trait]
+pub trait StateProvider: Send + Sync {
+    /// Returns the chain id.
+    async fn chain_id(&self) -> Result<u64, StateProviderError>;
+
+    /// Returns a block, including the details of its transactions.
+    async fn block(&self, block_num: u64) -> Result<Block<Transaction>, StateProviderError>;
+
+    /// Returns the execution traces of the transactions of a block.
+    async fn traces(&self, block_num: u64) -> Result<Vec<GethExecTrace>, StateProviderError>;
+
+    /// Returns the headers of several blocks, with the hashes of their
+    /// transactions.
+    async fn headers(&self, block_nums: &[u64]) -> Result<Vec<Block<H256>>, StateProviderError>;
+
+    /// Returns the proofs of accounts and of their storage keys in the state
+    /// after a block.
+    async fn proofs(
+        &self,
+        block_num: u64,
+        accounts: &[(Address, Vec<Word>)],
+    ) -> Result<Vec<EIP1186ProofResponse>, StateProviderError>;
+
+    /// Returns the codes of accounts in the state after a block.
+    async fn codes(
+        &self,
+        block_num: u64,
+        addresses: &[Address],
+    ) -> Result<Vec<Vec<u8>>, StateProviderError>;
+}
+
+/// [`StateProvider`] which reads the JSON-RPC responses of a node from a
+/// directory, so that the circuit inputs can be built without a node.  The
+/// directory contains the files:
+///   - `chain_id.json`
+///   - `block_<num>.json`, with the details of the transactions
+///   - `traces_<num>.json`
+///   - `header_<num>.json`, with the hashes of the transactions
+///   - `proof_<num>_<address>.json`
+///   - `code_<num>_<address>.json`
+#[derive(Clone, Debug)]
+pub struct FixtureStateProvider {
+    dir: PathBuf,
+}
+
+impl FixtureStateProvider {
+    /// Creates a provider reading the fixtures from `dir`.
+    pub fn new(dir: impl AsRef<Path>) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+        }
+    }
+
+    fn read<T: DeserializeOwned>(&self, name: String) -> Result<T, StateProviderError> {
+        let file = std::fs::File::open(self.dir.join(name))?;
+        Ok(serde_json::from_reader(std::io::BufReader::new(file))?)
+    }
+}
+
+#[async_trait]
+impl StateProvider for FixtureStateProvider {
+    async fn chain_id(&self) -> Result<u64, StateProviderError> {
+        self.read("chain_id.json".to_string())
+    }
+
+    async fn block(&self, block_num: u64) -> Result<Block<Transaction>, StateProviderError> {
+        self.read(format!("block_{}.json", block_num))
+    }
+
+    async fn traces(&self, block_num: u64) -> Result<Vec<GethExecTrace>, StateProviderError> {
+        self.read(format!("traces_{}.json", block_num))
+    }
+
+    async fn headers(&self, block_nums: &[u64]) -> Result<Vec<Block<H256>>, StateProviderError> {
+        block_nums
+            .iter()
+            .map(|block_num| self.read(format!("header_{}.json", block_num)))
+            .collect()
+    }
+
+    async fn proofs(
+        &self,
+        block_num: u64,
+        accounts: &[(Address, Vec<Word>)],
+    ) -> Result<Vec<EIP1186ProofResponse>, StateProviderError> {
+        accounts
+            .iter()
+            .map(|(address, _)| self.read(format!("proof_{}_{:?}.json", block_num, address)))
+            .collect()
+    }
+
+    async fn codes(
+        &self,
+        block_num: u64,
+        addresses: &[Address],
+    ) -> Result<Vec<Vec<u8>>, StateProviderError> {
+        addresses
+            .iter()
+            .map(|address| {
+                self.read::<Bytes>(format!("code_{}_{:?}.json", block_num, address))
+                    .map(|code| code.to_vec())
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit_input_builder::{AccessSet, BuilderClient, CircuitsParams};
+    use crate::Error;
+    use eth_types::{ToWord, U64};
+    use futures::executor::block_on;
+    use serde::Serialize;
+    use std::collections::{HashMap, HashSet};
+
+    fn fixture_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "bus-mapping-fixtures-{}-{}",
+            name,
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write<T: Serialize>(dir: &Path, name: &str, value: &T) {
+        std::fs::write(dir.join(name), serde_json::to_vec(value).unwrap()).unwrap();
+    }
+
+    /// Writes the fixtures of block 3 and of its ancestors.
+    fn write_chain(dir: &Path) {
+        write(dir, "chain_id.json", &1337u64);
+        for num in 0..3u64 {
+            let header = Block::<H256> {
+                number: Some(U64::from(num)),
+                hash: Some(H256::from_low_u64_be(num + 1)),
+                parent_hash: H256::from_low_u64_be(num),
+                state_root: H256::from_low_u64_be(0x100 + num),
+                ..Default::default()
+            };
+            write(dir, &format!("header_{}.json", num), &header);
+        }
+        let block = Block::<Transaction> {
+            number: Some(U64::from(3)),
+            hash: Some(H256::from_low_u64_be(4)),
+            parent_hash: H256::from_low_u64_be(3),
+            ..Default::default()
+        };
+        write(dir, "block_3.json", &block);
+        write(dir, "traces_3.json", &Vec::<GethExecTrace>::new());
+    }
+
+    #[test]
+    fn fixture_block() {
+        let dir = fixture_dir("block");
+        write_chain(&dir);
+        let cli = block_on(BuilderClient::new(
+            FixtureStateProvider::new(&dir),
+            CircuitsParams::default(),
+        ))
+        .unwrap();
+
+        let (eth_block, geth_traces, history_hashes, prev_state_root) =
+            block_on(cli.get_block(3)).unwrap();
+        assert_eq!(eth_block.number, Some(U64::from(3)));
+        assert!(geth_traces.is_empty());
+        assert_eq!(
+            history_hashes,
+            (1..4)
+                .map(|hash| H256::from_low_u64_be(hash).to_word())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(prev_state_root, H256::from_low_u64_be(0x102).to_word());
+
+        // header of block 1 which isn't the parent of block 2
+        let header = Block::<H256> {
+            hash: Some(H256::from_low_u64_be(0xbad)),
+            ..Default::default()
+        };
+        write(&dir, "header_1.json", &header);
+        assert!(matches!(
+            block_on(cli.get_block(3)),
+            Err(Error::StateProviderError(
+                StateProviderError::HeaderMismatch(1)
+            ))
+        ));
+        assert!(matches!(
+            block_on(cli.get_block(5)),
+            Err(Error::StateProviderError(StateProviderError::Io(_)))
+        ));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn fixture_state() {
+        let dir = fixture_dir("state");
+        write_chain(&dir);
+        let address = Address::repeat_byte(0xfe);
+        let proof = serde_json::json!({
+            "address": address,
+            "balance": "0x0",
+            "codeHash": H256::zero(),
+            "nonce": "0x1",
+            "storageHash": H256::zero(),
+            "accountProof": [],
+            "storageProof": [],
+        });
+        write(&dir, &format!("proof_2_{:?}.json", address), &proof);
+        write(
+            &dir,
+            &format!("code_2_{:?}.json", address),
+            &Bytes::from(vec![0x60, 0x00]),
+        );
+        let cli = block_on(BuilderClient::new(
+            FixtureStateProvider::new(&dir),
+            CircuitsParams::default(),
+        ))
+        .unwrap();
+
+        let access_set = AccessSet {
+            state: HashMap::from([(address, HashSet::from([Word::from(1)]))]),
+            code: HashSet::from([address]),
+        };
+        let (proofs, codes) = block_on(cli.get_state(3, access_set)).unwrap();
+        assert_eq!(proofs.len(), 1);
+        assert_eq!(proofs[0].address, address);
+        assert_eq!(proofs[0].nonce, Word::from(1));
+        assert_eq!(codes, HashMap::from([(address, vec![0x60, 0x00])]));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
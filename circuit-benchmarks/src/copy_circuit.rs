@@ -7,17 +7,7 @@ mod tests {
     use bus_mapping::mock::BlockData;
     use eth_types::geth_types::GethData;
     use eth_types::{bytecode, Word};
-    use halo2_proofs::plonk::{create_proof, keygen_pk, keygen_vk, verify_proof};
-    use halo2_proofs::poly::kzg::commitment::{KZGCommitmentScheme, ParamsKZG, ParamsVerifierKZG};
-    use halo2_proofs::poly::kzg::multiopen::{ProverSHPLONK, VerifierSHPLONK};
-    use halo2_proofs::poly::kzg::strategy::SingleStrategy;
-    use halo2_proofs::{
-        halo2curves::bn256::{Bn256, Fr, G1Affine},
-        poly::commitment::ParamsProver,
-        transcript::{
-            Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
-        },
-    };
+    use halo2_proofs::halo2curves::bn256::Fr;
     use mock::test_ctx::helpers::*;
     use mock::test_ctx::TestContext;
     use rand::SeedableRng;
@@ -25,6 +15,7 @@ mod tests {
     use std::env::var;
     use zkevm_circuits::copy_circuit::CopyCircuit;
     use zkevm_circuits::evm_circuit::witness::{block_convert, Block};
+    use zkevm_circuits::prover;
 
     #[cfg_attr(not(feature = "benches"), ignore)]
     #[test]
@@ -47,50 +38,24 @@ mod tests {
         // Bench setup generation
         let setup_message = format!("Setup generation with degree = {}", degree);
         let start1 = start_timer!(|| setup_message);
-        let general_params = ParamsKZG::<Bn256>::setup(degree, &mut rng);
-        let verifier_params: ParamsVerifierKZG<Bn256> = general_params.verifier_params().clone();
+        let general_params = prover::gen_params(degree, &mut rng);
+        let verifier_params = prover::verifier_params(&general_params);
         end_timer!(start1);
 
         // Initialize the proving key
-        let vk = keygen_vk(&general_params, &circuit).expect("keygen_vk should not fail");
-        let pk = keygen_pk(&general_params, vk, &circuit).expect("keygen_pk should not fail");
-        // Create a proof
-        let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+        let pk = prover::gen_pk(&general_params, &circuit).expect("keygen should not fail");
 
         // Bench proof generation time
         let proof_message = format!("Copy_circuit Proof generation with {} rows", degree);
         let start2 = start_timer!(|| proof_message);
-        create_proof::<
-            KZGCommitmentScheme<Bn256>,
-            ProverSHPLONK<'_, Bn256>,
-            Challenge255<G1Affine>,
-            XorShiftRng,
-            Blake2bWrite<Vec<u8>, G1Affine, Challenge255<G1Affine>>,
-            CopyCircuit<Fr>,
-        >(&general_params, &pk, &[circuit], &[], rng, &mut transcript)
-        .expect("proof generation should not fail");
-        let proof = transcript.finalize();
+        let proof = prover::create_proof(&general_params, &pk, circuit, &[], rng)
+            .expect("proof generation should not fail");
         end_timer!(start2);
 
         // Bench verification time
         let start3 = start_timer!(|| "Copy_circuit Proof verification");
-        let mut verifier_transcript = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(&proof[..]);
-        let strategy = SingleStrategy::new(&general_params);
-
-        verify_proof::<
-            KZGCommitmentScheme<Bn256>,
-            VerifierSHPLONK<'_, Bn256>,
-            Challenge255<G1Affine>,
-            Blake2bRead<&[u8], G1Affine, Challenge255<G1Affine>>,
-            SingleStrategy<'_, Bn256>,
-        >(
-            &verifier_params,
-            pk.get_vk(),
-            strategy,
-            &[],
-            &mut verifier_transcript,
-        )
-        .expect("failed to verify bench circuit");
+        prover::verify_proof(&verifier_params, pk.get_vk(), &proof, &[])
+            .expect("failed to verify bench circuit");
         end_timer!(start3);
     }
 
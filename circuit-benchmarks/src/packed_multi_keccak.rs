@@ -3,21 +3,13 @@
 #[cfg(test)]
 mod tests {
     use ark_std::{end_timer, start_timer};
-    use halo2_proofs::plonk::{create_proof, keygen_pk, keygen_vk, verify_proof};
-    use halo2_proofs::poly::kzg::commitment::{KZGCommitmentScheme, ParamsKZG, ParamsVerifierKZG};
-    use halo2_proofs::poly::kzg::multiopen::{ProverSHPLONK, VerifierSHPLONK};
-    use halo2_proofs::poly::kzg::strategy::SingleStrategy;
-    use halo2_proofs::{
-        halo2curves::bn256::{Bn256, Fr, G1Affine},
-        poly::commitment::ParamsProver,
-        transcript::{
-            Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
-        },
-    };
+    use bus_mapping::circuit_input_builder::CircuitsParams;
+    use halo2_proofs::halo2curves::bn256::Fr;
     use rand::SeedableRng;
     use rand_xorshift::XorShiftRng;
     use std::env::var;
     use zkevm_circuits::keccak_circuit::keccak_packed_multi::KeccakCircuit;
+    use zkevm_circuits::prover::{self, KeyCache};
 
     #[cfg_attr(not(feature = "benches"), ignore)]
     #[test]
@@ -39,18 +31,23 @@ mod tests {
             0xbc, 0xe5,
         ]);
 
+        // The parameters and keys are cached across runs
+        let key_cache = KeyCache::from_env();
+        let circuits_params = CircuitsParams::default();
+
         // Bench setup generation
         let setup_message = format!("Setup generation with degree = {}", degree);
         let start1 = start_timer!(|| setup_message);
-        let general_params = ParamsKZG::<Bn256>::setup(degree, &mut rng);
-        let verifier_params: ParamsVerifierKZG<Bn256> = general_params.verifier_params().clone();
+        let general_params = key_cache
+            .load_params(degree, &mut rng)
+            .expect("failed to load params");
+        let verifier_params = prover::verifier_params(&general_params);
         end_timer!(start1);
 
         // Initialize the proving key
-        let vk = keygen_vk(&general_params, &circuit).expect("keygen_vk should not fail");
-        let pk = keygen_pk(&general_params, vk, &circuit).expect("keygen_pk should not fail");
-        // Create a proof
-        let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+        let pk = key_cache
+            .load_pk(&general_params, &circuits_params, &circuit)
+            .expect("keygen should not fail");
 
         // Bench proof generation time
         let proof_message = format!(
@@ -58,44 +55,14 @@ mod tests {
             degree
         );
         let start2 = start_timer!(|| proof_message);
-        create_proof::<
-            KZGCommitmentScheme<Bn256>,
-            ProverSHPLONK<'_, Bn256>,
-            Challenge255<G1Affine>,
-            XorShiftRng,
-            Blake2bWrite<Vec<u8>, G1Affine, Challenge255<G1Affine>>,
-            KeccakCircuit<Fr>,
-        >(
-            &general_params,
-            &pk,
-            &[circuit],
-            &[&[]],
-            rng,
-            &mut transcript,
-        )
-        .expect("proof generation should not fail");
-        let proof = transcript.finalize();
+        let proof = prover::create_proof(&general_params, &pk, circuit, &[], rng)
+            .expect("proof generation should not fail");
         end_timer!(start2);
 
         // Bench verification time
         let start3 = start_timer!(|| "Packed Multi-Keccak Proof verification");
-        let mut verifier_transcript = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(&proof[..]);
-        let strategy = SingleStrategy::new(&general_params);
-
-        verify_proof::<
-            KZGCommitmentScheme<Bn256>,
-            VerifierSHPLONK<'_, Bn256>,
-            Challenge255<G1Affine>,
-            Blake2bRead<&[u8], G1Affine, Challenge255<G1Affine>>,
-            SingleStrategy<'_, Bn256>,
-        >(
-            &verifier_params,
-            pk.get_vk(),
-            strategy,
-            &[&[]],
-            &mut verifier_transcript,
-        )
-        .expect("failed to verify bench circuit");
+        prover::verify_proof(&verifier_params, pk.get_vk(), &proof, &[])
+            .expect("failed to verify bench circuit");
         end_timer!(start3);
     }
 }
@@ -2,26 +2,19 @@
 #[cfg(test)]
 mod tests {
     use ark_std::{end_timer, start_timer};
+    use bus_mapping::circuit_input_builder::CircuitsParams;
     use eth_types::Word;
     use halo2_proofs::arithmetic::Field;
-    use halo2_proofs::plonk::{create_proof, keygen_pk, keygen_vk, verify_proof};
-    use halo2_proofs::poly::kzg::commitment::{KZGCommitmentScheme, ParamsKZG, ParamsVerifierKZG};
-    use halo2_proofs::poly::kzg::multiopen::{ProverSHPLONK, VerifierSHPLONK};
-    use halo2_proofs::poly::kzg::strategy::SingleStrategy;
-    use halo2_proofs::{
-        halo2curves::bn256::{Bn256, Fr, G1Affine},
-        poly::commitment::ParamsProver,
-        transcript::{
-            Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
-        },
-    };
+    use halo2_proofs::halo2curves::bn256::Fr;
     use rand::SeedableRng;
     use rand_chacha::ChaCha20Rng;
     use rand_xorshift::XorShiftRng;
     use std::env::var;
     use zkevm_circuits::pi_circuit::{PiCircuit, PiTestCircuit, PublicData};
+    use zkevm_circuits::prover::{self, KeyCache};
     use zkevm_circuits::test_util::rand_tx;
     use zkevm_circuits::util::SubCircuit;
+    use zkevm_circuits::witness::Receipt;
 
     #[cfg_attr(not(feature = "benches"), ignore)]
     #[test]
@@ -33,6 +26,8 @@ mod tests {
 
         const MAX_TXS: usize = 10;
         const MAX_CALLDATA: usize = 128;
+        const MAX_ACCESS_LIST: usize = 0;
+        const MAX_RECEIPTS_LEN: usize = 512;
 
         let mut rng = ChaCha20Rng::seed_from_u64(2);
         let randomness = Fr::random(&mut rng);
@@ -41,73 +36,53 @@ mod tests {
         let circuit = PiTestCircuit::<Fr, MAX_TXS, MAX_CALLDATA>(PiCircuit::<Fr>::new(
             MAX_TXS,
             MAX_CALLDATA,
+            MAX_ACCESS_LIST,
+            MAX_RECEIPTS_LEN,
             randomness,
             rand_rpi,
             public_data,
         ));
         let public_inputs = circuit.0.instance();
-        let instance: Vec<&[Fr]> = public_inputs.iter().map(|input| &input[..]).collect();
-        let instances = &[&instance[..]][..];
 
         let mut rng = XorShiftRng::from_seed([
             0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
             0xbc, 0xe5,
         ]);
 
+        // The parameters and keys are cached across runs
+        let key_cache = KeyCache::from_env();
+        let circuits_params = CircuitsParams {
+            max_txs: MAX_TXS,
+            max_calldata: MAX_CALLDATA,
+            max_receipts_len: MAX_RECEIPTS_LEN,
+            ..Default::default()
+        };
+
         // Bench setup generation
         let setup_message = format!("Setup generation with degree = {}", degree);
         let start1 = start_timer!(|| setup_message);
-        let general_params = ParamsKZG::<Bn256>::setup(degree, &mut rng);
-        let verifier_params: ParamsVerifierKZG<Bn256> = general_params.verifier_params().clone();
+        let general_params = key_cache
+            .load_params(degree, &mut rng)
+            .expect("failed to load params");
+        let verifier_params = prover::verifier_params(&general_params);
         end_timer!(start1);
 
         // Initialize the proving key
-        let vk = keygen_vk(&general_params, &circuit).expect("keygen_vk should not fail");
-        let pk = keygen_pk(&general_params, vk, &circuit).expect("keygen_pk should not fail");
-        // Create a proof
-        let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+        let pk = key_cache
+            .load_pk(&general_params, &circuits_params, &circuit)
+            .expect("keygen should not fail");
 
         // Bench proof generation time
         let proof_message = format!("PI_circuit Proof generation with {} rows", degree);
         let start2 = start_timer!(|| proof_message);
-        create_proof::<
-            KZGCommitmentScheme<Bn256>,
-            ProverSHPLONK<'_, Bn256>,
-            Challenge255<G1Affine>,
-            XorShiftRng,
-            Blake2bWrite<Vec<u8>, G1Affine, Challenge255<G1Affine>>,
-            PiTestCircuit<Fr, MAX_TXS, MAX_CALLDATA>,
-        >(
-            &general_params,
-            &pk,
-            &[circuit],
-            instances,
-            rng,
-            &mut transcript,
-        )
-        .expect("proof generation should not fail");
-        let proof = transcript.finalize();
+        let proof = prover::create_proof(&general_params, &pk, circuit, &public_inputs, rng)
+            .expect("proof generation should not fail");
         end_timer!(start2);
 
         // Bench verification time
         let start3 = start_timer!(|| "PI_circuit Proof verification");
-        let mut verifier_transcript = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(&proof[..]);
-        let strategy = SingleStrategy::new(&general_params);
-
-        verify_proof::<
-            KZGCommitmentScheme<Bn256>,
-            VerifierSHPLONK<'_, Bn256>,
-            Challenge255<G1Affine>,
-            Blake2bRead<&[u8], G1Affine, Challenge255<G1Affine>>,
-            SingleStrategy<'_, Bn256>,
-        >(
-            &verifier_params,
-            pk.get_vk(),
-            strategy,
-            instances,
-            &mut verifier_transcript,
-        )
-        .expect("failed to verify bench circuit");
+        prover::verify_proof(&verifier_params, pk.get_vk(), &proof, &public_inputs)
+            .expect("failed to verify bench circuit");
         end_timer!(start3);
     }
 
@@ -122,6 +97,16 @@ mod tests {
             let eth_tx = eth_types::Transaction::from(&rand_tx(&mut rng, chain_id, true));
             public_data.transactions.push(eth_tx);
         }
+        public_data.transactions_root = public_data.txs_commitment();
+        public_data.receipts = (1..=n_tx)
+            .map(|tx_id| Receipt {
+                tx_id,
+                status: true,
+                cumulative_gas_used: 21_000 * tx_id as u64,
+                ..Default::default()
+            })
+            .collect();
+        public_data.receipts_root = public_data.receipts_commitment();
         public_data
     }
 }
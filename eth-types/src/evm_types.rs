@@ -4,12 +4,14 @@ use serde::{Deserialize, Serialize};
 use std::fmt;
 
 pub mod gas_utils;
+pub mod hardfork;
 pub mod memory;
 pub mod opcode_ids;
 pub mod stack;
 pub mod storage;
 
 pub use {
+    hardfork::Hardfork,
     memory::{Memory, MemoryAddress},
     opcode_ids::OpcodeId,
     stack::{Stack, StackAddress},
@@ -62,10 +64,19 @@ impl fmt::Debug for Gas {
     }
 }
 
-/// Quotient for max refund of gas used
+/// Quotient for max refund of gas used, since
+/// [EIP-3529](https://eips.ethereum.org/EIPS/eip-3529).
 pub const MAX_REFUND_QUOTIENT_OF_GAS_USED: usize = 5;
+/// Quotient for max refund of gas used before EIP-3529.
+pub const MAX_REFUND_QUOTIENT_OF_GAS_USED_PRE_EIP3529: usize = 2;
 /// Gas stipend when CALL or CALLCODE is attached with value.
 pub const GAS_STIPEND_CALL_WITH_VALUE: u64 = 2300;
+/// Maximum size of the deployed code, as specified in
+/// [EIP-170](https://eips.ethereum.org/EIPS/eip-170).
+pub const MAX_CODE_SIZE: u64 = 0x6000;
+/// Maximum size of the initialization code, as specified in
+/// [EIP-3860](https://eips.ethereum.org/EIPS/eip-3860).
+pub const MAX_INIT_CODE_SIZE: u64 = 2 * MAX_CODE_SIZE;
 
 /// Defines the gas consumption.
 #[derive(Clone, Copy, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
@@ -105,6 +116,9 @@ impl GasCost {
     /// Constant cost for copying every word, specifically in the case of SHA3
     /// opcode.
     pub const COPY_SHA3: Self = Self(6);
+    /// Constant cost for every word of the initialization code of a creation
+    /// (EIP-3860)
+    pub const INIT_CODE_WORD: Self = Self(2);
     /// Constant cost for accessing account or storage key
     pub const WARM_ACCESS: Self = Self(100);
     /// Constant cost for a cold SLOAD
@@ -118,10 +132,23 @@ impl GasCost {
     /// Constant cost for a storage clear. EIP-3529 changed it to 4800 from
     /// 15000.
     pub const SSTORE_CLEARS_SCHEDULE: Self = Self(4800);
+    /// Constant cost for a storage clear before EIP-3529.
+    pub const SSTORE_CLEARS_SCHEDULE_PRE_EIP3529: Self = Self(15000);
+    /// Refund of a SELFDESTRUCT before EIP-3529, which removed it.
+    pub const SELFDESTRUCT_REFUND_PRE_EIP3529: Self = Self(24000);
+    /// Minimum gas left required by SSTORE, which fails when the gas left is
+    /// not more than this stipend (EIP-2200).
+    pub const SSTORE_SENTRY: Self = Self(2300);
     /// Constant cost for a non-creation transaction
     pub const TX: Self = Self(21000);
     /// Constant cost for a creation transaction
     pub const CREATION_TX: Self = Self(53000);
+    /// Constant cost for every address in the access list of a transaction
+    /// (EIP-2930)
+    pub const ACCESS_LIST_ADDRESS: Self = Self(2400);
+    /// Constant cost for every storage key in the access list of a
+    /// transaction (EIP-2930)
+    pub const ACCESS_LIST_STORAGE_KEY: Self = Self(1900);
     /// Constant cost for calling with non-zero value
     pub const CALL_WITH_VALUE: Self = Self(9000);
     /// Constant cost for turning empty account into non-empty account
@@ -134,6 +161,12 @@ impl GasCost {
     pub const MEMORY_EXPANSION_LINEAR_COEFF: Self = Self(3);
     /// Constant gas for LOG[0-4] op codes
     pub const LOG: Self = Self(375);
+    /// Constant gas for a call to the ecrecover precompiled contract
+    pub const PRECOMPILE_EC_RECOVER: Self = Self(3000);
+    /// Constant gas for a call to the identity precompiled contract
+    pub const PRECOMPILE_IDENTITY_BASE: Self = Self(15);
+    /// Gas per word of input for a call to the identity precompiled contract
+    pub const PRECOMPILE_IDENTITY_PER_WORD: Self = Self(3);
 }
 
 impl GasCost {
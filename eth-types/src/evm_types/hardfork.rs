@@ -0,0 +1,178 @@
+//! Hardforks whose rules can be used to execute and prove the blocks.
+
+use super::{
+    GasCost, OpcodeId, MAX_REFUND_QUOTIENT_OF_GAS_USED, MAX_REFUND_QUOTIENT_OF_GAS_USED_PRE_EIP3529,
+};
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+
+/// Hardfork of the chain, which selects the available opcodes and the gas
+/// schedule.  Only the forks since Berlin are supported, since the circuits
+/// always charge the cold and warm accesses of
+/// [EIP-2929](https://eips.ethereum.org/EIPS/eip-2929).
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub enum Hardfork {
+    /// Berlin
+    Berlin,
+    /// London, which adds the base fee with
+    /// [EIP-1559](https://eips.ethereum.org/EIPS/eip-1559) and reduces the
+    /// refunds with [EIP-3529](https://eips.ethereum.org/EIPS/eip-3529)
+    #[default]
+    London,
+    /// Shanghai, which adds `PUSH0` with
+    /// [EIP-3855](https://eips.ethereum.org/EIPS/eip-3855), warms the coinbase
+    /// with [EIP-3651](https://eips.ethereum.org/EIPS/eip-3651) and limits and
+    /// meters the initialization code with
+    /// [EIP-3860](https://eips.ethereum.org/EIPS/eip-3860)
+    Shanghai,
+}
+
+impl Hardfork {
+    /// Returns `true` if the opcode can be executed in the hardfork, otherwise
+    /// its execution fails with an invalid opcode error.
+    pub fn is_opcode_enabled(&self, opcode: OpcodeId) -> bool {
+        match opcode {
+            OpcodeId::INVALID(_) => false,
+            OpcodeId::BASEFEE => *self >= Self::London,
+            OpcodeId::PUSH0 => *self >= Self::Shanghai,
+            _ => true,
+        }
+    }
+
+    /// Returns `true` if the base fee of
+    /// [EIP-1559](https://eips.ethereum.org/EIPS/eip-1559) is burned.  Blocks
+    /// before London have no base fee, which is then zero.
+    pub fn is_eip1559_enabled(&self) -> bool {
+        *self >= Self::London
+    }
+
+    /// Returns `true` if the refunds are reduced by EIP-3529.
+    pub fn is_eip3529_enabled(&self) -> bool {
+        *self >= Self::London
+    }
+
+    /// Returns `true` if the code starting with 0xEF is rejected by
+    /// [EIP-3541](https://eips.ethereum.org/EIPS/eip-3541).
+    pub fn is_eip3541_enabled(&self) -> bool {
+        *self >= Self::London
+    }
+
+    /// Returns `true` if the coinbase is warm at the start of the transactions,
+    /// as specified by [EIP-3651](https://eips.ethereum.org/EIPS/eip-3651).
+    pub fn is_eip3651_enabled(&self) -> bool {
+        *self >= Self::Shanghai
+    }
+
+    /// Returns `true` if the size of the initialization code is limited to
+    /// [`MAX_INIT_CODE_SIZE`](super::MAX_INIT_CODE_SIZE) and each of its words
+    /// is charged [`GasCost::INIT_CODE_WORD`], as specified by
+    /// [EIP-3860](https://eips.ethereum.org/EIPS/eip-3860).
+    pub fn is_eip3860_enabled(&self) -> bool {
+        *self >= Self::Shanghai
+    }
+
+    /// Returns the gas charged for the initialization code of `size` bytes of
+    /// a creation, on top of the hashing of CREATE2.
+    pub fn init_code_gas_cost(&self, size: u64) -> u64 {
+        if self.is_eip3860_enabled() {
+            GasCost::INIT_CODE_WORD.as_u64() * ((size + 31) / 32)
+        } else {
+            0
+        }
+    }
+
+    /// Returns the quotient of the gas used which caps the refund of a
+    /// transaction.
+    pub fn max_refund_quotient(&self) -> u64 {
+        if self.is_eip3529_enabled() {
+            MAX_REFUND_QUOTIENT_OF_GAS_USED as u64
+        } else {
+            MAX_REFUND_QUOTIENT_OF_GAS_USED_PRE_EIP3529 as u64
+        }
+    }
+
+    /// Returns the refund of a SSTORE clearing a storage slot.
+    pub fn sstore_clears_schedule(&self) -> GasCost {
+        if self.is_eip3529_enabled() {
+            GasCost::SSTORE_CLEARS_SCHEDULE
+        } else {
+            GasCost::SSTORE_CLEARS_SCHEDULE_PRE_EIP3529
+        }
+    }
+
+    /// Returns the refund of a SELFDESTRUCT of an account which isn't
+    /// destructed yet in the transaction.
+    pub fn selfdestruct_refund(&self) -> GasCost {
+        if self.is_eip3529_enabled() {
+            GasCost::ZERO
+        } else {
+            GasCost::SELFDESTRUCT_REFUND_PRE_EIP3529
+        }
+    }
+}
+
+impl fmt::Display for Hardfork {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl FromStr for Hardfork {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "berlin" => Ok(Self::Berlin),
+            "london" => Ok(Self::London),
+            "shanghai" => Ok(Self::Shanghai),
+            _ => Err(format!("unsupported hardfork: {}", s)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod hardfork_tests {
+    use super::*;
+    use crate::evm_types::MAX_INIT_CODE_SIZE;
+
+    #[test]
+    fn push0() {
+        assert!(!Hardfork::London.is_opcode_enabled(OpcodeId::PUSH0));
+        assert!(Hardfork::Shanghai.is_opcode_enabled(OpcodeId::PUSH0));
+        assert!(Hardfork::Berlin.is_opcode_enabled(OpcodeId::SELFBALANCE));
+        assert!(!Hardfork::Berlin.is_opcode_enabled(OpcodeId::BASEFEE));
+        assert!(!Hardfork::Shanghai.is_opcode_enabled(OpcodeId::INVALID(0xfe)));
+    }
+
+    #[test]
+    fn refunds() {
+        assert_eq!(Hardfork::Berlin.max_refund_quotient(), 2);
+        assert_eq!(Hardfork::London.max_refund_quotient(), 5);
+        assert_eq!(Hardfork::Berlin.sstore_clears_schedule(), GasCost(15000));
+        assert_eq!(Hardfork::Shanghai.sstore_clears_schedule(), GasCost(4800));
+        assert_eq!(Hardfork::Berlin.selfdestruct_refund(), GasCost(24000));
+        assert_eq!(Hardfork::London.selfdestruct_refund(), GasCost::ZERO);
+    }
+
+    #[test]
+    fn shanghai() {
+        assert!(!Hardfork::London.is_eip3651_enabled());
+        assert!(Hardfork::Shanghai.is_eip3651_enabled());
+        assert_eq!(Hardfork::London.init_code_gas_cost(MAX_INIT_CODE_SIZE), 0);
+        assert_eq!(Hardfork::Shanghai.init_code_gas_cost(33), 4);
+        assert_eq!(
+            Hardfork::Shanghai.init_code_gas_cost(MAX_INIT_CODE_SIZE),
+            3072
+        );
+    }
+
+    #[test]
+    fn from_str() {
+        assert_eq!(Hardfork::from_str("Berlin"), Ok(Hardfork::Berlin));
+        assert_eq!(Hardfork::from_str("shanghai"), Ok(Hardfork::Shanghai));
+        assert!(Hardfork::from_str("Istanbul").is_err());
+        assert_eq!(Hardfork::London.to_string(), "London");
+    }
+}
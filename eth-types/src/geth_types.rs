@@ -2,22 +2,38 @@
 
 use crate::{
     sign_types::{biguint_to_32bytes_le, ct_option_ok_or, recover_pk, SignData, SECP256K1_Q},
-    AccessList, Address, Block, Bytes, Error, GethExecTrace, Hash, ToBigEndian, ToLittleEndian,
-    Word, U64,
+    AccessList, Address, Block, Bytes, Error, GethExecTrace, Hash, Signature, ToBigEndian,
+    ToLittleEndian, ToWord, Word, U64,
+};
+use ethers_core::types::{
+    transaction::eip2718::TypedTransaction, Eip1559TransactionRequest, Eip2930TransactionRequest,
+    TransactionRequest,
 };
-use ethers_core::types::TransactionRequest;
 use ethers_signers::{LocalWallet, Signer};
 use halo2_proofs::halo2curves::{group::ff::PrimeField, secp256k1};
 use num::Integer;
 use num_bigint::BigUint;
-use serde::{Serialize, Serializer};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_with::serde_as;
 use sha3::{Digest, Keccak256};
 use std::collections::HashMap;
 
+/// Entries of an access list (EIP-2930) in the order they are warmed up at the
+/// beginning of a transaction: all the addresses, followed by all the storage
+/// keys together with the address they belong to.
+pub fn access_list_entries(access_list: &AccessList) -> Vec<(Address, Option<Word>)> {
+    let addresses = access_list.0.iter().map(|item| (item.address, None));
+    let storage_keys = access_list.0.iter().flat_map(|item| {
+        item.storage_keys
+            .iter()
+            .map(|key| (item.address, Some(key.to_word())))
+    });
+    addresses.chain(storage_keys).collect()
+}
+
 /// Definition of all of the data related to an account.
 #[serde_as]
-#[derive(PartialEq, Eq, Debug, Default, Clone, Serialize)]
+#[derive(PartialEq, Eq, Debug, Default, Clone, Serialize, Deserialize)]
 pub struct Account {
     /// Address
     pub address: Address,
@@ -28,7 +44,10 @@ pub struct Account {
     /// EVM Code
     pub code: Bytes,
     /// Storage
-    #[serde(serialize_with = "serde_account_storage")]
+    #[serde(
+        serialize_with = "serde_account_storage",
+        deserialize_with = "deserialize_account_storage"
+    )]
     pub storage: HashMap<Word, Word>,
 }
 
@@ -53,6 +72,20 @@ fn serde_account_storage<S: Serializer>(
         .serialize(serializer)
 }
 
+fn deserialize_account_storage<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<HashMap<Word, Word>, D::Error> {
+    Ok(HashMap::<Hash, Hash>::deserialize(deserializer)?
+        .into_iter()
+        .map(|(k, v)| {
+            (
+                Word::from_big_endian(k.as_bytes()),
+                Word::from_big_endian(v.as_bytes()),
+            )
+        })
+        .collect())
+}
+
 /// Definition of all of the constants related to an Ethereum block and
 /// chain to be used as setup for the external tracer.
 #[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
@@ -107,9 +140,16 @@ impl BlockConstants {
     }
 }
 
+/// Transaction type of the EIP-2930 access list transactions.
+pub const EIP2930_TX_TYPE: u64 = 1;
+/// Transaction type of the EIP-1559 dynamic fee transactions.
+pub const EIP1559_TX_TYPE: u64 = 2;
+
 /// Definition of all of the constants related to an Ethereum transaction.
 #[derive(Debug, Default, Clone, Serialize)]
 pub struct Transaction {
+    /// Transaction type (EIP-2718), 0 for legacy transactions
+    pub transaction_type: u64,
     /// Sender address
     pub from: Address,
     /// Recipient address (None for contract creation)
@@ -120,11 +160,11 @@ pub struct Transaction {
     pub gas_limit: Word,
     /// Transfered value
     pub value: Word,
-    /// Gas Price
+    /// Gas Price, which is the effective gas price for EIP-1559 transactions
     pub gas_price: Word,
-    /// Gas fee cap
+    /// Gas fee cap, which equals the gas price for legacy transactions
     pub gas_fee_cap: Word,
-    /// Gas tip cap
+    /// Gas tip cap, which equals the gas price for legacy transactions
     pub gas_tip_cap: Word,
     /// The compiled code of a contract OR the first 4 bytes of the hash of the
     /// invoked method signature and encoded parameters. For details see
@@ -144,14 +184,15 @@ pub struct Transaction {
 impl From<&Transaction> for crate::Transaction {
     fn from(tx: &Transaction) -> crate::Transaction {
         crate::Transaction {
+            transaction_type: Some(U64::from(tx.transaction_type)),
             from: tx.from,
             to: tx.to,
             nonce: tx.nonce,
             gas: tx.gas_limit,
             value: tx.value,
             gas_price: Some(tx.gas_price),
-            max_priority_fee_per_gas: Some(tx.gas_fee_cap),
-            max_fee_per_gas: Some(tx.gas_tip_cap),
+            max_priority_fee_per_gas: Some(tx.gas_tip_cap),
+            max_fee_per_gas: Some(tx.gas_fee_cap),
             input: tx.call_data.clone(),
             access_list: tx.access_list.clone(),
             v: tx.v.into(),
@@ -164,15 +205,26 @@ impl From<&Transaction> for crate::Transaction {
 
 impl From<&crate::Transaction> for Transaction {
     fn from(tx: &crate::Transaction) -> Transaction {
+        let transaction_type = tx.transaction_type.unwrap_or_default().as_u64();
+        let gas_price = tx.gas_price.unwrap_or_default();
+        let (gas_fee_cap, gas_tip_cap) = if transaction_type == EIP1559_TX_TYPE {
+            (
+                tx.max_fee_per_gas.unwrap_or_default(),
+                tx.max_priority_fee_per_gas.unwrap_or_default(),
+            )
+        } else {
+            (gas_price, gas_price)
+        };
         Transaction {
+            transaction_type,
             from: tx.from,
             to: tx.to,
             nonce: tx.nonce,
             gas_limit: tx.gas,
             value: tx.value,
-            gas_price: tx.gas_price.unwrap_or_default(),
-            gas_fee_cap: tx.max_priority_fee_per_gas.unwrap_or_default(),
-            gas_tip_cap: tx.max_fee_per_gas.unwrap_or_default(),
+            gas_price,
+            gas_fee_cap,
+            gas_tip_cap,
             call_data: tx.input.clone(),
             access_list: tx.access_list.clone(),
             v: tx.v.as_u64(),
@@ -195,7 +247,93 @@ impl From<&Transaction> for TransactionRequest {
     }
 }
 
+impl From<&Transaction> for TypedTransaction {
+    fn from(tx: &Transaction) -> TypedTransaction {
+        if tx.is_eip1559() {
+            let mut req = Eip1559TransactionRequest::new()
+                .from(tx.from)
+                .nonce(tx.nonce)
+                .value(tx.value)
+                .data(tx.call_data.clone())
+                .gas(tx.gas_limit)
+                .max_fee_per_gas(tx.gas_fee_cap)
+                .max_priority_fee_per_gas(tx.gas_tip_cap)
+                .access_list(tx.access_list.clone().unwrap_or_default());
+            if let Some(to) = tx.to {
+                req = req.to(to);
+            }
+            req.into()
+        } else {
+            let mut req = TransactionRequest::new()
+                .from(tx.from)
+                .nonce(tx.nonce)
+                .value(tx.value)
+                .data(tx.call_data.clone())
+                .gas(tx.gas_limit)
+                .gas_price(tx.gas_price);
+            if let Some(to) = tx.to {
+                req = req.to(to);
+            }
+            if tx.transaction_type == EIP2930_TX_TYPE {
+                Eip2930TransactionRequest::new(req, tx.access_list.clone().unwrap_or_default())
+                    .into()
+            } else {
+                req.into()
+            }
+        }
+    }
+}
+
 impl Transaction {
+    /// Whether this is an EIP-1559 dynamic fee transaction.
+    pub fn is_eip1559(&self) -> bool {
+        self.transaction_type == EIP1559_TX_TYPE
+    }
+
+    /// Whether this is a typed transaction (EIP-2718), whose signature
+    /// carries the y parity as `v` instead of the EIP-155 encoding.
+    pub fn is_typed(&self) -> bool {
+        self.transaction_type != 0
+    }
+
+    /// Number of addresses in the access list (EIP-2930).
+    pub fn access_list_addresses_len(&self) -> u64 {
+        self.access_list
+            .as_ref()
+            .map_or(0, |access_list| access_list.0.len() as u64)
+    }
+
+    /// Number of storage keys in the access list (EIP-2930).
+    pub fn access_list_storage_keys_len(&self) -> u64 {
+        self.access_list.as_ref().map_or(0, |access_list| {
+            access_list
+                .0
+                .iter()
+                .map(|item| item.storage_keys.len() as u64)
+                .sum()
+        })
+    }
+
+    /// Entries of the access list (EIP-2930), see [`access_list_entries`].
+    pub fn access_list_entries(&self) -> Vec<(Address, Option<Word>)> {
+        self.access_list
+            .as_ref()
+            .map_or_else(Vec::new, access_list_entries)
+    }
+
+    /// Returns the EIP-2718 encoding of the signed transaction, which is the
+    /// RLP list of its fields for legacy transactions and the transaction
+    /// type followed by that list for typed transactions.
+    pub fn rlp_signed(&self, chain_id: u64) -> Bytes {
+        let mut req: TypedTransaction = self.into();
+        req.set_chain_id(chain_id);
+        req.rlp_signed(&Signature {
+            r: self.r,
+            s: self.s,
+            v: self.v,
+        })
+    }
+
     /// Return the SignData associated with this Transaction.
     pub fn sign_data(&self, chain_id: u64) -> Result<SignData, Error> {
         let sig_r_le = self.r.to_le_bytes();
@@ -208,18 +346,25 @@ impl Transaction {
             secp256k1::Fq::from_repr(sig_s_le),
             Error::Signature(libsecp256k1::Error::InvalidSignature),
         )?;
-        // msg = rlp([nonce, gasPrice, gas, to, value, data, sig_v, r, s])
-        let req: TransactionRequest = self.into();
-        let msg = req.chain_id(chain_id).rlp();
+        // Legacy: msg = rlp([nonce, gasPrice, gas, to, value, data, chain_id, 0, 0])
+        // EIP-2930: msg = 0x01 || rlp([chain_id, nonce, gasPrice, gas, to, value,
+        // data, access_list])
+        // EIP-1559: msg = 0x02 || rlp([chain_id, nonce, gasTipCap, gasFeeCap, gas,
+        // to, value, data, access_list])
+        let mut req: TypedTransaction = self.into();
+        req.set_chain_id(chain_id);
+        let msg = req.rlp();
         let msg_hash: [u8; 32] = Keccak256::digest(&msg)
             .as_slice()
             .to_vec()
             .try_into()
             .expect("hash length isn't 32 bytes");
-        let v = self
-            .v
-            .checked_sub(35 + chain_id * 2)
-            .ok_or(Error::Signature(libsecp256k1::Error::InvalidSignature))? as u8;
+        let v = if self.is_typed() {
+            Some(self.v).filter(|v| *v <= 1)
+        } else {
+            self.v.checked_sub(35 + chain_id * 2)
+        }
+        .ok_or(Error::Signature(libsecp256k1::Error::InvalidSignature))? as u8;
         let pk = recover_pk(v, &self.r, &self.s, &msg_hash)?;
         // msg_hash = msg_hash % q
         let msg_hash = BigUint::from_bytes_be(msg_hash.as_slice());
@@ -238,7 +383,11 @@ impl Transaction {
 }
 
 /// GethData is a type that contains all the information of a Ethereum block
-#[derive(Debug, Clone)]
+///
+/// It can be deserialized from a JSON object with the block as returned by
+/// `eth_getBlockByNumber` with the full transactions, and the traces as
+/// returned by `debug_traceBlockByNumber` without the `result` wrapper.
+#[derive(Debug, Clone, Deserialize)]
 pub struct GethData {
     /// chain id
     pub chain_id: Word,
@@ -260,11 +409,46 @@ impl GethData {
             let wallet = wallets.get(&tx.from).unwrap();
             assert_eq!(Word::from(wallet.chain_id()), self.chain_id);
             let geth_tx: Transaction = (&*tx).into();
-            let req: TransactionRequest = (&geth_tx).into();
-            let sig = wallet.sign_transaction_sync(&req.chain_id(self.chain_id.as_u64()).into());
-            tx.v = U64::from(sig.v);
+            let mut req: TypedTransaction = (&geth_tx).into();
+            req.set_chain_id(self.chain_id.as_u64());
+            let sig = wallet.sign_transaction_sync(&req);
+            tx.v = if geth_tx.is_typed() {
+                U64::from(sig.v - 35 - self.chain_id.as_u64() * 2)
+            } else {
+                U64::from(sig.v)
+            };
             tx.r = sig.r;
             tx.s = sig.s;
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{address, word, word_map};
+
+    #[test]
+    fn deserialize_geth_data() {
+        let account = Account {
+            address: address!("0x00000000000000000000000000000000000000fe"),
+            nonce: Word::from(1),
+            balance: Word::from(1000),
+            code: Bytes::from(vec![0x60, 0x00]),
+            storage: word_map!("1" => "ff"),
+        };
+        let json = serde_json::json!({
+            "chain_id": "0x1",
+            "history_hashes": ["0x2a"],
+            "eth_block": Block::<crate::Transaction>::default(),
+            "geth_traces": [],
+            "accounts": [account],
+        });
+
+        let geth_data: GethData = serde_json::from_value(json).unwrap();
+        assert_eq!(geth_data.chain_id, Word::from(1));
+        assert_eq!(geth_data.history_hashes, vec![Word::from(42)]);
+        assert!(geth_data.geth_traces.is_empty());
+        assert_eq!(geth_data.accounts, vec![account]);
+    }
+}
@@ -20,6 +20,7 @@ pub mod error;
 pub mod bytecode;
 pub mod evm_types;
 pub mod geth_types;
+pub mod precompile;
 pub mod sign_types;
 
 pub use bytecode::Bytecode;
@@ -37,7 +38,10 @@ use crate::evm_types::{Gas, GasCost, OpcodeId, ProgramCounter};
 pub use ethers_core::abi::ethereum_types::{BigEndianHash, U512};
 use ethers_core::types;
 pub use ethers_core::types::{
-    transaction::{eip2930::AccessList, response::Transaction},
+    transaction::{
+        eip2930::{AccessList, AccessListItem},
+        response::Transaction,
+    },
     Address, Block, Bytes, Signature, H160, H256, H64, U256, U64,
 };
 
@@ -0,0 +1,572 @@
+//! Precompiled contracts: addresses, gas schedule and native execution, shared
+//! by the witness generation of calls to the precompiled contracts and the
+//! native tracer.
+
+use crate::{
+    evm_types::GasCost,
+    sign_types::{
+        biguint_to_32bytes_le, pk_bytes_le, pk_bytes_swap_endianness, recover_pk, SignData,
+        SECP256K1_Q,
+    },
+    Address, ToBigEndian, ToLittleEndian, Word,
+};
+use ethers_core::utils::keccak256;
+use halo2_proofs::halo2curves::{group::ff::PrimeField, secp256k1::Fq};
+use num_bigint::BigUint;
+use ripemd::Ripemd160;
+use sha2::{Digest, Sha256};
+use strum_macros::EnumIter;
+
+/// Addresses of the precompiled contracts.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, EnumIter)]
+pub enum PrecompileCalls {
+    /// Elliptic Curve Recovery
+    ECRecover = 0x01,
+    /// SHA2-256 hash function
+    Sha256 = 0x02,
+    /// Ripemd-160 hash function
+    Ripemd160 = 0x03,
+    /// Identity function
+    Identity = 0x04,
+    /// Modular exponentiation
+    Modexp = 0x05,
+    /// Point addition on bn256 curve
+    Bn256Add = 0x06,
+    /// Scalar multiplication on bn256 curve
+    Bn256ScalarMul = 0x07,
+    /// Bilinear function on groups over bn256 curve
+    Bn256Pairing = 0x08,
+    /// Compression function F used in BLAKE2 cryptographic hashing algorithm
+    Blake2F = 0x09,
+}
+
+impl From<PrecompileCalls> for Address {
+    fn from(value: PrecompileCalls) -> Self {
+        Address::from_low_u64_be(value as u64)
+    }
+}
+
+impl From<PrecompileCalls> for u64 {
+    fn from(value: PrecompileCalls) -> Self {
+        value as u64
+    }
+}
+
+impl TryFrom<&Address> for PrecompileCalls {
+    type Error = ();
+
+    fn try_from(address: &Address) -> Result<Self, Self::Error> {
+        if address.0[0..19] != [0u8; 19] {
+            return Err(());
+        }
+        Ok(match address.0[19] {
+            0x01 => Self::ECRecover,
+            0x02 => Self::Sha256,
+            0x03 => Self::Ripemd160,
+            0x04 => Self::Identity,
+            0x05 => Self::Modexp,
+            0x06 => Self::Bn256Add,
+            0x07 => Self::Bn256ScalarMul,
+            0x08 => Self::Bn256Pairing,
+            0x09 => Self::Blake2F,
+            _ => return Err(()),
+        })
+    }
+}
+
+/// Check if address is a precompiled or not.
+pub fn is_precompiled(address: &Address) -> bool {
+    PrecompileCalls::try_from(address).is_ok()
+}
+
+/// Result of the native execution of a precompiled contract.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PrecompileResult {
+    /// Returned bytes, empty when the call fails.
+    pub output: Vec<u8>,
+    /// Gas charged to the callee.  All the gas given to the call is consumed
+    /// when it fails.
+    pub gas_cost: u64,
+    /// Gas cost required by the precompile for the given input, regardless of
+    /// the gas given to the call.
+    pub required_gas: u64,
+    /// Whether the input is well formed for the precompile.
+    pub is_valid: bool,
+    /// Whether the call succeeds, which is `is_valid && required_gas <= gas`.
+    pub is_success: bool,
+}
+
+impl PrecompileCalls {
+    /// Gas required to execute the precompile with `input`.
+    pub fn required_gas(&self, input: &[u8]) -> u64 {
+        let words = (input.len() as u64 + 31) / 32;
+        match self {
+            Self::ECRecover => GasCost::PRECOMPILE_EC_RECOVER.as_u64(),
+            Self::Sha256 => 60 + 12 * words,
+            Self::Ripemd160 => 600 + 120 * words,
+            Self::Identity => {
+                GasCost::PRECOMPILE_IDENTITY_BASE.as_u64()
+                    + GasCost::PRECOMPILE_IDENTITY_PER_WORD.as_u64() * words
+            }
+            Self::Modexp => modexp_required_gas(input),
+            Self::Bn256Add => 150,
+            Self::Bn256ScalarMul => 6000,
+            Self::Bn256Pairing => 45000 + 34000 * (input.len() as u64 / 192),
+            Self::Blake2F => {
+                if input.len() == 213 {
+                    u32::from_be_bytes(input[0..4].try_into().unwrap()) as u64
+                } else {
+                    0
+                }
+            }
+        }
+    }
+
+    /// Execute the precompile natively, returning `None` when the input is
+    /// malformed, in which case the call fails.
+    pub fn execute(&self, input: &[u8]) -> Option<Vec<u8>> {
+        match self {
+            Self::ECRecover => Some(
+                ecrecover(input)
+                    .map(|address| {
+                        let mut output = vec![0u8; 12];
+                        output.extend_from_slice(address.as_bytes());
+                        output
+                    })
+                    .unwrap_or_default(),
+            ),
+            Self::Sha256 => Some(Sha256::digest(input).to_vec()),
+            Self::Ripemd160 => {
+                let mut output = vec![0u8; 12];
+                output.extend_from_slice(&Ripemd160::digest(input));
+                Some(output)
+            }
+            Self::Identity => Some(input.to_vec()),
+            Self::Modexp => Some(modexp(input)),
+            Self::Bn256Add => bn256_add(input),
+            Self::Bn256ScalarMul => bn256_scalar_mul(input),
+            Self::Bn256Pairing => bn256_pairing(input),
+            Self::Blake2F => blake2f(input),
+        }
+    }
+}
+
+/// Execute the precompiled contract at `address` with `input` and `gas` given
+/// to the call.
+pub fn execute_precompiled(address: &Address, input: &[u8], gas: u64) -> PrecompileResult {
+    let precompile = PrecompileCalls::try_from(address).expect("address is a precompile");
+    let required_gas = precompile.required_gas(input);
+    let output = precompile.execute(input);
+    let is_valid = output.is_some();
+    let is_success = is_valid && required_gas <= gas;
+    PrecompileResult {
+        output: if is_success {
+            output.unwrap()
+        } else {
+            Vec::new()
+        },
+        gas_cost: if is_success { required_gas } else { gas },
+        required_gas,
+        is_valid,
+        is_success,
+    }
+}
+
+/// Return `input[offset..offset + length]` right padded with zeros.
+fn get_data(input: &[u8], offset: usize, length: usize) -> Vec<u8> {
+    let mut data = vec![0u8; length];
+    if offset < input.len() {
+        let end = std::cmp::min(offset + length, input.len());
+        data[..end - offset].copy_from_slice(&input[offset..end]);
+    }
+    data
+}
+
+/// Split the input of ecrecover into `(msg_hash, v, r, s)`.
+fn ecrecover_input(input: &[u8]) -> ([u8; 32], Word, Word, Word) {
+    let input = get_data(input, 0, 128);
+    (
+        input[0..32].try_into().unwrap(),
+        Word::from_big_endian(&input[32..64]),
+        Word::from_big_endian(&input[64..96]),
+        Word::from_big_endian(&input[96..128]),
+    )
+}
+
+/// Recover the address that signed the message of an ecrecover input, if any.
+fn ecrecover(input: &[u8]) -> Option<Address> {
+    let sign_data = ecrecover_sign_data(input)?;
+    let pk_be = pk_bytes_swap_endianness(&pk_bytes_le(&sign_data.pk));
+    Some(Address::from_slice(&keccak256(pk_be)[12..]))
+}
+
+/// Return the signature data verified by the SignVerify chip for an ecrecover
+/// input which recovers an address successfully.
+pub fn ecrecover_sign_data(input: &[u8]) -> Option<SignData> {
+    let (msg_hash, v, r, s) = ecrecover_input(input);
+    if v != Word::from(27) && v != Word::from(28) {
+        return None;
+    }
+    let pk = recover_pk(v.as_u64() as u8 - 27, &r, &s, &msg_hash).ok()?;
+    let to_fq = |word: Word| Option::<Fq>::from(Fq::from_repr(word.to_le_bytes()));
+    let msg_hash = BigUint::from_bytes_be(&msg_hash) % &*SECP256K1_Q;
+    Some(SignData {
+        signature: (to_fq(r)?, to_fq(s)?),
+        pk,
+        msg_hash: Option::<Fq>::from(Fq::from_repr(biguint_to_32bytes_le(msg_hash)))?,
+    })
+}
+
+/// Read the lengths of base, exponent and modulus from the input of modexp.
+fn modexp_lengths(input: &[u8]) -> (Word, Word, Word) {
+    let lengths = get_data(input, 0, 96);
+    (
+        Word::from_big_endian(&lengths[0..32]),
+        Word::from_big_endian(&lengths[32..64]),
+        Word::from_big_endian(&lengths[64..96]),
+    )
+}
+
+/// Gas cost of modexp as specified by EIP-2565.
+fn modexp_required_gas(input: &[u8]) -> u64 {
+    let (base_len, exp_len, mod_len) = modexp_lengths(input);
+    let max_len = std::cmp::max(base_len, mod_len);
+    // Lengths this large can never be paid for within a block.
+    if max_len > Word::from(u32::MAX) || exp_len > Word::from(u32::MAX) {
+        return u64::MAX;
+    }
+    let (base_len, exp_len, max_len) = (
+        base_len.as_u64(),
+        exp_len.as_u64(),
+        max_len.as_u64() as u128,
+    );
+
+    let words = (max_len + 7) / 8;
+    let multiplication_complexity = words * words;
+
+    let exp_head = BigUint::from_bytes_be(&get_data(
+        input,
+        96usize.saturating_add(base_len as usize),
+        std::cmp::min(exp_len, 32) as usize,
+    ));
+    let exp_head_bits = exp_head.bits() as u128;
+    let iteration_count = if exp_len <= 32 {
+        exp_head_bits.saturating_sub(1)
+    } else {
+        8 * (exp_len as u128 - 32) + exp_head_bits.saturating_sub(1)
+    };
+    let iteration_count = std::cmp::max(iteration_count, 1);
+
+    let gas = multiplication_complexity * iteration_count / 3;
+    std::cmp::max(200, std::cmp::min(gas, u64::MAX as u128) as u64)
+}
+
+fn modexp(input: &[u8]) -> Vec<u8> {
+    let (base_len, exp_len, mod_len) = modexp_lengths(input);
+    let (base_len, exp_len, mod_len) =
+        (base_len.as_usize(), exp_len.as_usize(), mod_len.as_usize());
+    if mod_len == 0 {
+        return Vec::new();
+    }
+
+    let base = BigUint::from_bytes_be(&get_data(input, 96, base_len));
+    let exp = BigUint::from_bytes_be(&get_data(input, 96 + base_len, exp_len));
+    let modulus = BigUint::from_bytes_be(&get_data(input, 96 + base_len + exp_len, mod_len));
+
+    let mut output = vec![0u8; mod_len];
+    if modulus != BigUint::from(0u8) {
+        let result = base.modpow(&exp, &modulus).to_bytes_be();
+        output[mod_len - result.len()..].copy_from_slice(&result);
+    }
+    output
+}
+
+fn bn256_read_g1(input: &[u8]) -> Option<bn::G1> {
+    use bn::Group;
+    let x = bn::Fq::from_slice(&input[0..32]).ok()?;
+    let y = bn::Fq::from_slice(&input[32..64]).ok()?;
+    if x.is_zero() && y.is_zero() {
+        Some(bn::G1::zero())
+    } else {
+        bn::AffineG1::new(x, y).ok().map(Into::into)
+    }
+}
+
+fn bn256_read_g2(input: &[u8]) -> Option<bn::G2> {
+    use bn::Group;
+    let read_fq = |offset: usize| bn::Fq::from_slice(&input[offset..offset + 32]).ok();
+    // Fq2 elements are encoded as (imaginary, real).
+    let x = bn::Fq2::new(read_fq(32)?, read_fq(0)?);
+    let y = bn::Fq2::new(read_fq(96)?, read_fq(64)?);
+    if x.is_zero() && y.is_zero() {
+        Some(bn::G2::zero())
+    } else {
+        bn::AffineG2::new(x, y).ok().map(Into::into)
+    }
+}
+
+fn bn256_write_g1(point: bn::G1) -> Vec<u8> {
+    let mut output = vec![0u8; 64];
+    if let Some(point) = bn::AffineG1::from_jacobian(point) {
+        point.x().to_big_endian(&mut output[0..32]).unwrap();
+        point.y().to_big_endian(&mut output[32..64]).unwrap();
+    }
+    output
+}
+
+fn bn256_add(input: &[u8]) -> Option<Vec<u8>> {
+    let input = get_data(input, 0, 128);
+    let p = bn256_read_g1(&input[0..64])?;
+    let q = bn256_read_g1(&input[64..128])?;
+    Some(bn256_write_g1(p + q))
+}
+
+fn bn256_scalar_mul(input: &[u8]) -> Option<Vec<u8>> {
+    let input = get_data(input, 0, 96);
+    let p = bn256_read_g1(&input[0..64])?;
+    let scalar = bn::Fr::from_slice(&input[64..96]).ok()?;
+    Some(bn256_write_g1(p * scalar))
+}
+
+fn bn256_pairing(input: &[u8]) -> Option<Vec<u8>> {
+    if input.len() % 192 != 0 {
+        return None;
+    }
+    let pairs = input
+        .chunks_exact(192)
+        .map(|chunk| {
+            Some((
+                bn256_read_g1(&chunk[0..64])?,
+                bn256_read_g2(&chunk[64..192])?,
+            ))
+        })
+        .collect::<Option<Vec<_>>>()?;
+    let mut output = vec![0u8; 32];
+    if bn::pairing_batch(&pairs) == bn::Gt::one() {
+        output[31] = 1;
+    }
+    Some(output)
+}
+
+const BLAKE2B_IV: [u64; 8] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+const BLAKE2B_SIGMA: [[usize; 16]; 10] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+/// BLAKE2b compression function F as specified by EIP-152.
+fn blake2f(input: &[u8]) -> Option<Vec<u8>> {
+    if input.len() != 213 || input[212] > 1 {
+        return None;
+    }
+    let rounds = u32::from_be_bytes(input[0..4].try_into().unwrap());
+    let read_u64 =
+        |offset: usize| u64::from_le_bytes(input[offset..offset + 8].try_into().unwrap());
+    let mut h: [u64; 8] = std::array::from_fn(|i| read_u64(4 + 8 * i));
+    let m: [u64; 16] = std::array::from_fn(|i| read_u64(68 + 8 * i));
+    let t = [read_u64(196), read_u64(204)];
+    let is_final = input[212] == 1;
+
+    let mut v = [0u64; 16];
+    v[..8].copy_from_slice(&h);
+    v[8..].copy_from_slice(&BLAKE2B_IV);
+    v[12] ^= t[0];
+    v[13] ^= t[1];
+    if is_final {
+        v[14] = !v[14];
+    }
+
+    let g = |v: &mut [u64; 16], a: usize, b: usize, c: usize, d: usize, x: u64, y: u64| {
+        v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+        v[d] = (v[d] ^ v[a]).rotate_right(32);
+        v[c] = v[c].wrapping_add(v[d]);
+        v[b] = (v[b] ^ v[c]).rotate_right(24);
+        v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+        v[d] = (v[d] ^ v[a]).rotate_right(16);
+        v[c] = v[c].wrapping_add(v[d]);
+        v[b] = (v[b] ^ v[c]).rotate_right(63);
+    };
+    for round in 0..rounds as usize {
+        let s = &BLAKE2B_SIGMA[round % 10];
+        g(&mut v, 0, 4, 8, 12, m[s[0]], m[s[1]]);
+        g(&mut v, 1, 5, 9, 13, m[s[2]], m[s[3]]);
+        g(&mut v, 2, 6, 10, 14, m[s[4]], m[s[5]]);
+        g(&mut v, 3, 7, 11, 15, m[s[6]], m[s[7]]);
+        g(&mut v, 0, 5, 10, 15, m[s[8]], m[s[9]]);
+        g(&mut v, 1, 6, 11, 12, m[s[10]], m[s[11]]);
+        g(&mut v, 2, 7, 8, 13, m[s[12]], m[s[13]]);
+        g(&mut v, 3, 4, 9, 14, m[s[14]], m[s[15]]);
+    }
+    for i in 0..8 {
+        h[i] ^= v[i] ^ v[i + 8];
+    }
+
+    Some(h.iter().flat_map(|word| word.to_le_bytes()).collect())
+}
+
+#[cfg(test)]
+mod precompile_tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn precompile_addresses() {
+        for address in 1..=9u64 {
+            assert!(is_precompiled(&Address::from_low_u64_be(address)));
+        }
+        assert!(!is_precompiled(&Address::zero()));
+        assert!(!is_precompiled(&Address::from_low_u64_be(0x0a)));
+        assert!(!is_precompiled(&Address::from_low_u64_be(0x0101)));
+    }
+
+    #[test]
+    fn precompile_sha256_ripemd160_identity() {
+        let input = b"abc";
+        let result = execute_precompiled(&PrecompileCalls::Sha256.into(), input, 100);
+        assert_eq!(
+            hex::encode(result.output),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(result.gas_cost, 72);
+
+        let result = execute_precompiled(&PrecompileCalls::Ripemd160.into(), input, 1000);
+        assert_eq!(
+            hex::encode(result.output),
+            "0000000000000000000000008eb208f7e05d987a9b044a8e98c6b087f15a0bfc"
+        );
+        assert_eq!(result.gas_cost, 720);
+
+        let result = execute_precompiled(&PrecompileCalls::Identity.into(), input, 17);
+        assert!(!result.is_success);
+        assert_eq!(result.gas_cost, 17);
+        assert!(result.output.is_empty());
+    }
+
+    #[test]
+    fn precompile_modexp() {
+        // 3 ** 0xffff mod 2 ** 255 - 19
+        let modulus = (BigUint::from(1u8) << 255u32) - BigUint::from(19u8);
+        let input = [
+            Word::from(1u64).to_be_bytes().to_vec(),
+            Word::from(2u64).to_be_bytes().to_vec(),
+            Word::from(32u64).to_be_bytes().to_vec(),
+            vec![0x03, 0xff, 0xff],
+            modulus.to_bytes_be(),
+        ]
+        .concat();
+        let result = execute_precompiled(&PrecompileCalls::Modexp.into(), &input, 100000);
+        let expected = BigUint::from(3u8).modpow(&BigUint::from(0xffffu32), &modulus);
+        assert_eq!(BigUint::from_bytes_be(&result.output), expected);
+        assert_eq!(result.output.len(), 32);
+        // words = 4, iteration_count = 15, 16 * 15 / 3 < 200
+        assert_eq!(result.gas_cost, 200);
+    }
+
+    #[test]
+    fn precompile_bn256() {
+        // 1 * G + 1 * G == 2 * G
+        let mut generator = vec![0u8; 64];
+        generator[31] = 1;
+        generator[63] = 2;
+        let add = execute_precompiled(
+            &PrecompileCalls::Bn256Add.into(),
+            &[generator.clone(), generator.clone()].concat(),
+            150,
+        );
+        let mut two = vec![0u8; 32];
+        two[31] = 2;
+        let mul = execute_precompiled(
+            &PrecompileCalls::Bn256ScalarMul.into(),
+            &[generator.clone(), two].concat(),
+            6000,
+        );
+        assert!(add.is_success && mul.is_success);
+        assert_eq!(add.output, mul.output);
+
+        // A point not on the curve.
+        generator[63] = 3;
+        let add = execute_precompiled(&PrecompileCalls::Bn256Add.into(), &generator, 150);
+        assert!(!add.is_valid);
+
+        // The empty pairing check succeeds.
+        let pairing = execute_precompiled(&PrecompileCalls::Bn256Pairing.into(), &[], 45000);
+        assert_eq!(pairing.output, [vec![0u8; 31], vec![1]].concat());
+    }
+
+    #[test]
+    fn precompile_blake2f() {
+        // Test vector 5 from EIP-152.
+        let input = [
+            hex::decode("0000000c").unwrap(),
+            hex::decode(
+                "48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5\
+                 d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e1319cde05b",
+            )
+            .unwrap(),
+            hex::decode("616263").unwrap(),
+            vec![0u8; 125],
+            hex::decode("0300000000000000").unwrap(),
+            vec![0u8; 8],
+            vec![1],
+        ]
+        .concat();
+        let result = execute_precompiled(&PrecompileCalls::Blake2F.into(), &input, 12);
+        assert_eq!(
+            hex::encode(result.output),
+            "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d1\
+             7d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923"
+        );
+        assert_eq!(result.gas_cost, 12);
+    }
+
+    #[test]
+    fn precompile_ecrecover() {
+        let input = hex::decode(
+            "456e9aea5e197a1f1af7a3e85a3212fa4049a3ba34c2289b4c860fc0b0c64ef3\
+             000000000000000000000000000000000000000000000000000000000000001c\
+             9242685bf161793cc25603c231bc2f568eb630ea16aa137d2664ac8038825608\
+             4f8ae3bd7535248d0bd448298cc2e2071e56992d0774dc340c368ae950852ada",
+        )
+        .unwrap();
+        let result = execute_precompiled(&PrecompileCalls::ECRecover.into(), &input, 3000);
+        assert_eq!(
+            hex::encode(result.output),
+            "0000000000000000000000007156526fbd7a3c72969b54f64e42c10fbb768c8a"
+        );
+        // The recovery id is the one of `v`, which distinguishes the public keys
+        // recovered for the same signature.
+        let sign_data = ecrecover_sign_data(&input).unwrap();
+        assert_eq!(sign_data.recovery_id(), 1);
+        let mut input_v_27 = input.clone();
+        input_v_27[63] = 0x1b;
+        let sign_data_v_27 = ecrecover_sign_data(&input_v_27).unwrap();
+        assert_eq!(sign_data_v_27.recovery_id(), 0);
+        assert_ne!(sign_data_v_27.pk, sign_data.pk);
+
+        // Invalid `v` makes the recovery fail, but the call still succeeds.
+        let mut input = input;
+        input[63] = 0x1d;
+        let result = execute_precompiled(&PrecompileCalls::ECRecover.into(), &input, 3000);
+        assert!(result.is_success);
+        assert!(result.output.is_empty());
+        assert!(ecrecover_sign_data(&input).is_none());
+    }
+}
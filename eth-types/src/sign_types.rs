@@ -53,6 +53,23 @@ pub struct SignData {
     pub msg_hash: secp256k1::Fq,
 }
 
+impl SignData {
+    /// Return the recovery id of the signature, which is the parity of the y
+    /// coordinate of the point `R = (msg_hash * G + r * pk) / s` whose x
+    /// coordinate is `r`.
+    pub fn recovery_id(&self) -> u8 {
+        let (sig_r, sig_s) = self.signature;
+        let sig_s_inv = Option::<secp256k1::Fq>::from(sig_s.invert()).expect("cannot invert s");
+        let generator = Secp256k1Affine::generator();
+        let sig_point =
+            (generator * (self.msg_hash * sig_s_inv) + self.pk * (sig_r * sig_s_inv)).to_affine();
+        let y = *Option::<Coordinates<_>>::from(sig_point.coordinates())
+            .expect("point is the identity")
+            .y();
+        y.to_bytes()[0] & 1
+    }
+}
+
 lazy_static! {
     static ref SIGN_DATA_DEFAULT: SignData = {
         let generator = Secp256k1Affine::generator();
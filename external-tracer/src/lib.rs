@@ -1,6 +1,15 @@
-//! This module generates traces by connecting to an external tracer
+//! This module generates traces by connecting to an external tracer, which is
+//! geth through `geth-utils` with the `geth-tracer` feature, or a Rust EVM
+//! interpreter with the `native-tracer` feature.
+
+#[cfg(not(any(feature = "geth-tracer", feature = "native-tracer")))]
+compile_error!("either the `geth-tracer` or the `native-tracer` feature must be enabled");
+
+#[cfg(feature = "native-tracer")]
+mod native;
 
 use eth_types::{
+    evm_types::Hardfork,
     geth_types::{Account, BlockConstants, Transaction},
     Address, Error, GethExecTrace, Word,
 };
@@ -23,6 +32,8 @@ pub struct TraceConfig {
     pub transactions: Vec<Transaction>,
     /// logger
     pub logger_config: LoggerConfig,
+    /// hardfork whose rules are used to execute the transactions
+    pub hardfork: Hardfork,
 }
 
 /// Configuration structure for `logger.Config`
@@ -63,7 +74,20 @@ impl LoggerConfig {
 }
 
 /// Creates a trace for the specified config
+#[cfg(feature = "native-tracer")]
+pub fn trace(config: &TraceConfig) -> Result<Vec<GethExecTrace>, Error> {
+    native::trace(config)
+}
+
+/// Creates a trace for the specified config
+#[cfg(not(feature = "native-tracer"))]
 pub fn trace(config: &TraceConfig) -> Result<Vec<GethExecTrace>, Error> {
+    trace_geth(config)
+}
+
+/// Creates a trace for the specified config with geth.
+#[cfg(feature = "geth-tracer")]
+fn trace_geth(config: &TraceConfig) -> Result<Vec<GethExecTrace>, Error> {
     // Get the trace
     let trace_string = geth_utils::trace(&serde_json::to_string(&config).unwrap()).map_err(
         |error| match error {
@@ -74,3 +98,543 @@ pub fn trace(config: &TraceConfig) -> Result<Vec<GethExecTrace>, Error> {
     let trace = serde_json::from_str(&trace_string).map_err(Error::SerdeError)?;
     Ok(trace)
 }
+
+#[cfg(all(test, feature = "geth-tracer", feature = "native-tracer"))]
+mod native_tests {
+    use super::*;
+    use eth_types::{
+        address, bytecode, evm_types::OpcodeId, geth_types::BlockConstants, Bytecode, ToWord, U64,
+    };
+    use pretty_assertions::assert_eq;
+
+    const GAS_LIMIT: u64 = 1_000_000;
+
+    fn sender() -> Address {
+        address!("0x00000000000000000000000000000000000000fe")
+    }
+
+    fn contract(n: u64) -> Address {
+        Address::from_low_u64_be(0x1000 + n)
+    }
+
+    fn account(address: Address, code: Bytecode) -> Account {
+        Account {
+            address,
+            balance: Word::from(10).pow(18.into()),
+            code: code.to_vec().into(),
+            ..Default::default()
+        }
+    }
+
+    fn tx(to: Option<Address>, nonce: u64, value: u64, call_data: Vec<u8>) -> Transaction {
+        Transaction {
+            from: sender(),
+            to,
+            nonce: nonce.into(),
+            gas_limit: GAS_LIMIT.into(),
+            value: value.into(),
+            gas_price: 10.into(),
+            call_data: call_data.into(),
+            ..Default::default()
+        }
+    }
+
+    fn config(contracts: Vec<Bytecode>, transactions: Vec<Transaction>) -> TraceConfig {
+        let mut accounts: HashMap<_, _> = contracts
+            .into_iter()
+            .enumerate()
+            .map(|(n, code)| (contract(n as u64), account(contract(n as u64), code)))
+            .collect();
+        accounts.insert(sender(), account(sender(), Bytecode::default()));
+        TraceConfig {
+            chain_id: Word::one(),
+            history_hashes: vec![Word::from(0xbeef)],
+            block_constants: BlockConstants::new(
+                address!("0x00000000000000000000000000000000c014ba5e"),
+                Word::from(1_633_398_551),
+                U64::from(0x100),
+                Word::from(0x200000),
+                Word::from(10 * GAS_LIMIT),
+                Word::from(7),
+            ),
+            accounts,
+            transactions,
+            logger_config: LoggerConfig::enable_memory(),
+            hardfork: Hardfork::default(),
+        }
+    }
+
+    /// Assert that the native tracer returns the same traces, or the same
+    /// error, as geth.
+    fn assert_same_trace(config: TraceConfig) {
+        let expected = trace_geth(&config).map_err(|error| error.to_string());
+        let traces = native::trace(&config).map_err(|error| error.to_string());
+        assert_eq!(traces, expected);
+    }
+
+    fn call_tx(code: Bytecode) {
+        assert_same_trace(config(
+            vec![code],
+            vec![tx(Some(contract(0)), 0, 0, vec![])],
+        ));
+    }
+
+    /// Return code which calls `address` with the opcode of a call, forwarding
+    /// the call data and copying the returned data to the memory.
+    fn call(opcode: OpcodeId, address: Address, value: u64) -> Bytecode {
+        let mut code = bytecode! {
+            CALLDATASIZE
+            PUSH1(0)
+            PUSH1(0)
+            CALLDATACOPY
+            PUSH1(0x20)
+            PUSH1(0x40)
+            CALLDATASIZE
+            PUSH1(0)
+        };
+        if matches!(opcode, OpcodeId::CALL | OpcodeId::CALLCODE) {
+            code.push(32, value.into());
+        }
+        code.append(&bytecode! {
+            PUSH32(address.to_word())
+            GAS
+        });
+        code.write_op(opcode);
+        code.append(&bytecode! {
+            RETURNDATASIZE
+            PUSH1(0)
+            PUSH1(0x80)
+            RETURNDATACOPY
+        });
+        code
+    }
+
+    /// Return init code deploying `runtime_code`, which must be at most 32
+    /// bytes long.
+    fn init_code(runtime_code: &Bytecode) -> Bytecode {
+        let runtime_code = runtime_code.to_vec();
+        let mut word = runtime_code.clone();
+        word.resize(32, 0);
+        bytecode! {
+            PUSH32(Word::from_big_endian(&word))
+            PUSH1(0)
+            MSTORE
+            PUSH1(runtime_code.len())
+            PUSH1(0)
+            RETURN
+        }
+    }
+
+    #[test]
+    fn native_tracer_arithmetic() {
+        call_tx(bytecode! {
+            PUSH1(3)
+            PUSH1(5)
+            ADD
+            PUSH32(Word::MAX)
+            MUL
+            PUSH1(7)
+            SWAP1
+            SUB
+            PUSH1(2)
+            SWAP1
+            SDIV
+            PUSH1(3)
+            SWAP1
+            SMOD
+            PUSH1(9)
+            PUSH1(11)
+            PUSH32(Word::MAX)
+            ADDMOD
+            PUSH1(9)
+            PUSH1(11)
+            PUSH32(Word::MAX)
+            MULMOD
+            PUSH2(0x1234)
+            PUSH1(3)
+            EXP
+            PUSH1(0xf0)
+            PUSH1(0)
+            SIGNEXTEND
+            DUP1
+            PUSH1(4)
+            SAR
+            DUP2
+            PUSH1(4)
+            SHR
+            DUP3
+            PUSH1(4)
+            SHL
+            DUP4
+            PUSH1(31)
+            BYTE
+            SLT
+            SGT
+            LT
+            GT
+            ISZERO
+            NOT
+            PUSH1(0)
+            EQ
+            DIV
+            PUSH1(0)
+            MOD
+            STOP
+        });
+    }
+
+    #[test]
+    fn native_tracer_memory() {
+        let code = bytecode! {
+            PUSH1(0x42)
+            PUSH1(0x1f)
+            MSTORE8
+            PUSH32(Word::MAX)
+            PUSH1(0x41)
+            MSTORE
+            PUSH1(0x30)
+            MLOAD
+            PUSH1(0x50)
+            PUSH1(0x10)
+            SHA3
+            CALLDATASIZE
+            PUSH1(2)
+            PUSH2(0x100)
+            CALLDATACOPY
+            PUSH1(0x40)
+            PUSH1(4)
+            PUSH2(0x200)
+            CODECOPY
+            PUSH1(0)
+            CALLDATALOAD
+            PUSH1(0x30)
+            CALLDATALOAD
+            MSIZE
+            PC
+            PUSH1(0x20)
+            PUSH2(0x220)
+            RETURN
+        };
+        assert_same_trace(config(
+            vec![code],
+            vec![tx(Some(contract(0)), 0, 0, vec![0, 1, 2, 3, 0xff, 0, 0, 7])],
+        ));
+    }
+
+    #[test]
+    fn native_tracer_storage() {
+        let code = bytecode! {
+            PUSH1(1)
+            SLOAD
+            PUSH1(0)
+            PUSH1(1)
+            SSTORE
+            PUSH1(5)
+            PUSH1(1)
+            SSTORE
+            PUSH1(2)
+            PUSH1(1)
+            SSTORE
+            PUSH1(7)
+            PUSH1(2)
+            SSTORE
+            PUSH1(0)
+            PUSH1(2)
+            SSTORE
+            PUSH1(2)
+            SLOAD
+            PUSH1(2)
+            SLOAD
+            STOP
+        };
+        let mut config = config(vec![code], vec![]);
+        config
+            .accounts
+            .get_mut(&contract(0))
+            .unwrap()
+            .storage
+            .insert(Word::one(), Word::from(2));
+        config.transactions = vec![
+            tx(Some(contract(0)), 0, 0, vec![]),
+            tx(Some(contract(0)), 1, 0, vec![]),
+        ];
+        assert_same_trace(config);
+    }
+
+    #[test]
+    fn native_tracer_calls() {
+        let callee = bytecode! {
+            CALLVALUE
+            PUSH1(0)
+            SSTORE
+            CALLER
+            ADDRESS
+            ORIGIN
+            SELFBALANCE
+            PUSH1(0)
+            CALLDATALOAD
+            PUSH1(0)
+            MSTORE
+            PUSH1(0x20)
+            PUSH1(0)
+            RETURN
+        };
+        let mut caller = Bytecode::default();
+        for (opcode, address, value) in [
+            (OpcodeId::CALL, contract(1), 0x100),
+            (OpcodeId::CALLCODE, contract(1), 0x100),
+            (OpcodeId::DELEGATECALL, contract(1), 0),
+            (OpcodeId::STATICCALL, Address::from_low_u64_be(2), 0),
+            (OpcodeId::CALL, Address::from_low_u64_be(4), 0),
+            (OpcodeId::CALL, Address::from_low_u64_be(0xdead), 1),
+            (OpcodeId::STATICCALL, Address::from_low_u64_be(0xbeef), 0),
+        ] {
+            caller.append(&call(opcode, address, value));
+        }
+        caller.append(&bytecode! {
+            PUSH32(contract(1).to_word())
+            DUP1
+            DUP1
+            BALANCE
+            EXTCODESIZE
+            EXTCODEHASH
+            PUSH1(4)
+            PUSH1(0)
+            PUSH2(0x200)
+            PUSH32(contract(1).to_word())
+            EXTCODECOPY
+            PUSH32(Address::from_low_u64_be(0xdead).to_word())
+            EXTCODEHASH
+            STOP
+        });
+        assert_same_trace(config(
+            vec![caller, callee],
+            vec![tx(Some(contract(0)), 0, 0, vec![0xab; 40])],
+        ));
+    }
+
+    #[test]
+    fn native_tracer_create() {
+        let runtime_code = bytecode! {
+            PUSH1(0x2a)
+            PUSH1(0)
+            SSTORE
+            STOP
+        };
+        let code = bytecode! {
+            CALLDATASIZE
+            PUSH1(0)
+            PUSH1(0)
+            CALLDATACOPY
+            CALLDATASIZE
+            PUSH1(0)
+            PUSH1(0x10)
+            CREATE
+            PUSH1(0xff)
+            CALLDATASIZE
+            PUSH1(0)
+            PUSH1(0)
+            CREATE2
+            PUSH1(0)
+            PUSH1(0)
+            PUSH1(0)
+            PUSH1(0)
+            PUSH1(0)
+            DUP6
+            GAS
+            CALL
+            // Collides with the contract created above.
+            PUSH1(0xff)
+            CALLDATASIZE
+            PUSH1(0)
+            PUSH1(0)
+            CREATE2
+            STOP
+        };
+        let init_code = init_code(&runtime_code).to_vec();
+        assert_same_trace(config(
+            vec![code],
+            vec![
+                tx(Some(contract(0)), 0, 0, init_code.clone()),
+                tx(None, 1, 0x20, init_code),
+            ],
+        ));
+    }
+
+    #[test]
+    fn native_tracer_revert() {
+        let callee = bytecode! {
+            PUSH1(1)
+            PUSH1(0)
+            SSTORE
+            PUSH1(0xaa)
+            PUSH1(0)
+            MSTORE
+            PUSH1(0x20)
+            PUSH1(0)
+            REVERT
+        };
+        let mut caller = call(OpcodeId::CALL, contract(1), 0);
+        caller.append(&bytecode! {
+            PUSH1(0)
+            SLOAD
+            PUSH1(0x20)
+            PUSH1(0x80)
+            REVERT
+        });
+        assert_same_trace(config(
+            vec![caller, callee],
+            vec![tx(Some(contract(0)), 0, 0, vec![])],
+        ));
+    }
+
+    #[test]
+    fn native_tracer_errors() {
+        let out_of_gas = bytecode! {
+            PUSH1(0)
+            PUSH1(0)
+            PUSH1(0)
+            PUSH1(0)
+            PUSH1(0)
+            PUSH32(contract(4).to_word())
+            PUSH2(0x8000)
+            CALL
+            PUSH1(0)
+            PUSH1(0)
+            PUSH1(0)
+            PUSH1(0)
+            PUSH1(0)
+            PUSH32(contract(5).to_word())
+            PUSH2(2300)
+            CALL
+            PUSH3(0x100000)
+            MLOAD
+        };
+        let stack_underflow = bytecode! {
+            PUSH1(1)
+            ADD
+        };
+        let invalid_jump = bytecode! {
+            PUSH1(0)
+            JUMPDEST
+            PUSH1(3)
+            JUMP
+        };
+        let invalid_opcode = bytecode! {
+            PUSH1(1)
+            INVALID
+        };
+        let infinite_loop = bytecode! {
+            JUMPDEST
+            PUSH1(1)
+            PUSH1(0)
+            SSTORE
+            PUSH1(0)
+            JUMP
+        };
+        let sstore_sentry = bytecode! {
+            PUSH1(1)
+            PUSH1(0)
+            SSTORE
+        };
+        let mut caller = Bytecode::default();
+        for n in 1..=3 {
+            caller.append(&call(OpcodeId::CALL, contract(n), 0));
+        }
+        assert_same_trace(config(
+            vec![
+                caller,
+                stack_underflow,
+                invalid_jump,
+                invalid_opcode,
+                infinite_loop,
+                sstore_sentry,
+                out_of_gas,
+            ],
+            vec![
+                tx(Some(contract(0)), 0, 0, vec![]),
+                tx(Some(contract(6)), 1, 0, vec![]),
+            ],
+        ));
+    }
+
+    #[test]
+    fn native_tracer_static_call_write_protection() {
+        let callee = bytecode! {
+            PUSH1(1)
+            PUSH1(0)
+            SSTORE
+        };
+        let mut caller = call(OpcodeId::STATICCALL, contract(1), 0);
+        caller.append(&bytecode! {
+            STOP
+        });
+        assert_same_trace(config(
+            vec![caller, callee],
+            vec![tx(Some(contract(0)), 0, 0, vec![])],
+        ));
+    }
+
+    #[test]
+    fn native_tracer_selfdestruct() {
+        let callee = bytecode! {
+            PUSH32(Address::from_low_u64_be(0xdead).to_word())
+            SELFDESTRUCT
+        };
+        let mut caller = call(OpcodeId::CALL, contract(1), 0);
+        caller.append(&bytecode! {
+            PUSH32(contract(1).to_word())
+            EXTCODESIZE
+            PUSH32(Address::from_low_u64_be(0xdead).to_word())
+            BALANCE
+            STOP
+        });
+        assert_same_trace(config(
+            vec![caller, callee],
+            vec![
+                tx(Some(contract(0)), 0, 0, vec![]),
+                tx(Some(contract(0)), 1, 0, vec![]),
+            ],
+        ));
+    }
+
+    #[test]
+    fn native_tracer_block_context() {
+        call_tx(bytecode! {
+            COINBASE
+            TIMESTAMP
+            NUMBER
+            DIFFICULTY
+            GASLIMIT
+            CHAINID
+            BASEFEE
+            GASPRICE
+            PUSH1(0xff)
+            BLOCKHASH
+            PUSH2(0xff)
+            BLOCKHASH
+            STOP
+        });
+    }
+
+    #[test]
+    fn native_tracer_invalid_tx() {
+        for tx in [
+            tx(Some(contract(0)), 1, 0, vec![]),
+            Transaction {
+                gas_limit: 21_000.into(),
+                ..tx(Some(contract(0)), 0, 0, vec![1])
+            },
+            Transaction {
+                gas_price: 1.into(),
+                ..tx(Some(contract(0)), 0, 0, vec![])
+            },
+            Transaction {
+                from: contract(0),
+                ..tx(Some(sender()), 0, 0, vec![])
+            },
+        ] {
+            assert_same_trace(config(vec![bytecode! { STOP }], vec![tx]));
+        }
+    }
+}
@@ -0,0 +1,286 @@
+//! Pure Rust tracer, which executes the transactions of a [`TraceConfig`] the
+//! same way `geth_utils::trace` does with the rules of the hardfork of the
+//! config, so that the traces can be generated without the Go toolchain.
+//! Unlike the geth tracer, it also supports Shanghai.
+
+mod interpreter;
+mod state;
+
+use crate::TraceConfig;
+use eth_types::{
+    evm_types::{Gas, GasCost, Hardfork, MAX_INIT_CODE_SIZE},
+    geth_types::Transaction,
+    precompile::PrecompileCalls,
+    Address, Error, GethExecTrace, Word,
+};
+use ethers_core::utils::to_checksum;
+use interpreter::{BlockContext, Evm};
+use state::{StateDB, EMPTY_CODE_HASH};
+use strum::IntoEnumIterator;
+
+/// Gas per zero byte of the transaction data.
+const TX_DATA_ZERO_GAS: u64 = 4;
+/// Gas per non zero byte of the transaction data, as specified by
+/// [EIP-2028](https://eips.ethereum.org/EIPS/eip-2028).
+const TX_DATA_NON_ZERO_GAS: u64 = 16;
+
+/// Creates a trace for the specified config
+pub(crate) fn trace(config: &TraceConfig) -> Result<Vec<GethExecTrace>, Error> {
+    trace_block(config)
+        .map_err(|error| Error::TracingError(format!("Failed to run Trace, err: {}", error)))
+}
+
+fn trace_block(config: &TraceConfig) -> Result<Vec<GethExecTrace>, String> {
+    let block_gas_limit = config.block_constants.gas_limit.low_u64();
+    let txs_gas_limit = config
+        .transactions
+        .iter()
+        .fold(0u64, |acc, tx| acc.wrapping_add(tx.gas_limit.low_u64()));
+    if txs_gas_limit > block_gas_limit {
+        return Err(format!(
+            "txs total gas: {} Exceeds block gas limit: {}",
+            txs_gas_limit, block_gas_limit
+        ));
+    }
+
+    let block = BlockContext {
+        coinbase: config.block_constants.coinbase,
+        timestamp: config.block_constants.timestamp,
+        number: config.block_constants.number.low_u64(),
+        difficulty: config.block_constants.difficulty,
+        gas_limit: block_gas_limit,
+        base_fee: config.block_constants.base_fee,
+        chain_id: config.chain_id,
+        history_hashes: config.history_hashes.clone(),
+        hardfork: config.hardfork,
+    };
+
+    // Empty accounts are deleted by geth when finalising the initial state.
+    let mut state = StateDB::default();
+    for (address, account) in config.accounts.iter() {
+        let nonce = account.nonce.low_u64();
+        if nonce == 0 && account.balance.is_zero() && account.code.is_empty() {
+            continue;
+        }
+        state.insert_account(
+            *address,
+            nonce,
+            account.balance,
+            account.code.clone(),
+            account.storage.clone(),
+        );
+    }
+
+    config
+        .transactions
+        .iter()
+        .enumerate()
+        .map(|(index, tx)| {
+            let trace = apply_transaction(config, &block, &mut state, tx).map_err(|error| {
+                format!("Failed to apply config.Transactions[{}]: {}", index, error)
+            })?;
+            state.finalise();
+            Ok(trace)
+        })
+        .collect()
+}
+
+/// Apply the transaction to the state following the state transition of geth,
+/// returning the trace of its execution.
+fn apply_transaction(
+    config: &TraceConfig,
+    block: &BlockContext,
+    state: &mut StateDB,
+    tx: &Transaction,
+) -> Result<GethExecTrace, String> {
+    // Only dynamic fee transactions are priced by the fee caps, the gas price of
+    // which is the effective gas price, the others are treated as legacy type.
+    let (gas_price, gas_fee_cap, gas_tip_cap) = if tx.is_eip1559() {
+        (tx.gas_price, tx.gas_fee_cap, tx.gas_tip_cap)
+    } else {
+        (tx.gas_price, tx.gas_price, tx.gas_price)
+    };
+    let sender = tx.from;
+    let gas_limit = tx.gas_limit.low_u64();
+
+    // Check the nonce and that the sender is an EOA.
+    let nonce = tx.nonce.low_u64();
+    let state_nonce = state.nonce(&sender);
+    if state_nonce < nonce {
+        return Err(format!(
+            "nonce too high: address {}, tx: {} state: {}",
+            to_checksum(&sender, None),
+            nonce,
+            state_nonce
+        ));
+    }
+    if state_nonce > nonce {
+        return Err(format!(
+            "nonce too low: address {}, tx: {} state: {}",
+            to_checksum(&sender, None),
+            nonce,
+            state_nonce
+        ));
+    }
+    if state_nonce == u64::MAX {
+        return Err(format!(
+            "nonce has max value: address {}, nonce: {}",
+            to_checksum(&sender, None),
+            state_nonce
+        ));
+    }
+    let code_hash = state.code_hash(&sender);
+    if !code_hash.is_zero() && code_hash != *EMPTY_CODE_HASH {
+        return Err(format!(
+            "sender not an eoa: address {}, codehash: {:?}",
+            to_checksum(&sender, None),
+            code_hash
+        ));
+    }
+
+    // The base fee is not enforced when both fee caps are zero.
+    if !(gas_fee_cap.is_zero() && gas_tip_cap.is_zero()) && gas_fee_cap < block.base_fee {
+        return Err(format!(
+            "max fee per gas less than block base fee: address {}, maxFeePerGas: {} baseFee: {}",
+            to_checksum(&sender, None),
+            gas_fee_cap,
+            block.base_fee
+        ));
+    }
+
+    // Buy the gas.
+    let balance = state.balance(&sender);
+    let balance_check = Word::from(gas_limit) * gas_fee_cap + tx.value;
+    if balance < balance_check {
+        return Err(format!(
+            "insufficient funds for gas * price + value: address {} have {} want {}",
+            to_checksum(&sender, None),
+            balance,
+            balance_check
+        ));
+    }
+    state.sub_balance(sender, Word::from(gas_limit) * gas_price);
+
+    let intrinsic_gas = intrinsic_gas(tx, block.hardfork);
+    if gas_limit < intrinsic_gas {
+        return Err(format!(
+            "intrinsic gas too low: have {}, want {}",
+            gas_limit, intrinsic_gas
+        ));
+    }
+    let gas = gas_limit - intrinsic_gas;
+
+    if !tx.value.is_zero() && state.balance(&sender) < tx.value {
+        return Err(format!(
+            "insufficient funds for transfer: address {}",
+            to_checksum(&sender, None)
+        ));
+    }
+
+    if tx.to.is_none()
+        && block.hardfork.is_eip3860_enabled()
+        && tx.call_data.len() as u64 > MAX_INIT_CODE_SIZE
+    {
+        return Err(format!(
+            "max initcode size exceeded: code size {} limit {}",
+            tx.call_data.len(),
+            MAX_INIT_CODE_SIZE
+        ));
+    }
+
+    let access_list = tx
+        .access_list
+        .iter()
+        .flat_map(|access_list| access_list.0.iter())
+        .map(|item| {
+            let keys = item
+                .storage_keys
+                .iter()
+                .map(|key| Word::from_big_endian(key.as_bytes()))
+                .collect();
+            (item.address, keys)
+        })
+        .collect::<Vec<_>>();
+    // The coinbase is warm since EIP-3651.
+    let coinbase = block
+        .hardfork
+        .is_eip3651_enabled()
+        .then_some(block.coinbase);
+    state.prepare_access_list(
+        sender,
+        coinbase,
+        tx.to,
+        PrecompileCalls::iter().map(Address::from),
+        access_list,
+    );
+
+    let mut evm = Evm::new(
+        block,
+        state,
+        sender,
+        gas_price,
+        config.logger_config.clone(),
+    );
+    let (output, gas_left, result) = match tx.to {
+        None => {
+            let (output, _, gas_left, result) =
+                evm.create(sender, tx.call_data.to_vec(), gas, tx.value);
+            (output, gas_left, result)
+        }
+        Some(to) => {
+            evm.state.set_nonce(sender, nonce + 1);
+            evm.call(sender, to, tx.call_data.to_vec(), gas, tx.value)
+        }
+    };
+    let struct_logs = evm.into_steps();
+
+    // Refund the gas left and pay the coinbase.
+    let gas_used = gas_limit - gas_left;
+    let refund = (gas_used / block.hardfork.max_refund_quotient()).min(state.refund());
+    let gas_left = gas_left + refund;
+    state.add_balance(sender, Word::from(gas_left) * gas_price);
+    let gas_used = gas_limit - gas_left;
+    if !block.hardfork.is_eip1559_enabled() {
+        state.add_balance(block.coinbase, Word::from(gas_used) * gas_price);
+    } else if gas_fee_cap >= block.base_fee {
+        let effective_tip = gas_tip_cap.min(gas_fee_cap - block.base_fee);
+        state.add_balance(block.coinbase, Word::from(gas_used) * effective_tip);
+    } else {
+        // geth adds a negative amount when the fee cap is below the base fee,
+        // which only happens when both fee caps are zero.
+        state.sub_balance(
+            block.coinbase,
+            Word::from(gas_used) * (block.base_fee - gas_fee_cap),
+        );
+    }
+
+    Ok(GethExecTrace {
+        gas: Gas(gas_used),
+        failed: result.is_err(),
+        return_value: hex::encode(output),
+        struct_logs,
+    })
+}
+
+/// Gas charged for the transaction before its execution.
+fn intrinsic_gas(tx: &Transaction, hardfork: Hardfork) -> u64 {
+    let mut gas = if tx.to.is_none() {
+        GasCost::CREATION_TX.as_u64() + hardfork.init_code_gas_cost(tx.call_data.len() as u64)
+    } else {
+        GasCost::TX.as_u64()
+    };
+    for byte in tx.call_data.iter() {
+        gas += if *byte == 0 {
+            TX_DATA_ZERO_GAS
+        } else {
+            TX_DATA_NON_ZERO_GAS
+        };
+    }
+    if let Some(access_list) = &tx.access_list {
+        for item in access_list.0.iter() {
+            gas += GasCost::ACCESS_LIST_ADDRESS.as_u64()
+                + item.storage_keys.len() as u64 * GasCost::ACCESS_LIST_STORAGE_KEY.as_u64();
+        }
+    }
+    gas
+}
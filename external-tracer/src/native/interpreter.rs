@@ -0,0 +1,1513 @@
+//! EVM interpreter following the London rules of geth, which records a
+//! [`GethExecStep`] before the execution of each opcode the same way the
+//! struct logger of geth does.
+
+use super::state::StateDB;
+use crate::LoggerConfig;
+use eth_types::{
+    evm_types::{
+        Gas, GasCost, Hardfork, Memory, OpcodeId, ProgramCounter, Stack, Storage,
+        GAS_STIPEND_CALL_WITH_VALUE, MAX_CODE_SIZE, MAX_INIT_CODE_SIZE,
+    },
+    precompile::PrecompileCalls,
+    Address, Bytes, GethExecStep, ToAddress, ToBigEndian, ToWord, Word, U512,
+};
+use ethers_core::utils::{get_contract_address, get_create2_address, keccak256};
+use std::{collections::HashMap, fmt};
+
+/// Maximum depth of the call stack.
+const CALL_CREATE_DEPTH: usize = 1024;
+/// Maximum size of the stack.
+const STACK_LIMIT: usize = 1024;
+/// Gas per byte of a log's data.
+const LOG_DATA_GAS: u64 = 8;
+/// Gas per topic of a log.
+const LOG_TOPIC_GAS: u64 = 375;
+/// Gas per byte of the exponent of EXP.
+const EXP_BYTE_GAS: u64 = 50;
+
+/// Context of the block the transactions are executed in.
+#[derive(Debug, Clone)]
+pub(crate) struct BlockContext {
+    pub(crate) coinbase: Address,
+    pub(crate) timestamp: Word,
+    pub(crate) number: u64,
+    pub(crate) difficulty: Word,
+    pub(crate) gas_limit: u64,
+    pub(crate) base_fee: Word,
+    pub(crate) chain_id: Word,
+    /// Most recent block hashes, where the latest one is the last.
+    pub(crate) history_hashes: Vec<Word>,
+    pub(crate) hardfork: Hardfork,
+}
+
+impl BlockContext {
+    fn block_hash(&self, number: u64) -> Word {
+        if self.number > number && self.number - number <= 256 {
+            (self.history_hashes.len() as u64)
+                .checked_sub(self.number - number)
+                .and_then(|index| self.history_hashes.get(index as usize))
+                .cloned()
+                .unwrap_or_default()
+        } else {
+            Word::zero()
+        }
+    }
+}
+
+/// Error raised by the execution of a call frame, with the same message as
+/// the corresponding error of geth.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum VmError {
+    OutOfGas,
+    CodeStoreOutOfGas,
+    Depth,
+    InsufficientBalance,
+    ContractAddressCollision,
+    ExecutionReverted,
+    MaxCodeSizeExceeded,
+    InvalidJump,
+    WriteProtection,
+    ReturnDataOutOfBounds,
+    GasUintOverflow,
+    InvalidCode,
+    NonceUintOverflow,
+    PrecompileFailed,
+    StackUnderflow { stack_len: usize, required: usize },
+    StackOverflow { stack_len: usize, limit: usize },
+    InvalidOpcode(OpcodeId),
+}
+
+impl fmt::Display for VmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OutOfGas => write!(f, "out of gas"),
+            Self::CodeStoreOutOfGas => write!(f, "contract creation code storage out of gas"),
+            Self::Depth => write!(f, "max call depth exceeded"),
+            Self::InsufficientBalance => write!(f, "insufficient balance for transfer"),
+            Self::ContractAddressCollision => write!(f, "contract address collision"),
+            Self::ExecutionReverted => write!(f, "execution reverted"),
+            Self::MaxCodeSizeExceeded => write!(f, "max code size exceeded"),
+            Self::InvalidJump => write!(f, "invalid jump destination"),
+            Self::WriteProtection => write!(f, "write protection"),
+            Self::ReturnDataOutOfBounds => write!(f, "return data out of bounds"),
+            Self::GasUintOverflow => write!(f, "gas uint64 overflow"),
+            Self::InvalidCode => write!(f, "invalid code: must not begin with 0xef"),
+            Self::NonceUintOverflow => write!(f, "nonce uint64 overflow"),
+            Self::PrecompileFailed => write!(f, "precompiled contract failed"),
+            Self::StackUnderflow {
+                stack_len,
+                required,
+            } => write!(f, "stack underflow ({} <=> {})", stack_len, required),
+            Self::StackOverflow { stack_len, limit } => {
+                write!(f, "stack limit reached {} ({})", stack_len, limit)
+            }
+            Self::InvalidOpcode(OpcodeId::INVALID(0xfe)) => write!(f, "invalid opcode: INVALID"),
+            Self::InvalidOpcode(opcode) => write!(
+                f,
+                "invalid opcode: opcode {:#x} not defined",
+                opcode.as_u8()
+            ),
+        }
+    }
+}
+
+/// Outcome of a call frame: the returned data, the gas left and the error
+/// which made the frame fail, if any.
+pub(crate) type CallResult = (Vec<u8>, u64, Result<(), VmError>);
+
+/// Call frame executing code.
+struct Contract {
+    caller: Address,
+    address: Address,
+    value: Word,
+    input: Vec<u8>,
+    code: Bytes,
+    /// Whether each position of the code is an opcode rather than push data.
+    is_code: Vec<bool>,
+    gas: u64,
+}
+
+impl Contract {
+    fn new(
+        caller: Address,
+        address: Address,
+        value: Word,
+        input: Vec<u8>,
+        code: Bytes,
+        gas: u64,
+    ) -> Self {
+        let mut is_code = vec![false; code.len()];
+        let mut pc = 0;
+        while pc < code.len() {
+            is_code[pc] = true;
+            pc += 1 + OpcodeId::from(code[pc]).data_len();
+        }
+        Self {
+            caller,
+            address,
+            value,
+            input,
+            code,
+            is_code,
+            gas,
+        }
+    }
+
+    fn opcode(&self, pc: usize) -> OpcodeId {
+        OpcodeId::from(self.code.get(pc).cloned().unwrap_or_default())
+    }
+
+    fn use_gas(&mut self, gas: u64) -> bool {
+        if self.gas < gas {
+            return false;
+        }
+        self.gas -= gas;
+        true
+    }
+
+    fn valid_jump_destination(&self, dest: Word) -> bool {
+        if dest.bits() > 64 || dest.as_u64() >= self.code.len() as u64 {
+            return false;
+        }
+        let dest = dest.as_usize();
+        self.code[dest] == OpcodeId::JUMPDEST.as_u8() && self.is_code[dest]
+    }
+}
+
+/// Memory of a call frame.
+#[derive(Default)]
+struct FrameMemory {
+    data: Vec<u8>,
+    /// Total gas paid for the current memory size.
+    last_gas_cost: u64,
+}
+
+impl FrameMemory {
+    fn resize(&mut self, size: u64) {
+        if (self.data.len() as u64) < size {
+            self.data.resize(size as usize, 0);
+        }
+    }
+
+    fn read(&self, offset: Word, size: Word) -> Vec<u8> {
+        if size.is_zero() {
+            return Vec::new();
+        }
+        let offset = offset.as_usize();
+        self.data[offset..offset + size.as_usize()].to_vec()
+    }
+
+    /// Copy `value` into the memory at `offset`, truncated to `size` bytes.
+    fn set(&mut self, offset: Word, size: Word, value: &[u8]) {
+        if size.is_zero() {
+            return;
+        }
+        let (offset, size) = (offset.as_usize(), size.as_usize());
+        let length = size.min(value.len());
+        self.data[offset..offset + length].copy_from_slice(&value[..length]);
+    }
+
+    /// Gas cost of expanding the memory to `size` bytes.
+    fn expansion_gas_cost(&mut self, size: u64) -> Result<u64, VmError> {
+        if size == 0 {
+            return Ok(0);
+        }
+        // Larger sizes overflow the quadratic cost.
+        if size > 0x1FFFFFFFE0 {
+            return Err(VmError::GasUintOverflow);
+        }
+        let words = to_word_size(size);
+        if words * 32 <= self.data.len() as u64 {
+            return Ok(0);
+        }
+        let total_gas_cost = words * GasCost::MEMORY_EXPANSION_LINEAR_COEFF.as_u64()
+            + words * words / GasCost::MEMORY_EXPANSION_QUAD_DENOMINATOR.as_u64();
+        let gas_cost = total_gas_cost - self.last_gas_cost;
+        self.last_gas_cost = total_gas_cost;
+        Ok(gas_cost)
+    }
+}
+
+/// How an execution step ends.
+enum Halt {
+    Continue,
+    Jump(usize),
+    Return(Vec<u8>),
+    Revert(Vec<u8>),
+}
+
+/// Struct logger recording the execution steps of a transaction.
+struct StructLogger {
+    config: LoggerConfig,
+    steps: Vec<GethExecStep>,
+    /// Storage slots read or written by SLOAD and SSTORE of each account.
+    storage: HashMap<Address, HashMap<Word, Word>>,
+}
+
+/// EVM executing the transactions of a block.
+pub(crate) struct Evm<'a> {
+    block: &'a BlockContext,
+    origin: Address,
+    gas_price: Word,
+    pub(crate) state: &'a mut StateDB,
+    logger: StructLogger,
+    depth: usize,
+    read_only: bool,
+    /// Gas given to the callee, computed by the dynamic gas cost of the call.
+    call_gas_temp: u64,
+}
+
+impl<'a> Evm<'a> {
+    pub(crate) fn new(
+        block: &'a BlockContext,
+        state: &'a mut StateDB,
+        origin: Address,
+        gas_price: Word,
+        logger_config: LoggerConfig,
+    ) -> Self {
+        Self {
+            block,
+            origin,
+            gas_price,
+            state,
+            logger: StructLogger {
+                config: logger_config,
+                steps: Vec::new(),
+                storage: HashMap::new(),
+            },
+            depth: 0,
+            read_only: false,
+            call_gas_temp: 0,
+        }
+    }
+
+    /// Return the steps recorded so far.
+    pub(crate) fn into_steps(self) -> Vec<GethExecStep> {
+        self.logger.steps
+    }
+
+    fn can_transfer(&self, address: &Address, value: Word) -> bool {
+        self.state.balance(address) >= value
+    }
+
+    /// Execute the code at `address` with `input`, transferring `value` from
+    /// `caller`.
+    pub(crate) fn call(
+        &mut self,
+        caller: Address,
+        address: Address,
+        input: Vec<u8>,
+        gas: u64,
+        value: Word,
+    ) -> CallResult {
+        if self.depth > CALL_CREATE_DEPTH {
+            return (Vec::new(), gas, Err(VmError::Depth));
+        }
+        if !value.is_zero() && !self.can_transfer(&caller, value) {
+            return (Vec::new(), gas, Err(VmError::InsufficientBalance));
+        }
+        let snapshot = self.state.snapshot();
+        if !self.state.exist(&address) {
+            if PrecompileCalls::try_from(&address).is_err() && value.is_zero() {
+                return (Vec::new(), gas, Ok(()));
+            }
+            self.state.create_account(address);
+        }
+        self.state.transfer(caller, address, value);
+
+        let code = self.state.code(&address);
+        let contract = Contract::new(caller, address, value, input, code, gas);
+        self.run_call(snapshot, address, contract, false)
+    }
+
+    /// Execute the code at `address` in the context of `caller`, transferring
+    /// `value` from `caller` to itself.
+    fn call_code(
+        &mut self,
+        caller: Address,
+        address: Address,
+        input: Vec<u8>,
+        gas: u64,
+        value: Word,
+    ) -> CallResult {
+        if self.depth > CALL_CREATE_DEPTH {
+            return (Vec::new(), gas, Err(VmError::Depth));
+        }
+        if !self.can_transfer(&caller, value) {
+            return (Vec::new(), gas, Err(VmError::InsufficientBalance));
+        }
+        let snapshot = self.state.snapshot();
+        let code = self.state.code(&address);
+        let contract = Contract::new(caller, caller, value, input, code, gas);
+        self.run_call(snapshot, address, contract, false)
+    }
+
+    /// Execute the code at `address` in the context of `parent`, keeping its
+    /// caller and value.
+    fn delegate_call(
+        &mut self,
+        parent: &Contract,
+        address: Address,
+        input: Vec<u8>,
+        gas: u64,
+    ) -> CallResult {
+        if self.depth > CALL_CREATE_DEPTH {
+            return (Vec::new(), gas, Err(VmError::Depth));
+        }
+        let snapshot = self.state.snapshot();
+        let code = self.state.code(&address);
+        let contract = Contract::new(
+            parent.caller,
+            parent.address,
+            parent.value,
+            input,
+            code,
+            gas,
+        );
+        self.run_call(snapshot, address, contract, false)
+    }
+
+    /// Execute the code at `address` disallowing any state modification.
+    fn static_call(
+        &mut self,
+        caller: Address,
+        address: Address,
+        input: Vec<u8>,
+        gas: u64,
+    ) -> CallResult {
+        if self.depth > CALL_CREATE_DEPTH {
+            return (Vec::new(), gas, Err(VmError::Depth));
+        }
+        let snapshot = self.state.snapshot();
+        // Add zero balance to touch the account.
+        self.state.add_balance(address, Word::zero());
+        let code = self.state.code(&address);
+        let contract = Contract::new(caller, address, Word::zero(), input, code, gas);
+        self.run_call(snapshot, address, contract, true)
+    }
+
+    /// Run the precompile at `code_address` or the code of the contract,
+    /// reverting the state to `snapshot` when it fails.
+    fn run_call(
+        &mut self,
+        snapshot: usize,
+        code_address: Address,
+        mut contract: Contract,
+        read_only: bool,
+    ) -> CallResult {
+        let (output, mut gas, result) = match PrecompileCalls::try_from(&code_address) {
+            Ok(precompile) => {
+                let required_gas = precompile.required_gas(&contract.input);
+                if contract.gas < required_gas {
+                    (Vec::new(), 0, Err(VmError::OutOfGas))
+                } else {
+                    let gas = contract.gas - required_gas;
+                    match precompile.execute(&contract.input) {
+                        Some(output) => (output, gas, Ok(())),
+                        None => (Vec::new(), gas, Err(VmError::PrecompileFailed)),
+                    }
+                }
+            }
+            Err(_) => {
+                let (output, result) = self.run(&mut contract, read_only);
+                (output, contract.gas, result)
+            }
+        };
+        if let Err(err) = &result {
+            self.state.revert_to_snapshot(snapshot);
+            if *err != VmError::ExecutionReverted {
+                gas = 0;
+            }
+        }
+        (output, gas, result)
+    }
+
+    /// Create a contract with `init_code` at the address derived from the
+    /// nonce of `caller`.
+    pub(crate) fn create(
+        &mut self,
+        caller: Address,
+        init_code: Vec<u8>,
+        gas: u64,
+        value: Word,
+    ) -> (Vec<u8>, Address, u64, Result<(), VmError>) {
+        let address = get_contract_address(caller, self.state.nonce(&caller));
+        self.create_at(caller, init_code, gas, value, address)
+    }
+
+    /// Create a contract with `init_code` at the address derived from `salt`
+    /// as specified by [EIP-1014](https://eips.ethereum.org/EIPS/eip-1014).
+    fn create2(
+        &mut self,
+        caller: Address,
+        init_code: Vec<u8>,
+        gas: u64,
+        value: Word,
+        salt: Word,
+    ) -> (Vec<u8>, Address, u64, Result<(), VmError>) {
+        let address = get_create2_address(caller, salt.to_be_bytes().to_vec(), init_code.clone());
+        self.create_at(caller, init_code, gas, value, address)
+    }
+
+    fn create_at(
+        &mut self,
+        caller: Address,
+        init_code: Vec<u8>,
+        gas: u64,
+        value: Word,
+        address: Address,
+    ) -> (Vec<u8>, Address, u64, Result<(), VmError>) {
+        if self.depth > CALL_CREATE_DEPTH {
+            return (Vec::new(), Address::zero(), gas, Err(VmError::Depth));
+        }
+        if !self.can_transfer(&caller, value) {
+            return (
+                Vec::new(),
+                Address::zero(),
+                gas,
+                Err(VmError::InsufficientBalance),
+            );
+        }
+        let nonce = self.state.nonce(&caller);
+        if nonce == u64::MAX {
+            return (
+                Vec::new(),
+                Address::zero(),
+                gas,
+                Err(VmError::NonceUintOverflow),
+            );
+        }
+        self.state.set_nonce(caller, nonce + 1);
+        // The access list change is not reverted even if the creation fails.
+        self.state.add_address_to_access_list(address);
+        let code_hash = self.state.code_hash(&address);
+        if self.state.nonce(&address) != 0
+            || (!code_hash.is_zero() && code_hash != *super::state::EMPTY_CODE_HASH)
+        {
+            return (
+                Vec::new(),
+                Address::zero(),
+                0,
+                Err(VmError::ContractAddressCollision),
+            );
+        }
+        let snapshot = self.state.snapshot();
+        self.state.create_account(address);
+        self.state.set_nonce(address, 1);
+        self.state.transfer(caller, address, value);
+
+        let mut contract = Contract::new(
+            caller,
+            address,
+            value,
+            Vec::new(),
+            Bytes::from(init_code),
+            gas,
+        );
+        let (output, mut result) = self.run(&mut contract, false);
+
+        if result.is_ok() && output.len() as u64 > MAX_CODE_SIZE {
+            result = Err(VmError::MaxCodeSizeExceeded);
+        }
+        if result.is_ok()
+            && self.block.hardfork.is_eip3541_enabled()
+            && output.first() == Some(&0xef)
+        {
+            result = Err(VmError::InvalidCode);
+        }
+        if result.is_ok() {
+            let code_deposit_cost = output.len() as u64 * GasCost::CODE_DEPOSIT_BYTE_COST.as_u64();
+            if contract.use_gas(code_deposit_cost) {
+                self.state.set_code(address, Bytes::from(output.clone()));
+            } else {
+                result = Err(VmError::CodeStoreOutOfGas);
+            }
+        }
+        if let Err(err) = &result {
+            self.state.revert_to_snapshot(snapshot);
+            if *err != VmError::ExecutionReverted {
+                contract.gas = 0;
+            }
+        }
+        (output, address, contract.gas, result)
+    }
+
+    /// Run the code of the contract, returning the output.
+    fn run(&mut self, contract: &mut Contract, read_only: bool) -> (Vec<u8>, Result<(), VmError>) {
+        self.depth += 1;
+        let parent_read_only = self.read_only;
+        self.read_only |= read_only;
+        let result = if contract.code.is_empty() {
+            (Vec::new(), Ok(()))
+        } else {
+            self.interpret(contract)
+        };
+        self.read_only = parent_read_only;
+        self.depth -= 1;
+        result
+    }
+
+    fn interpret(&mut self, contract: &mut Contract) -> (Vec<u8>, Result<(), VmError>) {
+        let mut pc = 0;
+        let mut stack = Vec::new();
+        let mut memory = FrameMemory::default();
+        let mut return_data = Vec::new();
+        loop {
+            let mut opcode = contract.opcode(pc);
+            // Opcodes which aren't enabled yet in the hardfork are undefined.
+            if !self.block.hardfork.is_opcode_enabled(opcode) {
+                opcode = OpcodeId::INVALID(opcode.as_u8());
+            }
+            let gas = contract.gas;
+            let mut gas_cost = opcode.constant_gas_cost().as_u64();
+            if let Err(err) = self.charge_gas(opcode, contract, &stack, &mut memory, &mut gas_cost)
+            {
+                self.capture_state(
+                    pc,
+                    opcode,
+                    gas,
+                    gas_cost,
+                    contract,
+                    &stack,
+                    &memory,
+                    Some(&err),
+                );
+                return (Vec::new(), Err(err));
+            }
+            self.capture_state(pc, opcode, gas, gas_cost, contract, &stack, &memory, None);
+            match self.execute(
+                opcode,
+                contract,
+                &mut pc,
+                &mut stack,
+                &mut memory,
+                &mut return_data,
+            ) {
+                Ok(Halt::Continue) => pc += 1,
+                Ok(Halt::Jump(dest)) => pc = dest,
+                Ok(Halt::Return(output)) => return (output, Ok(())),
+                Ok(Halt::Revert(output)) => return (output, Err(VmError::ExecutionReverted)),
+                Err(err) => return (Vec::new(), Err(err)),
+            }
+        }
+    }
+
+    /// Validate the stack and charge the gas of the step, expanding the
+    /// memory when needed.  `gas_cost` is updated with the cost of the step
+    /// even if it can't be paid.
+    fn charge_gas(
+        &mut self,
+        opcode: OpcodeId,
+        contract: &mut Contract,
+        stack: &[Word],
+        memory: &mut FrameMemory,
+        gas_cost: &mut u64,
+    ) -> Result<(), VmError> {
+        let (min_stack, max_stack) = stack_bounds(opcode);
+        if stack.len() < min_stack {
+            return Err(VmError::StackUnderflow {
+                stack_len: stack.len(),
+                required: min_stack,
+            });
+        }
+        if stack.len() > max_stack {
+            return Err(VmError::StackOverflow {
+                stack_len: stack.len(),
+                limit: max_stack,
+            });
+        }
+        if !contract.use_gas(*gas_cost) {
+            return Err(VmError::OutOfGas);
+        }
+        if has_dynamic_gas(opcode) {
+            let memory_size = memory_size(opcode, stack)?;
+            let dynamic_gas_cost = self
+                .dynamic_gas_cost(opcode, contract, stack, memory, memory_size)
+                .map_err(|_| VmError::OutOfGas)?;
+            *gas_cost += dynamic_gas_cost;
+            if !contract.use_gas(dynamic_gas_cost) {
+                return Err(VmError::OutOfGas);
+            }
+            memory.resize(memory_size);
+        }
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn capture_state(
+        &mut self,
+        pc: usize,
+        opcode: OpcodeId,
+        gas: u64,
+        gas_cost: u64,
+        contract: &Contract,
+        stack: &[Word],
+        memory: &FrameMemory,
+        error: Option<&VmError>,
+    ) {
+        let config = &self.logger.config;
+        let mut storage = Storage::empty();
+        if !config.disable_storage && matches!(opcode, OpcodeId::SLOAD | OpcodeId::SSTORE) {
+            let entry = match opcode {
+                OpcodeId::SLOAD if !stack.is_empty() => {
+                    let key = back(stack, 0);
+                    Some((key, self.state.storage(&contract.address, &key)))
+                }
+                OpcodeId::SSTORE if stack.len() >= 2 => Some((back(stack, 0), back(stack, 1))),
+                _ => None,
+            };
+            let account_storage = self.logger.storage.entry(contract.address).or_default();
+            if let Some((key, value)) = entry {
+                account_storage.insert(key, value);
+                storage = Storage::new(account_storage.clone());
+            }
+        }
+        let step = GethExecStep {
+            pc: ProgramCounter(pc),
+            op: opcode,
+            gas: Gas(gas),
+            gas_cost: GasCost(gas_cost),
+            refund: Gas(self.state.refund()),
+            depth: self.depth as u16,
+            error: error.map(ToString::to_string),
+            stack: if config.disable_stack {
+                Stack::new()
+            } else {
+                Stack::from(stack.to_vec())
+            },
+            memory: if config.enable_memory {
+                Memory::from(memory.data.clone())
+            } else {
+                Memory::new()
+            },
+            storage,
+        };
+        self.logger.steps.push(step);
+    }
+
+    fn dynamic_gas_cost(
+        &mut self,
+        opcode: OpcodeId,
+        contract: &mut Contract,
+        stack: &[Word],
+        memory: &mut FrameMemory,
+        memory_size: u64,
+    ) -> Result<u64, VmError> {
+        let cold_account_access_cost =
+            GasCost::COLD_ACCOUNT_ACCESS.as_u64() - GasCost::WARM_ACCESS.as_u64();
+        Ok(match opcode {
+            OpcodeId::EXP => (back(stack, 1).bits() as u64 + 7) / 8 * EXP_BYTE_GAS,
+            OpcodeId::SHA3 => {
+                let word_gas_cost = to_word_size(to_u64(back(stack, 1))?)
+                    .checked_mul(GasCost::COPY_SHA3.as_u64())
+                    .ok_or(VmError::GasUintOverflow)?;
+                checked_add(memory.expansion_gas_cost(memory_size)?, word_gas_cost)?
+            }
+            OpcodeId::CREATE | OpcodeId::CREATE2 => {
+                let gas_cost = memory.expansion_gas_cost(memory_size)?;
+                // The initialization code is hashed by CREATE2, and limited
+                // and charged per word by EIP-3860.
+                let size = to_u64(back(stack, 2))?;
+                let mut word_gas = 0;
+                if opcode == OpcodeId::CREATE2 {
+                    word_gas += GasCost::COPY_SHA3.as_u64();
+                }
+                if self.block.hardfork.is_eip3860_enabled() {
+                    if size > MAX_INIT_CODE_SIZE {
+                        return Err(VmError::GasUintOverflow);
+                    }
+                    word_gas += GasCost::INIT_CODE_WORD.as_u64();
+                }
+                let word_gas_cost = to_word_size(size)
+                    .checked_mul(word_gas)
+                    .ok_or(VmError::GasUintOverflow)?;
+                checked_add(gas_cost, word_gas_cost)?
+            }
+            OpcodeId::CALLDATACOPY | OpcodeId::CODECOPY | OpcodeId::RETURNDATACOPY => {
+                memory_copier_gas_cost(memory, memory_size, back(stack, 2))?
+            }
+            OpcodeId::EXTCODECOPY => {
+                let gas_cost = memory_copier_gas_cost(memory, memory_size, back(stack, 3))?;
+                let address = back(stack, 0).to_address();
+                if self.state.address_in_access_list(&address) {
+                    gas_cost
+                } else {
+                    self.state.add_address_to_access_list(address);
+                    checked_add(gas_cost, cold_account_access_cost)?
+                }
+            }
+            OpcodeId::BALANCE | OpcodeId::EXTCODESIZE | OpcodeId::EXTCODEHASH => {
+                let address = back(stack, 0).to_address();
+                if self.state.address_in_access_list(&address) {
+                    0
+                } else {
+                    self.state.add_address_to_access_list(address);
+                    cold_account_access_cost
+                }
+            }
+            OpcodeId::MLOAD
+            | OpcodeId::MSTORE
+            | OpcodeId::MSTORE8
+            | OpcodeId::RETURN
+            | OpcodeId::REVERT => memory.expansion_gas_cost(memory_size)?,
+            OpcodeId::SLOAD => {
+                let key = back(stack, 0);
+                if self.state.slot_in_access_list(&contract.address, &key).1 {
+                    GasCost::WARM_ACCESS.as_u64()
+                } else {
+                    self.state.add_slot_to_access_list(contract.address, key);
+                    GasCost::COLD_SLOAD.as_u64()
+                }
+            }
+            OpcodeId::SSTORE => self.sstore_gas_cost(contract, stack)?,
+            OpcodeId::LOG0 | OpcodeId::LOG1 | OpcodeId::LOG2 | OpcodeId::LOG3 | OpcodeId::LOG4 => {
+                let size = to_u64(back(stack, 1))?;
+                let topics = (opcode.as_u8() - OpcodeId::LOG0.as_u8()) as u64;
+                let gas_cost = checked_add(
+                    memory.expansion_gas_cost(memory_size)?,
+                    GasCost::LOG.as_u64() + topics * LOG_TOPIC_GAS,
+                )?;
+                checked_add(
+                    gas_cost,
+                    size.checked_mul(LOG_DATA_GAS)
+                        .ok_or(VmError::GasUintOverflow)?,
+                )?
+            }
+            OpcodeId::CALL | OpcodeId::CALLCODE | OpcodeId::DELEGATECALL | OpcodeId::STATICCALL => {
+                let address = back(stack, 1).to_address();
+                let is_warm = self.state.address_in_access_list(&address);
+                if !is_warm {
+                    self.state.add_address_to_access_list(address);
+                    // Charge the cold access cost before computing the gas
+                    // available to the callee.
+                    if !contract.use_gas(cold_account_access_cost) {
+                        return Err(VmError::OutOfGas);
+                    }
+                }
+                let gas_cost = self.call_gas_cost(opcode, contract, stack, memory, memory_size)?;
+                if is_warm {
+                    gas_cost
+                } else {
+                    contract.gas += cold_account_access_cost;
+                    gas_cost + cold_account_access_cost
+                }
+            }
+            OpcodeId::SELFDESTRUCT => {
+                if !self.state.has_suicided(&contract.address) {
+                    self.state
+                        .add_refund(self.block.hardfork.selfdestruct_refund().as_u64());
+                }
+                let address = back(stack, 0).to_address();
+                let mut gas_cost = 0;
+                if !self.state.address_in_access_list(&address) {
+                    self.state.add_address_to_access_list(address);
+                    gas_cost = GasCost::COLD_ACCOUNT_ACCESS.as_u64();
+                }
+                if self.state.empty(&address) && !self.state.balance(&contract.address).is_zero() {
+                    gas_cost += GasCost::NEW_ACCOUNT.as_u64();
+                }
+                gas_cost
+            }
+            _ => unreachable!("{:?} has no dynamic gas cost", opcode),
+        })
+    }
+
+    /// Gas cost of SSTORE as specified by
+    /// [EIP-2200](https://eips.ethereum.org/EIPS/eip-2200),
+    /// [EIP-2929](https://eips.ethereum.org/EIPS/eip-2929) and
+    /// [EIP-3529](https://eips.ethereum.org/EIPS/eip-3529), which also updates
+    /// the refund counter.
+    fn sstore_gas_cost(&mut self, contract: &Contract, stack: &[Word]) -> Result<u64, VmError> {
+        if contract.gas <= GasCost::SSTORE_SENTRY.as_u64() {
+            return Err(VmError::OutOfGas);
+        }
+        let (key, value) = (back(stack, 0), back(stack, 1));
+        let address = contract.address;
+        let current = self.state.storage(&address, &key);
+        let mut gas_cost = 0;
+        if !self.state.slot_in_access_list(&address, &key).1 {
+            gas_cost = GasCost::COLD_SLOAD.as_u64();
+            self.state.add_slot_to_access_list(address, key);
+        }
+
+        let warm_access = GasCost::WARM_ACCESS.as_u64();
+        let clears_refund = self.block.hardfork.sstore_clears_schedule().as_u64();
+        if current == value {
+            return Ok(gas_cost + warm_access);
+        }
+        let original = self.state.committed_storage(&address, &key);
+        if original == current {
+            if original.is_zero() {
+                return Ok(gas_cost + GasCost::SSTORE_SET.as_u64());
+            }
+            if value.is_zero() {
+                self.state.add_refund(clears_refund);
+            }
+            return Ok(gas_cost + GasCost::SSTORE_RESET.as_u64());
+        }
+        if !original.is_zero() {
+            if current.is_zero() {
+                self.state.sub_refund(clears_refund);
+            } else if value.is_zero() {
+                self.state.add_refund(clears_refund);
+            }
+        }
+        if original == value {
+            if original.is_zero() {
+                self.state
+                    .add_refund(GasCost::SSTORE_SET.as_u64() - warm_access);
+            } else {
+                self.state
+                    .add_refund(GasCost::SSTORE_RESET.as_u64() - warm_access);
+            }
+        }
+        Ok(gas_cost + warm_access)
+    }
+
+    /// Gas cost of a call excluding the cold account access, including the gas
+    /// given to the callee which is stored in `call_gas_temp`.
+    fn call_gas_cost(
+        &mut self,
+        opcode: OpcodeId,
+        contract: &Contract,
+        stack: &[Word],
+        memory: &mut FrameMemory,
+        memory_size: u64,
+    ) -> Result<u64, VmError> {
+        let mut gas_cost = 0;
+        if matches!(opcode, OpcodeId::CALL | OpcodeId::CALLCODE) && !back(stack, 2).is_zero() {
+            if opcode == OpcodeId::CALL && self.state.empty(&back(stack, 1).to_address()) {
+                gas_cost += GasCost::NEW_ACCOUNT.as_u64();
+            }
+            gas_cost += GasCost::CALL_WITH_VALUE.as_u64();
+        }
+        let gas_cost = checked_add(gas_cost, memory.expansion_gas_cost(memory_size)?)?;
+
+        // Apply the 63/64 rule of EIP-150.
+        let available_gas = contract.gas.wrapping_sub(gas_cost);
+        let capped_gas = available_gas - available_gas / 64;
+        let requested_gas = back(stack, 0);
+        self.call_gas_temp = if requested_gas.bits() <= 64 && requested_gas.as_u64() <= capped_gas {
+            requested_gas.as_u64()
+        } else {
+            capped_gas
+        };
+        checked_add(gas_cost, self.call_gas_temp)
+    }
+
+    /// Execute the opcode of a step whose gas has been charged.
+    fn execute(
+        &mut self,
+        opcode: OpcodeId,
+        contract: &mut Contract,
+        pc: &mut usize,
+        stack: &mut Vec<Word>,
+        memory: &mut FrameMemory,
+        return_data: &mut Vec<u8>,
+    ) -> Result<Halt, VmError> {
+        macro_rules! pop {
+            () => {
+                stack.pop().unwrap()
+            };
+        }
+
+        match opcode {
+            OpcodeId::STOP => return Ok(Halt::Return(Vec::new())),
+            OpcodeId::ADD => {
+                let (a, b) = (pop!(), pop!());
+                stack.push(a.overflowing_add(b).0);
+            }
+            OpcodeId::MUL => {
+                let (a, b) = (pop!(), pop!());
+                stack.push(a.overflowing_mul(b).0);
+            }
+            OpcodeId::SUB => {
+                let (a, b) = (pop!(), pop!());
+                stack.push(a.overflowing_sub(b).0);
+            }
+            OpcodeId::DIV => {
+                let (a, b) = (pop!(), pop!());
+                stack.push(if b.is_zero() { b } else { a / b });
+            }
+            OpcodeId::SDIV => {
+                let (a, b) = (pop!(), pop!());
+                stack.push(if b.is_zero() {
+                    b
+                } else {
+                    let quotient = abs(a) / abs(b);
+                    if is_negative(a) != is_negative(b) {
+                        neg(quotient)
+                    } else {
+                        quotient
+                    }
+                });
+            }
+            OpcodeId::MOD => {
+                let (a, b) = (pop!(), pop!());
+                stack.push(if b.is_zero() { b } else { a % b });
+            }
+            OpcodeId::SMOD => {
+                let (a, b) = (pop!(), pop!());
+                stack.push(if b.is_zero() {
+                    b
+                } else {
+                    let remainder = abs(a) % abs(b);
+                    if is_negative(a) {
+                        neg(remainder)
+                    } else {
+                        remainder
+                    }
+                });
+            }
+            OpcodeId::ADDMOD | OpcodeId::MULMOD => {
+                let (a, b, n) = (pop!(), pop!(), pop!());
+                stack.push(if n.is_zero() {
+                    n
+                } else {
+                    let (a, b) = (U512::from(a), U512::from(b));
+                    let result = if opcode == OpcodeId::ADDMOD {
+                        (a + b) % U512::from(n)
+                    } else {
+                        (a * b) % U512::from(n)
+                    };
+                    Word([result.0[0], result.0[1], result.0[2], result.0[3]])
+                });
+            }
+            OpcodeId::EXP => {
+                let (base, exponent) = (pop!(), pop!());
+                stack.push(base.overflowing_pow(exponent).0);
+            }
+            OpcodeId::SIGNEXTEND => {
+                let (index, value) = (pop!(), pop!());
+                stack.push(if index < Word::from(31) {
+                    let bit = index.as_usize() * 8 + 7;
+                    let mask = (Word::one() << (bit + 1)) - 1;
+                    if value.bit(bit) {
+                        value | !mask
+                    } else {
+                        value & mask
+                    }
+                } else {
+                    value
+                });
+            }
+            OpcodeId::LT => {
+                let (a, b) = (pop!(), pop!());
+                stack.push((a < b).to_word());
+            }
+            OpcodeId::GT => {
+                let (a, b) = (pop!(), pop!());
+                stack.push((a > b).to_word());
+            }
+            OpcodeId::SLT => {
+                let (a, b) = (pop!(), pop!());
+                stack.push((flip_sign(a) < flip_sign(b)).to_word());
+            }
+            OpcodeId::SGT => {
+                let (a, b) = (pop!(), pop!());
+                stack.push((flip_sign(a) > flip_sign(b)).to_word());
+            }
+            OpcodeId::EQ => {
+                let (a, b) = (pop!(), pop!());
+                stack.push((a == b).to_word());
+            }
+            OpcodeId::ISZERO => {
+                let a = pop!();
+                stack.push(a.is_zero().to_word());
+            }
+            OpcodeId::AND => {
+                let (a, b) = (pop!(), pop!());
+                stack.push(a & b);
+            }
+            OpcodeId::OR => {
+                let (a, b) = (pop!(), pop!());
+                stack.push(a | b);
+            }
+            OpcodeId::XOR => {
+                let (a, b) = (pop!(), pop!());
+                stack.push(a ^ b);
+            }
+            OpcodeId::NOT => {
+                let a = pop!();
+                stack.push(!a);
+            }
+            OpcodeId::BYTE => {
+                let (index, value) = (pop!(), pop!());
+                stack.push(if index < Word::from(32) {
+                    Word::from(value.byte(31 - index.as_usize()))
+                } else {
+                    Word::zero()
+                });
+            }
+            OpcodeId::SHL | OpcodeId::SHR | OpcodeId::SAR => {
+                let (shift, value) = (pop!(), pop!());
+                let shift = if shift < Word::from(256) {
+                    shift.as_usize()
+                } else {
+                    256
+                };
+                stack.push(match opcode {
+                    _ if shift == 256 && opcode == OpcodeId::SAR && is_negative(value) => Word::MAX,
+                    _ if shift == 256 => Word::zero(),
+                    OpcodeId::SHL => value << shift,
+                    OpcodeId::SAR if is_negative(value) => !(!value >> shift),
+                    _ => value >> shift,
+                });
+            }
+            OpcodeId::SHA3 => {
+                let (offset, size) = (pop!(), pop!());
+                stack.push(Word::from_big_endian(&keccak256(memory.read(offset, size))));
+            }
+            OpcodeId::ADDRESS => stack.push(contract.address.to_word()),
+            OpcodeId::BALANCE => {
+                let address = pop!().to_address();
+                stack.push(self.state.balance(&address));
+            }
+            OpcodeId::ORIGIN => stack.push(self.origin.to_word()),
+            OpcodeId::CALLER => stack.push(contract.caller.to_word()),
+            OpcodeId::CALLVALUE => stack.push(contract.value),
+            OpcodeId::CALLDATALOAD => {
+                let offset = pop!();
+                stack.push(if offset.bits() <= 64 {
+                    Word::from_big_endian(&get_data(&contract.input, offset.as_u64(), 32))
+                } else {
+                    Word::zero()
+                });
+            }
+            OpcodeId::CALLDATASIZE => stack.push(Word::from(contract.input.len())),
+            OpcodeId::CALLDATACOPY | OpcodeId::CODECOPY => {
+                let (memory_offset, data_offset, size) = (pop!(), pop!(), pop!());
+                let data = if opcode == OpcodeId::CALLDATACOPY {
+                    &contract.input[..]
+                } else {
+                    &contract.code[..]
+                };
+                memory.set(
+                    memory_offset,
+                    size,
+                    &get_data(data, saturating_u64(data_offset), size.low_u64()),
+                );
+            }
+            OpcodeId::CODESIZE => stack.push(Word::from(contract.code.len())),
+            OpcodeId::GASPRICE => stack.push(self.gas_price),
+            OpcodeId::EXTCODESIZE => {
+                let address = pop!().to_address();
+                stack.push(Word::from(self.state.code(&address).len()));
+            }
+            OpcodeId::EXTCODECOPY => {
+                let (address, memory_offset, code_offset, size) = (pop!(), pop!(), pop!(), pop!());
+                let code = self.state.code(&address.to_address());
+                memory.set(
+                    memory_offset,
+                    size,
+                    &get_data(&code, saturating_u64(code_offset), size.low_u64()),
+                );
+            }
+            OpcodeId::RETURNDATASIZE => stack.push(Word::from(return_data.len())),
+            OpcodeId::RETURNDATACOPY => {
+                let (memory_offset, data_offset, size) = (pop!(), pop!(), pop!());
+                if data_offset.bits() > 64 {
+                    return Err(VmError::ReturnDataOutOfBounds);
+                }
+                let end = data_offset.overflowing_add(size).0;
+                if end.bits() > 64 || (return_data.len() as u64) < end.as_u64() {
+                    return Err(VmError::ReturnDataOutOfBounds);
+                }
+                memory.set(
+                    memory_offset,
+                    size,
+                    &return_data[data_offset.as_usize()..end.as_usize()],
+                );
+            }
+            OpcodeId::EXTCODEHASH => {
+                let address = pop!().to_address();
+                stack.push(if self.state.empty(&address) {
+                    Word::zero()
+                } else {
+                    self.state.code_hash(&address).to_word()
+                });
+            }
+            OpcodeId::BLOCKHASH => {
+                let number = pop!();
+                stack.push(if number.bits() > 64 {
+                    Word::zero()
+                } else {
+                    let lower = self.block.number.saturating_sub(256);
+                    let number = number.as_u64();
+                    if number >= lower && number < self.block.number {
+                        self.block.block_hash(number)
+                    } else {
+                        Word::zero()
+                    }
+                });
+            }
+            OpcodeId::COINBASE => stack.push(self.block.coinbase.to_word()),
+            OpcodeId::TIMESTAMP => stack.push(self.block.timestamp),
+            OpcodeId::NUMBER => stack.push(Word::from(self.block.number)),
+            OpcodeId::DIFFICULTY => stack.push(self.block.difficulty),
+            OpcodeId::GASLIMIT => stack.push(Word::from(self.block.gas_limit)),
+            OpcodeId::CHAINID => stack.push(self.block.chain_id),
+            OpcodeId::SELFBALANCE => stack.push(self.state.balance(&contract.address)),
+            OpcodeId::BASEFEE => stack.push(self.block.base_fee),
+            OpcodeId::POP => {
+                pop!();
+            }
+            OpcodeId::MLOAD => {
+                let offset = pop!();
+                stack.push(Word::from_big_endian(&memory.read(offset, Word::from(32))));
+            }
+            OpcodeId::MSTORE => {
+                let (offset, value) = (pop!(), pop!());
+                memory.set(offset, Word::from(32), &value.to_be_bytes());
+            }
+            OpcodeId::MSTORE8 => {
+                let (offset, value) = (pop!(), pop!());
+                memory.set(offset, Word::one(), &[value.byte(0)]);
+            }
+            OpcodeId::SLOAD => {
+                let key = pop!();
+                stack.push(self.state.storage(&contract.address, &key));
+            }
+            OpcodeId::SSTORE => {
+                if self.read_only {
+                    return Err(VmError::WriteProtection);
+                }
+                let (key, value) = (pop!(), pop!());
+                self.state.set_storage(contract.address, key, value);
+            }
+            OpcodeId::JUMP => {
+                let dest = pop!();
+                if !contract.valid_jump_destination(dest) {
+                    return Err(VmError::InvalidJump);
+                }
+                return Ok(Halt::Jump(dest.as_usize()));
+            }
+            OpcodeId::JUMPI => {
+                let (dest, condition) = (pop!(), pop!());
+                if !condition.is_zero() {
+                    if !contract.valid_jump_destination(dest) {
+                        return Err(VmError::InvalidJump);
+                    }
+                    return Ok(Halt::Jump(dest.as_usize()));
+                }
+            }
+            OpcodeId::PC => stack.push(Word::from(*pc)),
+            OpcodeId::MSIZE => stack.push(Word::from(memory.data.len())),
+            OpcodeId::GAS => stack.push(Word::from(contract.gas)),
+            OpcodeId::JUMPDEST => {}
+            OpcodeId::PUSH0 => stack.push(Word::zero()),
+            OpcodeId::DUP1
+            | OpcodeId::DUP2
+            | OpcodeId::DUP3
+            | OpcodeId::DUP4
+            | OpcodeId::DUP5
+            | OpcodeId::DUP6
+            | OpcodeId::DUP7
+            | OpcodeId::DUP8
+            | OpcodeId::DUP9
+            | OpcodeId::DUP10
+            | OpcodeId::DUP11
+            | OpcodeId::DUP12
+            | OpcodeId::DUP13
+            | OpcodeId::DUP14
+            | OpcodeId::DUP15
+            | OpcodeId::DUP16 => {
+                let n = (opcode.as_u8() - OpcodeId::DUP1.as_u8()) as usize;
+                stack.push(back(stack, n));
+            }
+            OpcodeId::SWAP1
+            | OpcodeId::SWAP2
+            | OpcodeId::SWAP3
+            | OpcodeId::SWAP4
+            | OpcodeId::SWAP5
+            | OpcodeId::SWAP6
+            | OpcodeId::SWAP7
+            | OpcodeId::SWAP8
+            | OpcodeId::SWAP9
+            | OpcodeId::SWAP10
+            | OpcodeId::SWAP11
+            | OpcodeId::SWAP12
+            | OpcodeId::SWAP13
+            | OpcodeId::SWAP14
+            | OpcodeId::SWAP15
+            | OpcodeId::SWAP16 => {
+                let n = (opcode.as_u8() - OpcodeId::SWAP1.as_u8()) as usize + 1;
+                let top = stack.len() - 1;
+                stack.swap(top, top - n);
+            }
+            OpcodeId::LOG0 | OpcodeId::LOG1 | OpcodeId::LOG2 | OpcodeId::LOG3 | OpcodeId::LOG4 => {
+                if self.read_only {
+                    return Err(VmError::WriteProtection);
+                }
+                // Logs are not part of the trace.
+                let topics = (opcode.as_u8() - OpcodeId::LOG0.as_u8()) as usize;
+                stack.truncate(stack.len() - 2 - topics);
+            }
+            OpcodeId::CREATE | OpcodeId::CREATE2 => {
+                if self.read_only {
+                    return Err(VmError::WriteProtection);
+                }
+                let (value, offset, size) = (pop!(), pop!(), pop!());
+                let salt = if opcode == OpcodeId::CREATE2 {
+                    pop!()
+                } else {
+                    Word::zero()
+                };
+                let init_code = memory.read(offset, size);
+                // Apply the 63/64 rule of EIP-150.
+                let gas = contract.gas - contract.gas / 64;
+                contract.use_gas(gas);
+                let (output, address, gas_left, result) = if opcode == OpcodeId::CREATE {
+                    self.create(contract.address, init_code, gas, value)
+                } else {
+                    self.create2(contract.address, init_code, gas, value, salt)
+                };
+                stack.push(if result.is_ok() {
+                    address.to_word()
+                } else {
+                    Word::zero()
+                });
+                contract.gas += gas_left;
+                *return_data = if result == Err(VmError::ExecutionReverted) {
+                    output
+                } else {
+                    Vec::new()
+                };
+            }
+            OpcodeId::CALL | OpcodeId::CALLCODE | OpcodeId::DELEGATECALL | OpcodeId::STATICCALL => {
+                pop!();
+                let mut gas = self.call_gas_temp;
+                let address = pop!().to_address();
+                let value = if matches!(opcode, OpcodeId::CALL | OpcodeId::CALLCODE) {
+                    pop!()
+                } else {
+                    Word::zero()
+                };
+                let (input_offset, input_size, return_offset, return_size) =
+                    (pop!(), pop!(), pop!(), pop!());
+                let input = memory.read(input_offset, input_size);
+                if opcode == OpcodeId::CALL && self.read_only && !value.is_zero() {
+                    return Err(VmError::WriteProtection);
+                }
+                if !value.is_zero() {
+                    gas += GAS_STIPEND_CALL_WITH_VALUE;
+                }
+                let (output, gas_left, result) = match opcode {
+                    OpcodeId::CALL => self.call(contract.address, address, input, gas, value),
+                    OpcodeId::CALLCODE => {
+                        self.call_code(contract.address, address, input, gas, value)
+                    }
+                    OpcodeId::DELEGATECALL => self.delegate_call(contract, address, input, gas),
+                    _ => self.static_call(contract.address, address, input, gas),
+                };
+                stack.push(result.is_ok().to_word());
+                if matches!(result, Ok(()) | Err(VmError::ExecutionReverted)) {
+                    memory.set(return_offset, return_size, &output);
+                }
+                contract.gas += gas_left;
+                *return_data = output;
+            }
+            OpcodeId::RETURN | OpcodeId::REVERT => {
+                let (offset, size) = (pop!(), pop!());
+                let output = memory.read(offset, size);
+                return Ok(if opcode == OpcodeId::RETURN {
+                    Halt::Return(output)
+                } else {
+                    Halt::Revert(output)
+                });
+            }
+            OpcodeId::SELFDESTRUCT => {
+                if self.read_only {
+                    return Err(VmError::WriteProtection);
+                }
+                let beneficiary = pop!().to_address();
+                let balance = self.state.balance(&contract.address);
+                self.state.add_balance(beneficiary, balance);
+                self.state.suicide(contract.address);
+                return Ok(Halt::Return(Vec::new()));
+            }
+            OpcodeId::INVALID(_) => return Err(VmError::InvalidOpcode(opcode)),
+            _ => {
+                // PUSH1..=PUSH32
+                let size = opcode.data_len();
+                let start = (*pc + 1).min(contract.code.len());
+                let end = (start + size).min(contract.code.len());
+                let mut bytes = contract.code[start..end].to_vec();
+                bytes.resize(size, 0);
+                stack.push(Word::from_big_endian(&bytes));
+                *pc += size;
+            }
+        }
+        Ok(Halt::Continue)
+    }
+}
+
+/// Return the minimum and maximum stack size required by the opcode.
+fn stack_bounds(opcode: OpcodeId) -> (usize, usize) {
+    match opcode {
+        OpcodeId::INVALID(_) => return (0, STACK_LIMIT),
+        // The table records the bounds of EXTCODEHASH as for an opcode which
+        // only pushes.
+        OpcodeId::EXTCODEHASH => return (1, STACK_LIMIT),
+        _ => {}
+    }
+    let (min_stack_pointer, max_stack_pointer) = opcode.valid_stack_ptr_range();
+    (
+        STACK_LIMIT - max_stack_pointer as usize,
+        STACK_LIMIT - min_stack_pointer as usize,
+    )
+}
+
+fn has_dynamic_gas(opcode: OpcodeId) -> bool {
+    matches!(
+        opcode,
+        OpcodeId::EXP
+            | OpcodeId::SHA3
+            | OpcodeId::BALANCE
+            | OpcodeId::CALLDATACOPY
+            | OpcodeId::CODECOPY
+            | OpcodeId::EXTCODESIZE
+            | OpcodeId::EXTCODECOPY
+            | OpcodeId::RETURNDATACOPY
+            | OpcodeId::EXTCODEHASH
+            | OpcodeId::MLOAD
+            | OpcodeId::MSTORE
+            | OpcodeId::MSTORE8
+            | OpcodeId::SLOAD
+            | OpcodeId::SSTORE
+            | OpcodeId::LOG0
+            | OpcodeId::LOG1
+            | OpcodeId::LOG2
+            | OpcodeId::LOG3
+            | OpcodeId::LOG4
+            | OpcodeId::CREATE
+            | OpcodeId::CALL
+            | OpcodeId::CALLCODE
+            | OpcodeId::RETURN
+            | OpcodeId::DELEGATECALL
+            | OpcodeId::CREATE2
+            | OpcodeId::STATICCALL
+            | OpcodeId::REVERT
+            | OpcodeId::SELFDESTRUCT
+    )
+}
+
+/// Memory size required by the opcode, rounded up to words.
+fn memory_size(opcode: OpcodeId, stack: &[Word]) -> Result<u64, VmError> {
+    let range =
+        |offset: usize, size: usize| memory_range_end(back(stack, offset), back(stack, size));
+    let size = match opcode {
+        OpcodeId::SHA3
+        | OpcodeId::LOG0
+        | OpcodeId::LOG1
+        | OpcodeId::LOG2
+        | OpcodeId::LOG3
+        | OpcodeId::LOG4
+        | OpcodeId::RETURN
+        | OpcodeId::REVERT => range(0, 1),
+        OpcodeId::CALLDATACOPY | OpcodeId::CODECOPY | OpcodeId::RETURNDATACOPY => range(0, 2),
+        OpcodeId::EXTCODECOPY => range(1, 3),
+        OpcodeId::MLOAD | OpcodeId::MSTORE => memory_range_end(back(stack, 0), Word::from(32)),
+        OpcodeId::MSTORE8 => memory_range_end(back(stack, 0), Word::one()),
+        OpcodeId::CREATE | OpcodeId::CREATE2 => range(1, 2),
+        OpcodeId::CALL | OpcodeId::CALLCODE => range(5, 6).zip(range(3, 4)).map(|(a, b)| a.max(b)),
+        OpcodeId::DELEGATECALL | OpcodeId::STATICCALL => {
+            range(4, 5).zip(range(2, 3)).map(|(a, b)| a.max(b))
+        }
+        _ => Some(0),
+    }
+    .ok_or(VmError::GasUintOverflow)?;
+    to_word_size(size)
+        .checked_mul(32)
+        .ok_or(VmError::GasUintOverflow)
+}
+
+/// End of the memory range `[offset, offset + size)`, which is zero for an
+/// empty range, or `None` if it overflows.
+fn memory_range_end(offset: Word, size: Word) -> Option<u64> {
+    if size.is_zero() {
+        return Some(0);
+    }
+    if size.bits() > 64 || offset.bits() > 64 {
+        return None;
+    }
+    offset.as_u64().checked_add(size.as_u64())
+}
+
+/// Gas cost of copying `size` bytes into the memory, including its expansion.
+fn memory_copier_gas_cost(
+    memory: &mut FrameMemory,
+    memory_size: u64,
+    size: Word,
+) -> Result<u64, VmError> {
+    let gas_cost = memory.expansion_gas_cost(memory_size)?;
+    let copy_gas_cost = to_word_size(to_u64(size)?)
+        .checked_mul(GasCost::COPY.as_u64())
+        .ok_or(VmError::GasUintOverflow)?;
+    checked_add(gas_cost, copy_gas_cost)
+}
+
+fn to_word_size(size: u64) -> u64 {
+    if size > u64::MAX - 31 {
+        u64::MAX / 32 + 1
+    } else {
+        (size + 31) / 32
+    }
+}
+
+fn to_u64(word: Word) -> Result<u64, VmError> {
+    if word.bits() > 64 {
+        Err(VmError::GasUintOverflow)
+    } else {
+        Ok(word.as_u64())
+    }
+}
+
+fn saturating_u64(word: Word) -> u64 {
+    if word.bits() > 64 {
+        u64::MAX
+    } else {
+        word.as_u64()
+    }
+}
+
+fn checked_add(a: u64, b: u64) -> Result<u64, VmError> {
+    a.checked_add(b).ok_or(VmError::GasUintOverflow)
+}
+
+/// Return the `n`-th element from the top of the stack.
+fn back(stack: &[Word], n: usize) -> Word {
+    stack[stack.len() - 1 - n]
+}
+
+/// Return `data[offset..offset + size]` right padded with zeros.
+fn get_data(data: &[u8], offset: u64, size: u64) -> Vec<u8> {
+    let length = data.len() as u64;
+    let start = offset.min(length);
+    let end = start.saturating_add(size).min(length);
+    let mut bytes = data[start as usize..end as usize].to_vec();
+    bytes.resize(size as usize, 0);
+    bytes
+}
+
+fn is_negative(value: Word) -> bool {
+    value.bit(255)
+}
+
+fn neg(value: Word) -> Word {
+    (!value).overflowing_add(Word::one()).0
+}
+
+fn abs(value: Word) -> Word {
+    if is_negative(value) {
+        neg(value)
+    } else {
+        value
+    }
+}
+
+/// Flip the sign bit so that the unsigned order matches the signed one.
+fn flip_sign(value: Word) -> Word {
+    value ^ (Word::one() << 255)
+}
@@ -0,0 +1,478 @@
+//! In-memory state database with a journal, so that the changes of a call
+//! frame can be reverted, mirroring the `StateDB` of geth.
+
+use eth_types::{Address, Bytes, Word, H256};
+use ethers_core::utils::keccak256;
+use lazy_static::lazy_static;
+use std::collections::{HashMap, HashSet};
+
+lazy_static! {
+    /// Hash of the empty code.
+    pub(crate) static ref EMPTY_CODE_HASH: H256 = H256(keccak256([]));
+}
+
+/// Account in the state.
+#[derive(Clone, Debug)]
+pub(crate) struct Account {
+    pub(crate) nonce: u64,
+    pub(crate) balance: Word,
+    pub(crate) code: Bytes,
+    pub(crate) code_hash: H256,
+    pub(crate) storage: HashMap<Word, Word>,
+    /// Storage at the beginning of the current transaction.
+    pub(crate) committed_storage: HashMap<Word, Word>,
+}
+
+impl Default for Account {
+    fn default() -> Self {
+        Self {
+            nonce: 0,
+            balance: Word::zero(),
+            code: Bytes::default(),
+            code_hash: *EMPTY_CODE_HASH,
+            storage: HashMap::new(),
+            committed_storage: HashMap::new(),
+        }
+    }
+}
+
+impl Account {
+    /// Return if the account is empty as defined by
+    /// [EIP-161](https://eips.ethereum.org/EIPS/eip-161).
+    pub(crate) fn is_empty(&self) -> bool {
+        self.nonce == 0 && self.balance.is_zero() && self.code.is_empty()
+    }
+}
+
+/// Change to the state that can be reverted.
+#[derive(Debug)]
+enum JournalEntry {
+    CreateAccount {
+        address: Address,
+        prev: Option<Account>,
+    },
+    Balance {
+        address: Address,
+        prev: Word,
+    },
+    Nonce {
+        address: Address,
+        prev: u64,
+    },
+    Code {
+        address: Address,
+        prev_code: Bytes,
+        prev_code_hash: H256,
+    },
+    Storage {
+        address: Address,
+        key: Word,
+        prev: Word,
+    },
+    Suicide {
+        address: Address,
+        prev_suicided: bool,
+        prev_balance: Word,
+    },
+    Touch {
+        address: Address,
+    },
+    Refund {
+        prev: u64,
+    },
+    AccessListAccount {
+        address: Address,
+    },
+    AccessListSlot {
+        address: Address,
+        key: Word,
+    },
+}
+
+impl JournalEntry {
+    /// Account whose state is changed by the entry, which is touched as long
+    /// as the entry is not reverted.
+    fn dirtied(&self) -> Option<Address> {
+        match self {
+            Self::CreateAccount {
+                address,
+                prev: None,
+            }
+            | Self::Balance { address, .. }
+            | Self::Nonce { address, .. }
+            | Self::Code { address, .. }
+            | Self::Storage { address, .. }
+            | Self::Suicide { address, .. }
+            | Self::Touch { address } => Some(*address),
+            _ => None,
+        }
+    }
+}
+
+/// State database.
+#[derive(Debug, Default)]
+pub(crate) struct StateDB {
+    accounts: HashMap<Address, Account>,
+    suicided: HashSet<Address>,
+    access_list_accounts: HashSet<Address>,
+    access_list_slots: HashSet<(Address, Word)>,
+    refund: u64,
+    journal: Vec<JournalEntry>,
+}
+
+impl StateDB {
+    /// Insert a committed account.
+    pub(crate) fn insert_account(
+        &mut self,
+        address: Address,
+        nonce: u64,
+        balance: Word,
+        code: Bytes,
+        storage: HashMap<Word, Word>,
+    ) {
+        let storage: HashMap<_, _> = storage
+            .into_iter()
+            .filter(|(_, value)| !value.is_zero())
+            .collect();
+        self.accounts.insert(
+            address,
+            Account {
+                nonce,
+                balance,
+                code_hash: H256(keccak256(&code)),
+                code,
+                committed_storage: storage.clone(),
+                storage,
+            },
+        );
+    }
+
+    pub(crate) fn exist(&self, address: &Address) -> bool {
+        self.accounts.contains_key(address)
+    }
+
+    pub(crate) fn empty(&self, address: &Address) -> bool {
+        self.accounts
+            .get(address)
+            .map(Account::is_empty)
+            .unwrap_or(true)
+    }
+
+    pub(crate) fn balance(&self, address: &Address) -> Word {
+        self.accounts
+            .get(address)
+            .map(|account| account.balance)
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn nonce(&self, address: &Address) -> u64 {
+        self.accounts
+            .get(address)
+            .map(|account| account.nonce)
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn code(&self, address: &Address) -> Bytes {
+        self.accounts
+            .get(address)
+            .map(|account| account.code.clone())
+            .unwrap_or_default()
+    }
+
+    /// Return the code hash of the account, or zero if it doesn't exist.
+    pub(crate) fn code_hash(&self, address: &Address) -> H256 {
+        self.accounts
+            .get(address)
+            .map(|account| account.code_hash)
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn storage(&self, address: &Address, key: &Word) -> Word {
+        self.accounts
+            .get(address)
+            .and_then(|account| account.storage.get(key))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn committed_storage(&self, address: &Address, key: &Word) -> Word {
+        self.accounts
+            .get(address)
+            .and_then(|account| account.committed_storage.get(key))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn refund(&self) -> u64 {
+        self.refund
+    }
+
+    fn account_mut(&mut self, address: Address) -> &mut Account {
+        if !self.accounts.contains_key(&address) {
+            self.journal.push(JournalEntry::CreateAccount {
+                address,
+                prev: None,
+            });
+        }
+        self.accounts.entry(address).or_default()
+    }
+
+    /// Create a new account at `address`, keeping the balance of the account
+    /// that may already exist.
+    pub(crate) fn create_account(&mut self, address: Address) {
+        let prev = self.accounts.remove(&address);
+        let balance = prev
+            .as_ref()
+            .map(|account| account.balance)
+            .unwrap_or_default();
+        self.journal
+            .push(JournalEntry::CreateAccount { address, prev });
+        self.accounts.insert(
+            address,
+            Account {
+                balance,
+                ..Account::default()
+            },
+        );
+    }
+
+    fn set_balance(&mut self, address: Address, balance: Word) {
+        let account = self.account_mut(address);
+        let prev = std::mem::replace(&mut account.balance, balance);
+        self.journal.push(JournalEntry::Balance { address, prev });
+    }
+
+    pub(crate) fn add_balance(&mut self, address: Address, amount: Word) {
+        let account = self.account_mut(address);
+        if amount.is_zero() {
+            if account.is_empty() {
+                self.journal.push(JournalEntry::Touch { address });
+            }
+            return;
+        }
+        let balance = account.balance.overflowing_add(amount).0;
+        self.set_balance(address, balance);
+    }
+
+    pub(crate) fn sub_balance(&mut self, address: Address, amount: Word) {
+        let account = self.account_mut(address);
+        if amount.is_zero() {
+            return;
+        }
+        let balance = account.balance.overflowing_sub(amount).0;
+        self.set_balance(address, balance);
+    }
+
+    /// Transfer `value` from `sender` to `receiver`.
+    pub(crate) fn transfer(&mut self, sender: Address, receiver: Address, value: Word) {
+        self.sub_balance(sender, value);
+        self.add_balance(receiver, value);
+    }
+
+    pub(crate) fn set_nonce(&mut self, address: Address, nonce: u64) {
+        let account = self.account_mut(address);
+        let prev = std::mem::replace(&mut account.nonce, nonce);
+        self.journal.push(JournalEntry::Nonce { address, prev });
+    }
+
+    pub(crate) fn set_code(&mut self, address: Address, code: Bytes) {
+        let account = self.account_mut(address);
+        let prev_code_hash = std::mem::replace(&mut account.code_hash, H256(keccak256(&code)));
+        let prev_code = std::mem::replace(&mut account.code, code);
+        self.journal.push(JournalEntry::Code {
+            address,
+            prev_code,
+            prev_code_hash,
+        });
+    }
+
+    pub(crate) fn set_storage(&mut self, address: Address, key: Word, value: Word) {
+        let prev = self.storage(&address, &key);
+        let account = self.account_mut(address);
+        if prev == value {
+            return;
+        }
+        account.storage.insert(key, value);
+        self.journal
+            .push(JournalEntry::Storage { address, key, prev });
+    }
+
+    /// Mark the account as suicided and clear its balance.  The account is
+    /// deleted at the end of the transaction.
+    pub(crate) fn suicide(&mut self, address: Address) {
+        if let Some(account) = self.accounts.get_mut(&address) {
+            let prev_balance = std::mem::take(&mut account.balance);
+            let prev_suicided = !self.suicided.insert(address);
+            self.journal.push(JournalEntry::Suicide {
+                address,
+                prev_suicided,
+                prev_balance,
+            });
+        }
+    }
+
+    pub(crate) fn has_suicided(&self, address: &Address) -> bool {
+        self.suicided.contains(address)
+    }
+
+    pub(crate) fn add_refund(&mut self, gas: u64) {
+        self.journal
+            .push(JournalEntry::Refund { prev: self.refund });
+        self.refund += gas;
+    }
+
+    pub(crate) fn sub_refund(&mut self, gas: u64) {
+        self.journal
+            .push(JournalEntry::Refund { prev: self.refund });
+        self.refund = self
+            .refund
+            .checked_sub(gas)
+            .expect("refund counter below zero");
+    }
+
+    pub(crate) fn address_in_access_list(&self, address: &Address) -> bool {
+        self.access_list_accounts.contains(address)
+    }
+
+    /// Return whether the address and the slot are in the access list.
+    pub(crate) fn slot_in_access_list(&self, address: &Address, key: &Word) -> (bool, bool) {
+        (
+            self.access_list_accounts.contains(address),
+            self.access_list_slots.contains(&(*address, *key)),
+        )
+    }
+
+    pub(crate) fn add_address_to_access_list(&mut self, address: Address) {
+        if self.access_list_accounts.insert(address) {
+            self.journal
+                .push(JournalEntry::AccessListAccount { address });
+        }
+    }
+
+    pub(crate) fn add_slot_to_access_list(&mut self, address: Address, key: Word) {
+        self.add_address_to_access_list(address);
+        if self.access_list_slots.insert((address, key)) {
+            self.journal
+                .push(JournalEntry::AccessListSlot { address, key });
+        }
+    }
+
+    /// Reset the access list to the accounts and slots accessed at the
+    /// beginning of a transaction, as specified by
+    /// [EIP-2929](https://eips.ethereum.org/EIPS/eip-2929), with the coinbase
+    /// when it's given by [EIP-3651](https://eips.ethereum.org/EIPS/eip-3651).
+    pub(crate) fn prepare_access_list(
+        &mut self,
+        sender: Address,
+        coinbase: Option<Address>,
+        receiver: Option<Address>,
+        precompiles: impl IntoIterator<Item = Address>,
+        access_list: impl IntoIterator<Item = (Address, Vec<Word>)>,
+    ) {
+        self.access_list_accounts.clear();
+        self.access_list_slots.clear();
+        self.add_address_to_access_list(sender);
+        if let Some(coinbase) = coinbase {
+            self.add_address_to_access_list(coinbase);
+        }
+        if let Some(receiver) = receiver {
+            self.add_address_to_access_list(receiver);
+        }
+        for address in precompiles {
+            self.add_address_to_access_list(address);
+        }
+        for (address, keys) in access_list {
+            self.add_address_to_access_list(address);
+            for key in keys {
+                self.add_slot_to_access_list(address, key);
+            }
+        }
+    }
+
+    pub(crate) fn snapshot(&self) -> usize {
+        self.journal.len()
+    }
+
+    /// Revert all the changes made after the snapshot was taken.
+    pub(crate) fn revert_to_snapshot(&mut self, snapshot: usize) {
+        while self.journal.len() > snapshot {
+            match self.journal.pop().unwrap() {
+                JournalEntry::CreateAccount { address, prev } => {
+                    match prev {
+                        Some(prev) => self.accounts.insert(address, prev),
+                        None => self.accounts.remove(&address),
+                    };
+                }
+                JournalEntry::Balance { address, prev } => {
+                    self.accounts.get_mut(&address).unwrap().balance = prev;
+                }
+                JournalEntry::Nonce { address, prev } => {
+                    self.accounts.get_mut(&address).unwrap().nonce = prev;
+                }
+                JournalEntry::Code {
+                    address,
+                    prev_code,
+                    prev_code_hash,
+                } => {
+                    let account = self.accounts.get_mut(&address).unwrap();
+                    account.code = prev_code;
+                    account.code_hash = prev_code_hash;
+                }
+                JournalEntry::Storage { address, key, prev } => {
+                    self.accounts
+                        .get_mut(&address)
+                        .unwrap()
+                        .storage
+                        .insert(key, prev);
+                }
+                JournalEntry::Suicide {
+                    address,
+                    prev_suicided,
+                    prev_balance,
+                } => {
+                    if !prev_suicided {
+                        self.suicided.remove(&address);
+                    }
+                    self.accounts.get_mut(&address).unwrap().balance = prev_balance;
+                }
+                JournalEntry::Touch { .. } => {}
+                JournalEntry::Refund { prev } => self.refund = prev,
+                JournalEntry::AccessListAccount { address } => {
+                    self.access_list_accounts.remove(&address);
+                }
+                JournalEntry::AccessListSlot { address, key } => {
+                    self.access_list_slots.remove(&(address, key));
+                }
+            }
+        }
+    }
+
+    /// Finalise the state at the end of a transaction: delete the suicided
+    /// accounts and the touched empty accounts, commit the storage and clear
+    /// the journal and the refund counter.
+    pub(crate) fn finalise(&mut self) {
+        let dirties: HashSet<_> = self
+            .journal
+            .iter()
+            .filter_map(JournalEntry::dirtied)
+            .collect();
+        for address in dirties {
+            let delete = self.suicided.contains(&address)
+                || self
+                    .accounts
+                    .get(&address)
+                    .map(Account::is_empty)
+                    .unwrap_or(false);
+            if delete {
+                self.accounts.remove(&address);
+            }
+        }
+        for account in self.accounts.values_mut() {
+            account.storage.retain(|_, value| !value.is_zero());
+            account.committed_storage = account.storage.clone();
+        }
+        self.suicided.clear();
+        self.refund = 0;
+        self.journal.clear();
+    }
+}
@@ -98,6 +98,7 @@ type Account struct {
 }
 
 type Transaction struct {
+	Type       uint64          `json:"transaction_type"`
 	From       common.Address  `json:"from"`
 	To         *common.Address `json:"to"`
 	Nonce      hexutil.Uint64  `json:"nonce"`
@@ -122,6 +123,7 @@ type TraceConfig struct {
 	Accounts      map[common.Address]Account `json:"accounts"`
 	Transactions  []Transaction              `json:"transactions"`
 	LoggerConfig  *logger.Config             `json:"logger_config"`
+	Hardfork      string                     `json:"hardfork"`
 }
 
 func Trace(config TraceConfig) ([]*ExecutionResult, error) {
@@ -142,13 +144,21 @@ func Trace(config TraceConfig) ([]*ExecutionResult, error) {
 		BerlinBlock:         big.NewInt(0),
 		LondonBlock:         big.NewInt(0),
 	}
+	switch config.Hardfork {
+	case "Berlin":
+		chainConfig.LondonBlock = nil
+	case "London":
+	default:
+		return nil, fmt.Errorf("unsupported hardfork: %s", config.Hardfork)
+	}
 
 	var txsGasLimit uint64
 	blockGasLimit := toBigInt(config.Block.GasLimit).Uint64()
 	messages := make([]types.Message, len(config.Transactions))
 	for i, tx := range config.Transactions {
-		// If gas price is specified directly, the tx is treated as legacy type.
-		if tx.GasPrice != nil {
+		// Only dynamic fee txs are priced by the fee caps, the gas price of
+		// which is the effective gas price, the others are treated as legacy type.
+		if tx.Type != types.DynamicFeeTxType {
 			tx.GasFeeCap = tx.GasPrice
 			tx.GasTipCap = tx.GasPrice
 		}
@@ -356,6 +356,24 @@ async fn main() {
         block_num.as_u64(),
     );
 
+    // OpenZeppelin ERC20 transfer of the whole balance (wallet4 sends back its
+    // 123.45 Tokens to wallet0), which clears the storage slot of its balance
+    info!("Doing OpenZeppelin ERC20 transfer of the whole balance...");
+    let amount = U256::from_dec_str("123450000000000000000").unwrap();
+    let tx = erc20_transfer(
+        wallets[4].clone(),
+        contract_address,
+        contract_abi,
+        wallets[0].address(),
+        amount,
+    );
+    let receipt = send_confirm_tx(&wallets[4], tx).await;
+    assert_eq!(receipt.status, Some(U64::from(1u64)));
+    blocks.insert(
+        "ERC20 OpenZeppelin transfer of the whole balance".to_string(),
+        receipt.block_number.unwrap().as_u64(),
+    );
+
     let gen_data = GenDataOutput {
         coinbase: accounts[0],
         wallets: wallets.iter().map(|w| w.address()).collect(),
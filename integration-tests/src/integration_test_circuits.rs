@@ -1,32 +1,25 @@
-use crate::{get_client, GenDataOutput};
+use crate::{get_state_provider, GenDataOutput};
 use bus_mapping::circuit_input_builder::{BuilderClient, CircuitInputBuilder, CircuitsParams};
 use bus_mapping::mock::BlockData;
-use eth_types::geth_types::GethData;
-use halo2_proofs::plonk::{
-    create_proof, keygen_pk, keygen_vk, verify_proof, Circuit, ProvingKey, VerifyingKey,
-};
-use halo2_proofs::poly::commitment::ParamsProver;
-use halo2_proofs::poly::kzg::commitment::{KZGCommitmentScheme, ParamsKZG, ParamsVerifierKZG};
-use halo2_proofs::poly::kzg::multiopen::{ProverSHPLONK, VerifierSHPLONK};
-use halo2_proofs::poly::kzg::strategy::SingleStrategy;
-use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
+use eth_types::{evm_types::Hardfork, geth_types::GethData, Word};
+use halo2_proofs::plonk::{Circuit, ProvingKey};
+use halo2_proofs::poly::kzg::commitment::ParamsKZG;
 use halo2_proofs::{
-    halo2curves::bn256::{Bn256, G1Affine},
-    transcript::{
-        Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
-    },
+    dev::MockProver,
+    halo2curves::bn256::{Bn256, Fr, G1Affine},
 };
 use lazy_static::lazy_static;
 use mock::test_ctx::TestContext;
 use rand_chacha::rand_core::SeedableRng;
-use rand_core::RngCore;
 use rand_xorshift::XorShiftRng;
 use std::collections::HashMap;
 use std::sync::Mutex;
 use zkevm_circuits::bytecode_circuit::bytecode_unroller::BytecodeCircuit;
 use zkevm_circuits::copy_circuit::CopyCircuit;
-use zkevm_circuits::evm_circuit::test::{get_test_degree, get_test_instance};
+use zkevm_circuits::evm_circuit::test::get_test_degree;
 use zkevm_circuits::evm_circuit::{test::get_test_cicuit_from_block, witness::block_convert};
+use zkevm_circuits::mpt_circuit::MptCircuit;
+use zkevm_circuits::prover::{self, KeyCache};
 use zkevm_circuits::state_circuit::StateCircuit;
 use zkevm_circuits::super_circuit::SuperCircuit;
 use zkevm_circuits::tx_circuit::TxCircuit;
@@ -37,14 +30,20 @@ const CIRCUITS_PARAMS: CircuitsParams = CircuitsParams {
     max_rws: 16384,
     max_txs: 4,
     max_calldata: 4000,
+    max_access_list: 64,
     max_bytecode: 4000,
+    max_ecrecover: 0,
+    max_receipts_len: 4000,
     keccak_padding: None,
+    block_reward: Word::zero(),
+    hardfork: Hardfork::London,
 };
 
 const STATE_CIRCUIT_DEGREE: u32 = 17;
 const TX_CIRCUIT_DEGREE: u32 = 20;
 const BYTECODE_CIRCUIT_DEGREE: u32 = 16;
 const COPY_CIRCUIT_DEGREE: u32 = 16;
+const MPT_CIRCUIT_DEGREE: u32 = 17;
 
 lazy_static! {
     /// Data generation.
@@ -57,6 +56,7 @@ lazy_static! {
 
 lazy_static! {
     static ref GEN_PARAMS: Mutex<HashMap<u32, ParamsKZG<Bn256>>> = Mutex::new(HashMap::new());
+    static ref KEY_CACHE: KeyCache = KeyCache::from_env();
 }
 
 lazy_static! {
@@ -65,36 +65,45 @@ lazy_static! {
         let circuit = StateCircuit::<Fr>::new_from_block(&block);
         let general_params = get_general_params(STATE_CIRCUIT_DEGREE);
 
-        let verifying_key =
-            keygen_vk(&general_params, &circuit).expect("keygen_vk should not fail");
-        keygen_pk(&general_params, verifying_key, &circuit).expect("keygen_pk should not fail")
+        KEY_CACHE
+            .load_pk(&general_params, &CIRCUITS_PARAMS, &circuit)
+            .expect("keygen should not fail")
     };
     static ref TX_CIRCUIT_KEY: ProvingKey<G1Affine> = {
         let block = new_empty_block();
         let circuit = TxCircuit::<Fr>::new_from_block(&block);
         let general_params = get_general_params(TX_CIRCUIT_DEGREE);
 
-        let verifying_key =
-            keygen_vk(&general_params, &circuit).expect("keygen_vk should not fail");
-        keygen_pk(&general_params, verifying_key, &circuit).expect("keygen_pk should not fail")
+        KEY_CACHE
+            .load_pk(&general_params, &CIRCUITS_PARAMS, &circuit)
+            .expect("keygen should not fail")
     };
     static ref BYTECODE_CIRCUIT_KEY: ProvingKey<G1Affine> = {
         let block = new_empty_block();
         let circuit = BytecodeCircuit::<Fr>::new_from_block(&block);
         let general_params = get_general_params(BYTECODE_CIRCUIT_DEGREE);
 
-        let verifying_key =
-            keygen_vk(&general_params, &circuit).expect("keygen_vk should not fail");
-        keygen_pk(&general_params, verifying_key, &circuit).expect("keygen_pk should not fail")
+        KEY_CACHE
+            .load_pk(&general_params, &CIRCUITS_PARAMS, &circuit)
+            .expect("keygen should not fail")
     };
     static ref COPY_CIRCUIT_KEY: ProvingKey<G1Affine> = {
         let block = new_empty_block();
         let circuit = CopyCircuit::<Fr>::new_from_block(&block);
         let general_params = get_general_params(COPY_CIRCUIT_DEGREE);
 
-        let verifying_key =
-            keygen_vk(&general_params, &circuit).expect("keygen_vk should not fail");
-        keygen_pk(&general_params, verifying_key, &circuit).expect("keygen_pk should not fail")
+        KEY_CACHE
+            .load_pk(&general_params, &CIRCUITS_PARAMS, &circuit)
+            .expect("keygen should not fail")
+    };
+    static ref MPT_CIRCUIT_KEY: ProvingKey<G1Affine> = {
+        let block = new_empty_block();
+        let circuit = MptCircuit::<Fr>::new_from_block(&block);
+        let general_params = get_general_params(MPT_CIRCUIT_DEGREE);
+
+        KEY_CACHE
+            .load_pk(&general_params, &CIRCUITS_PARAMS, &circuit)
+            .expect("keygen should not fail")
     };
 }
 
@@ -115,7 +124,9 @@ fn get_general_params(degree: u32) -> ParamsKZG<Bn256> {
     match map.get(&degree) {
         Some(params) => params.clone(),
         None => {
-            let params = ParamsKZG::<Bn256>::setup(degree, RNG.clone());
+            let params = KEY_CACHE
+                .load_params(degree, RNG.clone())
+                .expect("failed to load params");
             map.insert(degree, params.clone());
             params
         }
@@ -128,7 +139,7 @@ async fn gen_inputs(
     CircuitInputBuilder,
     eth_types::Block<eth_types::Transaction>,
 ) {
-    let cli = get_client();
+    let cli = get_state_provider();
     let cli = BuilderClient::new(cli, CIRCUITS_PARAMS).await.unwrap();
 
     cli.gen_inputs(block_num).await.unwrap()
@@ -140,94 +151,29 @@ fn test_actual<C: Circuit<Fr>>(
     instance: Vec<Vec<Fr>>,
     proving_key: Option<ProvingKey<G1Affine>>,
 ) {
-    fn test_gen_proof<C: Circuit<Fr>, R: RngCore>(
-        rng: R,
-        circuit: C,
-        general_params: &ParamsKZG<Bn256>,
-        proving_key: &ProvingKey<G1Affine>,
-        mut transcript: Blake2bWrite<Vec<u8>, G1Affine, Challenge255<G1Affine>>,
-        instances: &[&[Fr]],
-    ) -> Vec<u8> {
-        create_proof::<
-            KZGCommitmentScheme<Bn256>,
-            ProverSHPLONK<'_, Bn256>,
-            Challenge255<G1Affine>,
-            R,
-            Blake2bWrite<Vec<u8>, G1Affine, Challenge255<G1Affine>>,
-            C,
-        >(
-            general_params,
-            proving_key,
-            &[circuit],
-            &[instances],
-            rng,
-            &mut transcript,
-        )
-        .expect("proof generation should not fail");
-
-        transcript.finalize()
-    }
-
-    fn test_verify(
-        general_params: &ParamsKZG<Bn256>,
-        verifier_params: &ParamsKZG<Bn256>,
-        verifying_key: &VerifyingKey<G1Affine>,
-        proof: &[u8],
-        instances: &[&[Fr]],
-    ) {
-        let mut verifier_transcript = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(proof);
-        let strategy = SingleStrategy::new(general_params);
-
-        verify_proof::<
-            KZGCommitmentScheme<Bn256>,
-            VerifierSHPLONK<'_, Bn256>,
-            Challenge255<G1Affine>,
-            Blake2bRead<&[u8], G1Affine, Challenge255<G1Affine>>,
-            SingleStrategy<'_, Bn256>,
-        >(
-            verifier_params,
-            verifying_key,
-            strategy,
-            &[instances],
-            &mut verifier_transcript,
-        )
-        .expect("failed to verify circuit");
-    }
-
     let general_params = get_general_params(degree);
-    let verifier_params: ParamsVerifierKZG<Bn256> = general_params.verifier_params().clone();
+    let verifier_params = prover::verifier_params(&general_params);
 
     let proving_key = match proving_key {
         Some(pk) => pk,
-        None => {
-            let verifying_key =
-                keygen_vk(&general_params, &circuit).expect("keygen_vk should not fail");
-            keygen_pk(&general_params, verifying_key, &circuit).expect("keygen_pk should not fail")
-        }
+        // The key cache detects when the fixed columns of the circuit depend
+        // on the block, so the keys of any circuit can be cached.
+        None => KEY_CACHE
+            .load_pk(&general_params, &CIRCUITS_PARAMS, &circuit)
+            .expect("keygen should not fail"),
     };
 
-    let transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
-
-    // change instace to slice
-    let instance: Vec<&[Fr]> = instance.iter().map(|v| v.as_slice()).collect();
-
-    let proof = test_gen_proof(
-        RNG.clone(),
-        circuit,
+    let proof = prover::create_proof(
         &general_params,
         &proving_key,
-        transcript,
+        circuit,
         &instance,
-    );
+        RNG.clone(),
+    )
+    .expect("proof generation should not fail");
 
-    let verifying_key = proving_key.get_vk();
-    test_verify(
-        &general_params,
-        &verifier_params,
-        verifying_key,
-        &proof,
-        &instance,
-    );
+    prover::verify_proof(&verifier_params, proving_key.get_vk(), &proof, &instance)
+        .expect("failed to verify circuit");
 }
 
 fn test_mock<C: Circuit<Fr>>(degree: u32, circuit: &C, instance: Vec<Vec<Fr>>) {
@@ -245,8 +191,8 @@ pub async fn test_evm_circuit_block(block_num: u64, actual: bool) {
     let block = block_convert(&builder.block, &builder.code_db).unwrap();
 
     let degree = get_test_degree(&block);
-    let instance = get_test_instance(&block);
     let circuit = get_test_cicuit_from_block(block);
+    let instance = circuit.instance();
 
     if actual {
         test_actual(degree, circuit, instance, None);
@@ -339,23 +285,55 @@ pub async fn test_copy_circuit_block(block_num: u64, actual: bool) {
     }
 }
 
+/// Integration test for mpt circuit, whose witness is made of the proofs of the
+/// accessed accounts and storage slots returned by `eth_getProof`.
+pub async fn test_mpt_circuit_block(block_num: u64, actual: bool) {
+    log::info!("test mpt circuit, block number: {}", block_num);
+    let (builder, _) = gen_inputs(block_num).await;
+    let block = block_convert(&builder.block, &builder.code_db).unwrap();
+    assert!(
+        !builder.block.state_proofs.is_empty(),
+        "the witness should be built from the state proofs"
+    );
+
+    let circuit = MptCircuit::<Fr>::new_from_block(&block);
+
+    if actual {
+        test_actual(
+            MPT_CIRCUIT_DEGREE,
+            circuit,
+            vec![],
+            Some((*MPT_CIRCUIT_KEY).clone()),
+        );
+    } else {
+        test_mock(MPT_CIRCUIT_DEGREE, &circuit, vec![]);
+    }
+}
+
 /// Integration test for super circuit.
-pub async fn test_super_circuit_block(block_num: u64) {
+pub async fn test_super_circuit_block(block_num: u64, actual: bool) {
     const MAX_TXS: usize = 4;
     const MAX_CALLDATA: usize = 512;
+    const MAX_ACCESS_LIST: usize = 64;
     const MAX_RWS: usize = 5888;
     const MAX_BYTECODE: usize = 5000;
+    const MAX_RECEIPTS_LEN: usize = 4000;
 
     log::info!("test super circuit, block number: {}", block_num);
-    let cli = get_client();
+    let cli = get_state_provider();
     let cli = BuilderClient::new(
         cli,
         CircuitsParams {
             max_rws: MAX_RWS,
             max_txs: MAX_TXS,
             max_calldata: MAX_CALLDATA,
+            max_access_list: MAX_ACCESS_LIST,
             max_bytecode: MAX_BYTECODE,
+            max_ecrecover: 0,
+            max_receipts_len: MAX_RECEIPTS_LEN,
             keccak_padding: None,
+            block_reward: Word::zero(),
+            hardfork: Hardfork::London,
         },
     )
     .await
@@ -366,12 +344,16 @@ pub async fn test_super_circuit_block(block_num: u64) {
             &builder,
         )
         .unwrap();
-    // TODO: add actual prover
-    let prover = MockProver::run(k, &circuit, instance).unwrap();
-    let res = prover.verify_par();
-    if let Err(err) = res {
-        eprintln!("Verification failures:");
-        eprintln!("{:#?}", err);
-        panic!("Failed verification");
+
+    if actual {
+        test_actual(k, circuit, instance, None);
+    } else {
+        let prover = MockProver::run(k, &circuit, instance).unwrap();
+        let res = prover.verify_par();
+        if let Err(err) = res {
+            eprintln!("Verification failures:");
+            eprintln!("{:#?}", err);
+            panic!("Failed verification");
+        }
     }
 }
@@ -3,7 +3,7 @@
 #![deny(rustdoc::broken_intra_doc_links)]
 #![deny(missing_docs)]
 
-use bus_mapping::rpc::GethClient;
+use bus_mapping::rpc::{GethClient, GethStateProvider};
 use env_logger::Env;
 use eth_types::Address;
 use ethers::{
@@ -64,6 +64,11 @@ pub fn get_client() -> GethClient<Http> {
     GethClient::new(transport)
 }
 
+/// Get the integration test [`GethStateProvider`]
+pub fn get_state_provider() -> GethStateProvider {
+    GethStateProvider::new(Url::parse(&GETH0_URL).expect("invalid url"))
+}
+
 /// Get the integration test [`Provider`]
 pub fn get_provider() -> Provider<Http> {
     let transport = Http::new(Url::parse(&GETH0_URL).expect("invalid url"));
@@ -1,7 +1,8 @@
 #![cfg(feature = "circuit_input_builder")]
 
 use bus_mapping::circuit_input_builder::{BuilderClient, CircuitsParams};
-use integration_tests::{get_client, log_init, GenDataOutput};
+use eth_types::{evm_types::Hardfork, Word};
+use integration_tests::{get_state_provider, log_init, GenDataOutput};
 use lazy_static::lazy_static;
 use log::trace;
 
@@ -10,15 +11,20 @@ lazy_static! {
 }
 
 async fn test_circuit_input_builder_block(block_num: u64) {
-    let cli = get_client();
+    let cli = get_state_provider();
     let cli = BuilderClient::new(
         cli,
         CircuitsParams {
             max_rws: 16384,
             max_txs: 1,
             max_calldata: 4000,
+            max_access_list: 64,
             max_bytecode: 4000,
+            max_ecrecover: 0,
+            max_receipts_len: 4000,
             keccak_padding: None,
+            block_reward: Word::zero(),
+            hardfork: Hardfork::London,
         },
     )
     .await
@@ -96,3 +102,9 @@ declare_tests!(
     test_circuit_input_builder_multiple_erc20_openzeppelin_transfers,
     "Multiple ERC20 OpenZeppelin transfers"
 );
+// This test builds the complete circuit inputs for the block with an
+// OpenZeppelin ERC20 transfer tx of the whole balance of the sender.
+declare_tests!(
+    test_circuit_input_builder_erc20_openzeppelin_transfer_whole_balance,
+    "ERC20 OpenZeppelin transfer of the whole balance"
+);
@@ -1,7 +1,8 @@
 #![cfg(feature = "circuits_mock")]
 use integration_tests::integration_test_circuits::{
     test_bytecode_circuit_block, test_copy_circuit_block, test_evm_circuit_block,
-    test_state_circuit_block, test_super_circuit_block, test_tx_circuit_block, GEN_DATA,
+    test_mpt_circuit_block, test_state_circuit_block, test_super_circuit_block,
+    test_tx_circuit_block, GEN_DATA,
 };
 use integration_tests::log_init;
 use paste::paste;
@@ -44,11 +45,18 @@ macro_rules! declare_tests {
                 test_copy_circuit_block(*block_num, false).await;
             }
 
+            #[tokio::test]
+            async fn [<serial_test_mpt_ $name>]() {
+                log_init();
+                let block_num = GEN_DATA.blocks.get($block_tag).unwrap();
+                test_mpt_circuit_block(*block_num, false).await;
+            }
+
             #[tokio::test]
             async fn [<serial_test_super_ $name>]() {
                 log_init();
                 let block_num = GEN_DATA.blocks.get($block_tag).unwrap();
-                test_super_circuit_block(*block_num).await;
+                test_super_circuit_block(*block_num, false).await;
             }
         }
     };
@@ -75,3 +83,7 @@ declare_tests!(
     circuit_multiple_erc20_openzeppelin_transfers,
     "Multiple ERC20 OpenZeppelin transfers"
 );
+declare_tests!(
+    circuit_erc20_openzeppelin_transfer_whole_balance,
+    "ERC20 OpenZeppelin transfer of the whole balance"
+);
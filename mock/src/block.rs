@@ -91,7 +91,14 @@ impl From<MockBlock> for Block<Transaction> {
             transactions: mock
                 .transactions
                 .iter_mut()
-                .map(|mock_tx| (mock_tx.chain_id(mock.chain_id).to_owned()).into())
+                .map(|mock_tx| {
+                    let gas_price = mock_tx.effective_gas_price(mock.base_fee_per_gas);
+                    let mut tx: Transaction = mock_tx.chain_id(mock.chain_id).to_owned().into();
+                    // The gas price of a transaction in a block is the price
+                    // that it pays, as returned by `eth_getBlockByNumber`.
+                    tx.gas_price = Some(gas_price);
+                    tx
+                })
                 .collect::<Vec<Transaction>>(),
             size: Some(mock.size),
             mix_hash: Some(mock.mix_hash),
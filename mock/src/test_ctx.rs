@@ -2,6 +2,7 @@
 
 use crate::{eth, MockAccount, MockBlock, MockTransaction};
 use eth_types::{
+    evm_types::Hardfork,
     geth_types::{Account, BlockConstants, GethData},
     Block, Bytecode, Error, GethExecTrace, Transaction, Word,
 };
@@ -111,6 +112,53 @@ impl<const NACC: usize, const NTX: usize> TestContext<NACC, NTX> {
         func_block: Fb,
         logger_config: LoggerConfig,
     ) -> Result<Self, Error>
+    where
+        FTx: FnOnce(Vec<&mut MockTransaction>, [MockAccount; NACC]),
+        Fb: FnOnce(&mut MockBlock, Vec<MockTransaction>) -> &mut MockBlock,
+        FAcc: FnOnce([&mut MockAccount; NACC]),
+    {
+        Self::new_with_config(
+            history_hashes,
+            acc_fns,
+            func_tx,
+            func_block,
+            logger_config,
+            Hardfork::default(),
+        )
+    }
+
+    /// Create a new TestContext like [`TestContext::new`], whose transactions
+    /// are executed with the rules of the `hardfork`.
+    pub fn new_with_hardfork<FAcc, FTx, Fb>(
+        history_hashes: Option<Vec<Word>>,
+        acc_fns: FAcc,
+        func_tx: FTx,
+        func_block: Fb,
+        hardfork: Hardfork,
+    ) -> Result<Self, Error>
+    where
+        FTx: FnOnce(Vec<&mut MockTransaction>, [MockAccount; NACC]),
+        Fb: FnOnce(&mut MockBlock, Vec<MockTransaction>) -> &mut MockBlock,
+        FAcc: FnOnce([&mut MockAccount; NACC]),
+    {
+        Self::new_with_config(
+            history_hashes,
+            acc_fns,
+            func_tx,
+            func_block,
+            LoggerConfig::default(),
+            hardfork,
+        )
+    }
+
+    fn new_with_config<FAcc, FTx, Fb>(
+        history_hashes: Option<Vec<Word>>,
+        acc_fns: FAcc,
+        func_tx: FTx,
+        func_block: Fb,
+        logger_config: LoggerConfig,
+        hardfork: Hardfork,
+    ) -> Result<Self, Error>
     where
         FTx: FnOnce(Vec<&mut MockTransaction>, [MockAccount; NACC]),
         Fb: FnOnce(&mut MockBlock, Vec<MockTransaction>) -> &mut MockBlock,
@@ -174,6 +222,7 @@ impl<const NACC: usize, const NTX: usize> TestContext<NACC, NTX> {
             accounts.clone(),
             history_hashes.clone(),
             logger_config,
+            hardfork,
         )?;
 
         Ok(Self {
@@ -234,6 +283,7 @@ fn gen_geth_traces<const NACC: usize, const NTX: usize>(
     accounts: [Account; NACC],
     history_hashes: Option<Vec<Word>>,
     logger_config: LoggerConfig,
+    hardfork: Hardfork,
 ) -> Result<[GethExecTrace; NTX], Error> {
     let trace_config = TraceConfig {
         chain_id,
@@ -249,6 +299,7 @@ fn gen_geth_traces<const NACC: usize, const NTX: usize>(
             .map(eth_types::geth_types::Transaction::from)
             .collect(),
         logger_config,
+        hardfork,
     };
     let traces = trace(&trace_config)?;
     let result: [GethExecTrace; NTX] = traces.try_into().expect("Unexpected len mismatch");
@@ -3,13 +3,16 @@
 use super::{MOCK_ACCOUNTS, MOCK_CHAIN_ID, MOCK_GASPRICE};
 use eth_types::word;
 use eth_types::{
-    geth_types::Transaction as GethTransaction, AccessList, Address, Bytes, Hash, Transaction,
-    Word, U64,
+    geth_types::{Transaction as GethTransaction, EIP1559_TX_TYPE, EIP2930_TX_TYPE},
+    AccessList, Address, Bytes, Hash, Transaction, Word, U64,
 };
 use ethers_core::types::OtherFields;
 use ethers_core::{
     rand::{CryptoRng, RngCore},
-    types::TransactionRequest,
+    types::{
+        transaction::eip2718::TypedTransaction, Eip1559TransactionRequest,
+        Eip2930TransactionRequest, TransactionRequest,
+    },
 };
 use ethers_signers::{LocalWallet, Signer};
 use lazy_static::lazy_static;
@@ -298,18 +301,52 @@ impl MockTransaction {
         self
     }
 
+    /// Gas price paid by the transaction in a block with the given base fee,
+    /// which is the effective gas price for EIP-1559 transactions.
+    pub(crate) fn effective_gas_price(&self, base_fee: Word) -> Word {
+        if self.transaction_type.as_u64() == EIP1559_TX_TYPE {
+            self.max_fee_per_gas
+                .min(base_fee + self.max_priority_fee_per_gas)
+        } else {
+            self.gas_price
+        }
+    }
+
     /// Consumes the mutable ref to the MockTransaction returning the structure
     /// by value.
     pub fn build(&mut self) -> Self {
-        let tx = TransactionRequest::new()
-            .from(self.from.address())
-            .to(self.to.clone().unwrap_or_default().address())
-            .nonce(self.nonce)
-            .value(self.value)
-            .data(self.input.clone())
-            .gas(self.gas)
-            .gas_price(self.gas_price)
-            .chain_id(self.chain_id.low_u64());
+        let is_eip1559 = self.transaction_type.as_u64() == EIP1559_TX_TYPE;
+        let mut tx: TypedTransaction = if is_eip1559 {
+            Eip1559TransactionRequest::new()
+                .from(self.from.address())
+                .nonce(self.nonce)
+                .value(self.value)
+                .data(self.input.clone())
+                .gas(self.gas)
+                .max_fee_per_gas(self.max_fee_per_gas)
+                .max_priority_fee_per_gas(self.max_priority_fee_per_gas)
+                .access_list(self.access_list.clone())
+                .chain_id(self.chain_id.low_u64())
+                .into()
+        } else {
+            let req = TransactionRequest::new()
+                .from(self.from.address())
+                .nonce(self.nonce)
+                .value(self.value)
+                .data(self.input.clone())
+                .gas(self.gas)
+                .gas_price(self.gas_price)
+                .chain_id(self.chain_id.low_u64());
+            if self.transaction_type.as_u64() == EIP2930_TX_TYPE {
+                Eip2930TransactionRequest::new(req, self.access_list.clone()).into()
+            } else {
+                req.into()
+            }
+        };
+        // A transaction without `to` is a contract creation.
+        if let Some(to) = &self.to {
+            tx.set_to(to.address());
+        }
 
         match (self.v, self.r, self.s) {
             (None, None, None) => {
@@ -319,9 +356,15 @@ impl MockTransaction {
                         .from
                         .as_wallet()
                         .with_chain_id(self.chain_id.low_u64())
-                        .sign_transaction_sync(&tx.into());
+                        .sign_transaction_sync(&tx);
+                    // Typed transactions are signed with the y parity as `v`.
+                    let v = if !self.transaction_type.is_zero() {
+                        sig.v - 35 - self.chain_id.low_u64() * 2
+                    } else {
+                        sig.v
+                    };
                     // Set sig parameters
-                    self.sig_data((sig.v, sig.r, sig.s));
+                    self.sig_data((v, sig.r, sig.s));
                 }
             }
             (Some(_), Some(_), Some(_)) => (),
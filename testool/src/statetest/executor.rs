@@ -1,8 +1,9 @@
 use super::{AccountMatch, StateTest, StateTestResult};
 use crate::config::TestSuite;
+use crate::utils::TEST_FORK;
 use bus_mapping::circuit_input_builder::{CircuitInputBuilder, CircuitsParams};
 use bus_mapping::mock::BlockData;
-use eth_types::{geth_types, Address, Bytes, GethExecTrace, U256, U64};
+use eth_types::{evm_types::Hardfork, geth_types, Address, Bytes, GethExecTrace, U256, U64};
 use ethers_core::k256::ecdsa::SigningKey;
 use ethers_core::types::TransactionRequest;
 use ethers_signers::{LocalWallet, Signer};
@@ -10,7 +11,9 @@ use external_tracer::TraceConfig;
 use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
 use std::{collections::HashMap, str::FromStr};
 use thiserror::Error;
-use zkevm_circuits::{super_circuit::SuperCircuit, test_util::BytecodeTestConfig};
+use zkevm_circuits::{
+    pi_circuit::txs_commitment, super_circuit::SuperCircuit, test_util::BytecodeTestConfig,
+};
 
 const EVMERR_OOG: &str = "out of gas";
 const EVMERR_STACKUNDERFLOW: &str = "stack underflow";
@@ -109,6 +112,12 @@ fn check_post(
     Ok(())
 }
 
+fn test_hardfork() -> Hardfork {
+    TEST_FORK
+        .hardfork()
+        .expect("TEST_FORK is not supported by the circuits")
+}
+
 fn into_traceconfig(st: StateTest) -> (String, TraceConfig, StateTestResult) {
     let chain_id = 1;
     let wallet = LocalWallet::from_str(&hex::encode(st.secret_key.0)).unwrap();
@@ -142,14 +151,15 @@ fn into_traceconfig(st: StateTest) -> (String, TraceConfig, StateTestResult) {
             },
 
             transactions: vec![geth_types::Transaction {
+                transaction_type: 0,
                 from: st.from,
                 to: st.to,
                 nonce: st.nonce,
                 value: st.value,
                 gas_limit: U256::from(st.gas_limit),
                 gas_price: st.gas_price,
-                gas_fee_cap: U256::zero(),
-                gas_tip_cap: U256::zero(),
+                gas_fee_cap: st.gas_price,
+                gas_tip_cap: st.gas_price,
                 call_data: st.data,
                 access_list: None,
                 v: sig.v,
@@ -157,6 +167,7 @@ fn into_traceconfig(st: StateTest) -> (String, TraceConfig, StateTestResult) {
                 s: sig.s,
             }],
             accounts: st.pre,
+            hardfork: test_hardfork(),
             ..Default::default()
         },
         st.result,
@@ -181,12 +192,6 @@ pub fn run_test(
 
     let (_, trace_config, post) = into_traceconfig(st.clone());
 
-    if st.to.is_none() {
-        return Err(StateTestError::SkipUnimplemented(
-            "TransactionCreation".to_string(),
-        ));
-    }
-
     let geth_traces = external_tracer::trace(&trace_config);
     if st.exception {
         if geth_traces.is_ok() {
@@ -300,8 +305,13 @@ pub fn run_test(
             max_txs: 1,
             max_rws: 55000,
             max_calldata: 5000,
+            max_access_list: 1000,
             max_bytecode: 5000,
+            max_ecrecover: 0,
+            max_receipts_len: 5000,
             keccak_padding: None,
+            block_reward: U256::zero(),
+            hardfork: test_hardfork(),
         };
         let block_data = BlockData::new_from_geth_data_with_params(geth_data, circuits_params);
 
@@ -324,6 +334,12 @@ pub fn run_test(
             .map_err(|err| StateTestError::VerifierError(format!("{:#?}", err)))?;
     } else {
         geth_data.sign(&wallets);
+        geth_data.eth_block.transactions_root = txs_commitment(
+            geth_data.chain_id.as_u64(),
+            &geth_data.eth_block.transactions,
+        );
+        geth_data.eth_block.receipts_root =
+            SuperCircuit::<Fr, 1, 32, 255>::receipts_root(&geth_data);
 
         let (k, circuit, instance, _builder) =
             SuperCircuit::<Fr, 1, 32, 255>::build(geth_data).unwrap();
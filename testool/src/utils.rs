@@ -1,7 +1,7 @@
 use std::str::FromStr;
 
 use anyhow::{bail, Result};
-use eth_types::{bytecode::OpcodeWithData, Bytecode, GethExecTrace, U256};
+use eth_types::{bytecode::OpcodeWithData, evm_types::Hardfork, Bytecode, GethExecTrace, U256};
 use log::{error, info};
 use prettytable::Table;
 use std::process::Command;
@@ -51,6 +51,18 @@ impl FromStr for MainnetFork {
 }
 
 impl MainnetFork {
+    /// Returns the hardfork whose rules are used to execute the network's
+    /// blocks, or `None` if the network is older than Berlin.
+    pub fn hardfork(&self) -> Option<Hardfork> {
+        if *self >= Self::London {
+            Some(Hardfork::London)
+        } else if *self >= Self::Berlin {
+            Some(Hardfork::Berlin)
+        } else {
+            None
+        }
+    }
+
     pub fn in_network_range(expect: &[String]) -> Result<bool, anyhow::Error> {
         let in_network = if expect.is_empty() {
             true
@@ -187,4 +199,11 @@ mod test {
         assert!(MainnetFork::in_network_range(&[String::from(">=Istanbul")])
             .expect("can parse network"));
     }
+
+    #[test]
+    fn hardforks() {
+        assert_eq!(MainnetFork::Merge.hardfork(), Some(Hardfork::London));
+        assert_eq!(MainnetFork::Berlin.hardfork(), Some(Hardfork::Berlin));
+        assert_eq!(MainnetFork::Istanbul.hardfork(), None);
+    }
 }
@@ -0,0 +1,811 @@
+//! The Bloom circuit computes the logs bloom of the block from the TxLog rws
+//! and verifies it against the one in the block table.
+//!
+//! Each log contributes its address and each of its topics to the bloom. For
+//! each of these items, the keccak hash of its bytes is looked up in the
+//! keccak table, and the lower 11 bits of the first three pairs of bytes of
+//! the hash give the positions of the bits set in the 2048-bit bloom.
+//!
+//! The circuit has two kinds of rows:
+//! - Item rows, one per log item, which are looked up from the rw table, and
+//!   which expose the items in the bloom table for the EVM circuit.
+//! - Bit rows, one per bit of the bloom, from the most significant one. Each
+//!   bit is set if and only if some item sets it, and the bits are accumulated
+//!   into the words of the block table `LogsBloom` rows.
+
+use eth_types::{Field, ToAddress, ToBigEndian};
+use ethers_core::utils::keccak256;
+use gadgets::util::{select, sum, Expr};
+use halo2_proofs::{
+    circuit::{Layouter, Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, SecondPhase},
+    poly::Rotation,
+};
+use itertools::Itertools;
+use std::marker::PhantomData;
+
+use crate::{
+    evm_circuit::util::{constraint_builder::BaseConstraintBuilder, from_bytes, rlc},
+    table::{
+        BlockContextFieldTag, BlockTable, BloomTable, DynamicTableColumns, KeccakTable,
+        LookupTable, RwTable, RwTableTag, TxLogFieldTag,
+    },
+    util::{build_tx_log_expression, Challenges, SubCircuit, SubCircuitConfig},
+    witness::{self, Rw},
+};
+
+/// Number of bits of the logs bloom
+pub const LOGS_BLOOM_BITS: usize = 2048;
+/// Number of 32-byte words of the logs bloom
+pub const LOGS_BLOOM_WORDS: usize = LOGS_BLOOM_BITS / 256;
+/// Number of bits set in the logs bloom by each item
+const BITS_PER_ITEM: usize = 3;
+
+/// Returns the bytes of a log item which are hashed into the bloom
+fn item_input(rw: &Rw) -> Vec<u8> {
+    match rw {
+        Rw::TxLog {
+            field_tag: TxLogFieldTag::Address,
+            value,
+            ..
+        } => value.to_address().as_bytes().to_vec(),
+        Rw::TxLog {
+            field_tag: TxLogFieldTag::Topic,
+            value,
+            ..
+        } => value.to_be_bytes().to_vec(),
+        _ => unreachable!("log items are TxLog address or topic rws"),
+    }
+}
+
+/// Returns the pairs of bytes of the hash of a log item which give the bit
+/// positions set in the bloom
+fn hash_pairs(hash: &[u8; 32]) -> [usize; BITS_PER_ITEM] {
+    std::array::from_fn(|k| (hash[2 * k] as usize) << 8 | hash[2 * k + 1] as usize)
+}
+
+/// Config for BloomCircuit
+#[derive(Clone, Debug)]
+pub struct BloomCircuitConfig<F> {
+    /// Whether the row is an item row
+    q_item: Column<Fixed>,
+    /// Whether the row is a bit row
+    q_bit: Column<Fixed>,
+    /// Whether the bit row holds the most significant bit of a byte
+    q_byte_first: Column<Fixed>,
+    /// Whether the bit row holds the least significant bit of a byte
+    q_byte_last: Column<Fixed>,
+    /// Whether the bit row holds the least significant bit of the most
+    /// significant byte of a word
+    q_word_first: Column<Fixed>,
+    /// Whether the bit row holds the least significant bit of a word
+    q_word_last: Column<Fixed>,
+    /// Position of the bit in the bloom, from the least significant one
+    bit_position: Column<Fixed>,
+    /// Index of the word of the bit, from the most significant one
+    word_index: Column<Fixed>,
+    /// Rw counter of the TxLog write of the item
+    rw_counter: Column<Advice>,
+    /// Tx id of the log of the item
+    tx_id: Column<Advice>,
+    /// Log id of the log of the item
+    log_id: Column<Advice>,
+    /// Topic index of the item, 0 for the address
+    index: Column<Advice>,
+    /// Bytes of the item in little-endian order
+    item_bytes: [Column<Advice>; 32],
+    /// Bytes of the hash of the item in little-endian order
+    hash_bytes: [Column<Advice>; 32],
+    /// Bit positions set by the item
+    bit_positions: [Column<Advice>; BITS_PER_ITEM],
+    /// High bits of the pairs of bytes of the hash giving the bit positions
+    bit_positions_hi: [Column<Advice>; BITS_PER_ITEM],
+    /// Value of the bit
+    bit: Column<Advice>,
+    /// Which of the bit positions of some item sets the bit
+    bit_sources: [Column<Advice>; BITS_PER_ITEM],
+    /// Accumulator of the bits of the current byte
+    byte_acc: Column<Advice>,
+    /// Accumulator of the RLC of the bytes of the current word
+    word_rlc_acc: Column<Advice>,
+    /// BloomTable
+    pub bloom_table: BloomTable,
+    // External tables
+    /// RwTable
+    pub rw_table: RwTable,
+    /// KeccakTable
+    pub keccak_table: KeccakTable,
+    /// BlockTable
+    pub block_table: BlockTable,
+    _marker: PhantomData<F>,
+}
+
+/// Circuit configuration arguments
+pub struct BloomCircuitConfigArgs<F: Field> {
+    /// BloomTable
+    pub bloom_table: BloomTable,
+    /// RwTable
+    pub rw_table: RwTable,
+    /// KeccakTable
+    pub keccak_table: KeccakTable,
+    /// BlockTable
+    pub block_table: BlockTable,
+    /// Challenges
+    pub challenges: Challenges<Expression<F>>,
+}
+
+impl<F: Field> SubCircuitConfig<F> for BloomCircuitConfig<F> {
+    type ConfigArgs = BloomCircuitConfigArgs<F>;
+
+    /// Return a new BloomCircuitConfig
+    fn new(
+        meta: &mut ConstraintSystem<F>,
+        Self::ConfigArgs {
+            bloom_table,
+            rw_table,
+            keccak_table,
+            block_table,
+            challenges,
+        }: Self::ConfigArgs,
+    ) -> Self {
+        let q_item = meta.fixed_column();
+        let q_bit = meta.fixed_column();
+        let q_byte_first = meta.fixed_column();
+        let q_byte_last = meta.fixed_column();
+        let q_word_first = meta.fixed_column();
+        let q_word_last = meta.fixed_column();
+        let bit_position = meta.fixed_column();
+        let word_index = meta.fixed_column();
+        let rw_counter = meta.advice_column();
+        let tx_id = meta.advice_column();
+        let log_id = meta.advice_column();
+        let index = meta.advice_column();
+        let item_bytes = [(); 32].map(|_| meta.advice_column());
+        let hash_bytes = [(); 32].map(|_| meta.advice_column());
+        let bit_positions = [(); BITS_PER_ITEM].map(|_| meta.advice_column());
+        let bit_positions_hi = [(); BITS_PER_ITEM].map(|_| meta.advice_column());
+        let bit = meta.advice_column();
+        let bit_sources = [(); BITS_PER_ITEM].map(|_| meta.advice_column());
+        let byte_acc = meta.advice_column();
+        let word_rlc_acc = meta.advice_column_in(SecondPhase);
+
+        let evm_word_powers = challenges.evm_word_powers_of_randomness::<31>();
+        let keccak_input_powers = challenges.keccak_powers_of_randomness::<31>();
+
+        meta.create_gate("item row", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            let field_tag = meta.query_advice(bloom_table.field_tag, Rotation::cur());
+            let is_topic = field_tag - TxLogFieldTag::Address.expr();
+            let item_bytes = item_bytes.map(|column| meta.query_advice(column, Rotation::cur()));
+            let hash_bytes = hash_bytes.map(|column| meta.query_advice(column, Rotation::cur()));
+
+            cb.require_equal(
+                "is_enabled == 1 in item rows",
+                meta.query_advice(bloom_table.is_enabled, Rotation::cur()),
+                1.expr(),
+            );
+            cb.require_boolean("item is either an address or a topic", is_topic.clone());
+            cb.condition(1.expr() - is_topic.clone(), |cb| {
+                for byte in item_bytes[20..].iter() {
+                    cb.require_zero("address has 20 bytes", byte.clone());
+                }
+            });
+            cb.require_equal(
+                "value is built from the item bytes",
+                meta.query_advice(bloom_table.value, Rotation::cur()),
+                select::expr(
+                    is_topic,
+                    rlc::expr(&item_bytes, &evm_word_powers),
+                    from_bytes::expr(&item_bytes[..20]),
+                ),
+            );
+            for k in 0..BITS_PER_ITEM {
+                cb.require_equal(
+                    "pair of hash bytes == bit_position_hi * 2048 + bit_position",
+                    hash_bytes[31 - 2 * k].clone() * 256.expr() + hash_bytes[30 - 2 * k].clone(),
+                    meta.query_advice(bit_positions_hi[k], Rotation::cur())
+                        * LOGS_BLOOM_BITS.expr()
+                        + meta.query_advice(bit_positions[k], Rotation::cur()),
+                );
+            }
+
+            cb.gate(meta.query_fixed(q_item, Rotation::cur()))
+        });
+
+        meta.create_gate("bit row", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            let bit = meta.query_advice(bit, Rotation::cur());
+            let bit_sources = bit_sources.map(|column| meta.query_advice(column, Rotation::cur()));
+            let byte_acc = meta.query_advice(byte_acc, Rotation::cur());
+
+            cb.require_zero(
+                "is_enabled == 0 in bit rows",
+                meta.query_advice(bloom_table.is_enabled, Rotation::cur()),
+            );
+            cb.require_boolean("bit is boolean", bit.clone());
+            for source in bit_sources.iter() {
+                cb.require_boolean("bit source is boolean", source.clone());
+            }
+            cb.require_equal(
+                "a set bit has exactly one source",
+                sum::expr(&bit_sources),
+                bit.clone(),
+            );
+            cb.require_equal(
+                "byte_acc accumulates the bits from the most significant one",
+                byte_acc.clone(),
+                select::expr(
+                    meta.query_fixed(q_byte_first, Rotation::cur()),
+                    bit.clone(),
+                    meta.query_advice(byte_acc, Rotation::prev()) * 2.expr() + bit,
+                ),
+            );
+            cb.condition(meta.query_fixed(q_byte_last, Rotation::cur()), |cb| {
+                cb.require_equal(
+                    "word_rlc_acc accumulates the bytes from the most significant one",
+                    meta.query_advice(word_rlc_acc, Rotation::cur()),
+                    select::expr(
+                        meta.query_fixed(q_word_first, Rotation::cur()),
+                        byte_acc.clone(),
+                        meta.query_advice(word_rlc_acc, Rotation(-8)) * challenges.evm_word()
+                            + byte_acc.clone(),
+                    ),
+                );
+            });
+
+            cb.gate(meta.query_fixed(q_bit, Rotation::cur()))
+        });
+
+        meta.lookup_any("Item lookup in the rw table", |meta| {
+            let cond = meta.query_fixed(q_item, Rotation::cur());
+            vec![
+                meta.query_advice(rw_counter, Rotation::cur()),
+                1.expr(),
+                RwTableTag::TxLog.expr(),
+                meta.query_advice(tx_id, Rotation::cur()),
+                build_tx_log_expression(
+                    meta.query_advice(index, Rotation::cur()),
+                    meta.query_advice(bloom_table.field_tag, Rotation::cur()),
+                    meta.query_advice(log_id, Rotation::cur()),
+                ),
+                0.expr(),
+                0.expr(),
+                meta.query_advice(bloom_table.value, Rotation::cur()),
+                0.expr(),
+                0.expr(),
+                0.expr(),
+            ]
+            .into_iter()
+            .zip(rw_table.table_exprs(meta).into_iter())
+            .map(|(arg, table)| (cond.clone() * arg, table))
+            .collect()
+        });
+
+        meta.lookup_any("Item hash lookup in the keccak table", |meta| {
+            let cond = meta.query_fixed(q_item, Rotation::cur());
+            let is_topic = meta.query_advice(bloom_table.field_tag, Rotation::cur())
+                - TxLogFieldTag::Address.expr();
+            let item_bytes = item_bytes.map(|column| meta.query_advice(column, Rotation::cur()));
+            let hash_bytes = hash_bytes.map(|column| meta.query_advice(column, Rotation::cur()));
+            vec![
+                1.expr(),
+                rlc::expr(&item_bytes, &keccak_input_powers),
+                20.expr() + 12.expr() * is_topic,
+                rlc::expr(&hash_bytes, &evm_word_powers),
+            ]
+            .into_iter()
+            .zip(keccak_table.table_exprs(meta).into_iter())
+            .map(|(arg, table)| (cond.clone() * arg, table))
+            .collect()
+        });
+
+        meta.lookup_any("Bloom word lookup in the block table", |meta| {
+            let cond = meta.query_fixed(q_word_last, Rotation::cur());
+            vec![
+                BlockContextFieldTag::LogsBloom.expr(),
+                meta.query_fixed(word_index, Rotation::cur()),
+                meta.query_advice(word_rlc_acc, Rotation::cur()),
+            ]
+            .into_iter()
+            .zip(block_table.table_exprs(meta).into_iter())
+            .map(|(arg, table)| (cond.clone() * arg, table))
+            .collect()
+        });
+
+        for k in 0..BITS_PER_ITEM {
+            meta.lookup_any("Bit position of the item is set in the bloom", |meta| {
+                let q_item = meta.query_fixed(q_item, Rotation::cur());
+                let q_bit = meta.query_fixed(q_bit, Rotation::cur());
+                vec![
+                    (
+                        q_item.clone() * meta.query_advice(bit_positions[k], Rotation::cur()),
+                        q_bit.clone() * meta.query_fixed(bit_position, Rotation::cur()),
+                    ),
+                    (q_item, q_bit * meta.query_advice(bit, Rotation::cur())),
+                ]
+            });
+
+            meta.lookup_any("Bit position high bits are in range", |meta| {
+                let q_item = meta.query_fixed(q_item, Rotation::cur());
+                let q_bit = meta.query_fixed(q_bit, Rotation::cur());
+                vec![(
+                    q_item * meta.query_advice(bit_positions_hi[k], Rotation::cur()),
+                    q_bit * meta.query_fixed(bit_position, Rotation::cur()),
+                )]
+            });
+
+            meta.lookup_any(
+                "Set bit of the bloom is a bit position of an item",
+                |meta| {
+                    let cond = meta.query_fixed(q_bit, Rotation::cur())
+                        * meta.query_advice(bit_sources[k], Rotation::cur());
+                    let q_item = meta.query_fixed(q_item, Rotation::cur());
+                    vec![
+                        (
+                            cond.clone() * meta.query_fixed(bit_position, Rotation::cur()),
+                            q_item.clone() * meta.query_advice(bit_positions[k], Rotation::cur()),
+                        ),
+                        (cond, q_item),
+                    ]
+                },
+            );
+        }
+
+        Self {
+            q_item,
+            q_bit,
+            q_byte_first,
+            q_byte_last,
+            q_word_first,
+            q_word_last,
+            bit_position,
+            word_index,
+            rw_counter,
+            tx_id,
+            log_id,
+            index,
+            item_bytes,
+            hash_bytes,
+            bit_positions,
+            bit_positions_hi,
+            bit,
+            bit_sources,
+            byte_acc,
+            word_rlc_acc,
+            bloom_table,
+            rw_table,
+            keccak_table,
+            block_table,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F: Field> BloomCircuitConfig<F> {
+    /// Assign the log items and the bits of the bloom they set
+    pub fn assign_items(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        items: &[Rw],
+        challenges: &Challenges<Value<F>>,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "bloom circuit",
+            |mut region| {
+                // The first item setting each bit, with the index of the bit position
+                let mut sources = vec![None; LOGS_BLOOM_BITS];
+                for (offset, rw) in items.iter().enumerate() {
+                    let bit_positions =
+                        self.assign_item_row(&mut region, offset, rw, challenges)?;
+                    for (k, bit_position) in bit_positions.into_iter().enumerate() {
+                        sources[bit_position].get_or_insert(k);
+                    }
+                }
+
+                let mut byte_acc = 0u64;
+                let mut word_rlc_acc = Value::known(F::zero());
+                for row in 0..LOGS_BLOOM_BITS {
+                    let offset = items.len() + row;
+                    let bit_position = LOGS_BLOOM_BITS - 1 - row;
+                    let source = sources[bit_position];
+                    let bit = source.is_some() as u64;
+
+                    byte_acc = if row % 8 == 0 {
+                        bit
+                    } else {
+                        byte_acc * 2 + bit
+                    };
+                    if row % 8 == 7 {
+                        let byte = Value::known(F::from(byte_acc));
+                        word_rlc_acc = if row % 256 == 7 {
+                            byte
+                        } else {
+                            word_rlc_acc * challenges.evm_word() + byte
+                        };
+                    }
+
+                    for (name, column, value) in [
+                        ("q_bit", self.q_bit, true),
+                        ("q_byte_first", self.q_byte_first, row % 8 == 0),
+                        ("q_byte_last", self.q_byte_last, row % 8 == 7),
+                        ("q_word_first", self.q_word_first, row % 256 == 7),
+                        ("q_word_last", self.q_word_last, row % 256 == 255),
+                    ] {
+                        region.assign_fixed(
+                            || format!("{} {}", name, offset),
+                            column,
+                            offset,
+                            || Value::known(F::from(value as u64)),
+                        )?;
+                    }
+                    for (name, column, value) in [
+                        ("bit_position", self.bit_position, bit_position),
+                        ("word_index", self.word_index, row / 256),
+                    ] {
+                        region.assign_fixed(
+                            || format!("{} {}", name, offset),
+                            column,
+                            offset,
+                            || Value::known(F::from(value as u64)),
+                        )?;
+                    }
+
+                    for (column, value) in [(self.bit, bit), (self.byte_acc, byte_acc)]
+                        .into_iter()
+                        .chain(
+                            self.bit_sources
+                                .iter()
+                                .enumerate()
+                                .map(|(k, column)| (*column, (source == Some(k)) as u64)),
+                        )
+                    {
+                        region.assign_advice(
+                            || format!("bit row {}", offset),
+                            column,
+                            offset,
+                            || Value::known(F::from(value)),
+                        )?;
+                    }
+                    region.assign_advice(
+                        || format!("word_rlc_acc {}", offset),
+                        self.word_rlc_acc,
+                        offset,
+                        || word_rlc_acc,
+                    )?;
+                    for column in self.item_columns() {
+                        region.assign_advice(
+                            || format!("bit row {}", offset),
+                            column,
+                            offset,
+                            || Value::known(F::zero()),
+                        )?;
+                    }
+                }
+
+                Ok(())
+            },
+        )
+    }
+
+    /// Assign an item row, returning the bit positions set by the item
+    fn assign_item_row(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        rw: &Rw,
+        challenges: &Challenges<Value<F>>,
+    ) -> Result<[usize; BITS_PER_ITEM], Error> {
+        let (rw_counter, tx_id, log_id, index) = match *rw {
+            Rw::TxLog {
+                rw_counter,
+                tx_id,
+                log_id,
+                index,
+                ..
+            } => (rw_counter as u64, tx_id as u64, log_id, index as u64),
+            _ => unreachable!("log items are TxLog rws"),
+        };
+        let input = item_input(rw);
+        let hash = keccak256(&input);
+        let pairs = hash_pairs(&hash);
+        let bit_positions = pairs.map(|pair| pair % LOGS_BLOOM_BITS);
+
+        region.assign_fixed(
+            || format!("q_item {}", offset),
+            self.q_item,
+            offset,
+            || Value::known(F::one()),
+        )?;
+
+        for (column, value) in self
+            .bloom_table
+            .columns()
+            .into_iter()
+            .zip_eq(BloomTable::assignment(rw, challenges))
+        {
+            region.assign_advice(
+                || format!("bloom table row {}", offset),
+                column,
+                offset,
+                || value,
+            )?;
+        }
+
+        let item_bytes = input.iter().rev().copied().chain(std::iter::repeat(0));
+        let hash_bytes = hash.iter().rev().copied();
+        for (column, value) in [
+            (self.rw_counter, rw_counter),
+            (self.tx_id, tx_id),
+            (self.log_id, log_id),
+            (self.index, index),
+        ]
+        .into_iter()
+        .chain(self.item_bytes.into_iter().zip(item_bytes.map(u64::from)))
+        .chain(self.hash_bytes.into_iter().zip(hash_bytes.map(u64::from)))
+        .chain(
+            self.bit_positions
+                .into_iter()
+                .zip(bit_positions.map(|bit_position| bit_position as u64)),
+        )
+        .chain(
+            self.bit_positions_hi
+                .into_iter()
+                .zip(pairs.map(|pair| (pair / LOGS_BLOOM_BITS) as u64)),
+        ) {
+            region.assign_advice(
+                || format!("item row {}", offset),
+                column,
+                offset,
+                || Value::known(F::from(value)),
+            )?;
+        }
+
+        for column in [self.bit, self.byte_acc, self.word_rlc_acc]
+            .into_iter()
+            .chain(self.bit_sources)
+        {
+            region.assign_advice(
+                || format!("item row {}", offset),
+                column,
+                offset,
+                || Value::known(F::zero()),
+            )?;
+        }
+
+        Ok(bit_positions)
+    }
+
+    /// Advice columns only used by the item rows
+    fn item_columns(&self) -> Vec<Column<Advice>> {
+        [self.rw_counter, self.tx_id, self.log_id, self.index]
+            .into_iter()
+            .chain(self.item_bytes)
+            .chain(self.hash_bytes)
+            .chain(self.bit_positions)
+            .chain(self.bit_positions_hi)
+            .chain(self.bloom_table.columns())
+            .collect()
+    }
+}
+
+/// Bloom Circuit
+#[derive(Clone, Debug)]
+pub struct BloomCircuit<F: Field> {
+    /// Block
+    pub block: Option<witness::Block<F>>,
+}
+
+impl<F: Field> BloomCircuit<F> {
+    /// Return a new BloomCircuit
+    pub fn new(block: witness::Block<F>) -> Self {
+        Self { block: Some(block) }
+    }
+}
+
+impl<F: Field> Default for BloomCircuit<F> {
+    fn default() -> Self {
+        Self {
+            block: Some(witness::Block::default()),
+        }
+    }
+}
+
+impl<F: Field> SubCircuit<F> for BloomCircuit<F> {
+    type Config = BloomCircuitConfig<F>;
+
+    fn new_from_block(block: &witness::Block<F>) -> Self {
+        Self::new(block.clone())
+    }
+
+    /// Return the minimum number of rows required to prove the block
+    fn min_num_rows_block(block: &witness::Block<F>) -> usize {
+        block.rws.logs_bloom_items().len() + LOGS_BLOOM_BITS
+    }
+
+    /// Make the assignments to the BloomCircuit
+    fn synthesize_sub(
+        &self,
+        config: &Self::Config,
+        challenges: &Challenges<Value<F>>,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let block = self.block.as_ref().unwrap();
+        config.assign_items(layouter, &block.rws.logs_bloom_items(), challenges)
+    }
+}
+
+/// Dev helpers
+#[cfg(any(feature = "test", test))]
+pub mod dev {
+    use super::*;
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::{MockProver, VerifyFailure},
+        plonk::{Challenge, Circuit},
+    };
+
+    impl<F: Field> Circuit<F> for BloomCircuit<F> {
+        type Config = (BloomCircuitConfig<F>, Challenges<Challenge>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let bloom_table = BloomTable::construct(meta);
+            let rw_table = RwTable::construct(meta);
+            let keccak_table = KeccakTable::construct(meta);
+            let block_table = BlockTable::construct(meta);
+            let challenges = Challenges::construct(meta);
+            let challenge_exprs = challenges.exprs(meta);
+
+            (
+                BloomCircuitConfig::new(
+                    meta,
+                    BloomCircuitConfigArgs {
+                        bloom_table,
+                        rw_table,
+                        keccak_table,
+                        block_table,
+                        challenges: challenge_exprs,
+                    },
+                ),
+                challenges,
+            )
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let block = self.block.as_ref().unwrap();
+            let challenge_values = config.1.values(&mut layouter);
+
+            config.0.rw_table.load(
+                &mut layouter,
+                &block.rws.table_assignments(),
+                block.circuits_params.max_rws,
+                &challenge_values,
+            )?;
+            config.0.keccak_table.dev_load(
+                &mut layouter,
+                &block.keccak_inputs,
+                &challenge_values,
+            )?;
+            config
+                .0
+                .block_table
+                .load(&mut layouter, &block.context, &challenge_values)?;
+            self.synthesize_sub(&config.0, &challenge_values, &mut layouter)
+        }
+    }
+
+    /// Test bloom circuit with the provided block witness
+    pub fn test_bloom_circuit<F: Field>(
+        k: u32,
+        block: witness::Block<F>,
+    ) -> Result<(), Vec<VerifyFailure>> {
+        let circuit = BloomCircuit::<F>::new(block);
+        let prover = MockProver::<F>::run(k, &circuit, vec![]).unwrap();
+        prover.verify_par()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::dev::test_bloom_circuit;
+    use bus_mapping::mock::BlockData;
+    use eth_types::{bytecode, geth_types::GethData, Bytecode, ToBigEndian, Word};
+    use ethers_core::types::{Bloom, BloomInput};
+    use halo2_proofs::halo2curves::bn256::Fr;
+    use mock::{
+        test_ctx::helpers::{account_0_code_account_1_no_code, tx_from_1_to_0},
+        TestContext, MOCK_ACCOUNTS,
+    };
+
+    use crate::evm_circuit::witness::block_convert;
+
+    fn test_ok(
+        code: Bytecode,
+        logs_bloom: Bloom,
+    ) -> Result<(), Vec<halo2_proofs::dev::VerifyFailure>> {
+        let test_ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _txs| block.logs_bloom(logs_bloom),
+        )
+        .unwrap();
+        let block: GethData = test_ctx.into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        let block = block_convert::<Fr>(&builder.block, &builder.code_db).unwrap();
+        test_bloom_circuit(12, block)
+    }
+
+    fn logs_bloom(logs: &[&[Word]]) -> Bloom {
+        let mut bloom = Bloom::default();
+        for topics in logs {
+            bloom.accrue(BloomInput::Raw(MOCK_ACCOUNTS[0].as_bytes()));
+            for topic in topics.iter() {
+                bloom.accrue(BloomInput::Raw(&topic.to_be_bytes()));
+            }
+        }
+        bloom
+    }
+
+    fn log_code() -> Bytecode {
+        bytecode! {
+            PUSH1(0)            // length
+            PUSH1(0)            // offset
+            LOG0
+            PUSH32(Word::MAX)   // topic
+            PUSH32(0xcafe)      // topic
+            PUSH1(0)            // length
+            PUSH1(0)            // offset
+            LOG2
+            STOP
+        }
+    }
+
+    #[test]
+    fn bloom_circuit_no_logs() {
+        let code = bytecode! {
+            STOP
+        };
+        assert_eq!(test_ok(code, Bloom::default()), Ok(()));
+    }
+
+    #[test]
+    fn bloom_circuit_logs() {
+        let logs_bloom = logs_bloom(&[&[], &[Word::MAX, Word::from(0xcafe)]]);
+        assert_eq!(test_ok(log_code(), logs_bloom), Ok(()));
+    }
+
+    #[test]
+    fn bloom_circuit_reverted_logs() {
+        let code = bytecode! {
+            PUSH32(Word::MAX)   // topic
+            PUSH1(0)            // length
+            PUSH1(0)            // offset
+            LOG1
+            PUSH1(0)
+            PUSH1(0)
+            REVERT
+        };
+        assert_eq!(test_ok(code, Bloom::default()), Ok(()));
+    }
+
+    #[test]
+    fn bloom_circuit_wrong_bloom() {
+        assert!(test_ok(log_code(), Bloom::default()).is_err());
+        let logs_bloom = logs_bloom(&[&[Word::MAX]]);
+        assert!(test_ok(log_code(), logs_bloom).is_err());
+    }
+}
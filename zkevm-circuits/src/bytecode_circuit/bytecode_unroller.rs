@@ -616,7 +616,7 @@ impl<F: Field> BytecodeCircuitConfig<F> {
     /// load fixed tables
     pub(crate) fn load_aux_tables(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
         // push table: BYTE -> NUM_PUSHED:
-        // [0, OpcodeId::PUSH1] -> 0
+        // [0, OpcodeId::PUSH1] -> 0, including OpcodeId::PUSH0
         // [OpcodeId::PUSH1, OpcodeId::PUSH32] -> [1..32]
         // [OpcodeId::PUSH32, 256] -> 0
         layouter.assign_region(
@@ -780,6 +780,20 @@ mod tests {
         F::from(123456)
     }
 
+    /// Verify PUSH0 has no push data
+    #[test]
+    fn bytecode_push0() {
+        assert_eq!(get_push_size(OpcodeId::PUSH0.as_u8()), 0);
+        let unrolled = unroll::<Fr>(vec![
+            OpcodeId::PUSH0.as_u8(),
+            OpcodeId::PUSH1.as_u8(),
+            OpcodeId::PUSH0.as_u8(),
+            OpcodeId::PUSH0.as_u8(),
+        ]);
+        let is_code: Vec<_> = unrolled.rows[1..].iter().map(|row| row.is_code).collect();
+        assert_eq!(is_code, [1u64, 1, 0, 1].map(Fr::from).to_vec());
+    }
+
     /// Verify unrolling code
     #[test]
     fn bytecode_unrolling() {
@@ -308,7 +308,7 @@ impl<F: Field> SubCircuitConfig<F> for CopyCircuitConfig<F> {
             cb.require_equal(
                 "rows[2].value == rows[0].value * r + rows[1].value",
                 meta.query_advice(value, Rotation(2)),
-                meta.query_advice(value, Rotation::cur()) * challenges.evm_word()
+                meta.query_advice(value, Rotation::cur()) * challenges.keccak_input()
                     + meta.query_advice(value, Rotation::next()),
             );
 
@@ -725,13 +725,11 @@ pub mod dev {
                 .tx_table
                 .load(&mut layouter, &block.txs, self.max_txs, &challenge_values)?;
 
-            // TODO: RW table can't be passed `Challenges<Value<F>>` until the EVM circuit
-            // is not migrated to the challenge API.
             config.0.rw_table.load(
                 &mut layouter,
                 &block.rws.table_assignments(),
                 block.circuits_params.max_rws,
-                challenge_values.evm_word(),
+                &challenge_values,
             )?;
             config.0.bytecode_table.load(
                 &mut layouter,
@@ -13,13 +13,17 @@ pub(crate) mod util;
 
 pub mod table;
 
-use crate::table::{BlockTable, BytecodeTable, CopyTable, ExpTable, KeccakTable, RwTable, TxTable};
+use crate::table::{
+    BlockTable, BloomTable, BytecodeTable, CopyTable, ExpTable, KeccakTable, PrecompileTable,
+    RwTable, SigTable, TxTable,
+};
 use crate::util::{log2_ceil, Challenges, SubCircuit, SubCircuitConfig};
 pub use crate::witness;
 use bus_mapping::evm::OpcodeId;
-use eth_types::Field;
+use eth_types::{evm_types::Hardfork, Field};
 use execution::ExecutionConfig;
 use itertools::Itertools;
+use step::ExecutionState;
 use strum::IntoEnumIterator;
 use table::FixedTableTag;
 use witness::Block;
@@ -38,12 +42,15 @@ pub struct EvmCircuitConfig<F> {
     copy_table: CopyTable,
     keccak_table: KeccakTable,
     exp_table: ExpTable,
+    sig_table: SigTable,
+    precompile_table: PrecompileTable,
+    bloom_table: BloomTable,
 }
 
 /// Circuit configuration arguments
 pub struct EvmCircuitConfigArgs<F: Field> {
-    /// Power of randomness
-    pub power_of_randomness: [Expression<F>; 31],
+    /// Challenges
+    pub challenges: Challenges<Expression<F>>,
     /// TxTable
     pub tx_table: TxTable,
     /// RwTable
@@ -58,6 +65,12 @@ pub struct EvmCircuitConfigArgs<F: Field> {
     pub keccak_table: KeccakTable,
     /// ExpTable
     pub exp_table: ExpTable,
+    /// SigTable
+    pub sig_table: SigTable,
+    /// PrecompileTable
+    pub precompile_table: PrecompileTable,
+    /// BloomTable
+    pub bloom_table: BloomTable,
 }
 
 impl<F: Field> SubCircuitConfig<F> for EvmCircuitConfig<F> {
@@ -68,7 +81,7 @@ impl<F: Field> SubCircuitConfig<F> for EvmCircuitConfig<F> {
     fn new(
         meta: &mut ConstraintSystem<F>,
         Self::ConfigArgs {
-            power_of_randomness,
+            challenges,
             tx_table,
             rw_table,
             bytecode_table,
@@ -76,13 +89,16 @@ impl<F: Field> SubCircuitConfig<F> for EvmCircuitConfig<F> {
             copy_table,
             keccak_table,
             exp_table,
+            sig_table,
+            precompile_table,
+            bloom_table,
         }: Self::ConfigArgs,
     ) -> Self {
         let fixed_table = [(); 4].map(|_| meta.fixed_column());
         let byte_table = [(); 1].map(|_| meta.fixed_column());
         let execution = Box::new(ExecutionConfig::configure(
             meta,
-            power_of_randomness,
+            challenges,
             &fixed_table,
             &byte_table,
             &tx_table,
@@ -92,6 +108,9 @@ impl<F: Field> SubCircuitConfig<F> for EvmCircuitConfig<F> {
             &copy_table,
             &keccak_table,
             &exp_table,
+            &sig_table,
+            &precompile_table,
+            &bloom_table,
         ));
 
         Self {
@@ -105,22 +124,26 @@ impl<F: Field> SubCircuitConfig<F> for EvmCircuitConfig<F> {
             copy_table,
             keccak_table,
             exp_table,
+            sig_table,
+            precompile_table,
+            bloom_table,
         }
     }
 }
 
 impl<F: Field> EvmCircuitConfig<F> {
-    /// Load fixed table
+    /// Load fixed table built for the hardfork
     pub fn load_fixed_table(
         &self,
         layouter: &mut impl Layouter<F>,
         fixed_table_tags: Vec<FixedTableTag>,
+        hardfork: Hardfork,
     ) -> Result<(), Error> {
         layouter.assign_region(
             || "fixed table",
             |mut region| {
                 for (offset, row) in std::iter::once([F::zero(); 4])
-                    .chain(fixed_table_tags.iter().flat_map(|tag| tag.build()))
+                    .chain(fixed_table_tags.iter().flat_map(|tag| tag.build(hardfork)))
                     .enumerate()
                 {
                     for (column, value) in self.fixed_table.iter().zip_eq(row) {
@@ -172,6 +195,7 @@ impl<F: Field> EvmCircuitConfig<F> {
                     num_rows += self.execution.get_step_height(step.execution_state);
                 }
             }
+            num_rows += self.execution.get_step_height(ExecutionState::BlockReward);
             num_rows += 1; // EndBlock
         } else {
             num_rows += block.evm_circuit_pad_to;
@@ -218,7 +242,7 @@ impl<F: Field> SubCircuit<F> for EvmCircuit<F> {
             EvmCircuit::<F>::get_num_rows_required(block);
         let num_rows_required_for_fixed_table: usize = detect_fixed_table_tags(block)
             .iter()
-            .map(|tag| tag.build::<F>().count())
+            .map(|tag| tag.build::<F>(block.circuits_params.hardfork).count())
             .sum();
         std::cmp::max(
             num_rows_required_for_execution_steps,
@@ -230,14 +254,18 @@ impl<F: Field> SubCircuit<F> for EvmCircuit<F> {
     fn synthesize_sub(
         &self,
         config: &Self::Config,
-        _challenges: &Challenges<Value<F>>,
+        challenges: &Challenges<Value<F>>,
         layouter: &mut impl Layouter<F>,
     ) -> Result<(), Error> {
         let block = self.block.as_ref().unwrap();
 
-        config.load_fixed_table(layouter, self.fixed_table_tags.clone())?;
+        config.load_fixed_table(
+            layouter,
+            self.fixed_table_tags.clone(),
+            block.circuits_params.hardfork,
+        )?;
         config.load_byte_table(layouter)?;
-        config.execution.assign_block(layouter, block)
+        config.execution.assign_block(layouter, block, challenges)
     }
 }
 
@@ -270,15 +298,18 @@ pub mod test {
     use crate::{
         evm_circuit::{witness::Block, EvmCircuitConfig},
         exp_circuit::OFFSET_INCREMENT,
-        table::{BlockTable, BytecodeTable, CopyTable, ExpTable, KeccakTable, RwTable, TxTable},
-        util::{power_of_randomness_from_instance, Challenges},
+        table::{
+            BlockTable, BloomTable, BytecodeTable, CopyTable, ExpTable, KeccakTable,
+            PrecompileTable, RwTable, SigTable, TxTable,
+        },
+        util::Challenges,
         witness::block_convert,
     };
     use bus_mapping::{circuit_input_builder::CircuitsParams, mock::BlockData};
     use eth_types::{geth_types::GethData, Field, Word};
     use halo2_proofs::halo2curves::bn256::Fr;
     use halo2_proofs::{
-        circuit::{Layouter, SimpleFloorPlanner, Value},
+        circuit::{Layouter, SimpleFloorPlanner},
         dev::{MockProver, VerifyFailure},
         plonk::{Circuit, ConstraintSystem, Error},
     };
@@ -308,7 +339,7 @@ pub mod test {
     }
 
     impl<F: Field> Circuit<F> for EvmCircuit<F> {
-        type Config = EvmCircuitConfig<F>;
+        type Config = (EvmCircuitConfig<F>, Challenges);
         type FloorPlanner = SimpleFloorPlanner;
 
         fn without_witnesses(&self) -> Self {
@@ -324,33 +355,41 @@ pub mod test {
             let copy_table = CopyTable::construct(meta, q_copy_table);
             let keccak_table = KeccakTable::construct(meta);
             let exp_table = ExpTable::construct(meta);
-
-            let power_of_randomness = power_of_randomness_from_instance(meta);
-            EvmCircuitConfig::new(
-                meta,
-                EvmCircuitConfigArgs {
-                    power_of_randomness,
-                    tx_table,
-                    rw_table,
-                    bytecode_table,
-                    block_table,
-                    copy_table,
-                    keccak_table,
-                    exp_table,
-                },
+            let sig_table = SigTable::construct(meta);
+            let precompile_table = PrecompileTable::construct(meta);
+            let bloom_table = BloomTable::construct(meta);
+
+            let challenges = Challenges::construct(meta);
+            let challenge_exprs = challenges.exprs(meta);
+
+            (
+                EvmCircuitConfig::new(
+                    meta,
+                    EvmCircuitConfigArgs {
+                        challenges: challenge_exprs,
+                        tx_table,
+                        rw_table,
+                        bytecode_table,
+                        block_table,
+                        copy_table,
+                        keccak_table,
+                        exp_table,
+                        sig_table,
+                        precompile_table,
+                        bloom_table,
+                    },
+                ),
+                challenges,
             )
         }
 
         fn synthesize(
             &self,
-            config: Self::Config,
+            (config, challenges): Self::Config,
             mut layouter: impl Layouter<F>,
         ) -> Result<(), Error> {
             let block = self.block.as_ref().unwrap();
-            let challenges = Challenges::mock(
-                Value::known(block.randomness),
-                Value::known(block.randomness),
-            );
+            let challenges = challenges.values(&mut layouter);
 
             config.tx_table.load(
                 &mut layouter,
@@ -363,19 +402,30 @@ pub mod test {
                 &mut layouter,
                 &block.rws.table_assignments(),
                 block.circuits_params.max_rws,
-                Value::known(block.randomness),
+                &challenges,
             )?;
             config
                 .bytecode_table
                 .load(&mut layouter, block.bytecodes.values(), &challenges)?;
             config
                 .block_table
-                .load(&mut layouter, &block.context, block.randomness)?;
+                .load(&mut layouter, &block.context, &challenges)?;
             config.copy_table.load(&mut layouter, block, &challenges)?;
             config
                 .keccak_table
                 .dev_load(&mut layouter, &block.sha3_inputs, &challenges)?;
             config.exp_table.load(&mut layouter, block)?;
+            config
+                .sig_table
+                .dev_load(&mut layouter, block, &challenges)?;
+            config.precompile_table.dev_load(
+                &mut layouter,
+                &block.precompile_events,
+                &challenges,
+            )?;
+            config
+                .bloom_table
+                .dev_load(&mut layouter, &block.rws, &challenges)?;
 
             self.synthesize_sub(&config, &challenges, &mut layouter)
         }
@@ -384,13 +434,13 @@ pub mod test {
     impl<F: Field> EvmCircuit<F> {
         pub fn get_num_rows_required(block: &Block<F>) -> usize {
             let mut cs = ConstraintSystem::default();
-            let config = EvmCircuit::<F>::configure(&mut cs);
+            let (config, _) = EvmCircuit::<F>::configure(&mut cs);
             config.get_num_rows_required(block)
         }
 
         pub fn get_active_rows(block: &Block<F>) -> (Vec<usize>, Vec<usize>) {
             let mut cs = ConstraintSystem::default();
-            let config = EvmCircuit::<F>::configure(&mut cs);
+            let (config, _) = EvmCircuit::<F>::configure(&mut cs);
             config.get_active_rows(block)
         }
     }
@@ -427,7 +477,7 @@ pub mod test {
         let num_rows_required_for_rw_table: usize = block.circuits_params.max_rws;
         let num_rows_required_for_fixed_table: usize = detect_fixed_table_tags(block)
             .iter()
-            .map(|tag| tag.build::<F>().count())
+            .map(|tag| tag.build::<F>(block.circuits_params.hardfork).count())
             .sum();
         let num_rows_required_for_bytecode_table: usize = block
             .bytecodes
@@ -444,6 +494,9 @@ pub mod test {
             .iter()
             .map(|e| e.steps.len() * OFFSET_INCREMENT)
             .sum();
+        let num_rows_required_for_sig_table: usize = block.precompile_events.len() + 1;
+        let num_rows_required_for_precompile_table: usize = block.precompile_events.len() + 1;
+        let num_rows_required_for_bloom_table: usize = block.rws.logs_bloom_items().len() + 1;
 
         const NUM_BLINDING_ROWS: usize = 64;
 
@@ -456,6 +509,9 @@ pub mod test {
             num_rows_required_for_keccak_table,
             num_rows_required_for_tx_table,
             num_rows_required_for_exp_table,
+            num_rows_required_for_sig_table,
+            num_rows_required_for_precompile_table,
+            num_rows_required_for_bloom_table,
         ])
         .unwrap();
 
@@ -467,7 +523,10 @@ pub mod test {
             num_rows_required_for_copy_table,
             num_rows_required_for_keccak_table,
             num_rows_required_for_tx_table,
-            num_rows_required_for_exp_table
+            num_rows_required_for_exp_table,
+            num_rows_required_for_sig_table,
+            num_rows_required_for_precompile_table,
+            num_rows_required_for_bloom_table
         ]);
         log::debug!("evm circuit uses k = {}, rows = {}", k, rows_needed);
         k
@@ -479,23 +538,13 @@ pub mod test {
         EvmCircuit::<F>::new_dev(block, fixed_table_tags)
     }
 
-    pub fn get_test_instance<F: Field>(block: &Block<F>) -> Vec<Vec<F>> {
-        let k = get_test_degree(block);
-
-        (1..32)
-            .map(|exp| vec![block.randomness.pow(&[exp, 0, 0, 0]); (1 << k) - 64])
-            .collect()
-    }
-
     pub fn run_test_circuit<F: Field>(block: Block<F>) -> Result<(), Vec<VerifyFailure>> {
         let k = get_test_degree(&block);
 
         let (active_gate_rows, active_lookup_rows) = EvmCircuit::<F>::get_active_rows(&block);
 
-        let power_of_randomness = get_test_instance(&block);
-
         let circuit = get_test_cicuit_from_block(block);
-        let prover = MockProver::<F>::run(k, &circuit, power_of_randomness).unwrap();
+        let prover = MockProver::<F>::run(k, &circuit, vec![]).unwrap();
         prover.verify_at_rows_par(active_gate_rows.into_iter(), active_lookup_rows.into_iter())
     }
 }
@@ -552,7 +601,7 @@ mod evm_circuit_stats {
     #[test]
     pub fn get_evm_states_stats() {
         let mut meta = ConstraintSystem::<Fr>::default();
-        let circuit = EvmCircuit::configure(&mut meta);
+        let (circuit, _) = EvmCircuit::configure(&mut meta);
 
         let mut implemented_states = Vec::new();
         for state in ExecutionState::iter() {
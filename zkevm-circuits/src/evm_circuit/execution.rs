@@ -11,14 +11,17 @@ use crate::{
         witness::{Block, Call, ExecStep, Transaction},
     },
     table::LookupTable,
-    util::{query_expression, Expr},
+    util::{query_expression, Challenges, Expr},
 };
 use eth_types::Field;
 use gadgets::util::not;
 use halo2_proofs::{
     arithmetic::FieldExt,
     circuit::{Layouter, Region, Value},
-    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Selector, VirtualCells},
+    plonk::{
+        Advice, Column, ConstraintSystem, Error, Expression, Fixed, SecondPhase, Selector,
+        VirtualCells,
+    },
     poly::Rotation,
 };
 use std::{
@@ -32,8 +35,10 @@ mod addmod;
 mod address;
 mod balance;
 mod begin_tx;
+mod begin_tx_access_list;
 mod bitwise;
 mod block_ctx;
+mod block_reward;
 mod blockhash;
 mod byte;
 mod calldatacopy;
@@ -46,16 +51,34 @@ mod chainid;
 mod codecopy;
 mod codesize;
 mod comparator;
+mod create;
 mod dummy;
 mod dup;
 mod end_block;
 mod end_tx;
+mod error_contract_address_collision;
+mod error_invalid_creation_code;
 mod error_invalid_jump;
+mod error_invalid_opcode;
+mod error_max_code_size_exceeded;
+mod error_oog_account_access;
 mod error_oog_call;
+mod error_oog_code_store;
 mod error_oog_constant;
+mod error_oog_create2;
+mod error_oog_exp;
+mod error_oog_log;
+mod error_oog_memory_copy;
+mod error_oog_self_destruct;
+mod error_oog_sha3;
+mod error_oog_sload_sstore;
 mod error_oog_static_memory;
+mod error_precheck;
+mod error_return_data_oob;
 mod error_stack;
+mod error_write_protection;
 mod exp;
+mod extcodecopy;
 mod extcodehash;
 mod extcodesize;
 mod gas;
@@ -74,12 +97,16 @@ mod opcode_not;
 mod origin;
 mod pc;
 mod pop;
+mod precompile;
 mod push;
+mod push0;
 mod return_revert;
 mod returndatacopy;
 mod returndatasize;
+mod sar;
 mod sdiv_smod;
 mod selfbalance;
+mod selfdestruct;
 mod sha3;
 mod shl_shr;
 mod signed_comparator;
@@ -95,8 +122,10 @@ use addmod::AddModGadget;
 use address::AddressGadget;
 use balance::BalanceGadget;
 use begin_tx::BeginTxGadget;
+use begin_tx_access_list::BeginTxAccessListGadget;
 use bitwise::BitwiseGadget;
 use block_ctx::{BlockCtxU160Gadget, BlockCtxU256Gadget, BlockCtxU64Gadget};
+use block_reward::BlockRewardGadget;
 use blockhash::BlockHashGadget;
 use byte::ByteGadget;
 use calldatacopy::CallDataCopyGadget;
@@ -109,15 +138,33 @@ use chainid::ChainIdGadget;
 use codecopy::CodeCopyGadget;
 use codesize::CodesizeGadget;
 use comparator::ComparatorGadget;
+use create::CreateGadget;
 use dummy::DummyGadget;
 use dup::DupGadget;
 use end_block::EndBlockGadget;
 use end_tx::EndTxGadget;
+use error_contract_address_collision::ErrorContractAddressCollisionGadget;
+use error_invalid_creation_code::ErrorInvalidCreationCodeGadget;
 use error_invalid_jump::ErrorInvalidJumpGadget;
+use error_invalid_opcode::ErrorInvalidOpcodeGadget;
+use error_max_code_size_exceeded::ErrorMaxCodeSizeExceededGadget;
+use error_oog_account_access::ErrorOOGAccountAccessGadget;
 use error_oog_call::ErrorOOGCallGadget;
+use error_oog_code_store::ErrorOOGCodeStoreGadget;
 use error_oog_constant::ErrorOOGConstantGadget;
+use error_oog_create2::ErrorOOGCreate2Gadget;
+use error_oog_exp::ErrorOOGExpGadget;
+use error_oog_log::ErrorOOGLogGadget;
+use error_oog_memory_copy::ErrorOOGMemoryCopyGadget;
+use error_oog_self_destruct::ErrorOOGSelfDestructGadget;
+use error_oog_sha3::ErrorOOGSha3Gadget;
+use error_oog_sload_sstore::ErrorOOGSloadSstoreGadget;
+use error_precheck::ErrorPrecheckGadget;
+use error_return_data_oob::ErrorReturnDataOutOfBoundGadget;
 use error_stack::ErrorStackGadget;
+use error_write_protection::ErrorWriteProtectionGadget;
 use exp::ExponentiationGadget;
+use extcodecopy::ExtcodecopyGadget;
 use extcodehash::ExtcodehashGadget;
 use extcodesize::ExtcodesizeGadget;
 use gas::GasGadget;
@@ -135,12 +182,16 @@ use opcode_not::NotGadget;
 use origin::OriginGadget;
 use pc::PcGadget;
 use pop::PopGadget;
+use precompile::PrecompileGadget;
 use push::PushGadget;
+use push0::Push0Gadget;
 use return_revert::ReturnRevertGadget;
 use returndatacopy::ReturnDataCopyGadget;
 use returndatasize::ReturnDataSizeGadget;
+use sar::SarGadget;
 use sdiv_smod::SignedDivModGadget;
 use selfbalance::SelfbalanceGadget;
+use selfdestruct::SelfdestructGadget;
 use shl_shr::ShlShrGadget;
 use signed_comparator::SignedComparatorGadget;
 use signextend::SignextendGadget;
@@ -190,6 +241,8 @@ pub(crate) struct ExecutionConfig<F> {
     stored_expressions_map: HashMap<ExecutionState, Vec<StoredExpression<F>>>,
     // internal state gadgets
     begin_tx_gadget: BeginTxGadget<F>,
+    begin_tx_access_list_gadget: BeginTxAccessListGadget<F>,
+    block_reward_gadget: BlockRewardGadget<F>,
     end_block_gadget: EndBlockGadget<F>,
     end_tx_gadget: EndTxGadget<F>,
     // opcode gadgets
@@ -209,9 +262,11 @@ pub(crate) struct ExecutionConfig<F> {
     codecopy_gadget: CodeCopyGadget<F>,
     codesize_gadget: CodesizeGadget<F>,
     comparator_gadget: ComparatorGadget<F>,
+    create_gadget: CreateGadget<F>,
     dup_gadget: DupGadget<F>,
     exp_gadget: ExponentiationGadget<F>,
     extcodehash_gadget: ExtcodehashGadget<F>,
+    extcodecopy_gadget: ExtcodecopyGadget<F>,
     extcodesize_gadget: ExtcodesizeGadget<F>,
     gas_gadget: GasGadget<F>,
     gasprice_gadget: GasPriceGadget<F>,
@@ -229,18 +284,16 @@ pub(crate) struct ExecutionConfig<F> {
     pc_gadget: PcGadget<F>,
     pop_gadget: PopGadget<F>,
     push_gadget: PushGadget<F>,
+    push0_gadget: Push0Gadget<F>,
     return_revert_gadget: ReturnRevertGadget<F>,
+    sar_gadget: SarGadget<F>,
     sdiv_smod_gadget: SignedDivModGadget<F>,
     selfbalance_gadget: SelfbalanceGadget<F>,
     sha3_gadget: Sha3Gadget<F>,
     shl_shr_gadget: ShlShrGadget<F>,
-    sar_gadget: DummyGadget<F, 2, 1, { ExecutionState::SAR }>,
-    extcodecopy_gadget: DummyGadget<F, 4, 0, { ExecutionState::EXTCODECOPY }>,
     returndatasize_gadget: ReturnDataSizeGadget<F>,
     returndatacopy_gadget: ReturnDataCopyGadget<F>,
-    create_gadget: DummyGadget<F, 3, 1, { ExecutionState::CREATE }>,
-    create2_gadget: DummyGadget<F, 4, 1, { ExecutionState::CREATE2 }>,
-    selfdestruct_gadget: DummyGadget<F, 1, 0, { ExecutionState::SELFDESTRUCT }>,
+    selfdestruct_gadget: SelfdestructGadget<F>,
     signed_comparator_gadget: SignedComparatorGadget<F>,
     signextend_gadget: SignextendGadget<F>,
     sload_gadget: SloadGadget<F>,
@@ -251,45 +304,56 @@ pub(crate) struct ExecutionConfig<F> {
     block_ctx_u64_gadget: BlockCtxU64Gadget<F>,
     block_ctx_u160_gadget: BlockCtxU160Gadget<F>,
     block_ctx_u256_gadget: BlockCtxU256Gadget<F>,
+    // precompile gadgets
+    precompile_ecrecover: PrecompileGadget<F, { ExecutionState::PrecompileEcRecover }>,
+    precompile_sha256: PrecompileGadget<F, { ExecutionState::PrecompileSha256 }>,
+    precompile_ripemd160: PrecompileGadget<F, { ExecutionState::PrecompileRipemd160 }>,
+    precompile_identity: PrecompileGadget<F, { ExecutionState::PrecompileIdentity }>,
+    precompile_modexp: PrecompileGadget<F, { ExecutionState::PrecompileBigModExp }>,
+    precompile_bn256_add: PrecompileGadget<F, { ExecutionState::PrecompileBn256Add }>,
+    precompile_bn256_scalar_mul: PrecompileGadget<F, { ExecutionState::PrecompileBn256ScalarMul }>,
+    precompile_bn256_pairing: PrecompileGadget<F, { ExecutionState::PrecompileBn256Pairing }>,
+    precompile_blake2f: PrecompileGadget<F, { ExecutionState::PrecompileBlake2F }>,
     // error gadgets
-    error_oog_call: ErrorOOGCallGadget<F>,
+    error_oog_call: ErrorOOGCallGadget<F, { ExecutionState::ErrorOutOfGasCALL }>,
     error_oog_constant: ErrorOOGConstantGadget<F>,
     error_oog_static_memory_gadget:
         DummyGadget<F, 0, 0, { ExecutionState::ErrorOutOfGasStaticMemoryExpansion }>,
     error_stack: ErrorStackGadget<F>,
     error_oog_dynamic_memory_gadget:
         DummyGadget<F, 0, 0, { ExecutionState::ErrorOutOfGasDynamicMemoryExpansion }>,
-    error_oog_log: DummyGadget<F, 0, 0, { ExecutionState::ErrorOutOfGasLOG }>,
-    error_oog_sload: DummyGadget<F, 0, 0, { ExecutionState::ErrorOutOfGasSLOAD }>,
-    error_oog_sstore: DummyGadget<F, 0, 0, { ExecutionState::ErrorOutOfGasSSTORE }>,
-    error_oog_memory_copy: DummyGadget<F, 0, 0, { ExecutionState::ErrorOutOfGasMemoryCopy }>,
-    error_oog_account_access: DummyGadget<F, 0, 0, { ExecutionState::ErrorOutOfGasAccountAccess }>,
-    error_oog_sha3: DummyGadget<F, 0, 0, { ExecutionState::ErrorOutOfGasSHA3 }>,
-    error_oog_ext_codecopy: DummyGadget<F, 0, 0, { ExecutionState::ErrorOutOfGasEXTCODECOPY }>,
-    error_oog_call_code: DummyGadget<F, 0, 0, { ExecutionState::ErrorOutOfGasCALLCODE }>,
-    error_oog_delegate_call: DummyGadget<F, 0, 0, { ExecutionState::ErrorOutOfGasDELEGATECALL }>,
-    error_oog_exp: DummyGadget<F, 0, 0, { ExecutionState::ErrorOutOfGasEXP }>,
-    error_oog_create2: DummyGadget<F, 0, 0, { ExecutionState::ErrorOutOfGasCREATE2 }>,
-    error_oog_static_call: DummyGadget<F, 0, 0, { ExecutionState::ErrorOutOfGasSTATICCALL }>,
-    error_oog_self_destruct: DummyGadget<F, 0, 0, { ExecutionState::ErrorOutOfGasSELFDESTRUCT }>,
-    error_oog_code_store: DummyGadget<F, 0, 0, { ExecutionState::ErrorOutOfGasCodeStore }>,
-    error_insufficient_balance: DummyGadget<F, 0, 0, { ExecutionState::ErrorInsufficientBalance }>,
+    error_oog_log: ErrorOOGLogGadget<F>,
+    error_oog_sload: ErrorOOGSloadSstoreGadget<F, { ExecutionState::ErrorOutOfGasSLOAD }>,
+    error_oog_sstore: ErrorOOGSloadSstoreGadget<F, { ExecutionState::ErrorOutOfGasSSTORE }>,
+    error_oog_memory_copy: ErrorOOGMemoryCopyGadget<F, { ExecutionState::ErrorOutOfGasMemoryCopy }>,
+    error_oog_account_access: ErrorOOGAccountAccessGadget<F>,
+    error_oog_sha3: ErrorOOGSha3Gadget<F>,
+    error_oog_ext_codecopy:
+        ErrorOOGMemoryCopyGadget<F, { ExecutionState::ErrorOutOfGasEXTCODECOPY }>,
+    error_oog_call_code: ErrorOOGCallGadget<F, { ExecutionState::ErrorOutOfGasCALLCODE }>,
+    error_oog_delegate_call: ErrorOOGCallGadget<F, { ExecutionState::ErrorOutOfGasDELEGATECALL }>,
+    error_oog_exp: ErrorOOGExpGadget<F>,
+    error_oog_create2: ErrorOOGCreate2Gadget<F>,
+    error_oog_static_call: ErrorOOGCallGadget<F, { ExecutionState::ErrorOutOfGasSTATICCALL }>,
+    error_oog_self_destruct: ErrorOOGSelfDestructGadget<F>,
+    error_oog_code_store: ErrorOOGCodeStoreGadget<F>,
+    error_insufficient_balance:
+        ErrorPrecheckGadget<F, { ExecutionState::ErrorInsufficientBalance }>,
     error_invalid_jump: ErrorInvalidJumpGadget<F>,
-    error_depth: DummyGadget<F, 0, 0, { ExecutionState::ErrorDepth }>,
-    error_write_protection: DummyGadget<F, 0, 0, { ExecutionState::ErrorWriteProtection }>,
-    error_contract_address_collision:
-        DummyGadget<F, 0, 0, { ExecutionState::ErrorContractAddressCollision }>,
-    error_invalid_creation_code: DummyGadget<F, 0, 0, { ExecutionState::ErrorInvalidCreationCode }>,
-    error_return_data_out_of_bound:
-        DummyGadget<F, 0, 0, { ExecutionState::ErrorReturnDataOutOfBound }>,
-    invalid_opcode_gadget: DummyGadget<F, 0, 0, { ExecutionState::ErrorInvalidOpcode }>,
+    error_depth: ErrorPrecheckGadget<F, { ExecutionState::ErrorDepth }>,
+    error_write_protection: ErrorWriteProtectionGadget<F>,
+    error_contract_address_collision: ErrorContractAddressCollisionGadget<F>,
+    error_invalid_creation_code: ErrorInvalidCreationCodeGadget<F>,
+    error_max_code_size_exceeded: ErrorMaxCodeSizeExceededGadget<F>,
+    error_return_data_out_of_bound: ErrorReturnDataOutOfBoundGadget<F>,
+    invalid_opcode_gadget: ErrorInvalidOpcodeGadget<F>,
 }
 
 impl<F: Field> ExecutionConfig<F> {
     #[allow(clippy::too_many_arguments)]
     pub(crate) fn configure(
         meta: &mut ConstraintSystem<F>,
-        power_of_randomness: [Expression<F>; 31],
+        challenges: Challenges<Expression<F>>,
         fixed_table: &dyn LookupTable<F>,
         byte_table: &dyn LookupTable<F>,
         tx_table: &dyn LookupTable<F>,
@@ -299,6 +363,9 @@ impl<F: Field> ExecutionConfig<F> {
         copy_table: &dyn LookupTable<F>,
         keccak_table: &dyn LookupTable<F>,
         exp_table: &dyn LookupTable<F>,
+        sig_table: &dyn LookupTable<F>,
+        precompile_table: &dyn LookupTable<F>,
+        bloom_table: &dyn LookupTable<F>,
     ) -> Self {
         let q_usable = meta.complex_selector();
         let q_step = meta.advice_column();
@@ -308,7 +375,9 @@ impl<F: Field> ExecutionConfig<F> {
         let num_rows_inv = meta.advice_column();
         let q_step_first = meta.complex_selector();
         let q_step_last = meta.complex_selector();
-        let advices = [(); STEP_WIDTH].map(|_| meta.advice_column());
+        // The cells hold random linear combinations, so they are assigned in
+        // the second phase, once the challenges are known.
+        let advices = [(); STEP_WIDTH].map(|_| meta.advice_column_in(SecondPhase));
 
         let step_curr = Step::new(meta, advices, 0, false);
         let mut height_map = HashMap::new();
@@ -323,11 +392,13 @@ impl<F: Field> ExecutionConfig<F> {
 
             // NEW: Enabled, this will break hand crafted tests, maybe we can remove them?
             let first_step_check = {
-                let begin_tx_end_block_selector = step_curr
-                    .execution_state_selector([ExecutionState::BeginTx, ExecutionState::EndBlock]);
+                let begin_tx_block_reward_selector = step_curr.execution_state_selector([
+                    ExecutionState::BeginTx,
+                    ExecutionState::BlockReward,
+                ]);
                 iter::once((
-                    "First step should be BeginTx or EndBlock",
-                    q_step_first * (1.expr() - begin_tx_end_block_selector),
+                    "First step should be BeginTx or BlockReward",
+                    q_step_first * (1.expr() - begin_tx_block_reward_selector),
                 ))
             };
 
@@ -406,7 +477,7 @@ impl<F: Field> ExecutionConfig<F> {
                     num_rows_until_next_step,
                     q_step_first,
                     q_step_last,
-                    &power_of_randomness,
+                    &challenges,
                     &step_curr,
                     &step_next,
                     &mut height_map,
@@ -427,6 +498,8 @@ impl<F: Field> ExecutionConfig<F> {
             advices,
             // internal states
             begin_tx_gadget: configure_gadget!(),
+            begin_tx_access_list_gadget: configure_gadget!(),
+            block_reward_gadget: configure_gadget!(),
             end_block_gadget: configure_gadget!(),
             end_tx_gadget: configure_gadget!(),
             // opcode gadgets
@@ -444,6 +517,7 @@ impl<F: Field> ExecutionConfig<F> {
             codecopy_gadget: configure_gadget!(),
             codesize_gadget: configure_gadget!(),
             comparator_gadget: configure_gadget!(),
+            create_gadget: configure_gadget!(),
             dup_gadget: configure_gadget!(),
             extcodehash_gadget: configure_gadget!(),
             extcodesize_gadget: configure_gadget!(),
@@ -463,6 +537,7 @@ impl<F: Field> ExecutionConfig<F> {
             pc_gadget: configure_gadget!(),
             pop_gadget: configure_gadget!(),
             push_gadget: configure_gadget!(),
+            push0_gadget: configure_gadget!(),
             return_revert_gadget: configure_gadget!(),
             sdiv_smod_gadget: configure_gadget!(),
             selfbalance_gadget: configure_gadget!(),
@@ -475,8 +550,6 @@ impl<F: Field> ExecutionConfig<F> {
             extcodecopy_gadget: configure_gadget!(),
             returndatasize_gadget: configure_gadget!(),
             returndatacopy_gadget: configure_gadget!(),
-            create_gadget: configure_gadget!(),
-            create2_gadget: configure_gadget!(),
             selfdestruct_gadget: configure_gadget!(),
             shl_shr_gadget: configure_gadget!(),
             signed_comparator_gadget: configure_gadget!(),
@@ -488,6 +561,16 @@ impl<F: Field> ExecutionConfig<F> {
             block_ctx_u64_gadget: configure_gadget!(),
             block_ctx_u160_gadget: configure_gadget!(),
             block_ctx_u256_gadget: configure_gadget!(),
+            // precompile gadgets
+            precompile_ecrecover: configure_gadget!(),
+            precompile_sha256: configure_gadget!(),
+            precompile_ripemd160: configure_gadget!(),
+            precompile_identity: configure_gadget!(),
+            precompile_modexp: configure_gadget!(),
+            precompile_bn256_add: configure_gadget!(),
+            precompile_bn256_scalar_mul: configure_gadget!(),
+            precompile_bn256_pairing: configure_gadget!(),
+            precompile_blake2f: configure_gadget!(),
             // error gadgets
             error_oog_constant: configure_gadget!(),
             error_oog_static_memory_gadget: configure_gadget!(),
@@ -514,6 +597,7 @@ impl<F: Field> ExecutionConfig<F> {
             error_depth: configure_gadget!(),
             error_contract_address_collision: configure_gadget!(),
             error_invalid_creation_code: configure_gadget!(),
+            error_max_code_size_exceeded: configure_gadget!(),
             error_return_data_out_of_bound: configure_gadget!(),
             invalid_opcode_gadget: configure_gadget!(),
             // step and presets
@@ -533,7 +617,10 @@ impl<F: Field> ExecutionConfig<F> {
             copy_table,
             keccak_table,
             exp_table,
-            &power_of_randomness,
+            sig_table,
+            precompile_table,
+            bloom_table,
+            &challenges.evm_word_powers_of_randomness(),
             &cell_manager,
         );
 
@@ -558,7 +645,7 @@ impl<F: Field> ExecutionConfig<F> {
         num_rows_until_next_step: Column<Advice>,
         q_step_first: Selector,
         q_step_last: Selector,
-        power_of_randomness: &[Expression<F>; 31],
+        challenges: &Challenges<Expression<F>>,
         step_curr: &Step<F>,
         step_next: &Step<F>,
         height_map: &mut HashMap<ExecutionState, usize>,
@@ -570,7 +657,7 @@ impl<F: Field> ExecutionConfig<F> {
             let mut cb = ConstraintBuilder::new(
                 step_curr.clone(),
                 step_next.clone(),
-                power_of_randomness,
+                challenges,
                 G::EXECUTION_STATE,
             );
             G::configure(&mut cb);
@@ -583,7 +670,7 @@ impl<F: Field> ExecutionConfig<F> {
         let mut cb = ConstraintBuilder::new(
             step_curr.clone(),
             step_next.clone(),
-            power_of_randomness,
+            challenges,
             G::EXECUTION_STATE,
         );
 
@@ -649,9 +736,14 @@ impl<F: Field> ExecutionConfig<F> {
                 .chain(
                     IntoIterator::into_iter([
                         (
-                            "EndTx can only transit to BeginTx or EndBlock",
+                            "EndTx can only transit to BeginTx or BlockReward",
                             ExecutionState::EndTx,
-                            vec![ExecutionState::BeginTx, ExecutionState::EndBlock],
+                            vec![ExecutionState::BeginTx, ExecutionState::BlockReward],
+                        ),
+                        (
+                            "BlockReward can only transit to EndBlock",
+                            ExecutionState::BlockReward,
+                            vec![ExecutionState::EndBlock],
                         ),
                         (
                             "EndBlock can only transit to EndBlock",
@@ -670,17 +762,27 @@ impl<F: Field> ExecutionConfig<F> {
                             vec![ExecutionState::EndTx],
                         ),
                         (
-                            "Only ExecutionState which halts or BeginTx can transit to EndTx",
+                            "Only BeginTx or BeginTxAccessList can transit to BeginTxAccessList",
+                            ExecutionState::BeginTxAccessList,
+                            vec![ExecutionState::BeginTx, ExecutionState::BeginTxAccessList],
+                        ),
+                        (
+                            "Only ExecutionState which halts or begins a Tx can transit to EndTx",
                             ExecutionState::EndTx,
                             ExecutionState::iter()
                                 .filter(ExecutionState::halts)
-                                .chain(iter::once(ExecutionState::BeginTx))
+                                .chain([ExecutionState::BeginTx, ExecutionState::BeginTxAccessList])
                                 .collect(),
                         ),
                         (
-                            "Only EndTx or EndBlock can transit to EndBlock",
+                            "Only EndTx can transit to BlockReward",
+                            ExecutionState::BlockReward,
+                            vec![ExecutionState::EndTx],
+                        ),
+                        (
+                            "Only BlockReward or EndBlock can transit to EndBlock",
                             ExecutionState::EndBlock,
-                            vec![ExecutionState::EndTx, ExecutionState::EndBlock],
+                            vec![ExecutionState::BlockReward, ExecutionState::EndBlock],
                         ),
                     ])
                     .filter(move |(_, _, from)| !from.contains(&G::EXECUTION_STATE))
@@ -713,6 +815,9 @@ impl<F: Field> ExecutionConfig<F> {
         copy_table: &dyn LookupTable<F>,
         keccak_table: &dyn LookupTable<F>,
         exp_table: &dyn LookupTable<F>,
+        sig_table: &dyn LookupTable<F>,
+        precompile_table: &dyn LookupTable<F>,
+        bloom_table: &dyn LookupTable<F>,
         power_of_randomness: &[Expression<F>; 31],
         cell_manager: &CellManager<F>,
     ) {
@@ -730,6 +835,9 @@ impl<F: Field> ExecutionConfig<F> {
                         Table::Copy => copy_table,
                         Table::Keccak => keccak_table,
                         Table::Exp => exp_table,
+                        Table::Sig => sig_table,
+                        Table::Precompile => precompile_table,
+                        Table::Bloom => bloom_table,
                     }
                     .table_exprs(meta);
                     vec![(
@@ -785,13 +893,8 @@ impl<F: Field> ExecutionConfig<F> {
         &self,
         layouter: &mut impl Layouter<F>,
         block: &Block<F>,
+        challenges: &Challenges<Value<F>>,
     ) -> Result<(), Error> {
-        let power_of_randomness = (1..32)
-            .map(|exp| block.randomness.pow(&[exp, 0, 0, 0]))
-            .collect::<Vec<F>>()
-            .try_into()
-            .unwrap();
-
         layouter.assign_region(
             || "Execution step",
             |mut region| {
@@ -806,6 +909,7 @@ impl<F: Field> ExecutionConfig<F> {
                     .last()
                     .map(|tx| tx.calls[0].clone())
                     .unwrap_or_else(Call::default);
+                let block_reward = &block.block_reward;
                 let end_block_not_last = &block.end_block_not_last;
                 let end_block_last = &block.end_block_last;
                 // Collect all steps
@@ -817,6 +921,7 @@ impl<F: Field> ExecutionConfig<F> {
                             .iter()
                             .map(move |step| (tx, &tx.calls[step.call_index], step))
                     })
+                    .chain(std::iter::once((&dummy_tx, &last_call, block_reward)))
                     .chain(std::iter::once((&dummy_tx, &last_call, end_block_not_last)))
                     .peekable();
 
@@ -842,7 +947,7 @@ impl<F: Field> ExecutionConfig<F> {
                         step,
                         height,
                         next.copied(),
-                        power_of_randomness,
+                        challenges,
                     )?;
 
                     // q_step logic
@@ -878,7 +983,7 @@ impl<F: Field> ExecutionConfig<F> {
                         &last_call,
                         end_block_not_last,
                         height,
-                        power_of_randomness,
+                        challenges,
                     )?;
 
                     for row_idx in offset..last_row {
@@ -900,7 +1005,7 @@ impl<F: Field> ExecutionConfig<F> {
                     end_block_last,
                     height,
                     None,
-                    power_of_randomness,
+                    challenges,
                 )?;
                 self.assign_q_step(&mut region, offset, height)?;
                 // enable q_step_last
@@ -939,7 +1044,7 @@ impl<F: Field> ExecutionConfig<F> {
         call: &Call,
         step: &ExecStep,
         height: usize,
-        power_of_randomness: [F; 31],
+        challenges: &Challenges<Value<F>>,
     ) -> Result<(), Error> {
         if offset_end <= offset_begin {
             return Ok(());
@@ -951,7 +1056,7 @@ impl<F: Field> ExecutionConfig<F> {
         // Disable access to next step deliberately for "repeatable" step
         let region = &mut CachedRegion::<'_, '_, F>::new(
             region,
-            power_of_randomness,
+            challenges,
             self.advices.to_vec(),
             1,
             offset_begin,
@@ -978,7 +1083,7 @@ impl<F: Field> ExecutionConfig<F> {
         step: &ExecStep,
         height: usize,
         next: Option<(&Transaction, &Call, &ExecStep)>,
-        power_of_randomness: [F; 31],
+        challenges: &Challenges<Value<F>>,
     ) -> Result<(), Error> {
         if !matches!(step.execution_state, ExecutionState::EndBlock) {
             log::trace!(
@@ -994,7 +1099,7 @@ impl<F: Field> ExecutionConfig<F> {
         // enough for 3 steps.
         let region = &mut CachedRegion::<'_, '_, F>::new(
             region,
-            power_of_randomness,
+            challenges,
             self.advices.to_vec(),
             MAX_STEP_HEIGHT * 3,
             offset,
@@ -1027,8 +1132,7 @@ impl<F: Field> ExecutionConfig<F> {
         call: &Call,
         step: &ExecStep,
     ) -> Result<(), Error> {
-        self.step
-            .assign_exec_step(region, offset, block, call, step)?;
+        self.step.assign_exec_step(region, offset, call, step)?;
 
         macro_rules! assign_exec_step {
             ($gadget:expr) => {
@@ -1039,7 +1143,11 @@ impl<F: Field> ExecutionConfig<F> {
         match step.execution_state {
             // internal states
             ExecutionState::BeginTx => assign_exec_step!(self.begin_tx_gadget),
+            ExecutionState::BeginTxAccessList => {
+                assign_exec_step!(self.begin_tx_access_list_gadget)
+            }
             ExecutionState::EndTx => assign_exec_step!(self.end_tx_gadget),
+            ExecutionState::BlockReward => assign_exec_step!(self.block_reward_gadget),
             ExecutionState::EndBlock => assign_exec_step!(self.end_block_gadget),
             // opcode
             ExecutionState::ADD_SUB => assign_exec_step!(self.add_sub_gadget),
@@ -1058,6 +1166,7 @@ impl<F: Field> ExecutionConfig<F> {
             ExecutionState::CODECOPY => assign_exec_step!(self.codecopy_gadget),
             ExecutionState::CODESIZE => assign_exec_step!(self.codesize_gadget),
             ExecutionState::CMP => assign_exec_step!(self.comparator_gadget),
+            ExecutionState::CREATE => assign_exec_step!(self.create_gadget),
             ExecutionState::DUP => assign_exec_step!(self.dup_gadget),
             ExecutionState::EXP => assign_exec_step!(self.exp_gadget),
             ExecutionState::EXTCODEHASH => assign_exec_step!(self.extcodehash_gadget),
@@ -1078,6 +1187,7 @@ impl<F: Field> ExecutionConfig<F> {
             ExecutionState::PC => assign_exec_step!(self.pc_gadget),
             ExecutionState::POP => assign_exec_step!(self.pop_gadget),
             ExecutionState::PUSH => assign_exec_step!(self.push_gadget),
+            ExecutionState::PUSH0 => assign_exec_step!(self.push0_gadget),
             ExecutionState::RETURN_REVERT => assign_exec_step!(self.return_revert_gadget),
             ExecutionState::RETURNDATASIZE => assign_exec_step!(self.returndatasize_gadget),
             ExecutionState::RETURNDATACOPY => assign_exec_step!(self.returndatacopy_gadget),
@@ -1088,13 +1198,9 @@ impl<F: Field> ExecutionConfig<F> {
             ExecutionState::BLOCKCTXU256 => assign_exec_step!(self.block_ctx_u256_gadget),
             ExecutionState::BLOCKHASH => assign_exec_step!(self.blockhash_gadget),
             ExecutionState::SELFBALANCE => assign_exec_step!(self.selfbalance_gadget),
-            // dummy gadgets
             ExecutionState::SAR => assign_exec_step!(self.sar_gadget),
             ExecutionState::EXTCODECOPY => assign_exec_step!(self.extcodecopy_gadget),
-            ExecutionState::CREATE => assign_exec_step!(self.create_gadget),
-            ExecutionState::CREATE2 => assign_exec_step!(self.create2_gadget),
             ExecutionState::SELFDESTRUCT => assign_exec_step!(self.selfdestruct_gadget),
-            // end of dummy gadgets
             ExecutionState::SHA3 => assign_exec_step!(self.sha3_gadget),
             ExecutionState::SHL_SHR => assign_exec_step!(self.shl_shr_gadget),
             ExecutionState::SIGNEXTEND => assign_exec_step!(self.signextend_gadget),
@@ -1102,7 +1208,21 @@ impl<F: Field> ExecutionConfig<F> {
             ExecutionState::SSTORE => assign_exec_step!(self.sstore_gadget),
             ExecutionState::STOP => assign_exec_step!(self.stop_gadget),
             ExecutionState::SWAP => assign_exec_step!(self.swap_gadget),
-            // dummy errors
+            // precompiles
+            ExecutionState::PrecompileEcRecover => assign_exec_step!(self.precompile_ecrecover),
+            ExecutionState::PrecompileSha256 => assign_exec_step!(self.precompile_sha256),
+            ExecutionState::PrecompileRipemd160 => assign_exec_step!(self.precompile_ripemd160),
+            ExecutionState::PrecompileIdentity => assign_exec_step!(self.precompile_identity),
+            ExecutionState::PrecompileBigModExp => assign_exec_step!(self.precompile_modexp),
+            ExecutionState::PrecompileBn256Add => assign_exec_step!(self.precompile_bn256_add),
+            ExecutionState::PrecompileBn256ScalarMul => {
+                assign_exec_step!(self.precompile_bn256_scalar_mul)
+            }
+            ExecutionState::PrecompileBn256Pairing => {
+                assign_exec_step!(self.precompile_bn256_pairing)
+            }
+            ExecutionState::PrecompileBlake2F => assign_exec_step!(self.precompile_blake2f),
+            // errors
             ExecutionState::ErrorOutOfGasStaticMemoryExpansion => {
                 assign_exec_step!(self.error_oog_static_memory_gadget)
             }
@@ -1180,6 +1300,9 @@ impl<F: Field> ExecutionConfig<F> {
             ExecutionState::ErrorInvalidCreationCode => {
                 assign_exec_step!(self.error_invalid_creation_code)
             }
+            ExecutionState::ErrorMaxCodeSizeExceeded => {
+                assign_exec_step!(self.error_max_code_size_exceeded)
+            }
             ExecutionState::ErrorReturnDataOutOfBound => {
                 assign_exec_step!(self.error_return_data_out_of_bound)
             }
@@ -1200,7 +1323,12 @@ impl<F: Field> ExecutionConfig<F> {
                 && step.rw_indices.is_empty();
             if !is_padding_step {
                 // expensive function call
-                Self::check_rw_lookup(&assigned_stored_expressions, step, block);
+                Self::check_rw_lookup(
+                    &assigned_stored_expressions,
+                    step,
+                    block,
+                    region.evm_word_randomness(),
+                );
             }
         }
         Ok(())
@@ -1231,6 +1359,7 @@ impl<F: Field> ExecutionConfig<F> {
         assigned_stored_expressions: &[(String, F)],
         step: &ExecStep,
         block: &Block<F>,
+        randomness: F,
     ) {
         let mut assigned_rw_values = Vec::new();
         // Reversion lookup expressions have different ordering compared to rw table,
@@ -1250,10 +1379,7 @@ impl<F: Field> ExecutionConfig<F> {
             .rws
             .table_assignments()
             .iter()
-            .map(|rw| {
-                rw.table_assignment_aux(block.randomness)
-                    .rlc(block.randomness)
-            })
+            .map(|rw| rw.table_assignment_aux(randomness).rlc(randomness))
             .collect();
 
         for (name, value) in assigned_rw_values.iter() {
@@ -1264,8 +1390,8 @@ impl<F: Field> ExecutionConfig<F> {
         for (idx, assigned_rw_value) in assigned_rw_values.iter().enumerate() {
             let rw_idx = step.rw_indices[idx];
             let rw = block.rws[rw_idx];
-            let table_assignments = rw.table_assignment_aux(block.randomness);
-            let rlc = table_assignments.rlc(block.randomness);
+            let table_assignments = rw.table_assignment_aux(randomness);
+            let rlc = table_assignments.rlc(randomness);
             if rlc != assigned_rw_value.1 {
                 log::error!(
                     "incorrect rw witness. lookup input name: \"{}\"\n{:?}\nrw: {:?}, rw index: {:?}, {}th rw of step {:?}",
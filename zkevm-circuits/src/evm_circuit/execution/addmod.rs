@@ -144,6 +144,7 @@ impl<F: Field> ExecutionGadget<F> for AddModGadget<F> {
         _: &Call,
         step: &ExecStep,
     ) -> Result<(), Error> {
+        let randomness = region.evm_word_randomness();
         self.same_context.assign_exec_step(region, offset, step)?;
 
         // get stack values
@@ -216,7 +217,7 @@ impl<F: Field> ExecutionGadget<F> for AddModGadget<F> {
         self.n_is_zero.assign(
             region,
             offset,
-            Word::random_linear_combine(n.to_le_bytes(), block.randomness),
+            Word::random_linear_combine(n.to_le_bytes(), randomness),
         )?;
 
         Ok(())
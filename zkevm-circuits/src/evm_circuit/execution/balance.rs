@@ -103,6 +103,7 @@ impl<F: Field> ExecutionGadget<F> for BalanceGadget<F> {
         call: &Call,
         step: &ExecStep,
     ) -> Result<(), Error> {
+        let randomness = region.evm_word_randomness();
         self.same_context.assign_exec_step(region, offset, step)?;
 
         let address = block.rws[step.rw_indices[0]].stack_value().to_address();
@@ -141,7 +142,7 @@ impl<F: Field> ExecutionGadget<F> for BalanceGadget<F> {
             offset,
             Value::known(RandomLinearCombination::random_linear_combine(
                 balance.to_le_bytes(),
-                block.randomness,
+                randomness,
             )),
         )?;
         self.exists
@@ -1,23 +1,35 @@
 use crate::{
     evm_circuit::{
         execution::ExecutionGadget,
-        param::N_BYTES_GAS,
+        param::{N_BYTES_ACCOUNT_ADDRESS, N_BYTES_GAS, N_BYTES_U64},
         step::ExecutionState,
+        table::HardforkParam,
         util::{
-            common_gadget::TransferWithGasFeeGadget,
+            common_gadget::{ContractAddressGadget, TransferWithGasFeeGadget},
             constraint_builder::{
                 ConstraintBuilder, ReversionInfo, StepStateTransition,
                 Transition::{Delta, To},
             },
-            math_gadget::{IsEqualGadget, IsZeroGadget, MulWordByU64Gadget, RangeCheckGadget},
-            select, CachedRegion, Cell, RandomLinearCombination, Word,
+            from_bytes,
+            math_gadget::{
+                AddWordsGadget, IsEqualGadget, IsZeroGadget, LtGadget, LtWordGadget,
+                MulWordByU64Gadget, RangeCheckGadget,
+            },
+            memory_gadget::MemoryWordSizeGadget,
+            not, select, CachedRegion, Cell, RandomLinearCombination, Word,
         },
         witness::{Block, Call, ExecStep, Transaction},
     },
-    table::{AccountFieldTag, CallContextFieldTag, TxFieldTag as TxContextFieldTag},
+    table::{
+        AccountFieldTag, BlockContextFieldTag, CallContextFieldTag, TxFieldTag as TxContextFieldTag,
+    },
     util::Expr,
 };
-use eth_types::{evm_types::GasCost, Field, ToLittleEndian, ToScalar};
+use bus_mapping::circuit_input_builder::CopyDataType;
+use eth_types::{
+    evm_types::{GasCost, MAX_INIT_CODE_SIZE},
+    Field, ToLittleEndian, ToScalar, ToWord, U256,
+};
 use halo2_proofs::circuit::Value;
 use halo2_proofs::plonk::Error;
 use keccak256::EMPTY_HASH_LE;
@@ -28,19 +40,41 @@ pub(crate) struct BeginTxGadget<F> {
     tx_nonce: Cell<F>,
     tx_gas: Cell<F>,
     tx_gas_price: Word<F>,
+    tx_gas_fee_cap: Word<F>,
+    add_base_fee_to_tip_cap: AddWordsGadget<F, 2, true>,
+    fee_cap_lt_base_fee_plus_tip_cap: LtWordGadget<F>,
+    fee_cap_lt_base_fee: LtWordGadget<F>,
+    fee_cap_lt_tip_cap: LtWordGadget<F>,
     mul_gas_fee_by_gas: MulWordByU64Gadget<F>,
     tx_caller_address: Cell<F>,
     tx_caller_address_is_zero: IsZeroGadget<F>,
     tx_callee_address: Cell<F>,
     tx_is_create: Cell<F>,
+    caller_address: RandomLinearCombination<F, N_BYTES_ACCOUNT_ADDRESS>,
+    contract_address: ContractAddressGadget<F>,
+    callee_address: Cell<F>,
     tx_value: Word<F>,
     tx_call_data_length: Cell<F>,
+    tx_call_data_length_is_zero: IsZeroGadget<F>,
     tx_call_data_gas_cost: Cell<F>,
+    tx_access_list_addresses_len: Cell<F>,
+    tx_access_list_storage_keys_len: Cell<F>,
+    is_eip3860_enabled: Cell<F>,
+    init_code_word_size: MemoryWordSizeGadget<F>,
+    init_code_size_gt_max: LtGadget<F, N_BYTES_U64>,
+    is_eip3651_enabled: Cell<F>,
+    coinbase: Cell<F>,
+    is_coinbase_warm_prev: Cell<F>,
     reversion_info: ReversionInfo<F>,
     sufficient_gas_left: RangeCheckGadget<F, N_BYTES_GAS>,
     transfer_with_gas_fee: TransferWithGasFeeGadget<F>,
     code_hash: Cell<F>,
     is_empty_code_hash: IsEqualGadget<F>,
+    tx_callee_address_is_zero: IsZeroGadget<F>,
+    tx_callee_address_lt_10: LtGadget<F, N_BYTES_ACCOUNT_ADDRESS>,
+    is_precompile: Cell<F>,
+    is_empty_code: Cell<F>,
+    is_access_list_empty: IsZeroGadget<F>,
 }
 
 impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
@@ -67,7 +101,7 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             reversion_info.is_persistent(),
         );
 
-        let [tx_nonce, tx_gas, tx_caller_address, tx_callee_address, tx_is_create, tx_call_data_length, tx_call_data_gas_cost] =
+        let [tx_nonce, tx_gas, tx_caller_address, tx_callee_address, tx_is_create, tx_call_data_length, tx_call_data_gas_cost, tx_access_list_addresses_len, tx_access_list_storage_keys_len] =
             [
                 TxContextFieldTag::Nonce,
                 TxContextFieldTag::Gas,
@@ -76,6 +110,8 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                 TxContextFieldTag::IsCreate,
                 TxContextFieldTag::CallDataLength,
                 TxContextFieldTag::CallDataGasCost,
+                TxContextFieldTag::AccessListAddressesLen,
+                TxContextFieldTag::AccessListStorageKeysLen,
             ]
             .map(|field_tag| cb.tx_context(tx_id.expr(), field_tag, None));
         let tx_caller_address_is_zero = IsZeroGadget::construct(cb, tx_caller_address.expr());
@@ -84,14 +120,43 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             tx_caller_address_is_zero.expr(),
             false.expr(),
         );
-        let [tx_gas_price, tx_value] = [TxContextFieldTag::GasPrice, TxContextFieldTag::Value]
-            .map(|field_tag| cb.tx_context_as_word(tx_id.expr(), field_tag, None));
+        let [tx_gas_price, tx_gas_fee_cap, tx_gas_tip_cap, tx_value] = [
+            TxContextFieldTag::GasPrice,
+            TxContextFieldTag::GasFeeCap,
+            TxContextFieldTag::GasTipCap,
+            TxContextFieldTag::Value,
+        ]
+        .map(|field_tag| cb.tx_context_as_word(tx_id.expr(), field_tag, None));
 
         // Add first BeginTx step constraint to have tx_id == 1
         cb.step_first(|cb| {
             cb.require_equal("tx_id is initialized to be 1", tx_id.expr(), 1.expr());
         });
 
+        // The contract address of a creation transaction is derived from the
+        // caller's address and nonce, and the callee address is the contract
+        // address in this case.
+        let caller_address = cb.query_rlc();
+        let contract_address = cb.condition(tx_is_create.expr(), |cb| {
+            cb.require_equal(
+                "caller_address == tx_caller_address",
+                from_bytes::expr(&caller_address.cells),
+                tx_caller_address.expr(),
+            );
+            let contract_address = ContractAddressGadget::construct(cb, &caller_address, 0.expr());
+            cb.require_equal(
+                "contract address is derived from tx_nonce",
+                contract_address.caller_nonce(),
+                tx_nonce.expr(),
+            );
+            contract_address
+        });
+        let callee_address = cb.copy(select::expr(
+            tx_is_create.expr(),
+            contract_address.address(),
+            tx_callee_address.expr(),
+        ));
+
         // Increase caller's nonce.
         // (tx caller's nonce always increases even tx ends with error)
         cb.account_write(
@@ -102,19 +167,67 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             None,
         );
 
-        // TODO: Implement EIP 1559 (currently it only supports legacy
-        // transaction format)
+        // The effective gas price is min(gas_fee_cap, base_fee + gas_tip_cap)
+        // (EIP-1559), which is the gas price itself for legacy transactions,
+        // whose gas_fee_cap and gas_tip_cap are set to the gas price.
+        let base_fee = cb.query_word();
+        cb.block_lookup(BlockContextFieldTag::BaseFee.expr(), None, base_fee.expr());
+
+        // The fee cap has to cover the base fee and the tip cap (EIP-1559).
+        let fee_cap_lt_base_fee = LtWordGadget::construct(cb, &tx_gas_fee_cap, &base_fee);
+        cb.require_zero("tx_gas_fee_cap >= base_fee", fee_cap_lt_base_fee.expr());
+        let fee_cap_lt_tip_cap = LtWordGadget::construct(cb, &tx_gas_fee_cap, &tx_gas_tip_cap);
+        cb.require_zero(
+            "tx_gas_tip_cap <= tx_gas_fee_cap",
+            fee_cap_lt_tip_cap.expr(),
+        );
+
+        let base_fee_plus_tip_cap = cb.query_word();
+        let add_base_fee_to_tip_cap = AddWordsGadget::construct(
+            cb,
+            [base_fee, tx_gas_tip_cap],
+            base_fee_plus_tip_cap.clone(),
+        );
+        let fee_cap_lt_base_fee_plus_tip_cap =
+            LtWordGadget::construct(cb, &tx_gas_fee_cap, &base_fee_plus_tip_cap);
+        cb.require_equal(
+            "tx_gas_price == min(tx_gas_fee_cap, base_fee + tx_gas_tip_cap)",
+            tx_gas_price.expr(),
+            select::expr(
+                fee_cap_lt_base_fee_plus_tip_cap.expr(),
+                tx_gas_fee_cap.expr(),
+                base_fee_plus_tip_cap.expr(),
+            ),
+        );
+
         // Calculate transaction gas fee
         let mul_gas_fee_by_gas =
             MulWordByU64Gadget::construct(cb, tx_gas_price.clone(), tx_gas.expr());
 
-        // TODO: Take gas cost of access list (EIP 2930) into consideration.
-        // Use intrinsic gas
+        // The initialization code of a creation is limited in size and
+        // charged per word once EIP-3860 is enabled.
+        let is_eip3860_enabled = cb.query_bool();
+        cb.hardfork_param_lookup(HardforkParam::IsEip3860Enabled, is_eip3860_enabled.expr());
+        let init_code_word_size = MemoryWordSizeGadget::construct(cb, tx_call_data_length.expr());
+        let init_code_size_gt_max =
+            LtGadget::construct(cb, MAX_INIT_CODE_SIZE.expr(), tx_call_data_length.expr());
+        cb.require_zero(
+            "Initialization code size <= MAX_INIT_CODE_SIZE if EIP-3860 is enabled",
+            tx_is_create.expr() * is_eip3860_enabled.expr() * init_code_size_gt_max.expr(),
+        );
+
+        // Use intrinsic gas, including the gas cost of access list (EIP 2930)
+        // and of the initialization code (EIP 3860)
         let intrinsic_gas_cost = select::expr(
             tx_is_create.expr(),
-            GasCost::CREATION_TX.expr(),
+            GasCost::CREATION_TX.expr()
+                + is_eip3860_enabled.expr()
+                    * init_code_word_size.expr()
+                    * GasCost::INIT_CODE_WORD.expr(),
             GasCost::TX.expr(),
-        ) + tx_call_data_gas_cost.expr();
+        ) + tx_call_data_gas_cost.expr()
+            + tx_access_list_addresses_len.expr() * GasCost::ACCESS_LIST_ADDRESS.expr()
+            + tx_access_list_storage_keys_len.expr() * GasCost::ACCESS_LIST_STORAGE_KEY.expr();
 
         // Check gas_left is sufficient
         let gas_left = tx_gas.expr() - intrinsic_gas_cost;
@@ -130,56 +243,132 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
         );
         cb.account_access_list_write(
             tx_id.expr(),
-            tx_callee_address.expr(),
+            callee_address.expr(),
             1.expr(),
             0.expr(),
             None,
         );
 
+        // Prepare access list of coinbase once EIP-3651 is enabled, which is
+        // already warm when it's the caller or the callee.
+        let is_eip3651_enabled = cb.query_bool();
+        cb.hardfork_param_lookup(HardforkParam::IsEip3651Enabled, is_eip3651_enabled.expr());
+        let coinbase = cb.query_cell();
+        cb.block_lookup(BlockContextFieldTag::Coinbase.expr(), None, coinbase.expr());
+        let is_coinbase_warm_prev = cb.query_bool();
+        cb.condition(is_eip3651_enabled.expr(), |cb| {
+            cb.account_access_list_write(
+                tx_id.expr(),
+                coinbase.expr(),
+                1.expr(),
+                is_coinbase_warm_prev.expr(),
+                None,
+            );
+        });
+
         // Transfer value from caller to callee
         let transfer_with_gas_fee = TransferWithGasFeeGadget::construct(
             cb,
             tx_caller_address.expr(),
-            tx_callee_address.expr(),
+            callee_address.expr(),
             tx_value.clone(),
             mul_gas_fee_by_gas.product().clone(),
             &mut reversion_info,
         );
 
-        // TODO: Handle creation transaction
-        // TODO: Handle precompiled
-
-        // Read code_hash of callee
         let code_hash = cb.query_cell();
-        cb.account_read(
-            tx_callee_address.expr(),
-            AccountFieldTag::CodeHash,
-            code_hash.expr(),
+        let empty_code_hash_rlc = Word::random_linear_combine_expr(
+            (*EMPTY_HASH_LE).map(|byte| byte.expr()),
+            cb.power_of_randomness(),
         );
+        let tx_call_data_length_is_zero = IsZeroGadget::construct(cb, tx_call_data_length.expr());
+        cb.condition(tx_is_create.expr(), |cb| {
+            // Increase callee's nonce to 1, as specified in EIP-161.
+            cb.account_write(
+                callee_address.expr(),
+                AccountFieldTag::Nonce,
+                1.expr(),
+                0.expr(),
+                Some(&mut reversion_info),
+            );
+
+            // Copy the initialization code from the tx calldata into the
+            // bytecode table, where it's identified by its code hash.
+            cb.condition(not::expr(tx_call_data_length_is_zero.expr()), |cb| {
+                cb.copy_table_lookup(
+                    tx_id.expr(),
+                    CopyDataType::TxCalldata.expr(),
+                    code_hash.expr(),
+                    CopyDataType::Bytecode.expr(),
+                    0.expr(),
+                    tx_call_data_length.expr(),
+                    0.expr(),
+                    tx_call_data_length.expr(),
+                    0.expr(),
+                    0.expr(),
+                );
+            });
+            cb.condition(tx_call_data_length_is_zero.expr(), |cb| {
+                cb.require_equal(
+                    "Empty initialization code has the hash of empty bytes",
+                    code_hash.expr(),
+                    empty_code_hash_rlc.clone(),
+                );
+            });
+        });
+
+        // Read code_hash of callee
+        cb.condition(not::expr(tx_is_create.expr()), |cb| {
+            cb.account_read(
+                callee_address.expr(),
+                AccountFieldTag::CodeHash,
+                code_hash.expr(),
+            );
+        });
 
-        let is_empty_code_hash = IsEqualGadget::construct(
+        let is_empty_code_hash =
+            IsEqualGadget::construct(cb, code_hash.expr(), empty_code_hash_rlc);
+
+        // Precompiled contracts are at the addresses from 0x01 to 0x09, and are
+        // executed in the next step regardless of their code hash.
+        let tx_callee_address_is_zero = IsZeroGadget::construct(cb, tx_callee_address.expr());
+        let tx_callee_address_lt_10 = LtGadget::construct(cb, tx_callee_address.expr(), 10.expr());
+        let is_precompile =
+            cb.copy(tx_callee_address_lt_10.expr() * (1.expr() - tx_callee_address_is_zero.expr()));
+        let is_empty_code = cb.copy(is_empty_code_hash.expr() * (1.expr() - is_precompile.expr()));
+
+        // The entries of the access list are warmed up in the steps following
+        // this one, which keep its resulting state and go to the step that
+        // would have followed it.
+        let is_access_list_empty = IsZeroGadget::construct(
             cb,
-            code_hash.expr(),
-            Word::random_linear_combine_expr(
-                (*EMPTY_HASH_LE).map(|byte| byte.expr()),
-                cb.power_of_randomness(),
-            ),
+            tx_access_list_addresses_len.expr() + tx_access_list_storage_keys_len.expr(),
         );
-
-        cb.condition(is_empty_code_hash.expr(), |cb| {
+        cb.condition(not::expr(is_access_list_empty.expr()), |cb| {
             cb.require_equal(
-                "Tx to account with empty code should be persistent",
-                reversion_info.is_persistent(),
+                "Go to BeginTxAccessList when Tx has an access list",
+                cb.next
+                    .execution_state_selector([ExecutionState::BeginTxAccessList]),
                 1.expr(),
             );
+        });
+
+        cb.condition(is_empty_code.expr(), |cb| {
             cb.require_equal(
-                "Go to EndTx when Tx to account with empty code",
-                cb.next.execution_state_selector([ExecutionState::EndTx]),
+                "Tx to account with empty code should be persistent",
+                reversion_info.is_persistent(),
                 1.expr(),
             );
+            cb.condition(is_access_list_empty.expr(), |cb| {
+                cb.require_equal(
+                    "Go to EndTx when Tx to account with empty code",
+                    cb.next.execution_state_selector([ExecutionState::EndTx]),
+                    1.expr(),
+                );
+            });
 
             cb.require_step_state_transition(StepStateTransition {
-                // 10 reads and writes:
+                // 10 reads and writes, 11 with EIP-3651:
                 //   - Write CallContext TxId
                 //   - Write CallContext RwCounterEndOfReversion
                 //   - Write CallContext IsPersistent
@@ -187,25 +376,32 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                 //   - Write Account Nonce
                 //   - Write TxAccessListAccount
                 //   - Write TxAccessListAccount
+                //   - Write TxAccessListAccount (coinbase with EIP-3651)
                 //   - Write Account Balance
                 //   - Write Account Balance
-                //   - Read Account CodeHash
-                rw_counter: Delta(10.expr()),
+                //   - Read Account CodeHash (Write Account Nonce if is_create)
+                rw_counter: Delta(10.expr() + is_eip3651_enabled.expr()),
                 call_id: To(call_id.expr()),
+                code_hash: To(code_hash.expr()),
                 ..StepStateTransition::any()
             });
         });
 
-        cb.condition(1.expr() - is_empty_code_hash.expr(), |cb| {
+        cb.condition(1.expr() - is_empty_code.expr(), |cb| {
+            cb.condition(is_access_list_empty.expr(), |cb| {
+                cb.require_next_state_precompile(is_precompile.expr(), tx_callee_address.expr());
+            });
+
             // Setup first call's context.
             for (field_tag, value) in [
                 (CallContextFieldTag::Depth, 1.expr()),
                 (CallContextFieldTag::CallerAddress, tx_caller_address.expr()),
-                (CallContextFieldTag::CalleeAddress, tx_callee_address.expr()),
+                (CallContextFieldTag::CalleeAddress, callee_address.expr()),
                 (CallContextFieldTag::CallDataOffset, 0.expr()),
+                // The tx calldata is the initialization code of a creation.
                 (
                     CallContextFieldTag::CallDataLength,
-                    tx_call_data_length.expr(),
+                    not::expr(tx_is_create.expr()) * tx_call_data_length.expr(),
                 ),
                 (CallContextFieldTag::Value, tx_value.expr()),
                 (CallContextFieldTag::IsStatic, 0.expr()),
@@ -220,7 +416,7 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             }
 
             cb.require_step_state_transition(StepStateTransition {
-                // 23 reads and writes:
+                // 23 reads and writes, 24 with EIP-3651:
                 //   - Write CallContext TxId
                 //   - Write CallContext RwCounterEndOfReversion
                 //   - Write CallContext IsPersistent
@@ -228,9 +424,10 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                 //   - Write Account Nonce
                 //   - Write TxAccessListAccount
                 //   - Write TxAccessListAccount
+                //   - Write TxAccessListAccount (coinbase with EIP-3651)
                 //   - Write Account Balance
                 //   - Write Account Balance
-                //   - Read Account CodeHash
+                //   - Read Account CodeHash (Write Account Nonce if is_create)
                 //   - Write CallContext Depth
                 //   - Write CallContext CallerAddress
                 //   - Write CallContext CalleeAddress
@@ -244,13 +441,15 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                 //   - Write CallContext IsRoot
                 //   - Write CallContext IsCreate
                 //   - Write CallContext CodeHash
-                rw_counter: Delta(23.expr()),
+                rw_counter: Delta(23.expr() + is_eip3651_enabled.expr()),
                 call_id: To(call_id.expr()),
                 is_root: To(true.expr()),
                 is_create: To(tx_is_create.expr()),
                 code_hash: To(code_hash.expr()),
                 gas_left: To(gas_left),
-                reversible_write_counter: To(2.expr()),
+                // The two balance updates of the transfer, and the callee's
+                // nonce update if is_create.
+                reversible_write_counter: To(2.expr() + tx_is_create.expr()),
                 log_id: To(0.expr()),
                 ..StepStateTransition::new_context()
             });
@@ -261,19 +460,41 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             tx_nonce,
             tx_gas,
             tx_gas_price,
+            tx_gas_fee_cap,
+            add_base_fee_to_tip_cap,
+            fee_cap_lt_base_fee_plus_tip_cap,
+            fee_cap_lt_base_fee,
+            fee_cap_lt_tip_cap,
             mul_gas_fee_by_gas,
             tx_caller_address,
             tx_caller_address_is_zero,
             tx_callee_address,
             tx_is_create,
+            caller_address,
+            contract_address,
+            callee_address,
             tx_value,
             tx_call_data_length,
+            tx_call_data_length_is_zero,
             tx_call_data_gas_cost,
+            tx_access_list_addresses_len,
+            tx_access_list_storage_keys_len,
+            is_eip3860_enabled,
+            init_code_word_size,
+            init_code_size_gt_max,
+            is_eip3651_enabled,
+            coinbase,
+            is_coinbase_warm_prev,
             reversion_info,
             sufficient_gas_left,
             transfer_with_gas_fee,
             code_hash,
             is_empty_code_hash,
+            tx_callee_address_is_zero,
+            tx_callee_address_lt_10,
+            is_precompile,
+            is_empty_code,
+            is_access_list_empty,
         }
     }
 
@@ -286,10 +507,15 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
         call: &Call,
         step: &ExecStep,
     ) -> Result<(), Error> {
+        let randomness = region.evm_word_randomness();
         let gas_fee = tx.gas_price * tx.gas;
-        let [caller_balance_pair, callee_balance_pair, (callee_code_hash, _)] =
-            [step.rw_indices[7], step.rw_indices[8], step.rw_indices[9]]
-                .map(|idx| block.rws[idx].account_value_pair());
+        let hardfork = block.circuits_params.hardfork;
+        // The coinbase is added to the access list before the transfer with
+        // EIP-3651.
+        let rw_offset = hardfork.is_eip3651_enabled() as usize;
+        let [caller_balance_pair, callee_balance_pair] =
+            [7, 8].map(|i| block.rws[step.rw_indices[i + rw_offset]].account_value_pair());
+        let callee_code_hash = call.code_hash;
 
         self.tx_id
             .assign(region, offset, Value::known(F::from(tx.id as u64)))?;
@@ -299,6 +525,25 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             .assign(region, offset, Value::known(F::from(tx.gas)))?;
         self.tx_gas_price
             .assign(region, offset, Some(tx.gas_price.to_le_bytes()))?;
+        self.tx_gas_fee_cap
+            .assign(region, offset, Some(tx.gas_fee_cap.to_le_bytes()))?;
+        let base_fee_plus_tip_cap = block.context.base_fee + tx.gas_tip_cap;
+        self.add_base_fee_to_tip_cap.assign(
+            region,
+            offset,
+            [block.context.base_fee, tx.gas_tip_cap],
+            base_fee_plus_tip_cap,
+        )?;
+        self.fee_cap_lt_base_fee_plus_tip_cap.assign(
+            region,
+            offset,
+            tx.gas_fee_cap,
+            base_fee_plus_tip_cap,
+        )?;
+        self.fee_cap_lt_base_fee
+            .assign(region, offset, tx.gas_fee_cap, block.context.base_fee)?;
+        self.fee_cap_lt_tip_cap
+            .assign(region, offset, tx.gas_fee_cap, tx.gas_tip_cap)?;
         self.mul_gas_fee_by_gas
             .assign(region, offset, tx.gas_price, tx.gas, gas_fee)?;
         let caller_address = tx
@@ -320,16 +565,99 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
         )?;
         self.tx_is_create
             .assign(region, offset, Value::known(F::from(tx.is_create as u64)))?;
+        if tx.is_create {
+            self.caller_address.assign(
+                region,
+                offset,
+                Some(
+                    tx.caller_address.to_word().to_le_bytes()[..N_BYTES_ACCOUNT_ADDRESS]
+                        .try_into()
+                        .unwrap(),
+                ),
+            )?;
+            self.contract_address.assign(
+                region,
+                offset,
+                tx.caller_address,
+                tx.nonce,
+                U256::zero(),
+                [0; 32],
+                false,
+            )?;
+        }
+        self.callee_address.assign(
+            region,
+            offset,
+            Value::known(
+                call.callee_address
+                    .to_scalar()
+                    .expect("unexpected Address -> Scalar conversion failure"),
+            ),
+        )?;
         self.tx_call_data_length.assign(
             region,
             offset,
             Value::known(F::from(tx.call_data_length as u64)),
         )?;
+        self.tx_call_data_length_is_zero.assign(
+            region,
+            offset,
+            F::from(tx.call_data_length as u64),
+        )?;
         self.tx_call_data_gas_cost.assign(
             region,
             offset,
             Value::known(F::from(tx.call_data_gas_cost)),
         )?;
+        self.tx_access_list_addresses_len.assign(
+            region,
+            offset,
+            Value::known(F::from(tx.access_list_addresses_len)),
+        )?;
+        self.tx_access_list_storage_keys_len.assign(
+            region,
+            offset,
+            Value::known(F::from(tx.access_list_storage_keys_len)),
+        )?;
+        self.is_eip3860_enabled.assign(
+            region,
+            offset,
+            Value::known(F::from(hardfork.is_eip3860_enabled() as u64)),
+        )?;
+        self.init_code_word_size
+            .assign(region, offset, tx.call_data_length as u64)?;
+        self.init_code_size_gt_max.assign(
+            region,
+            offset,
+            F::from(MAX_INIT_CODE_SIZE),
+            F::from(tx.call_data_length as u64),
+        )?;
+        self.is_eip3651_enabled.assign(
+            region,
+            offset,
+            Value::known(F::from(hardfork.is_eip3651_enabled() as u64)),
+        )?;
+        self.coinbase.assign(
+            region,
+            offset,
+            Value::known(
+                block
+                    .context
+                    .coinbase
+                    .to_scalar()
+                    .expect("unexpected Address -> Scalar conversion failure"),
+            ),
+        )?;
+        let is_coinbase_warm_prev = if hardfork.is_eip3651_enabled() {
+            block.rws[step.rw_indices[7]].tx_access_list_value_pair().1
+        } else {
+            false
+        };
+        self.is_coinbase_warm_prev.assign(
+            region,
+            offset,
+            Value::known(F::from(is_coinbase_warm_prev as u64)),
+        )?;
         self.reversion_info.assign(
             region,
             offset,
@@ -351,14 +679,37 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             offset,
             Value::known(RandomLinearCombination::random_linear_combine(
                 callee_code_hash.to_le_bytes(),
-                block.randomness,
+                randomness,
             )),
         )?;
-        self.is_empty_code_hash.assign(
+        let is_empty_code_hash = self.is_empty_code_hash.assign(
             region,
             offset,
-            Word::random_linear_combine(callee_code_hash.to_le_bytes(), block.randomness),
-            Word::random_linear_combine(*EMPTY_HASH_LE, block.randomness),
+            Word::random_linear_combine(callee_code_hash.to_le_bytes(), randomness),
+            Word::random_linear_combine(*EMPTY_HASH_LE, randomness),
+        )?;
+        let callee_address = tx
+            .callee_address
+            .to_scalar()
+            .expect("unexpected Address -> Scalar conversion failure");
+        let callee_address_is_zero =
+            self.tx_callee_address_is_zero
+                .assign(region, offset, callee_address)?;
+        let (is_precompile, _) =
+            self.tx_callee_address_lt_10
+                .assign(region, offset, callee_address, F::from(10))?;
+        let is_precompile = is_precompile * (F::one() - callee_address_is_zero);
+        self.is_precompile
+            .assign(region, offset, Value::known(is_precompile))?;
+        self.is_empty_code.assign(
+            region,
+            offset,
+            Value::known(is_empty_code_hash * (F::one() - is_precompile)),
+        )?;
+        self.is_access_list_empty.assign(
+            region,
+            offset,
+            F::from(tx.access_list_addresses_len + tx.access_list_storage_keys_len),
         )?;
         Ok(())
     }
@@ -366,10 +717,15 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
 
 #[cfg(test)]
 mod test {
-    use crate::evm_circuit::test::{rand_bytes, run_test_circuit_geth_data_default};
-    use bus_mapping::evm::OpcodeId;
+    use crate::evm_circuit::test::{
+        rand_bytes, run_test_circuit_geth_data, run_test_circuit_geth_data_default,
+    };
+    use bus_mapping::{circuit_input_builder::CircuitsParams, evm::OpcodeId};
     use eth_types::{
-        self, bytecode, evm_types::GasCost, geth_types::GethData, word, Bytecode, Word,
+        self, address, bytecode,
+        evm_types::{GasCost, Hardfork},
+        geth_types::GethData,
+        word, AccessList, AccessListItem, Address, Bytecode, Word, H256,
     };
     use halo2_proofs::halo2curves::bn256::Fr;
     use mock::{eth, gwei, TestContext, MOCK_ACCOUNTS};
@@ -517,6 +873,87 @@ mod test {
         }
     }
 
+    #[test]
+    fn begin_tx_eip1559() {
+        // The effective gas price is bounded by the fee cap in the first case,
+        // and by the base fee plus the tip cap in the second one. The fee cap
+        // is equal to both the base fee and the tip cap in the last one.
+        for (max_fee_per_gas, max_priority_fee_per_gas) in
+            [(gwei(3), gwei(2)), (gwei(5), gwei(1)), (gwei(2), gwei(2))]
+        {
+            let block: GethData = TestContext::<2, 1>::new(
+                None,
+                |accs| {
+                    accs[0]
+                        .address(MOCK_ACCOUNTS[0])
+                        .balance(eth(10))
+                        .code(code_with_return());
+                    accs[1].address(MOCK_ACCOUNTS[1]).balance(eth(10));
+                },
+                |mut txs, _accs| {
+                    txs[0]
+                        .from(MOCK_ACCOUNTS[1])
+                        .to(MOCK_ACCOUNTS[0])
+                        .transaction_type(2)
+                        .max_fee_per_gas(max_fee_per_gas)
+                        .max_priority_fee_per_gas(max_priority_fee_per_gas)
+                        .gas(Word::from(0x10000))
+                        .value(eth(1));
+                },
+                |block, _tx| block.number(0xcafeu64).base_fee_per_gas(gwei(2)),
+            )
+            .unwrap()
+            .into();
+
+            assert_eq!(run_test_circuit_geth_data_default::<Fr>(block), Ok(()));
+        }
+    }
+
+    #[test]
+    fn begin_tx_access_list() {
+        // The access list warms up an extra account, the callee again and some
+        // of their storage slots, for both the EIP-2930 and the EIP-1559 txs.
+        let access_list = AccessList(vec![
+            AccessListItem {
+                address: address!("0x0000000000000000000000000000000000c0ffee"),
+                storage_keys: vec![H256::zero(), H256::from_low_u64_be(1)],
+            },
+            AccessListItem {
+                address: MOCK_ACCOUNTS[0],
+                storage_keys: vec![H256::zero()],
+            },
+        ]);
+        for transaction_type in [1, 2] {
+            let block: GethData = TestContext::<2, 1>::new(
+                None,
+                |accs| {
+                    accs[0]
+                        .address(MOCK_ACCOUNTS[0])
+                        .balance(eth(10))
+                        .code(code_with_return());
+                    accs[1].address(MOCK_ACCOUNTS[1]).balance(eth(10));
+                },
+                |mut txs, _accs| {
+                    txs[0]
+                        .from(MOCK_ACCOUNTS[1])
+                        .to(MOCK_ACCOUNTS[0])
+                        .transaction_type(transaction_type)
+                        .access_list(access_list.clone())
+                        .gas_price(gwei(2))
+                        .max_fee_per_gas(gwei(2))
+                        .max_priority_fee_per_gas(gwei(2))
+                        .gas(Word::from(0x10000))
+                        .value(eth(1));
+                },
+                |block, _tx| block.number(0xcafeu64),
+            )
+            .unwrap()
+            .into();
+
+            assert_eq!(run_test_circuit_geth_data_default::<Fr>(block), Ok(()));
+        }
+    }
+
     #[test]
     fn begin_tx_no_code() {
         let block: GethData = TestContext::<2, 1>::new(
@@ -564,9 +1001,6 @@ mod test {
         assert_eq!(run_test_circuit_geth_data_default::<Fr>(block), Ok(()));
     }
 
-    // TODO: Enable this test once we have support for contract deployment from
-    // BeginTx.
-    #[ignore]
     #[test]
     fn begin_tx_deploy() {
         let code = bytecode! {
@@ -599,4 +1033,111 @@ mod test {
 
         assert_eq!(run_test_circuit_geth_data_default::<Fr>(block), Ok(()));
     }
+
+    #[test]
+    fn begin_tx_deploy_empty_init_code() {
+        let block: GethData = TestContext::<1, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(MOCK_ACCOUNTS[0]).balance(eth(20));
+            },
+            |mut txs, _accs| {
+                txs[0]
+                    .from(MOCK_ACCOUNTS[0])
+                    .gas_price(gwei(2))
+                    .gas(Word::from(0x10000))
+                    .value(eth(2));
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        assert_eq!(run_test_circuit_geth_data_default::<Fr>(block), Ok(()));
+    }
+
+    #[test]
+    fn begin_tx_deploy_revert() {
+        let block: GethData = TestContext::<1, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(MOCK_ACCOUNTS[0]).balance(eth(20));
+            },
+            |mut txs, _accs| {
+                txs[0]
+                    .from(MOCK_ACCOUNTS[0])
+                    .gas_price(gwei(2))
+                    .gas(Word::from(0x10000))
+                    .value(eth(2))
+                    .input(code_with_revert().into());
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        assert_eq!(run_test_circuit_geth_data_default::<Fr>(block), Ok(()));
+    }
+
+    fn test_ok_shanghai(coinbase: Address, code: Option<Bytecode>, init_code: Option<Bytecode>) {
+        let block: GethData = TestContext::<2, 1>::new_with_hardfork(
+            None,
+            |accs| {
+                accs[0].address(MOCK_ACCOUNTS[0]).balance(eth(10));
+                if let Some(code) = code {
+                    accs[0].code(code);
+                }
+                accs[1].address(MOCK_ACCOUNTS[1]).balance(eth(10));
+            },
+            |mut txs, _accs| {
+                txs[0]
+                    .from(MOCK_ACCOUNTS[1])
+                    .gas_price(gwei(2))
+                    .gas(Word::from(0x10000))
+                    .value(eth(1));
+                match init_code {
+                    Some(init_code) => txs[0].input(init_code.into()),
+                    None => txs[0].to(MOCK_ACCOUNTS[0]),
+                };
+            },
+            |block, _tx| block.author(coinbase).number(0xcafeu64),
+            Hardfork::Shanghai,
+        )
+        .unwrap()
+        .into();
+
+        assert_eq!(
+            run_test_circuit_geth_data::<Fr>(
+                block,
+                CircuitsParams {
+                    hardfork: Hardfork::Shanghai,
+                    ..Default::default()
+                }
+            ),
+            Ok(())
+        );
+    }
+
+    // The geth tracer doesn't support Shanghai yet, so only the native tracer
+    // can trace the warm coinbase and the initialization code gas.
+    #[test]
+    #[cfg_attr(not(feature = "native-tracer"), ignore)]
+    fn begin_tx_shanghai_warm_coinbase() {
+        let code = bytecode! {
+            COINBASE
+            BALANCE
+            STOP
+        };
+        test_ok_shanghai(Address::repeat_byte(0xc0), None, None);
+        test_ok_shanghai(Address::repeat_byte(0xc0), Some(code), None);
+        // The coinbase is already warm as the caller.
+        test_ok_shanghai(MOCK_ACCOUNTS[1], None, None);
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tracer"), ignore)]
+    fn begin_tx_shanghai_deploy() {
+        test_ok_shanghai(Address::repeat_byte(0xc0), None, Some(code_with_return()));
+        test_ok_shanghai(Address::repeat_byte(0xc0), None, Some(Bytecode::default()));
+    }
 }
@@ -0,0 +1,372 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::{N_BYTES_ACCOUNT_ADDRESS, N_BYTES_U64},
+        step::ExecutionState,
+        table::HardforkParam,
+        util::{
+            constraint_builder::{ConstraintBuilder, StepStateTransition, Transition::Delta},
+            math_gadget::{IsEqualGadget, IsZeroGadget, LtGadget},
+            not, select, CachedRegion, Cell, RandomLinearCombination, Word,
+        },
+        witness::{Block, Call, ExecStep, Rw, Transaction},
+    },
+    table::{CallContextFieldTag, TxContextFieldTag},
+    util::Expr,
+};
+use eth_types::{Field, ToLittleEndian, ToScalar, U256};
+use halo2_proofs::{circuit::Value, plonk::Error};
+use keccak256::EMPTY_HASH_LE;
+
+/// Gadget for the steps following BeginTx, which warm up the entries of the
+/// access list of the tx (EIP-2930) one by one: all the addresses, followed by
+/// all the storage keys.  They keep the state resulting from BeginTx, and the
+/// last one goes to the step that would have followed BeginTx.
+#[derive(Clone, Debug)]
+pub(crate) struct BeginTxAccessListGadget<F> {
+    tx_id: Cell<F>,
+    tx_callee_address: Cell<F>,
+    tx_access_list_addresses_len: Cell<F>,
+    tx_access_list_storage_keys_len: Cell<F>,
+    is_eip3651_enabled: Cell<F>,
+    index: Cell<F>,
+    index_lt_addresses_len: LtGadget<F, N_BYTES_U64>,
+    is_last: IsEqualGadget<F>,
+    address: Cell<F>,
+    storage_key: Cell<F>,
+    is_warm_prev: Cell<F>,
+    is_empty_code_hash: IsEqualGadget<F>,
+    tx_callee_address_is_zero: IsZeroGadget<F>,
+    tx_callee_address_lt_10: LtGadget<F, N_BYTES_ACCOUNT_ADDRESS>,
+    is_precompile: Cell<F>,
+    is_empty_code: Cell<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for BeginTxAccessListGadget<F> {
+    const NAME: &'static str = "BeginTxAccessList";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::BeginTxAccessList;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let tx_id = cb.call_context(None, CallContextFieldTag::TxId);
+        let [tx_callee_address, tx_access_list_addresses_len, tx_access_list_storage_keys_len] = [
+            TxContextFieldTag::CalleeAddress,
+            TxContextFieldTag::AccessListAddressesLen,
+            TxContextFieldTag::AccessListStorageKeysLen,
+        ]
+        .map(|field_tag| cb.tx_context(tx_id.expr(), field_tag, None));
+
+        // Recompute the step that follows BeginTx, as it does.
+        let empty_code_hash_rlc = Word::random_linear_combine_expr(
+            (*EMPTY_HASH_LE).map(|byte| byte.expr()),
+            cb.power_of_randomness(),
+        );
+        let is_empty_code_hash =
+            IsEqualGadget::construct(cb, cb.curr.state.code_hash.expr(), empty_code_hash_rlc);
+        let tx_callee_address_is_zero = IsZeroGadget::construct(cb, tx_callee_address.expr());
+        let tx_callee_address_lt_10 = LtGadget::construct(cb, tx_callee_address.expr(), 10.expr());
+        let is_precompile =
+            cb.copy(tx_callee_address_lt_10.expr() * (1.expr() - tx_callee_address_is_zero.expr()));
+        let is_empty_code = cb.copy(is_empty_code_hash.expr() * (1.expr() - is_precompile.expr()));
+
+        // BeginTx, whose rw_counter is the call id, does 10 reads and writes
+        // when it goes to EndTx and 23 otherwise, plus the coinbase access
+        // list write with EIP-3651, and it's followed by the entries of the
+        // access list in order, with 2 reads and writes each.
+        let is_eip3651_enabled = cb.query_bool();
+        cb.hardfork_param_lookup(HardforkParam::IsEip3651Enabled, is_eip3651_enabled.expr());
+        let index = cb.query_cell();
+        cb.require_equal(
+            "rw_counter == call_id + BeginTx reads and writes + 2 * index",
+            cb.curr.state.rw_counter.expr(),
+            cb.curr.state.call_id.expr()
+                + select::expr(is_empty_code.expr(), 10.expr(), 23.expr())
+                + is_eip3651_enabled.expr()
+                + 2.expr() * index.expr(),
+        );
+        let index_lt_addresses_len =
+            LtGadget::construct(cb, index.expr(), tx_access_list_addresses_len.expr());
+        let is_last = IsEqualGadget::construct(
+            cb,
+            index.expr() + 1.expr(),
+            tx_access_list_addresses_len.expr() + tx_access_list_storage_keys_len.expr(),
+        );
+
+        // The entry is the one at index in the tx table, whose storage key is
+        // zero for an address.
+        let address = cb.query_cell();
+        let storage_key = cb.query_cell();
+        cb.tx_context_lookup(
+            tx_id.expr(),
+            TxContextFieldTag::AccessListAddress,
+            Some(index.expr()),
+            address.expr(),
+        );
+        cb.tx_context_lookup(
+            tx_id.expr(),
+            TxContextFieldTag::AccessListStorageKey,
+            Some(index.expr()),
+            storage_key.expr(),
+        );
+        let is_warm_prev = cb.query_bool();
+        cb.condition(index_lt_addresses_len.expr(), |cb| {
+            cb.account_access_list_write(
+                tx_id.expr(),
+                address.expr(),
+                1.expr(),
+                is_warm_prev.expr(),
+                None,
+            );
+        });
+        cb.condition(not::expr(index_lt_addresses_len.expr()), |cb| {
+            cb.account_storage_access_list_write(
+                tx_id.expr(),
+                address.expr(),
+                storage_key.expr(),
+                1.expr(),
+                is_warm_prev.expr(),
+                None,
+            );
+        });
+
+        cb.condition(not::expr(is_last.expr()), |cb| {
+            cb.require_equal(
+                "Go to BeginTxAccessList for the next entry",
+                cb.next
+                    .execution_state_selector([ExecutionState::BeginTxAccessList]),
+                1.expr(),
+            );
+        });
+        cb.condition(is_last.expr() * is_empty_code.expr(), |cb| {
+            cb.require_equal(
+                "Go to EndTx when Tx to account with empty code",
+                cb.next.execution_state_selector([ExecutionState::EndTx]),
+                1.expr(),
+            );
+        });
+        cb.condition(is_last.expr() * not::expr(is_empty_code.expr()), |cb| {
+            cb.require_next_state_precompile(is_precompile.expr(), tx_callee_address.expr());
+        });
+
+        cb.require_step_state_transition(StepStateTransition {
+            // 2 reads and writes:
+            //   - Read CallContext TxId
+            //   - Write TxAccessListAccount(Storage)
+            rw_counter: Delta(2.expr()),
+            ..Default::default()
+        });
+
+        Self {
+            tx_id,
+            tx_callee_address,
+            tx_access_list_addresses_len,
+            tx_access_list_storage_keys_len,
+            is_eip3651_enabled,
+            index,
+            index_lt_addresses_len,
+            is_last,
+            address,
+            storage_key,
+            is_warm_prev,
+            is_empty_code_hash,
+            tx_callee_address_is_zero,
+            tx_callee_address_lt_10,
+            is_precompile,
+            is_empty_code,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        tx: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let randomness = region.evm_word_randomness();
+        let (address, storage_key, is_warm_prev) = match block.rws[step.rw_indices[1]] {
+            Rw::TxAccessListAccount {
+                account_address,
+                is_warm_prev,
+                ..
+            } => (account_address, U256::zero(), is_warm_prev),
+            Rw::TxAccessListAccountStorage {
+                account_address,
+                storage_key,
+                is_warm_prev,
+                ..
+            } => (account_address, storage_key, is_warm_prev),
+            _ => unreachable!("expected a TxAccessListAccount(Storage) write"),
+        };
+
+        self.tx_id
+            .assign(region, offset, Value::known(F::from(tx.id as u64)))?;
+        let callee_address = tx
+            .callee_address
+            .to_scalar()
+            .expect("unexpected Address -> Scalar conversion failure");
+        self.tx_callee_address
+            .assign(region, offset, Value::known(callee_address))?;
+        self.tx_access_list_addresses_len.assign(
+            region,
+            offset,
+            Value::known(F::from(tx.access_list_addresses_len)),
+        )?;
+        self.tx_access_list_storage_keys_len.assign(
+            region,
+            offset,
+            Value::known(F::from(tx.access_list_storage_keys_len)),
+        )?;
+
+        let is_empty_code_hash = self.is_empty_code_hash.assign(
+            region,
+            offset,
+            Word::random_linear_combine(call.code_hash.to_le_bytes(), randomness),
+            Word::random_linear_combine(*EMPTY_HASH_LE, randomness),
+        )?;
+        let callee_address_is_zero =
+            self.tx_callee_address_is_zero
+                .assign(region, offset, callee_address)?;
+        let (is_precompile, _) =
+            self.tx_callee_address_lt_10
+                .assign(region, offset, callee_address, F::from(10))?;
+        let is_precompile = is_precompile * (F::one() - callee_address_is_zero);
+        self.is_precompile
+            .assign(region, offset, Value::known(is_precompile))?;
+        let is_empty_code = is_empty_code_hash * (F::one() - is_precompile);
+        self.is_empty_code
+            .assign(region, offset, Value::known(is_empty_code))?;
+
+        let is_eip3651_enabled = block.circuits_params.hardfork.is_eip3651_enabled();
+        self.is_eip3651_enabled.assign(
+            region,
+            offset,
+            Value::known(F::from(is_eip3651_enabled as u64)),
+        )?;
+        let begin_tx_rws = if is_empty_code == F::one() { 10 } else { 23 };
+        let index = (step.rw_counter - call.id - begin_tx_rws - is_eip3651_enabled as usize) / 2;
+        self.index
+            .assign(region, offset, Value::known(F::from(index as u64)))?;
+        self.index_lt_addresses_len.assign(
+            region,
+            offset,
+            F::from(index as u64),
+            F::from(tx.access_list_addresses_len),
+        )?;
+        self.is_last.assign(
+            region,
+            offset,
+            F::from(index as u64 + 1),
+            F::from(tx.access_list_addresses_len + tx.access_list_storage_keys_len),
+        )?;
+
+        self.address.assign(
+            region,
+            offset,
+            Value::known(
+                address
+                    .to_scalar()
+                    .expect("unexpected Address -> Scalar conversion failure"),
+            ),
+        )?;
+        self.storage_key.assign(
+            region,
+            offset,
+            Value::known(RandomLinearCombination::random_linear_combine(
+                storage_key.to_le_bytes(),
+                randomness,
+            )),
+        )?;
+        self.is_warm_prev
+            .assign(region, offset, Value::known(F::from(is_warm_prev as u64)))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::evm_circuit::test::run_test_circuit_geth_data_default;
+    use eth_types::{
+        address, bytecode, geth_types::GethData, AccessList, AccessListItem, Address, Bytecode,
+        Word, H256,
+    };
+    use halo2_proofs::halo2curves::bn256::Fr;
+    use mock::{eth, gwei, TestContext, MOCK_ACCOUNTS};
+
+    fn test_ok(callee: Address, code: Bytecode, access_list: AccessList) {
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(MOCK_ACCOUNTS[0]).balance(eth(10));
+                accs[1].address(callee).balance(eth(1)).code(code);
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[0].address)
+                    .to(accs[1].address)
+                    .input(vec![1, 2, 3].into())
+                    .gas(Word::from(100_000))
+                    .gas_price(gwei(2))
+                    .transaction_type(1)
+                    .access_list(access_list);
+            },
+            |block, _| block,
+        )
+        .unwrap()
+        .into();
+
+        assert_eq!(run_test_circuit_geth_data_default::<Fr>(block), Ok(()));
+    }
+
+    fn access_list() -> AccessList {
+        AccessList(vec![
+            AccessListItem {
+                address: address!("0x0000000000000000000000000000000000c0ffee"),
+                storage_keys: vec![H256::from_low_u64_be(1), H256::from_low_u64_be(2)],
+            },
+            // The caller is warm already.
+            AccessListItem {
+                address: MOCK_ACCOUNTS[0],
+                storage_keys: vec![H256::from_low_u64_be(1)],
+            },
+        ])
+    }
+
+    #[test]
+    fn begin_tx_access_list_to_code() {
+        let callee = address!("0x0000000000000000000000000000000000c0ffee");
+        // Read a storage slot warmed up by the access list.
+        let code = bytecode! {
+            PUSH1(2)
+            SLOAD
+            STOP
+        };
+        test_ok(callee, code, access_list());
+    }
+
+    #[test]
+    fn begin_tx_access_list_to_empty_code() {
+        test_ok(MOCK_ACCOUNTS[1], Bytecode::default(), access_list());
+    }
+
+    #[test]
+    fn begin_tx_access_list_to_precompile() {
+        let identity = address!("0x0000000000000000000000000000000000000004");
+        test_ok(identity, Bytecode::default(), access_list());
+    }
+
+    #[test]
+    fn begin_tx_access_list_without_storage_keys() {
+        test_ok(
+            MOCK_ACCOUNTS[1],
+            Bytecode::default(),
+            AccessList(vec![AccessListItem {
+                address: MOCK_ACCOUNTS[2],
+                storage_keys: vec![],
+            }]),
+        );
+    }
+}
@@ -0,0 +1,161 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        step::ExecutionState,
+        util::{
+            common_gadget::UpdateBalanceGadget,
+            constraint_builder::{
+                ConstraintBuilder, StepStateTransition,
+                Transition::{Delta, Same},
+            },
+            math_gadget::IsZeroGadget,
+            not, sum, CachedRegion, Cell,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    table::BlockContextFieldTag,
+    util::Expr,
+};
+use eth_types::{Field, ToLittleEndian, ToScalar, Word};
+use halo2_proofs::{circuit::Value, plonk::Error};
+
+/// Gadget paying the block reward to the coinbase once all the txs of the
+/// block are processed, also when the block has none. It's a step of its own
+/// right before EndBlock, since EndBlock is kept to a single row for padding.
+#[derive(Clone, Debug)]
+pub(crate) struct BlockRewardGadget<F> {
+    coinbase: Cell<F>,
+    is_block_reward_zero: IsZeroGadget<F>,
+    coinbase_reward: UpdateBalanceGadget<F, 2, true>,
+}
+
+impl<F: Field> ExecutionGadget<F> for BlockRewardGadget<F> {
+    const NAME: &'static str = "BlockReward";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::BlockReward;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let coinbase = cb.query_cell();
+        let block_reward = cb.query_word();
+        for (tag, value) in [
+            (BlockContextFieldTag::Coinbase, coinbase.expr()),
+            (BlockContextFieldTag::BlockReward, block_reward.expr()),
+        ] {
+            cb.block_lookup(tag.expr(), None, value);
+        }
+
+        // The balance of the coinbase is only updated when there's a reward.
+        let is_block_reward_zero = IsZeroGadget::construct(cb, sum::expr(&block_reward.cells));
+        let coinbase_reward = cb.condition(not::expr(is_block_reward_zero.expr()), |cb| {
+            UpdateBalanceGadget::construct(cb, coinbase.expr(), vec![block_reward], None)
+        });
+
+        cb.require_step_state_transition(StepStateTransition {
+            rw_counter: Delta(cb.rw_counter_offset()),
+            // We propagate call_id so that EndBlock can get the last tx_id
+            // in order to count processed txs.
+            call_id: Same,
+            ..StepStateTransition::any()
+        });
+
+        Self {
+            coinbase,
+            is_block_reward_zero,
+            coinbase_reward,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        _: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let block_reward = block.context.block_reward;
+        let (coinbase_balance, coinbase_balance_prev) = if block_reward.is_zero() {
+            (Word::zero(), Word::zero())
+        } else {
+            block.rws[step.rw_indices[0]].account_value_pair()
+        };
+
+        self.coinbase.assign(
+            region,
+            offset,
+            Value::known(
+                block
+                    .context
+                    .coinbase
+                    .to_scalar()
+                    .expect("unexpected Address -> Scalar conversion failure"),
+            ),
+        )?;
+        self.is_block_reward_zero.assign(
+            region,
+            offset,
+            sum::value(&block_reward.to_le_bytes()),
+        )?;
+        self.coinbase_reward.assign(
+            region,
+            offset,
+            coinbase_balance_prev,
+            vec![block_reward],
+            coinbase_balance,
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::evm_circuit::test::run_test_circuit_geth_data;
+    use bus_mapping::circuit_input_builder::CircuitsParams;
+    use eth_types::{bytecode, geth_types::GethData, Word};
+    use halo2_proofs::halo2curves::bn256::Fr;
+    use mock::{eth, test_ctx::helpers::account_0_code_account_1_no_code, TestContext};
+
+    fn test_ok<const NTX: usize>(block_reward: Word) {
+        let block: GethData = TestContext::<2, NTX>::new(
+            None,
+            account_0_code_account_1_no_code(bytecode! { STOP }),
+            |txs, accs| {
+                for tx in txs {
+                    tx.to(accs[0].address).from(accs[1].address).value(eth(1));
+                }
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        assert_eq!(
+            run_test_circuit_geth_data::<Fr>(
+                block,
+                CircuitsParams {
+                    max_txs: 4,
+                    block_reward,
+                    ..Default::default()
+                }
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn block_reward_gadget_simple() {
+        test_ok::<2>(eth(2));
+    }
+
+    #[test]
+    fn block_reward_gadget_zero() {
+        test_ok::<2>(Word::zero());
+    }
+
+    #[test]
+    fn block_reward_gadget_no_txs() {
+        test_ok::<0>(eth(2));
+    }
+}
@@ -167,6 +167,7 @@ impl<F: Field> ExecutionGadget<F> for CallDataCopyGadget<F> {
         call: &Call,
         step: &ExecStep,
     ) -> Result<(), Error> {
+        let randomness = region.evm_word_randomness();
         self.same_context.assign_exec_step(region, offset, step)?;
 
         let [memory_offset, data_offset, length] =
@@ -174,7 +175,7 @@ impl<F: Field> ExecutionGadget<F> for CallDataCopyGadget<F> {
                 .map(|idx| block.rws[idx].stack_value());
         let memory_address =
             self.memory_address
-                .assign(region, offset, memory_offset, length, block.randomness)?;
+                .assign(region, offset, memory_offset, length, randomness)?;
         self.data_offset.assign(
             region,
             offset,
@@ -7,7 +7,7 @@ use crate::evm_circuit::util::constraint_builder::{
     ConstraintBuilder, ReversionInfo, StepStateTransition,
 };
 use crate::evm_circuit::util::math_gadget::{
-    CmpWordsGadget, ConstantDivisionGadget, IsEqualGadget, IsZeroGadget, MinMaxGadget,
+    CmpWordsGadget, ConstantDivisionGadget, IsEqualGadget, IsZeroGadget, LtGadget, MinMaxGadget,
 };
 use crate::evm_circuit::util::memory_gadget::{MemoryAddressGadget, MemoryExpansionGadget};
 use crate::evm_circuit::util::{
@@ -56,6 +56,10 @@ pub(crate) struct CallOpGadget<F> {
     callee_code_hash: Cell<F>,
     enough_transfer_balance: CmpWordsGadget<F>,
     is_empty_code_hash: IsEqualGadget<F>,
+    code_address_is_zero: IsZeroGadget<F>,
+    code_address_lt_10: LtGadget<F, N_BYTES_ACCOUNT_ADDRESS>,
+    is_precompile: Cell<F>,
+    is_empty_code: Cell<F>,
     one_64th_gas: ConstantDivisionGadget<F, N_BYTES_GAS>,
     capped_callee_gas_left: MinMaxGadget<F, N_BYTES_GAS>,
 }
@@ -241,7 +245,7 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
             );
         });
         cb.condition(1.expr() - callee_exists.expr(), |cb| {
-            cb.account_read(code_address, AccountFieldTag::NonExisting, 0.expr());
+            cb.account_read(code_address.expr(), AccountFieldTag::NonExisting, 0.expr());
         });
 
         let is_empty_code_hash = IsEqualGadget::construct(
@@ -275,11 +279,17 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
             all_but_one_64th_gas,
         );
 
-        // TODO: Handle precompiled
+        // Precompiled contracts are at the addresses from 0x01 to 0x09, and are
+        // executed in the next step regardless of their code hash.
+        let code_address_is_zero = IsZeroGadget::construct(cb, code_address.clone());
+        let code_address_lt_10 = LtGadget::construct(cb, code_address.clone(), 10.expr());
+        let is_precompile =
+            cb.copy(code_address_lt_10.expr() * (1.expr() - code_address_is_zero.expr()));
+        let is_empty_code = cb.copy(is_empty_code_hash.expr() * (1.expr() - is_precompile.expr()));
 
         let stack_pointer_delta =
             select::expr(is_call.expr() + is_callcode.expr(), 6.expr(), 5.expr());
-        cb.condition(is_empty_code_hash.expr(), |cb| {
+        cb.condition(is_empty_code.expr(), |cb| {
             // Save caller's call state
             for field_tag in [
                 CallContextFieldTag::LastCalleeId,
@@ -317,7 +327,9 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
             });
         });
 
-        cb.condition(1.expr() - is_empty_code_hash.expr(), |cb| {
+        cb.condition(1.expr() - is_empty_code.expr(), |cb| {
+            cb.require_next_state_precompile(is_precompile.expr(), code_address);
+
             // Save caller's call state
             for (field_tag, value) in [
                 (
@@ -434,6 +446,10 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
             callee_code_hash,
             enough_transfer_balance,
             is_empty_code_hash,
+            code_address_is_zero,
+            code_address_lt_10,
+            is_precompile,
+            is_empty_code,
             one_64th_gas,
             capped_callee_gas_left,
         }
@@ -448,6 +464,7 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
         call: &Call,
         step: &ExecStep,
     ) -> Result<(), Error> {
+        let randomness = region.evm_word_randomness();
         let opcode = step.opcode.unwrap();
         let is_call = opcode == OpcodeId::CALL;
         let is_callcode = opcode == OpcodeId::CALLCODE;
@@ -524,7 +541,7 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
             _ => unreachable!(),
         };
         let callee_code_hash =
-            RandomLinearCombination::random_linear_combine(callee_code_hash, block.randomness);
+            RandomLinearCombination::random_linear_combine(callee_code_hash, randomness);
         self.opcode
             .assign(region, offset, Value::known(F::from(opcode.as_u64())))?;
         self.is_call.assign(
@@ -603,12 +620,12 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
         )?;
         self.value_is_zero
             .assign(region, offset, sum::value(&value.to_le_bytes()))?;
-        let cd_address =
-            self.cd_address
-                .assign(region, offset, cd_offset, cd_length, block.randomness)?;
-        let rd_address =
-            self.rd_address
-                .assign(region, offset, rd_offset, rd_length, block.randomness)?;
+        let cd_address = self
+            .cd_address
+            .assign(region, offset, cd_offset, cd_length, randomness)?;
+        let rd_address = self
+            .rd_address
+            .assign(region, offset, rd_offset, rd_length, randomness)?;
         let (_, memory_expansion_gas_cost) = self.memory_expansion.assign(
             region,
             offset,
@@ -628,11 +645,27 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
             .assign(region, offset, Value::known(callee_code_hash))?;
         self.enough_transfer_balance
             .assign(region, offset, value, caller_balance_pair.1)?;
-        self.is_empty_code_hash.assign(
+        let is_empty_code_hash = self.is_empty_code_hash.assign(
             region,
             offset,
             callee_code_hash,
-            Word::random_linear_combine(*EMPTY_HASH_LE, block.randomness),
+            Word::random_linear_combine(*EMPTY_HASH_LE, randomness),
+        )?;
+        let code_address_value =
+            from_bytes::value::<F>(&code_address.to_le_bytes()[..N_BYTES_ACCOUNT_ADDRESS]);
+        let code_address_is_zero =
+            self.code_address_is_zero
+                .assign(region, offset, code_address_value)?;
+        let (is_precompile, _) =
+            self.code_address_lt_10
+                .assign(region, offset, code_address_value, F::from(10))?;
+        let is_precompile = is_precompile * (F::one() - code_address_is_zero);
+        self.is_precompile
+            .assign(region, offset, Value::known(is_precompile))?;
+        self.is_empty_code.assign(
+            region,
+            offset,
+            Value::known(is_empty_code_hash * (F::one() - is_precompile)),
         )?;
         let has_value = !value.is_zero() && !is_delegatecall;
         let gas_cost = if is_warm_prev {
@@ -69,6 +69,7 @@ impl<F: Field> ExecutionGadget<F> for CallValueGadget<F> {
         _: &Call,
         step: &ExecStep,
     ) -> Result<(), Error> {
+        let randomness = region.evm_word_randomness();
         self.same_context.assign_exec_step(region, offset, step)?;
 
         let call_value = block.rws[step.rw_indices[1]].stack_value();
@@ -78,7 +79,7 @@ impl<F: Field> ExecutionGadget<F> for CallValueGadget<F> {
             offset,
             Value::known(Word::random_linear_combine(
                 call_value.to_le_bytes(),
-                block.randomness,
+                randomness,
             )),
         )?;
 
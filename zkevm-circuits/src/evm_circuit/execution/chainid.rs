@@ -62,6 +62,7 @@ impl<F: Field> ExecutionGadget<F> for ChainIdGadget<F> {
         _: &Call,
         step: &ExecStep,
     ) -> Result<(), Error> {
+        let randomness = region.evm_word_randomness();
         self.same_context.assign_exec_step(region, offset, step)?;
         let chain_id = block.rws[step.rw_indices[0]].stack_value();
 
@@ -70,7 +71,7 @@ impl<F: Field> ExecutionGadget<F> for ChainIdGadget<F> {
             offset,
             Value::known(Word::random_linear_combine(
                 chain_id.to_le_bytes(),
-                block.randomness,
+                randomness,
             )),
         )?;
         Ok(())
@@ -133,6 +133,7 @@ impl<F: Field> ExecutionGadget<F> for CodeCopyGadget<F> {
         call: &Call,
         step: &ExecStep,
     ) -> Result<(), Error> {
+        let randomness = region.evm_word_randomness();
         self.same_context.assign_exec_step(region, offset, step)?;
 
         // 1. `dest_offset` is the bytes offset in the memory where we start to
@@ -168,7 +169,7 @@ impl<F: Field> ExecutionGadget<F> for CodeCopyGadget<F> {
         // assign the destination memory offset.
         let memory_address =
             self.dst_memory_addr
-                .assign(region, offset, dest_offset, size, block.randomness)?;
+                .assign(region, offset, dest_offset, size, randomness)?;
 
         // assign to gadgets handling memory expansion cost and copying cost.
         let (_, memory_expansion_cost) = self.memory_expansion.assign(
@@ -0,0 +1,659 @@
+use crate::evm_circuit::execution::ExecutionGadget;
+use crate::evm_circuit::param::{
+    N_BYTES_ACCOUNT_ADDRESS, N_BYTES_GAS, N_BYTES_MEMORY_ADDRESS, N_BYTES_MEMORY_WORD_SIZE,
+};
+use crate::evm_circuit::step::ExecutionState;
+use crate::evm_circuit::table::HardforkParam;
+use crate::evm_circuit::util::common_gadget::{ContractAddressGadget, TransferGadget};
+use crate::evm_circuit::util::constraint_builder::Transition::{Delta, To};
+use crate::evm_circuit::util::constraint_builder::{
+    ConstraintBuilder, ReversionInfo, StepStateTransition,
+};
+use crate::evm_circuit::util::math_gadget::{ConstantDivisionGadget, IsEqualGadget, LtGadget};
+use crate::evm_circuit::util::memory_gadget::{
+    MemoryAddressGadget, MemoryCopierGasGadget, MemoryExpansionGadget,
+};
+use crate::evm_circuit::util::{
+    from_bytes, not, select, CachedRegion, Cell, RandomLinearCombination, Word,
+};
+use crate::evm_circuit::witness::{Block, Call, ExecStep, Transaction};
+use crate::table::{AccountFieldTag, CallContextFieldTag};
+use crate::util::Expr;
+use bus_mapping::circuit_input_builder::CopyDataType;
+use bus_mapping::evm::OpcodeId;
+use eth_types::evm_types::{GasCost, MAX_INIT_CODE_SIZE};
+use eth_types::{Field, ToAddress, ToLittleEndian, U256};
+use ethers_core::utils::keccak256;
+use halo2_proofs::circuit::Value;
+use halo2_proofs::plonk::Error;
+use keccak256::EMPTY_HASH_LE;
+
+/// Gadget for CREATE and CREATE2 opcodes.
+#[derive(Clone, Debug)]
+pub(crate) struct CreateGadget<F> {
+    opcode: Cell<F>,
+    is_create2: IsEqualGadget<F>,
+    tx_id: Cell<F>,
+    reversion_info: ReversionInfo<F>,
+    depth: Cell<F>,
+    caller_address: RandomLinearCombination<F, N_BYTES_ACCOUNT_ADDRESS>,
+    value: Word<F>,
+    is_success: Cell<F>,
+    contract_address: ContractAddressGadget<F>,
+    is_warm_prev: Cell<F>,
+    init_code: MemoryAddressGadget<F>,
+    is_eip3860_enabled: Cell<F>,
+    init_code_size_gt_max: LtGadget<F, N_BYTES_MEMORY_ADDRESS>,
+    callee_reversion_info: ReversionInfo<F>,
+    transfer: TransferGadget<F>,
+    memory_expansion: MemoryExpansionGadget<F, 1, N_BYTES_MEMORY_WORD_SIZE>,
+    gas_cost: MemoryCopierGasGadget<F, { GasCost::COPY_SHA3 }>,
+    init_code_gas_cost: MemoryCopierGasGadget<F, { GasCost::INIT_CODE_WORD }>,
+    one_64th_gas: ConstantDivisionGadget<F, N_BYTES_GAS>,
+}
+
+impl<F: Field> ExecutionGadget<F> for CreateGadget<F> {
+    const NAME: &'static str = "CREATE";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::CREATE;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        cb.opcode_lookup(opcode.expr(), 1.expr());
+        let is_create2 = IsEqualGadget::construct(cb, opcode.expr(), OpcodeId::CREATE2.expr());
+
+        // We do the responsible opcode check explicitly here because we're not
+        // using the SameContextGadget for CREATE or CREATE2.
+        cb.require_equal(
+            "Opcode should be CREATE or CREATE2",
+            opcode.expr(),
+            select::expr(
+                is_create2.expr(),
+                OpcodeId::CREATE2.expr(),
+                OpcodeId::CREATE.expr(),
+            ),
+        );
+
+        // Use rw_counter of the step which triggers next call as its call_id.
+        let callee_call_id = cb.curr.state.rw_counter.clone();
+
+        let tx_id = cb.call_context(None, CallContextFieldTag::TxId);
+        let mut reversion_info = cb.reversion_info_read(None);
+        let depth = cb.call_context(None, CallContextFieldTag::Depth);
+        let caller_address = cb.query_rlc();
+        cb.call_context_lookup(
+            false.expr(),
+            None,
+            CallContextFieldTag::CalleeAddress,
+            from_bytes::expr(&caller_address.cells),
+        );
+
+        cb.range_lookup(depth.expr(), 1024);
+
+        // The new contract address is derived from the caller's address and
+        // either its nonce or the salt and the hash of the initialization code.
+        let contract_address =
+            ContractAddressGadget::construct(cb, &caller_address, is_create2.expr());
+        let callee_address = contract_address.address();
+
+        let value = cb.query_word();
+        let offset = cb.query_cell();
+        let length = cb.query_rlc::<N_BYTES_MEMORY_ADDRESS>();
+        cb.stack_pop(value.expr());
+        cb.stack_pop(offset.expr());
+        cb.stack_pop(length.expr());
+        cb.condition(is_create2.expr(), |cb| {
+            cb.stack_pop(contract_address.salt().expr())
+        });
+
+        let is_success = cb.query_bool();
+        cb.stack_push(is_success.expr() * contract_address.address_rlc(cb.power_of_randomness()));
+
+        // Increase caller's nonce
+        cb.account_write(
+            from_bytes::expr(&caller_address.cells),
+            AccountFieldTag::Nonce,
+            contract_address.caller_nonce() + 1.expr(),
+            contract_address.caller_nonce(),
+            Some(&mut reversion_info),
+        );
+
+        // Add the new contract address into access list
+        let is_warm_prev = cb.query_bool();
+        cb.account_access_list_write(
+            tx_id.expr(),
+            callee_address.clone(),
+            1.expr(),
+            is_warm_prev.expr(),
+            Some(&mut reversion_info),
+        );
+
+        // Copy the initialization code into the bytecode table, where it's
+        // identified by its code hash.
+        let init_code = MemoryAddressGadget::construct(cb, offset, length);
+        let code_hash = contract_address.code_hash().clone();
+        cb.condition(init_code.has_length(), |cb| {
+            cb.copy_table_lookup(
+                cb.curr.state.call_id.expr(),
+                CopyDataType::Memory.expr(),
+                code_hash.expr(),
+                CopyDataType::Bytecode.expr(),
+                init_code.offset(),
+                init_code.address(),
+                0.expr(),
+                init_code.length(),
+                0.expr(),
+                init_code.length(),
+            );
+        });
+        cb.condition(not::expr(init_code.has_length()), |cb| {
+            cb.require_equal(
+                "Empty initialization code has the hash of empty bytes",
+                code_hash.expr(),
+                Word::random_linear_combine_expr(
+                    (*EMPTY_HASH_LE).map(|byte| byte.expr()),
+                    cb.power_of_randomness(),
+                ),
+            );
+        });
+
+        // The initialization code is limited in size once EIP-3860 is
+        // enabled, otherwise ErrorOutOfGasCREATE2 (or the dynamic memory
+        // expansion one for CREATE) happens instead.
+        let is_eip3860_enabled = cb.query_bool();
+        cb.hardfork_param_lookup(HardforkParam::IsEip3860Enabled, is_eip3860_enabled.expr());
+        let init_code_size_gt_max =
+            LtGadget::construct(cb, MAX_INIT_CODE_SIZE.expr(), init_code.length());
+        cb.require_zero(
+            "Initialization code size <= MAX_INIT_CODE_SIZE if EIP-3860 is enabled",
+            is_eip3860_enabled.expr() * init_code_size_gt_max.expr(),
+        );
+
+        // Propagate rw_counter_end_of_reversion and is_persistent
+        let mut callee_reversion_info = cb.reversion_info_write(Some(callee_call_id.expr()));
+        cb.require_equal(
+            "callee_is_persistent == is_persistent ⋅ is_success",
+            callee_reversion_info.is_persistent(),
+            reversion_info.is_persistent() * is_success.expr(),
+        );
+        cb.condition(is_success.expr() * (1.expr() - reversion_info.is_persistent()), |cb| {
+            cb.require_equal(
+                "callee_rw_counter_end_of_reversion == rw_counter_end_of_reversion - (reversible_write_counter + 1)",
+                callee_reversion_info.rw_counter_end_of_reversion(),
+                reversion_info.rw_counter_of_reversion(),
+            );
+        });
+
+        // Increase callee's nonce to 1, as specified in EIP-161.
+        cb.account_write(
+            callee_address.clone(),
+            AccountFieldTag::Nonce,
+            1.expr(),
+            0.expr(),
+            Some(&mut callee_reversion_info),
+        );
+
+        let transfer = TransferGadget::construct(
+            cb,
+            from_bytes::expr(&caller_address.cells),
+            callee_address.clone(),
+            value.clone(),
+            &mut callee_reversion_info,
+        );
+
+        // Sum up and verify gas cost. Hashing the initialization code costs
+        // extra gas for CREATE2 only, and each of its words costs extra gas
+        // once EIP-3860 is enabled.
+        let memory_expansion = MemoryExpansionGadget::construct(cb, [init_code.address()]);
+        let gas_cost = MemoryCopierGasGadget::construct(
+            cb,
+            is_create2.expr() * init_code.length(),
+            memory_expansion.gas_cost(),
+        );
+        let init_code_gas_cost = MemoryCopierGasGadget::construct(
+            cb,
+            is_eip3860_enabled.expr() * init_code.length(),
+            gas_cost.gas_cost(),
+        );
+        let total_gas_cost =
+            OpcodeId::CREATE.constant_gas_cost().expr() + init_code_gas_cost.gas_cost();
+
+        // Apply EIP 150
+        let gas_available = cb.curr.state.gas_left.expr() - total_gas_cost.clone();
+        let one_64th_gas = ConstantDivisionGadget::construct(cb, gas_available.clone(), 64);
+        let callee_gas_left = gas_available - one_64th_gas.quotient();
+
+        let stack_pointer_delta = 2.expr() + is_create2.expr();
+        cb.condition(not::expr(init_code.has_length()), |cb| {
+            // Without initialization code there is no callee execution that
+            // could fail, so the contract is created.
+            cb.require_equal(
+                "is_success == 1 for empty initialization code",
+                is_success.expr(),
+                1.expr(),
+            );
+
+            // Save caller's call state
+            for field_tag in [
+                CallContextFieldTag::LastCalleeId,
+                CallContextFieldTag::LastCalleeReturnDataOffset,
+                CallContextFieldTag::LastCalleeReturnDataLength,
+            ] {
+                cb.call_context_lookup(true.expr(), None, field_tag, 0.expr());
+            }
+
+            cb.require_step_state_transition(StepStateTransition {
+                rw_counter: Delta(cb.rw_counter_offset()),
+                program_counter: Delta(1.expr()),
+                stack_pointer: Delta(stack_pointer_delta.clone()),
+                gas_left: Delta(-total_gas_cost.clone()),
+                memory_word_size: To(memory_expansion.next_memory_word_size()),
+                // The caller's nonce update and the access list write.
+                reversible_write_counter: Delta(2.expr()),
+                ..StepStateTransition::default()
+            });
+        });
+
+        cb.condition(init_code.has_length(), |cb| {
+            // Save caller's call state
+            for (field_tag, value) in [
+                (
+                    CallContextFieldTag::ProgramCounter,
+                    cb.curr.state.program_counter.expr() + 1.expr(),
+                ),
+                (
+                    CallContextFieldTag::StackPointer,
+                    cb.curr.state.stack_pointer.expr() + stack_pointer_delta,
+                ),
+                (
+                    CallContextFieldTag::GasLeft,
+                    cb.curr.state.gas_left.expr() - total_gas_cost - callee_gas_left.clone(),
+                ),
+                (
+                    CallContextFieldTag::MemorySize,
+                    memory_expansion.next_memory_word_size(),
+                ),
+                (
+                    CallContextFieldTag::ReversibleWriteCounter,
+                    cb.curr.state.reversible_write_counter.expr() + 2.expr(),
+                ),
+            ] {
+                cb.call_context_lookup(true.expr(), None, field_tag, value);
+            }
+
+            // Setup next call's context.
+            for (field_tag, value) in [
+                (CallContextFieldTag::CallerId, cb.curr.state.call_id.expr()),
+                (CallContextFieldTag::TxId, tx_id.expr()),
+                (CallContextFieldTag::Depth, depth.expr() + 1.expr()),
+                (
+                    CallContextFieldTag::CallerAddress,
+                    from_bytes::expr(&caller_address.cells),
+                ),
+                (CallContextFieldTag::CalleeAddress, callee_address),
+                (CallContextFieldTag::CallDataOffset, 0.expr()),
+                (CallContextFieldTag::CallDataLength, 0.expr()),
+                (CallContextFieldTag::ReturnDataOffset, 0.expr()),
+                (CallContextFieldTag::ReturnDataLength, 0.expr()),
+                (CallContextFieldTag::Value, value.expr()),
+                (CallContextFieldTag::IsSuccess, is_success.expr()),
+                (CallContextFieldTag::IsStatic, 0.expr()),
+                (CallContextFieldTag::LastCalleeId, 0.expr()),
+                (CallContextFieldTag::LastCalleeReturnDataOffset, 0.expr()),
+                (CallContextFieldTag::LastCalleeReturnDataLength, 0.expr()),
+                (CallContextFieldTag::IsRoot, 0.expr()),
+                (CallContextFieldTag::IsCreate, 1.expr()),
+                (CallContextFieldTag::CodeHash, code_hash.expr()),
+            ] {
+                cb.call_context_lookup(true.expr(), Some(callee_call_id.expr()), field_tag, value);
+            }
+
+            cb.require_step_state_transition(StepStateTransition {
+                rw_counter: Delta(cb.rw_counter_offset()),
+                call_id: To(callee_call_id.expr()),
+                is_root: To(false.expr()),
+                is_create: To(true.expr()),
+                code_hash: To(code_hash.expr()),
+                gas_left: To(callee_gas_left),
+                // The callee's nonce update and the two balance updates of
+                // the transfer.
+                reversible_write_counter: To(3.expr()),
+                ..StepStateTransition::new_context()
+            });
+        });
+
+        Self {
+            opcode,
+            is_create2,
+            tx_id,
+            reversion_info,
+            depth,
+            caller_address,
+            value,
+            is_success,
+            contract_address,
+            is_warm_prev,
+            init_code,
+            is_eip3860_enabled,
+            init_code_size_gt_max,
+            callee_reversion_info,
+            transfer,
+            memory_expansion,
+            gas_cost,
+            init_code_gas_cost,
+            one_64th_gas,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let randomness = region.evm_word_randomness();
+        let opcode = step.opcode.unwrap();
+        let is_create2 = opcode == OpcodeId::CREATE2;
+        self.opcode
+            .assign(region, offset, Value::known(F::from(opcode.as_u64())))?;
+        self.is_create2.assign(
+            region,
+            offset,
+            F::from(opcode.as_u64()),
+            F::from(OpcodeId::CREATE2.as_u64()),
+        )?;
+
+        let [tx_id, depth, caller_address] =
+            [step.rw_indices[0], step.rw_indices[3], step.rw_indices[4]]
+                .map(|idx| block.rws[idx].call_context_value());
+        self.tx_id
+            .assign(region, offset, Value::known(F::from(tx_id.low_u64())))?;
+        self.reversion_info.assign(
+            region,
+            offset,
+            call.rw_counter_end_of_reversion,
+            call.is_persistent,
+        )?;
+        self.depth
+            .assign(region, offset, Value::known(F::from(depth.low_u64())))?;
+        self.caller_address.assign(
+            region,
+            offset,
+            Some(
+                caller_address.to_le_bytes()[..N_BYTES_ACCOUNT_ADDRESS]
+                    .try_into()
+                    .unwrap(),
+            ),
+        )?;
+
+        let [value, init_code_offset, init_code_length] =
+            [step.rw_indices[5], step.rw_indices[6], step.rw_indices[7]]
+                .map(|idx| block.rws[idx].stack_value());
+        self.value
+            .assign(region, offset, Some(value.to_le_bytes()))?;
+        // This offset is used to change the index offset of `step.rw_indices`,
+        // since CREATE2 has an extra stack pop `salt`.
+        let mut rw_offset = 0;
+        let salt = if is_create2 {
+            rw_offset += 1;
+            block.rws[step.rw_indices[8]].stack_value()
+        } else {
+            U256::zero()
+        };
+
+        let callee_address = block.rws[step.rw_indices[8 + rw_offset]].stack_value();
+        self.is_success.assign(
+            region,
+            offset,
+            Value::known(F::from(!callee_address.is_zero() as u64)),
+        )?;
+
+        let (_, caller_nonce) = block.rws[step.rw_indices[9 + rw_offset]].account_value_pair();
+
+        let (_, is_warm_prev) =
+            block.rws[step.rw_indices[10 + rw_offset]].tx_access_list_value_pair();
+        self.is_warm_prev
+            .assign(region, offset, Value::known(F::from(is_warm_prev as u64)))?;
+
+        let init_code_address = self.init_code.assign(
+            region,
+            offset,
+            init_code_offset,
+            init_code_length,
+            randomness,
+        )?;
+        let init_code_length = init_code_length.as_usize();
+        let is_eip3860_enabled = block.circuits_params.hardfork.is_eip3860_enabled();
+        self.is_eip3860_enabled.assign(
+            region,
+            offset,
+            Value::known(F::from(is_eip3860_enabled as u64)),
+        )?;
+        self.init_code_size_gt_max.assign(
+            region,
+            offset,
+            F::from(MAX_INIT_CODE_SIZE),
+            F::from(init_code_length as u64),
+        )?;
+        let init_code: Vec<u8> = (11 + rw_offset..11 + rw_offset + init_code_length)
+            .map(|i| block.rws[step.rw_indices[i]].memory_value())
+            .collect();
+        rw_offset += init_code_length;
+        self.contract_address.assign(
+            region,
+            offset,
+            caller_address.to_address(),
+            caller_nonce.low_u64(),
+            salt,
+            keccak256(&init_code),
+            is_create2,
+        )?;
+
+        let [callee_rw_counter_end_of_reversion, callee_is_persistent] = [
+            step.rw_indices[11 + rw_offset],
+            step.rw_indices[12 + rw_offset],
+        ]
+        .map(|idx| block.rws[idx].call_context_value());
+        self.callee_reversion_info.assign(
+            region,
+            offset,
+            callee_rw_counter_end_of_reversion.low_u64() as usize,
+            callee_is_persistent.low_u64() != 0,
+        )?;
+
+        let [caller_balance_pair, callee_balance_pair] = [
+            step.rw_indices[14 + rw_offset],
+            step.rw_indices[15 + rw_offset],
+        ]
+        .map(|idx| block.rws[idx].account_value_pair());
+        self.transfer.assign(
+            region,
+            offset,
+            caller_balance_pair,
+            callee_balance_pair,
+            value,
+        )?;
+
+        let (_, memory_expansion_gas_cost) = self.memory_expansion.assign(
+            region,
+            offset,
+            step.memory_word_size(),
+            [init_code_address],
+        )?;
+        let gas_cost = self.gas_cost.assign(
+            region,
+            offset,
+            if is_create2 {
+                init_code_length as u64
+            } else {
+                0
+            },
+            memory_expansion_gas_cost,
+        )?;
+        let gas_cost = self.init_code_gas_cost.assign(
+            region,
+            offset,
+            if is_eip3860_enabled {
+                init_code_length as u64
+            } else {
+                0
+            },
+            gas_cost,
+        )?;
+        let gas_available = step.gas_left - GasCost::CREATE.as_u64() - gas_cost;
+        self.one_64th_gas
+            .assign(region, offset, gas_available as u128)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::evm_circuit::test::run_test_circuit_geth_data;
+    use bus_mapping::circuit_input_builder::CircuitsParams;
+    use eth_types::evm_types::Hardfork;
+    use eth_types::geth_types::{Account, GethData};
+    use eth_types::{address, bytecode, Address, Bytecode, Word};
+    use halo2_proofs::halo2curves::bn256::Fr;
+    use itertools::Itertools;
+    use mock::TestContext;
+
+    // RETURN a 4-byte runtime code from the initialization code.
+    fn init_code(is_success: bool) -> Bytecode {
+        let terminator = if is_success {
+            bytecode! { RETURN }
+        } else {
+            bytecode! { REVERT }
+        };
+        let mut code = bytecode! {
+            PUSH4(0x6000_6000)
+            PUSH1(0)
+            MSTORE
+            PUSH1(4)
+            PUSH1(28)
+        };
+        code.append(&terminator);
+        code
+    }
+
+    fn creator(init_code: Bytecode, is_create2: bool, value: u64) -> Account {
+        let init_code = init_code.to_vec();
+        let init_code_length = init_code.len();
+        // Store the initialization code at memory offset 0.
+        let mut code = Bytecode::default();
+        for (i, byte) in init_code.iter().enumerate() {
+            code.append(&bytecode! {
+                PUSH1(*byte)
+                PUSH2(i)
+                MSTORE8
+            });
+        }
+        if is_create2 {
+            code.append(&bytecode! { PUSH32(Word::from(0xdeadbeefu64)) });
+        }
+        code.append(&bytecode! {
+            PUSH2(init_code_length)
+            PUSH1(0)
+            PUSH32(Word::from(value))
+        });
+        code.write_op(if is_create2 {
+            eth_types::evm_types::OpcodeId::CREATE2
+        } else {
+            eth_types::evm_types::OpcodeId::CREATE
+        });
+        code.append(&bytecode! { STOP });
+
+        Account {
+            address: Address::repeat_byte(0xfe),
+            balance: Word::from(10).pow(20.into()),
+            nonce: Word::from(1),
+            code: code.to_vec().into(),
+            ..Default::default()
+        }
+    }
+
+    fn test_ok(creator: Account) {
+        test_ok_with_hardfork(creator, Hardfork::default());
+    }
+
+    fn test_ok_with_hardfork(creator: Account, hardfork: Hardfork) {
+        let block: GethData = TestContext::<2, 1>::new_with_hardfork(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x000000000000000000000000000000000000cafe"))
+                    .balance(Word::from(10u64.pow(19)));
+                accs[1]
+                    .address(creator.address)
+                    .code(creator.code)
+                    .nonce(creator.nonce)
+                    .balance(creator.balance);
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[0].address)
+                    .to(accs[1].address)
+                    .gas(1_000_000.into());
+            },
+            |block, _tx| block.number(0xcafeu64),
+            hardfork,
+        )
+        .unwrap()
+        .into();
+        assert_eq!(
+            run_test_circuit_geth_data::<Fr>(
+                block,
+                CircuitsParams {
+                    max_rws: 4500,
+                    hardfork,
+                    ..Default::default()
+                }
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn create_empty_init_code() {
+        for (is_create2, value) in [false, true].into_iter().cartesian_product([0, 1000]) {
+            test_ok(creator(Bytecode::default(), is_create2, value));
+        }
+    }
+
+    #[test]
+    fn create() {
+        for ((is_create2, is_success), value) in [false, true]
+            .into_iter()
+            .cartesian_product([true, false])
+            .cartesian_product([0, 1000])
+        {
+            test_ok(creator(init_code(is_success), is_create2, value));
+        }
+    }
+
+    #[test]
+    fn create_with_long_nonce() {
+        let mut creator = creator(init_code(true), false, 0);
+        for nonce in [0x7f, 0x80, 0xffff, 0xdeadbeef] {
+            creator.nonce = Word::from(nonce);
+            test_ok(creator.clone());
+        }
+    }
+
+    // The geth tracer doesn't support Shanghai yet, so only the native tracer
+    // can trace the initialization code gas of EIP-3860.
+    #[test]
+    #[cfg_attr(not(feature = "native-tracer"), ignore)]
+    fn create_shanghai() {
+        for (is_create2, init_code) in [false, true]
+            .into_iter()
+            .cartesian_product([Bytecode::default(), init_code(true)])
+        {
+            test_ok_with_hardfork(creator(init_code, is_create2, 0), Hardfork::Shanghai);
+        }
+    }
+}
@@ -36,22 +36,18 @@ impl<F: Field> ExecutionGadget<F> for EndBlockGadget<F> {
         let max_rws = cb.query_copy_cell();
         let total_txs = cb.query_cell();
         let total_txs_is_max_txs = IsEqualGadget::construct(cb, total_txs.expr(), max_txs.expr());
+        // The block is empty when it has no txs, but it may still have the rw of
+        // the block reward, so we can't tell it from the rw_counter.
+        let is_empty_block = IsZeroGadget::construct(cb, total_txs.expr());
         // Note that rw_counter starts at 1
-        let is_empty_block =
-            IsZeroGadget::construct(cb, cb.curr.state.rw_counter.clone().expr() - 1.expr());
         // If the block is empty, we do 0 rw_table lookups
         // If the block is not empty, we will do 1 call_context lookup
-        let total_rws = not::expr(is_empty_block.expr())
-            * (cb.curr.state.rw_counter.clone().expr() - 1.expr() + 1.expr());
-
-        // 1. Constraint total_rws and total_txs witness values depending on the empty
-        // block case.
-        cb.condition(is_empty_block.expr(), |cb| {
-            // 1a.
-            cb.require_equal("total_txs is 0 in empty block", total_txs.expr(), 0.expr());
-        });
+        let total_rws =
+            cb.curr.state.rw_counter.clone().expr() - 1.expr() + not::expr(is_empty_block.expr());
+
+        // 1. If the block is not empty, total_txs matches the tx_id that corresponds
+        // to the final step.
         cb.condition(not::expr(is_empty_block.expr()), |cb| {
-            // 1b. total_txs matches the tx_id that corresponds to the final step.
             cb.call_context_lookup(0.expr(), None, CallContextFieldTag::TxId, total_txs.expr());
         });
 
@@ -86,10 +82,6 @@ impl<F: Field> ExecutionGadget<F> for EndBlockGadget<F> {
         // We conclude that the number of meaningful entries in the rw_table
         // is total_rws.
 
-        // cb.step_last(|cb| {
-        //     // TODO: Handle reward to coinbase.  Depends on spec:
-        //     // https://github.com/privacy-scaling-explorations/zkevm-specs/issues/290
-        // });
         cb.not_step_last(|cb| {
             // Propagate rw_counter and call_id all the way down.
             cb.require_step_state_transition(StepStateTransition {
@@ -117,8 +109,6 @@ impl<F: Field> ExecutionGadget<F> for EndBlockGadget<F> {
         _: &Call,
         step: &ExecStep,
     ) -> Result<(), Error> {
-        self.is_empty_block
-            .assign(region, offset, F::from(step.rw_counter as u64 - 1))?;
         let max_rws = F::from(block.circuits_params.max_rws as u64);
         let max_rws_assigned = self.max_rws.assign(region, offset, Value::known(max_rws))?;
 
@@ -126,6 +116,7 @@ impl<F: Field> ExecutionGadget<F> for EndBlockGadget<F> {
         let max_txs = F::from(block.circuits_params.max_txs as u64);
         self.total_txs
             .assign(region, offset, Value::known(total_txs))?;
+        self.is_empty_block.assign(region, offset, total_txs)?;
         self.total_txs_is_max_txs
             .assign(region, offset, total_txs, max_txs)?;
         let max_txs_assigned = self.max_txs.assign(region, offset, Value::known(max_txs))?;
@@ -3,6 +3,7 @@ use crate::{
         execution::ExecutionGadget,
         param::N_BYTES_GAS,
         step::ExecutionState,
+        table::HardforkParam,
         util::{
             common_gadget::UpdateBalanceGadget,
             constraint_builder::{
@@ -10,8 +11,8 @@ use crate::{
                 Transition::{Delta, Same},
             },
             math_gadget::{
-                AddWordsGadget, ConstantDivisionGadget, IsEqualGadget, MinMaxGadget,
-                MulWordByU64Gadget,
+                AddWordsGadget, IsEqualGadget, LtGadget, MinMaxGadget, MulWordByU64Gadget,
+                RangeCheckGadget,
             },
             CachedRegion, Cell,
         },
@@ -22,7 +23,7 @@ use crate::{
     },
     util::Expr,
 };
-use eth_types::{evm_types::MAX_REFUND_QUOTIENT_OF_GAS_USED, Field, ToScalar};
+use eth_types::{Field, ToScalar};
 use halo2_proofs::{circuit::Value, plonk::Error};
 use strum::EnumCount;
 
@@ -30,7 +31,11 @@ use strum::EnumCount;
 pub(crate) struct EndTxGadget<F> {
     tx_id: Cell<F>,
     tx_gas: Cell<F>,
-    max_refund: ConstantDivisionGadget<F, N_BYTES_GAS>,
+    refund_quotient: Cell<F>,
+    max_refund: Cell<F>,
+    max_refund_range_check: RangeCheckGadget<F, N_BYTES_GAS>,
+    max_refund_remainder: Cell<F>,
+    max_refund_remainder_lt_quotient: LtGadget<F, 1>,
     refund: Cell<F>,
     effective_refund: MinMaxGadget<F, N_BYTES_GAS>,
     mul_gas_price_by_refund: MulWordByU64Gadget<F>,
@@ -61,14 +66,30 @@ impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
 
         // Calculate effective gas to refund
         let gas_used = tx_gas.expr() - cb.curr.state.gas_left.expr();
-        let max_refund = ConstantDivisionGadget::construct(
-            cb,
-            gas_used.clone(),
-            MAX_REFUND_QUOTIENT_OF_GAS_USED as u64,
+        // The refund is capped to gas_used / refund_quotient, where the
+        // quotient depends on the hardfork.
+        let refund_quotient = cb.query_cell();
+        cb.hardfork_param_lookup(HardforkParam::RefundQuotient, refund_quotient.expr());
+        let max_refund = cb.query_cell();
+        let max_refund_range_check = RangeCheckGadget::construct(cb, max_refund.expr());
+        let max_refund_remainder = cb.query_cell();
+        // The quotient is at most 5, so the remainder fits in a byte.
+        cb.range_lookup(max_refund_remainder.expr(), 5);
+        let max_refund_remainder_lt_quotient =
+            LtGadget::construct(cb, max_refund_remainder.expr(), refund_quotient.expr());
+        cb.require_equal(
+            "max_refund_remainder < refund_quotient",
+            max_refund_remainder_lt_quotient.expr(),
+            1.expr(),
+        );
+        cb.require_equal(
+            "gas_used - max_refund_remainder == max_refund ⋅ refund_quotient",
+            gas_used.clone() - max_refund_remainder.expr(),
+            max_refund.expr() * refund_quotient.expr(),
         );
         let refund = cb.query_cell();
         cb.tx_refund_read(tx_id.expr(), refund.expr());
-        let effective_refund = MinMaxGadget::construct(cb, max_refund.quotient(), refund.expr());
+        let effective_refund = MinMaxGadget::construct(cb, max_refund.expr(), refund.expr());
 
         // Add effective_refund * tx_gas_price back to caller's balance
         let mul_gas_price_by_refund = MulWordByU64Gadget::construct(
@@ -83,7 +104,9 @@ impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
             None,
         );
 
-        // Add gas_used * effective_tip to coinbase's balance
+        // Add gas_used * effective_tip to coinbase's balance. The effective tip is
+        // min(gas_tip_cap, gas_fee_cap - base_fee), since the tx gas price is the
+        // effective gas price and the base fee part of it is burned.
         let coinbase = cb.query_cell();
         let base_fee = cb.query_word();
         for (tag, value) in [
@@ -162,7 +185,8 @@ impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
         );
 
         cb.condition(
-            cb.next.execution_state_selector([ExecutionState::EndBlock]),
+            cb.next
+                .execution_state_selector([ExecutionState::BlockReward]),
             |cb| {
                 cb.require_step_state_transition(StepStateTransition {
                     rw_counter: Delta(9.expr() - is_first_tx.expr()),
@@ -177,7 +201,11 @@ impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
         Self {
             tx_id,
             tx_gas,
+            refund_quotient,
             max_refund,
+            max_refund_range_check,
+            max_refund_remainder,
+            max_refund_remainder_lt_quotient,
             refund,
             effective_refund,
             mul_gas_price_by_refund,
@@ -211,16 +239,31 @@ impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
             .assign(region, offset, Value::known(F::from(tx.id as u64)))?;
         self.tx_gas
             .assign(region, offset, Value::known(F::from(tx.gas)))?;
-        let (max_refund, _) = self.max_refund.assign(region, offset, gas_used as u128)?;
-        self.refund
-            .assign(region, offset, Value::known(F::from(refund)))?;
-        self.effective_refund.assign(
+        let refund_quotient = block.circuits_params.hardfork.max_refund_quotient();
+        let max_refund = gas_used / refund_quotient;
+        let max_refund_remainder = gas_used % refund_quotient;
+        self.refund_quotient
+            .assign(region, offset, Value::known(F::from(refund_quotient)))?;
+        self.max_refund
+            .assign(region, offset, Value::known(F::from(max_refund)))?;
+        self.max_refund_range_check
+            .assign(region, offset, F::from(max_refund))?;
+        self.max_refund_remainder.assign(
+            region,
+            offset,
+            Value::known(F::from(max_refund_remainder)),
+        )?;
+        self.max_refund_remainder_lt_quotient.assign(
             region,
             offset,
-            F::from(max_refund as u64),
-            F::from(refund),
+            F::from(max_refund_remainder),
+            F::from(refund_quotient),
         )?;
-        let effective_refund = refund.min(max_refund as u64);
+        self.refund
+            .assign(region, offset, Value::known(F::from(refund)))?;
+        self.effective_refund
+            .assign(region, offset, F::from(max_refund), F::from(refund))?;
+        let effective_refund = refund.min(max_refund);
         let gas_fee_refund = tx.gas_price * (effective_refund + step.gas_left);
         self.mul_gas_price_by_refund.assign(
             region,
@@ -313,7 +356,7 @@ mod test {
     use bus_mapping::circuit_input_builder::CircuitsParams;
     use eth_types::{self, bytecode, geth_types::GethData};
     use halo2_proofs::halo2curves::bn256::Fr;
-    use mock::{eth, test_ctx::helpers::account_0_code_account_1_no_code, TestContext};
+    use mock::{eth, gwei, test_ctx::helpers::account_0_code_account_1_no_code, TestContext};
 
     fn test_ok(block: GethData) {
         assert_eq!(
@@ -370,4 +413,38 @@ mod test {
             .into(),
         );
     }
+
+    #[test]
+    fn end_tx_gadget_priority_fee() {
+        // The coinbase only gets the priority fee of each tx, since the base fee
+        // is burned.
+        let block: GethData = TestContext::<2, 2>::new(
+            None,
+            account_0_code_account_1_no_code(bytecode! { STOP }),
+            |txs, accs| {
+                for tx in txs {
+                    tx.to(accs[0].address)
+                        .from(accs[1].address)
+                        .transaction_type(2)
+                        .max_fee_per_gas(gwei(3))
+                        .max_priority_fee_per_gas(gwei(1))
+                        .value(eth(1));
+                }
+            },
+            |block, _tx| block.number(0xcafeu64).base_fee_per_gas(gwei(1)),
+        )
+        .unwrap()
+        .into();
+
+        assert_eq!(
+            run_test_circuit_geth_data::<Fr>(
+                block,
+                CircuitsParams {
+                    max_txs: 4,
+                    ..Default::default()
+                }
+            ),
+            Ok(())
+        );
+    }
 }
@@ -0,0 +1,461 @@
+use crate::evm_circuit::{
+    execution::ExecutionGadget,
+    param::{
+        N_BYTES_ACCOUNT_ADDRESS, N_BYTES_GAS, N_BYTES_MEMORY_ADDRESS, N_BYTES_MEMORY_WORD_SIZE,
+    },
+    step::ExecutionState,
+    table::{FixedTableTag, Lookup},
+    util::{
+        common_gadget::ContractAddressGadget,
+        constraint_builder::{
+            ConstraintBuilder, ReversionInfo, StepStateTransition,
+            Transition::{Delta, To},
+        },
+        from_bytes,
+        math_gadget::{ConstantDivisionGadget, IsEqualGadget, IsZeroGadget},
+        memory_gadget::{MemoryAddressGadget, MemoryCopierGasGadget, MemoryExpansionGadget},
+        not, CachedRegion, Cell, RandomLinearCombination, Word,
+    },
+    witness::{Block, Call, ExecStep, Transaction},
+};
+use crate::table::{AccountFieldTag, CallContextFieldTag};
+use crate::util::Expr;
+use bus_mapping::{circuit_input_builder::CopyDataType, evm::OpcodeId};
+use eth_types::{evm_types::GasCost, Field, ToAddress, ToLittleEndian, U256};
+use ethers_core::utils::keccak256;
+use halo2_proofs::{circuit::Value, plonk::Error};
+use keccak256::EMPTY_HASH_LE;
+
+/// Gadget for CREATE and CREATE2 whose new contract address already has a
+/// non-zero nonce or non-empty code. The caller's nonce increase and the access
+/// list write persist, while all the gas forwarded to the callee is consumed.
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorContractAddressCollisionGadget<F> {
+    opcode: Cell<F>,
+    is_create2: IsEqualGadget<F>,
+    tx_id: Cell<F>,
+    reversion_info: ReversionInfo<F>,
+    depth: Cell<F>,
+    caller_address: RandomLinearCombination<F, N_BYTES_ACCOUNT_ADDRESS>,
+    value: Word<F>,
+    contract_address: ContractAddressGadget<F>,
+    is_warm_prev: Cell<F>,
+    init_code: MemoryAddressGadget<F>,
+    callee_nonce: Cell<F>,
+    callee_code_hash: Cell<F>,
+    callee_nonce_is_zero: IsZeroGadget<F>,
+    callee_code_hash_is_zero: IsZeroGadget<F>,
+    callee_code_hash_is_empty: IsEqualGadget<F>,
+    memory_expansion: MemoryExpansionGadget<F, 1, N_BYTES_MEMORY_WORD_SIZE>,
+    gas_cost: MemoryCopierGasGadget<F, { GasCost::COPY_SHA3 }>,
+    one_64th_gas: ConstantDivisionGadget<F, N_BYTES_GAS>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorContractAddressCollisionGadget<F> {
+    const NAME: &'static str = "ErrorContractAddressCollision";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorContractAddressCollision;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        cb.opcode_lookup(opcode.expr(), 1.expr());
+        cb.add_lookup(
+            "Responsible opcode lookup",
+            Lookup::Fixed {
+                tag: FixedTableTag::ResponsibleOpcode.expr(),
+                values: [
+                    cb.execution_state().as_u64().expr(),
+                    opcode.expr(),
+                    0.expr(),
+                ],
+            },
+        );
+        let is_create2 = IsEqualGadget::construct(cb, opcode.expr(), OpcodeId::CREATE2.expr());
+
+        let tx_id = cb.call_context(None, CallContextFieldTag::TxId);
+        let mut reversion_info = cb.reversion_info_read(None);
+        let depth = cb.call_context(None, CallContextFieldTag::Depth);
+        let caller_address = cb.query_rlc();
+        cb.call_context_lookup(
+            false.expr(),
+            None,
+            CallContextFieldTag::CalleeAddress,
+            from_bytes::expr(&caller_address.cells),
+        );
+
+        // The precheck of depth and balance has passed.
+        cb.range_lookup(depth.expr(), 1024);
+
+        let contract_address =
+            ContractAddressGadget::construct(cb, &caller_address, is_create2.expr());
+        let callee_address = contract_address.address();
+
+        let value = cb.query_word();
+        let offset = cb.query_cell();
+        let length = cb.query_rlc::<N_BYTES_MEMORY_ADDRESS>();
+        cb.stack_pop(value.expr());
+        cb.stack_pop(offset.expr());
+        cb.stack_pop(length.expr());
+        cb.condition(is_create2.expr(), |cb| {
+            cb.stack_pop(contract_address.salt().expr())
+        });
+        cb.stack_push(0.expr());
+
+        // Increase caller's nonce
+        cb.account_write(
+            from_bytes::expr(&caller_address.cells),
+            AccountFieldTag::Nonce,
+            contract_address.caller_nonce() + 1.expr(),
+            contract_address.caller_nonce(),
+            Some(&mut reversion_info),
+        );
+
+        // Add the new contract address into access list
+        let is_warm_prev = cb.query_bool();
+        cb.account_access_list_write(
+            tx_id.expr(),
+            callee_address.clone(),
+            1.expr(),
+            is_warm_prev.expr(),
+            Some(&mut reversion_info),
+        );
+
+        // Copy the initialization code into the bytecode table to verify its
+        // code hash, as in the CREATE gadget.
+        let init_code = MemoryAddressGadget::construct(cb, offset, length);
+        let code_hash = contract_address.code_hash();
+        cb.condition(init_code.has_length(), |cb| {
+            cb.copy_table_lookup(
+                cb.curr.state.call_id.expr(),
+                CopyDataType::Memory.expr(),
+                code_hash.expr(),
+                CopyDataType::Bytecode.expr(),
+                init_code.offset(),
+                init_code.address(),
+                0.expr(),
+                init_code.length(),
+                0.expr(),
+                init_code.length(),
+            );
+        });
+        cb.condition(not::expr(init_code.has_length()), |cb| {
+            cb.require_equal(
+                "Empty initialization code has the hash of empty bytes",
+                code_hash.expr(),
+                Word::random_linear_combine_expr(
+                    (*EMPTY_HASH_LE).map(|byte| byte.expr()),
+                    cb.power_of_randomness(),
+                ),
+            );
+        });
+
+        // The new contract address collides when it has a non-zero nonce or a
+        // code hash other than zero and the hash of empty bytes.
+        let callee_nonce = cb.query_cell();
+        let callee_code_hash = cb.query_cell();
+        cb.account_read(
+            callee_address.clone(),
+            AccountFieldTag::Nonce,
+            callee_nonce.expr(),
+        );
+        cb.account_read(
+            callee_address,
+            AccountFieldTag::CodeHash,
+            callee_code_hash.expr(),
+        );
+        let callee_nonce_is_zero = IsZeroGadget::construct(cb, callee_nonce.expr());
+        let callee_code_hash_is_zero = IsZeroGadget::construct(cb, callee_code_hash.expr());
+        let callee_code_hash_is_empty = IsEqualGadget::construct(
+            cb,
+            callee_code_hash.expr(),
+            Word::random_linear_combine_expr(
+                (*EMPTY_HASH_LE).map(|byte| byte.expr()),
+                cb.power_of_randomness(),
+            ),
+        );
+        cb.require_zero(
+            "contract address has a non-zero nonce or non-empty code",
+            callee_nonce_is_zero.expr()
+                * (callee_code_hash_is_zero.expr() + callee_code_hash_is_empty.expr()),
+        );
+
+        // The return data of the last callee is cleared.
+        for field_tag in [
+            CallContextFieldTag::LastCalleeId,
+            CallContextFieldTag::LastCalleeReturnDataOffset,
+            CallContextFieldTag::LastCalleeReturnDataLength,
+        ] {
+            cb.call_context_lookup(true.expr(), None, field_tag, 0.expr());
+        }
+
+        // Sum up the gas cost as in the CREATE gadget. The callee would get all
+        // but one 64th of the gas available, which is consumed by the failure.
+        let memory_expansion = MemoryExpansionGadget::construct(cb, [init_code.address()]);
+        let gas_cost = MemoryCopierGasGadget::construct(
+            cb,
+            is_create2.expr() * init_code.length(),
+            memory_expansion.gas_cost(),
+        );
+        let gas_available = cb.curr.state.gas_left.expr()
+            - OpcodeId::CREATE.constant_gas_cost().expr()
+            - gas_cost.gas_cost();
+        let one_64th_gas = ConstantDivisionGadget::construct(cb, gas_available, 64);
+
+        cb.require_step_state_transition(StepStateTransition {
+            rw_counter: Delta(cb.rw_counter_offset()),
+            program_counter: Delta(1.expr()),
+            stack_pointer: Delta(2.expr() + is_create2.expr()),
+            gas_left: To(one_64th_gas.quotient()),
+            memory_word_size: To(memory_expansion.next_memory_word_size()),
+            // The caller's nonce update and the access list write.
+            reversible_write_counter: Delta(2.expr()),
+            ..StepStateTransition::default()
+        });
+
+        Self {
+            opcode,
+            is_create2,
+            tx_id,
+            reversion_info,
+            depth,
+            caller_address,
+            value,
+            contract_address,
+            is_warm_prev,
+            init_code,
+            callee_nonce,
+            callee_code_hash,
+            callee_nonce_is_zero,
+            callee_code_hash_is_zero,
+            callee_code_hash_is_empty,
+            memory_expansion,
+            gas_cost,
+            one_64th_gas,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let randomness = region.evm_word_randomness();
+        let opcode = step.opcode.unwrap();
+        let is_create2 = opcode == OpcodeId::CREATE2;
+        self.opcode
+            .assign(region, offset, Value::known(F::from(opcode.as_u64())))?;
+        self.is_create2.assign(
+            region,
+            offset,
+            F::from(opcode.as_u64()),
+            F::from(OpcodeId::CREATE2.as_u64()),
+        )?;
+
+        let [tx_id, depth, caller_address] =
+            [0, 3, 4].map(|i| block.rws[step.rw_indices[i]].call_context_value());
+        self.tx_id
+            .assign(region, offset, Value::known(F::from(tx_id.low_u64())))?;
+        self.reversion_info.assign(
+            region,
+            offset,
+            call.rw_counter_end_of_reversion,
+            call.is_persistent,
+        )?;
+        self.depth
+            .assign(region, offset, Value::known(F::from(depth.low_u64())))?;
+        self.caller_address.assign(
+            region,
+            offset,
+            Some(
+                caller_address.to_le_bytes()[..N_BYTES_ACCOUNT_ADDRESS]
+                    .try_into()
+                    .unwrap(),
+            ),
+        )?;
+
+        let [value, init_code_offset, init_code_length] =
+            [5, 6, 7].map(|i| block.rws[step.rw_indices[i]].stack_value());
+        self.value
+            .assign(region, offset, Some(value.to_le_bytes()))?;
+        // This offset is used to change the index offset of `step.rw_indices`,
+        // since CREATE2 has an extra stack pop `salt`.
+        let mut rw_offset = 0;
+        let salt = if is_create2 {
+            rw_offset += 1;
+            block.rws[step.rw_indices[8]].stack_value()
+        } else {
+            U256::zero()
+        };
+
+        let (_, caller_nonce) = block.rws[step.rw_indices[9 + rw_offset]].account_value_pair();
+        let (_, is_warm_prev) =
+            block.rws[step.rw_indices[10 + rw_offset]].tx_access_list_value_pair();
+        self.is_warm_prev
+            .assign(region, offset, Value::known(F::from(is_warm_prev as u64)))?;
+
+        let init_code_address = self.init_code.assign(
+            region,
+            offset,
+            init_code_offset,
+            init_code_length,
+            randomness,
+        )?;
+        let init_code_length = init_code_length.as_usize();
+        let init_code: Vec<u8> = (11 + rw_offset..11 + rw_offset + init_code_length)
+            .map(|i| block.rws[step.rw_indices[i]].memory_value())
+            .collect();
+        rw_offset += init_code_length;
+        self.contract_address.assign(
+            region,
+            offset,
+            caller_address.to_address(),
+            caller_nonce.low_u64(),
+            salt,
+            keccak256(&init_code),
+            is_create2,
+        )?;
+
+        let [callee_nonce, callee_code_hash] = [11 + rw_offset, 12 + rw_offset]
+            .map(|i| block.rws[step.rw_indices[i]].account_value_pair().0);
+        let callee_nonce = F::from(callee_nonce.low_u64());
+        let callee_code_hash =
+            Word::random_linear_combine(callee_code_hash.to_le_bytes(), randomness);
+        self.callee_nonce
+            .assign(region, offset, Value::known(callee_nonce))?;
+        self.callee_code_hash
+            .assign(region, offset, Value::known(callee_code_hash))?;
+        self.callee_nonce_is_zero
+            .assign(region, offset, callee_nonce)?;
+        self.callee_code_hash_is_zero
+            .assign(region, offset, callee_code_hash)?;
+        self.callee_code_hash_is_empty.assign(
+            region,
+            offset,
+            callee_code_hash,
+            Word::random_linear_combine(*EMPTY_HASH_LE, randomness),
+        )?;
+
+        let (_, memory_expansion_gas_cost) = self.memory_expansion.assign(
+            region,
+            offset,
+            step.memory_word_size(),
+            [init_code_address],
+        )?;
+        let gas_cost = self.gas_cost.assign(
+            region,
+            offset,
+            if is_create2 {
+                init_code_length as u64
+            } else {
+                0
+            },
+            memory_expansion_gas_cost,
+        )?;
+        let gas_available = step.gas_left - GasCost::CREATE.as_u64() - gas_cost;
+        self.one_64th_gas
+            .assign(region, offset, gas_available as u128)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::run_test_circuits;
+    use eth_types::{address, bytecode, evm_types::OpcodeId, Address, Bytecode, Word};
+    use ethers_core::utils::{get_contract_address, get_create2_address};
+    use mock::TestContext;
+
+    const CREATOR: Address = address!("0x00000000000000000000000000000000000000fe");
+
+    // CREATE or CREATE2 a contract from the 1-byte initialization code `STOP`.
+    fn creator_code(is_create2: bool) -> Bytecode {
+        let mut code = bytecode! {
+            PUSH1(0x00) // STOP
+            PUSH1(0x00)
+            MSTORE8
+        };
+        if is_create2 {
+            code.append(&bytecode! { PUSH1(0x01) }); // salt
+        }
+        code.append(&bytecode! {
+            PUSH1(0x01) // length
+            PUSH1(0x00) // offset
+            PUSH1(0x00) // value
+        });
+        code.write_op(if is_create2 {
+            OpcodeId::CREATE2
+        } else {
+            OpcodeId::CREATE
+        });
+        code.append(&bytecode! { STOP });
+        code
+    }
+
+    fn test_contract_address_collision(is_create2: bool) {
+        let collided_address = if is_create2 {
+            get_create2_address(CREATOR, Word::one().to_be_bytes().to_vec(), vec![0u8])
+        } else {
+            get_contract_address(CREATOR, Word::zero())
+        };
+        // The collided account has a non-zero nonce only, so that its code hash
+        // is the hash of empty bytes.
+        let ctx = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x000000000000000000000000000000000000cafe"))
+                    .balance(Word::from(10u64.pow(19)));
+                accs[1].address(CREATOR).code(creator_code(is_create2));
+                accs[2].address(collided_address).nonce(Word::one());
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[0].address)
+                    .to(accs[1].address)
+                    .gas(Word::from(100_000));
+            },
+            |block, _tx| block,
+        )
+        .unwrap();
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+
+    #[test]
+    fn contract_address_collision_create() {
+        test_contract_address_collision(false);
+    }
+
+    #[test]
+    fn contract_address_collision_create2() {
+        test_contract_address_collision(true);
+    }
+
+    #[test]
+    fn contract_address_collision_with_code() {
+        let collided_address = get_contract_address(CREATOR, Word::zero());
+        let ctx = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x000000000000000000000000000000000000cafe"))
+                    .balance(Word::from(10u64.pow(19)));
+                accs[1].address(CREATOR).code(creator_code(false));
+                accs[2].address(collided_address).code(bytecode! { STOP });
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[0].address)
+                    .to(accs[1].address)
+                    .gas(Word::from(100_000));
+            },
+            |block, _tx| block,
+        )
+        .unwrap();
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+}
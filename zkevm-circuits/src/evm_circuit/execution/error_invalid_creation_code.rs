@@ -0,0 +1,137 @@
+use crate::evm_circuit::{
+    execution::ExecutionGadget,
+    step::ExecutionState,
+    table::HardforkParam,
+    util::{
+        common_gadget::CommonErrorGadget, constraint_builder::ConstraintBuilder,
+        memory_gadget::MemoryAddressGadget, CachedRegion, Cell,
+    },
+    witness::{Block, Call, ExecStep, Transaction},
+};
+use crate::table::CallContextFieldTag;
+use crate::util::Expr;
+use eth_types::Field;
+use halo2_proofs::{circuit::Value, plonk::Error};
+
+/// Gadget for the code returned by RETURN in a contract creation starting with
+/// the byte 0xEF, which is rejected as specified in
+/// [EIP-3541](https://eips.ethereum.org/EIPS/eip-3541).
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorInvalidCreationCodeGadget<F> {
+    memory_address: MemoryAddressGadget<F>,
+    first_byte: Cell<F>,
+    common_error_gadget: CommonErrorGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorInvalidCreationCodeGadget<F> {
+    const NAME: &'static str = "ErrorInvalidCreationCode";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorInvalidCreationCode;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+
+        let offset = cb.query_cell();
+        let length = cb.query_rlc();
+        cb.stack_pop(offset.expr());
+        cb.stack_pop(length.expr());
+        let memory_address = MemoryAddressGadget::construct(cb, offset, length);
+
+        // Only the returned code of a contract creation is stored.
+        cb.call_context_lookup(false.expr(), None, CallContextFieldTag::IsCreate, 1.expr());
+
+        cb.require_equal(
+            "Returned code is not empty",
+            memory_address.has_length(),
+            1.expr(),
+        );
+        let first_byte = cb.query_cell();
+        cb.memory_lookup(0.expr(), memory_address.offset(), first_byte.expr(), None);
+        cb.require_equal(
+            "Returned code starts with 0xEF",
+            first_byte.expr(),
+            0xef.expr(),
+        );
+        // The code is only rejected since London.
+        cb.hardfork_param_lookup(HardforkParam::IsEip3541Enabled, 1.expr());
+
+        let common_error_gadget = CommonErrorGadget::construct(cb, opcode);
+
+        Self {
+            memory_address,
+            first_byte,
+            common_error_gadget,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _tx: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let randomness = region.evm_word_randomness();
+        let [memory_offset, length] =
+            [step.rw_indices[0], step.rw_indices[1]].map(|idx| block.rws[idx].stack_value());
+        self.memory_address
+            .assign(region, offset, memory_offset, length, randomness)?;
+
+        let first_byte = block.rws[step.rw_indices[3]].memory_value();
+        self.first_byte
+            .assign(region, offset, Value::known(F::from(first_byte as u64)))?;
+
+        self.common_error_gadget
+            .assign(region, offset, block, call, step, 5)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::run_test_circuits;
+    use eth_types::{bytecode, Word};
+    use mock::{test_ctx::helpers::account_0_code_account_1_no_code, TestContext, MOCK_ACCOUNTS};
+
+    fn test_invalid_creation_code(first_byte: u8) {
+        // Return 1 byte of code at the end of the first memory word.
+        let init_code = bytecode! {
+            PUSH1(first_byte)
+            PUSH1(0)
+            MSTORE8
+            PUSH1(1)
+            PUSH1(0)
+            RETURN
+        }
+        .to_vec();
+        let code = bytecode! {
+            PUSH32(Word::from_big_endian(&init_code))
+            PUSH1(0)
+            MSTORE
+            PUSH1(init_code.len())
+            PUSH1(32 - init_code.len())
+            PUSH1(0)
+            CREATE
+            STOP
+        };
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            |mut txs, _accs| {
+                txs[0]
+                    .from(MOCK_ACCOUNTS[1])
+                    .to(MOCK_ACCOUNTS[0])
+                    .gas(Word::from(200_000));
+            },
+            |block, _tx| block,
+        )
+        .unwrap();
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+
+    #[test]
+    fn invalid_creation_code() {
+        test_invalid_creation_code(0xef);
+    }
+}
@@ -154,6 +154,7 @@ impl<F: Field> ExecutionGadget<F> for ErrorInvalidJumpGadget<F> {
         call: &Call,
         step: &ExecStep,
     ) -> Result<(), Error> {
+        let randomness = region.evm_word_randomness();
         let opcode = step.opcode.unwrap();
         let is_jumpi = opcode == OpcodeId::JUMPI;
 
@@ -165,8 +166,7 @@ impl<F: Field> ExecutionGadget<F> for ErrorInvalidJumpGadget<F> {
         } else {
             Word::zero()
         };
-        let condition_rlc =
-            RLCWord::random_linear_combine(condition.to_le_bytes(), block.randomness);
+        let condition_rlc = RLCWord::random_linear_combine(condition.to_le_bytes(), randomness);
 
         self.destination.assign(
             region,
@@ -0,0 +1,93 @@
+use crate::evm_circuit::{
+    execution::ExecutionGadget,
+    step::ExecutionState,
+    util::{common_gadget::CommonErrorGadget, constraint_builder::ConstraintBuilder, CachedRegion},
+    witness::{Block, Call, ExecStep, Transaction},
+};
+use eth_types::Field;
+use halo2_proofs::plonk::Error;
+
+/// Gadget for invalid opcodes. The responsible opcode lookup is enough to
+/// verify the opcode is undefined.
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorInvalidOpcodeGadget<F> {
+    common_error_gadget: CommonErrorGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorInvalidOpcodeGadget<F> {
+    const NAME: &'static str = "ErrorInvalidOpcode";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorInvalidOpcode;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        let common_error_gadget = CommonErrorGadget::construct(cb, opcode);
+
+        Self {
+            common_error_gadget,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _tx: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.common_error_gadget
+            .assign(region, offset, block, call, step, 1)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::run_test_circuits;
+    use eth_types::{bytecode, bytecode::Bytecode, Word};
+    use mock::{test_ctx::helpers::account_0_code_account_1_no_code, TestContext, MOCK_ACCOUNTS};
+
+    fn test_invalid_opcode(byte: u8) {
+        let mut code = bytecode! {
+            PUSH1(0x01)
+            PUSH1(0x02)
+        };
+        code.write(byte, true);
+        test_ok(code);
+    }
+
+    fn test_ok(code: Bytecode) {
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            |mut txs, _accs| {
+                txs[0]
+                    .from(MOCK_ACCOUNTS[1])
+                    .to(MOCK_ACCOUNTS[0])
+                    .gas(Word::from(30_000));
+            },
+            |block, _tx| block,
+        )
+        .unwrap();
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+
+    #[test]
+    fn invalid_opcode_designated() {
+        // The designated INVALID opcode
+        test_invalid_opcode(0xfe);
+    }
+
+    #[test]
+    fn invalid_opcode_undefined() {
+        for byte in [0x0c, 0x21, 0xef] {
+            test_invalid_opcode(byte);
+        }
+    }
+
+    #[test]
+    fn invalid_opcode_push0_before_shanghai() {
+        test_invalid_opcode(0x5f);
+    }
+}
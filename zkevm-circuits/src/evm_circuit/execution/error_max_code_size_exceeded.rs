@@ -0,0 +1,147 @@
+use crate::evm_circuit::{
+    execution::ExecutionGadget,
+    param::N_BYTES_MEMORY_ADDRESS,
+    step::ExecutionState,
+    util::{
+        common_gadget::CommonErrorGadget, constraint_builder::ConstraintBuilder, from_bytes,
+        math_gadget::LtGadget, CachedRegion, Cell, RandomLinearCombination, Word,
+    },
+    witness::{Block, Call, ExecStep, Transaction},
+};
+use crate::table::CallContextFieldTag;
+use crate::util::Expr;
+use eth_types::{evm_types::MAX_CODE_SIZE, Field, ToLittleEndian};
+use halo2_proofs::{circuit::Value, plonk::Error};
+
+/// Gadget for the code returned by RETURN in a contract creation exceeding the
+/// maximum code size, as specified in
+/// [EIP-170](https://eips.ethereum.org/EIPS/eip-170).
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorMaxCodeSizeExceededGadget<F> {
+    memory_offset: Cell<F>,
+    length: RandomLinearCombination<F, N_BYTES_MEMORY_ADDRESS>,
+    max_code_size_exceeded: LtGadget<F, N_BYTES_MEMORY_ADDRESS>,
+    common_error_gadget: CommonErrorGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorMaxCodeSizeExceededGadget<F> {
+    const NAME: &'static str = "ErrorMaxCodeSizeExceeded";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorMaxCodeSizeExceeded;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+
+        // The length fits in `N_BYTES_MEMORY_ADDRESS` bytes since the memory
+        // expansion of RETURN has been charged.
+        let memory_offset = cb.query_cell();
+        let length = cb.query_rlc();
+        cb.stack_pop(memory_offset.expr());
+        cb.stack_pop(length.expr());
+
+        // Only the returned code of a contract creation is stored.
+        cb.call_context_lookup(false.expr(), None, CallContextFieldTag::IsCreate, 1.expr());
+
+        let max_code_size_exceeded =
+            LtGadget::construct(cb, MAX_CODE_SIZE.expr(), from_bytes::expr(&length.cells));
+        cb.require_equal(
+            "Returned code size > MAX_CODE_SIZE",
+            max_code_size_exceeded.expr(),
+            1.expr(),
+        );
+
+        let common_error_gadget = CommonErrorGadget::construct(cb, opcode);
+
+        Self {
+            memory_offset,
+            length,
+            max_code_size_exceeded,
+            common_error_gadget,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _tx: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let randomness = region.evm_word_randomness();
+        let [memory_offset, length] =
+            [step.rw_indices[0], step.rw_indices[1]].map(|idx| block.rws[idx].stack_value());
+        self.memory_offset.assign(
+            region,
+            offset,
+            Value::known(Word::random_linear_combine(
+                memory_offset.to_le_bytes(),
+                randomness,
+            )),
+        )?;
+        self.length.assign(
+            region,
+            offset,
+            Some(
+                length.to_le_bytes()[..N_BYTES_MEMORY_ADDRESS]
+                    .try_into()
+                    .unwrap(),
+            ),
+        )?;
+        self.max_code_size_exceeded.assign(
+            region,
+            offset,
+            F::from(MAX_CODE_SIZE),
+            F::from(length.low_u64()),
+        )?;
+
+        self.common_error_gadget
+            .assign(region, offset, block, call, step, 4)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::run_test_circuits;
+    use eth_types::{bytecode, evm_types::MAX_CODE_SIZE, Word};
+    use mock::{test_ctx::helpers::account_0_code_account_1_no_code, TestContext, MOCK_ACCOUNTS};
+
+    fn test_max_code_size_exceeded(code_size: u64) {
+        let init_code = bytecode! {
+            PUSH2(code_size)
+            PUSH1(0)
+            RETURN
+        }
+        .to_vec();
+        let code = bytecode! {
+            PUSH32(Word::from_big_endian(&init_code))
+            PUSH1(0)
+            MSTORE
+            PUSH1(init_code.len())
+            PUSH1(32 - init_code.len())
+            PUSH1(0)
+            CREATE
+            STOP
+        };
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            |mut txs, _accs| {
+                txs[0]
+                    .from(MOCK_ACCOUNTS[1])
+                    .to(MOCK_ACCOUNTS[0])
+                    .gas(Word::from(1_000_000));
+            },
+            |block, _tx| block,
+        )
+        .unwrap();
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+
+    #[test]
+    fn max_code_size_exceeded() {
+        test_max_code_size_exceeded(MAX_CODE_SIZE + 1);
+        test_max_code_size_exceeded(0xffff);
+    }
+}
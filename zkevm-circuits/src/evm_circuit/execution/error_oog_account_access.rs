@@ -0,0 +1,160 @@
+use crate::evm_circuit::{
+    execution::ExecutionGadget,
+    param::{N_BYTES_ACCOUNT_ADDRESS, N_BYTES_GAS},
+    step::ExecutionState,
+    util::{
+        common_gadget::CommonErrorGadget, constraint_builder::ConstraintBuilder, from_bytes,
+        math_gadget::LtGadget, select, CachedRegion, Cell, Word,
+    },
+    witness::{Block, Call, ExecStep, Transaction},
+};
+use crate::table::CallContextFieldTag;
+use crate::util::Expr;
+use eth_types::{evm_types::GasCost, Field, ToLittleEndian};
+use halo2_proofs::{circuit::Value, plonk::Error};
+
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorOOGAccountAccessGadget<F> {
+    address: Word<F>,
+    tx_id: Cell<F>,
+    is_warm: Cell<F>,
+    insufficient_gas: LtGadget<F, N_BYTES_GAS>,
+    common_error_gadget: CommonErrorGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorOOGAccountAccessGadget<F> {
+    const NAME: &'static str = "ErrorOutOfGasAccountAccess";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorOutOfGasAccountAccess;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+
+        let address = cb.query_word();
+        cb.stack_pop(address.expr());
+
+        let tx_id = cb.call_context(None, CallContextFieldTag::TxId);
+        let is_warm = cb.query_bool();
+        cb.account_access_list_read(
+            tx_id.expr(),
+            from_bytes::expr(&address.cells[..N_BYTES_ACCOUNT_ADDRESS]),
+            is_warm.expr(),
+        );
+
+        let gas_cost = select::expr(
+            is_warm.expr(),
+            GasCost::WARM_ACCESS.expr(),
+            GasCost::COLD_ACCOUNT_ACCESS.expr(),
+        );
+
+        // Check if the amount of gas available is less than the amount of gas
+        // required
+        let insufficient_gas = LtGadget::construct(cb, cb.curr.state.gas_left.expr(), gas_cost);
+        cb.require_equal(
+            "Gas left is less than gas required",
+            insufficient_gas.expr(),
+            1.expr(),
+        );
+
+        let common_error_gadget = CommonErrorGadget::construct(cb, opcode);
+
+        Self {
+            address,
+            tx_id,
+            is_warm,
+            insufficient_gas,
+            common_error_gadget,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        tx: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let address = block.rws[step.rw_indices[0]].stack_value();
+        self.address
+            .assign(region, offset, Some(address.to_le_bytes()))?;
+        self.tx_id
+            .assign(region, offset, Value::known(F::from(tx.id as u64)))?;
+
+        let (is_warm, _) = block.rws[step.rw_indices[2]].tx_access_list_value_pair();
+        self.is_warm
+            .assign(region, offset, Value::known(F::from(is_warm as u64)))?;
+
+        let gas_cost = if is_warm {
+            GasCost::WARM_ACCESS
+        } else {
+            GasCost::COLD_ACCOUNT_ACCESS
+        };
+        self.insufficient_gas.assign(
+            region,
+            offset,
+            F::from(step.gas_left),
+            F::from(gas_cost.as_u64()),
+        )?;
+
+        self.common_error_gadget
+            .assign(region, offset, block, call, step, 4)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::run_test_circuits;
+    use eth_types::{bytecode, bytecode::Bytecode, evm_types::OpcodeId, ToWord, Word};
+    use mock::{test_ctx::helpers::account_0_code_account_1_no_code, TestContext, MOCK_ACCOUNTS};
+
+    fn test_oog_account_access(code: Bytecode, gas: u64) {
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            |mut txs, _accs| {
+                txs[0]
+                    .from(MOCK_ACCOUNTS[1])
+                    .to(MOCK_ACCOUNTS[0])
+                    .gas(Word::from(gas));
+            },
+            |block, _tx| block,
+        )
+        .unwrap();
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+
+    #[test]
+    fn account_access_oog_cold() {
+        for opcode in [
+            OpcodeId::BALANCE,
+            OpcodeId::EXTCODESIZE,
+            OpcodeId::EXTCODEHASH,
+        ] {
+            let mut code = bytecode! {
+                PUSH20(0xff)
+            };
+            code.write_op(opcode);
+            // 21000 + PUSH20 leaves 2000 gas for the cold account access
+            test_oog_account_access(code, 23003);
+        }
+    }
+
+    #[test]
+    fn account_access_oog_warm() {
+        for opcode in [
+            OpcodeId::BALANCE,
+            OpcodeId::EXTCODESIZE,
+            OpcodeId::EXTCODEHASH,
+        ] {
+            // The transaction callee is always warm.
+            let mut code = bytecode! {
+                PUSH20(MOCK_ACCOUNTS[0].to_word())
+            };
+            code.write_op(opcode);
+            // 21000 + PUSH20 leaves 99 gas for the warm account access
+            test_oog_account_access(code, 21102);
+        }
+    }
+}
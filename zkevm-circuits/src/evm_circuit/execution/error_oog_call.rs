@@ -3,11 +3,8 @@ use crate::evm_circuit::{
     param::{N_BYTES_ACCOUNT_ADDRESS, N_BYTES_GAS, N_BYTES_MEMORY_WORD_SIZE},
     step::ExecutionState,
     util::{
-        common_gadget::RestoreContextGadget,
-        constraint_builder::{
-            ConstraintBuilder, StepStateTransition,
-            Transition::{Delta, Same},
-        },
+        common_gadget::CommonErrorGadget,
+        constraint_builder::ConstraintBuilder,
         from_bytes,
         math_gadget::{BatchedIsZeroGadget, IsEqualGadget, IsZeroGadget, LtGadget},
         memory_gadget::{MemoryAddressGadget, MemoryExpansionGadget},
@@ -18,13 +15,16 @@ use crate::evm_circuit::{
 use crate::table::{AccountFieldTag, CallContextFieldTag};
 use crate::util::Expr;
 use bus_mapping::evm::OpcodeId;
-use eth_types::{evm_types::GasCost, Field, ToLittleEndian, ToScalar};
+use eth_types::{evm_types::GasCost, Field, ToLittleEndian, ToScalar, U256};
 use halo2_proofs::{circuit::Value, plonk::Error};
 use keccak256::EMPTY_HASH_LE;
 
+/// Gadget for the out of gas errors of CALL, CALLCODE, DELEGATECALL and
+/// STATICCALL, where only CALL and CALLCODE pop a value to transfer.
 #[derive(Clone, Debug)]
-pub(crate) struct ErrorOOGCallGadget<F> {
-    opcode: Cell<F>,
+pub(crate) struct ErrorOOGCallGadget<F, const S: ExecutionState> {
+    is_call: IsZeroGadget<F>,
+    is_callcode: IsZeroGadget<F>,
     tx_id: Cell<F>,
     is_static: Cell<F>,
     gas: Word<F>,
@@ -41,24 +41,19 @@ pub(crate) struct ErrorOOGCallGadget<F> {
     is_empty_nonce_and_balance: BatchedIsZeroGadget<F, 2>,
     is_empty_code_hash: IsEqualGadget<F>,
     insufficient_gas: LtGadget<F, N_BYTES_GAS>,
-    restore_context: RestoreContextGadget<F>,
+    common_error_gadget: CommonErrorGadget<F>,
 }
 
-impl<F: Field> ExecutionGadget<F> for ErrorOOGCallGadget<F> {
+impl<F: Field, const S: ExecutionState> ExecutionGadget<F> for ErrorOOGCallGadget<F, S> {
     const NAME: &'static str = "ErrorOutOfGasCall";
 
-    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorOutOfGasCALL;
+    const EXECUTION_STATE: ExecutionState = S;
 
     fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
         let opcode = cb.query_cell();
-        cb.opcode_lookup(opcode.expr(), 1.expr());
-        // TODO: add CallCode etc. when handle ErrorOutOfGasCALLCODE in furture
-        // implementation
-        cb.require_equal(
-            "ErrorOutOfGasCall opcode is Call",
-            opcode.expr(),
-            OpcodeId::CALL.expr(),
-        );
+        let is_call = IsZeroGadget::construct(cb, opcode.expr() - OpcodeId::CALL.expr());
+        let is_callcode = IsZeroGadget::construct(cb, opcode.expr() - OpcodeId::CALLCODE.expr());
+        let has_value_arg = is_call.expr() + is_callcode.expr();
 
         let gas_word = cb.query_word();
         let callee_address_word = cb.query_word();
@@ -76,7 +71,10 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGCallGadget<F> {
         // Lookup values from stack
         cb.stack_pop(gas_word.expr());
         cb.stack_pop(callee_address_word.expr());
-        cb.stack_pop(value.expr());
+        // Only CALL and CALLCODE take a value
+        cb.condition(has_value_arg.clone(), |cb| {
+            cb.stack_pop(value.expr());
+        });
         cb.stack_pop(cd_offset.expr());
         cb.stack_pop(cd_length.expr());
         cb.stack_pop(rd_offset.expr());
@@ -96,7 +94,7 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGCallGadget<F> {
         cb.account_access_list_read(tx_id.expr(), callee_address.clone(), is_warm.expr());
 
         let value_is_zero = IsZeroGadget::construct(cb, sum::expr(&value.cells));
-        let has_value = 1.expr() - value_is_zero.expr();
+        let has_value = has_value_arg * (1.expr() - value_is_zero.expr());
         cb.account_read(
             callee_address.clone(),
             AccountFieldTag::Balance,
@@ -121,13 +119,14 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGCallGadget<F> {
             ),
         );
         let is_empty_account = is_empty_nonce_and_balance.expr() * is_empty_code_hash.expr();
-        // Sum up gas cost
+        // Sum up gas cost, where only CALL could create a new account
         let gas_cost = select::expr(
             is_warm.expr(),
             GasCost::WARM_ACCESS.expr(),
             GasCost::COLD_ACCOUNT_ACCESS.expr(),
         ) + has_value
-            * (GasCost::CALL_WITH_VALUE.expr() + is_empty_account * GasCost::NEW_ACCOUNT.expr())
+            * (GasCost::CALL_WITH_VALUE.expr()
+                + is_call.expr() * is_empty_account * GasCost::NEW_ACCOUNT.expr())
             + memory_expansion.gas_cost();
 
         // Check if the amount of gas available is less than the amount of gas
@@ -139,44 +138,11 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGCallGadget<F> {
             1.expr(),
         );
 
-        // current call must be failed.
-        cb.call_context_lookup(false.expr(), None, CallContextFieldTag::IsSuccess, 0.expr());
-
-        // Go to EndTx only when is_root
-        let is_to_end_tx = cb.next.execution_state_selector([ExecutionState::EndTx]);
-        cb.require_equal(
-            "Go to EndTx only when is_root",
-            cb.curr.state.is_root.expr(),
-            is_to_end_tx,
-        );
-
-        // When it's a root call
-        cb.condition(cb.curr.state.is_root.expr(), |cb| {
-            // Do step state transition
-            cb.require_step_state_transition(StepStateTransition {
-                call_id: Same,
-                rw_counter: Delta(15.expr() + cb.curr.state.reversible_write_counter.expr()),
-
-                ..StepStateTransition::any()
-            });
-        });
-
-        // When it's an internal call, need to restore caller's state as finishing this
-        // call. Restore caller state to next StepState
-        let restore_context = cb.condition(1.expr() - cb.curr.state.is_root.expr(), |cb| {
-            RestoreContextGadget::construct(
-                cb,
-                0.expr(),
-                0.expr(),
-                0.expr(),
-                0.expr(),
-                0.expr(),
-                0.expr(),
-            )
-        });
+        let common_error_gadget = CommonErrorGadget::construct(cb, opcode);
 
         Self {
-            opcode,
+            is_call,
+            is_callcode,
             tx_id,
             is_static,
             gas: gas_word,
@@ -193,7 +159,7 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGCallGadget<F> {
             is_empty_nonce_and_balance,
             is_empty_code_hash,
             insufficient_gas,
-            restore_context,
+            common_error_gadget,
         }
     }
 
@@ -206,29 +172,53 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGCallGadget<F> {
         call: &Call,
         step: &ExecStep,
     ) -> Result<(), Error> {
+        let randomness = region.evm_word_randomness();
         let opcode = step.opcode.unwrap();
-        self.opcode
-            .assign(region, offset, Value::known(F::from(opcode.as_u64())))?;
+        let is_call = opcode == OpcodeId::CALL;
+        let has_value_arg = is_call || opcode == OpcodeId::CALLCODE;
+        self.is_call.assign(
+            region,
+            offset,
+            F::from(opcode.as_u64()) - F::from(OpcodeId::CALL.as_u64()),
+        )?;
+        self.is_callcode.assign(
+            region,
+            offset,
+            F::from(opcode.as_u64()) - F::from(OpcodeId::CALLCODE.as_u64()),
+        )?;
 
         let [tx_id, is_static] =
             [step.rw_indices[0], step.rw_indices[1]].map(|idx| block.rws[idx].call_context_value());
         let stack_index = 2;
-        let [gas, callee_address, value, cd_offset, cd_length, rd_offset, rd_length] = [
+        let [gas, callee_address] = [
+            step.rw_indices[stack_index],
+            step.rw_indices[stack_index + 1],
+        ]
+        .map(|idx| block.rws[idx].stack_value());
+        let (value, stack_index) = if has_value_arg {
+            (
+                block.rws[step.rw_indices[stack_index + 2]].stack_value(),
+                stack_index + 3,
+            )
+        } else {
+            (U256::zero(), stack_index + 2)
+        };
+        let [cd_offset, cd_length, rd_offset, rd_length] = [
             step.rw_indices[stack_index],
             step.rw_indices[stack_index + 1],
             step.rw_indices[stack_index + 2],
             step.rw_indices[stack_index + 3],
-            step.rw_indices[stack_index + 4],
-            step.rw_indices[stack_index + 5],
-            step.rw_indices[stack_index + 6],
         ]
         .map(|idx| block.rws[idx].stack_value());
 
-        let (is_warm, is_warm_prev) = block.rws[step.rw_indices[10]].tx_access_list_value_pair();
+        // Skip the stack write of the call result
+        let rw_index = stack_index + 5;
+        let (is_warm, is_warm_prev) =
+            block.rws[step.rw_indices[rw_index]].tx_access_list_value_pair();
         let [callee_balance_pair, (callee_nonce, _), (callee_code_hash, _)] = [
-            step.rw_indices[11],
-            step.rw_indices[12],
-            step.rw_indices[13],
+            step.rw_indices[rw_index + 1],
+            step.rw_indices[rw_index + 2],
+            step.rw_indices[rw_index + 3],
         ]
         .map(|idx| block.rws[idx].account_value_pair());
 
@@ -250,12 +240,12 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGCallGadget<F> {
         self.value_is_zero
             .assign(region, offset, sum::value(&value.to_le_bytes()))?;
 
-        let cd_address =
-            self.cd_address
-                .assign(region, offset, cd_offset, cd_length, block.randomness)?;
-        let rd_address =
-            self.rd_address
-                .assign(region, offset, rd_offset, rd_length, block.randomness)?;
+        let cd_address = self
+            .cd_address
+            .assign(region, offset, cd_offset, cd_length, randomness)?;
+        let rd_address = self
+            .rd_address
+            .assign(region, offset, rd_offset, rd_length, randomness)?;
         let (_, memory_expansion_gas_cost) = self.memory_expansion.assign(
             region,
             offset,
@@ -281,7 +271,7 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGCallGadget<F> {
             offset,
             Value::known(Word::random_linear_combine(
                 callee_code_hash.to_le_bytes(),
-                block.randomness,
+                randomness,
             )),
         )?;
         let is_empty_nonce_and_balance = self.is_empty_nonce_and_balance.assign(
@@ -289,14 +279,14 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGCallGadget<F> {
             offset,
             [
                 F::from(callee_nonce.low_u64()),
-                Word::random_linear_combine(callee_balance_pair.1.to_le_bytes(), block.randomness),
+                Word::random_linear_combine(callee_balance_pair.1.to_le_bytes(), randomness),
             ],
         )?;
         let is_empty_code_hash = self.is_empty_code_hash.assign(
             region,
             offset,
-            Word::random_linear_combine(callee_code_hash.to_le_bytes(), block.randomness),
-            Word::random_linear_combine(*EMPTY_HASH_LE, block.randomness),
+            Word::random_linear_combine(callee_code_hash.to_le_bytes(), randomness),
+            Word::random_linear_combine(*EMPTY_HASH_LE, randomness),
         )?;
         let is_empty_account = is_empty_nonce_and_balance * is_empty_code_hash;
         let has_value = !value.is_zero();
@@ -306,7 +296,7 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGCallGadget<F> {
             GasCost::COLD_ACCOUNT_ACCESS.as_u64()
         } + if has_value {
             GasCost::CALL_WITH_VALUE.as_u64()
-                + if is_empty_account == F::one() {
+                + if is_call && is_empty_account == F::one() {
                     GasCost::NEW_ACCOUNT.as_u64()
                 } else {
                     0
@@ -318,9 +308,10 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGCallGadget<F> {
         self.insufficient_gas
             .assign(region, offset, F::from(step.gas_left), F::from(gas_cost))?;
 
-        self.restore_context
-            .assign(region, offset, block, call, step, 15)?;
-        Ok(())
+        // The caller's context is read after the 3 account reads and the
+        // IsSuccess read.
+        self.common_error_gadget
+            .assign(region, offset, block, call, step, rw_index + 5)
     }
 }
 
@@ -335,6 +326,13 @@ mod test {
     use mock::TestContext;
     use std::default::Default;
 
+    const TEST_CALL_OPCODES: &[OpcodeId] = &[
+        OpcodeId::CALL,
+        OpcodeId::CALLCODE,
+        OpcodeId::DELEGATECALL,
+        OpcodeId::STATICCALL,
+    ];
+
     #[derive(Clone, Copy, Debug, Default)]
     struct Stack {
         gas: u64,
@@ -345,27 +343,30 @@ mod test {
         rd_length: u64,
     }
 
-    fn caller(stack: Stack, caller_is_success: bool) -> Account {
+    fn caller(opcode: OpcodeId, stack: Stack, caller_is_success: bool) -> Account {
         let terminator = if caller_is_success {
             OpcodeId::RETURN
         } else {
             OpcodeId::REVERT
         };
 
-        // Call twice for testing both cold and warm access
-        let bytecode = bytecode! {
+        let mut bytecode = bytecode! {
             PUSH32(Word::from(stack.rd_length))
             PUSH32(Word::from(stack.rd_offset))
             PUSH32(Word::from(stack.cd_length))
             PUSH32(Word::from(stack.cd_offset))
-            PUSH32(stack.value)
+        };
+        if opcode == OpcodeId::CALL || opcode == OpcodeId::CALLCODE {
+            bytecode.push(32, stack.value);
+        }
+        bytecode.append(&bytecode! {
             PUSH32(Address::repeat_byte(0xff).to_word())
             PUSH32(Word::from(stack.gas))
-            CALL
+            .write_op(opcode)
             PUSH1(0)
             PUSH1(0)
             .write_op(terminator)
-        };
+        });
 
         Account {
             address: Address::repeat_byte(0xfe),
@@ -445,8 +446,12 @@ mod test {
             STOP
         };
         let callees = vec![callee(bytecode)];
-        for (stack, callee) in stacks.into_iter().cartesian_product(callees.into_iter()) {
-            test_oog(caller(stack, true), callee, true);
+        for ((opcode, stack), callee) in TEST_CALL_OPCODES
+            .iter()
+            .cartesian_product(stacks.into_iter())
+            .cartesian_product(callees.into_iter())
+        {
+            test_oog(caller(*opcode, stack, true), callee, true);
         }
     }
 
@@ -487,6 +492,6 @@ mod test {
             PUSH32(Word::from(0))
         };
         let callee = callee(bytecode);
-        test_oog(caller(stacks[0], false), callee, false);
+        test_oog(caller(OpcodeId::CALL, stacks[0], false), callee, false);
     }
 }
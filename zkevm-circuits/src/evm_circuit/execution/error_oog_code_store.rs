@@ -0,0 +1,146 @@
+use crate::evm_circuit::{
+    execution::ExecutionGadget,
+    param::{N_BYTES_GAS, N_BYTES_MEMORY_WORD_SIZE},
+    step::ExecutionState,
+    util::{
+        common_gadget::CommonErrorGadget,
+        constraint_builder::ConstraintBuilder,
+        math_gadget::LtGadget,
+        memory_gadget::{MemoryAddressGadget, MemoryExpansionGadget},
+        CachedRegion,
+    },
+    witness::{Block, Call, ExecStep, Transaction},
+};
+use crate::table::CallContextFieldTag;
+use crate::util::Expr;
+use eth_types::{evm_types::GasCost, Field};
+use halo2_proofs::plonk::Error;
+
+/// Gadget for the out of gas error of storing the code returned by RETURN in
+/// a contract creation, which happens after the memory expansion of RETURN is
+/// charged.
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorOOGCodeStoreGadget<F> {
+    memory_address: MemoryAddressGadget<F>,
+    memory_expansion: MemoryExpansionGadget<F, 1, N_BYTES_MEMORY_WORD_SIZE>,
+    insufficient_gas: LtGadget<F, N_BYTES_GAS>,
+    common_error_gadget: CommonErrorGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorOOGCodeStoreGadget<F> {
+    const NAME: &'static str = "ErrorOutOfGasCodeStore";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorOutOfGasCodeStore;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+
+        let offset = cb.query_cell();
+        let length = cb.query_rlc();
+        cb.stack_pop(offset.expr());
+        cb.stack_pop(length.expr());
+        let memory_address = MemoryAddressGadget::construct(cb, offset, length);
+
+        // Only the returned code of a contract creation is stored.
+        cb.call_context_lookup(false.expr(), None, CallContextFieldTag::IsCreate, 1.expr());
+
+        let memory_expansion = MemoryExpansionGadget::construct(cb, [memory_address.address()]);
+        let gas_cost = memory_expansion.gas_cost()
+            + GasCost::CODE_DEPOSIT_BYTE_COST.expr() * memory_address.length();
+
+        // Check if the amount of gas available is less than the amount of gas
+        // required
+        let insufficient_gas = LtGadget::construct(cb, cb.curr.state.gas_left.expr(), gas_cost);
+        cb.require_equal(
+            "Gas left is less than gas required",
+            insufficient_gas.expr(),
+            1.expr(),
+        );
+
+        let common_error_gadget = CommonErrorGadget::construct(cb, opcode);
+
+        Self {
+            memory_address,
+            memory_expansion,
+            insufficient_gas,
+            common_error_gadget,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _tx: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let randomness = region.evm_word_randomness();
+        let [memory_offset, length] =
+            [step.rw_indices[0], step.rw_indices[1]].map(|idx| block.rws[idx].stack_value());
+        let memory_address =
+            self.memory_address
+                .assign(region, offset, memory_offset, length, randomness)?;
+        let (_, memory_expansion_gas_cost) = self.memory_expansion.assign(
+            region,
+            offset,
+            step.memory_word_size(),
+            [memory_address],
+        )?;
+
+        let gas_cost =
+            memory_expansion_gas_cost + GasCost::CODE_DEPOSIT_BYTE_COST.as_u64() * length.as_u64();
+        self.insufficient_gas
+            .assign(region, offset, F::from(step.gas_left), F::from(gas_cost))?;
+
+        self.common_error_gadget
+            .assign(region, offset, block, call, step, 4)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::run_test_circuits;
+    use eth_types::{bytecode, Word};
+    use mock::{test_ctx::helpers::account_0_code_account_1_no_code, TestContext, MOCK_ACCOUNTS};
+
+    fn test_oog_code_store(code_size: u64) {
+        let init_code = bytecode! {
+            PUSH2(code_size)
+            PUSH1(0)
+            RETURN
+        }
+        .to_vec();
+        let code = bytecode! {
+            // Store the init code at the end of the first memory word
+            PUSH32(Word::from_big_endian(&init_code))
+            PUSH1(0)
+            MSTORE
+            PUSH1(init_code.len())
+            PUSH1(32 - init_code.len())
+            PUSH1(0)
+            CREATE
+            STOP
+        };
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            |mut txs, _accs| {
+                txs[0]
+                    .from(MOCK_ACCOUNTS[1])
+                    .to(MOCK_ACCOUNTS[0])
+                    .gas(Word::from(200_000));
+            },
+            |block, _tx| block,
+        )
+        .unwrap();
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+
+    #[test]
+    fn code_store_oog() {
+        test_oog_code_store(0x1000);
+        test_oog_code_store(0x6000);
+    }
+}
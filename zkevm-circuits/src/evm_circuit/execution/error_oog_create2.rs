@@ -0,0 +1,254 @@
+use crate::evm_circuit::{
+    execution::ExecutionGadget,
+    param::{N_BYTES_GAS, N_BYTES_MEMORY_WORD_SIZE},
+    step::ExecutionState,
+    table::HardforkParam,
+    util::{
+        common_gadget::CommonErrorGadget,
+        constraint_builder::ConstraintBuilder,
+        math_gadget::LtGadget,
+        memory_gadget::{
+            MemoryCopierGasGadget, MemoryExpandedAddressGadget, MemoryExpansionGadget,
+        },
+        not, or, CachedRegion, Cell, Word,
+    },
+    witness::{Block, Call, ExecStep, Transaction},
+};
+use crate::util::Expr;
+use eth_types::{
+    evm_types::{GasCost, MAX_INIT_CODE_SIZE},
+    Field, ToLittleEndian,
+};
+use halo2_proofs::{circuit::Value, plonk::Error};
+
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorOOGCreate2Gadget<F> {
+    value: Word<F>,
+    memory_address: MemoryExpandedAddressGadget<F>,
+    memory_expansion: MemoryExpansionGadget<F, 1, N_BYTES_MEMORY_WORD_SIZE>,
+    memory_copier_gas: MemoryCopierGasGadget<F, { GasCost::COPY_SHA3 }>,
+    is_eip3860_enabled: Cell<F>,
+    init_code_gas: MemoryCopierGasGadget<F, { GasCost::INIT_CODE_WORD }>,
+    init_code_size_gt_max: LtGadget<F, N_BYTES_MEMORY_WORD_SIZE>,
+    insufficient_gas: LtGadget<F, N_BYTES_GAS>,
+    common_error_gadget: CommonErrorGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorOOGCreate2Gadget<F> {
+    const NAME: &'static str = "ErrorOutOfGasCREATE2";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorOutOfGasCREATE2;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+
+        let value = cb.query_word();
+        let memory_address = MemoryExpandedAddressGadget::construct(cb);
+        cb.stack_pop(value.expr());
+        cb.stack_pop(memory_address.offset_rlc());
+        cb.stack_pop(memory_address.length_rlc());
+
+        let memory_expansion = MemoryExpansionGadget::construct(cb, [memory_address.address()]);
+        // The init code is hashed to derive the contract address.
+        let memory_copier_gas = MemoryCopierGasGadget::construct(
+            cb,
+            memory_address.length(),
+            memory_expansion.gas_cost(),
+        );
+        // Once EIP-3860 is enabled, the init code is charged per word, and the
+        // creation fails like out of gas when it's larger than
+        // MAX_INIT_CODE_SIZE.
+        let is_eip3860_enabled = cb.query_bool();
+        cb.hardfork_param_lookup(HardforkParam::IsEip3860Enabled, is_eip3860_enabled.expr());
+        let init_code_gas = MemoryCopierGasGadget::construct(
+            cb,
+            is_eip3860_enabled.expr() * memory_address.length(),
+            memory_copier_gas.gas_cost(),
+        );
+        let init_code_size_gt_max =
+            LtGadget::construct(cb, MAX_INIT_CODE_SIZE.expr(), memory_address.length());
+
+        // Check if the amount of gas available is less than the amount of gas
+        // required
+        let insufficient_gas = LtGadget::construct(
+            cb,
+            cb.curr.state.gas_left.expr(),
+            GasCost::CREATE.expr() + init_code_gas.gas_cost(),
+        );
+        cb.require_equal(
+            "Memory address is out of range, init code size is larger than MAX_INIT_CODE_SIZE or gas left is less than gas required",
+            or::expr([
+                not::expr(memory_address.within_range()),
+                is_eip3860_enabled.expr() * init_code_size_gt_max.expr(),
+                insufficient_gas.expr(),
+            ]),
+            1.expr(),
+        );
+
+        let common_error_gadget = CommonErrorGadget::construct(cb, opcode);
+
+        Self {
+            value,
+            memory_address,
+            memory_expansion,
+            memory_copier_gas,
+            is_eip3860_enabled,
+            init_code_gas,
+            init_code_size_gt_max,
+            insufficient_gas,
+            common_error_gadget,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _tx: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let [value, memory_offset, memory_length] =
+            [0, 1, 2].map(|i| block.rws[step.rw_indices[i]].stack_value());
+        self.value
+            .assign(region, offset, Some(value.to_le_bytes()))?;
+        let memory_address =
+            self.memory_address
+                .assign(region, offset, memory_offset, memory_length)?;
+        let (_, memory_expansion_gas_cost) = self.memory_expansion.assign(
+            region,
+            offset,
+            step.memory_word_size(),
+            [memory_address],
+        )?;
+        let init_code_size = if memory_address == 0 {
+            0
+        } else {
+            memory_length.as_u64()
+        };
+        let memory_copier_gas_cost = self.memory_copier_gas.assign(
+            region,
+            offset,
+            init_code_size,
+            memory_expansion_gas_cost,
+        )?;
+        let is_eip3860_enabled = block.circuits_params.hardfork.is_eip3860_enabled();
+        self.is_eip3860_enabled.assign(
+            region,
+            offset,
+            Value::known(F::from(is_eip3860_enabled as u64)),
+        )?;
+        let init_code_gas_cost = self.init_code_gas.assign(
+            region,
+            offset,
+            if is_eip3860_enabled {
+                init_code_size
+            } else {
+                0
+            },
+            memory_copier_gas_cost,
+        )?;
+        self.init_code_size_gt_max.assign(
+            region,
+            offset,
+            F::from(MAX_INIT_CODE_SIZE),
+            F::from(init_code_size),
+        )?;
+
+        self.insufficient_gas.assign(
+            region,
+            offset,
+            F::from(step.gas_left),
+            F::from(GasCost::CREATE.as_u64() + init_code_gas_cost),
+        )?;
+
+        self.common_error_gadget
+            .assign(region, offset, block, call, step, 4)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::{run_test_circuits, run_test_circuits_with_params};
+    use bus_mapping::circuit_input_builder::CircuitsParams;
+    use eth_types::{
+        bytecode,
+        evm_types::{Hardfork, MAX_INIT_CODE_SIZE},
+        Word,
+    };
+    use mock::{test_ctx::helpers::account_0_code_account_1_no_code, TestContext, MOCK_ACCOUNTS};
+
+    fn test_oog_create2(offset: Word, size: Word) {
+        let code = bytecode! {
+            PUSH32(Word::from(0x1234))
+            PUSH32(size)
+            PUSH32(offset)
+            PUSH32(Word::zero())
+            CREATE2
+        };
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            |mut txs, _accs| {
+                txs[0]
+                    .from(MOCK_ACCOUNTS[1])
+                    .to(MOCK_ACCOUNTS[0])
+                    .gas(Word::from(50_000));
+            },
+            |block, _tx| block,
+        )
+        .unwrap();
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+
+    #[test]
+    fn create2_oog_constant_cost() {
+        test_oog_create2(Word::zero(), Word::zero());
+        test_oog_create2(Word::zero(), Word::from(0x20));
+    }
+
+    #[test]
+    fn create2_oog_memory_address_out_of_range() {
+        test_oog_create2(Word::MAX, Word::from(0x20));
+        test_oog_create2(Word::from(0x20), Word::from(1u64 << 40));
+    }
+
+    // The geth tracer doesn't support Shanghai yet, so only the native tracer
+    // can trace the limit of the initialization code size of EIP-3860.
+    #[test]
+    #[cfg_attr(not(feature = "native-tracer"), ignore)]
+    fn create2_oog_init_code_size_exceeded() {
+        let code = bytecode! {
+            PUSH32(Word::from(0x1234))
+            PUSH32(Word::from(MAX_INIT_CODE_SIZE + 1))
+            PUSH32(Word::zero())
+            PUSH32(Word::zero())
+            CREATE2
+        };
+        let ctx = TestContext::<2, 1>::new_with_hardfork(
+            None,
+            account_0_code_account_1_no_code(code),
+            |mut txs, _accs| {
+                txs[0]
+                    .from(MOCK_ACCOUNTS[1])
+                    .to(MOCK_ACCOUNTS[0])
+                    .gas(Word::from(1_000_000));
+            },
+            |block, _tx| block,
+            Hardfork::Shanghai,
+        )
+        .unwrap();
+        assert_eq!(
+            run_test_circuits_with_params(
+                ctx,
+                None,
+                CircuitsParams {
+                    hardfork: Hardfork::Shanghai,
+                    ..Default::default()
+                }
+            ),
+            Ok(())
+        );
+    }
+}
@@ -0,0 +1,129 @@
+use crate::evm_circuit::{
+    execution::ExecutionGadget,
+    param::N_BYTES_GAS,
+    step::ExecutionState,
+    util::{
+        common_gadget::CommonErrorGadget,
+        constraint_builder::ConstraintBuilder,
+        math_gadget::{ByteSizeGadget, LtGadget},
+        CachedRegion, Word,
+    },
+    witness::{Block, Call, ExecStep, Transaction},
+};
+use crate::util::Expr;
+use eth_types::{evm_types::OpcodeId, Field, ToLittleEndian};
+use halo2_proofs::plonk::Error;
+
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorOOGExpGadget<F> {
+    base: Word<F>,
+    exponent: Word<F>,
+    exponent_byte_size: ByteSizeGadget<F>,
+    insufficient_gas: LtGadget<F, N_BYTES_GAS>,
+    common_error_gadget: CommonErrorGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorOOGExpGadget<F> {
+    const NAME: &'static str = "ErrorOutOfGasEXP";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorOutOfGasEXP;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+
+        let base = cb.query_word();
+        let exponent = cb.query_word();
+        cb.stack_pop(base.expr());
+        cb.stack_pop(exponent.expr());
+
+        let exponent_byte_size = ByteSizeGadget::construct(cb, &exponent);
+
+        // Check if the amount of gas available is less than the amount of gas
+        // required
+        let insufficient_gas = LtGadget::construct(
+            cb,
+            cb.curr.state.gas_left.expr(),
+            OpcodeId::EXP.constant_gas_cost().expr() + 50.expr() * exponent_byte_size.byte_size(),
+        );
+        cb.require_equal(
+            "Gas left is less than gas required",
+            insufficient_gas.expr(),
+            1.expr(),
+        );
+
+        let common_error_gadget = CommonErrorGadget::construct(cb, opcode);
+
+        Self {
+            base,
+            exponent,
+            exponent_byte_size,
+            insufficient_gas,
+            common_error_gadget,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _tx: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let [base, exponent] =
+            [step.rw_indices[0], step.rw_indices[1]].map(|idx| block.rws[idx].stack_value());
+        self.base.assign(region, offset, Some(base.to_le_bytes()))?;
+        self.exponent
+            .assign(region, offset, Some(exponent.to_le_bytes()))?;
+        self.exponent_byte_size.assign(region, offset, exponent)?;
+
+        let gas_cost =
+            OpcodeId::EXP.constant_gas_cost().as_u64() + 50 * ((exponent.bits() as u64 + 7) / 8);
+        self.insufficient_gas
+            .assign(region, offset, F::from(step.gas_left), F::from(gas_cost))?;
+
+        self.common_error_gadget
+            .assign(region, offset, block, call, step, 3)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::run_test_circuits;
+    use eth_types::{bytecode, Word};
+    use mock::{test_ctx::helpers::account_0_code_account_1_no_code, TestContext, MOCK_ACCOUNTS};
+
+    fn test_oog_exp(base: Word, exponent: Word, gas: u64) {
+        let code = bytecode! {
+            PUSH32(exponent)
+            PUSH32(base)
+            EXP
+        };
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            |mut txs, _accs| {
+                txs[0]
+                    .from(MOCK_ACCOUNTS[1])
+                    .to(MOCK_ACCOUNTS[0])
+                    .gas(Word::from(gas));
+            },
+            |block, _tx| block,
+        )
+        .unwrap();
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+
+    #[test]
+    fn exp_oog_constant_cost() {
+        // 21000 + 2 * PUSH32 leaves 9 gas for EXP
+        test_oog_exp(Word::from(2), Word::zero(), 21015);
+    }
+
+    #[test]
+    fn exp_oog_dynamic_cost() {
+        test_oog_exp(Word::from(2), Word::from(0x100), 21100);
+        test_oog_exp(Word::from(3), Word::MAX, 22000);
+    }
+}
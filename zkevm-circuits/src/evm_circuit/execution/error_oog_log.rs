@@ -0,0 +1,169 @@
+use crate::evm_circuit::{
+    execution::ExecutionGadget,
+    param::{N_BYTES_GAS, N_BYTES_MEMORY_WORD_SIZE},
+    step::ExecutionState,
+    util::{
+        common_gadget::CommonErrorGadget,
+        constraint_builder::ConstraintBuilder,
+        math_gadget::LtGadget,
+        memory_gadget::{MemoryExpandedAddressGadget, MemoryExpansionGadget},
+        not, or, CachedRegion,
+    },
+    witness::{Block, Call, ExecStep, Transaction},
+};
+use crate::util::Expr;
+use eth_types::{
+    evm_types::{GasCost, OpcodeId},
+    Field,
+};
+use halo2_proofs::plonk::Error;
+
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorOOGLogGadget<F> {
+    memory_address: MemoryExpandedAddressGadget<F>,
+    memory_expansion: MemoryExpansionGadget<F, 1, N_BYTES_MEMORY_WORD_SIZE>,
+    insufficient_gas: LtGadget<F, N_BYTES_GAS>,
+    common_error_gadget: CommonErrorGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorOOGLogGadget<F> {
+    const NAME: &'static str = "ErrorOutOfGasLOG";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorOutOfGasLOG;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        let topic_count = opcode.expr() - OpcodeId::LOG0.as_u8().expr();
+
+        let memory_address = MemoryExpandedAddressGadget::construct(cb);
+        cb.stack_pop(memory_address.offset_rlc());
+        cb.stack_pop(memory_address.length_rlc());
+
+        let memory_expansion = MemoryExpansionGadget::construct(cb, [memory_address.address()]);
+        let gas_cost = GasCost::LOG.expr()
+            + GasCost::LOG.expr() * topic_count
+            + 8.expr() * memory_address.length()
+            + memory_expansion.gas_cost();
+
+        // Check if the amount of gas available is less than the amount of gas
+        // required
+        let insufficient_gas = LtGadget::construct(cb, cb.curr.state.gas_left.expr(), gas_cost);
+        cb.require_equal(
+            "Memory address is out of range or gas left is less than gas required",
+            or::expr([
+                not::expr(memory_address.within_range()),
+                insufficient_gas.expr(),
+            ]),
+            1.expr(),
+        );
+
+        let common_error_gadget = CommonErrorGadget::construct(cb, opcode);
+
+        Self {
+            memory_address,
+            memory_expansion,
+            insufficient_gas,
+            common_error_gadget,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _tx: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode.unwrap();
+        let topic_count = opcode.as_u64() - OpcodeId::LOG0.as_u64();
+
+        let [memory_offset, memory_length] =
+            [step.rw_indices[0], step.rw_indices[1]].map(|idx| block.rws[idx].stack_value());
+        let memory_address =
+            self.memory_address
+                .assign(region, offset, memory_offset, memory_length)?;
+        let (_, memory_expansion_gas_cost) = self.memory_expansion.assign(
+            region,
+            offset,
+            step.memory_word_size(),
+            [memory_address],
+        )?;
+
+        let length = if memory_address == 0 {
+            0
+        } else {
+            memory_length.as_u64()
+        };
+        let gas_cost =
+            GasCost::LOG.as_u64() * (1 + topic_count) + 8 * length + memory_expansion_gas_cost;
+        self.insufficient_gas
+            .assign(region, offset, F::from(step.gas_left), F::from(gas_cost))?;
+
+        self.common_error_gadget
+            .assign(region, offset, block, call, step, 3)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::run_test_circuits;
+    use eth_types::{bytecode, bytecode::Bytecode, Word};
+    use mock::{test_ctx::helpers::account_0_code_account_1_no_code, TestContext, MOCK_ACCOUNTS};
+
+    fn test_oog_log(code: Bytecode) {
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            |mut txs, _accs| {
+                txs[0]
+                    .from(MOCK_ACCOUNTS[1])
+                    .to(MOCK_ACCOUNTS[0])
+                    .gas(Word::from(100_000));
+            },
+            |block, _tx| block,
+        )
+        .unwrap();
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+
+    #[test]
+    fn log_oog_memory_expansion() {
+        test_oog_log(bytecode! {
+            PUSH32(Word::from(0xff))
+            PUSH32(Word::from(0x10000))
+            PUSH32(Word::from(0x20))
+            LOG1
+        });
+    }
+
+    #[test]
+    fn log_oog_memory_address_out_of_range() {
+        test_oog_log(bytecode! {
+            PUSH32(Word::from(0x10))
+            PUSH32(Word::MAX)
+            LOG0
+        });
+        test_oog_log(bytecode! {
+            PUSH32(Word::from(0x20))
+            PUSH32(Word::from(0x10))
+            PUSH32(Word::from(0x10))
+            PUSH32(Word::from(1u64 << 32))
+            LOG2
+        });
+    }
+
+    #[test]
+    fn log_oog_topics() {
+        test_oog_log(bytecode! {
+            PUSH32(Word::from(0x10))
+            PUSH32(Word::from(0x20))
+            PUSH32(Word::from(0x30))
+            PUSH32(Word::from(0x40))
+            PUSH32(Word::from(0x4000))
+            PUSH32(Word::from(0x1000))
+            LOG4
+        });
+    }
+}
@@ -0,0 +1,277 @@
+use crate::evm_circuit::{
+    execution::ExecutionGadget,
+    param::{N_BYTES_ACCOUNT_ADDRESS, N_BYTES_GAS, N_BYTES_MEMORY_WORD_SIZE},
+    step::ExecutionState,
+    util::{
+        common_gadget::CommonErrorGadget,
+        constraint_builder::ConstraintBuilder,
+        from_bytes,
+        math_gadget::{IsZeroGadget, LtGadget},
+        memory_gadget::{
+            MemoryCopierGasGadget, MemoryExpandedAddressGadget, MemoryExpansionGadget,
+        },
+        not, or, select, CachedRegion, Cell, Word,
+    },
+    witness::{Block, Call, ExecStep, Transaction},
+};
+use crate::table::CallContextFieldTag;
+use crate::util::Expr;
+use eth_types::{
+    evm_types::{GasCost, OpcodeId},
+    Field, ToLittleEndian,
+};
+use halo2_proofs::{circuit::Value, plonk::Error};
+
+/// Gadget for the out of gas errors of the memory copy opcodes
+/// CALLDATACOPY, CODECOPY and RETURNDATACOPY, and of EXTCODECOPY, whose gas
+/// cost additionally depends on the access list status of the external
+/// address.
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorOOGMemoryCopyGadget<F, const S: ExecutionState> {
+    is_extcodecopy: IsZeroGadget<F>,
+    external_address: Word<F>,
+    tx_id: Cell<F>,
+    is_warm: Cell<F>,
+    src_offset: Word<F>,
+    dst_memory_address: MemoryExpandedAddressGadget<F>,
+    memory_expansion: MemoryExpansionGadget<F, 1, N_BYTES_MEMORY_WORD_SIZE>,
+    memory_copier_gas: MemoryCopierGasGadget<F, { GasCost::COPY }>,
+    insufficient_gas: LtGadget<F, N_BYTES_GAS>,
+    common_error_gadget: CommonErrorGadget<F>,
+}
+
+impl<F: Field, const S: ExecutionState> ExecutionGadget<F> for ErrorOOGMemoryCopyGadget<F, S> {
+    const NAME: &'static str = "ErrorOutOfGasMemoryCopy";
+
+    const EXECUTION_STATE: ExecutionState = S;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        let is_extcodecopy =
+            IsZeroGadget::construct(cb, opcode.expr() - OpcodeId::EXTCODECOPY.expr());
+
+        let external_address = cb.query_word();
+        let src_offset = cb.query_word();
+        let dst_memory_address = MemoryExpandedAddressGadget::construct(cb);
+
+        cb.condition(is_extcodecopy.expr(), |cb| {
+            cb.stack_pop(external_address.expr());
+        });
+        cb.stack_pop(dst_memory_address.offset_rlc());
+        cb.stack_pop(src_offset.expr());
+        cb.stack_pop(dst_memory_address.length_rlc());
+
+        let tx_id = cb.query_cell();
+        let is_warm = cb.query_bool();
+        cb.condition(is_extcodecopy.expr(), |cb| {
+            cb.call_context_lookup(false.expr(), None, CallContextFieldTag::TxId, tx_id.expr());
+            cb.account_access_list_read(
+                tx_id.expr(),
+                from_bytes::expr(&external_address.cells[..N_BYTES_ACCOUNT_ADDRESS]),
+                is_warm.expr(),
+            );
+        });
+
+        let memory_expansion = MemoryExpansionGadget::construct(cb, [dst_memory_address.address()]);
+        let memory_copier_gas = MemoryCopierGasGadget::construct(
+            cb,
+            dst_memory_address.length(),
+            memory_expansion.gas_cost(),
+        );
+        let constant_gas_cost = select::expr(
+            is_extcodecopy.expr(),
+            select::expr(
+                is_warm.expr(),
+                GasCost::WARM_ACCESS.expr(),
+                GasCost::COLD_ACCOUNT_ACCESS.expr(),
+            ),
+            GasCost::FASTEST.expr(),
+        );
+
+        // Check if the amount of gas available is less than the amount of gas
+        // required
+        let insufficient_gas = LtGadget::construct(
+            cb,
+            cb.curr.state.gas_left.expr(),
+            constant_gas_cost + memory_copier_gas.gas_cost(),
+        );
+        cb.require_equal(
+            "Memory address is out of range or gas left is less than gas required",
+            or::expr([
+                not::expr(dst_memory_address.within_range()),
+                insufficient_gas.expr(),
+            ]),
+            1.expr(),
+        );
+
+        let common_error_gadget = CommonErrorGadget::construct(cb, opcode);
+
+        Self {
+            is_extcodecopy,
+            external_address,
+            tx_id,
+            is_warm,
+            src_offset,
+            dst_memory_address,
+            memory_expansion,
+            memory_copier_gas,
+            insufficient_gas,
+            common_error_gadget,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        transaction: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode.unwrap();
+        let is_extcodecopy = opcode == OpcodeId::EXTCODECOPY;
+        self.is_extcodecopy.assign(
+            region,
+            offset,
+            F::from(opcode.as_u64()) - F::from(OpcodeId::EXTCODECOPY.as_u64()),
+        )?;
+
+        let stack_index = is_extcodecopy as usize;
+        let [dst_offset, src_offset, length] =
+            [0, 1, 2].map(|i| block.rws[step.rw_indices[stack_index + i]].stack_value());
+
+        let mut constant_gas_cost = GasCost::FASTEST.as_u64();
+        if is_extcodecopy {
+            let external_address = block.rws[step.rw_indices[0]].stack_value();
+            self.external_address
+                .assign(region, offset, Some(external_address.to_le_bytes()))?;
+            self.tx_id
+                .assign(region, offset, Value::known(F::from(transaction.id as u64)))?;
+            let (is_warm, _) = block.rws[step.rw_indices[5]].tx_access_list_value_pair();
+            self.is_warm
+                .assign(region, offset, Value::known(F::from(is_warm as u64)))?;
+            constant_gas_cost = if is_warm {
+                GasCost::WARM_ACCESS.as_u64()
+            } else {
+                GasCost::COLD_ACCOUNT_ACCESS.as_u64()
+            };
+        }
+
+        self.src_offset
+            .assign(region, offset, Some(src_offset.to_le_bytes()))?;
+        let memory_address = self
+            .dst_memory_address
+            .assign(region, offset, dst_offset, length)?;
+        let (_, memory_expansion_gas_cost) = self.memory_expansion.assign(
+            region,
+            offset,
+            step.memory_word_size(),
+            [memory_address],
+        )?;
+        let memory_copier_gas_cost = self.memory_copier_gas.assign(
+            region,
+            offset,
+            if memory_address == 0 {
+                0
+            } else {
+                length.as_u64()
+            },
+            memory_expansion_gas_cost,
+        )?;
+
+        self.insufficient_gas.assign(
+            region,
+            offset,
+            F::from(step.gas_left),
+            F::from(constant_gas_cost + memory_copier_gas_cost),
+        )?;
+
+        self.common_error_gadget.assign(
+            region,
+            offset,
+            block,
+            call,
+            step,
+            if is_extcodecopy { 7 } else { 4 },
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::run_test_circuits;
+    use eth_types::{bytecode, bytecode::Bytecode, evm_types::OpcodeId, Word};
+    use mock::{test_ctx::helpers::account_0_code_account_1_no_code, TestContext, MOCK_ACCOUNTS};
+
+    fn test_oog_memory_copy(code: Bytecode) {
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            |mut txs, _accs| {
+                txs[0]
+                    .from(MOCK_ACCOUNTS[1])
+                    .to(MOCK_ACCOUNTS[0])
+                    .gas(Word::from(50_000));
+            },
+            |block, _tx| block,
+        )
+        .unwrap();
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+
+    fn memory_copy_code(opcode: OpcodeId, dst_offset: Word, length: Word) -> Bytecode {
+        let mut code = Bytecode::default();
+        code.push(32, length);
+        code.push(32, Word::zero());
+        code.push(32, dst_offset);
+        if opcode == OpcodeId::EXTCODECOPY {
+            code.push(32, Word::from(0xff));
+        }
+        code.write_op(opcode);
+        code
+    }
+
+    #[test]
+    fn memory_copy_oog_memory_expansion() {
+        for opcode in [
+            OpcodeId::CALLDATACOPY,
+            OpcodeId::CODECOPY,
+            OpcodeId::RETURNDATACOPY,
+            OpcodeId::EXTCODECOPY,
+        ] {
+            test_oog_memory_copy(memory_copy_code(
+                opcode,
+                Word::from(0x20),
+                Word::from(0x20000),
+            ));
+        }
+    }
+
+    #[test]
+    fn memory_copy_oog_memory_address_out_of_range() {
+        for opcode in [OpcodeId::CALLDATACOPY, OpcodeId::EXTCODECOPY] {
+            test_oog_memory_copy(memory_copy_code(opcode, Word::MAX, Word::from(0x20)));
+            test_oog_memory_copy(memory_copy_code(
+                opcode,
+                Word::from(0x20),
+                Word::from(1u64 << 40),
+            ));
+        }
+    }
+
+    #[test]
+    fn extcodecopy_oog_warm_address() {
+        let mut code = bytecode! {
+            PUSH1(0xff)
+            EXTCODESIZE
+            POP
+        };
+        code.append(&memory_copy_code(
+            OpcodeId::EXTCODECOPY,
+            Word::from(0x20),
+            Word::from(0x20000),
+        ));
+        test_oog_memory_copy(code);
+    }
+}
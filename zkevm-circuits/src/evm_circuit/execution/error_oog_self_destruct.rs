@@ -0,0 +1,232 @@
+use crate::evm_circuit::{
+    execution::ExecutionGadget,
+    param::{N_BYTES_ACCOUNT_ADDRESS, N_BYTES_GAS},
+    step::ExecutionState,
+    util::{
+        common_gadget::CommonErrorGadget,
+        constraint_builder::ConstraintBuilder,
+        from_bytes,
+        math_gadget::{IsZeroGadget, LtGadget},
+        not, sum, CachedRegion, Cell, Word,
+    },
+    witness::{Block, Call, ExecStep, Rw, Transaction},
+};
+use crate::table::{AccountFieldTag, CallContextFieldTag};
+use crate::util::Expr;
+use eth_types::{evm_types::GasCost, Field, ToLittleEndian, ToScalar, U256};
+use halo2_proofs::{circuit::Value, plonk::Error};
+
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorOOGSelfDestructGadget<F> {
+    beneficiary: Word<F>,
+    tx_id: Cell<F>,
+    callee_address: Cell<F>,
+    is_warm: Cell<F>,
+    beneficiary_exists: Cell<F>,
+    beneficiary_code_hash: Cell<F>,
+    value: Word<F>,
+    value_is_zero: IsZeroGadget<F>,
+    insufficient_gas: LtGadget<F, N_BYTES_GAS>,
+    common_error_gadget: CommonErrorGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorOOGSelfDestructGadget<F> {
+    const NAME: &'static str = "ErrorOutOfGasSELFDESTRUCT";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorOutOfGasSELFDESTRUCT;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+
+        let beneficiary = cb.query_word();
+        cb.stack_pop(beneficiary.expr());
+        let beneficiary_address = from_bytes::expr(&beneficiary.cells[..N_BYTES_ACCOUNT_ADDRESS]);
+
+        let tx_id = cb.call_context(None, CallContextFieldTag::TxId);
+        let callee_address = cb.call_context(None, CallContextFieldTag::CalleeAddress);
+
+        let is_warm = cb.query_bool();
+        cb.account_access_list_read(tx_id.expr(), beneficiary_address.clone(), is_warm.expr());
+
+        let beneficiary_exists = cb.query_bool();
+        let beneficiary_code_hash = cb.query_cell();
+        cb.condition(beneficiary_exists.expr(), |cb| {
+            cb.account_read(
+                beneficiary_address.clone(),
+                AccountFieldTag::CodeHash,
+                beneficiary_code_hash.expr(),
+            );
+        });
+        cb.condition(not::expr(beneficiary_exists.expr()), |cb| {
+            cb.account_read(
+                beneficiary_address.clone(),
+                AccountFieldTag::NonExisting,
+                0.expr(),
+            );
+        });
+
+        let value = cb.query_word();
+        cb.account_read(
+            callee_address.expr(),
+            AccountFieldTag::Balance,
+            value.expr(),
+        );
+        let value_is_zero = IsZeroGadget::construct(cb, sum::expr(&value.cells));
+
+        let gas_cost = GasCost::SELFDESTRUCT.expr()
+            + not::expr(is_warm.expr()) * GasCost::COLD_ACCOUNT_ACCESS.expr()
+            // Sending a non-zero balance to an empty account creates it.
+            + not::expr(value_is_zero.expr())
+                * not::expr(beneficiary_exists.expr())
+                * GasCost::NEW_ACCOUNT.expr();
+
+        // Check if the amount of gas available is less than the amount of gas
+        // required
+        let insufficient_gas = LtGadget::construct(cb, cb.curr.state.gas_left.expr(), gas_cost);
+        cb.require_equal(
+            "Gas left is less than gas required",
+            insufficient_gas.expr(),
+            1.expr(),
+        );
+
+        let common_error_gadget = CommonErrorGadget::construct(cb, opcode);
+
+        Self {
+            beneficiary,
+            tx_id,
+            callee_address,
+            is_warm,
+            beneficiary_exists,
+            beneficiary_code_hash,
+            value,
+            value_is_zero,
+            insufficient_gas,
+            common_error_gadget,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        tx: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let randomness = region.evm_word_randomness();
+        let beneficiary = block.rws[step.rw_indices[0]].stack_value();
+        self.beneficiary
+            .assign(region, offset, Some(beneficiary.to_le_bytes()))?;
+
+        self.tx_id
+            .assign(region, offset, Value::known(F::from(tx.id as u64)))?;
+        self.callee_address.assign(
+            region,
+            offset,
+            Value::known(
+                call.callee_address
+                    .to_scalar()
+                    .expect("unexpected Address -> Scalar conversion failure"),
+            ),
+        )?;
+
+        let (is_warm, _) = block.rws[step.rw_indices[3]].tx_access_list_value_pair();
+        self.is_warm
+            .assign(region, offset, Value::known(F::from(is_warm as u64)))?;
+
+        let (beneficiary_code_hash, beneficiary_exists) = match block.rws[step.rw_indices[4]] {
+            Rw::Account {
+                field_tag: AccountFieldTag::CodeHash,
+                value,
+                ..
+            } => (value, true),
+            Rw::Account {
+                field_tag: AccountFieldTag::NonExisting,
+                ..
+            } => (U256::zero(), false),
+            _ => unreachable!(),
+        };
+        self.beneficiary_exists.assign(
+            region,
+            offset,
+            Value::known(F::from(beneficiary_exists)),
+        )?;
+        self.beneficiary_code_hash.assign(
+            region,
+            offset,
+            Value::known(Word::random_linear_combine(
+                beneficiary_code_hash.to_le_bytes(),
+                randomness,
+            )),
+        )?;
+
+        let (value, _) = block.rws[step.rw_indices[5]].account_value_pair();
+        self.value
+            .assign(region, offset, Some(value.to_le_bytes()))?;
+        self.value_is_zero
+            .assign(region, offset, sum::value(&value.to_le_bytes()))?;
+
+        let gas_cost = GasCost::SELFDESTRUCT.as_u64()
+            + if is_warm {
+                0
+            } else {
+                GasCost::COLD_ACCOUNT_ACCESS.as_u64()
+            }
+            + if !value.is_zero() && !beneficiary_exists {
+                GasCost::NEW_ACCOUNT.as_u64()
+            } else {
+                0
+            };
+        self.insufficient_gas
+            .assign(region, offset, F::from(step.gas_left), F::from(gas_cost))?;
+
+        self.common_error_gadget
+            .assign(region, offset, block, call, step, 7)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::run_test_circuits;
+    use eth_types::{bytecode, Address, ToWord, Word};
+    use mock::{test_ctx::helpers::account_0_code_account_1_no_code, TestContext, MOCK_ACCOUNTS};
+
+    fn test_oog_self_destruct(beneficiary: Address, gas: u64) {
+        let code = bytecode! {
+            PUSH20(beneficiary.to_word())
+            SELFDESTRUCT
+        };
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            |mut txs, _accs| {
+                txs[0]
+                    .from(MOCK_ACCOUNTS[1])
+                    .to(MOCK_ACCOUNTS[0])
+                    .gas(Word::from(gas));
+            },
+            |block, _tx| block,
+        )
+        .unwrap();
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+
+    #[test]
+    fn self_destruct_oog_warm() {
+        // 21000 + PUSH20 leaves 4999 gas for SELFDESTRUCT
+        test_oog_self_destruct(MOCK_ACCOUNTS[1], 26002);
+    }
+
+    #[test]
+    fn self_destruct_oog_cold() {
+        // The beneficiary is cold
+        test_oog_self_destruct(MOCK_ACCOUNTS[2], 28000);
+    }
+
+    #[test]
+    fn self_destruct_oog_new_account() {
+        // The non-zero balance would create the beneficiary
+        test_oog_self_destruct(Address::repeat_byte(0xff), 50000);
+    }
+}
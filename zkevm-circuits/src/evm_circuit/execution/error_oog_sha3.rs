@@ -0,0 +1,159 @@
+use crate::evm_circuit::{
+    execution::ExecutionGadget,
+    param::{N_BYTES_GAS, N_BYTES_MEMORY_WORD_SIZE},
+    step::ExecutionState,
+    util::{
+        common_gadget::CommonErrorGadget,
+        constraint_builder::ConstraintBuilder,
+        math_gadget::LtGadget,
+        memory_gadget::{
+            MemoryCopierGasGadget, MemoryExpandedAddressGadget, MemoryExpansionGadget,
+        },
+        not, or, CachedRegion,
+    },
+    witness::{Block, Call, ExecStep, Transaction},
+};
+use crate::util::Expr;
+use eth_types::{
+    evm_types::{GasCost, OpcodeId},
+    Field,
+};
+use halo2_proofs::plonk::Error;
+
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorOOGSha3Gadget<F> {
+    memory_address: MemoryExpandedAddressGadget<F>,
+    memory_expansion: MemoryExpansionGadget<F, 1, N_BYTES_MEMORY_WORD_SIZE>,
+    memory_copier_gas: MemoryCopierGasGadget<F, { GasCost::COPY_SHA3 }>,
+    insufficient_gas: LtGadget<F, N_BYTES_GAS>,
+    common_error_gadget: CommonErrorGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorOOGSha3Gadget<F> {
+    const NAME: &'static str = "ErrorOutOfGasSHA3";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorOutOfGasSHA3;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+
+        let memory_address = MemoryExpandedAddressGadget::construct(cb);
+        cb.stack_pop(memory_address.offset_rlc());
+        cb.stack_pop(memory_address.length_rlc());
+
+        let memory_expansion = MemoryExpansionGadget::construct(cb, [memory_address.address()]);
+        let memory_copier_gas = MemoryCopierGasGadget::construct(
+            cb,
+            memory_address.length(),
+            memory_expansion.gas_cost(),
+        );
+
+        // Check if the amount of gas available is less than the amount of gas
+        // required
+        let insufficient_gas = LtGadget::construct(
+            cb,
+            cb.curr.state.gas_left.expr(),
+            OpcodeId::SHA3.constant_gas_cost().expr() + memory_copier_gas.gas_cost(),
+        );
+        cb.require_equal(
+            "Memory address is out of range or gas left is less than gas required",
+            or::expr([
+                not::expr(memory_address.within_range()),
+                insufficient_gas.expr(),
+            ]),
+            1.expr(),
+        );
+
+        let common_error_gadget = CommonErrorGadget::construct(cb, opcode);
+
+        Self {
+            memory_address,
+            memory_expansion,
+            memory_copier_gas,
+            insufficient_gas,
+            common_error_gadget,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _tx: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let [memory_offset, memory_length] =
+            [step.rw_indices[0], step.rw_indices[1]].map(|idx| block.rws[idx].stack_value());
+        let memory_address =
+            self.memory_address
+                .assign(region, offset, memory_offset, memory_length)?;
+        let (_, memory_expansion_gas_cost) = self.memory_expansion.assign(
+            region,
+            offset,
+            step.memory_word_size(),
+            [memory_address],
+        )?;
+        let memory_copier_gas_cost = self.memory_copier_gas.assign(
+            region,
+            offset,
+            if memory_address == 0 {
+                0
+            } else {
+                memory_length.as_u64()
+            },
+            memory_expansion_gas_cost,
+        )?;
+
+        self.insufficient_gas.assign(
+            region,
+            offset,
+            F::from(step.gas_left),
+            F::from(OpcodeId::SHA3.constant_gas_cost().as_u64() + memory_copier_gas_cost),
+        )?;
+
+        self.common_error_gadget
+            .assign(region, offset, block, call, step, 3)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::run_test_circuits;
+    use eth_types::{bytecode, Word};
+    use mock::{test_ctx::helpers::account_0_code_account_1_no_code, TestContext, MOCK_ACCOUNTS};
+
+    fn test_oog_sha3(offset: Word, size: Word) {
+        let code = bytecode! {
+            PUSH32(size)
+            PUSH32(offset)
+            SHA3
+        };
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            |mut txs, _accs| {
+                txs[0]
+                    .from(MOCK_ACCOUNTS[1])
+                    .to(MOCK_ACCOUNTS[0])
+                    .gas(Word::from(50_000));
+            },
+            |block, _tx| block,
+        )
+        .unwrap();
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+
+    #[test]
+    fn sha3_oog_memory_expansion() {
+        test_oog_sha3(Word::from(0x20), Word::from(0x20000));
+        test_oog_sha3(Word::from(0x20000), Word::from(0x20));
+    }
+
+    #[test]
+    fn sha3_oog_memory_address_out_of_range() {
+        test_oog_sha3(Word::MAX, Word::from(0x20));
+        test_oog_sha3(Word::from(0x20), Word::from(1u64 << 40));
+    }
+}
@@ -0,0 +1,277 @@
+use crate::evm_circuit::{
+    execution::{
+        sload::SloadGasGadget,
+        sstore::{calc_expected_gas_cost, SstoreGasGadget},
+        ExecutionGadget,
+    },
+    param::N_BYTES_GAS,
+    step::ExecutionState,
+    util::{
+        common_gadget::CommonErrorGadget,
+        constraint_builder::ConstraintBuilder,
+        math_gadget::{IsEqualGadget, LtGadget},
+        or, select, CachedRegion, Cell, Word,
+    },
+    witness::{Block, Call, ExecStep, Transaction},
+};
+use crate::table::CallContextFieldTag;
+use crate::util::Expr;
+use eth_types::{
+    evm_types::{GasCost, OpcodeId},
+    Field, ToLittleEndian, ToScalar, U256,
+};
+use halo2_proofs::{circuit::Value, plonk::Error};
+
+/// Gadget for the out of gas errors of SLOAD and SSTORE. Besides the gas cost,
+/// SSTORE also fails when the gas left is not more than the call stipend
+/// (EIP-2200).
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorOOGSloadSstoreGadget<F, const S: ExecutionState> {
+    is_sstore: IsEqualGadget<F>,
+    tx_id: Cell<F>,
+    callee_address: Cell<F>,
+    key: Cell<F>,
+    sload_gas: SloadGasGadget<F>,
+    sstore_gas: SstoreGasGadget<F>,
+    insufficient_gas_cost: LtGadget<F, N_BYTES_GAS>,
+    insufficient_gas_sentry: LtGadget<F, N_BYTES_GAS>,
+    common_error_gadget: CommonErrorGadget<F>,
+}
+
+impl<F: Field, const S: ExecutionState> ExecutionGadget<F> for ErrorOOGSloadSstoreGadget<F, S> {
+    const NAME: &'static str = "ErrorOutOfGasSloadSstore";
+
+    const EXECUTION_STATE: ExecutionState = S;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        let is_sstore = IsEqualGadget::construct(cb, opcode.expr(), OpcodeId::SSTORE.expr());
+
+        let tx_id = cb.call_context(None, CallContextFieldTag::TxId);
+        let callee_address = cb.call_context(None, CallContextFieldTag::CalleeAddress);
+
+        let key = cb.query_cell();
+        cb.stack_pop(key.expr());
+
+        let value = cb.query_cell();
+        let value_prev = cb.query_cell();
+        let original_value = cb.query_cell();
+        cb.condition(is_sstore.expr(), |cb| {
+            cb.stack_pop(value.expr());
+            cb.account_storage_read(
+                callee_address.expr(),
+                key.expr(),
+                value_prev.expr(),
+                tx_id.expr(),
+                original_value.expr(),
+            );
+        });
+
+        let is_warm = cb.query_bool();
+        cb.account_storage_access_list_read(
+            tx_id.expr(),
+            callee_address.expr(),
+            key.expr(),
+            is_warm.expr(),
+        );
+
+        let sload_gas = SloadGasGadget::construct(cb, is_warm.expr());
+        let sstore_gas = SstoreGasGadget::construct(cb, value, value_prev, original_value, is_warm);
+        let gas_cost = select::expr(is_sstore.expr(), sstore_gas.expr(), sload_gas.expr());
+
+        // Check if the amount of gas available is less than the amount of gas
+        // required, or is not more than the stipend for SSTORE
+        let insufficient_gas_cost =
+            LtGadget::construct(cb, cb.curr.state.gas_left.expr(), gas_cost);
+        let insufficient_gas_sentry = LtGadget::construct(
+            cb,
+            cb.curr.state.gas_left.expr(),
+            GasCost::SSTORE_SENTRY.expr() + 1.expr(),
+        );
+        cb.require_equal(
+            "Gas left is less than gas required, or not more than the stipend for SSTORE",
+            or::expr([
+                insufficient_gas_cost.expr(),
+                is_sstore.expr() * insufficient_gas_sentry.expr(),
+            ]),
+            1.expr(),
+        );
+
+        let common_error_gadget = CommonErrorGadget::construct(cb, opcode);
+
+        Self {
+            is_sstore,
+            tx_id,
+            callee_address,
+            key,
+            sload_gas,
+            sstore_gas,
+            insufficient_gas_cost,
+            insufficient_gas_sentry,
+            common_error_gadget,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        tx: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let randomness = region.evm_word_randomness();
+        let opcode = step.opcode.unwrap();
+        let is_sstore = opcode == OpcodeId::SSTORE;
+        self.is_sstore.assign(
+            region,
+            offset,
+            F::from(opcode.as_u64()),
+            F::from(OpcodeId::SSTORE.as_u64()),
+        )?;
+
+        self.tx_id
+            .assign(region, offset, Value::known(F::from(tx.id as u64)))?;
+        self.callee_address.assign(
+            region,
+            offset,
+            Value::known(
+                call.callee_address
+                    .to_scalar()
+                    .expect("unexpected Address -> Scalar conversion failure"),
+            ),
+        )?;
+
+        let key = block.rws[step.rw_indices[2]].stack_value();
+        self.key.assign(
+            region,
+            offset,
+            Value::known(Word::random_linear_combine(key.to_le_bytes(), randomness)),
+        )?;
+
+        let (value, value_prev, original_value, access_list_index) = if is_sstore {
+            let value = block.rws[step.rw_indices[3]].stack_value();
+            let (_, value_prev, _, original_value) =
+                block.rws[step.rw_indices[4]].storage_value_aux();
+            (value, value_prev, original_value, 5)
+        } else {
+            (U256::zero(), U256::zero(), U256::zero(), 3)
+        };
+        let (is_warm, _) =
+            block.rws[step.rw_indices[access_list_index]].tx_access_list_value_pair();
+
+        let sstore_gas_cost = calc_expected_gas_cost(value, value_prev, original_value, is_warm);
+        self.sstore_gas.assign(
+            region,
+            offset,
+            sstore_gas_cost,
+            value,
+            value_prev,
+            original_value,
+            is_warm,
+            randomness,
+        )?;
+
+        let gas_cost = if is_sstore {
+            sstore_gas_cost
+        } else if is_warm {
+            GasCost::WARM_ACCESS.as_u64()
+        } else {
+            GasCost::COLD_SLOAD.as_u64()
+        };
+        self.insufficient_gas_cost.assign(
+            region,
+            offset,
+            F::from(step.gas_left),
+            F::from(gas_cost),
+        )?;
+        self.insufficient_gas_sentry.assign(
+            region,
+            offset,
+            F::from(step.gas_left),
+            F::from(GasCost::SSTORE_SENTRY.as_u64() + 1),
+        )?;
+
+        self.common_error_gadget.assign(
+            region,
+            offset,
+            block,
+            call,
+            step,
+            if is_sstore { 7 } else { 5 },
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::run_test_circuits;
+    use eth_types::{bytecode, bytecode::Bytecode, Word};
+    use mock::{test_ctx::helpers::account_0_code_account_1_no_code, TestContext, MOCK_ACCOUNTS};
+
+    fn test_oog_storage(code: Bytecode, gas: u64) {
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            |mut txs, _accs| {
+                txs[0]
+                    .from(MOCK_ACCOUNTS[1])
+                    .to(MOCK_ACCOUNTS[0])
+                    .gas(Word::from(gas));
+            },
+            |block, _tx| block,
+        )
+        .unwrap();
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+
+    #[test]
+    fn sload_oog_cold() {
+        let code = bytecode! {
+            PUSH32(Word::from(0x030201))
+            SLOAD
+        };
+        // 21000 + PUSH32 leaves 2000 gas for the cold SLOAD
+        test_oog_storage(code, 23003);
+    }
+
+    #[test]
+    fn sload_oog_warm() {
+        let code = bytecode! {
+            PUSH32(Word::from(0x030201))
+            SLOAD
+            PUSH32(Word::from(0x030201))
+            SLOAD
+        };
+        // 21000 + 2 * PUSH32 + cold SLOAD leaves 99 gas for the warm SLOAD
+        test_oog_storage(code, 23205);
+    }
+
+    #[test]
+    fn sstore_oog_cost() {
+        let code = bytecode! {
+            PUSH32(Word::from(0x060504))
+            PUSH32(Word::from(0x030201))
+            SSTORE
+        };
+        // 21000 + 2 * PUSH32 leaves 20000 gas for the cold SSTORE setting a
+        // new slot, which costs 22100
+        test_oog_storage(code, 41006);
+    }
+
+    #[test]
+    fn sstore_oog_sentry() {
+        let code = bytecode! {
+            PUSH32(Word::zero())
+            PUSH32(Word::from(0x030201))
+            SLOAD
+            POP
+            PUSH32(Word::from(0x030201))
+            SSTORE
+        };
+        // The warm SSTORE without changing the slot costs 100 only, but it
+        // fails with 2300 gas left.
+        test_oog_storage(code, 21000 + 3 + 2100 + 2 + 3 + 3 + 2300);
+    }
+}
@@ -0,0 +1,491 @@
+use crate::evm_circuit::{
+    execution::ExecutionGadget,
+    param::{N_BYTES_ACCOUNT_ADDRESS, N_BYTES_MEMORY_WORD_SIZE},
+    step::ExecutionState,
+    table::{FixedTableTag, Lookup},
+    util::{
+        constraint_builder::{
+            ConstraintBuilder, ReversionInfo, StepStateTransition,
+            Transition::{Delta, To},
+        },
+        from_bytes,
+        math_gadget::{IsZeroGadget, LtWordGadget},
+        memory_gadget::{MemoryAddressGadget, MemoryCopierGasGadget, MemoryExpansionGadget},
+        select, sum, CachedRegion, Cell, Word,
+    },
+    witness::{Block, Call, ExecStep, Rw, Transaction},
+};
+use crate::table::{AccountFieldTag, CallContextFieldTag};
+use crate::util::Expr;
+use bus_mapping::evm::OpcodeId;
+use eth_types::{
+    evm_types::{GasCost, GAS_STIPEND_CALL_WITH_VALUE},
+    Field, ToLittleEndian, ToScalar, U256,
+};
+use halo2_proofs::{circuit::Value, plonk::Error};
+
+/// Gadget for the *CALL* and CREATE* opcodes failing their precheck, which is
+/// either exceeding the call depth limit (`ErrorDepth`) or transferring more
+/// than the caller's balance (`ErrorInsufficientBalance`). The callee is never
+/// executed, so the caller continues with 0 pushed on the stack and the gas
+/// forwarded to the callee refunded.
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorPrecheckGadget<F, const S: ExecutionState> {
+    opcode: Cell<F>,
+    is_call: IsZeroGadget<F>,
+    is_callcode: IsZeroGadget<F>,
+    is_delegatecall: IsZeroGadget<F>,
+    is_create: IsZeroGadget<F>,
+    is_create2: IsZeroGadget<F>,
+    tx_id: Cell<F>,
+    reversion_info: ReversionInfo<F>,
+    depth: Cell<F>,
+    caller_address: Cell<F>,
+    gas: Cell<F>,
+    code_address: Word<F>,
+    value: Word<F>,
+    value_is_zero: IsZeroGadget<F>,
+    cd_address: MemoryAddressGadget<F>,
+    rd_address: MemoryAddressGadget<F>,
+    salt: Cell<F>,
+    is_warm_prev: Cell<F>,
+    callee_exists: Cell<F>,
+    callee_code_hash: Cell<F>,
+    balance: Word<F>,
+    insufficient_balance: LtWordGadget<F>,
+    memory_expansion: MemoryExpansionGadget<F, 2, N_BYTES_MEMORY_WORD_SIZE>,
+    gas_cost: MemoryCopierGasGadget<F, { GasCost::COPY_SHA3 }>,
+}
+
+impl<F: Field, const S: ExecutionState> ExecutionGadget<F> for ErrorPrecheckGadget<F, S> {
+    const NAME: &'static str = "ErrorPrecheck";
+
+    const EXECUTION_STATE: ExecutionState = S;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        cb.opcode_lookup(opcode.expr(), 1.expr());
+        cb.add_lookup(
+            "Responsible opcode lookup",
+            Lookup::Fixed {
+                tag: FixedTableTag::ResponsibleOpcode.expr(),
+                values: [
+                    cb.execution_state().as_u64().expr(),
+                    opcode.expr(),
+                    0.expr(),
+                ],
+            },
+        );
+
+        let is_call = IsZeroGadget::construct(cb, opcode.expr() - OpcodeId::CALL.expr());
+        let is_callcode = IsZeroGadget::construct(cb, opcode.expr() - OpcodeId::CALLCODE.expr());
+        let is_delegatecall =
+            IsZeroGadget::construct(cb, opcode.expr() - OpcodeId::DELEGATECALL.expr());
+        let is_create = IsZeroGadget::construct(cb, opcode.expr() - OpcodeId::CREATE.expr());
+        let is_create2 = IsZeroGadget::construct(cb, opcode.expr() - OpcodeId::CREATE2.expr());
+        let is_create_op = is_create.expr() + is_create2.expr();
+        let is_call_op = 1.expr() - is_create_op.clone();
+        let has_value_arg = is_call.expr() + is_callcode.expr() + is_create_op.clone();
+
+        let tx_id = cb.call_context(None, CallContextFieldTag::TxId);
+        let mut reversion_info = cb.reversion_info_read(None);
+        let depth = cb.call_context(None, CallContextFieldTag::Depth);
+        let caller_address = cb.call_context(None, CallContextFieldTag::CalleeAddress);
+
+        if S == ExecutionState::ErrorDepth {
+            cb.require_equal("depth == 1024", depth.expr(), 1024.expr());
+        } else {
+            cb.range_lookup(depth.expr(), 1024);
+        }
+
+        // The *CALL* opcodes pop `gas, address, [value], cd_offset, cd_length,
+        // rd_offset, rd_length`, while CREATE* pop `value, offset, length,
+        // [salt]`. The memory range of CREATE* shares the cells of the call
+        // data one.
+        let gas = cb.query_cell();
+        let code_address = cb.query_word();
+        let value = cb.query_word();
+        let cd_offset = cb.query_cell();
+        let cd_length = cb.query_rlc();
+        let rd_offset = cb.query_cell();
+        let rd_length = cb.query_rlc();
+        let salt = cb.query_cell();
+        cb.condition(is_call_op.clone(), |cb| {
+            cb.stack_pop(gas.expr());
+            cb.stack_pop(code_address.expr());
+        });
+        cb.condition(has_value_arg.clone(), |cb| cb.stack_pop(value.expr()));
+        cb.stack_pop(cd_offset.expr());
+        cb.stack_pop(cd_length.expr());
+        cb.condition(is_call_op.clone(), |cb| {
+            cb.stack_pop(rd_offset.expr());
+            cb.stack_pop(rd_length.expr());
+        });
+        cb.condition(is_create2.expr(), |cb| cb.stack_pop(salt.expr()));
+        cb.stack_push(0.expr());
+
+        cb.condition(1.expr() - has_value_arg, |cb| {
+            cb.require_zero("value is 0 when not popped", sum::expr(&value.cells));
+        });
+        let value_is_zero = IsZeroGadget::construct(cb, sum::expr(&value.cells));
+        let cd_address = MemoryAddressGadget::construct(cb, cd_offset, cd_length);
+        let rd_address = MemoryAddressGadget::construct(cb, rd_offset, rd_length);
+        cb.condition(is_create_op.clone(), |cb| {
+            cb.require_zero("CREATE* has no return data range", rd_address.length());
+        });
+
+        // The callee is still added into the access list, which isn't the case
+        // for the new contract address of CREATE*.
+        let code_address_expr = from_bytes::expr(&code_address.cells[..N_BYTES_ACCOUNT_ADDRESS]);
+        let is_warm_prev = cb.query_bool();
+        let callee_exists = cb.query_bool();
+        let callee_code_hash = cb.query_cell();
+        cb.condition(is_call_op.clone(), |cb| {
+            cb.account_access_list_write(
+                tx_id.expr(),
+                code_address_expr.clone(),
+                1.expr(),
+                is_warm_prev.expr(),
+                Some(&mut reversion_info),
+            );
+        });
+        cb.condition(is_call_op.clone() * callee_exists.expr(), |cb| {
+            cb.account_read(
+                code_address_expr.clone(),
+                AccountFieldTag::CodeHash,
+                callee_code_hash.expr(),
+            );
+        });
+        cb.condition(
+            is_call_op.clone() * (1.expr() - callee_exists.expr()),
+            |cb| {
+                cb.account_read(code_address_expr, AccountFieldTag::NonExisting, 0.expr());
+            },
+        );
+
+        let balance = cb.query_word();
+        let insufficient_balance = LtWordGadget::construct(cb, &balance, &value);
+        if S == ExecutionState::ErrorInsufficientBalance {
+            cb.account_read(
+                caller_address.expr(),
+                AccountFieldTag::Balance,
+                balance.expr(),
+            );
+            cb.require_equal(
+                "caller balance < value",
+                insufficient_balance.expr(),
+                1.expr(),
+            );
+        }
+
+        // The return data of the last callee is cleared.
+        for field_tag in [
+            CallContextFieldTag::LastCalleeId,
+            CallContextFieldTag::LastCalleeReturnDataOffset,
+            CallContextFieldTag::LastCalleeReturnDataLength,
+        ] {
+            cb.call_context_lookup(true.expr(), None, field_tag, 0.expr());
+        }
+
+        let memory_expansion =
+            MemoryExpansionGadget::construct(cb, [cd_address.address(), rd_address.address()]);
+
+        // The *CALL* opcodes charge the same gas as a call to an account
+        // without code, and the gas stipend is returned along with the
+        // forwarded gas. CREATE* only charge the constant gas, memory expansion
+        // and the hashing of the initialization code for CREATE2.
+        let has_value = (is_call.expr() + is_callcode.expr()) * (1.expr() - value_is_zero.expr());
+        let call_gas_cost = select::expr(
+            is_warm_prev.expr(),
+            GasCost::WARM_ACCESS.expr(),
+            GasCost::COLD_ACCOUNT_ACCESS.expr(),
+        ) + has_value.clone()
+            * (GasCost::CALL_WITH_VALUE.expr()
+                + is_call.expr() * (1.expr() - callee_exists.expr()) * GasCost::NEW_ACCOUNT.expr())
+            + memory_expansion.gas_cost();
+        let gas_cost = MemoryCopierGasGadget::construct(
+            cb,
+            is_create2.expr() * cd_address.length(),
+            memory_expansion.gas_cost(),
+        );
+        let create_gas_cost = OpcodeId::CREATE.constant_gas_cost().expr() + gas_cost.gas_cost();
+
+        cb.require_step_state_transition(StepStateTransition {
+            rw_counter: Delta(cb.rw_counter_offset()),
+            program_counter: Delta(1.expr()),
+            stack_pointer: Delta(cb.stack_pointer_offset()),
+            gas_left: Delta(select::expr(
+                is_create_op,
+                -create_gas_cost,
+                has_value * GAS_STIPEND_CALL_WITH_VALUE.expr() - call_gas_cost,
+            )),
+            memory_word_size: To(memory_expansion.next_memory_word_size()),
+            reversible_write_counter: Delta(is_call_op),
+            ..StepStateTransition::default()
+        });
+
+        Self {
+            opcode,
+            is_call,
+            is_callcode,
+            is_delegatecall,
+            is_create,
+            is_create2,
+            tx_id,
+            reversion_info,
+            depth,
+            caller_address,
+            gas,
+            code_address,
+            value,
+            value_is_zero,
+            cd_address,
+            rd_address,
+            salt,
+            is_warm_prev,
+            callee_exists,
+            callee_code_hash,
+            balance,
+            insufficient_balance,
+            memory_expansion,
+            gas_cost,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let randomness = region.evm_word_randomness();
+        let opcode = step.opcode.unwrap();
+        let is_create_op = opcode.is_create();
+        let is_create2 = opcode == OpcodeId::CREATE2;
+        let has_value_arg = matches!(
+            opcode,
+            OpcodeId::CALL | OpcodeId::CALLCODE | OpcodeId::CREATE | OpcodeId::CREATE2
+        );
+        self.opcode
+            .assign(region, offset, Value::known(F::from(opcode.as_u64())))?;
+        for (gadget, target) in [
+            (&self.is_call, OpcodeId::CALL),
+            (&self.is_callcode, OpcodeId::CALLCODE),
+            (&self.is_delegatecall, OpcodeId::DELEGATECALL),
+            (&self.is_create, OpcodeId::CREATE),
+            (&self.is_create2, OpcodeId::CREATE2),
+        ] {
+            gadget.assign(
+                region,
+                offset,
+                F::from(opcode.as_u64()) - F::from(target.as_u64()),
+            )?;
+        }
+
+        let [tx_id, depth, caller_address] =
+            [0, 3, 4].map(|i| block.rws[step.rw_indices[i]].call_context_value());
+        self.tx_id
+            .assign(region, offset, Value::known(F::from(tx_id.low_u64())))?;
+        self.reversion_info.assign(
+            region,
+            offset,
+            call.rw_counter_end_of_reversion,
+            call.is_persistent,
+        )?;
+        self.depth
+            .assign(region, offset, Value::known(F::from(depth.low_u64())))?;
+        self.caller_address.assign(
+            region,
+            offset,
+            Value::known(
+                caller_address
+                    .to_scalar()
+                    .expect("unexpected Address -> Scalar conversion failure"),
+            ),
+        )?;
+
+        // Collect the stack pops in the order of the constraints.
+        let mut rw_index = 5;
+        let mut next_stack_value = || {
+            rw_index += 1;
+            block.rws[step.rw_indices[rw_index - 1]].stack_value()
+        };
+        let [gas, code_address] = if is_create_op {
+            [U256::zero(); 2]
+        } else {
+            [next_stack_value(), next_stack_value()]
+        };
+        let value = if has_value_arg {
+            next_stack_value()
+        } else {
+            U256::zero()
+        };
+        let [cd_offset, cd_length] = [next_stack_value(), next_stack_value()];
+        let [rd_offset, rd_length] = if is_create_op {
+            [U256::zero(); 2]
+        } else {
+            [next_stack_value(), next_stack_value()]
+        };
+        let salt = if is_create2 {
+            next_stack_value()
+        } else {
+            U256::zero()
+        };
+        // Skip the stack push of 0.
+        rw_index += 1;
+
+        for (cell, value) in [(&self.gas, gas), (&self.salt, salt)] {
+            cell.assign(
+                region,
+                offset,
+                Value::known(Word::random_linear_combine(value.to_le_bytes(), randomness)),
+            )?;
+        }
+        self.code_address
+            .assign(region, offset, Some(code_address.to_le_bytes()))?;
+        self.value
+            .assign(region, offset, Some(value.to_le_bytes()))?;
+        self.value_is_zero
+            .assign(region, offset, sum::value(&value.to_le_bytes()))?;
+        let cd_address = self
+            .cd_address
+            .assign(region, offset, cd_offset, cd_length, randomness)?;
+        let rd_address = self
+            .rd_address
+            .assign(region, offset, rd_offset, rd_length, randomness)?;
+
+        let (is_warm_prev, callee_exists, callee_code_hash) = if is_create_op {
+            (false, false, U256::zero())
+        } else {
+            let (_, is_warm_prev) =
+                block.rws[step.rw_indices[rw_index]].tx_access_list_value_pair();
+            let (callee_exists, callee_code_hash) = match block.rws[step.rw_indices[rw_index + 1]] {
+                Rw::Account {
+                    field_tag: AccountFieldTag::CodeHash,
+                    value,
+                    ..
+                } => (true, value),
+                Rw::Account {
+                    field_tag: AccountFieldTag::NonExisting,
+                    ..
+                } => (false, U256::zero()),
+                _ => unreachable!(),
+            };
+            rw_index += 2;
+            (is_warm_prev, callee_exists, callee_code_hash)
+        };
+        self.is_warm_prev
+            .assign(region, offset, Value::known(F::from(is_warm_prev as u64)))?;
+        self.callee_exists
+            .assign(region, offset, Value::known(F::from(callee_exists as u64)))?;
+        self.callee_code_hash.assign(
+            region,
+            offset,
+            Value::known(Word::random_linear_combine(
+                callee_code_hash.to_le_bytes(),
+                randomness,
+            )),
+        )?;
+
+        let balance = if S == ExecutionState::ErrorInsufficientBalance {
+            block.rws[step.rw_indices[rw_index]].account_value_pair().0
+        } else {
+            U256::zero()
+        };
+        self.balance
+            .assign(region, offset, Some(balance.to_le_bytes()))?;
+        self.insufficient_balance
+            .assign(region, offset, balance, value)?;
+
+        let (_, memory_expansion_gas_cost) = self.memory_expansion.assign(
+            region,
+            offset,
+            step.memory_word_size(),
+            [cd_address, rd_address],
+        )?;
+        self.gas_cost.assign(
+            region,
+            offset,
+            if is_create2 { cd_length.as_u64() } else { 0 },
+            memory_expansion_gas_cost,
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::run_test_circuits;
+    use eth_types::{address, bytecode, bytecode::Bytecode, evm_types::OpcodeId, ToWord, Word};
+    use mock::TestContext;
+
+    fn test_insufficient_balance(code: Bytecode) {
+        let ctx = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x000000000000000000000000000000000000cafe"))
+                    .balance(Word::from(10u64.pow(19)));
+                accs[1]
+                    .address(address!("0x00000000000000000000000000000000000000fe"))
+                    .balance(Word::from(10u64.pow(5)))
+                    .code(code);
+                accs[2]
+                    .address(address!("0x00000000000000000000000000000000000000ff"))
+                    .code(bytecode! { STOP });
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[0].address)
+                    .to(accs[1].address)
+                    .gas(Word::from(100_000));
+            },
+            |block, _tx| block,
+        )
+        .unwrap();
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+
+    #[test]
+    fn insufficient_balance_call() {
+        for opcode in [OpcodeId::CALL, OpcodeId::CALLCODE] {
+            let mut code = bytecode! {
+                PUSH1(0x20) // retLength
+                PUSH1(0x00) // retOffset
+                PUSH1(0x20) // argsLength
+                PUSH1(0x00) // argsOffset
+                PUSH3(0x10_00_00) // value
+                PUSH20(address!("0x00000000000000000000000000000000000000ff").to_word())
+                PUSH2(0x1000) // gas
+            };
+            code.write_op(opcode);
+            code.append(&bytecode! {
+                RETURNDATASIZE
+                STOP
+            });
+            test_insufficient_balance(code);
+        }
+    }
+
+    #[test]
+    fn insufficient_balance_create() {
+        for opcode in [OpcodeId::CREATE, OpcodeId::CREATE2] {
+            let mut code = Bytecode::default();
+            if opcode == OpcodeId::CREATE2 {
+                code.append(&bytecode! { PUSH1(0x01) }); // salt
+            }
+            code.append(&bytecode! {
+                PUSH1(0x20) // length
+                PUSH1(0x00) // offset
+                PUSH3(0x10_00_00) // value
+            });
+            code.write_op(opcode);
+            code.append(&bytecode! { STOP });
+            test_insufficient_balance(code);
+        }
+    }
+}
@@ -0,0 +1,200 @@
+use crate::evm_circuit::{
+    execution::ExecutionGadget,
+    param::N_BYTES_U64,
+    step::ExecutionState,
+    util::{
+        common_gadget::CommonErrorGadget,
+        constraint_builder::ConstraintBuilder,
+        from_bytes,
+        math_gadget::{IsZeroGadget, LtGadget},
+        not, or, sum, CachedRegion, Cell, Word,
+    },
+    witness::{Block, Call, ExecStep, Transaction},
+};
+use crate::table::CallContextFieldTag;
+use crate::util::Expr;
+use eth_types::{Field, ToLittleEndian};
+use halo2_proofs::{circuit::Value, plonk::Error};
+
+/// Gadget for RETURNDATACOPY reading beyond the return data of the last
+/// callee, which happens when `data_offset + size` overflows u64 or exceeds
+/// the return data length.
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorReturnDataOutOfBoundGadget<F> {
+    memory_offset: Cell<F>,
+    data_offset: Word<F>,
+    size: Word<F>,
+    return_data_length: Cell<F>,
+    data_offset_is_u64: IsZeroGadget<F>,
+    size_is_u64: IsZeroGadget<F>,
+    is_end_over_length: LtGadget<F, { N_BYTES_U64 + 1 }>,
+    common_error_gadget: CommonErrorGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorReturnDataOutOfBoundGadget<F> {
+    const NAME: &'static str = "ErrorReturnDataOutOfBound";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorReturnDataOutOfBound;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+
+        let memory_offset = cb.query_cell();
+        let data_offset = cb.query_word();
+        let size = cb.query_word();
+        cb.stack_pop(memory_offset.expr());
+        cb.stack_pop(data_offset.expr());
+        cb.stack_pop(size.expr());
+
+        let return_data_length =
+            cb.call_context(None, CallContextFieldTag::LastCalleeReturnDataLength);
+
+        let data_offset_is_u64 =
+            IsZeroGadget::construct(cb, sum::expr(&data_offset.cells[N_BYTES_U64..]));
+        let size_is_u64 = IsZeroGadget::construct(cb, sum::expr(&size.cells[N_BYTES_U64..]));
+        let end = from_bytes::expr(&data_offset.cells[..N_BYTES_U64])
+            + from_bytes::expr(&size.cells[..N_BYTES_U64]);
+        let is_end_over_length = LtGadget::construct(cb, return_data_length.expr(), end);
+        cb.require_equal(
+            "data_offset or size overflows u64, or data_offset + size > return_data_length",
+            or::expr([
+                not::expr(data_offset_is_u64.expr()),
+                not::expr(size_is_u64.expr()),
+                is_end_over_length.expr(),
+            ]),
+            1.expr(),
+        );
+
+        let common_error_gadget = CommonErrorGadget::construct(cb, opcode);
+
+        Self {
+            memory_offset,
+            data_offset,
+            size,
+            return_data_length,
+            data_offset_is_u64,
+            size_is_u64,
+            is_end_over_length,
+            common_error_gadget,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _tx: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let randomness = region.evm_word_randomness();
+        let [memory_offset, data_offset, size] =
+            [0, 1, 2].map(|i| block.rws[step.rw_indices[i]].stack_value());
+        let return_data_length = block.rws[step.rw_indices[3]].call_context_value();
+
+        self.memory_offset.assign(
+            region,
+            offset,
+            Value::known(Word::random_linear_combine(
+                memory_offset.to_le_bytes(),
+                randomness,
+            )),
+        )?;
+        self.data_offset
+            .assign(region, offset, Some(data_offset.to_le_bytes()))?;
+        self.size.assign(region, offset, Some(size.to_le_bytes()))?;
+        self.return_data_length.assign(
+            region,
+            offset,
+            Value::known(F::from(return_data_length.low_u64())),
+        )?;
+
+        self.data_offset_is_u64.assign(
+            region,
+            offset,
+            sum::value(&data_offset.to_le_bytes()[N_BYTES_U64..]),
+        )?;
+        self.size_is_u64.assign(
+            region,
+            offset,
+            sum::value(&size.to_le_bytes()[N_BYTES_U64..]),
+        )?;
+        self.is_end_over_length.assign(
+            region,
+            offset,
+            F::from(return_data_length.low_u64()),
+            F::from(data_offset.low_u64()) + F::from(size.low_u64()),
+        )?;
+
+        self.common_error_gadget
+            .assign(region, offset, block, call, step, 5)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::run_test_circuits;
+    use eth_types::{address, bytecode, Word};
+    use mock::TestContext;
+
+    fn test_return_data_out_of_bound(data_offset: Word, size: Word) {
+        // The callee returns 32 bytes, which are then copied out of bound.
+        let callee_code = bytecode! {
+            PUSH1(0x20)
+            PUSH1(0x00)
+            RETURN
+        };
+        let caller_code = bytecode! {
+            PUSH1(0x00) // retLength
+            PUSH1(0x00) // retOffset
+            PUSH1(0x00) // argsLength
+            PUSH1(0x00) // argsOffset
+            PUSH1(0x00) // value
+            PUSH20(0xff) // address
+            PUSH2(0x8000) // gas
+            CALL
+            PUSH32(size)
+            PUSH32(data_offset)
+            PUSH1(0x00) // memoryOffset
+            RETURNDATACOPY
+            STOP
+        };
+        let ctx = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x000000000000000000000000000000000000cafe"))
+                    .balance(Word::from(10u64.pow(19)));
+                accs[1]
+                    .address(address!("0x00000000000000000000000000000000000000fe"))
+                    .code(caller_code);
+                accs[2]
+                    .address(address!("0x00000000000000000000000000000000000000ff"))
+                    .code(callee_code);
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[0].address)
+                    .to(accs[1].address)
+                    .gas(Word::from(100_000));
+            },
+            |block, _tx| block,
+        )
+        .unwrap();
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+
+    #[test]
+    fn return_data_out_of_bound_end() {
+        test_return_data_out_of_bound(Word::from(0x10), Word::from(0x11));
+        test_return_data_out_of_bound(Word::from(0x21), Word::zero());
+    }
+
+    #[test]
+    fn return_data_out_of_bound_overflow() {
+        test_return_data_out_of_bound(Word::MAX, Word::zero());
+        test_return_data_out_of_bound(Word::zero(), Word::from(u64::MAX) + 1);
+        test_return_data_out_of_bound(Word::from(u64::MAX), Word::from(u64::MAX));
+    }
+}
@@ -0,0 +1,185 @@
+use crate::evm_circuit::{
+    execution::ExecutionGadget,
+    step::ExecutionState,
+    util::{
+        common_gadget::CommonErrorGadget, constraint_builder::ConstraintBuilder,
+        math_gadget::IsZeroGadget, sum, CachedRegion, Cell, Word,
+    },
+    witness::{Block, Call, ExecStep, Transaction},
+};
+use crate::table::CallContextFieldTag;
+use crate::util::Expr;
+use eth_types::{evm_types::OpcodeId, Field, ToLittleEndian, U256};
+use halo2_proofs::{circuit::Value, plonk::Error};
+
+/// Gadget for the state modifying opcodes in a static call, which are SSTORE,
+/// CREATE, CREATE2, SELFDESTRUCT, LOG0-4 and CALL with a non-zero value.
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorWriteProtectionGadget<F> {
+    is_call: IsZeroGadget<F>,
+    gas: Cell<F>,
+    code_address: Cell<F>,
+    value: Word<F>,
+    value_is_zero: IsZeroGadget<F>,
+    common_error_gadget: CommonErrorGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorWriteProtectionGadget<F> {
+    const NAME: &'static str = "ErrorWriteProtection";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorWriteProtection;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        let is_call = IsZeroGadget::construct(cb, opcode.expr() - OpcodeId::CALL.expr());
+
+        let gas = cb.query_cell();
+        let code_address = cb.query_cell();
+        let value = cb.query_word();
+        let value_is_zero = IsZeroGadget::construct(cb, sum::expr(&value.cells));
+
+        // CALL is only write protected when it transfers a value.
+        cb.condition(is_call.expr(), |cb| {
+            cb.stack_pop(gas.expr());
+            cb.stack_pop(code_address.expr());
+            cb.stack_pop(value.expr());
+            cb.require_zero("CALL value is not zero", value_is_zero.expr());
+        });
+
+        cb.call_context_lookup(false.expr(), None, CallContextFieldTag::IsStatic, 1.expr());
+
+        let common_error_gadget = CommonErrorGadget::construct(cb, opcode);
+
+        Self {
+            is_call,
+            gas,
+            code_address,
+            value,
+            value_is_zero,
+            common_error_gadget,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _tx: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let randomness = region.evm_word_randomness();
+        let opcode = step.opcode.unwrap();
+        let is_call = opcode == OpcodeId::CALL;
+        self.is_call.assign(
+            region,
+            offset,
+            F::from(opcode.as_u64()) - F::from(OpcodeId::CALL.as_u64()),
+        )?;
+
+        let [gas, code_address, value] = if is_call {
+            [0, 1, 2].map(|i| block.rws[step.rw_indices[i]].stack_value())
+        } else {
+            [U256::zero(); 3]
+        };
+        for (cell, value) in [(&self.gas, gas), (&self.code_address, code_address)] {
+            cell.assign(
+                region,
+                offset,
+                Value::known(Word::random_linear_combine(value.to_le_bytes(), randomness)),
+            )?;
+        }
+        self.value
+            .assign(region, offset, Some(value.to_le_bytes()))?;
+        self.value_is_zero
+            .assign(region, offset, sum::value(&value.to_le_bytes()))?;
+
+        self.common_error_gadget.assign(
+            region,
+            offset,
+            block,
+            call,
+            step,
+            if is_call { 5 } else { 2 },
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::run_test_circuits;
+    use eth_types::{address, bytecode, bytecode::Bytecode, evm_types::OpcodeId, ToWord, Word};
+    use mock::TestContext;
+
+    fn callee_code(opcode: OpcodeId) -> Bytecode {
+        let mut code = bytecode! {
+            PUSH1(0x00) // retLength
+            PUSH1(0x00) // retOffset
+            PUSH1(0x00) // argsLength
+            PUSH1(0x00) // argsOffset
+            PUSH1(0x01) // value
+            PUSH1(0x00) // address
+            PUSH2(0x1000) // gas
+        };
+        code.write_op(opcode);
+        code
+    }
+
+    fn test_write_protection(opcode: OpcodeId) {
+        // The caller STATICCALLs into the callee, which executes the state
+        // modifying opcode with enough stack items.
+        let caller_code = bytecode! {
+            PUSH1(0x00) // retLength
+            PUSH1(0x00) // retOffset
+            PUSH1(0x00) // argsLength
+            PUSH1(0x00) // argsOffset
+            PUSH20(address!("0x00000000000000000000000000000000000000ff").to_word())
+            PUSH2(0x8000) // gas
+            STATICCALL
+            STOP
+        };
+        let ctx = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x000000000000000000000000000000000000cafe"))
+                    .balance(Word::from(10u64.pow(19)));
+                accs[1]
+                    .address(address!("0x00000000000000000000000000000000000000fe"))
+                    .code(caller_code);
+                accs[2]
+                    .address(address!("0x00000000000000000000000000000000000000ff"))
+                    .code(callee_code(opcode));
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[0].address)
+                    .to(accs[1].address)
+                    .gas(Word::from(100_000));
+            },
+            |block, _tx| block,
+        )
+        .unwrap();
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+
+    #[test]
+    fn write_protection_call_with_value() {
+        test_write_protection(OpcodeId::CALL);
+    }
+
+    #[test]
+    fn write_protection_state_modifying_opcodes() {
+        for opcode in [
+            OpcodeId::SSTORE,
+            OpcodeId::CREATE,
+            OpcodeId::CREATE2,
+            OpcodeId::SELFDESTRUCT,
+            OpcodeId::LOG0,
+            OpcodeId::LOG4,
+        ] {
+            test_write_protection(opcode);
+        }
+    }
+}
@@ -0,0 +1,370 @@
+use bus_mapping::circuit_input_builder::CopyDataType;
+use eth_types::{evm_types::GasCost, Field, ToAddress, ToLittleEndian, ToScalar};
+use halo2_proofs::{circuit::Value, plonk::Error};
+
+use crate::{
+    evm_circuit::{
+        param::{N_BYTES_ACCOUNT_ADDRESS, N_BYTES_MEMORY_ADDRESS, N_BYTES_MEMORY_WORD_SIZE},
+        step::ExecutionState,
+        util::{
+            common_gadget::SameContextGadget,
+            constraint_builder::{
+                ConstraintBuilder, ReversionInfo, StepStateTransition, Transition,
+            },
+            from_bytes,
+            memory_gadget::{MemoryAddressGadget, MemoryCopierGasGadget, MemoryExpansionGadget},
+            not, select, CachedRegion, Cell, MemoryAddress, RandomLinearCombination,
+        },
+        witness::{Block, Call, ExecStep, Rw, Transaction},
+    },
+    table::{AccountFieldTag, CallContextFieldTag},
+    util::Expr,
+};
+
+use super::ExecutionGadget;
+
+#[derive(Clone, Debug)]
+pub(crate) struct ExtcodecopyGadget<F> {
+    same_context: SameContextGadget<F>,
+    /// Address of the account whose code is copied.
+    external_address: RandomLinearCombination<F, N_BYTES_ACCOUNT_ADDRESS>,
+    /// Holds the memory address for the offset in code from where we read.
+    code_offset: MemoryAddress<F>,
+    tx_id: Cell<F>,
+    reversion_info: ReversionInfo<F>,
+    is_warm: Cell<F>,
+    exists: Cell<F>,
+    /// Code hash of the external account, which is 0 if it doesn't exist.
+    code_hash: Cell<F>,
+    /// Holds the size of the external account's bytecode.
+    code_size: Cell<F>,
+    /// The code of the external account is copied to memory. To verify this
+    /// copy operation we need the MemoryAddressGadget.
+    dst_memory_addr: MemoryAddressGadget<F>,
+    memory_expansion: MemoryExpansionGadget<F, 1, N_BYTES_MEMORY_WORD_SIZE>,
+    /// Opcode EXTCODECOPY needs to copy code bytes into memory. We account for
+    /// the copying costs using the memory copier gas gadget.
+    memory_copier_gas: MemoryCopierGasGadget<F, { GasCost::COPY }>,
+    /// RW inverse counter from the copy table at the start of related copy
+    /// steps.
+    copy_rwc_inc: Cell<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ExtcodecopyGadget<F> {
+    const NAME: &'static str = "EXTCODECOPY";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::EXTCODECOPY;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+
+        // Query elements to be popped from the stack.
+        let external_address = cb.query_rlc();
+        let dst_memory_offset = cb.query_cell();
+        let code_offset = cb.query_rlc();
+        let size = cb.query_rlc();
+
+        // Pop items from stack.
+        cb.stack_pop(external_address.expr());
+        cb.stack_pop(dst_memory_offset.expr());
+        cb.stack_pop(code_offset.expr());
+        cb.stack_pop(size.expr());
+
+        let tx_id = cb.call_context(None, CallContextFieldTag::TxId);
+        let mut reversion_info = cb.reversion_info_read(None);
+        let is_warm = cb.query_bool();
+        cb.account_access_list_write(
+            tx_id.expr(),
+            from_bytes::expr(&external_address.cells),
+            1.expr(),
+            is_warm.expr(),
+            Some(&mut reversion_info),
+        );
+
+        let exists = cb.query_bool();
+        let code_hash = cb.query_cell();
+        let code_size = cb.condition(exists.expr(), |cb| {
+            cb.account_read(
+                from_bytes::expr(&external_address.cells),
+                AccountFieldTag::CodeHash,
+                code_hash.expr(),
+            );
+            cb.bytecode_length(code_hash.expr())
+        });
+        cb.condition(not::expr(exists.expr()), |cb| {
+            cb.account_read(
+                from_bytes::expr(&external_address.cells),
+                AccountFieldTag::NonExisting,
+                0.expr(),
+            );
+            cb.require_zero("code hash is 0 for non-existing account", code_hash.expr());
+        });
+
+        // Construct memory address in the destination (memory) to which we copy code.
+        let dst_memory_addr = MemoryAddressGadget::construct(cb, dst_memory_offset, size);
+
+        // Calculate the next memory size and the gas cost for this memory
+        // access. This also accounts for the dynamic gas required to copy bytes to
+        // memory.
+        let memory_expansion = MemoryExpansionGadget::construct(cb, [dst_memory_addr.address()]);
+        let memory_copier_gas = MemoryCopierGasGadget::construct(
+            cb,
+            dst_memory_addr.length(),
+            memory_expansion.gas_cost(),
+        );
+
+        let copy_rwc_inc = cb.query_cell();
+        cb.condition(dst_memory_addr.has_length(), |cb| {
+            cb.copy_table_lookup(
+                code_hash.expr(),
+                CopyDataType::Bytecode.expr(),
+                cb.curr.state.call_id.expr(),
+                CopyDataType::Memory.expr(),
+                from_bytes::expr(&code_offset.cells),
+                select::expr(exists.expr(), code_size.expr(), 0.expr()),
+                dst_memory_addr.offset(),
+                dst_memory_addr.length(),
+                0.expr(), // for EXTCODECOPY, rlc_acc is 0
+                copy_rwc_inc.expr(),
+            );
+        });
+        cb.condition(not::expr(dst_memory_addr.has_length()), |cb| {
+            cb.require_zero(
+                "if no bytes to copy, copy table rwc inc == 0",
+                copy_rwc_inc.expr(),
+            );
+        });
+
+        let gas_cost = select::expr(
+            is_warm.expr(),
+            GasCost::WARM_ACCESS.expr(),
+            GasCost::COLD_ACCOUNT_ACCESS.expr(),
+        ) + memory_copier_gas.gas_cost();
+
+        // Expected state transition.
+        let step_state_transition = StepStateTransition {
+            rw_counter: Transition::Delta(cb.rw_counter_offset()),
+            program_counter: Transition::Delta(1.expr()),
+            stack_pointer: Transition::Delta(4.expr()),
+            memory_word_size: Transition::To(memory_expansion.next_memory_word_size()),
+            gas_left: Transition::Delta(-gas_cost),
+            reversible_write_counter: Transition::Delta(1.expr()),
+            ..Default::default()
+        };
+        let same_context = SameContextGadget::construct(cb, opcode, step_state_transition);
+
+        Self {
+            same_context,
+            external_address,
+            code_offset,
+            tx_id,
+            reversion_info,
+            is_warm,
+            exists,
+            code_hash,
+            code_size,
+            dst_memory_addr,
+            memory_expansion,
+            memory_copier_gas,
+            copy_rwc_inc,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        tx: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let randomness = region.evm_word_randomness();
+        self.same_context.assign_exec_step(region, offset, step)?;
+
+        let [external_address, dest_offset, code_offset, size] =
+            [0, 1, 2, 3].map(|i| block.rws[step.rw_indices[i]].stack_value());
+
+        let mut external_address_bytes = external_address.to_address().0;
+        external_address_bytes.reverse();
+        self.external_address
+            .assign(region, offset, Some(external_address_bytes))?;
+
+        // assign the code offset memory address.
+        self.code_offset.assign(
+            region,
+            offset,
+            Some(
+                code_offset.to_le_bytes()[..N_BYTES_MEMORY_ADDRESS]
+                    .try_into()
+                    .unwrap(),
+            ),
+        )?;
+
+        self.tx_id
+            .assign(region, offset, Value::known(F::from(tx.id as u64)))?;
+        self.reversion_info.assign(
+            region,
+            offset,
+            call.rw_counter_end_of_reversion,
+            call.is_persistent,
+        )?;
+
+        let (_, is_warm) = block.rws[step.rw_indices[7]].tx_access_list_value_pair();
+        self.is_warm
+            .assign(region, offset, Value::known(F::from(is_warm)))?;
+
+        let (exists, code_hash) = match block.rws[step.rw_indices[8]] {
+            Rw::Account {
+                field_tag: AccountFieldTag::CodeHash,
+                value,
+                ..
+            } => (true, value),
+            Rw::Account {
+                field_tag: AccountFieldTag::NonExisting,
+                ..
+            } => (false, 0.into()),
+            _ => unreachable!(),
+        };
+        self.exists
+            .assign(region, offset, Value::known(F::from(exists)))?;
+        self.code_hash.assign(
+            region,
+            offset,
+            Value::known(RandomLinearCombination::random_linear_combine(
+                code_hash.to_le_bytes(),
+                randomness,
+            )),
+        )?;
+
+        let code_size = if exists {
+            block
+                .bytecodes
+                .get(&code_hash)
+                .expect("could not find external bytecode")
+                .bytes
+                .len() as u64
+        } else {
+            0
+        };
+        self.code_size
+            .assign(region, offset, Value::known(F::from(code_size)))?;
+
+        // assign the destination memory offset.
+        let memory_address =
+            self.dst_memory_addr
+                .assign(region, offset, dest_offset, size, randomness)?;
+
+        // assign to gadgets handling memory expansion cost and copying cost.
+        let (_, memory_expansion_cost) = self.memory_expansion.assign(
+            region,
+            offset,
+            step.memory_word_size(),
+            [memory_address],
+        )?;
+        self.memory_copier_gas
+            .assign(region, offset, size.as_u64(), memory_expansion_cost)?;
+        // rw_counter increase from copy table lookup is number of bytes copied.
+        self.copy_rwc_inc.assign(
+            region,
+            offset,
+            Value::known(
+                size.to_scalar()
+                    .expect("unexpected U256 -> Scalar conversion failure"),
+            ),
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::run_test_circuits;
+    use eth_types::geth_types::Account;
+    use eth_types::{bytecode, Bytecode, ToWord, Word};
+    use mock::{TestContext, MOCK_1_ETH, MOCK_ACCOUNTS, MOCK_CODES};
+
+    fn test_ok(
+        external_account: Option<Account>,
+        memory_offset: usize,
+        code_offset: usize,
+        size: usize,
+        is_warm: bool,
+    ) {
+        let external_address = MOCK_ACCOUNTS[4];
+
+        let mut code = Bytecode::default();
+        if is_warm {
+            code.append(&bytecode! {
+                PUSH20(external_address.to_word())
+                EXTCODEHASH
+                POP
+            });
+        }
+        code.append(&bytecode! {
+            PUSH32(Word::from(size))
+            PUSH32(Word::from(code_offset))
+            PUSH32(Word::from(memory_offset))
+            PUSH20(external_address.to_word())
+            EXTCODECOPY
+            STOP
+        });
+
+        let ctx = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(MOCK_ACCOUNTS[0])
+                    .balance(MOCK_1_ETH)
+                    .code(code);
+                accs[1].address(MOCK_ACCOUNTS[1]).balance(MOCK_1_ETH);
+                if let Some(external_account) = external_account {
+                    accs[2]
+                        .address(external_account.address)
+                        .code(external_account.code);
+                } else {
+                    accs[2].address(MOCK_ACCOUNTS[2]).balance(MOCK_1_ETH);
+                }
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[1].address);
+            },
+            |block, _tx| block,
+        )
+        .unwrap();
+
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+
+    fn external_account() -> Option<Account> {
+        Some(Account {
+            address: MOCK_ACCOUNTS[4],
+            code: MOCK_CODES[4].clone(),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn extcodecopy_empty_account() {
+        test_ok(None, 0x00, 0x00, 0x36, true);
+        test_ok(None, 0x00, 0x00, 0x36, false);
+    }
+
+    #[test]
+    fn extcodecopy_nonempty_account() {
+        test_ok(external_account(), 0x00, 0x00, 0x36, true);
+        test_ok(external_account(), 0x00, 0x00, 0x36, false);
+    }
+
+    #[test]
+    fn extcodecopy_out_of_bound() {
+        test_ok(external_account(), 0x20, 0x10, 0x200, false);
+        test_ok(external_account(), 0x40, 0x1000, 0x20, true);
+    }
+
+    #[test]
+    fn extcodecopy_zero_size() {
+        test_ok(external_account(), 0x20, 0x10, 0x00, false);
+    }
+}
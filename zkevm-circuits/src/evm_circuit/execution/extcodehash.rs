@@ -129,6 +129,7 @@ impl<F: Field> ExecutionGadget<F> for ExtcodehashGadget<F> {
         call: &Call,
         step: &ExecStep,
     ) -> Result<(), Error> {
+        let randomness = region.evm_word_randomness();
         self.same_context.assign_exec_step(region, offset, step)?;
 
         let external_address = block.rws[step.rw_indices[0]].stack_value().to_address();
@@ -156,7 +157,7 @@ impl<F: Field> ExecutionGadget<F> for ExtcodehashGadget<F> {
 
         let [nonce, balance, code_hash] = [5, 6, 7].map(|i| {
             block.rws[step.rw_indices[i]]
-                .table_assignment_aux(block.randomness)
+                .table_assignment_aux(randomness)
                 .value
         });
 
@@ -165,7 +166,7 @@ impl<F: Field> ExecutionGadget<F> for ExtcodehashGadget<F> {
         self.code_hash
             .assign(region, offset, Value::known(code_hash))?;
 
-        let empty_code_hash_rlc = Word::random_linear_combine(*EMPTY_HASH_LE, block.randomness);
+        let empty_code_hash_rlc = Word::random_linear_combine(*EMPTY_HASH_LE, randomness);
         self.is_empty.assign(
             region,
             offset,
@@ -106,6 +106,7 @@ impl<F: Field> ExecutionGadget<F> for ExtcodesizeGadget<F> {
         call: &Call,
         step: &ExecStep,
     ) -> Result<(), Error> {
+        let randomness = region.evm_word_randomness();
         self.same_context.assign_exec_step(region, offset, step)?;
 
         let address = block.rws[step.rw_indices[0]].stack_value().to_address();
@@ -149,7 +150,7 @@ impl<F: Field> ExecutionGadget<F> for ExtcodesizeGadget<F> {
             offset,
             Value::known(RandomLinearCombination::random_linear_combine(
                 code_hash.to_le_bytes(),
-                block.randomness,
+                randomness,
             )),
         )?;
         self.code_size
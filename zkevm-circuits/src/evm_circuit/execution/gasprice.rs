@@ -72,6 +72,7 @@ impl<F: Field> ExecutionGadget<F> for GasPriceGadget<F> {
         _: &Call,
         step: &ExecStep,
     ) -> Result<(), Error> {
+        let randomness = region.evm_word_randomness();
         let gas_price = block.rws[step.rw_indices[1]].stack_value();
 
         self.tx_id
@@ -82,7 +83,7 @@ impl<F: Field> ExecutionGadget<F> for GasPriceGadget<F> {
             offset,
             Value::known(Word::random_linear_combine(
                 gas_price.to_le_bytes(),
-                block.randomness,
+                randomness,
             )),
         )?;
 
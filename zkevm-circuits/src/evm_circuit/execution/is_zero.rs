@@ -62,10 +62,11 @@ impl<F: Field> ExecutionGadget<F> for IsZeroGadget<F> {
         _: &Call,
         step: &ExecStep,
     ) -> Result<(), Error> {
+        let randomness = region.evm_word_randomness();
         self.same_context.assign_exec_step(region, offset, step)?;
 
         let value = block.rws[step.rw_indices[0]].stack_value();
-        let value = Word::random_linear_combine(value.to_le_bytes(), block.randomness);
+        let value = Word::random_linear_combine(value.to_le_bytes(), randomness);
         self.value.assign(region, offset, Value::known(value))?;
         self.is_zero.assign(region, offset, value)?;
 
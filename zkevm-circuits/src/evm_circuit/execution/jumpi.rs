@@ -90,11 +90,12 @@ impl<F: Field> ExecutionGadget<F> for JumpiGadget<F> {
         _: &Call,
         step: &ExecStep,
     ) -> Result<(), Error> {
+        let randomness = region.evm_word_randomness();
         self.same_context.assign_exec_step(region, offset, step)?;
 
         let [destination, condition] =
             [step.rw_indices[0], step.rw_indices[1]].map(|idx| block.rws[idx].stack_value());
-        let condition = Word::random_linear_combine(condition.to_le_bytes(), block.randomness);
+        let condition = Word::random_linear_combine(condition.to_le_bytes(), randomness);
 
         self.destination.assign(
             region,
@@ -71,6 +71,8 @@ impl<F: Field> ExecutionGadget<F> for LogGadget<F> {
                 0.expr(),
                 contract_address.expr(),
             );
+            // the address is accrued into the logs bloom
+            cb.bloom_table_lookup(TxLogFieldTag::Address, contract_address.expr());
         });
 
         // constrain topics in logs
@@ -88,6 +90,7 @@ impl<F: Field> ExecutionGadget<F> for LogGadget<F> {
                     idx.expr(),
                     topic.expr(),
                 );
+                cb.bloom_table_lookup(TxLogFieldTag::Topic, topic.expr());
             });
         }
 
@@ -117,6 +120,24 @@ impl<F: Field> ExecutionGadget<F> for LogGadget<F> {
         // check memory copy
         let memory_address = MemoryAddressGadget::construct(cb, mstart, msize);
 
+        // record the lengths needed to rebuild the log RLP of the receipt
+        cb.condition(is_persistent.expr(), |cb| {
+            cb.tx_log_lookup(
+                tx_id.expr(),
+                cb.curr.state.log_id.expr() + 1.expr(),
+                TxLogFieldTag::TopicLength,
+                0.expr(),
+                topic_count.clone(),
+            );
+            cb.tx_log_lookup(
+                tx_id.expr(),
+                cb.curr.state.log_id.expr() + 1.expr(),
+                TxLogFieldTag::DataLength,
+                0.expr(),
+                memory_address.length(),
+            );
+        });
+
         // Calculate the next memory size and the gas cost for this memory
         // access
         let memory_expansion = MemoryExpansionGadget::construct(cb, [memory_address.address()]);
@@ -190,6 +211,7 @@ impl<F: Field> ExecutionGadget<F> for LogGadget<F> {
         call: &Call,
         step: &ExecStep,
     ) -> Result<(), Error> {
+        let randomness = region.evm_word_randomness();
         self.same_context.assign_exec_step(region, offset, step)?;
 
         let [memory_start, msize] =
@@ -197,7 +219,7 @@ impl<F: Field> ExecutionGadget<F> for LogGadget<F> {
 
         let memory_address =
             self.memory_address
-                .assign(region, offset, memory_start, msize, block.randomness)?;
+                .assign(region, offset, memory_start, msize, randomness)?;
 
         // Memory expansion
         self.memory_expansion
@@ -216,11 +238,11 @@ impl<F: Field> ExecutionGadget<F> for LogGadget<F> {
         };
 
         for i in 0..4 {
-            let mut topic = Word::random_linear_combine([0; 32], block.randomness);
+            let mut topic = Word::random_linear_combine([0; 32], randomness);
             if i < topic_count {
                 topic = Word::random_linear_combine(
                     block.rws[topic_stack_entry].stack_value().to_le_bytes(),
-                    block.randomness,
+                    randomness,
                 );
                 self.topic_selectors[i].assign(region, offset, Value::known(F::one()))?;
                 topic_stack_entry.1 += 1;
@@ -111,6 +111,7 @@ impl<F: Field> ExecutionGadget<F> for MulModGadget<F> {
         _: &Call,
         step: &ExecStep,
     ) -> Result<(), Error> {
+        let randomness = region.evm_word_randomness();
         self.same_context.assign_exec_step(region, offset, step)?;
 
         let [r, n, b, a] = [3, 2, 1, 0]
@@ -148,7 +149,7 @@ impl<F: Field> ExecutionGadget<F> for MulModGadget<F> {
         self.e.assign(region, offset, Some(e.to_le_bytes()))?;
 
         self.modword
-            .assign(region, offset, a, n, a_reduced, k1, block.randomness)?;
+            .assign(region, offset, a, n, a_reduced, k1, randomness)?;
         self.mul512_left
             .assign(region, offset, [a_reduced, b, d, e], None)?;
         self.mul512_right
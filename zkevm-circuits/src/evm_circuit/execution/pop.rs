@@ -58,16 +58,14 @@ impl<F: Field> ExecutionGadget<F> for PopGadget<F> {
         _: &Call,
         step: &ExecStep,
     ) -> Result<(), Error> {
+        let randomness = region.evm_word_randomness();
         self.same_context.assign_exec_step(region, offset, step)?;
 
         let value = block.rws[step.rw_indices[0]].stack_value();
         self.value.assign(
             region,
             offset,
-            Value::known(Word::random_linear_combine(
-                value.to_le_bytes(),
-                block.randomness,
-            )),
+            Value::known(Word::random_linear_combine(value.to_le_bytes(), randomness)),
         )?;
 
         Ok(())
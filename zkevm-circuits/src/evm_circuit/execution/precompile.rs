@@ -0,0 +1,740 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::{N_BYTES_GAS, N_BYTES_MEMORY_ADDRESS, STACK_CAPACITY},
+        step::ExecutionState,
+        util::{
+            common_gadget::RestoreContextGadget,
+            constraint_builder::{
+                ConstraintBuilder, StepStateTransition,
+                Transition::{Delta, To},
+            },
+            math_gadget::{ConstantDivisionGadget, IsZeroGadget, LtGadget, MinMaxGadget},
+            not, rlc, select, CachedRegion, Cell,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    precompile_circuit::is_precompile_table_call,
+    table::CallContextFieldTag,
+    util::Expr,
+};
+use bus_mapping::circuit_input_builder::CopyDataType;
+use eth_types::{evm_types::GasCost, Field};
+use halo2_proofs::{circuit::Value, plonk::Error};
+use std::iter;
+
+/// Length of the input of ecrecover: the message hash, `v`, `r` and `s`.
+const ECRECOVER_INPUT_LENGTH: u64 = 128;
+
+/// Gadget for the execution of a precompiled contract, which is entered right
+/// after a `*CALL` to the precompile address or the `BeginTx` of a
+/// transaction sent to it.
+///
+/// The identity precompile is verified by copying the input from the caller's
+/// memory (or the tx calldata for a root call) into the callee's memory, where
+/// it's returned from.
+///
+/// A successful call to ecrecover whose signature is verified by the
+/// SignVerify chip of the Tx Circuit is looked up from the sig table: the
+/// input, right padded with zeros, and the output written into the callee's
+/// memory are accumulated into RLCs, where the input RLC includes `v` which
+/// binds the recovered address to the recovery id.  A call to ecrecover which
+/// runs out of gas needs no lookup, as its required gas and validity are
+/// constant.
+///
+/// Any other call is looked up from the precompile table, which holds the
+/// input, the output (even when the call runs out of gas and returns
+/// nothing), the required gas and the validity of the input of every call
+/// verified by the Precompile Circuit.
+#[derive(Clone, Debug)]
+pub(crate) struct PrecompileGadget<F, const S: ExecutionState> {
+    is_success: Cell<F>,
+    call_data_offset: Cell<F>,
+    call_data_length: Cell<F>,
+    tx_id: Cell<F>,
+    return_data_offset: Cell<F>,
+    return_data_length: Cell<F>,
+    required_gas: Cell<F>,
+    is_valid: Cell<F>,
+    output_length: Cell<F>,
+    return_length: Cell<F>,
+    is_sig_verified: Cell<F>,
+    input_words: ConstantDivisionGadget<F, N_BYTES_MEMORY_ADDRESS>,
+    insufficient_gas: LtGadget<F, N_BYTES_GAS>,
+    input_length_is_zero: IsZeroGadget<F>,
+    return_length_is_zero: IsZeroGadget<F>,
+    copy_length: MinMaxGadget<F, N_BYTES_MEMORY_ADDRESS>,
+    copy_length_is_zero: IsZeroGadget<F>,
+    input_copy_length: MinMaxGadget<F, N_BYTES_MEMORY_ADDRESS>,
+    input_rlc: Cell<F>,
+    output_rlc: Cell<F>,
+    output_rlc_is_zero: IsZeroGadget<F>,
+    restore_context: RestoreContextGadget<F>,
+}
+
+impl<F: Field, const S: ExecutionState> ExecutionGadget<F> for PrecompileGadget<F, S> {
+    const NAME: &'static str = "Precompile";
+
+    const EXECUTION_STATE: ExecutionState = S;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let is_identity = S == ExecutionState::PrecompileIdentity;
+        let is_ecrecover = S == ExecutionState::PrecompileEcRecover;
+        let address = u64::from(S.precompile().unwrap());
+        let call_id = cb.curr.state.call_id.expr();
+        let is_root = cb.curr.state.is_root.expr();
+
+        let is_success = cb.call_context(None, CallContextFieldTag::IsSuccess);
+        cb.call_context_lookup(
+            false.expr(),
+            None,
+            CallContextFieldTag::IsRoot,
+            is_root.expr(),
+        );
+        let [call_data_offset, call_data_length] = [
+            CallContextFieldTag::CallDataOffset,
+            CallContextFieldTag::CallDataLength,
+        ]
+        .map(|field_tag| cb.call_context(None, field_tag));
+        let tx_id = cb.condition(is_root.expr(), |cb| {
+            cb.call_context(None, CallContextFieldTag::TxId)
+        });
+        let [return_data_offset, return_data_length] =
+            cb.condition(not::expr(is_root.expr()), |cb| {
+                [
+                    CallContextFieldTag::ReturnDataOffset,
+                    CallContextFieldTag::ReturnDataLength,
+                ]
+                .map(|field_tag| cb.call_context(None, field_tag))
+            });
+
+        // Constrain the gas required, the validity of the input and the output
+        // length of identity and ecrecover, while the ones of the other
+        // precompiles are looked up from the precompile table.
+        let required_gas = cb.query_cell();
+        let is_valid = cb.query_bool();
+        let output_length = cb.query_cell();
+        let input_words =
+            ConstantDivisionGadget::construct(cb, call_data_length.expr() + 31.expr(), 32);
+        if is_identity {
+            cb.require_equal(
+                "required_gas == 15 + 3 * ceil(call_data_length / 32)",
+                required_gas.expr(),
+                GasCost::PRECOMPILE_IDENTITY_BASE.expr()
+                    + GasCost::PRECOMPILE_IDENTITY_PER_WORD.expr() * input_words.quotient(),
+            );
+            cb.require_equal("identity input is always valid", is_valid.expr(), 1.expr());
+            cb.require_equal(
+                "identity output is the input",
+                output_length.expr(),
+                call_data_length.expr(),
+            );
+        } else if is_ecrecover {
+            cb.require_equal(
+                "required_gas == 3000",
+                required_gas.expr(),
+                GasCost::PRECOMPILE_EC_RECOVER.expr(),
+            );
+            cb.require_equal("ecrecover input is always valid", is_valid.expr(), 1.expr());
+        }
+
+        // The call succeeds when the input is valid and there is enough gas,
+        // otherwise it consumes all the gas given and returns nothing.
+        let insufficient_gas =
+            LtGadget::construct(cb, cb.curr.state.gas_left.expr(), required_gas.expr());
+        cb.require_equal(
+            "is_success == is_valid && required_gas <= gas_left",
+            is_success.expr(),
+            is_valid.expr() * not::expr(insufficient_gas.expr()),
+        );
+        let return_length = cb.copy(is_success.expr() * output_length.expr());
+        let gas_cost = select::expr(
+            is_success.expr(),
+            required_gas.expr(),
+            cb.curr.state.gas_left.expr(),
+        );
+
+        // Choose whether the call is looked up from the sig table or from the
+        // precompile table.
+        let is_sig_verified = cb.query_bool();
+        let is_table_verified = if is_ecrecover {
+            cb.require_zero(
+                "signature is verified only when ecrecover succeeds",
+                not::expr(is_success.expr()) * is_sig_verified.expr(),
+            );
+            is_success.expr() - is_sig_verified.expr()
+        } else {
+            cb.require_zero(
+                "only ecrecover is verified by the sig table",
+                is_sig_verified.expr(),
+            );
+            (!is_identity as u64).expr()
+        };
+
+        // Copy the input from the caller's memory or the tx calldata.
+        let input_src_id = select::expr(is_root.expr(), tx_id.expr(), cb.next.state.call_id.expr());
+        let input_src_tag = select::expr(
+            is_root.expr(),
+            CopyDataType::TxCalldata.expr(),
+            CopyDataType::Memory.expr(),
+        );
+        let input_length_is_zero = IsZeroGadget::construct(cb, call_data_length.expr());
+        let input_copy_length =
+            MinMaxGadget::construct(cb, call_data_length.expr(), ECRECOVER_INPUT_LENGTH.expr());
+        let input_rlc = cb.query_cell();
+        if is_identity {
+            cb.condition(not::expr(input_length_is_zero.expr()), |cb| {
+                cb.copy_table_lookup(
+                    input_src_id,
+                    input_src_tag,
+                    call_id.expr(),
+                    CopyDataType::Memory.expr(),
+                    call_data_offset.expr(),
+                    call_data_offset.expr() + call_data_length.expr(),
+                    0.expr(),
+                    call_data_length.expr(),
+                    0.expr(),
+                    (2.expr() - is_root.expr()) * call_data_length.expr(),
+                );
+            });
+        } else {
+            if is_ecrecover {
+                cb.condition(is_sig_verified.expr(), |cb| {
+                    cb.copy_table_lookup(
+                        input_src_id.clone(),
+                        input_src_tag.clone(),
+                        call_id.expr(),
+                        CopyDataType::RlcAcc.expr(),
+                        call_data_offset.expr(),
+                        call_data_offset.expr() + call_data_length.expr(),
+                        0.expr(),
+                        ECRECOVER_INPUT_LENGTH.expr(),
+                        input_rlc.expr(),
+                        not::expr(is_root.expr()) * input_copy_length.min(),
+                    );
+                });
+            }
+            cb.condition(
+                is_table_verified.clone() * not::expr(input_length_is_zero.expr()),
+                |cb| {
+                    cb.copy_table_lookup(
+                        input_src_id,
+                        input_src_tag,
+                        call_id.expr(),
+                        CopyDataType::RlcAcc.expr(),
+                        call_data_offset.expr(),
+                        call_data_offset.expr() + call_data_length.expr(),
+                        0.expr(),
+                        call_data_length.expr(),
+                        input_rlc.expr(),
+                        not::expr(is_root.expr()) * call_data_length.expr(),
+                    );
+                },
+            );
+        }
+        cb.require_zero(
+            "input_rlc == 0 for an empty input",
+            input_length_is_zero.expr() * input_rlc.expr(),
+        );
+
+        // Copy the output into the callee's memory, where it's returned from,
+        // and read it back into an RLC.
+        let return_length_is_zero = IsZeroGadget::construct(cb, return_length.expr());
+        let output_rlc = cb.query_cell();
+        if !is_identity {
+            cb.condition(not::expr(return_length_is_zero.expr()), |cb| {
+                cb.copy_table_lookup(
+                    call_id.expr(),
+                    CopyDataType::PrecompileOutput.expr(),
+                    call_id.expr(),
+                    CopyDataType::Memory.expr(),
+                    0.expr(),
+                    output_length.expr(),
+                    0.expr(),
+                    output_length.expr(),
+                    0.expr(),
+                    output_length.expr(),
+                );
+                cb.copy_table_lookup(
+                    call_id.expr(),
+                    CopyDataType::Memory.expr(),
+                    call_id.expr(),
+                    CopyDataType::RlcAcc.expr(),
+                    0.expr(),
+                    output_length.expr(),
+                    0.expr(),
+                    output_length.expr(),
+                    output_rlc.expr(),
+                    output_length.expr(),
+                );
+            });
+        }
+
+        // Look up the input and the recovered address from the sig table.  The
+        // zero row of the sig table is excluded by requiring a non zero
+        // address.
+        let output_rlc_is_zero = IsZeroGadget::construct(cb, output_rlc.expr());
+        if is_ecrecover {
+            cb.condition(is_sig_verified.expr(), |cb| {
+                cb.require_equal(
+                    "ecrecover output is the 32 bytes address",
+                    output_length.expr(),
+                    32.expr(),
+                );
+                cb.require_zero("recovered address is not zero", output_rlc_is_zero.expr());
+                cb.sig_table_lookup(input_rlc.expr(), output_rlc.expr());
+            });
+        }
+
+        // Look up the input, the output, the required gas and the validity of
+        // the input from the precompile table.
+        if !is_identity {
+            cb.condition(is_table_verified, |cb| {
+                cb.precompile_table_lookup(
+                    address.expr(),
+                    call_data_length.expr(),
+                    input_rlc.expr(),
+                    output_length.expr(),
+                    output_rlc.expr(),
+                    required_gas.expr(),
+                    is_valid.expr(),
+                );
+            });
+        }
+
+        // Copy the return data into the caller's memory.
+        let copy_length =
+            MinMaxGadget::construct(cb, return_data_length.expr(), return_length.expr());
+        let copy_length_is_zero = IsZeroGadget::construct(cb, copy_length.min());
+        cb.condition(
+            not::expr(is_root.expr()) * not::expr(copy_length_is_zero.expr()),
+            |cb| {
+                cb.copy_table_lookup(
+                    call_id.expr(),
+                    CopyDataType::Memory.expr(),
+                    cb.next.state.call_id.expr(),
+                    CopyDataType::Memory.expr(),
+                    0.expr(),
+                    output_length.expr(),
+                    return_data_offset.expr(),
+                    copy_length.min(),
+                    0.expr(),
+                    copy_length.min() + copy_length.min(),
+                );
+            },
+        );
+
+        cb.condition(is_root.expr(), |cb| {
+            cb.require_next_state(ExecutionState::EndTx);
+            cb.require_step_state_transition(StepStateTransition {
+                program_counter: To(0.expr()),
+                stack_pointer: To(STACK_CAPACITY.expr()),
+                rw_counter: Delta(
+                    cb.rw_counter_offset()
+                        + not::expr(is_success.expr())
+                            * cb.curr.state.reversible_write_counter.expr(),
+                ),
+                gas_left: Delta(-gas_cost.clone()),
+                reversible_write_counter: To(0.expr()),
+                memory_word_size: To(0.expr()),
+                ..StepStateTransition::default()
+            });
+        });
+
+        let restore_context = cb.condition(not::expr(is_root.expr()), |cb| {
+            RestoreContextGadget::construct(
+                cb,
+                is_success.expr(),
+                0.expr(),
+                0.expr(),
+                return_length.expr(),
+                gas_cost,
+                0.expr(),
+            )
+        });
+
+        Self {
+            is_success,
+            call_data_offset,
+            call_data_length,
+            tx_id,
+            return_data_offset,
+            return_data_length,
+            required_gas,
+            is_valid,
+            output_length,
+            return_length,
+            is_sig_verified,
+            input_words,
+            insufficient_gas,
+            input_length_is_zero,
+            return_length_is_zero,
+            copy_length,
+            copy_length_is_zero,
+            input_copy_length,
+            input_rlc,
+            output_rlc,
+            output_rlc_is_zero,
+            restore_context,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        tx: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let is_identity = S == ExecutionState::PrecompileIdentity;
+        let event = block
+            .precompile_events
+            .iter()
+            .find(|event| event.call_id == call.id)
+            .expect("precompile event should exist for the call");
+        let is_sig_verified = event.ecrecover_sign_data().is_some();
+        let is_table_verified = is_precompile_table_call(event);
+        let input_length = call.call_data_length;
+        let output_length = event.output.len() as u64;
+        let return_length = if call.is_success { output_length } else { 0 };
+
+        for (cell, value) in [
+            (&self.is_success, call.is_success as u64),
+            (&self.call_data_offset, call.call_data_offset),
+            (&self.call_data_length, input_length),
+            (&self.tx_id, if call.is_root { tx.id as u64 } else { 0 }),
+            (
+                &self.return_data_offset,
+                if call.is_root {
+                    0
+                } else {
+                    call.return_data_offset
+                },
+            ),
+            (
+                &self.return_data_length,
+                if call.is_root {
+                    0
+                } else {
+                    call.return_data_length
+                },
+            ),
+            (&self.required_gas, event.required_gas),
+            (&self.is_valid, event.is_valid as u64),
+            (&self.output_length, output_length),
+            (&self.return_length, return_length),
+            (&self.is_sig_verified, is_sig_verified as u64),
+        ] {
+            cell.assign(region, offset, Value::known(F::from(value)))?;
+        }
+
+        self.input_words
+            .assign(region, offset, input_length as u128 + 31)?;
+        self.insufficient_gas.assign(
+            region,
+            offset,
+            F::from(step.gas_left),
+            F::from(event.required_gas),
+        )?;
+        self.input_length_is_zero
+            .assign(region, offset, F::from(input_length))?;
+        self.return_length_is_zero
+            .assign(region, offset, F::from(return_length))?;
+        let return_data_length = if call.is_root {
+            0
+        } else {
+            call.return_data_length
+        };
+        let (copy_length, _) = self.copy_length.assign(
+            region,
+            offset,
+            F::from(return_data_length),
+            F::from(return_length),
+        )?;
+        self.copy_length_is_zero
+            .assign(region, offset, copy_length)?;
+
+        self.input_copy_length.assign(
+            region,
+            offset,
+            F::from(input_length),
+            F::from(ECRECOVER_INPUT_LENGTH),
+        )?;
+        let input = if is_sig_verified {
+            event
+                .input
+                .iter()
+                .copied()
+                .chain(iter::repeat(0))
+                .take(ECRECOVER_INPUT_LENGTH as usize)
+                .collect()
+        } else if is_table_verified {
+            event.input.clone()
+        } else {
+            vec![]
+        };
+        let output = if is_identity {
+            vec![]
+        } else {
+            event.output.clone()
+        };
+        let [input_rlc, output_rlc] = [input, output].map(|bytes| {
+            region
+                .challenges()
+                .keccak_input()
+                .map(|randomness| rlc::value(bytes.iter().rev(), randomness))
+        });
+        self.input_rlc.assign(region, offset, input_rlc)?;
+        self.output_rlc.assign(region, offset, output_rlc)?;
+        self.output_rlc_is_zero
+            .assign_value(region, offset, output_rlc)?;
+
+        if !call.is_root {
+            let input_rws = if is_identity {
+                2 * input_length
+            } else if is_sig_verified {
+                std::cmp::min(input_length, ECRECOVER_INPUT_LENGTH)
+            } else if is_table_verified {
+                input_length
+            } else {
+                0
+            };
+            // The output is written into the callee's memory and read back,
+            // except for identity whose input is copied there.
+            let output_rws = if is_identity { 0 } else { 2 * return_length };
+            let return_rws = 2 * std::cmp::min(return_data_length, return_length);
+            let rw_offset = 6 + input_rws + output_rws + return_rws;
+            self.restore_context
+                .assign(region, offset, block, call, step, rw_offset as usize)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        evm_circuit::{test::run_test_circuit, witness::block_convert},
+        test_util::{run_test_circuits, run_test_circuits_with_params},
+    };
+    use bus_mapping::{
+        circuit_input_builder::CircuitsParams, mock::BlockData, precompile::PrecompileCalls,
+    };
+    use eth_types::{bytecode, geth_types::GethData, Address, Bytecode, Word};
+    use halo2_proofs::halo2curves::bn256::Fr;
+    use mock::{eth, TestContext};
+
+    const ECRECOVER_INPUT: &str = "\
+        456e9aea5e197a1f1af7a3e85a3212fa4049a3ba34c2289b4c860fc0b0c64ef3\
+        000000000000000000000000000000000000000000000000000000000000001c\
+        9242685bf161793cc25603c231bc2f568eb630ea16aa137d2664ac8038825608\
+        4f8ae3bd7535248d0bd448298cc2e2071e56992d0774dc340c368ae950852ada";
+
+    fn call_precompile(address: u64, input: &[u8], gas: u64, return_length: u64) -> Bytecode {
+        let mut code = Bytecode::default();
+        for (i, chunk) in input.chunks(32).enumerate() {
+            let mut word = [0u8; 32];
+            word[..chunk.len()].copy_from_slice(chunk);
+            code.append(&bytecode! {
+                PUSH32(Word::from_big_endian(&word))
+                PUSH32(i * 32)
+                MSTORE
+            });
+        }
+        code.append(&bytecode! {
+            PUSH32(return_length) // return data length
+            PUSH32(0x200) // return data offset
+            PUSH32(input.len()) // call data length
+            PUSH32(0) // call data offset
+            PUSH32(0) // value
+            PUSH32(address) // address
+            PUSH32(gas) // gas
+            CALL
+            STOP
+        });
+        code
+    }
+
+    fn test_ok(code: Bytecode) {
+        assert_eq!(
+            run_test_circuits(
+                TestContext::<2, 1>::simple_ctx_with_bytecode(code).unwrap(),
+                None
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn precompile_identity() {
+        for (input_length, return_length) in [(0, 0), (1, 1), (40, 32), (64, 80)] {
+            let input: Vec<u8> = (0..input_length).map(|i| i as u8 + 1).collect();
+            test_ok(call_precompile(0x04, &input, 1000, return_length));
+        }
+    }
+
+    #[test]
+    fn precompile_identity_out_of_gas() {
+        test_ok(call_precompile(0x04, &[0xab; 64], 20, 64));
+    }
+
+    #[test]
+    fn precompile_ecrecover() {
+        let input = hex::decode(ECRECOVER_INPUT).unwrap();
+        for (input_length, return_length) in [(128, 32), (160, 20)] {
+            let mut input = input.clone();
+            input.resize(input_length, 0);
+            assert_eq!(
+                run_test_circuits_with_params(
+                    TestContext::<2, 1>::simple_ctx_with_bytecode(call_precompile(
+                        0x01,
+                        &input,
+                        5000,
+                        return_length
+                    ))
+                    .unwrap(),
+                    None,
+                    CircuitsParams {
+                        max_ecrecover: 1,
+                        ..Default::default()
+                    },
+                ),
+                Ok(())
+            );
+        }
+    }
+
+    #[test]
+    fn precompile_ecrecover_out_of_gas() {
+        let input = hex::decode(ECRECOVER_INPUT).unwrap();
+        test_ok(call_precompile(0x01, &input, 2999, 32));
+    }
+
+    #[test]
+    fn precompile_ecrecover_invalid_signature() {
+        // Recovery fails, so the call succeeds with an empty output.
+        test_ok(call_precompile(0x01, &[0x01; 128], 5000, 32));
+    }
+
+    #[test]
+    fn precompile_ecrecover_wrong_v() {
+        // v is neither 27 nor 28, so the call succeeds with an empty output.
+        let mut input = hex::decode(ECRECOVER_INPUT).unwrap();
+        input[63] = 0x1d;
+        test_ok(call_precompile(0x01, &input, 5000, 32));
+    }
+
+    #[test]
+    fn precompile_sha256() {
+        for (input, return_length) in [(&b""[..], 32), (&b"abc"[..], 32), (&[0xab; 70][..], 16)] {
+            test_ok(call_precompile(0x02, input, 1000, return_length));
+        }
+    }
+
+    #[test]
+    fn precompile_sha256_out_of_gas() {
+        test_ok(call_precompile(0x02, &[0xab; 70], 80, 32));
+    }
+
+    #[test]
+    fn precompile_bn256_add() {
+        // (1, 2) + (1, 2) on bn256
+        let mut input = [0u8; 128];
+        input[31] = 1;
+        input[63] = 2;
+        input[95] = 1;
+        input[127] = 2;
+        test_ok(call_precompile(0x06, &input, 1000, 64));
+    }
+
+    #[test]
+    fn precompile_bn256_add_invalid_point() {
+        // (1, 1) isn't on bn256, so the input is invalid.
+        let mut input = [0u8; 128];
+        input[31] = 1;
+        input[63] = 1;
+        test_ok(call_precompile(0x06, &input, 1000, 64));
+    }
+
+    #[test]
+    fn precompile_bn256_pairing_empty() {
+        // The pairing check of no pairs succeeds.
+        test_ok(call_precompile(0x08, &[], 50000, 32));
+    }
+
+    #[test]
+    fn precompile_output_is_bound() {
+        let block: GethData =
+            TestContext::<2, 1>::simple_ctx_with_bytecode(call_precompile(0x02, b"abc", 1000, 32))
+                .unwrap()
+                .into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        let mut block = block_convert::<Fr>(&builder.block, &builder.code_db).unwrap();
+
+        // Return another output than the one written into the callee's memory,
+        // as if the precompile table held it.
+        let event = &mut block.precompile_events[0];
+        assert_eq!(event.precompile, PrecompileCalls::Sha256);
+        event.output[0] ^= 1;
+        assert!(run_test_circuit(block).is_err());
+    }
+
+    #[test]
+    fn precompile_tx_to_identity() {
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(Address::from_low_u64_be(0x04))
+                    .balance(Word::zero());
+                accs[1].address(mock::MOCK_ACCOUNTS[0]).balance(eth(10));
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[1].address)
+                    .to(accs[0].address)
+                    .input(vec![1, 2, 3, 4].into());
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+
+    #[test]
+    fn precompile_call_with_value() {
+        let code = bytecode! {
+            PUSH1(0) // return data length
+            PUSH1(0) // return data offset
+            PUSH1(0) // call data length
+            PUSH1(0) // call data offset
+            PUSH1(1) // value
+            PUSH1(0x04) // address
+            PUSH32(Word::from(1000))
+            CALL
+            STOP
+        };
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(mock::MOCK_ACCOUNTS[0])
+                    .balance(eth(10))
+                    .code(code);
+                accs[1].address(mock::MOCK_ACCOUNTS[1]).balance(eth(10));
+            },
+            |mut txs, accs| {
+                txs[0].from(accs[1].address).to(accs[0].address);
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+}
@@ -0,0 +1,101 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        step::ExecutionState,
+        util::{
+            common_gadget::SameContextGadget,
+            constraint_builder::{ConstraintBuilder, StepStateTransition, Transition::Delta},
+            CachedRegion,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use eth_types::{evm_types::OpcodeId, Field};
+use halo2_proofs::plonk::Error;
+
+#[derive(Clone, Debug)]
+pub(crate) struct Push0Gadget<F> {
+    same_context: SameContextGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for Push0Gadget<F> {
+    const NAME: &'static str = "PUSH0";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::PUSH0;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+
+        // PUSH0 has no immediate data, the random linear combination of the
+        // zero word is zero.
+        cb.stack_push(0.expr());
+
+        // State transition
+        let step_state_transition = StepStateTransition {
+            rw_counter: Delta(1.expr()),
+            program_counter: Delta(1.expr()),
+            stack_pointer: Delta((-1).expr()),
+            gas_left: Delta(-OpcodeId::PUSH0.constant_gas_cost().expr()),
+            ..Default::default()
+        };
+        let same_context = SameContextGadget::construct(cb, opcode, step_state_transition);
+
+        Self { same_context }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        _: &Block<F>,
+        _: &Transaction,
+        _: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.same_context.assign_exec_step(region, offset, step)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::run_test_circuits_with_params;
+    use bus_mapping::circuit_input_builder::CircuitsParams;
+    use eth_types::{bytecode, evm_types::Hardfork};
+    use mock::{
+        test_ctx::helpers::{account_0_code_account_1_no_code, tx_from_1_to_0},
+        TestContext,
+    };
+
+    // The geth tracer doesn't support Shanghai yet, so only the native tracer
+    // can trace PUSH0.
+    #[test]
+    #[cfg_attr(not(feature = "native-tracer"), ignore)]
+    fn push0_gadget_simple() {
+        let bytecode = bytecode! {
+            PUSH0
+            PUSH0
+            ADD
+            STOP
+        };
+
+        assert_eq!(
+            run_test_circuits_with_params(
+                TestContext::<2, 1>::new_with_hardfork(
+                    None,
+                    account_0_code_account_1_no_code(bytecode),
+                    tx_from_1_to_0,
+                    |block, _txs| block,
+                    Hardfork::Shanghai,
+                )
+                .unwrap(),
+                None,
+                CircuitsParams {
+                    hardfork: Hardfork::Shanghai,
+                    ..Default::default()
+                }
+            ),
+            Ok(())
+        );
+    }
+}
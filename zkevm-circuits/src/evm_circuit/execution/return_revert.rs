@@ -1,15 +1,16 @@
 use crate::{
     evm_circuit::{
         execution::ExecutionGadget,
-        param::{N_BYTES_MEMORY_ADDRESS, N_BYTES_MEMORY_WORD_SIZE, STACK_CAPACITY},
+        param::{N_BYTES_GAS, N_BYTES_MEMORY_ADDRESS, N_BYTES_MEMORY_WORD_SIZE, STACK_CAPACITY},
         step::ExecutionState,
+        table::HardforkParam,
         util::{
             common_gadget::RestoreContextGadget,
             constraint_builder::{
                 ConstraintBuilder, ReversionInfo, StepStateTransition,
                 Transition::{Delta, To},
             },
-            math_gadget::{IsZeroGadget, MinMaxGadget},
+            math_gadget::{IsEqualGadget, IsZeroGadget, LtGadget, MinMaxGadget},
             memory_gadget::{MemoryAddressGadget, MemoryExpansionGadget},
             not, CachedRegion, Cell, RandomLinearCombination, Word,
         },
@@ -19,7 +20,10 @@ use crate::{
     util::Expr,
 };
 use bus_mapping::{circuit_input_builder::CopyDataType, evm::OpcodeId};
-use eth_types::{Field, ToScalar};
+use eth_types::{
+    evm_types::{GasCost, MAX_CODE_SIZE},
+    Field, ToScalar,
+};
 use ethers_core::utils::keccak256;
 use halo2_proofs::{circuit::Value, plonk::Error};
 use keccak256::EMPTY_HASH_LE;
@@ -42,6 +46,11 @@ pub(crate) struct ReturnRevertGadget<F> {
 
     memory_expansion: MemoryExpansionGadget<F, 1, N_BYTES_MEMORY_WORD_SIZE>,
     code_hash: Cell<F>,
+    code_size_not_exceeded: LtGadget<F, N_BYTES_MEMORY_ADDRESS>,
+    insufficient_gas: LtGadget<F, N_BYTES_GAS>,
+    first_byte: Cell<F>,
+    is_first_byte_ef: IsEqualGadget<F>,
+    is_eip3541_enabled: Cell<F>,
 
     caller_id: Cell<F>,
     address: Cell<F>,
@@ -86,59 +95,117 @@ impl<F: Field> ExecutionGadget<F> for ReturnRevertGadget<F> {
         let memory_expansion = MemoryExpansionGadget::construct(cb, [range.address()]);
 
         // Case A in the specs.
-        cb.condition(is_create.clone() * is_success.expr(), |cb| {
-            cb.require_equal(
-                "increase rw counter once for each memory to bytecode byte copied",
-                copy_rw_increase.expr(),
-                range.length(),
-            );
-        });
-
-        let is_contract_deployment =
-            is_create.clone() * is_success.expr() * not::expr(copy_rw_increase_is_zero.expr());
-        let (caller_id, address, reversion_info, code_hash) =
-            cb.condition(is_contract_deployment.clone(), |cb| {
-                // We don't need to place any additional constraints on code_hash because the
-                // copy circuit enforces that it is the hash of the bytes in the copy lookup.
-                let code_hash = cb.query_cell();
-                cb.copy_table_lookup(
-                    cb.curr.state.call_id.expr(),
-                    CopyDataType::Memory.expr(),
-                    code_hash.expr(),
-                    CopyDataType::Bytecode.expr(),
-                    range.offset(),
-                    range.address(),
-                    0.expr(),
-                    range.length(),
-                    0.expr(),
+        let (code_size_not_exceeded, insufficient_gas) =
+            cb.condition(is_create.clone() * is_success.expr(), |cb| {
+                cb.require_equal(
+                    "increase rw counter once for each memory to bytecode byte copied",
                     copy_rw_increase.expr(),
+                    range.length(),
                 );
 
-                let [caller_id, address] = [
-                    CallContextFieldTag::CallerId,
-                    CallContextFieldTag::CalleeAddress,
-                ]
-                .map(|tag| cb.call_context(None, tag));
-                let mut reversion_info = cb.reversion_info_read(None);
-
-                let empty_code_hash_rlc = Word::random_linear_combine_expr(
-                    (*EMPTY_HASH_LE).map(|byte| byte.expr()),
-                    cb.power_of_randomness(),
+                // The returned code is at most MAX_CODE_SIZE bytes long, as specified
+                // in EIP-170, otherwise ErrorMaxCodeSizeExceeded happens instead.
+                let code_size_not_exceeded =
+                    LtGadget::construct(cb, range.length(), (MAX_CODE_SIZE + 1).expr());
+                cb.require_equal(
+                    "Returned code size <= MAX_CODE_SIZE",
+                    code_size_not_exceeded.expr(),
+                    1.expr(),
                 );
 
-                cb.account_write(
-                    address.expr(),
-                    AccountFieldTag::CodeHash,
-                    code_hash.expr(),
-                    empty_code_hash_rlc,
-                    Some(&mut reversion_info),
+                // The gas left after the memory expansion covers the code deposit
+                // cost, otherwise ErrorOutOfGasCodeStore happens instead.
+                let insufficient_gas = LtGadget::construct(
+                    cb,
+                    cb.curr.state.gas_left.expr() - memory_expansion.gas_cost(),
+                    GasCost::CODE_DEPOSIT_BYTE_COST.expr() * range.length(),
+                );
+                cb.require_zero(
+                    "Gas left covers the code deposit cost",
+                    insufficient_gas.expr(),
                 );
 
-                (caller_id, address, reversion_info, code_hash)
+                (code_size_not_exceeded, insufficient_gas)
             });
 
+        let is_contract_deployment =
+            is_create.clone() * is_success.expr() * not::expr(copy_rw_increase_is_zero.expr());
+        let (
+            caller_id,
+            address,
+            reversion_info,
+            code_hash,
+            first_byte,
+            is_first_byte_ef,
+            is_eip3541_enabled,
+        ) = cb.condition(is_contract_deployment.clone(), |cb| {
+            // We don't need to place any additional constraints on code_hash because the
+            // copy circuit enforces that it is the hash of the bytes in the copy lookup.
+            let code_hash = cb.query_cell();
+            cb.copy_table_lookup(
+                cb.curr.state.call_id.expr(),
+                CopyDataType::Memory.expr(),
+                code_hash.expr(),
+                CopyDataType::Bytecode.expr(),
+                range.offset(),
+                range.address(),
+                0.expr(),
+                range.length(),
+                0.expr(),
+                copy_rw_increase.expr(),
+            );
+
+            // The returned code doesn't start with 0xEF once EIP-3541 is
+            // enabled, otherwise ErrorInvalidCreationCode happens instead.
+            let first_byte = cb.query_cell();
+            cb.bytecode_lookup(code_hash.expr(), 0.expr(), 1.expr(), first_byte.expr());
+            let is_first_byte_ef = IsEqualGadget::construct(cb, first_byte.expr(), 0xef.expr());
+            let is_eip3541_enabled = cb.query_bool();
+            cb.hardfork_param_lookup(HardforkParam::IsEip3541Enabled, is_eip3541_enabled.expr());
+            cb.require_zero(
+                "Returned code doesn't start with 0xEF if EIP-3541 is enabled",
+                is_eip3541_enabled.expr() * is_first_byte_ef.expr(),
+            );
+
+            let [caller_id, address] = [
+                CallContextFieldTag::CallerId,
+                CallContextFieldTag::CalleeAddress,
+            ]
+            .map(|tag| cb.call_context(None, tag));
+            let mut reversion_info = cb.reversion_info_read(None);
+
+            let empty_code_hash_rlc = Word::random_linear_combine_expr(
+                (*EMPTY_HASH_LE).map(|byte| byte.expr()),
+                cb.power_of_randomness(),
+            );
+
+            cb.account_write(
+                address.expr(),
+                AccountFieldTag::CodeHash,
+                code_hash.expr(),
+                empty_code_hash_rlc,
+                Some(&mut reversion_info),
+            );
+
+            (
+                caller_id,
+                address,
+                reversion_info,
+                code_hash,
+                first_byte,
+                is_first_byte_ef,
+                is_eip3541_enabled,
+            )
+        });
+
         // Case B in the specs.
         cb.condition(is_root.expr(), |cb| {
+            // The code deposit cost of a creation transaction is charged here,
+            // since there's no caller context to restore.
+            let code_deposit_cost = is_create.clone()
+                * is_success.expr()
+                * GasCost::CODE_DEPOSIT_BYTE_COST.expr()
+                * range.length();
             cb.require_next_state(ExecutionState::EndTx);
             cb.call_context_lookup(
                 false.expr(),
@@ -154,7 +221,7 @@ impl<F: Field> ExecutionGadget<F> for ReturnRevertGadget<F> {
                         + not::expr(is_success.expr())
                             * cb.curr.state.reversible_write_counter.expr(),
                 ),
-                gas_left: Delta(-memory_expansion.gas_cost()),
+                gas_left: Delta(-memory_expansion.gas_cost() - code_deposit_cost),
                 reversible_write_counter: To(0.expr()),
                 memory_word_size: To(0.expr()),
                 ..StepStateTransition::default()
@@ -234,6 +301,11 @@ impl<F: Field> ExecutionGadget<F> for ReturnRevertGadget<F> {
             restore_context,
             memory_expansion,
             code_hash,
+            code_size_not_exceeded,
+            insufficient_gas,
+            first_byte,
+            is_first_byte_ef,
+            is_eip3541_enabled,
             address,
             caller_id,
             reversion_info,
@@ -249,6 +321,7 @@ impl<F: Field> ExecutionGadget<F> for ReturnRevertGadget<F> {
         call: &Call,
         step: &ExecStep,
     ) -> Result<(), Error> {
+        let randomness = region.evm_word_randomness();
         self.opcode.assign(
             region,
             offset,
@@ -258,9 +331,23 @@ impl<F: Field> ExecutionGadget<F> for ReturnRevertGadget<F> {
         let [memory_offset, length] = [0, 1].map(|i| block.rws[step.rw_indices[i]].stack_value());
         let range = self
             .range
-            .assign(region, offset, memory_offset, length, block.randomness)?;
-        self.memory_expansion
-            .assign(region, offset, step.memory_word_size(), [range])?;
+            .assign(region, offset, memory_offset, length, randomness)?;
+        let (_, memory_expansion_gas_cost) =
+            self.memory_expansion
+                .assign(region, offset, step.memory_word_size(), [range])?;
+
+        self.code_size_not_exceeded.assign(
+            region,
+            offset,
+            F::from(length.as_u64()),
+            F::from(MAX_CODE_SIZE + 1),
+        )?;
+        self.insufficient_gas.assign(
+            region,
+            offset,
+            F::from(step.gas_left - memory_expansion_gas_cost),
+            F::from(GasCost::CODE_DEPOSIT_BYTE_COST.as_u64() * length.as_u64()),
+        )?;
 
         self.is_success
             .assign(region, offset, Value::known(call.is_success.into()))?;
@@ -285,14 +372,30 @@ impl<F: Field> ExecutionGadget<F> for ReturnRevertGadget<F> {
             let values: Vec<_> = (3..3 + length.as_usize())
                 .map(|i| block.rws[step.rw_indices[i]].memory_value())
                 .collect();
+            if let Some(&first_byte) = values.first() {
+                self.first_byte
+                    .assign(region, offset, Value::known(F::from(first_byte as u64)))?;
+                self.is_first_byte_ef.assign(
+                    region,
+                    offset,
+                    F::from(first_byte as u64),
+                    F::from(0xef),
+                )?;
+                self.is_eip3541_enabled.assign(
+                    region,
+                    offset,
+                    Value::known(F::from(
+                        block.circuits_params.hardfork.is_eip3541_enabled() as u64
+                    )),
+                )?;
+            }
             let mut code_hash = keccak256(&values);
             code_hash.reverse();
             self.code_hash.assign(
                 region,
                 offset,
                 Value::known(RandomLinearCombination::random_linear_combine(
-                    code_hash,
-                    block.randomness,
+                    code_hash, randomness,
                 )),
             )?;
         }
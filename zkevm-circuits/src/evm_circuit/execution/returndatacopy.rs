@@ -173,6 +173,7 @@ impl<F: Field> ExecutionGadget<F> for ReturnDataCopyGadget<F> {
         _call: &Call,
         step: &ExecStep,
     ) -> Result<(), Error> {
+        let randomness = region.evm_word_randomness();
         self.same_context.assign_exec_step(region, offset, step)?;
 
         let [dest_offset, data_offset, size] =
@@ -229,7 +230,7 @@ impl<F: Field> ExecutionGadget<F> for ReturnDataCopyGadget<F> {
         // assign the destination memory offset.
         let memory_address =
             self.dst_memory_addr
-                .assign(region, offset, dest_offset, size, block.randomness)?;
+                .assign(region, offset, dest_offset, size, randomness)?;
 
         // assign to gadgets handling memory expansion cost and copying cost.
         let (_, memory_expansion_cost) = self.memory_expansion.assign(
@@ -0,0 +1,268 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        step::ExecutionState,
+        table::{FixedTableTag, Lookup},
+        util::{
+            self,
+            common_gadget::SameContextGadget,
+            constraint_builder::{ConstraintBuilder, StepStateTransition, Transition::Delta},
+            from_bytes,
+            math_gadget::{AbsWordGadget, IsZeroGadget, LtWordGadget, MulAddWordsGadget},
+            sum, CachedRegion,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use bus_mapping::evm::OpcodeId;
+use eth_types::{Field, ToLittleEndian, U256};
+use halo2_proofs::plonk::Error;
+
+/// SarGadget verifies opcode SAR.
+/// An arithmetic right shift of a negative `a` equals `!((!a) >> shift)`, so
+/// with `a' = a` when `a >= 0` and `a' = !a` when `a < 0`, verify
+/// a' / (2^shift) == q' and push == q' (or !q' when `a < 0`), where the
+/// divisor is 0 and q' is 0 when shift >= 256.
+#[derive(Clone, Debug)]
+pub(crate) struct SarGadget<F> {
+    same_context: SameContextGadget<F>,
+    /// Shift word
+    shift: util::Word<F>,
+    /// Popped value, which also gives its sign
+    a: AbsWordGadget<F>,
+    /// Pushed value
+    push: util::Word<F>,
+    /// a' as a non-negative word
+    dividend: util::Word<F>,
+    /// 2^shift when shift < 256, 0 otherwise
+    divisor: util::Word<F>,
+    quotient: util::Word<F>,
+    remainder: util::Word<F>,
+    /// Gadget that verifies quotient * divisor + remainder = dividend
+    mul_add_words: MulAddWordsGadget<F>,
+    /// Check if shift < 256, i.e. all but its first byte are zero
+    shift_lt_256: IsZeroGadget<F>,
+    /// Check if remainder < divisor when shift < 256
+    remainder_lt_divisor: LtWordGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for SarGadget<F> {
+    const NAME: &'static str = "SAR";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::SAR;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+
+        let shift = cb.query_word();
+        let a = AbsWordGadget::construct(cb);
+        let push = cb.query_word();
+        let dividend = cb.query_word();
+        let divisor = cb.query_word();
+        let quotient = cb.query_word();
+        let remainder = cb.query_word();
+
+        cb.stack_pop(shift.expr());
+        cb.stack_pop(a.x().expr());
+        cb.stack_push(push.expr());
+
+        // Flip every byte of the value and of the result when the value is
+        // negative, which turns the arithmetic shift into a logical one.
+        let is_neg = a.is_neg().expr();
+        for (a_byte, dividend_byte) in a.x().cells.iter().zip(dividend.cells.iter()) {
+            cb.require_equal(
+                "dividend byte == a byte, or its complement when a < 0",
+                dividend_byte.expr(),
+                a_byte.expr() + is_neg.clone() * (255.expr() - 2.expr() * a_byte.expr()),
+            );
+        }
+        for (quotient_byte, push_byte) in quotient.cells.iter().zip(push.cells.iter()) {
+            cb.require_equal(
+                "push byte == quotient byte, or its complement when a < 0",
+                push_byte.expr(),
+                quotient_byte.expr()
+                    + is_neg.clone() * (255.expr() - 2.expr() * quotient_byte.expr()),
+            );
+        }
+
+        let mul_add_words =
+            MulAddWordsGadget::construct(cb, [&quotient, &divisor, &remainder, &dividend]);
+        cb.require_zero("overflow == 0", mul_add_words.overflow());
+
+        let shift_lt_256 = IsZeroGadget::construct(cb, sum::expr(&shift.cells[1..]));
+        let remainder_lt_divisor = LtWordGadget::construct(cb, &remainder, &divisor);
+
+        // When shift < 256, constrain divisor_lo == 2^shift when shift < 128,
+        // and divisor_hi == 2^(shift - 128) otherwise.
+        cb.condition(shift_lt_256.expr(), |cb| {
+            cb.add_lookup(
+                "Pow2 lookup of shift, divisor_lo and divisor_hi",
+                Lookup::Fixed {
+                    tag: FixedTableTag::Pow2.expr(),
+                    values: [
+                        shift.cells[0].expr(),
+                        from_bytes::expr(&divisor.cells[..16]),
+                        from_bytes::expr(&divisor.cells[16..]),
+                    ],
+                },
+            );
+            cb.require_equal(
+                "remainder < divisor when shift < 256",
+                remainder_lt_divisor.expr(),
+                1.expr(),
+            );
+        });
+
+        // When shift >= 256, every bit is shifted out, so the result is 0 for
+        // a >= 0 and -1 for a < 0.
+        cb.condition(1.expr() - shift_lt_256.expr(), |cb| {
+            cb.require_zero("divisor == 0 when shift >= 256", sum::expr(&divisor.cells));
+            cb.require_zero(
+                "quotient == 0 when shift >= 256",
+                sum::expr(&quotient.cells),
+            );
+        });
+
+        let step_state_transition = StepStateTransition {
+            rw_counter: Delta(3.expr()),
+            program_counter: Delta(1.expr()),
+            stack_pointer: Delta(1.expr()),
+            gas_left: Delta(-OpcodeId::SAR.constant_gas_cost().expr()),
+            ..Default::default()
+        };
+
+        let same_context = SameContextGadget::construct(cb, opcode, step_state_transition);
+
+        Self {
+            same_context,
+            shift,
+            a,
+            push,
+            dividend,
+            divisor,
+            quotient,
+            remainder,
+            mul_add_words,
+            shift_lt_256,
+            remainder_lt_divisor,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        _: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.same_context.assign_exec_step(region, offset, step)?;
+        let indices = [step.rw_indices[0], step.rw_indices[1], step.rw_indices[2]];
+        let [shift, a, push] = indices.map(|idx| block.rws[idx].stack_value());
+
+        let is_neg = 127 < a.to_le_bytes()[31];
+        let a_abs = if is_neg {
+            U256::MAX - a + U256::from(1)
+        } else {
+            a
+        };
+        let dividend = if is_neg { !a } else { a };
+        let quotient = if is_neg { !push } else { push };
+        let (divisor, remainder) = if shift < U256::from(256) {
+            let divisor = U256::from(1) << shift.as_usize();
+            (divisor, dividend - quotient * divisor)
+        } else {
+            (U256::zero(), dividend)
+        };
+
+        self.shift
+            .assign(region, offset, Some(shift.to_le_bytes()))?;
+        self.a.assign(region, offset, a, a_abs)?;
+        self.push.assign(region, offset, Some(push.to_le_bytes()))?;
+        self.dividend
+            .assign(region, offset, Some(dividend.to_le_bytes()))?;
+        self.divisor
+            .assign(region, offset, Some(divisor.to_le_bytes()))?;
+        self.quotient
+            .assign(region, offset, Some(quotient.to_le_bytes()))?;
+        self.remainder
+            .assign(region, offset, Some(remainder.to_le_bytes()))?;
+        self.mul_add_words
+            .assign(region, offset, [quotient, divisor, remainder, dividend])?;
+        let shift_hi_sum = (1..32).fold(0, |acc, idx| acc + shift.byte(idx) as u64);
+        self.shift_lt_256
+            .assign(region, offset, F::from(shift_hi_sum))?;
+        self.remainder_lt_divisor
+            .assign(region, offset, remainder, divisor)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        evm_circuit::test::{rand_range, rand_word},
+        test_util::run_test_circuits,
+    };
+    use eth_types::{bytecode, Word};
+    use mock::TestContext;
+
+    fn test_ok(a: Word, shift: Word) {
+        let bytecode = bytecode! {
+            PUSH32(a)
+            PUSH32(shift)
+            #[start]
+            SAR
+            STOP
+        };
+
+        assert_eq!(
+            run_test_circuits(
+                TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode).unwrap(),
+                None
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn sar_gadget_simple() {
+        test_ok(0x8000.into(), 8.into());
+        test_ok(0xABCD.into(), 0.into());
+        test_ok(0x1234.into(), 7.into());
+        test_ok(0x8765.into(), 255.into());
+        test_ok(0xFFFF.into(), 256.into());
+        test_ok(0x12345.into(), (256 + 8 + 1).into());
+    }
+
+    #[test]
+    fn sar_gadget_negative() {
+        let max_word = Word::MAX;
+        // -1 stays -1 for any shift
+        test_ok(max_word, 0.into());
+        test_ok(max_word, 63.into());
+        test_ok(max_word, 255.into());
+        test_ok(max_word, 256.into());
+        test_ok(max_word, max_word);
+        // -(1 << 255)
+        let min_word = Word::from(1) << 255;
+        test_ok(min_word, 1.into());
+        test_ok(min_word, 128.into());
+        test_ok(min_word, 129.into());
+        test_ok(min_word, 255.into());
+        test_ok(min_word, 0x1000.into());
+        // -0x1234, which is not a multiple of the divisor
+        test_ok(max_word - Word::from(0x1233), 4.into());
+    }
+
+    #[test]
+    fn sar_gadget_rand() {
+        test_ok(rand_word(), Word::from(rand_range(0..256u64)));
+        test_ok(
+            rand_word() | (Word::from(1) << 255),
+            Word::from(rand_range(0..256u64)),
+        );
+        test_ok(rand_word(), rand_word());
+    }
+}
@@ -66,6 +66,7 @@ impl<F: Field> ExecutionGadget<F> for SelfbalanceGadget<F> {
         call: &Call,
         step: &ExecStep,
     ) -> Result<(), Error> {
+        let randomness = region.evm_word_randomness();
         self.same_context.assign_exec_step(region, offset, step)?;
 
         self.callee_address.assign(
@@ -84,7 +85,7 @@ impl<F: Field> ExecutionGadget<F> for SelfbalanceGadget<F> {
             offset,
             Value::known(Word::random_linear_combine(
                 self_balance.to_le_bytes(),
-                block.randomness,
+                randomness,
             )),
         )?;
 
@@ -0,0 +1,488 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::N_BYTES_ACCOUNT_ADDRESS,
+        step::ExecutionState,
+        table::HardforkParam,
+        util::{
+            common_gadget::{RestoreContextGadget, UpdateBalanceGadget},
+            constraint_builder::{
+                ConstraintBuilder, ReversionInfo, StepStateTransition,
+                Transition::{Delta, Same},
+            },
+            from_bytes,
+            math_gadget::{IsEqualGadget, IsZeroGadget},
+            not, sum, CachedRegion, Cell, RandomLinearCombination, Word,
+        },
+        witness::{Block, Call, ExecStep, Rw, Transaction},
+    },
+    table::{AccountFieldTag, CallContextFieldTag},
+    util::Expr,
+};
+use eth_types::{evm_types::GasCost, Field, ToAddress, ToLittleEndian, ToScalar, U256};
+use halo2_proofs::{circuit::Value, plonk::Error};
+
+/// Gadget for SELFDESTRUCT. The whole balance of the current account is moved
+/// to the beneficiary and the account is marked as destructed in the rw table.
+/// The first destruction of an account in a tx is refunded, which is removed
+/// since [EIP-3529](https://eips.ethereum.org/EIPS/eip-3529), in which case
+/// the tx refund isn't written.
+#[derive(Clone, Debug)]
+pub(crate) struct SelfdestructGadget<F> {
+    opcode: Cell<F>,
+    tx_id: Cell<F>,
+    reversion_info: ReversionInfo<F>,
+    address: Cell<F>,
+    beneficiary: RandomLinearCombination<F, N_BYTES_ACCOUNT_ADDRESS>,
+    is_warm_prev: Cell<F>,
+    beneficiary_exists: Cell<F>,
+    beneficiary_code_hash: Cell<F>,
+    value: Word<F>,
+    value_is_zero: IsZeroGadget<F>,
+    is_beneficiary_self: IsEqualGadget<F>,
+    beneficiary_balance: UpdateBalanceGadget<F, 2, true>,
+    is_destructed_prev: Cell<F>,
+    selfdestruct_refund: Cell<F>,
+    selfdestruct_refund_is_zero: IsZeroGadget<F>,
+    tx_refund_prev: Cell<F>,
+    restore_context: RestoreContextGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for SelfdestructGadget<F> {
+    const NAME: &'static str = "SELFDESTRUCT";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::SELFDESTRUCT;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        cb.opcode_lookup(opcode.expr(), 1.expr());
+
+        let tx_id = cb.call_context(None, CallContextFieldTag::TxId);
+        let mut reversion_info = cb.reversion_info_read(None);
+        let address = cb.call_context(None, CallContextFieldTag::CalleeAddress);
+        // Call ends with SELFDESTRUCT must be successful
+        cb.call_context_lookup(false.expr(), None, CallContextFieldTag::IsSuccess, 1.expr());
+
+        let beneficiary = cb.query_rlc();
+        cb.stack_pop(beneficiary.expr());
+        let beneficiary_address = from_bytes::expr(&beneficiary.cells);
+
+        let is_warm_prev = cb.query_bool();
+        cb.account_access_list_write(
+            tx_id.expr(),
+            beneficiary_address.clone(),
+            1.expr(),
+            is_warm_prev.expr(),
+            Some(&mut reversion_info),
+        );
+
+        let beneficiary_exists = cb.query_bool();
+        let beneficiary_code_hash = cb.query_cell();
+        cb.condition(beneficiary_exists.expr(), |cb| {
+            cb.account_read(
+                beneficiary_address.clone(),
+                AccountFieldTag::CodeHash,
+                beneficiary_code_hash.expr(),
+            );
+        });
+        cb.condition(not::expr(beneficiary_exists.expr()), |cb| {
+            cb.account_read(
+                beneficiary_address.clone(),
+                AccountFieldTag::NonExisting,
+                0.expr(),
+            );
+        });
+
+        // The beneficiary is credited before the current account is cleared, so
+        // the balance is burnt when the beneficiary is the current account
+        // itself.
+        let value = cb.query_word();
+        let value_is_zero = IsZeroGadget::construct(cb, sum::expr(&value.cells));
+        let is_beneficiary_self =
+            IsEqualGadget::construct(cb, beneficiary_address.clone(), address.expr());
+        let beneficiary_balance = UpdateBalanceGadget::construct(
+            cb,
+            beneficiary_address,
+            vec![value.clone()],
+            Some(&mut reversion_info),
+        );
+        cb.condition(is_beneficiary_self.expr(), |cb| {
+            cb.require_equal(
+                "value is the balance of the beneficiary when it's the current account",
+                beneficiary_balance.balance_prev().expr(),
+                value.expr(),
+            );
+        });
+        cb.account_write(
+            address.expr(),
+            AccountFieldTag::Balance,
+            0.expr(),
+            is_beneficiary_self.expr() * beneficiary_balance.balance().expr()
+                + not::expr(is_beneficiary_self.expr()) * value.expr(),
+            Some(&mut reversion_info),
+        );
+
+        let is_destructed_prev = cb.query_bool();
+        cb.account_destructed_write(
+            tx_id.expr(),
+            address.expr(),
+            1.expr(),
+            is_destructed_prev.expr(),
+            Some(&mut reversion_info),
+        );
+
+        let selfdestruct_refund = cb.query_cell();
+        cb.hardfork_param_lookup(
+            HardforkParam::SelfdestructRefund,
+            selfdestruct_refund.expr(),
+        );
+        let selfdestruct_refund_is_zero = IsZeroGadget::construct(cb, selfdestruct_refund.expr());
+        let has_refund = not::expr(selfdestruct_refund_is_zero.expr());
+        let tx_refund_prev = cb.query_cell();
+        cb.condition(has_refund.clone(), |cb| {
+            cb.tx_refund_write(
+                tx_id.expr(),
+                tx_refund_prev.expr()
+                    + not::expr(is_destructed_prev.expr()) * selfdestruct_refund.expr(),
+                tx_refund_prev.expr(),
+                Some(&mut reversion_info),
+            );
+        });
+
+        let gas_cost = GasCost::SELFDESTRUCT.expr()
+            + not::expr(is_warm_prev.expr()) * GasCost::COLD_ACCOUNT_ACCESS.expr()
+            // Sending a non-zero balance to an empty account creates it.
+            + not::expr(value_is_zero.expr())
+                * not::expr(beneficiary_exists.expr())
+                * GasCost::NEW_ACCOUNT.expr();
+
+        let is_to_end_tx = cb.next.execution_state_selector([ExecutionState::EndTx]);
+        cb.require_equal(
+            "Go to EndTx only when is_root",
+            cb.curr.state.is_root.expr(),
+            is_to_end_tx,
+        );
+
+        // When it's a root call
+        cb.condition(cb.curr.state.is_root.expr(), |cb| {
+            cb.require_step_state_transition(StepStateTransition {
+                call_id: Same,
+                rw_counter: Delta(11.expr() + has_refund.clone()),
+                gas_left: Delta(-gas_cost.clone()),
+                reversible_write_counter: Delta(4.expr() + has_refund.clone()),
+                ..StepStateTransition::any()
+            });
+        });
+
+        // When it's an internal call
+        let restore_context = cb.condition(not::expr(cb.curr.state.is_root.expr()), |cb| {
+            RestoreContextGadget::construct(
+                cb,
+                true.expr(),
+                0.expr(),
+                0.expr(),
+                0.expr(),
+                gas_cost,
+                4.expr() + has_refund,
+            )
+        });
+
+        Self {
+            opcode,
+            tx_id,
+            reversion_info,
+            address,
+            beneficiary,
+            is_warm_prev,
+            beneficiary_exists,
+            beneficiary_code_hash,
+            value,
+            value_is_zero,
+            is_beneficiary_self,
+            beneficiary_balance,
+            is_destructed_prev,
+            selfdestruct_refund,
+            selfdestruct_refund_is_zero,
+            tx_refund_prev,
+            restore_context,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        tx: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let randomness = region.evm_word_randomness();
+        let opcode = step.opcode.unwrap();
+        self.opcode
+            .assign(region, offset, Value::known(F::from(opcode.as_u64())))?;
+
+        self.tx_id
+            .assign(region, offset, Value::known(F::from(tx.id as u64)))?;
+        self.reversion_info.assign(
+            region,
+            offset,
+            call.rw_counter_end_of_reversion,
+            call.is_persistent,
+        )?;
+        self.address.assign(
+            region,
+            offset,
+            Value::known(call.callee_address.to_scalar().unwrap()),
+        )?;
+
+        let beneficiary = block.rws[step.rw_indices[5]].stack_value().to_address();
+        let mut beneficiary_bytes = beneficiary.0;
+        beneficiary_bytes.reverse();
+        self.beneficiary
+            .assign(region, offset, Some(beneficiary_bytes))?;
+
+        let (_, is_warm_prev) = block.rws[step.rw_indices[6]].tx_access_list_value_pair();
+        self.is_warm_prev
+            .assign(region, offset, Value::known(F::from(is_warm_prev)))?;
+
+        let (beneficiary_code_hash, beneficiary_exists) = match block.rws[step.rw_indices[7]] {
+            Rw::Account {
+                field_tag: AccountFieldTag::CodeHash,
+                value,
+                ..
+            } => (value, true),
+            Rw::Account {
+                field_tag: AccountFieldTag::NonExisting,
+                ..
+            } => (U256::zero(), false),
+            _ => unreachable!(),
+        };
+        self.beneficiary_exists.assign(
+            region,
+            offset,
+            Value::known(F::from(beneficiary_exists)),
+        )?;
+        self.beneficiary_code_hash.assign(
+            region,
+            offset,
+            Value::known(RandomLinearCombination::random_linear_combine(
+                beneficiary_code_hash.to_le_bytes(),
+                randomness,
+            )),
+        )?;
+
+        let (beneficiary_balance, beneficiary_balance_prev) =
+            block.rws[step.rw_indices[8]].account_value_pair();
+        let value = beneficiary_balance - beneficiary_balance_prev;
+        self.value
+            .assign(region, offset, Some(value.to_le_bytes()))?;
+        self.value_is_zero
+            .assign(region, offset, sum::value(&value.to_le_bytes()))?;
+        self.is_beneficiary_self.assign(
+            region,
+            offset,
+            beneficiary.to_scalar().unwrap(),
+            call.callee_address.to_scalar().unwrap(),
+        )?;
+        self.beneficiary_balance.assign(
+            region,
+            offset,
+            beneficiary_balance_prev,
+            vec![value],
+            beneficiary_balance,
+        )?;
+
+        let (_, is_destructed_prev) =
+            block.rws[step.rw_indices[10]].account_destructed_value_pair();
+        self.is_destructed_prev.assign(
+            region,
+            offset,
+            Value::known(F::from(is_destructed_prev)),
+        )?;
+
+        let selfdestruct_refund = block.circuits_params.hardfork.selfdestruct_refund();
+        self.selfdestruct_refund.assign(
+            region,
+            offset,
+            Value::known(F::from(selfdestruct_refund.as_u64())),
+        )?;
+        self.selfdestruct_refund_is_zero.assign(
+            region,
+            offset,
+            F::from(selfdestruct_refund.as_u64()),
+        )?;
+        let has_refund = selfdestruct_refund.as_u64() > 0;
+        if has_refund {
+            let (_, tx_refund_prev) = block.rws[step.rw_indices[11]].tx_refund_value_pair();
+            self.tx_refund_prev
+                .assign(region, offset, Value::known(F::from(tx_refund_prev)))?;
+        }
+
+        if !call.is_root {
+            self.restore_context.assign(
+                region,
+                offset,
+                block,
+                call,
+                step,
+                11 + has_refund as usize,
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::{run_test_circuits, run_test_circuits_with_params};
+    use bus_mapping::circuit_input_builder::CircuitsParams;
+    use eth_types::{address, bytecode, evm_types::Hardfork, Address, Bytecode, ToWord, Word};
+    use mock::{eth, TestContext};
+
+    const CALLEE_ADDRESS: Address = Address::repeat_byte(0xff);
+
+    fn selfdestruct_code(beneficiary: Address) -> Bytecode {
+        bytecode! {
+            PUSH20(beneficiary.to_word())
+            SELFDESTRUCT
+        }
+    }
+
+    fn test_root_ok(beneficiary: Address, balance: Word, beneficiary_balance: Option<Word>) {
+        let ctx = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x000000000000000000000000000000000000cafe"))
+                    .balance(eth(10));
+                accs[1]
+                    .address(CALLEE_ADDRESS)
+                    .balance(balance)
+                    .code(selfdestruct_code(beneficiary));
+                // Only add the beneficiary when it's supposed to exist and isn't the
+                // current account.
+                if let Some(beneficiary_balance) = beneficiary_balance {
+                    accs[2].address(beneficiary).balance(beneficiary_balance);
+                } else {
+                    accs[2]
+                        .address(address!("0x0000000000000000000000000000000000000010"))
+                        .balance(eth(1));
+                }
+            },
+            |mut txs, accs| {
+                txs[0].from(accs[0].address).to(accs[1].address);
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+
+    fn test_internal_ok(beneficiary: Address, is_persistent: bool) {
+        test_internal_ok_with_hardfork(beneficiary, is_persistent, Hardfork::default());
+    }
+
+    fn test_internal_ok_with_hardfork(
+        beneficiary: Address,
+        is_persistent: bool,
+        hardfork: Hardfork,
+    ) {
+        let caller_code = {
+            let mut code = bytecode! {
+                PUSH1(0) // retLength
+                PUSH1(0) // retOffset
+                PUSH1(0) // argsLength
+                PUSH1(0) // argsOffset
+                PUSH1(0) // value
+                PUSH20(CALLEE_ADDRESS.to_word()) // address
+                PUSH2(0xffff) // gas
+                CALL
+            };
+            if is_persistent {
+                code.write_op(eth_types::evm_types::OpcodeId::STOP);
+            } else {
+                code.append(&bytecode! {
+                    PUSH1(0)
+                    PUSH1(0)
+                    REVERT
+                });
+            }
+            code
+        };
+
+        let ctx = TestContext::<3, 1>::new_with_hardfork(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x000000000000000000000000000000000000cafe"))
+                    .balance(eth(10));
+                accs[1]
+                    .address(address!("0x00000000000000000000000000000000000000fe"))
+                    .balance(eth(1))
+                    .code(caller_code);
+                accs[2]
+                    .address(CALLEE_ADDRESS)
+                    .balance(eth(1))
+                    .code(selfdestruct_code(beneficiary));
+            },
+            |mut txs, accs| {
+                txs[0].from(accs[0].address).to(accs[1].address);
+            },
+            |block, _tx| block.number(0xcafeu64),
+            hardfork,
+        )
+        .unwrap();
+
+        assert_eq!(
+            run_test_circuits_with_params(
+                ctx,
+                None,
+                CircuitsParams {
+                    hardfork,
+                    ..Default::default()
+                }
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn selfdestruct_gadget_root() {
+        let beneficiary = Address::repeat_byte(0xbe);
+        // Beneficiary doesn't exist, so it's created by the transfer.
+        test_root_ok(beneficiary, eth(1), None);
+        // Nothing is transferred, so no account is created.
+        test_root_ok(beneficiary, Word::zero(), None);
+        // Beneficiary exists already.
+        test_root_ok(beneficiary, eth(1), Some(eth(2)));
+    }
+
+    #[test]
+    fn selfdestruct_gadget_to_self() {
+        test_root_ok(CALLEE_ADDRESS, eth(1), None);
+        test_internal_ok(CALLEE_ADDRESS, true);
+    }
+
+    #[test]
+    fn selfdestruct_gadget_internal() {
+        // Beneficiary is warm since the caller is in the access list.
+        test_internal_ok(address!("0x00000000000000000000000000000000000000fe"), true);
+        test_internal_ok(Address::repeat_byte(0xbe), true);
+    }
+
+    #[test]
+    fn selfdestruct_gadget_reverted() {
+        test_internal_ok(Address::repeat_byte(0xbe), false);
+        test_internal_ok(CALLEE_ADDRESS, false);
+    }
+
+    #[test]
+    fn selfdestruct_gadget_refund_berlin() {
+        // The first destruction of the account is refunded before EIP-3529.
+        test_internal_ok_with_hardfork(Address::repeat_byte(0xbe), true, Hardfork::Berlin);
+        test_internal_ok_with_hardfork(Address::repeat_byte(0xbe), false, Hardfork::Berlin);
+    }
+}
@@ -101,6 +101,7 @@ impl<F: Field> ExecutionGadget<F> for SloadGadget<F> {
         call: &Call,
         step: &ExecStep,
     ) -> Result<(), Error> {
+        let randomness = region.evm_word_randomness();
         self.same_context.assign_exec_step(region, offset, step)?;
 
         self.tx_id
@@ -126,18 +127,12 @@ impl<F: Field> ExecutionGadget<F> for SloadGadget<F> {
         self.key.assign(
             region,
             offset,
-            Value::known(Word::random_linear_combine(
-                key.to_le_bytes(),
-                block.randomness,
-            )),
+            Value::known(Word::random_linear_combine(key.to_le_bytes(), randomness)),
         )?;
         self.value.assign(
             region,
             offset,
-            Value::known(Word::random_linear_combine(
-                value.to_le_bytes(),
-                block.randomness,
-            )),
+            Value::known(Word::random_linear_combine(value.to_le_bytes(), randomness)),
         )?;
 
         let (_, committed_value) = block.rws[step.rw_indices[5]].aux_pair();
@@ -146,7 +141,7 @@ impl<F: Field> ExecutionGadget<F> for SloadGadget<F> {
             offset,
             Value::known(Word::random_linear_combine(
                 committed_value.to_le_bytes(),
-                block.randomness,
+                randomness,
             )),
         )?;
 
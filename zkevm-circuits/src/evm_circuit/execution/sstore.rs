@@ -2,6 +2,7 @@ use crate::{
     evm_circuit::{
         execution::ExecutionGadget,
         step::ExecutionState,
+        table::HardforkParam,
         util::{
             common_gadget::SameContextGadget,
             constraint_builder::{
@@ -145,6 +146,7 @@ impl<F: Field> ExecutionGadget<F> for SstoreGadget<F> {
         call: &Call,
         step: &ExecStep,
     ) -> Result<(), Error> {
+        let randomness = region.evm_word_randomness();
         self.same_context.assign_exec_step(region, offset, step)?;
 
         self.tx_id
@@ -172,18 +174,12 @@ impl<F: Field> ExecutionGadget<F> for SstoreGadget<F> {
         self.key.assign(
             region,
             offset,
-            Value::known(Word::random_linear_combine(
-                key.to_le_bytes(),
-                block.randomness,
-            )),
+            Value::known(Word::random_linear_combine(key.to_le_bytes(), randomness)),
         )?;
         self.value.assign(
             region,
             offset,
-            Value::known(Word::random_linear_combine(
-                value.to_le_bytes(),
-                block.randomness,
-            )),
+            Value::known(Word::random_linear_combine(value.to_le_bytes(), randomness)),
         )?;
 
         let (_, value_prev, _, original_value) = block.rws[step.rw_indices[7]].storage_value_aux();
@@ -192,7 +188,7 @@ impl<F: Field> ExecutionGadget<F> for SstoreGadget<F> {
             offset,
             Value::known(Word::random_linear_combine(
                 value_prev.to_le_bytes(),
-                block.randomness,
+                randomness,
             )),
         )?;
         self.original_value.assign(
@@ -200,7 +196,7 @@ impl<F: Field> ExecutionGadget<F> for SstoreGadget<F> {
             offset,
             Value::known(Word::random_linear_combine(
                 original_value.to_le_bytes(),
-                block.randomness,
+                randomness,
             )),
         )?;
 
@@ -220,7 +216,7 @@ impl<F: Field> ExecutionGadget<F> for SstoreGadget<F> {
             value_prev,
             original_value,
             is_warm,
-            block.randomness,
+            randomness,
         )?;
 
         self.tx_refund.assign(
@@ -231,7 +227,12 @@ impl<F: Field> ExecutionGadget<F> for SstoreGadget<F> {
             value,
             value_prev,
             original_value,
-            block.randomness,
+            block
+                .circuits_params
+                .hardfork
+                .sstore_clears_schedule()
+                .as_u64(),
+            randomness,
         )?;
         Ok(())
     }
@@ -364,6 +365,7 @@ pub(crate) struct SstoreTxRefundGadget<F> {
     value: Cell<F>,
     value_prev: Cell<F>,
     original_value: Cell<F>,
+    sstore_clears_schedule: Cell<F>,
     value_prev_is_zero_gadget: IsZeroGadget<F>,
     value_is_zero_gadget: IsZeroGadget<F>,
     original_is_zero_gadget: IsZeroGadget<F>,
@@ -380,6 +382,13 @@ impl<F: Field> SstoreTxRefundGadget<F> {
         value_prev: Cell<F>,
         original_value: Cell<F>,
     ) -> Self {
+        // The refund of clearing a slot depends on the hardfork.
+        let sstore_clears_schedule = cb.query_cell();
+        cb.hardfork_param_lookup(
+            HardforkParam::SstoreClearsSchedule,
+            sstore_clears_schedule.expr(),
+        );
+
         let value_prev_is_zero_gadget = IsZeroGadget::construct(cb, value_prev.expr());
         let value_is_zero_gadget = IsZeroGadget::construct(cb, value.expr());
         let original_is_zero_gadget = IsZeroGadget::construct(cb, original_value.expr());
@@ -417,15 +426,15 @@ impl<F: Field> SstoreTxRefundGadget<F> {
             not::expr(prev_eq_value) * not::expr(original_eq_prev) * (value_prev_is_zero);
 
         let tx_refund_new = tx_refund_old.expr()
-            + delete_slot * GasCost::SSTORE_CLEARS_SCHEDULE.expr()
+            + (delete_slot - recreate_slot) * sstore_clears_schedule.expr()
             + reset_existing * (GasCost::SSTORE_RESET.expr() - GasCost::WARM_ACCESS.expr())
-            + reset_inexistent * (GasCost::SSTORE_SET.expr() - GasCost::WARM_ACCESS.expr())
-            - recreate_slot * (GasCost::SSTORE_CLEARS_SCHEDULE.expr());
+            + reset_inexistent * (GasCost::SSTORE_SET.expr() - GasCost::WARM_ACCESS.expr());
 
         Self {
             value,
             value_prev,
             original_value,
+            sstore_clears_schedule,
             tx_refund_old,
             tx_refund_new,
             value_prev_is_zero_gadget,
@@ -452,8 +461,14 @@ impl<F: Field> SstoreTxRefundGadget<F> {
         value: eth_types::Word,
         value_prev: eth_types::Word,
         original_value: eth_types::Word,
+        sstore_clears_schedule: u64,
         randomness: F,
     ) -> Result<(), Error> {
+        self.sstore_clears_schedule.assign(
+            region,
+            offset,
+            Value::known(F::from(sstore_clears_schedule)),
+        )?;
         self.tx_refund_old
             .assign(region, offset, Value::known(F::from(tx_refund_old)))?;
         self.value.assign(
@@ -511,14 +526,20 @@ impl<F: Field> SstoreTxRefundGadget<F> {
             Word::random_linear_combine(value_prev.to_le_bytes(), randomness),
         )?;
         debug_assert_eq!(
-            calc_expected_tx_refund(tx_refund_old, value, value_prev, original_value),
+            calc_expected_tx_refund(
+                tx_refund_old,
+                value,
+                value_prev,
+                original_value,
+                sstore_clears_schedule
+            ),
             tx_refund
         );
         Ok(())
     }
 }
 
-fn calc_expected_gas_cost(
+pub(crate) fn calc_expected_gas_cost(
     value: eth_types::Word,
     value_prev: eth_types::Word,
     original_value: eth_types::Word,
@@ -547,6 +568,7 @@ fn calc_expected_tx_refund(
     value: eth_types::Word,
     value_prev: eth_types::Word,
     original_value: eth_types::Word,
+    sstore_clears_schedule: u64,
 ) -> u64 {
     // Same clause tags(like "delete slot (2.1.2b)") used as [`makeGasSStoreFunc` in go-ethereum](https://github.com/ethereum/go-ethereum/blob/9fd8825d5a196edde6d8ef81382979875145b346/core/vm/operations_acl.go#L27)
     // Control flow of this function try to follow `makeGasSStoreFunc` for better
@@ -573,11 +595,11 @@ fn calc_expected_tx_refund(
         if !original_value.is_zero() {
             if value_prev.is_zero() {
                 // recreate slot (2.2.1.1)
-                tx_refund_new -= GasCost::SSTORE_CLEARS_SCHEDULE.as_u64()
+                tx_refund_new -= sstore_clears_schedule
             }
             if value.is_zero() {
                 // delete slot (2.2.1.2)
-                tx_refund_new += GasCost::SSTORE_CLEARS_SCHEDULE.as_u64()
+                tx_refund_new += sstore_clears_schedule
             }
         }
 
@@ -599,9 +621,10 @@ fn calc_expected_tx_refund(
 #[cfg(test)]
 mod test {
 
-    use crate::test_util::{run_test_circuits, BytecodeTestConfig};
+    use crate::test_util::{run_test_circuits_with_params, BytecodeTestConfig};
 
-    use eth_types::{bytecode, Word};
+    use bus_mapping::circuit_input_builder::CircuitsParams;
+    use eth_types::{bytecode, evm_types::Hardfork, Word};
     use mock::{test_ctx::helpers::tx_from_1_to_0, TestContext, MOCK_ACCOUNTS};
 
     #[test]
@@ -665,7 +688,40 @@ mod test {
         );
     }
 
+    #[test]
+    fn sstore_gadget_delete_slot_berlin() {
+        // The refund of clearing a slot is higher before EIP-3529
+        test_ok_with_hardfork(
+            0x030201.into(),
+            0x0.into(),
+            0x060505.into(),
+            0x060506.into(),
+            Hardfork::Berlin,
+        );
+    }
+
+    #[test]
+    fn sstore_gadget_recreate_slot_berlin() {
+        test_ok_with_hardfork(
+            0x030201.into(),
+            0x060504.into(),
+            0x0.into(),
+            0x060506.into(),
+            Hardfork::Berlin,
+        );
+    }
+
     fn test_ok(key: Word, value: Word, value_prev: Word, original_value: Word) {
+        test_ok_with_hardfork(key, value, value_prev, original_value, Hardfork::default());
+    }
+
+    fn test_ok_with_hardfork(
+        key: Word,
+        value: Word,
+        value_prev: Word,
+        original_value: Word,
+        hardfork: Hardfork,
+    ) {
         // Here we use two bytecodes to test both is_persistent(STOP) or not(REVERT)
         // Besides, in bytecode we use two SSTOREs,
         // the first SSTORE is used to test cold,  and the second is used to test warm
@@ -690,7 +746,7 @@ mod test {
             REVERT
         };
         for bytecode in [bytecode_success, bytecode_failure] {
-            let ctx = TestContext::<2, 1>::new(
+            let ctx = TestContext::<2, 1>::new_with_hardfork(
                 None,
                 |accs| {
                     accs[0]
@@ -704,13 +760,24 @@ mod test {
                 },
                 tx_from_1_to_0,
                 |block, _txs| block,
+                hardfork,
             )
             .unwrap();
             let test_config = BytecodeTestConfig {
                 enable_state_circuit_test: true,
                 ..Default::default()
             };
-            assert_eq!(run_test_circuits(ctx, Some(test_config),), Ok(()));
+            assert_eq!(
+                run_test_circuits_with_params(
+                    ctx,
+                    Some(test_config),
+                    CircuitsParams {
+                        hardfork,
+                        ..Default::default()
+                    }
+                ),
+                Ok(())
+            );
         }
     }
 }
@@ -67,6 +67,7 @@ impl<F: Field> ExecutionGadget<F> for SwapGadget<F> {
         _: &Call,
         step: &ExecStep,
     ) -> Result<(), Error> {
+        let randomness = region.evm_word_randomness();
         self.same_context.assign_exec_step(region, offset, step)?;
 
         for (cell, value) in self.values.iter().zip(
@@ -77,10 +78,7 @@ impl<F: Field> ExecutionGadget<F> for SwapGadget<F> {
             cell.assign(
                 region,
                 offset,
-                Value::known(Word::random_linear_combine(
-                    value.to_le_bytes(),
-                    block.randomness,
-                )),
+                Value::known(Word::random_linear_combine(value.to_le_bytes(), randomness)),
             )?;
         }
 
@@ -24,6 +24,8 @@ pub(crate) const LOOKUP_CONFIG: &[(Table, usize)] = &[
     (Table::Copy, 1),
     (Table::Keccak, 1),
     (Table::Exp, 1),
+    (Table::Sig, 1),
+    (Table::Bloom, 1),
 ];
 
 /// Maximum number of bytes that an integer can fit in field without wrapping
@@ -3,12 +3,12 @@ use crate::{
     evm_circuit::{
         param::{MAX_STEP_HEIGHT, STEP_STATE_HEIGHT, STEP_WIDTH},
         util::{Cell, RandomLinearCombination},
-        witness::{Block, Call, ExecStep},
+        witness::{Call, ExecStep},
     },
     util::Expr,
 };
-use bus_mapping::evm::OpcodeId;
-use eth_types::ToLittleEndian;
+use bus_mapping::{evm::OpcodeId, precompile::PrecompileCalls};
+use eth_types::{evm_types::Hardfork, ToLittleEndian};
 use halo2_proofs::{
     arithmetic::FieldExt,
     circuit::Value,
@@ -23,7 +23,9 @@ use strum_macros::EnumIter;
 pub enum ExecutionState {
     // Internal state
     BeginTx,
+    BeginTxAccessList,
     EndTx,
+    BlockReward,
     EndBlock,
     // Opcode successful cases
     STOP,
@@ -75,15 +77,25 @@ pub enum ExecutionState {
     MSIZE,
     GAS,
     JUMPDEST,
-    PUSH, // PUSH1, PUSH2, ..., PUSH32
-    DUP,  // DUP1, DUP2, ..., DUP16
-    SWAP, // SWAP1, SWAP2, ..., SWAP16
-    LOG,  // LOG0, LOG1, ..., LOG4
-    CREATE,
+    PUSH0,
+    PUSH,          // PUSH1, PUSH2, ..., PUSH32
+    DUP,           // DUP1, DUP2, ..., DUP16
+    SWAP,          // SWAP1, SWAP2, ..., SWAP16
+    LOG,           // LOG0, LOG1, ..., LOG4
+    CREATE,        // CREATE, CREATE2
     CALL_OP,       // CALL, CALLCODE, DELEGATECALL, STATICCALL
     RETURN_REVERT, // RETURN, REVERT
-    CREATE2,
     SELFDESTRUCT,
+    // Precompiled contract calls
+    PrecompileEcRecover,
+    PrecompileSha256,
+    PrecompileRipemd160,
+    PrecompileIdentity,
+    PrecompileBigModExp,
+    PrecompileBn256Add,
+    PrecompileBn256ScalarMul,
+    PrecompileBn256Pairing,
+    PrecompileBlake2F,
     // Error cases
     ErrorInvalidOpcode,
     ErrorStack,
@@ -121,6 +133,22 @@ impl Default for ExecutionState {
     }
 }
 
+impl From<PrecompileCalls> for ExecutionState {
+    fn from(precompile: PrecompileCalls) -> Self {
+        match precompile {
+            PrecompileCalls::ECRecover => Self::PrecompileEcRecover,
+            PrecompileCalls::Sha256 => Self::PrecompileSha256,
+            PrecompileCalls::Ripemd160 => Self::PrecompileRipemd160,
+            PrecompileCalls::Identity => Self::PrecompileIdentity,
+            PrecompileCalls::Modexp => Self::PrecompileBigModExp,
+            PrecompileCalls::Bn256Add => Self::PrecompileBn256Add,
+            PrecompileCalls::Bn256ScalarMul => Self::PrecompileBn256ScalarMul,
+            PrecompileCalls::Bn256Pairing => Self::PrecompileBn256Pairing,
+            PrecompileCalls::Blake2F => Self::PrecompileBlake2F,
+        }
+    }
+}
+
 impl ExecutionState {
     pub(crate) const fn as_u64(&self) -> u64 {
         *self as u64
@@ -136,9 +164,6 @@ impl ExecutionState {
             Self::ErrorInvalidOpcode
                 | Self::ErrorStack
                 | Self::ErrorWriteProtection
-                | Self::ErrorDepth
-                | Self::ErrorInsufficientBalance
-                | Self::ErrorContractAddressCollision
                 | Self::ErrorInvalidCreationCode
                 | Self::ErrorMaxCodeSizeExceeded
                 | Self::ErrorInvalidJump
@@ -167,6 +192,27 @@ impl ExecutionState {
     pub(crate) fn halts(&self) -> bool {
         matches!(self, Self::STOP | Self::RETURN_REVERT | Self::SELFDESTRUCT)
             || self.halts_in_exception()
+            || self.precompile().is_some()
+    }
+
+    /// Return the precompiled contract executed by this state, if any.
+    pub(crate) fn precompile(&self) -> Option<PrecompileCalls> {
+        match self {
+            Self::PrecompileEcRecover => Some(PrecompileCalls::ECRecover),
+            Self::PrecompileSha256 => Some(PrecompileCalls::Sha256),
+            Self::PrecompileRipemd160 => Some(PrecompileCalls::Ripemd160),
+            Self::PrecompileIdentity => Some(PrecompileCalls::Identity),
+            Self::PrecompileBigModExp => Some(PrecompileCalls::Modexp),
+            Self::PrecompileBn256Add => Some(PrecompileCalls::Bn256Add),
+            Self::PrecompileBn256ScalarMul => Some(PrecompileCalls::Bn256ScalarMul),
+            Self::PrecompileBn256Pairing => Some(PrecompileCalls::Bn256Pairing),
+            Self::PrecompileBlake2F => Some(PrecompileCalls::Blake2F),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn precompiles() -> impl Iterator<Item = Self> {
+        Self::iter().filter(|state| state.precompile().is_some())
     }
 
     pub(crate) fn responsible_opcodes(&self) -> Vec<OpcodeId> {
@@ -222,6 +268,7 @@ impl ExecutionState {
             Self::MSIZE => vec![OpcodeId::MSIZE],
             Self::GAS => vec![OpcodeId::GAS],
             Self::JUMPDEST => vec![OpcodeId::JUMPDEST],
+            Self::PUSH0 => vec![OpcodeId::PUSH0],
             Self::PUSH => vec![
                 OpcodeId::PUSH1,
                 OpcodeId::PUSH2,
@@ -299,7 +346,7 @@ impl ExecutionState {
                 OpcodeId::LOG3,
                 OpcodeId::LOG4,
             ],
-            Self::CREATE => vec![OpcodeId::CREATE],
+            Self::CREATE => vec![OpcodeId::CREATE, OpcodeId::CREATE2],
             Self::CALL_OP => vec![
                 OpcodeId::CALL,
                 OpcodeId::CALLCODE,
@@ -307,11 +354,89 @@ impl ExecutionState {
                 OpcodeId::STATICCALL,
             ],
             Self::RETURN_REVERT => vec![OpcodeId::RETURN, OpcodeId::REVERT],
-            Self::CREATE2 => vec![OpcodeId::CREATE2],
             Self::SELFDESTRUCT => vec![OpcodeId::SELFDESTRUCT],
+            Self::ErrorInvalidOpcode => (0..=u8::MAX)
+                .map(OpcodeId::from)
+                .filter(|opcode| matches!(opcode, OpcodeId::INVALID(_)))
+                .collect(),
+            Self::ErrorWriteProtection => vec![
+                OpcodeId::SSTORE,
+                OpcodeId::CREATE,
+                OpcodeId::CREATE2,
+                OpcodeId::CALL,
+                OpcodeId::SELFDESTRUCT,
+                OpcodeId::LOG0,
+                OpcodeId::LOG1,
+                OpcodeId::LOG2,
+                OpcodeId::LOG3,
+                OpcodeId::LOG4,
+            ],
+            Self::ErrorDepth => vec![
+                OpcodeId::CALL,
+                OpcodeId::CALLCODE,
+                OpcodeId::DELEGATECALL,
+                OpcodeId::STATICCALL,
+                OpcodeId::CREATE,
+                OpcodeId::CREATE2,
+            ],
+            Self::ErrorInsufficientBalance => vec![
+                OpcodeId::CALL,
+                OpcodeId::CALLCODE,
+                OpcodeId::CREATE,
+                OpcodeId::CREATE2,
+            ],
+            Self::ErrorContractAddressCollision => vec![OpcodeId::CREATE, OpcodeId::CREATE2],
+            Self::ErrorInvalidCreationCode => vec![OpcodeId::RETURN],
+            Self::ErrorMaxCodeSizeExceeded => vec![OpcodeId::RETURN],
+            Self::ErrorReturnDataOutOfBound => vec![OpcodeId::RETURNDATACOPY],
+            Self::ErrorOutOfGasMemoryCopy => vec![
+                OpcodeId::CALLDATACOPY,
+                OpcodeId::CODECOPY,
+                OpcodeId::RETURNDATACOPY,
+            ],
+            Self::ErrorOutOfGasAccountAccess => vec![
+                OpcodeId::BALANCE,
+                OpcodeId::EXTCODESIZE,
+                OpcodeId::EXTCODEHASH,
+            ],
+            Self::ErrorOutOfGasCodeStore => vec![OpcodeId::RETURN],
+            Self::ErrorOutOfGasLOG => vec![
+                OpcodeId::LOG0,
+                OpcodeId::LOG1,
+                OpcodeId::LOG2,
+                OpcodeId::LOG3,
+                OpcodeId::LOG4,
+            ],
+            Self::ErrorOutOfGasEXP => vec![OpcodeId::EXP],
+            Self::ErrorOutOfGasSHA3 => vec![OpcodeId::SHA3],
+            Self::ErrorOutOfGasEXTCODECOPY => vec![OpcodeId::EXTCODECOPY],
+            Self::ErrorOutOfGasSLOAD => vec![OpcodeId::SLOAD],
+            Self::ErrorOutOfGasSSTORE => vec![OpcodeId::SSTORE],
+            Self::ErrorOutOfGasCALL => vec![OpcodeId::CALL],
+            Self::ErrorOutOfGasCALLCODE => vec![OpcodeId::CALLCODE],
+            Self::ErrorOutOfGasDELEGATECALL => vec![OpcodeId::DELEGATECALL],
+            Self::ErrorOutOfGasCREATE2 => vec![OpcodeId::CREATE2],
+            Self::ErrorOutOfGasSTATICCALL => vec![OpcodeId::STATICCALL],
+            Self::ErrorOutOfGasSELFDESTRUCT => vec![OpcodeId::SELFDESTRUCT],
             _ => vec![],
         }
     }
+
+    /// Returns the opcodes handled by the execution state in the hardfork,
+    /// where the opcodes which aren't enabled yet are invalid.
+    pub(crate) fn hardfork_responsible_opcodes(&self, hardfork: Hardfork) -> Vec<OpcodeId> {
+        match self {
+            Self::ErrorInvalidOpcode => (0..=u8::MAX)
+                .map(OpcodeId::from)
+                .filter(|opcode| !hardfork.is_opcode_enabled(*opcode))
+                .collect(),
+            _ => self
+                .responsible_opcodes()
+                .into_iter()
+                .filter(|opcode| hardfork.is_opcode_enabled(*opcode))
+                .collect(),
+        }
+    }
 }
 
 /// Dynamic selector that generates expressions of degree 2 to select from N
@@ -504,10 +629,10 @@ impl<F: FieldExt> Step<F> {
         &self,
         region: &mut CachedRegion<'_, '_, F>,
         offset: usize,
-        block: &Block<F>,
         call: &Call,
         step: &ExecStep,
     ) -> Result<(), Error> {
+        let randomness = region.evm_word_randomness();
         self.state
             .execution_state
             .assign(region, offset, step.execution_state as usize)?;
@@ -532,7 +657,7 @@ impl<F: FieldExt> Step<F> {
             offset,
             Value::known(RandomLinearCombination::random_linear_combine(
                 call.code_hash.to_le_bytes(),
-                block.randomness,
+                randomness,
             )),
         )?;
         self.state.program_counter.assign(
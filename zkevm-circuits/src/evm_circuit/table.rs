@@ -2,7 +2,7 @@ use crate::evm_circuit::step::ExecutionState;
 use crate::impl_expr;
 pub use crate::table::TxContextFieldTag;
 use bus_mapping::evm::OpcodeId;
-use eth_types::Field;
+use eth_types::{evm_types::Hardfork, Field};
 use gadgets::util::Expr;
 use halo2_proofs::plonk::Expression;
 use strum::IntoEnumIterator;
@@ -26,11 +26,51 @@ pub enum FixedTableTag {
     Pow2,
     ConstantGasCost,
     OpcodeStack,
+    HardforkParam,
 }
 impl_expr!(FixedTableTag);
 
+/// Parameters of the gas schedule and the rules which change between the
+/// hardforks, looked up in the fixed table with
+/// [`FixedTableTag::HardforkParam`].
+#[derive(Clone, Copy, Debug, EnumIter)]
+pub enum HardforkParam {
+    /// Quotient of the gas used which caps the refund of a transaction.
+    RefundQuotient,
+    /// Refund of a SSTORE clearing a storage slot.
+    SstoreClearsSchedule,
+    /// Refund of the first SELFDESTRUCT of an account in a transaction.
+    SelfdestructRefund,
+    /// Whether the code starting with 0xEF is rejected by
+    /// [EIP-3541](https://eips.ethereum.org/EIPS/eip-3541).
+    IsEip3541Enabled,
+    /// Whether the coinbase is warm at the start of the transactions, as
+    /// specified by [EIP-3651](https://eips.ethereum.org/EIPS/eip-3651).
+    IsEip3651Enabled,
+    /// Whether the initialization code is limited and charged per word by
+    /// [EIP-3860](https://eips.ethereum.org/EIPS/eip-3860).
+    IsEip3860Enabled,
+}
+impl_expr!(HardforkParam);
+
+impl HardforkParam {
+    /// Returns the value of the parameter in the hardfork.
+    pub fn value(&self, hardfork: Hardfork) -> u64 {
+        match self {
+            Self::RefundQuotient => hardfork.max_refund_quotient(),
+            Self::SstoreClearsSchedule => hardfork.sstore_clears_schedule().as_u64(),
+            Self::SelfdestructRefund => hardfork.selfdestruct_refund().as_u64(),
+            Self::IsEip3541Enabled => hardfork.is_eip3541_enabled() as u64,
+            Self::IsEip3651Enabled => hardfork.is_eip3651_enabled() as u64,
+            Self::IsEip3860Enabled => hardfork.is_eip3860_enabled() as u64,
+        }
+    }
+}
+
 impl FixedTableTag {
-    pub fn build<F: Field>(&self) -> Box<dyn Iterator<Item = [F; 4]>> {
+    /// Returns the rows of the table, which depend on the opcodes and the gas
+    /// schedule of the hardfork.
+    pub fn build<F: Field>(&self, hardfork: Hardfork) -> Box<dyn Iterator<Item = [F; 4]>> {
         let tag = F::from(*self as u64);
         match self {
             Self::Zero => Box::new((0..1).map(move |_| [tag, F::zero(), F::zero(), F::zero()])),
@@ -75,7 +115,7 @@ impl FixedTableTag {
             Self::ResponsibleOpcode => {
                 Box::new(ExecutionState::iter().flat_map(move |execution_state| {
                     execution_state
-                        .responsible_opcodes()
+                        .hardfork_responsible_opcodes(hardfork)
                         .into_iter()
                         .map(move |opcode| {
                             [
@@ -97,7 +137,9 @@ impl FixedTableTag {
             })),
             Self::ConstantGasCost => Box::new(
                 OpcodeId::iter()
-                    .filter(move |opcode| opcode.constant_gas_cost().0 > 0)
+                    .filter(move |opcode| {
+                        hardfork.is_opcode_enabled(*opcode) && opcode.constant_gas_cost().0 > 0
+                    })
                     .map(move |opcode| {
                         [
                             tag,
@@ -109,7 +151,9 @@ impl FixedTableTag {
             ),
             Self::OpcodeStack => Box::new(
                 OpcodeId::iter()
-                    .filter(move |opcode| opcode.constant_gas_cost().0 > 0)
+                    .filter(move |opcode| {
+                        hardfork.is_opcode_enabled(*opcode) && opcode.constant_gas_cost().0 > 0
+                    })
                     .map(move |opcode| {
                         [
                             tag,
@@ -119,6 +163,14 @@ impl FixedTableTag {
                         ]
                     }),
             ),
+            Self::HardforkParam => Box::new(HardforkParam::iter().map(move |param| {
+                [
+                    tag,
+                    F::from(param as u64),
+                    F::from(param.value(hardfork)),
+                    F::zero(),
+                ]
+            })),
         }
     }
 }
@@ -134,6 +186,9 @@ pub(crate) enum Table {
     Copy,
     Keccak,
     Exp,
+    Sig,
+    Precompile,
+    Bloom,
 }
 
 #[derive(Clone, Debug)]
@@ -288,6 +343,37 @@ pub(crate) enum Lookup<F> {
         exponent_lo_hi: [Expression<F>; 2],
         exponentiation_lo_hi: [Expression<F>; 2],
     },
+    /// Lookup to sig table.
+    SigTable {
+        /// RLC of the input of ecrecover, right padded with zeros.
+        input_rlc: Expression<F>,
+        /// RLC of the recovered address.
+        address_rlc: Expression<F>,
+    },
+    /// Lookup to precompile table.
+    PrecompileTable {
+        /// Address of the precompiled contract.
+        address: Expression<F>,
+        /// Length of the input.
+        input_length: Expression<F>,
+        /// RLC of the input.
+        input_rlc: Expression<F>,
+        /// Length of the output.
+        output_length: Expression<F>,
+        /// RLC of the output.
+        output_rlc: Expression<F>,
+        /// Gas required for the input.
+        required_gas: Expression<F>,
+        /// Whether the input is well formed.
+        is_valid: Expression<F>,
+    },
+    /// Lookup to bloom table.
+    BloomTable {
+        /// Tag to specify whether the item is an address or a topic.
+        field_tag: Expression<F>,
+        /// Value of the item.
+        value: Expression<F>,
+    },
     /// Conditional lookup enabled by the first element.
     Conditional(Expression<F>, Box<Lookup<F>>),
 }
@@ -308,6 +394,9 @@ impl<F: Field> Lookup<F> {
             Self::CopyTable { .. } => Table::Copy,
             Self::KeccakTable { .. } => Table::Keccak,
             Self::ExpTable { .. } => Table::Exp,
+            Self::SigTable { .. } => Table::Sig,
+            Self::PrecompileTable { .. } => Table::Precompile,
+            Self::BloomTable { .. } => Table::Bloom,
             Self::Conditional(_, lookup) => lookup.table(),
         }
     }
@@ -321,6 +410,11 @@ impl<F: Field> Lookup<F> {
                 index,
                 value,
             } => vec![id.clone(), field_tag.clone(), index.clone(), value.clone()],
+            Self::BloomTable { field_tag, value } => vec![
+                1.expr(), // is_enabled
+                field_tag.clone(),
+                value.clone(),
+            ],
             Self::Rw {
                 counter,
                 is_write,
@@ -422,6 +516,27 @@ impl<F: Field> Lookup<F> {
                 exponentiation_lo_hi[0].clone(),
                 exponentiation_lo_hi[1].clone(),
             ],
+            Self::SigTable {
+                input_rlc,
+                address_rlc,
+            } => vec![input_rlc.clone(), address_rlc.clone()],
+            Self::PrecompileTable {
+                address,
+                input_length,
+                input_rlc,
+                output_length,
+                output_rlc,
+                required_gas,
+                is_valid,
+            } => vec![
+                address.clone(),
+                input_length.clone(),
+                input_rlc.clone(),
+                output_length.clone(),
+                output_rlc.clone(),
+                required_gas.clone(),
+                is_valid.clone(),
+            ],
             Self::Conditional(condition, lookup) => lookup
                 .input_exprs()
                 .into_iter()
@@ -438,3 +553,61 @@ impl<F: Field> Lookup<F> {
             .unwrap()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use halo2_proofs::halo2curves::bn256::Fr;
+
+    fn responsible_states(hardfork: Hardfork, opcode: OpcodeId) -> Vec<ExecutionState> {
+        let tag = Fr::from(FixedTableTag::ResponsibleOpcode as u64);
+        ExecutionState::iter()
+            .filter(|state| {
+                FixedTableTag::ResponsibleOpcode
+                    .build::<Fr>(hardfork)
+                    .any(|row| {
+                        row == [
+                            tag,
+                            Fr::from(state.as_u64()),
+                            Fr::from(opcode.as_u64()),
+                            Fr::from(0),
+                        ]
+                    })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn responsible_opcode_by_hardfork() {
+        assert_eq!(
+            responsible_states(Hardfork::London, OpcodeId::PUSH0),
+            vec![ExecutionState::ErrorInvalidOpcode]
+        );
+        assert!(responsible_states(Hardfork::Shanghai, OpcodeId::PUSH0)
+            .contains(&ExecutionState::PUSH0));
+        assert!(!responsible_states(Hardfork::Shanghai, OpcodeId::PUSH0)
+            .contains(&ExecutionState::ErrorInvalidOpcode));
+        assert_eq!(
+            responsible_states(Hardfork::Berlin, OpcodeId::BASEFEE),
+            vec![ExecutionState::ErrorInvalidOpcode]
+        );
+    }
+
+    #[test]
+    fn hardfork_params() {
+        let rows = |hardfork| {
+            FixedTableTag::HardforkParam
+                .build::<Fr>(hardfork)
+                .map(|row| row[2])
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            rows(Hardfork::Berlin),
+            vec![Fr::from(2), Fr::from(15000), Fr::from(24000), Fr::from(0)]
+        );
+        assert_eq!(
+            rows(Hardfork::London),
+            vec![Fr::from(5), Fr::from(4800), Fr::from(0), Fr::from(1)]
+        );
+    }
+}
@@ -3,7 +3,7 @@ use crate::{
         param::{LOOKUP_CONFIG, N_BYTES_MEMORY_ADDRESS, N_COPY_COLUMNS},
         table::Table,
     },
-    util::{query_expression, Expr},
+    util::{query_expression, Challenges, Expr},
 };
 use eth_types::U256;
 use halo2_proofs::{
@@ -76,7 +76,7 @@ pub struct CachedRegion<'r, 'b, F: FieldExt> {
     region: &'r mut Region<'b, F>,
     advice: Vec<Vec<F>>,
     advice_columns: Vec<Column<Advice>>,
-    power_of_randomness: [F; 31],
+    challenges: &'r Challenges<Value<F>>,
     width_start: usize,
     height_start: usize,
 }
@@ -85,7 +85,7 @@ impl<'r, 'b, F: FieldExt> CachedRegion<'r, 'b, F> {
     /// New cached region
     pub(crate) fn new(
         region: &'r mut Region<'b, F>,
-        power_of_randomness: [F; 31],
+        challenges: &'r Challenges<Value<F>>,
         advice_columns: Vec<Column<Advice>>,
         height: usize,
         height_start: usize,
@@ -93,7 +93,7 @@ impl<'r, 'b, F: FieldExt> CachedRegion<'r, 'b, F> {
         Self {
             region,
             advice: vec![vec![F::zero(); height]; advice_columns.len()],
-            power_of_randomness,
+            challenges,
             width_start: advice_columns[0].index(),
             height_start,
             advice_columns,
@@ -168,8 +168,18 @@ impl<'r, 'b, F: FieldExt> CachedRegion<'r, 'b, F> {
             [(((row_index - self.height_start) as i32) + rotation.0) as usize]
     }
 
-    pub fn get_instance(&self, _row_index: usize, column_index: usize, _rotation: Rotation) -> F {
-        self.power_of_randomness[column_index]
+    pub fn challenges(&self) -> &Challenges<Value<F>> {
+        self.challenges
+    }
+
+    /// Returns the `evm_word` challenge, with which the witness of the words
+    /// is encoded, or zero while it's unknown.
+    pub fn evm_word_randomness(&self) -> F {
+        let mut randomness = F::zero();
+        self.challenges
+            .evm_word()
+            .map(|challenge| randomness = challenge);
+        randomness
     }
 
     /// Constrains a cell to have a constant value.
@@ -204,28 +214,30 @@ impl<F: FieldExt> StoredExpression<F> {
         offset: usize,
     ) -> Result<AssignedCell<F, F>, Error> {
         let value = self.expr.evaluate(
-            &|scalar| scalar,
+            &|scalar| Value::known(scalar),
             &|_| unimplemented!("selector column"),
             &|fixed_query| {
-                region.get_fixed(offset, fixed_query.column_index(), fixed_query.rotation())
+                Value::known(region.get_fixed(
+                    offset,
+                    fixed_query.column_index(),
+                    fixed_query.rotation(),
+                ))
             },
             &|advide_query| {
-                region.get_advice(offset, advide_query.column_index(), advide_query.rotation())
-            },
-            &|instance_query| {
-                region.get_instance(
+                Value::known(region.get_advice(
                     offset,
-                    instance_query.column_index(),
-                    instance_query.rotation(),
-                )
+                    advide_query.column_index(),
+                    advide_query.rotation(),
+                ))
             },
-            &|_| unimplemented!(),
+            &|_| unimplemented!("instance column"),
+            &|challenge| *region.challenges().indexed()[challenge.index()],
             &|a| -a,
             &|a, b| a + b,
             &|a, b| a * b,
-            &|a, scalar| a * scalar,
+            &|a, scalar| a * Value::known(scalar),
         );
-        self.cell.assign(region, offset, Value::known(value))
+        self.cell.assign(region, offset, value)
     }
 }
 
@@ -1,22 +1,28 @@
 use super::CachedRegion;
 use crate::{
     evm_circuit::{
-        param::N_BYTES_GAS,
+        param::{N_BYTES_ACCOUNT_ADDRESS, N_BYTES_GAS, N_BYTES_U64},
+        step::ExecutionState,
         table::{FixedTableTag, Lookup},
         util::{
             constraint_builder::{
                 ConstraintBuilder, ReversionInfo, StepStateTransition,
                 Transition::{Delta, Same, To},
             },
-            math_gadget::{AddWordsGadget, RangeCheckGadget},
-            not, Cell, Word,
+            from_bytes,
+            math_gadget::{AddWordsGadget, LtGadget, RangeCheckGadget},
+            not, rlc, select, sum, Cell, RandomLinearCombination, Word,
         },
     },
     table::{AccountFieldTag, CallContextFieldTag},
     util::Expr,
     witness::{Block, Call, ExecStep},
 };
-use eth_types::{evm_types::GasCost, Field, ToLittleEndian, ToScalar, U256};
+use bus_mapping::circuit_input_builder::{
+    get_create2_address_preimage, get_create_address_preimage,
+};
+use eth_types::{evm_types::GasCost, Address, Field, ToLittleEndian, ToScalar, U256};
+use ethers_core::utils::keccak256;
 use halo2_proofs::{
     circuit::Value,
     plonk::{Error, Expression},
@@ -202,6 +208,7 @@ impl<F: Field> RestoreContextGadget<F> {
         step: &ExecStep,
         rw_offset: usize,
     ) -> Result<(), Error> {
+        let randomness = region.evm_word_randomness();
         let [caller_id, caller_is_root, caller_is_create, caller_code_hash, caller_program_counter, caller_stack_pointer, caller_gas_left, caller_memory_word_size, caller_reversible_write_counter] =
             if call.is_root {
                 [U256::zero(); 9]
@@ -240,7 +247,7 @@ impl<F: Field> RestoreContextGadget<F> {
             offset,
             Value::known(Word::random_linear_combine(
                 caller_code_hash.to_le_bytes(),
-                block.randomness,
+                randomness,
             )),
         )?;
 
@@ -248,6 +255,93 @@ impl<F: Field> RestoreContextGadget<F> {
     }
 }
 
+/// Construction of execution state that halts in an exception, which lookups
+/// the opcode and verifies the execution state is responsible for it, then
+/// fails the current call and either ends the tx if it's a root call, or
+/// restores the caller's context otherwise.
+#[derive(Clone, Debug)]
+pub(crate) struct CommonErrorGadget<F> {
+    opcode: Cell<F>,
+    restore_context: RestoreContextGadget<F>,
+}
+
+impl<F: Field> CommonErrorGadget<F> {
+    pub(crate) fn construct(cb: &mut ConstraintBuilder<F>, opcode: Cell<F>) -> Self {
+        cb.opcode_lookup(opcode.expr(), 1.expr());
+        cb.add_lookup(
+            "Responsible opcode lookup",
+            Lookup::Fixed {
+                tag: FixedTableTag::ResponsibleOpcode.expr(),
+                values: [
+                    cb.execution_state().as_u64().expr(),
+                    opcode.expr(),
+                    0.expr(),
+                ],
+            },
+        );
+
+        // Current call must fail.
+        cb.call_context_lookup(false.expr(), None, CallContextFieldTag::IsSuccess, 0.expr());
+
+        // Go to EndTx only when is_root
+        let is_to_end_tx = cb.next.execution_state_selector([ExecutionState::EndTx]);
+        cb.require_equal(
+            "Go to EndTx only when is_root",
+            cb.curr.state.is_root.expr(),
+            is_to_end_tx,
+        );
+
+        // When it's a root call
+        let rw_counter_delta =
+            cb.rw_counter_offset() + cb.curr.state.reversible_write_counter.expr();
+        cb.condition(cb.curr.state.is_root.expr(), |cb| {
+            cb.require_step_state_transition(StepStateTransition {
+                call_id: Same,
+                rw_counter: Delta(rw_counter_delta),
+                ..StepStateTransition::any()
+            });
+        });
+
+        // When it's an internal call, need to restore caller's state as finishing this
+        // call. Restore caller state to next StepState
+        let restore_context = cb.condition(not::expr(cb.curr.state.is_root.expr()), |cb| {
+            RestoreContextGadget::construct(
+                cb,
+                0.expr(),
+                0.expr(),
+                0.expr(),
+                0.expr(),
+                0.expr(),
+                0.expr(),
+            )
+        });
+
+        Self {
+            opcode,
+            restore_context,
+        }
+    }
+
+    /// `rw_offset` is the number of rw lookups of the step up to and including
+    /// the `IsSuccess` read, after which the caller's context is read.
+    pub(crate) fn assign(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        call: &Call,
+        step: &ExecStep,
+        rw_offset: usize,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode.unwrap();
+        self.opcode
+            .assign(region, offset, Value::known(F::from(opcode.as_u64())))?;
+
+        self.restore_context
+            .assign(region, offset, block, call, step, rw_offset)
+    }
+}
+
 #[derive(Clone, Debug)]
 pub(crate) struct UpdateBalanceGadget<F, const N_ADDENDS: usize, const INCREASE: bool> {
     add_words: AddWordsGadget<F, N_ADDENDS, true>,
@@ -445,3 +539,268 @@ impl<F: Field> TransferGadget<F> {
         Ok(())
     }
 }
+
+/// Construction of the new contract address of CREATE and CREATE2, which is
+/// the lower 20 bytes of the keccak hash of:
+/// - CREATE: `rlp([caller_address, caller_nonce])`
+/// - CREATE2: `0xff ++ caller_address ++ salt ++ keccak256(init_code)`
+#[derive(Clone, Debug)]
+pub(crate) struct ContractAddressGadget<F> {
+    caller_nonce: RlpU64Gadget<F>,
+    salt: Word<F>,
+    code_hash: Word<F>,
+    keccak_output: Word<F>,
+}
+
+impl<F: Field> ContractAddressGadget<F> {
+    pub(crate) fn construct(
+        cb: &mut ConstraintBuilder<F>,
+        caller_address: &RandomLinearCombination<F, N_BYTES_ACCOUNT_ADDRESS>,
+        is_create2: Expression<F>,
+    ) -> Self {
+        let caller_nonce = RlpU64Gadget::construct(cb);
+        let salt = cb.query_word();
+        let code_hash = cb.query_word();
+        let keccak_output = cb.query_word();
+
+        // The preimage is encoded with the keccak input randomness, so the
+        // words are combined again from their bytes.
+        let power_of_randomness: Vec<_> =
+            cb.challenges().keccak_powers_of_randomness::<31>().to_vec();
+        let r_pow = |n: usize| power_of_randomness_expr(&power_of_randomness, n);
+        let keccak_rlc = |cells: &[Cell<F>]| {
+            let bytes: Vec<_> = cells.iter().map(Expr::expr).collect();
+            rlc::expr(&bytes, &power_of_randomness)
+        };
+        let caller_address_rlc = keccak_rlc(&caller_address.cells);
+        let create_input_rlc = (((0xc0 + 1 + N_BYTES_ACCOUNT_ADDRESS).expr()
+            + caller_nonce.rlp_length())
+            * r_pow(1 + N_BYTES_ACCOUNT_ADDRESS)
+            + (0x80 + N_BYTES_ACCOUNT_ADDRESS).expr() * r_pow(N_BYTES_ACCOUNT_ADDRESS)
+            + caller_address_rlc.clone())
+            * caller_nonce.rlp_power_of_randomness(&power_of_randomness)
+            + caller_nonce.rlp_rlc(&power_of_randomness);
+        let create_input_len = (2 + N_BYTES_ACCOUNT_ADDRESS).expr() + caller_nonce.rlp_length();
+        let create2_input_rlc = 0xff.expr() * r_pow(N_BYTES_ACCOUNT_ADDRESS + 64)
+            + caller_address_rlc * r_pow(64)
+            + keccak_rlc(&salt.cells) * r_pow(32)
+            + keccak_rlc(&code_hash.cells);
+        let create2_input_len = (1 + N_BYTES_ACCOUNT_ADDRESS + 64).expr();
+        cb.keccak_table_lookup(
+            select::expr(is_create2.clone(), create2_input_rlc, create_input_rlc),
+            select::expr(is_create2, create2_input_len, create_input_len),
+            keccak_output.expr(),
+        );
+
+        Self {
+            caller_nonce,
+            salt,
+            code_hash,
+            keccak_output,
+        }
+    }
+
+    pub(crate) fn caller_nonce(&self) -> Expression<F> {
+        self.caller_nonce.value()
+    }
+
+    pub(crate) fn salt(&self) -> &Word<F> {
+        &self.salt
+    }
+
+    /// Code hash of the initialization code, which is only used by CREATE2.
+    pub(crate) fn code_hash(&self) -> &Word<F> {
+        &self.code_hash
+    }
+
+    pub(crate) fn address(&self) -> Expression<F> {
+        from_bytes::expr(&self.keccak_output.cells[..N_BYTES_ACCOUNT_ADDRESS])
+    }
+
+    pub(crate) fn address_rlc(&self, power_of_randomness: &[Expression<F>]) -> Expression<F> {
+        let bytes: Vec<_> = self.keccak_output.cells[..N_BYTES_ACCOUNT_ADDRESS]
+            .iter()
+            .map(Expr::expr)
+            .collect();
+        rlc::expr(&bytes, power_of_randomness)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn assign(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        caller_address: Address,
+        caller_nonce: u64,
+        salt: U256,
+        code_hash: [u8; 32],
+        is_create2: bool,
+    ) -> Result<(), Error> {
+        self.caller_nonce.assign(region, offset, caller_nonce)?;
+        self.salt.assign(region, offset, Some(salt.to_le_bytes()))?;
+        self.code_hash.assign(
+            region,
+            offset,
+            Some(U256::from_big_endian(&code_hash).to_le_bytes()),
+        )?;
+
+        let keccak_input = if is_create2 {
+            get_create2_address_preimage(caller_address, salt, code_hash.into())
+        } else {
+            get_create_address_preimage(caller_address, caller_nonce)
+        };
+        self.keccak_output.assign(
+            region,
+            offset,
+            Some(U256::from_big_endian(&keccak256(&keccak_input)).to_le_bytes()),
+        )?;
+        Ok(())
+    }
+}
+
+/// Returns `r^n` built from the powers of randomness `[r, r^2, ..., r^31]`.
+fn power_of_randomness_expr<F: Field>(
+    power_of_randomness: &[Expression<F>],
+    n: usize,
+) -> Expression<F> {
+    let n_max = power_of_randomness.len();
+    let mut expr = 1.expr();
+    for _ in 0..n / n_max {
+        expr = expr * power_of_randomness[n_max - 1].clone();
+    }
+    if n % n_max != 0 {
+        expr = expr * power_of_randomness[n % n_max - 1].clone();
+    }
+    expr
+}
+
+/// Gadget for the RLP encoding of an u64 value, which is used to encode the
+/// caller's nonce in the CREATE address preimage. The encoding is:
+/// - `0x80` if the value is zero.
+/// - The value itself as a single byte if it's less than `0x80`.
+/// - `0x80 + byte_size` followed by the big-endian bytes otherwise.
+#[derive(Clone, Debug)]
+struct RlpU64Gadget<F> {
+    bytes: RandomLinearCombination<F, N_BYTES_U64>,
+    /// Selectors of the byte size of the value. Only the one at index
+    /// `byte_size` is turned on.
+    byte_size_selectors: [Cell<F>; N_BYTES_U64 + 1],
+    /// The inverse of the most significant non-zero byte of the value.
+    most_significant_byte_inverse: Cell<F>,
+    is_short: LtGadget<F, N_BYTES_U64>,
+}
+
+impl<F: Field> RlpU64Gadget<F> {
+    fn construct(cb: &mut ConstraintBuilder<F>) -> Self {
+        let bytes = cb.query_rlc();
+        let byte_size_selectors = [(); N_BYTES_U64 + 1].map(|()| cb.query_bool());
+        cb.require_equal(
+            "exactly one byte size selector is 1",
+            sum::expr(&byte_size_selectors),
+            1.expr(),
+        );
+
+        let most_significant_byte_inverse = cb.query_cell();
+        for (i, selector) in byte_size_selectors.iter().enumerate() {
+            cb.condition(selector.expr(), |cb| {
+                cb.require_zero("more significant bytes are 0", sum::expr(&bytes.cells[i..]));
+                if i > 0 {
+                    cb.require_equal(
+                        "most significant non-zero byte's inverse exists",
+                        bytes.cells[i - 1].expr() * most_significant_byte_inverse.expr(),
+                        1.expr(),
+                    );
+                }
+            });
+        }
+
+        let is_short = LtGadget::construct(cb, from_bytes::expr(&bytes.cells), 0x80.expr());
+
+        Self {
+            bytes,
+            byte_size_selectors,
+            most_significant_byte_inverse,
+            is_short,
+        }
+    }
+
+    fn value(&self) -> Expression<F> {
+        from_bytes::expr(&self.bytes.cells)
+    }
+
+    fn byte_size(&self) -> Expression<F> {
+        sum::expr(
+            self.byte_size_selectors
+                .iter()
+                .enumerate()
+                .map(|(i, selector)| i.expr() * selector.expr()),
+        )
+    }
+
+    /// Whether the value is encoded in a single byte.
+    fn is_short(&self) -> Expression<F> {
+        self.is_short.expr()
+    }
+
+    fn rlp_length(&self) -> Expression<F> {
+        select::expr(self.is_short(), 1.expr(), 1.expr() + self.byte_size())
+    }
+
+    /// Returns `r^rlp_length`.
+    fn rlp_power_of_randomness(&self, power_of_randomness: &[Expression<F>]) -> Expression<F> {
+        select::expr(
+            self.is_short(),
+            power_of_randomness[0].clone(),
+            sum::expr(
+                self.byte_size_selectors
+                    .iter()
+                    .enumerate()
+                    .map(|(i, selector)| selector.expr() * power_of_randomness[i].clone()),
+            ),
+        )
+    }
+
+    /// Returns the RLC of the big-endian RLP encoding.
+    fn rlp_rlc(&self, power_of_randomness: &[Expression<F>]) -> Expression<F> {
+        let short_rlc = self.value() + 0x80.expr() * self.byte_size_selectors[0].expr();
+        let long_rlc = sum::expr(self.byte_size_selectors.iter().enumerate().skip(1).map(
+            |(i, selector)| {
+                selector.expr() * (0x80 + i).expr() * power_of_randomness[i - 1].clone()
+            },
+        )) + rlc::expr(
+            &self.bytes.cells.iter().map(Expr::expr).collect::<Vec<_>>(),
+            power_of_randomness,
+        );
+        select::expr(self.is_short(), short_rlc, long_rlc)
+    }
+
+    fn assign(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        value: u64,
+    ) -> Result<(), Error> {
+        let bytes = value.to_le_bytes();
+        self.bytes.assign(region, offset, Some(bytes))?;
+        let byte_size = (64 - value.leading_zeros() as usize + 7) / 8;
+        for (i, selector) in self.byte_size_selectors.iter().enumerate() {
+            selector.assign(
+                region,
+                offset,
+                Value::known(F::from((i == byte_size) as u64)),
+            )?;
+        }
+        self.most_significant_byte_inverse.assign(
+            region,
+            offset,
+            Value::known(if byte_size > 0 {
+                F::from(bytes[byte_size - 1] as u64).invert().unwrap()
+            } else {
+                F::zero()
+            }),
+        )?;
+        self.is_short
+            .assign(region, offset, F::from(value), F::from(0x80))?;
+        Ok(())
+    }
+}
@@ -2,14 +2,14 @@ use crate::{
     evm_circuit::{
         param::STACK_CAPACITY,
         step::{ExecutionState, Step},
-        table::{FixedTableTag, Lookup, RwValues, Table},
+        table::{FixedTableTag, HardforkParam, Lookup, RwValues, Table},
         util::{Cell, RandomLinearCombination, Word},
     },
     table::{
         AccountFieldTag, BytecodeFieldTag, CallContextFieldTag, RwTableTag, TxContextFieldTag,
         TxLogFieldTag, TxReceiptFieldTag,
     },
-    util::{build_tx_log_expression, Expr},
+    util::{build_tx_log_expression, Challenges, Expr},
 };
 use eth_types::Field;
 use gadgets::util::{and, not};
@@ -264,7 +264,8 @@ pub(crate) struct ConstraintBuilder<'a, F> {
     pub max_degree: usize,
     pub(crate) curr: Step<F>,
     pub(crate) next: Step<F>,
-    power_of_randomness: &'a [Expression<F>; 31],
+    challenges: &'a Challenges<Expression<F>>,
+    power_of_randomness: [Expression<F>; 31],
     execution_state: ExecutionState,
     constraints: Constraints<F>,
     rw_counter_offset: Expression<F>,
@@ -281,14 +282,15 @@ impl<'a, F: Field> ConstraintBuilder<'a, F> {
     pub(crate) fn new(
         curr: Step<F>,
         next: Step<F>,
-        power_of_randomness: &'a [Expression<F>; 31],
+        challenges: &'a Challenges<Expression<F>>,
         execution_state: ExecutionState,
     ) -> Self {
         Self {
             max_degree: MAX_DEGREE,
             curr,
             next,
-            power_of_randomness,
+            challenges,
+            power_of_randomness: challenges.evm_word_powers_of_randomness(),
             execution_state,
             constraints: Constraints {
                 step: Vec::new(),
@@ -329,8 +331,12 @@ impl<'a, F: Field> ConstraintBuilder<'a, F> {
         )
     }
 
+    pub(crate) fn challenges(&self) -> &Challenges<Expression<F>> {
+        self.challenges
+    }
+
     pub(crate) fn power_of_randomness(&self) -> &[Expression<F>] {
-        self.power_of_randomness
+        &self.power_of_randomness
     }
 
     pub(crate) fn execution_state(&self) -> ExecutionState {
@@ -341,6 +347,12 @@ impl<'a, F: Field> ConstraintBuilder<'a, F> {
         self.rw_counter_offset.clone()
     }
 
+    /// Increase the rw_counter_offset by `count` for rw operations which are
+    /// not looked up in this step.
+    pub(crate) fn increase_rw_counter_offset(&mut self, count: Expression<F>) {
+        self.rw_counter_offset = self.rw_counter_offset.clone() + self.condition_expr() * count;
+    }
+
     pub(crate) fn program_counter_offset(&self) -> usize {
         self.program_counter_offset
     }
@@ -376,7 +388,7 @@ impl<'a, F: Field> ConstraintBuilder<'a, F> {
     }
 
     pub(crate) fn query_rlc<const N: usize>(&mut self) -> RandomLinearCombination<F, N> {
-        RandomLinearCombination::<F, N>::new(self.query_bytes(), self.power_of_randomness)
+        RandomLinearCombination::<F, N>::new(self.query_bytes(), &self.power_of_randomness)
     }
 
     pub(crate) fn query_bytes<const N: usize>(&mut self) -> [Cell<F>; N] {
@@ -454,6 +466,31 @@ impl<'a, F: Field> ConstraintBuilder<'a, F> {
         self.add_constraint("Constrain next execution state not", next_state.expr());
     }
 
+    /// Constrain the next execution state to be the one of the precompiled
+    /// contract at `address` when `is_precompile`, and to be none of the
+    /// precompiled contracts otherwise.
+    pub(crate) fn require_next_state_precompile(
+        &mut self,
+        is_precompile: Expression<F>,
+        address: Expression<F>,
+    ) {
+        let next_state = self
+            .next
+            .execution_state_selector(ExecutionState::precompiles());
+        self.add_constraint(
+            "Constrain next execution state is precompile",
+            is_precompile - next_state.clone(),
+        );
+        let next_address = ExecutionState::precompiles().fold(0.expr(), |acc, state| {
+            acc + self.next.execution_state_selector([state])
+                * u64::from(state.precompile().unwrap()).expr()
+        });
+        self.add_constraint(
+            "Constrain next precompile is the one at address",
+            next_state * address - next_address,
+        );
+    }
+
     pub(crate) fn require_step_state_transition(
         &mut self,
         step_state_transition: StepStateTransition<F>,
@@ -543,6 +580,17 @@ impl<'a, F: Field> ConstraintBuilder<'a, F> {
         );
     }
 
+    // look up the value of a parameter of the hardfork
+    pub(crate) fn hardfork_param_lookup(&mut self, param: HardforkParam, value: Expression<F>) {
+        self.add_lookup(
+            "hardfork param",
+            Lookup::Fixed {
+                tag: FixedTableTag::HardforkParam.expr(),
+                values: [param.expr(), value, 0.expr()],
+            },
+        );
+    }
+
     // Opcode
 
     pub(crate) fn opcode_lookup(&mut self, opcode: Expression<F>, is_code: Expression<F>) {
@@ -818,6 +866,30 @@ impl<'a, F: Field> ConstraintBuilder<'a, F> {
         );
     }
 
+    pub(crate) fn account_storage_access_list_read(
+        &mut self,
+        tx_id: Expression<F>,
+        account_address: Expression<F>,
+        storage_key: Expression<F>,
+        value: Expression<F>,
+    ) {
+        self.rw_lookup(
+            "TxAccessListAccountStorage read",
+            false.expr(),
+            RwTableTag::TxAccessListAccountStorage,
+            RwValues::new(
+                tx_id,
+                account_address,
+                0.expr(),
+                storage_key,
+                value.clone(),
+                value,
+                0.expr(),
+                0.expr(),
+            ),
+        );
+    }
+
     pub(crate) fn account_storage_access_list_write(
         &mut self,
         tx_id: Expression<F>,
@@ -938,6 +1010,33 @@ impl<'a, F: Field> ConstraintBuilder<'a, F> {
         );
     }
 
+    // Account Destructed
+
+    pub(crate) fn account_destructed_write(
+        &mut self,
+        tx_id: Expression<F>,
+        account_address: Expression<F>,
+        value: Expression<F>,
+        value_prev: Expression<F>,
+        reversion_info: Option<&mut ReversionInfo<F>>,
+    ) {
+        self.reversible_write(
+            "AccountDestructed write",
+            RwTableTag::AccountDestructed,
+            RwValues::new(
+                tx_id,
+                account_address,
+                0.expr(),
+                0.expr(),
+                value,
+                value_prev,
+                0.expr(),
+                0.expr(),
+            ),
+            reversion_info,
+        );
+    }
+
     // Account Storage
 
     pub(crate) fn account_storage_read(
@@ -1289,6 +1388,61 @@ impl<'a, F: Field> ConstraintBuilder<'a, F> {
         );
     }
 
+    // Sig Table
+
+    pub(crate) fn sig_table_lookup(
+        &mut self,
+        input_rlc: Expression<F>,
+        address_rlc: Expression<F>,
+    ) {
+        self.add_lookup(
+            "sig lookup",
+            Lookup::SigTable {
+                input_rlc,
+                address_rlc,
+            },
+        );
+    }
+
+    // Precompile Table
+
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn precompile_table_lookup(
+        &mut self,
+        address: Expression<F>,
+        input_length: Expression<F>,
+        input_rlc: Expression<F>,
+        output_length: Expression<F>,
+        output_rlc: Expression<F>,
+        required_gas: Expression<F>,
+        is_valid: Expression<F>,
+    ) {
+        self.add_lookup(
+            "precompile lookup",
+            Lookup::PrecompileTable {
+                address,
+                input_length,
+                input_rlc,
+                output_length,
+                output_rlc,
+                required_gas,
+                is_valid,
+            },
+        );
+    }
+
+    // Bloom Table
+
+    pub(crate) fn bloom_table_lookup(&mut self, field_tag: TxLogFieldTag, value: Expression<F>) {
+        self.add_lookup(
+            "bloom lookup",
+            Lookup::BloomTable {
+                field_tag: field_tag.expr(),
+                value,
+            },
+        );
+    }
+
     // Validation
 
     pub(crate) fn validate_degree(&self, degree: usize, name: &'static str) {
@@ -1413,7 +1567,7 @@ impl<'a, F: Field> ConstraintBuilder<'a, F> {
 
         let compressed_expr = self.split_expression(
             "Lookup compression",
-            rlc::expr(&lookup.input_exprs(), self.power_of_randomness),
+            rlc::expr(&lookup.input_exprs(), &self.power_of_randomness),
             MAX_DEGREE - IMPLICIT_DEGREE,
         );
         self.store_expression(name, compressed_expr, CellType::Lookup(lookup.table()));
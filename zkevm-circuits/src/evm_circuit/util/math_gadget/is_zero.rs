@@ -51,6 +51,23 @@ impl<F: Field> IsZeroGadget<F> {
             F::zero()
         })
     }
+
+    pub(crate) fn assign_value(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        value: Value<F>,
+    ) -> Result<Value<F>, Error> {
+        let inverse = value.map(|value| value.invert().unwrap_or(F::zero()));
+        self.inverse.assign(region, offset, inverse)?;
+        Ok(value.map(|value| {
+            if value.is_zero().into() {
+                F::one()
+            } else {
+                F::zero()
+            }
+        }))
+    }
 }
 
 #[cfg(test)]
@@ -12,13 +12,13 @@ use crate::evm_circuit::{
     Advice, Column, Fixed,
 };
 use crate::table::LookupTable;
-use crate::util::power_of_randomness_from_instance;
-use eth_types::{Field, Word, U256};
+use crate::util::Challenges;
+use eth_types::{evm_types::Hardfork, Field, Word, U256};
 pub(crate) use halo2_proofs::circuit::{Layouter, Value};
 use halo2_proofs::{
     circuit::SimpleFloorPlanner,
     dev::MockProver,
-    plonk::{Circuit, ConstraintSystem, Error, Expression, Selector},
+    plonk::{Circuit, ConstraintSystem, Error, Selector},
 };
 
 pub(crate) const WORD_LOW_MAX: Word = U256([u64::MAX, u64::MAX, 0, 0]);
@@ -31,10 +31,6 @@ pub(crate) const WORD_CELL_MAX: Word = U256([
     0x30644e72e131a029,
 ]);
 
-pub(crate) fn generate_power_of_randomness<F: Field>(randomness: F) -> Vec<F> {
-    (1..32).map(|exp| randomness.pow(&[exp, 0, 0, 0])).collect()
-}
-
 pub(crate) trait MathGadgetContainer<F: Field>: Clone {
     fn configure_gadget_container(cb: &mut ConstraintBuilder<F>) -> Self
     where
@@ -59,22 +55,20 @@ where
     stored_expressions: Vec<StoredExpression<F>>,
     math_gadget_container: G,
     _marker: PhantomData<F>,
-    power_of_randomness: [Expression<F>; 31],
+    challenges: Challenges,
 }
 
 pub(crate) struct UnitTestMathGadgetBaseCircuit<F, G> {
     size: usize,
     witnesses: Vec<Word>,
-    randomness: F,
-    _marker: PhantomData<G>,
+    _marker: PhantomData<(F, G)>,
 }
 
 impl<F: Field, G> UnitTestMathGadgetBaseCircuit<F, G> {
-    fn new(size: usize, witnesses: Vec<Word>, randomness: F) -> Self {
+    fn new(size: usize, witnesses: Vec<Word>) -> Self {
         UnitTestMathGadgetBaseCircuit {
             size,
             witnesses,
-            randomness,
             _marker: PhantomData,
         }
     }
@@ -88,7 +82,6 @@ impl<F: Field, G: MathGadgetContainer<F>> Circuit<F> for UnitTestMathGadgetBaseC
         UnitTestMathGadgetBaseCircuit {
             size: 0,
             witnesses: vec![],
-            randomness: F::from(123456u64),
             _marker: PhantomData,
         }
     }
@@ -99,12 +92,13 @@ impl<F: Field, G: MathGadgetContainer<F>> Circuit<F> for UnitTestMathGadgetBaseC
         let advices = [(); STEP_WIDTH].map(|_| meta.advice_column());
         let step_curr = Step::new(meta, advices, 0, false);
         let step_next = Step::new(meta, advices, MAX_STEP_HEIGHT, true);
-        let power_of_randomness = power_of_randomness_from_instance(meta);
+        let challenges = Challenges::construct(meta);
+        let challenge_exprs = challenges.exprs(meta);
 
         let mut cb = ConstraintBuilder::new(
             step_curr.clone(),
             step_next,
-            &power_of_randomness,
+            &challenge_exprs,
             ExecutionState::STOP,
         );
         let math_gadget_container = G::configure_gadget_container(&mut cb);
@@ -129,7 +123,10 @@ impl<F: Field, G: MathGadgetContainer<F>> Circuit<F> for UnitTestMathGadgetBaseC
                         let table_expressions = fixed_table.table_exprs(meta);
                         vec![(
                             column.expr(),
-                            rlc::expr(&table_expressions, &power_of_randomness),
+                            rlc::expr(
+                                &table_expressions,
+                                &challenge_exprs.evm_word_powers_of_randomness::<31>(),
+                            ),
                         )]
                     });
                 }
@@ -144,7 +141,7 @@ impl<F: Field, G: MathGadgetContainer<F>> Circuit<F> for UnitTestMathGadgetBaseC
             stored_expressions,
             math_gadget_container,
             _marker: PhantomData,
-            power_of_randomness,
+            challenges,
         }
     }
 
@@ -153,15 +150,15 @@ impl<F: Field, G: MathGadgetContainer<F>> Circuit<F> for UnitTestMathGadgetBaseC
         config: Self::Config,
         mut layouter: impl Layouter<F>,
     ) -> Result<(), Error> {
+        let challenges = config.challenges.values(&mut layouter);
         layouter.assign_region(
             || "assign test container",
             |mut region| {
                 let offset = 0;
                 config.q_usable.enable(&mut region, offset)?;
-                let power_of_randomness = generate_power_of_randomness(self.randomness);
                 let cached_region = &mut CachedRegion::<'_, '_, F>::new(
                     &mut region,
-                    power_of_randomness.try_into().unwrap(),
+                    &challenges,
                     config.advices.to_vec(),
                     MAX_STEP_HEIGHT * 3,
                     offset,
@@ -201,7 +198,7 @@ impl<F: Field, G: MathGadgetContainer<F>> Circuit<F> for UnitTestMathGadgetBaseC
                                         | FixedTableTag::Range1024
                                 )
                             })
-                            .flat_map(|tag| tag.build()),
+                            .flat_map(|tag| tag.build(Hardfork::default())),
                     )
                     .enumerate()
                 {
@@ -225,14 +222,9 @@ pub(crate) fn test_math_gadget_container<F: Field, G: MathGadgetContainer<F>>(
     expected_success: bool,
 ) {
     const K: usize = 12;
-    let randomness = F::from(123456u64);
-    let power_of_randomness_instances: Vec<Vec<F>> = generate_power_of_randomness(randomness)
-        .iter()
-        .map(|power_of_randomn: &F| vec![*power_of_randomn; (1 << K) - 64])
-        .collect();
-    let circuit = UnitTestMathGadgetBaseCircuit::<F, G>::new(K, witnesses, randomness);
-
-    let prover = MockProver::<F>::run(K as u32, &circuit, power_of_randomness_instances).unwrap();
+    let circuit = UnitTestMathGadgetBaseCircuit::<F, G>::new(K, witnesses);
+
+    let prover = MockProver::<F>::run(K as u32, &circuit, vec![]).unwrap();
     if expected_success {
         assert_eq!(prover.verify(), Ok(()));
     } else {
@@ -157,6 +157,101 @@ impl<F: Field> MemoryAddressGadget<F> {
     }
 }
 
+/// Convert the memory offset and length of a memory access in an exception
+/// case, where both can be any word, to integer. The access is within range
+/// when its length is zero, or both offset and length fit in
+/// `N_BYTES_MEMORY_WORD_SIZE` bytes. An access out of range would expand the
+/// memory to more than 2^32 bytes, which costs more than 2^45 gas, so it's
+/// always out of gas.
+#[derive(Clone, Debug)]
+pub(crate) struct MemoryExpandedAddressGadget<F> {
+    memory_offset: Word<F>,
+    memory_length: Word<F>,
+    memory_length_is_zero: IsZeroGadget<F>,
+    high_bytes_are_zero: IsZeroGadget<F>,
+}
+
+impl<F: Field> MemoryExpandedAddressGadget<F> {
+    pub(crate) fn construct(cb: &mut ConstraintBuilder<F>) -> Self {
+        let memory_offset = cb.query_word();
+        let memory_length = cb.query_word();
+        let memory_length_is_zero = IsZeroGadget::construct(cb, sum::expr(&memory_length.cells));
+        let high_bytes_are_zero = IsZeroGadget::construct(
+            cb,
+            sum::expr(&memory_offset.cells[N_BYTES_MEMORY_WORD_SIZE..])
+                + sum::expr(&memory_length.cells[N_BYTES_MEMORY_WORD_SIZE..]),
+        );
+
+        Self {
+            memory_offset,
+            memory_length,
+            memory_length_is_zero,
+            high_bytes_are_zero,
+        }
+    }
+
+    pub(crate) fn assign(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        memory_offset: U256,
+        memory_length: U256,
+    ) -> Result<u64, Error> {
+        let memory_offset_bytes = memory_offset.to_le_bytes();
+        let memory_length_bytes = memory_length.to_le_bytes();
+        self.memory_offset
+            .assign(region, offset, Some(memory_offset_bytes))?;
+        self.memory_length
+            .assign(region, offset, Some(memory_length_bytes))?;
+        self.memory_length_is_zero
+            .assign(region, offset, sum::value(&memory_length_bytes))?;
+        let high_bytes_sum = sum::value::<F>(&memory_offset_bytes[N_BYTES_MEMORY_WORD_SIZE..])
+            + sum::value::<F>(&memory_length_bytes[N_BYTES_MEMORY_WORD_SIZE..]);
+        self.high_bytes_are_zero
+            .assign(region, offset, high_bytes_sum)?;
+
+        let within_range = high_bytes_sum == F::zero();
+        Ok(if memory_length.is_zero() || !within_range {
+            0
+        } else {
+            memory_offset.as_u64() + memory_length.as_u64()
+        })
+    }
+
+    /// Memory offset as a word, to be popped from the stack.
+    pub(crate) fn offset_rlc(&self) -> Expression<F> {
+        self.memory_offset.expr()
+    }
+
+    /// Memory length as a word, to be popped from the stack.
+    pub(crate) fn length_rlc(&self) -> Expression<F> {
+        self.memory_length.expr()
+    }
+
+    pub(crate) fn has_length(&self) -> Expression<F> {
+        1.expr() - self.memory_length_is_zero.expr()
+    }
+
+    pub(crate) fn within_range(&self) -> Expression<F> {
+        1.expr() - self.has_length() * (1.expr() - self.high_bytes_are_zero.expr())
+    }
+
+    /// Memory length, which is 0 when the access is out of range.
+    pub(crate) fn length(&self) -> Expression<F> {
+        self.within_range()
+            * from_bytes::expr(&self.memory_length.cells[..N_BYTES_MEMORY_WORD_SIZE])
+    }
+
+    /// End address of the access, which is 0 when the access has no length or
+    /// is out of range.
+    pub(crate) fn address(&self) -> Expression<F> {
+        self.has_length()
+            * self.high_bytes_are_zero.expr()
+            * (from_bytes::expr(&self.memory_offset.cells[..N_BYTES_MEMORY_WORD_SIZE])
+                + from_bytes::expr(&self.memory_length.cells[..N_BYTES_MEMORY_WORD_SIZE]))
+    }
+}
+
 /// Calculates the memory size in words required for a memory access at the
 /// specified address.
 /// `memory_word_size = ceil(address/32) = floor((address + 31) / 32)`
@@ -16,12 +16,16 @@
 #![deny(unsafe_code)]
 #![deny(clippy::debug_assert_with_mut_call)]
 
+pub mod bloom_circuit;
 pub mod bytecode_circuit;
 pub mod copy_circuit;
 pub mod evm_circuit;
 pub mod exp_circuit;
 pub mod keccak_circuit;
+pub mod mpt_circuit;
 pub mod pi_circuit;
+pub mod precompile_circuit;
+pub mod prover;
 pub mod state_circuit;
 pub mod super_circuit;
 pub mod table;
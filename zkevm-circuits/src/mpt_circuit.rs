@@ -0,0 +1,1918 @@
+//! The MPT circuit proves the updates of the MptTable against the tries of the
+//! state: every update is proved by the nodes visited to find its key in the
+//! state trie, and in the storage trie of the account for storage updates,
+//! before and after the update.
+//!
+//! The rows of an update are split in parts, one for the account in the state
+//! trie followed, for storage updates, by one for the slot in the storage trie
+//! of the account. A part is made of blocks of rows:
+//! - A key block of 32 rows holding the bytes of the key and of its hash, whose
+//!   nibbles are the path of the key in the trie.
+//! - A level block for every depth of the path where a node is visited on one
+//!   of the sides of the update, holding the nodes of both sides, one byte per
+//!   row. The last level holds the leaf of the key, another leaf or an
+//!   extension proving that the key is not in the trie, or no node.
+//!
+//! An update inserting a key next to another leaf or extension splits it: a
+//! branch, under an extension of their common path if any, holds the leaf of
+//! the key and the drifted node, which is the other node with the rest of its
+//! path. Deleting the key collapses the branch back. The side without the
+//! branch is the short side: its last node is repeated up to the level of the
+//! branch, and the drifted node, or no node when the other node is an extension
+//! of the branch nibble only, is on the short side of the last level.
+
+mod node;
+#[cfg(test)]
+mod test;
+
+use crate::{
+    evm_circuit::util::{constraint_builder::BaseConstraintBuilder, not, select, sum},
+    table::{DynamicTableColumns, KeccakTable, MptTable, ProofType},
+    util::{Challenges, Expr, SubCircuit, SubCircuitConfig},
+    witness::{
+        self, MptProof, MptUpdate, MptUpdateRow, MptUpdateWitness, MptUpdates, EMPTY_CODE_HASH,
+        EMPTY_TRIE_ROOT,
+    },
+};
+use eth_types::{Field, ToBigEndian};
+use ethers_core::utils::keccak256;
+#[cfg(any(feature = "test", test))]
+use halo2_proofs::{circuit::SimpleFloorPlanner, plonk::Circuit};
+use halo2_proofs::{
+    circuit::{Layouter, Region, Value},
+    plonk::{
+        Advice, Column, ConstraintSystem, Error, Expression, Fixed, SecondPhase, VirtualCells,
+    },
+    poly::Rotation,
+};
+use node::{at, node_kind, path_nibbles, rlc_word, NodeConfig, NodeContext, NodeKind, NodeWitness};
+use std::marker::PhantomData;
+
+/// The proof types supported by the circuit, in the order of their flag
+/// columns.
+const PROOF_TYPES: [ProofType; 6] = [
+    ProofType::NonceChanged,
+    ProofType::BalanceChanged,
+    ProofType::CodeHashExists,
+    ProofType::AccountDoesNotExist,
+    ProofType::StorageChanged,
+    ProofType::StorageDoesNotExist,
+];
+/// Number of rows of a key block
+const KEY_ROWS: usize = 32;
+/// Number of nibbles of a key path
+const PATH_LEN: usize = 64;
+
+/// Config for MptCircuit
+#[derive(Clone, Debug)]
+pub struct MptCircuitConfig<F> {
+    q_enable: Column<Fixed>,
+    q_first: Column<Fixed>,
+    q_last: Column<Fixed>,
+    // Byte, high nibble, low nibble and whether the byte is at least 0x80
+    byte_table: [Column<Fixed>; 4],
+    // Blocks
+    is_key: Column<Advice>,
+    is_level: Column<Advice>,
+    is_first: Column<Advice>,
+    is_last: Column<Advice>,
+    index: Column<Advice>,
+    // Constant in a part
+    part_id: Column<Advice>,
+    is_storage: Column<Advice>,
+    key_rlc: Column<Advice>,
+    // Key rows
+    key_acc: Column<Advice>,
+    key_hash_acc: Column<Advice>,
+    preimage_rlc: Column<Advice>,
+    preimage_value: Column<Advice>,
+    // Constant in a level
+    depth: Column<Advice>,
+    depth_half: Column<Advice>,
+    depth_odd: Column<Advice>,
+    nibble: Column<Advice>,
+    nib_acc: Column<Advice>,
+    prefix_acc: Column<Advice>,
+    prefix_hi: Column<Advice>,
+    prefix_lo: Column<Advice>,
+    is_last_level: Column<Advice>,
+    // Whether each side is the short side of the update, and whether the level
+    // holds its last node
+    short: [Column<Advice>; 2],
+    is_fork: Column<Advice>,
+    // Path of the key to the end of an extension leaving it
+    end_half: Column<Advice>,
+    end_odd: Column<Advice>,
+    end_acc: Column<Advice>,
+    end_hi: Column<Advice>,
+    end_lo: Column<Advice>,
+    is_creation: Column<Advice>,
+    is_deletion: Column<Advice>,
+    value_inv: Column<Advice>,
+    // Constant in an update
+    proof_types: [Column<Advice>; 6],
+    storage_roots: [Column<Advice>; 2],
+    // Constant in the circuit
+    empty_root: Column<Advice>,
+    empty_code: Column<Advice>,
+    // Nodes of the old and new sides
+    nodes: [NodeConfig; 2],
+    mpt_table: MptTable,
+    keccak_table: KeccakTable,
+    _marker: PhantomData<F>,
+}
+
+/// Circuit configuration arguments
+pub struct MptCircuitConfigArgs<F: Field> {
+    /// MptTable
+    pub mpt_table: MptTable,
+    /// KeccakTable
+    pub keccak_table: KeccakTable,
+    /// Challenges
+    pub challenges: Challenges<Expression<F>>,
+}
+
+impl<F: Field> SubCircuitConfig<F> for MptCircuitConfig<F> {
+    type ConfigArgs = MptCircuitConfigArgs<F>;
+
+    /// Return a new MptCircuitConfig
+    fn new(
+        meta: &mut ConstraintSystem<F>,
+        Self::ConfigArgs {
+            mpt_table,
+            keccak_table,
+            challenges,
+        }: Self::ConfigArgs,
+    ) -> Self {
+        let q_enable = meta.fixed_column();
+        let is_level = meta.advice_column();
+        let is_first = meta.advice_column();
+        let is_last = meta.advice_column();
+        let index = meta.advice_column();
+        let is_storage = meta.advice_column();
+        let nibble = meta.advice_column();
+        let byte_table = [(); 4].map(|_| meta.fixed_column());
+        let ctx = NodeContext {
+            q_enable,
+            is_level,
+            is_first,
+            is_last,
+            index,
+            nibble,
+            is_storage,
+            byte_table,
+            keccak_table: keccak_table.clone(),
+            challenges: challenges.clone(),
+        };
+        let nodes = [(); 2].map(|_| NodeConfig::configure(meta, &ctx));
+
+        let config = Self {
+            q_enable,
+            q_first: meta.fixed_column(),
+            q_last: meta.fixed_column(),
+            byte_table,
+            is_key: meta.advice_column(),
+            is_level,
+            is_first,
+            is_last,
+            index,
+            part_id: meta.advice_column(),
+            is_storage,
+            key_rlc: meta.advice_column_in(SecondPhase),
+            key_acc: meta.advice_column_in(SecondPhase),
+            key_hash_acc: meta.advice_column_in(SecondPhase),
+            preimage_rlc: meta.advice_column_in(SecondPhase),
+            preimage_value: meta.advice_column_in(SecondPhase),
+            depth: meta.advice_column(),
+            depth_half: meta.advice_column(),
+            depth_odd: meta.advice_column(),
+            nibble,
+            nib_acc: meta.advice_column_in(SecondPhase),
+            prefix_acc: meta.advice_column_in(SecondPhase),
+            prefix_hi: meta.advice_column(),
+            prefix_lo: meta.advice_column(),
+            is_last_level: meta.advice_column(),
+            short: [(); 2].map(|_| meta.advice_column()),
+            is_fork: meta.advice_column(),
+            end_half: meta.advice_column(),
+            end_odd: meta.advice_column(),
+            end_acc: meta.advice_column_in(SecondPhase),
+            end_hi: meta.advice_column(),
+            end_lo: meta.advice_column(),
+            is_creation: meta.advice_column(),
+            is_deletion: meta.advice_column(),
+            value_inv: meta.advice_column_in(SecondPhase),
+            proof_types: [(); 6].map(|_| meta.advice_column()),
+            storage_roots: [(); 2].map(|_| meta.advice_column_in(SecondPhase)),
+            empty_root: meta.advice_column_in(SecondPhase),
+            empty_code: meta.advice_column_in(SecondPhase),
+            nodes,
+            mpt_table,
+            keccak_table,
+            _marker: PhantomData,
+        };
+        config.configure_blocks(meta);
+        config.configure_keys(meta, &challenges);
+        config.configure_levels(meta, &challenges);
+        config
+    }
+}
+
+impl<F: Field> MptCircuitConfig<F> {
+    /// Whether the update is a storage update
+    fn storage_flag(&self, meta: &mut VirtualCells<'_, F>, rotation: i32) -> Expression<F> {
+        at(meta, self.proof_types[4], rotation) + at(meta, self.proof_types[5], rotation)
+    }
+
+    fn configure_blocks(&self, meta: &mut ConstraintSystem<F>) {
+        let c = self;
+        let table = c.mpt_table.columns();
+
+        meta.create_gate("mpt rows", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let is_block = at(meta, c.is_key, 0) + at(meta, c.is_level, 0);
+
+            for column in [
+                c.is_key,
+                c.is_level,
+                c.is_first,
+                c.is_last,
+                c.is_storage,
+                c.depth_odd,
+                c.is_last_level,
+                c.is_fork,
+                c.end_odd,
+                c.is_creation,
+                c.is_deletion,
+            ]
+            .into_iter()
+            .chain(c.short)
+            .chain(c.proof_types)
+            {
+                cb.require_boolean("row flag is boolean", at(meta, column, 0));
+            }
+            cb.require_boolean("at most one block kind", is_block.clone());
+
+            for column in [c.is_first, c.is_last, c.is_storage]
+                .into_iter()
+                .chain(c.proof_types)
+                .chain(table.iter().copied())
+            {
+                cb.require_zero(
+                    "padding rows are empty",
+                    not::expr(is_block.clone()) * at(meta, column, 0),
+                );
+            }
+            cb.condition(is_block, |cb| {
+                let proof_types = c.proof_types.map(|column| at(meta, column, 0));
+                cb.require_equal("one proof type", sum::expr(proof_types.clone()), 1.expr());
+                cb.require_equal(
+                    "proof type of the update",
+                    at(meta, table[2], 0),
+                    sum::expr(
+                        PROOF_TYPES
+                            .iter()
+                            .zip(proof_types)
+                            .map(|(proof_type, flag)| proof_type.expr() * flag),
+                    ),
+                );
+            });
+
+            cb.gate(meta.query_fixed(c.q_enable, Rotation::cur()))
+        });
+
+        meta.create_gate("mpt first row", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let is_key = at(meta, c.is_key, 0);
+            cb.require_zero("first row is not a level", at(meta, c.is_level, 0));
+            cb.require_equal(
+                "first row starts a key block",
+                at(meta, c.is_first, 0),
+                is_key.clone(),
+            );
+            cb.require_equal("first part", at(meta, c.part_id, 0), is_key);
+            cb.require_zero("first part is an account part", at(meta, c.is_storage, 0));
+            cb.gate(meta.query_fixed(c.q_first, Rotation::cur()))
+        });
+
+        meta.create_gate("mpt last row", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            cb.require_zero(
+                "last row is padding",
+                at(meta, c.is_key, 0) + at(meta, c.is_level, 0),
+            );
+            cb.gate(meta.query_fixed(c.q_last, Rotation::cur()))
+        });
+
+        // The empty trie root is the hash of [0x80], and the empty code hash
+        // the hash of no byte.
+        for (name, column, input_rlc, input_len) in [
+            ("mpt empty trie root", c.empty_root, 0x80, 1),
+            ("mpt empty code hash", c.empty_code, 0, 0),
+        ] {
+            meta.lookup_any(name, |meta| {
+                let q_first = meta.query_fixed(c.q_first, Rotation::cur());
+                vec![
+                    (q_first.clone(), at(meta, c.keccak_table.is_enabled, 0)),
+                    (
+                        q_first.clone() * input_rlc.expr(),
+                        at(meta, c.keccak_table.input_rlc, 0),
+                    ),
+                    (
+                        q_first.clone() * input_len.expr(),
+                        at(meta, c.keccak_table.input_len, 0),
+                    ),
+                    (
+                        q_first * at(meta, column, 0),
+                        at(meta, c.keccak_table.output_rlc, 0),
+                    ),
+                ]
+            });
+        }
+
+        meta.create_gate("mpt block transitions", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let is_key = at(meta, c.is_key, 0);
+            let is_level = at(meta, c.is_level, 0);
+            let is_first = at(meta, c.is_first, 0);
+            let is_storage = at(meta, c.is_storage, 0);
+            let is_block = is_key.clone() + is_level.clone();
+            let is_key_prev = at(meta, c.is_key, -1);
+            let is_level_prev = at(meta, c.is_level, -1);
+            let is_last_prev = at(meta, c.is_last, -1);
+            let is_block_prev = is_key_prev.clone() + is_level_prev.clone();
+
+            for column in [c.empty_root, c.empty_code] {
+                cb.require_equal(
+                    "globals are constant",
+                    at(meta, column, 0),
+                    at(meta, column, -1),
+                );
+            }
+
+            cb.require_zero(
+                "no block after padding",
+                is_block.clone() * not::expr(is_block_prev.clone()),
+            );
+            cb.require_zero(
+                "a block starts after the last row of a block",
+                is_block.clone() * (is_first.clone() - is_last_prev.clone()),
+            );
+            cb.condition(is_block_prev * not::expr(is_last_prev.clone()), |cb| {
+                cb.require_equal("block continues", is_key.clone(), is_key_prev.clone());
+                cb.require_equal("block continues", is_level.clone(), is_level_prev.clone());
+            });
+            cb.condition(is_block.clone(), |cb| {
+                cb.require_equal(
+                    "index in the block",
+                    at(meta, c.index, 0),
+                    not::expr(is_first.clone()) * (at(meta, c.index, -1) + 1.expr()),
+                );
+                cb.require_equal(
+                    "a part starts with a key block",
+                    at(meta, c.part_id, 0),
+                    at(meta, c.part_id, -1) + is_key.clone() * is_first.clone(),
+                );
+            });
+
+            // Constants of the parts and of the updates
+            let is_part_start = is_key.clone() * is_first.clone();
+            for column in [c.is_storage, c.key_rlc] {
+                cb.require_zero(
+                    "part constant",
+                    is_block.clone()
+                        * not::expr(is_part_start.clone())
+                        * (at(meta, column, 0) - at(meta, column, -1)),
+                );
+            }
+            let is_update_start = is_part_start * not::expr(is_storage.clone());
+            for column in c
+                .proof_types
+                .into_iter()
+                .chain(c.storage_roots)
+                .chain(table.iter().copied())
+            {
+                cb.require_zero(
+                    "update constant",
+                    is_block.clone()
+                        * not::expr(is_update_start.clone())
+                        * (at(meta, column, 0) - at(meta, column, -1)),
+                );
+            }
+
+            // The key block is followed by the first level, at the root.
+            cb.condition(is_key_prev * is_last_prev.clone(), |cb| {
+                cb.require_equal("first level after the key", is_level.clone(), 1.expr());
+                cb.require_zero("first level is at the root", at(meta, c.depth, 0));
+                cb.require_zero("first level is at the root", at(meta, c.nib_acc, 0));
+            });
+
+            // The part ends after the last level, where a storage part follows
+            // the account part of a storage update.
+            let storage_flag_prev = c.storage_flag(meta, -1);
+            let is_account_prev = not::expr(at(meta, c.is_storage, -1));
+            cb.condition(
+                is_level_prev * is_last_prev * at(meta, c.is_last_level, -1),
+                |cb| {
+                    cb.require_zero("part ends after the last level", is_level);
+                    cb.require_zero(
+                        "storage part of the update",
+                        is_key.clone()
+                            * (is_storage - is_account_prev.clone() * storage_flag_prev.clone()),
+                    );
+                    cb.require_zero(
+                        "storage update has a storage part",
+                        is_account_prev * storage_flag_prev * not::expr(is_key),
+                    );
+                },
+            );
+
+            cb.gate(
+                meta.query_fixed(c.q_enable, Rotation::cur())
+                    * not::expr(meta.query_fixed(c.q_first, Rotation::cur())),
+            )
+        });
+    }
+
+    fn configure_keys(
+        &self,
+        meta: &mut ConstraintSystem<F>,
+        challenges: &Challenges<Expression<F>>,
+    ) {
+        let c = self;
+        let r_k = challenges.keccak_input();
+        let r_w = challenges.evm_word();
+        let table = c.mpt_table.columns();
+        let [hash, preimage] = c.nodes;
+
+        meta.create_gate("mpt key rows", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let is_first = at(meta, c.is_first, 0);
+            let is_storage = at(meta, c.is_storage, 0);
+            let hash_byte = at(meta, hash.byte, 0);
+            let preimage_byte = at(meta, preimage.byte, 0);
+            let key_acc = at(meta, c.key_acc, 0);
+            let nibbles = |meta: &mut VirtualCells<'_, F>, rotation| {
+                at(meta, hash.hi, rotation) * r_k.clone() + at(meta, hash.lo, rotation)
+            };
+
+            cb.condition(is_first.clone(), |cb| {
+                cb.require_zero("key path starts at 0", key_acc.clone());
+                cb.require_equal(
+                    "key hash starts",
+                    at(meta, c.key_hash_acc, 0),
+                    hash_byte.clone(),
+                );
+                cb.require_equal(
+                    "key preimage starts",
+                    at(meta, c.preimage_rlc, 0),
+                    preimage_byte.clone(),
+                );
+                cb.require_equal(
+                    "key value starts",
+                    at(meta, c.preimage_value, 0),
+                    preimage_byte.clone(),
+                );
+            });
+            cb.condition(not::expr(is_first), |cb| {
+                cb.require_equal(
+                    "key path",
+                    key_acc.clone(),
+                    at(meta, c.key_acc, -1) * r_k.clone() * r_k.clone() + nibbles(meta, -1),
+                );
+                cb.require_equal(
+                    "key hash",
+                    at(meta, c.key_hash_acc, 0),
+                    at(meta, c.key_hash_acc, -1) * r_w.clone() + hash_byte,
+                );
+                cb.require_equal(
+                    "key preimage",
+                    at(meta, c.preimage_rlc, 0),
+                    at(meta, c.preimage_rlc, -1) * r_k.clone() + preimage_byte.clone(),
+                );
+                cb.require_equal(
+                    "key value",
+                    at(meta, c.preimage_value, 0),
+                    at(meta, c.preimage_value, -1)
+                        * select::expr(is_storage.clone(), r_w.clone(), 256.expr())
+                        + preimage_byte,
+                );
+            });
+            cb.condition(at(meta, c.is_last, 0), |cb| {
+                cb.require_equal(
+                    "key block length",
+                    at(meta, c.index, 0),
+                    (KEY_ROWS - 1).expr(),
+                );
+                cb.require_equal(
+                    "key path of the part",
+                    at(meta, c.key_rlc, 0),
+                    key_acc * r_k.clone() * r_k.clone() + nibbles(meta, 0),
+                );
+                cb.require_equal(
+                    "key of the update",
+                    at(meta, c.preimage_value, 0),
+                    select::expr(is_storage, at(meta, table[1], 0), at(meta, table[0], 0)),
+                );
+            });
+
+            cb.gate(meta.query_fixed(c.q_enable, Rotation::cur()) * at(meta, c.is_key, 0))
+        });
+
+        meta.lookup_any("mpt key hash", |meta| {
+            let enable = meta.query_fixed(c.q_enable, Rotation::cur())
+                * at(meta, c.is_key, 0)
+                * at(meta, c.is_last, 0);
+            let input_len = 20.expr() + 12.expr() * at(meta, c.is_storage, 0);
+            vec![
+                (enable.clone(), at(meta, c.keccak_table.is_enabled, 0)),
+                (
+                    enable.clone() * at(meta, c.preimage_rlc, 0),
+                    at(meta, c.keccak_table.input_rlc, 0),
+                ),
+                (
+                    enable.clone() * input_len,
+                    at(meta, c.keccak_table.input_len, 0),
+                ),
+                (
+                    enable * at(meta, c.key_hash_acc, 0),
+                    at(meta, c.keccak_table.output_rlc, 0),
+                ),
+            ]
+        });
+    }
+
+    fn configure_levels(
+        &self,
+        meta: &mut ConstraintSystem<F>,
+        challenges: &Challenges<Expression<F>>,
+    ) {
+        let c = self;
+        let r_k = challenges.keccak_input();
+        let table = c.mpt_table.columns();
+        let q_level = |meta: &mut VirtualCells<'_, F>| {
+            meta.query_fixed(c.q_enable, Rotation::cur()) * at(meta, c.is_level, 0)
+        };
+
+        meta.create_gate("mpt level constants", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            for column in [
+                c.depth,
+                c.depth_half,
+                c.depth_odd,
+                c.nibble,
+                c.nib_acc,
+                c.prefix_acc,
+                c.prefix_hi,
+                c.prefix_lo,
+                c.is_last_level,
+                c.is_fork,
+                c.end_half,
+                c.end_odd,
+                c.end_acc,
+                c.end_hi,
+                c.end_lo,
+                c.is_creation,
+                c.is_deletion,
+                c.value_inv,
+            ]
+            .into_iter()
+            .chain(c.short)
+            {
+                cb.require_equal("level constant", at(meta, column, 1), at(meta, column, 0));
+            }
+            cb.gate(q_level(meta) * not::expr(at(meta, c.is_last, 0)))
+        });
+
+        meta.create_gate("mpt level start", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let depth = at(meta, c.depth, 0);
+            let depth_odd = at(meta, c.depth_odd, 0);
+            let nib_acc = at(meta, c.nib_acc, 0);
+            let prefix_acc = at(meta, c.prefix_acc, 0);
+            let prefix_hi = at(meta, c.prefix_hi, 0);
+            let key_rlc = at(meta, c.key_rlc, 0);
+            let is_storage = at(meta, c.is_storage, 0);
+            let is_last_level = at(meta, c.is_last_level, 0);
+            let is_fork = at(meta, c.is_fork, 0);
+            let short = c.short.map(|column| at(meta, column, 0));
+            let is_split = sum::expr(short.clone());
+            let end_odd = at(meta, c.end_odd, 0);
+            let end_acc = at(meta, c.end_acc, 0);
+            let end = at(meta, c.end_half, 0) * 2.expr() + end_odd.clone();
+            let end_rlc = select::expr(
+                end_odd,
+                end_acc.clone() * r_k.clone() + at(meta, c.end_hi, 0),
+                end_acc,
+            );
+
+            cb.require_equal(
+                "depth",
+                depth.clone(),
+                at(meta, c.depth_half, 0) * 2.expr() + depth_odd.clone(),
+            );
+            cb.require_equal(
+                "path of the key before the level",
+                nib_acc.clone(),
+                select::expr(
+                    depth_odd.clone(),
+                    prefix_acc.clone() * r_k.clone() + prefix_hi.clone(),
+                    prefix_acc,
+                ),
+            );
+            cb.require_equal(
+                "nibble of the key at the level",
+                at(meta, c.nibble, 0),
+                select::expr(depth_odd, at(meta, c.prefix_lo, 0), prefix_hi),
+            );
+
+            // The first level holds the roots of the tries.
+            cb.condition(at(meta, c.is_key, -1), |cb| {
+                for (s, node) in c.nodes.iter().enumerate() {
+                    let has_node = node.has_node(meta, 0);
+                    let root = select::expr(
+                        is_storage.clone(),
+                        at(meta, c.storage_roots[s], 0),
+                        at(meta, table[4 - s], 0),
+                    );
+                    cb.require_zero(
+                        "root is the hash of the first node",
+                        has_node.clone() * (at(meta, node.hash, 0) - root.clone()),
+                    );
+                    cb.require_zero(
+                        "trie without node is empty",
+                        not::expr(has_node) * (root - at(meta, c.empty_root, 0)),
+                    );
+                }
+                cb.require_equal("split at the root", is_fork.clone(), is_split.clone());
+            });
+
+            // A split starts at the fork, where the short side holds its last
+            // node, which is repeated at the level of the split branch.
+            cb.require_boolean("one short side", is_split.clone());
+            cb.require_zero(
+                "fork is on the short side",
+                is_fork.clone() * not::expr(is_split.clone()),
+            );
+            cb.require_zero(
+                "fork is not the last level",
+                is_fork.clone() * is_last_level.clone(),
+            );
+
+            let [old, new] = c.nodes;
+            for (old_column, new_column) in [
+                (old.is_branch, new.is_branch),
+                (old.is_extension, new.is_extension),
+            ] {
+                cb.require_zero(
+                    "both sides have the same inner node",
+                    not::expr(is_split.clone())
+                        * not::expr(is_last_level.clone())
+                        * (at(meta, old_column, 0) - at(meta, new_column, 0)),
+                );
+            }
+            for (s, node) in c.nodes.iter().enumerate() {
+                let is_short = short[s].clone();
+                let is_repeated =
+                    is_short.clone() * (1.expr() - is_fork.clone() - is_last_level.clone());
+                let is_leaf = at(meta, node.is_leaf, 0);
+                let is_other_leaf = at(meta, node.is_other_leaf, 0);
+                let is_other_extension = at(meta, node.is_other_extension, 0);
+                let is_inner = at(meta, node.is_branch, 0) + at(meta, node.is_extension, 0)
+                    - is_other_extension.clone();
+                let path_len = at(meta, node.path_len, 0);
+                let path_rlc = at(meta, node.path_rlc, 0);
+                let key_inv = at(meta, node.key_inv, 0);
+
+                cb.require_zero(
+                    "node is on the path of the key",
+                    (1.expr() - is_short.clone() * not::expr(is_fork.clone()))
+                        * (at(meta, node.nib_acc, 0) - nib_acc.clone()),
+                );
+                cb.require_zero(
+                    "inner levels hold a branch or an extension",
+                    not::expr(is_short.clone())
+                        * not::expr(is_last_level.clone())
+                        * (is_inner.clone() - 1.expr()),
+                );
+                cb.require_zero(
+                    "last level holds no inner node",
+                    is_last_level.clone() * is_inner,
+                );
+                cb.require_zero(
+                    "short side ends with another leaf or extension",
+                    is_short.clone()
+                        * not::expr(is_last_level.clone())
+                        * (is_other_leaf.clone() + is_other_extension.clone() - 1.expr()),
+                );
+                cb.require_zero(
+                    "long side holds the leaf of the key",
+                    is_short * is_last_level.clone() * not::expr(c.nodes[1 - s].exists(meta)),
+                );
+                cb.require_zero(
+                    "leaf path ends the key",
+                    not::expr(is_repeated.clone())
+                        * is_leaf.clone()
+                        * (depth.clone() + path_len.clone() - PATH_LEN.expr()),
+                );
+                cb.require_zero(
+                    "leaf of the key",
+                    (is_leaf - is_other_leaf.clone()) * (path_rlc.clone() - key_rlc.clone()),
+                );
+                cb.require_zero(
+                    "leaf of another key",
+                    is_other_leaf
+                        * ((path_rlc.clone() - key_rlc.clone()) * key_inv.clone() - 1.expr()),
+                );
+                cb.require_zero(
+                    "end of the extension leaving the key",
+                    not::expr(is_repeated)
+                        * is_other_extension.clone()
+                        * (depth.clone() + path_len - end.clone()),
+                );
+                cb.require_zero(
+                    "extension leaving the key",
+                    is_other_extension * ((path_rlc - end_rlc.clone()) * key_inv - 1.expr()),
+                );
+            }
+
+            cb.gate(q_level(meta) * at(meta, c.is_first, 0))
+        });
+
+        // The path of the key before the level, and to the end of an extension
+        // leaving it, are looked up in the key block of the part.
+        for (name, half, acc, hi, lo) in [
+            (
+                "mpt key prefix",
+                c.depth_half,
+                c.prefix_acc,
+                c.prefix_hi,
+                c.prefix_lo,
+            ),
+            ("mpt key end", c.end_half, c.end_acc, c.end_hi, c.end_lo),
+        ] {
+            meta.lookup_any(name, |meta| {
+                let enable = q_level(meta) * at(meta, c.is_first, 0);
+                let q_key = meta.query_fixed(c.q_enable, Rotation::cur()) * at(meta, c.is_key, 0);
+                let [hash, _] = c.nodes;
+                [
+                    (c.part_id, c.part_id),
+                    (half, c.index),
+                    (acc, c.key_acc),
+                    (hi, hash.hi),
+                    (lo, hash.lo),
+                ]
+                .into_iter()
+                .map(|(input, table)| {
+                    (
+                        enable.clone() * at(meta, input, 0),
+                        q_key.clone() * at(meta, table, 0),
+                    )
+                })
+                .collect()
+            });
+        }
+
+        meta.create_gate("mpt level end", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let [old, new] = c.nodes;
+            let depth = at(meta, c.depth, 0);
+            let nib_acc = at(meta, c.nib_acc, 0);
+            let short = c.short.map(|column| at(meta, column, 0));
+            let is_split = sum::expr(short.clone());
+            let is_last_level_next = at(meta, c.is_last_level, 1);
+            let end = at(meta, c.end_half, 0) * 2.expr() + at(meta, c.end_odd, 0);
+            let end_next = at(meta, c.end_half, 1) * 2.expr() + at(meta, c.end_odd, 1);
+
+            // The key follows the node of the long side of a split.
+            let path_node = |meta: &mut VirtualCells<'_, F>, old_column, new_column| {
+                let old_value = at(meta, old_column, 0);
+                old_value.clone() + short[0].clone() * (at(meta, new_column, 0) - old_value)
+            };
+            let is_branch = path_node(meta, old.is_branch, new.is_branch);
+            let path_len = path_node(meta, old.path_len, new.path_len);
+            let path_rlc = path_node(meta, old.path_rlc, new.path_rlc);
+
+            cb.require_equal("next level", at(meta, c.is_level, 1), 1.expr());
+            cb.require_equal(
+                "depth of the next level",
+                at(meta, c.depth, 1),
+                depth.clone() + select::expr(is_branch.clone(), 1.expr(), path_len),
+            );
+            cb.require_equal(
+                "path of the next level",
+                at(meta, c.nib_acc, 1),
+                select::expr(
+                    is_branch,
+                    nib_acc.clone() * r_k.clone() + at(meta, c.nibble, 0),
+                    path_rlc,
+                ),
+            );
+
+            for (s, is_short) in short.iter().enumerate() {
+                cb.require_zero(
+                    "short side stays short",
+                    is_short.clone() * not::expr(at(meta, c.short[s], 1)),
+                );
+            }
+            cb.require_equal(
+                "fork where the short side starts",
+                at(meta, c.is_fork, 1),
+                sum::expr(c.short.map(|column| at(meta, column, 1))) - is_split.clone(),
+            );
+
+            for (s, node) in c.nodes.iter().enumerate() {
+                let is_short = short[s].clone();
+                let child_is_empty = at(meta, node.child_is_empty, 0);
+                let has_node = node.has_node(meta, 1);
+                cb.require_zero(
+                    "empty child ends the path",
+                    not::expr(is_short.clone())
+                        * child_is_empty.clone()
+                        * not::expr(is_last_level_next.clone()),
+                );
+                cb.require_zero(
+                    "empty child has no node",
+                    not::expr(is_short.clone()) * child_is_empty.clone() * has_node.clone(),
+                );
+                cb.require_zero(
+                    "child is the next node",
+                    not::expr(is_short.clone())
+                        * not::expr(child_is_empty.clone())
+                        * not::expr(has_node.clone()),
+                );
+                cb.require_zero(
+                    "hash of the next node",
+                    not::expr(is_short.clone())
+                        * not::expr(child_is_empty)
+                        * (at(meta, node.hash, 1) - at(meta, node.child, 0)),
+                );
+
+                // The last node of the short side is repeated once, when the
+                // long side has an extension of the common path before the
+                // split branch.
+                let is_repeated = is_short.clone() * not::expr(is_last_level_next.clone());
+                let long = c.nodes[1 - s];
+                for column in [node.hash, node.nib_acc] {
+                    cb.require_zero(
+                        "repeated node",
+                        is_repeated.clone() * (at(meta, column, 1) - at(meta, column, 0)),
+                    );
+                }
+                cb.require_zero(
+                    "repeated node",
+                    is_repeated.clone() * (end_next.clone() - end.clone()),
+                );
+                cb.require_zero(
+                    "node is repeated once",
+                    is_repeated.clone() * not::expr(at(meta, c.is_fork, 0)),
+                );
+                cb.require_zero(
+                    "node is repeated after an extension",
+                    is_repeated * not::expr(at(meta, long.is_extension, 0)),
+                );
+
+                // The branch of the long side splits the last node of the short
+                // side, which drifts to the next level without the nibbles of
+                // the branch and of the extension before it.
+                let is_split_level = is_short * is_last_level_next.clone();
+                let other_child = at(meta, long.other_child, 0);
+                let branch_path = nib_acc.clone() * r_k.clone() + at(meta, long.other_nibble, 0);
+                let is_leaf = at(meta, node.is_leaf, 0);
+                let is_extension = at(meta, node.is_extension, 0);
+                let drifted_is_extension = at(meta, node.is_extension, 1);
+                cb.require_zero(
+                    "split node is a branch",
+                    is_split_level.clone() * not::expr(at(meta, long.is_branch, 0)),
+                );
+                cb.require_zero(
+                    "split branch has two children",
+                    is_split_level.clone() * (at(meta, long.children, 0) - 2.expr()),
+                );
+                cb.require_zero(
+                    "split branch holds the key",
+                    is_split_level.clone() * at(meta, long.child_is_empty, 0),
+                );
+                cb.require_zero(
+                    "path of the drifted node",
+                    is_split_level.clone() * (at(meta, node.nib_acc, 1) - branch_path.clone()),
+                );
+                cb.require_zero(
+                    "drifted leaf",
+                    is_split_level.clone() * is_leaf.clone() * not::expr(at(meta, node.is_leaf, 1)),
+                );
+                for column in node.values {
+                    cb.require_zero(
+                        "value of the drifted leaf",
+                        is_split_level.clone()
+                            * is_leaf.clone()
+                            * (at(meta, column, 1) - at(meta, column, 0)),
+                    );
+                }
+                cb.require_zero(
+                    "drifted extension",
+                    is_split_level.clone() * drifted_is_extension.clone() * not::expr(is_extension),
+                );
+                cb.require_zero(
+                    "child of the drifted extension",
+                    is_split_level.clone()
+                        * drifted_is_extension.clone()
+                        * (at(meta, node.child, 1) - at(meta, node.child, 0)),
+                );
+                cb.require_zero(
+                    "end of the drifted extension",
+                    is_split_level.clone()
+                        * drifted_is_extension
+                        * (end_next.clone() - end.clone()),
+                );
+                cb.require_zero(
+                    "hash of the drifted node",
+                    is_split_level.clone()
+                        * has_node.clone()
+                        * (at(meta, node.hash, 1) - other_child.clone()),
+                );
+                cb.require_zero(
+                    "path of the drifted node",
+                    is_split_level.clone()
+                        * has_node.clone()
+                        * (at(meta, node.path_rlc, 1) - at(meta, node.path_rlc, 0)),
+                );
+
+                // An extension of the nibble of the split branch only is
+                // replaced by the branch.
+                let no_drift = is_split_level * not::expr(has_node);
+                cb.require_zero(
+                    "extension of the branch nibble",
+                    no_drift.clone() * (at(meta, node.child, 0) - other_child),
+                );
+                cb.require_zero(
+                    "extension of the branch nibble",
+                    no_drift.clone() * (end.clone() - depth.clone() - 1.expr()),
+                );
+                cb.require_zero(
+                    "extension of the branch nibble",
+                    no_drift * (at(meta, node.path_rlc, 0) - branch_path),
+                );
+            }
+            for (old_column, new_column) in
+                [(old.pre_rlc, new.pre_rlc), (old.post_rlc, new.post_rlc)]
+            {
+                cb.require_zero(
+                    "other items are unchanged",
+                    not::expr(is_split.clone())
+                        * (at(meta, old_column, 0) - at(meta, new_column, 0)),
+                );
+            }
+
+            cb.gate(
+                q_level(meta) * at(meta, c.is_last, 0) * not::expr(at(meta, c.is_last_level, 0)),
+            )
+        });
+
+        meta.create_gate("mpt last level", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let [old, new] = c.nodes;
+            let is_storage = at(meta, c.is_storage, 0);
+            let is_account = not::expr(is_storage.clone());
+            let is_split = sum::expr(c.short.map(|column| at(meta, column, 0)));
+            let exists = [old.exists(meta), new.exists(meta)];
+            let values = [at(meta, table[6], 0), at(meta, table[5], 0)];
+            let proof_types = c.proof_types.map(|column| at(meta, column, 0));
+            let storage_flag = c.storage_flag(meta, 0);
+            let storage_roots = c.storage_roots.map(|column| at(meta, column, 0));
+            let empty_root = at(meta, c.empty_root, 0);
+            let empty_code = at(meta, c.empty_code, 0);
+
+            // Without a split, the node proving that the key is not in the
+            // trie is on both sides.
+            for (name, old_column, new_column) in [
+                (
+                    "leaf of another key is unchanged",
+                    old.is_other_leaf,
+                    new.is_other_leaf,
+                ),
+                (
+                    "extension off the key is unchanged",
+                    old.is_other_extension,
+                    new.is_other_extension,
+                ),
+            ] {
+                let is_other = at(meta, old_column, 0);
+                cb.require_zero(
+                    name,
+                    not::expr(is_split.clone()) * (is_other.clone() - at(meta, new_column, 0)),
+                );
+                cb.require_zero(
+                    name,
+                    not::expr(is_split.clone())
+                        * is_other
+                        * (at(meta, old.hash, 0) - at(meta, new.hash, 0)),
+                );
+            }
+
+            // Storage values
+            for (s, node) in c.nodes.iter().enumerate() {
+                cb.require_zero(
+                    "storage value",
+                    is_storage.clone()
+                        * (values[s].clone() - exists[s].clone() * at(meta, node.values[1], 0)),
+                );
+            }
+
+            // Account fields, which have their default values when the account
+            // does not exist.
+            let defaults = [0.expr(), 0.expr(), empty_root.clone(), empty_code.clone()];
+            let fields = [0, 1].map(|s| {
+                let node = c.nodes[s];
+                let exists = exists[s].clone();
+                defaults
+                    .iter()
+                    .enumerate()
+                    .map(|(k, default)| {
+                        exists.clone() * at(meta, node.values[k], 0)
+                            + not::expr(exists.clone()) * default.clone()
+                    })
+                    .collect::<Vec<_>>()
+            });
+            let updated = [
+                proof_types[0].clone(),
+                proof_types[1].clone(),
+                storage_flag.clone(),
+                proof_types[2].clone(),
+            ];
+            for (k, updated) in updated.iter().enumerate() {
+                cb.require_zero(
+                    "account field is unchanged",
+                    is_account.clone()
+                        * not::expr(updated.clone())
+                        * (fields[0][k].clone() - fields[1][k].clone()),
+                );
+            }
+            for (s, node) in c.nodes.iter().enumerate() {
+                for k in [0, 1] {
+                    cb.require_zero(
+                        "account value",
+                        proof_types[k].clone()
+                            * (values[s].clone() - exists[s].clone() * at(meta, node.values[k], 0)),
+                    );
+                }
+                cb.require_zero(
+                    "code hash",
+                    proof_types[2].clone()
+                        * exists[s].clone()
+                        * (values[s].clone() - at(meta, node.values[3], 0)),
+                );
+                cb.require_zero(
+                    "code hash of a missing account",
+                    proof_types[2].clone()
+                        * not::expr(exists[s].clone())
+                        * values[s].clone()
+                        * (values[s].clone() - empty_code.clone()),
+                );
+                cb.require_zero(
+                    "storage root of the account",
+                    is_account.clone()
+                        * storage_flag.clone()
+                        * (storage_roots[s].clone() - fields[s][2].clone()),
+                );
+                cb.require_zero(
+                    "account does not exist",
+                    proof_types[3].clone() * exists[s].clone(),
+                );
+                cb.require_zero(
+                    "account does not exist",
+                    proof_types[3].clone() * values[s].clone(),
+                );
+            }
+
+            // A created account is not empty, and an account is deleted when
+            // the update empties it.
+            let is_creation = at(meta, c.is_creation, 0);
+            let is_deletion = at(meta, c.is_deletion, 0);
+            cb.require_equal(
+                "account creation",
+                is_creation.clone(),
+                is_account.clone() * not::expr(exists[0].clone()) * exists[1].clone(),
+            );
+            cb.require_equal(
+                "account deletion",
+                is_deletion.clone(),
+                is_account * exists[0].clone() * not::expr(exists[1].clone()),
+            );
+            let difference = |s: usize| {
+                (proof_types[0].clone() + proof_types[1].clone()) * values[s].clone()
+                    + proof_types[2].clone() * (values[s].clone() - empty_code.clone())
+                    + storage_flag.clone() * (storage_roots[s].clone() - empty_root.clone())
+            };
+            let value_inv = at(meta, c.value_inv, 0);
+            cb.require_zero(
+                "created account is not empty",
+                is_creation * (difference(1) * value_inv.clone() - 1.expr()),
+            );
+            cb.require_zero(
+                "deleted account was not empty",
+                is_deletion * (difference(0) * value_inv - 1.expr()),
+            );
+
+            cb.gate(q_level(meta) * at(meta, c.is_first, 0) * at(meta, c.is_last_level, 0))
+        });
+    }
+
+    /// Load the byte table
+    pub(crate) fn load_aux_tables(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        layouter.assign_region(
+            || "mpt byte table",
+            |mut region| {
+                for byte in 0..256u64 {
+                    for (column, value) in self.byte_table.iter().zip([
+                        byte,
+                        byte >> 4,
+                        byte & 0xf,
+                        (byte >= 0x80) as u64,
+                    ]) {
+                        region.assign_fixed(
+                            || "mpt byte table",
+                            *column,
+                            byte as usize,
+                            || Value::known(F::from(value)),
+                        )?;
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+
+    /// Assign the proofs of the updates, and the MptTable.
+    pub(crate) fn assign(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        updates: &MptUpdates,
+        challenges: &Challenges<Value<F>>,
+    ) -> Result<(), Error> {
+        let witnesses = updates
+            .witnesses()
+            .iter()
+            .map(UpdateWitness::new)
+            .collect::<Result<Vec<_>, _>>()?;
+        let r_w = challenges.evm_word();
+        let globals = MptRow {
+            empty_root: r_w.map(|r_w| rlc_word(EMPTY_TRIE_ROOT.as_bytes(), r_w)),
+            empty_code: r_w.map(|r_w| rlc_word(EMPTY_CODE_HASH.as_bytes(), r_w)),
+            ..Default::default()
+        };
+
+        layouter.assign_region(
+            || "mpt circuit",
+            |mut region| {
+                self.assign_row(&mut region, 0, &MptRow::default())?;
+                for node in self.nodes {
+                    node.assign_empty(&mut region, 0)?;
+                }
+
+                let mut offset = 1;
+                let mut part_id = 0;
+                for witness in witnesses.iter() {
+                    let update = MptRow {
+                        proof_type: Some(witness.proof_type),
+                        storage_roots: witness
+                            .storage_roots
+                            .map(|root| r_w.map(|r_w| rlc_word(&root, r_w))),
+                        table: witness.update.table_row(r_w),
+                        ..globals
+                    };
+                    for part in witness.parts.iter() {
+                        part_id += 1;
+                        let row = MptRow {
+                            part_id,
+                            is_storage: part.is_storage,
+                            ..update
+                        };
+                        offset = self.assign_part(&mut region, offset, row, part, challenges)?;
+                    }
+                }
+
+                // Padding, with at least one row where the circuit ends
+                region.assign_fixed(
+                    || "mpt q_last",
+                    self.q_last,
+                    offset,
+                    || Value::known(F::one()),
+                )?;
+                self.assign_row(&mut region, offset, &globals)?;
+                for node in self.nodes {
+                    node.assign_empty(&mut region, offset)?;
+                }
+                for offset in 1..=offset {
+                    region.assign_fixed(
+                        || "mpt q_enable",
+                        self.q_enable,
+                        offset,
+                        || Value::known(F::one()),
+                    )?;
+                }
+                region.assign_fixed(
+                    || "mpt q_first",
+                    self.q_first,
+                    1,
+                    || Value::known(F::one()),
+                )?;
+                Ok(())
+            },
+        )
+    }
+
+    /// Assigns the key block and the levels of a part from `offset`, returning
+    /// the offset after them.
+    fn assign_part(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        row: MptRow<F>,
+        part: &PartWitness,
+        challenges: &Challenges<Value<F>>,
+    ) -> Result<usize, Error> {
+        let (r_k, r_w) = (challenges.keccak_input(), challenges.evm_word());
+        let path = &part.path;
+        let key_acc = |len: usize| {
+            r_k.map(|r_k| horner(path[..len].iter().map(|nibble| *nibble as u64), r_k))
+        };
+        let row = MptRow {
+            key_rlc: key_acc(PATH_LEN),
+            ..row
+        };
+
+        let mut offset = offset;
+        let preimage_base = if part.is_storage {
+            r_w
+        } else {
+            Value::known(F::from(256))
+        };
+        for index in 0..KEY_ROWS {
+            let bytes = |bytes: &[u8]| {
+                bytes[..=index]
+                    .iter()
+                    .map(|byte| *byte as u64)
+                    .collect::<Vec<_>>()
+            };
+            let (hash, preimage) = (bytes(&part.hash), bytes(&part.preimage));
+            self.assign_row(
+                region,
+                offset,
+                &MptRow {
+                    is_key: true,
+                    is_first: index == 0,
+                    is_last: index == KEY_ROWS - 1,
+                    index,
+                    key_acc: key_acc(2 * index),
+                    key_hash_acc: r_w.map(|r_w| horner(hash.iter().copied(), r_w)),
+                    preimage_rlc: r_k.map(|r_k| horner(preimage.iter().copied(), r_k)),
+                    preimage_value: preimage_base
+                        .map(|base| horner(preimage.iter().copied(), base)),
+                    ..row
+                },
+            )?;
+            for (node, byte) in self
+                .nodes
+                .iter()
+                .zip([part.hash[index], part.preimage[index]])
+            {
+                // The node columns are empty, except for the byte of the key.
+                node.assign_empty(region, offset)?;
+                node.assign_byte(region, offset, byte)?;
+            }
+            offset += 1;
+        }
+
+        for level in part.levels.iter() {
+            let depth = level.depth;
+            let (half, end_half) = (depth / 2, level.end / 2);
+            let height = level.height();
+            let (is_creation, is_deletion, value_inv) = if level.is_last && !part.is_storage {
+                self.creation(&row, level)
+            } else {
+                (false, false, Value::known(F::zero()))
+            };
+            let level_row = MptRow {
+                is_level: true,
+                depth,
+                nibble: path[depth],
+                nib_acc: key_acc(depth),
+                prefix_acc: key_acc(2 * half),
+                prefix: [path[2 * half], path[2 * half + 1]],
+                is_last_level: level.is_last,
+                short: [0, 1].map(|s| level.short == Some(s)),
+                is_fork: level.is_fork,
+                end: level.end,
+                end_acc: key_acc(2 * end_half),
+                end_prefix: [path[2 * end_half], path[2 * end_half + 1]],
+                is_creation,
+                is_deletion,
+                value_inv,
+                ..row
+            };
+            for index in 0..height {
+                self.assign_row(
+                    region,
+                    offset + index,
+                    &MptRow {
+                        is_first: index == 0,
+                        is_last: index == height - 1,
+                        index,
+                        ..level_row
+                    },
+                )?;
+            }
+            for ((node, witness), prefix) in self
+                .nodes
+                .iter()
+                .zip(level.nodes.iter())
+                .zip(level.prefixes.iter())
+            {
+                // The path of an extension leaving the key is compared to the
+                // path of the key to its end.
+                let key_rlc = match witness.as_ref().map(|witness| witness.kind) {
+                    Some(NodeKind::OtherExtension) => key_acc(level.end),
+                    _ => row.key_rlc,
+                };
+                node.assign(
+                    region,
+                    offset,
+                    height,
+                    witness.as_ref(),
+                    r_k.map(|r_k| horner(prefix.iter().map(|nibble| *nibble as u64), r_k)),
+                    key_rlc,
+                    challenges,
+                )?;
+            }
+            offset += height;
+        }
+        Ok(offset)
+    }
+
+    /// Returns whether the last level of an account part creates or deletes
+    /// the account, with the inverse of the difference between the updated
+    /// value of the existing account and its default.
+    fn creation(&self, row: &MptRow<F>, level: &LevelWitness) -> (bool, bool, Value<F>) {
+        let exists = level
+            .nodes
+            .clone()
+            .map(|node| node.map_or(false, |node| node.kind == NodeKind::Leaf));
+        let s = match exists {
+            [false, true] => 1,
+            [true, false] => 0,
+            _ => return (false, false, Value::known(F::zero())),
+        };
+        let value = row.table.0[6 - s];
+        let difference = match row.proof_type {
+            Some(ProofType::NonceChanged | ProofType::BalanceChanged) => value,
+            Some(ProofType::CodeHashExists) => value - row.empty_code,
+            _ => row.storage_roots[s] - row.empty_root,
+        };
+        (
+            s == 1,
+            s == 0,
+            difference.map(|value| value.invert().unwrap_or(F::zero())),
+        )
+    }
+
+    fn assign_row(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        row: &MptRow<F>,
+    ) -> Result<(), Error> {
+        let depth = row.depth as u64;
+        let flags = PROOF_TYPES.map(|proof_type| row.proof_type == Some(proof_type));
+        for (column, value) in [
+            (self.is_key, row.is_key as u64),
+            (self.is_level, row.is_level as u64),
+            (self.is_first, row.is_first as u64),
+            (self.is_last, row.is_last as u64),
+            (self.index, row.index as u64),
+            (self.part_id, row.part_id as u64),
+            (self.is_storage, row.is_storage as u64),
+            (self.depth, depth),
+            (self.depth_half, depth / 2),
+            (self.depth_odd, depth % 2),
+            (self.nibble, row.nibble as u64),
+            (self.prefix_hi, row.prefix[0] as u64),
+            (self.prefix_lo, row.prefix[1] as u64),
+            (self.is_last_level, row.is_last_level as u64),
+            (self.short[0], row.short[0] as u64),
+            (self.short[1], row.short[1] as u64),
+            (self.is_fork, row.is_fork as u64),
+            (self.end_half, row.end as u64 / 2),
+            (self.end_odd, row.end as u64 % 2),
+            (self.end_hi, row.end_prefix[0] as u64),
+            (self.end_lo, row.end_prefix[1] as u64),
+            (self.is_creation, row.is_creation as u64),
+            (self.is_deletion, row.is_deletion as u64),
+        ]
+        .into_iter()
+        .chain(
+            self.proof_types
+                .into_iter()
+                .zip(flags.map(|flag| flag as u64)),
+        ) {
+            region.assign_advice(
+                || "mpt row",
+                column,
+                offset,
+                || Value::known(F::from(value)),
+            )?;
+        }
+        for (column, value) in [
+            (self.key_rlc, row.key_rlc),
+            (self.key_acc, row.key_acc),
+            (self.key_hash_acc, row.key_hash_acc),
+            (self.preimage_rlc, row.preimage_rlc),
+            (self.preimage_value, row.preimage_value),
+            (self.nib_acc, row.nib_acc),
+            (self.prefix_acc, row.prefix_acc),
+            (self.end_acc, row.end_acc),
+            (self.value_inv, row.value_inv),
+            (self.storage_roots[0], row.storage_roots[0]),
+            (self.storage_roots[1], row.storage_roots[1]),
+            (self.empty_root, row.empty_root),
+            (self.empty_code, row.empty_code),
+        ] {
+            region.assign_advice(|| "mpt row", column, offset, || value)?;
+        }
+        self.mpt_table.assign(region, offset, &row.table)
+    }
+}
+
+/// The assignments of a row of the columns shared by both sides.
+#[derive(Clone, Copy, Debug)]
+struct MptRow<F> {
+    is_key: bool,
+    is_level: bool,
+    is_first: bool,
+    is_last: bool,
+    index: usize,
+    part_id: usize,
+    is_storage: bool,
+    key_rlc: Value<F>,
+    key_acc: Value<F>,
+    key_hash_acc: Value<F>,
+    preimage_rlc: Value<F>,
+    preimage_value: Value<F>,
+    depth: usize,
+    nibble: u8,
+    nib_acc: Value<F>,
+    prefix_acc: Value<F>,
+    prefix: [u8; 2],
+    is_last_level: bool,
+    short: [bool; 2],
+    is_fork: bool,
+    end: usize,
+    end_acc: Value<F>,
+    end_prefix: [u8; 2],
+    is_creation: bool,
+    is_deletion: bool,
+    value_inv: Value<F>,
+    proof_type: Option<ProofType>,
+    storage_roots: [Value<F>; 2],
+    empty_root: Value<F>,
+    empty_code: Value<F>,
+    table: MptUpdateRow<Value<F>>,
+}
+
+impl<F: Field> Default for MptRow<F> {
+    fn default() -> Self {
+        let zero = Value::known(F::zero());
+        Self {
+            is_key: false,
+            is_level: false,
+            is_first: false,
+            is_last: false,
+            index: 0,
+            part_id: 0,
+            is_storage: false,
+            key_rlc: zero,
+            key_acc: zero,
+            key_hash_acc: zero,
+            preimage_rlc: zero,
+            preimage_value: zero,
+            depth: 0,
+            nibble: 0,
+            nib_acc: zero,
+            prefix_acc: zero,
+            prefix: [0; 2],
+            is_last_level: false,
+            short: [false; 2],
+            is_fork: false,
+            end: 0,
+            end_acc: zero,
+            end_prefix: [0; 2],
+            is_creation: false,
+            is_deletion: false,
+            value_inv: zero,
+            proof_type: None,
+            storage_roots: [zero; 2],
+            empty_root: zero,
+            empty_code: zero,
+            table: MptUpdateRow([zero; 7]),
+        }
+    }
+}
+
+/// Horner evaluation of `values` with `base`, the first value getting the
+/// highest power.
+fn horner<F: Field>(values: impl IntoIterator<Item = u64>, base: F) -> F {
+    values
+        .into_iter()
+        .fold(F::zero(), |acc, value| acc * base + F::from(value))
+}
+
+/// The witness of an update, split in its parts.
+#[derive(Clone, Debug)]
+struct UpdateWitness {
+    update: MptUpdate,
+    proof_type: ProofType,
+    /// The storage roots of the account before and after a storage update
+    storage_roots: [[u8; 32]; 2],
+    parts: Vec<PartWitness>,
+}
+
+impl UpdateWitness {
+    fn new(witness: &MptUpdateWitness) -> Result<Self, Error> {
+        let update = &witness.update;
+        let proof_type = update.proof_type_tag();
+        if !PROOF_TYPES.contains(&proof_type) {
+            log::error!("unsupported mpt proof type {:?}", proof_type);
+            return Err(Error::Synthesis);
+        }
+
+        let mut preimage = [0; 32];
+        preimage[12..].copy_from_slice(update.address().as_bytes());
+        let mut parts = vec![PartWitness::new(&witness.account_proof, preimage, false)?];
+        let mut storage_roots = [*EMPTY_TRIE_ROOT.as_fixed_bytes(); 2];
+        if let (Some(proof), Some(storage_key)) = (&witness.storage_proof, update.storage_key()) {
+            for (root, nodes) in storage_roots.iter_mut().zip([&proof.old, &proof.new]) {
+                if let Some(node) = nodes.first() {
+                    *root = keccak256(node);
+                }
+            }
+            parts.push(PartWitness::new(proof, storage_key.to_be_bytes(), true)?);
+        }
+
+        Ok(Self {
+            update: *update,
+            proof_type,
+            storage_roots,
+            parts,
+        })
+    }
+}
+
+/// The witness of a part: the key of a trie and the levels of its proof.
+#[derive(Clone, Debug)]
+struct PartWitness {
+    is_storage: bool,
+    /// The key, which is hashed to get its path
+    preimage: [u8; 32],
+    hash: [u8; 32],
+    /// The nibbles of the hash of the key
+    path: Vec<u8>,
+    levels: Vec<LevelWitness>,
+}
+
+/// The nodes of both sides of a part at the same depth.
+#[derive(Clone, Debug)]
+struct LevelWitness {
+    depth: usize,
+    nodes: [Option<NodeWitness>; 2],
+    /// The path of the key before the node of each side, which is not the path
+    /// before the level for the short side of a split
+    prefixes: [Vec<u8>; 2],
+    /// The short side of a split, and whether the level holds its last node
+    short: Option<usize>,
+    is_fork: bool,
+    /// The depth of the end of an extension leaving the key, or of the level
+    /// without one
+    end: usize,
+    is_last: bool,
+}
+
+impl LevelWitness {
+    fn height(&self) -> usize {
+        self.nodes
+            .iter()
+            .flatten()
+            .map(|node| node.bytes.len())
+            .max()
+            .unwrap_or(1)
+    }
+}
+
+fn unsupported(reason: &str) -> Error {
+    log::error!("unsupported mpt proof: {}", reason);
+    Error::Synthesis
+}
+
+impl PartWitness {
+    /// Splits the proof of a key in levels, where both sides follow the same
+    /// path until one of them splits the last node of the other.
+    fn new(proof: &MptProof, preimage: [u8; 32], is_storage: bool) -> Result<Self, Error> {
+        let key = if is_storage {
+            &preimage[..]
+        } else {
+            &preimage[12..]
+        };
+        let hash = keccak256(key);
+        let path: Vec<u8> = hash
+            .iter()
+            .flat_map(|byte| [byte >> 4, byte & 0xf])
+            .collect();
+
+        let mut levels = vec![];
+        let mut depth = 0;
+        for i in 0.. {
+            if depth >= PATH_LEN {
+                return Err(unsupported("path is longer than the key"));
+            }
+            let nodes = [&proof.old, &proof.new].map(|nodes| {
+                nodes.get(i).map(|bytes| NodeWitness {
+                    kind: node_kind(bytes, &path[depth..]),
+                    bytes: bytes.clone(),
+                    nibble: path[depth],
+                    is_storage,
+                })
+            });
+            let kinds = nodes.clone().map(|node| node.map(|node| node.kind));
+            let is_inner = |kind: Option<NodeKind>| {
+                matches!(kind, Some(NodeKind::Branch | NodeKind::Extension))
+            };
+            let is_other = |kind: Option<NodeKind>| {
+                matches!(kind, Some(NodeKind::OtherLeaf | NodeKind::OtherExtension))
+            };
+            let next_depth = match kinds {
+                [Some(NodeKind::Branch), Some(NodeKind::Branch)] => Some(depth + 1),
+                [Some(NodeKind::Extension), Some(NodeKind::Extension)] => {
+                    let extension = path_nibbles(&nodes[0].as_ref().unwrap().bytes);
+                    if extension != path_nibbles(&nodes[1].as_ref().unwrap().bytes) {
+                        return Err(unsupported("extension node is updated"));
+                    }
+                    Some(depth + extension.len())
+                }
+                [old, new] if is_inner(old) && is_other(new) || is_other(old) && is_inner(new) => {
+                    let short = usize::from(is_other(new));
+                    levels.extend(Self::split(proof, i, depth, short, &path, is_storage)?);
+                    break;
+                }
+                [old, new] if is_inner(old) || is_inner(new) => {
+                    return Err(unsupported("inner node is added or removed"))
+                }
+                [old, new] => {
+                    if (is_other(old) || is_other(new))
+                        && nodes[0].as_ref().map(|node| &node.bytes)
+                            != nodes[1].as_ref().map(|node| &node.bytes)
+                    {
+                        return Err(unsupported("node of another key is updated"));
+                    }
+                    if proof.old.len() > i + 1 || proof.new.len() > i + 1 {
+                        return Err(unsupported("nodes after the leaf"));
+                    }
+                    None
+                }
+            };
+            let end = nodes
+                .iter()
+                .flatten()
+                .find(|node| node.kind == NodeKind::OtherExtension)
+                .map_or(depth, |node| depth + path_nibbles(&node.bytes).len());
+            levels.push(LevelWitness {
+                depth,
+                nodes,
+                prefixes: [(); 2].map(|_| path[..depth].to_vec()),
+                short: None,
+                is_fork: false,
+                end,
+                is_last: next_depth.is_none(),
+            });
+            match next_depth {
+                Some(next_depth) => depth = next_depth,
+                None => break,
+            }
+        }
+
+        Ok(Self {
+            is_storage,
+            preimage,
+            hash,
+            path,
+            levels,
+        })
+    }
+
+    /// Returns the levels from the fork at the node `index` of the proof,
+    /// where the `short` side ends with a leaf or an extension leaving the key
+    /// which the long side splits with a branch, under an extension of their
+    /// common path if any: the fork, the level of the branch if it is under an
+    /// extension, and the last level with the drifted node and the leaf of
+    /// the key.
+    fn split(
+        proof: &MptProof,
+        index: usize,
+        depth: usize,
+        short: usize,
+        path: &[u8],
+        is_storage: bool,
+    ) -> Result<Vec<LevelWitness>, Error> {
+        let witness = |bytes: &Vec<u8>, kind, depth: usize| NodeWitness {
+            kind,
+            bytes: bytes.clone(),
+            nibble: path[depth],
+            is_storage,
+        };
+        let sides = [&proof.old, &proof.new];
+        let (nodes, long_nodes) = (sides[short], sides[1 - short]);
+        let node = witness(
+            &nodes[index],
+            node_kind(&nodes[index], &path[depth..]),
+            depth,
+        );
+        let node_path = path_nibbles(&node.bytes);
+        let fork_kind = node_kind(&long_nodes[index], &path[depth..]);
+        let common = match fork_kind {
+            NodeKind::Extension => path_nibbles(&long_nodes[index]).len(),
+            _ => 0,
+        };
+        let branch = index + usize::from(common > 0);
+        let branch_depth = depth + common;
+        if nodes.len() != index + 1
+            || long_nodes.len() != branch + 2
+            || node_path.len() <= common
+            || !path[depth..].starts_with(&node_path[..common])
+            || branch_depth + 1 >= PATH_LEN
+            || node_kind(&long_nodes[branch], &path[branch_depth..]) != NodeKind::Branch
+            || node_kind(&long_nodes[branch + 1], &path[branch_depth + 1..]) != NodeKind::Leaf
+        {
+            return Err(unsupported("node is not split by a branch holding the key"));
+        }
+        let drifted = match &proof.drifted {
+            Some(bytes) => Some(witness(bytes, node.kind, branch_depth + 1)),
+            None if node.kind == NodeKind::OtherExtension && node_path.len() == common + 1 => None,
+            None => return Err(unsupported("split node without drifted node")),
+        };
+        let end = match node.kind {
+            NodeKind::OtherExtension => depth + node_path.len(),
+            _ => depth,
+        };
+
+        let level = |depth: usize, nodes: [Option<NodeWitness>; 2], prefix: Vec<u8>| {
+            let mut prefixes = [(); 2].map(|_| path[..depth].to_vec());
+            prefixes[short] = prefix;
+            LevelWitness {
+                depth,
+                nodes,
+                prefixes,
+                short: Some(short),
+                is_fork: false,
+                end,
+                is_last: false,
+            }
+        };
+        let by_side = |short_node: Option<NodeWitness>, long_node: NodeWitness| {
+            let mut nodes = [None, None];
+            nodes[short] = short_node;
+            nodes[1 - short] = Some(long_node);
+            nodes
+        };
+        let mut levels = vec![LevelWitness {
+            is_fork: true,
+            ..level(
+                depth,
+                by_side(
+                    Some(node.clone()),
+                    witness(&long_nodes[index], fork_kind, depth),
+                ),
+                path[..depth].to_vec(),
+            )
+        }];
+        if common > 0 {
+            levels.push(level(
+                branch_depth,
+                by_side(
+                    Some(node),
+                    witness(&long_nodes[branch], NodeKind::Branch, branch_depth),
+                ),
+                path[..depth].to_vec(),
+            ));
+        }
+        levels.push(LevelWitness {
+            is_last: true,
+            ..level(
+                branch_depth + 1,
+                by_side(
+                    drifted,
+                    witness(&long_nodes[branch + 1], NodeKind::Leaf, branch_depth + 1),
+                ),
+                [&path[..branch_depth], &[node_path[common]]].concat(),
+            )
+        });
+        Ok(levels)
+    }
+}
+
+/// MPT Circuit for proving the updates of the MptTable
+#[derive(Clone, Default, Debug)]
+pub struct MptCircuit<F: Field> {
+    updates: MptUpdates,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> MptCircuit<F> {
+    /// Return a new MptCircuit
+    pub fn new(updates: MptUpdates) -> Self {
+        Self {
+            updates,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Return the number of rows used by the updates: the rows of the key
+    /// blocks and of the levels of their parts.
+    fn num_rows(updates: &MptUpdates) -> usize {
+        let part_rows = |part: &PartWitness| {
+            KEY_ROWS + part.levels.iter().map(LevelWitness::height).sum::<usize>()
+        };
+        // The zero row and the last row are added to the rows of the parts.
+        2 + updates
+            .witnesses()
+            .iter()
+            .filter_map(|witness| UpdateWitness::new(witness).ok())
+            .flat_map(|witness| witness.parts)
+            .map(|part| part_rows(&part))
+            .sum::<usize>()
+    }
+}
+
+impl<F: Field> SubCircuit<F> for MptCircuit<F> {
+    type Config = MptCircuitConfig<F>;
+
+    fn new_from_block(block: &witness::Block<F>) -> Self {
+        Self::new(block.mpt_updates.clone())
+    }
+
+    /// Return the minimum number of rows required to prove the block
+    fn min_num_rows_block(block: &witness::Block<F>) -> usize {
+        Self::num_rows(&block.mpt_updates)
+    }
+
+    /// Make the assignments to the MptCircuit
+    fn synthesize_sub(
+        &self,
+        config: &Self::Config,
+        challenges: &Challenges<Value<F>>,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<(), Error> {
+        config.load_aux_tables(layouter)?;
+        config.assign(layouter, &self.updates, challenges)
+    }
+}
+
+#[cfg(any(feature = "test", test))]
+impl<F: Field> Circuit<F> for MptCircuit<F> {
+    type Config = (MptCircuitConfig<F>, Challenges);
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let mpt_table = MptTable::construct(meta);
+        let keccak_table = KeccakTable::construct(meta);
+        let challenges = Challenges::construct(meta);
+
+        let config = {
+            let challenges = challenges.exprs(meta);
+            MptCircuitConfig::new(
+                meta,
+                MptCircuitConfigArgs {
+                    mpt_table,
+                    keccak_table,
+                    challenges,
+                },
+            )
+        };
+
+        (config, challenges)
+    }
+
+    fn synthesize(
+        &self,
+        (config, challenges): Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let challenges = challenges.values(&mut layouter);
+        let keccak_inputs = self.updates.keccak_inputs();
+        config
+            .keccak_table
+            .dev_load(&mut layouter, &keccak_inputs, &challenges)?;
+        self.synthesize_sub(&config, &challenges, &mut layouter)
+    }
+}
@@ -0,0 +1,1630 @@
+//! The columns holding the trie node of one side of an MPT update level, one
+//! byte per row, and the constraints parsing its RLP encoding.
+
+use crate::{
+    evm_circuit::util::{and, constraint_builder::BaseConstraintBuilder, not, select, sum},
+    table::{DynamicTableColumns, KeccakTable},
+    util::{Challenges, Expr},
+};
+use eth_types::{Field, ToLittleEndian, Word};
+use halo2_proofs::{
+    circuit::{Region, Value},
+    plonk::{
+        Advice, Column, ConstraintSystem, Error, Expression, Fixed, SecondPhase, VirtualCells,
+    },
+    poly::Rotation,
+};
+use keccak256::plain::Keccak;
+
+/// Number of items of a branch node, with the value item.
+const BRANCH_ITEMS: u64 = 17;
+
+/// The columns of the MPT circuit shared by both sides that are used to parse
+/// a node.
+#[derive(Clone, Debug)]
+pub(crate) struct NodeContext<F> {
+    pub(crate) q_enable: Column<Fixed>,
+    pub(crate) is_level: Column<Advice>,
+    pub(crate) is_first: Column<Advice>,
+    pub(crate) is_last: Column<Advice>,
+    pub(crate) index: Column<Advice>,
+    pub(crate) nibble: Column<Advice>,
+    pub(crate) is_storage: Column<Advice>,
+    pub(crate) byte_table: [Column<Fixed>; 4],
+    pub(crate) keccak_table: KeccakTable,
+    pub(crate) challenges: Challenges<Expression<F>>,
+}
+
+/// The kind of a trie node in a level
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum NodeKind {
+    /// Branch node whose child at the nibble of the key is followed
+    Branch,
+    /// Extension node whose path is a prefix of the key
+    Extension,
+    /// Leaf node of the key
+    Leaf,
+    /// Leaf node of another key, proving that the key is not in the trie
+    OtherLeaf,
+    /// Extension node whose path leaves the key, proving that the key is not
+    /// in the trie
+    OtherExtension,
+}
+
+impl NodeKind {
+    fn is_extension(self) -> bool {
+        matches!(self, Self::Extension | Self::OtherExtension)
+    }
+}
+
+/// A trie node of a level, with what is needed to know how its bytes are
+/// parsed.
+#[derive(Clone, Debug)]
+pub(crate) struct NodeWitness {
+    pub(crate) kind: NodeKind,
+    pub(crate) bytes: Vec<u8>,
+    /// Nibble of the key followed in a branch
+    pub(crate) nibble: u8,
+    /// Whether the value of a leaf is a storage value instead of an account
+    pub(crate) is_storage: bool,
+}
+
+/// The assignments of a byte row of a node which don't depend on the
+/// challenges.
+#[derive(Clone, Debug, Default)]
+struct NodeRow {
+    byte: u8,
+    active: bool,
+    is_end: bool,
+    is_start: bool,
+    rem: u64,
+    is_hdr: bool,
+    item: u64,
+    is_pre: bool,
+    is_path: bool,
+    is_post: bool,
+    is_flag: bool,
+    count: u64,
+    other_item: u64,
+    path_count: u64,
+    field: [bool; 4],
+    sub_start: bool,
+    sub_rem: u64,
+    inv: u64,
+    inv2: Option<u64>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct NodeConfig {
+    // Constant in a level
+    pub(crate) is_branch: Column<Advice>,
+    pub(crate) is_extension: Column<Advice>,
+    pub(crate) is_leaf: Column<Advice>,
+    pub(crate) is_other_leaf: Column<Advice>,
+    pub(crate) is_other_extension: Column<Advice>,
+    len: Column<Advice>,
+    is_long1: Column<Advice>,
+    is_long2: Column<Advice>,
+    path_odd: Column<Advice>,
+    pub(crate) child_is_empty: Column<Advice>,
+    pub(crate) path_len: Column<Advice>,
+    pub(crate) hash: Column<Advice>,
+    pub(crate) child: Column<Advice>,
+    // The path of the key before the node, and the path to the end of the node
+    pub(crate) nib_acc: Column<Advice>,
+    pub(crate) path_rlc: Column<Advice>,
+    pub(crate) values: [Column<Advice>; 4],
+    pub(crate) key_inv: Column<Advice>,
+    // Number of children of a branch, and the last of its children which is
+    // not on the path of the key
+    pub(crate) children: Column<Advice>,
+    pub(crate) other_child: Column<Advice>,
+    pub(crate) other_nibble: Column<Advice>,
+    // One byte of the node per row
+    pub(crate) byte: Column<Advice>,
+    pub(crate) hi: Column<Advice>,
+    pub(crate) lo: Column<Advice>,
+    ge80: Column<Advice>,
+    active: Column<Advice>,
+    is_end: Column<Advice>,
+    is_start: Column<Advice>,
+    rem: Column<Advice>,
+    is_hdr: Column<Advice>,
+    item: Column<Advice>,
+    is_pre: Column<Advice>,
+    is_path: Column<Advice>,
+    is_post: Column<Advice>,
+    is_flag: Column<Advice>,
+    count: Column<Advice>,
+    other_item: Column<Advice>,
+    path_count: Column<Advice>,
+    field: [Column<Advice>; 4],
+    sub_start: Column<Advice>,
+    sub_rem: Column<Advice>,
+    inv: Column<Advice>,
+    inv2: Column<Advice>,
+    rlc: Column<Advice>,
+    pub(crate) pre_rlc: Column<Advice>,
+    pub(crate) post_rlc: Column<Advice>,
+    acc: Column<Advice>,
+    other_acc: Column<Advice>,
+    path_acc: Column<Advice>,
+}
+
+pub(crate) fn at<F: Field>(
+    meta: &mut VirtualCells<'_, F>,
+    column: Column<Advice>,
+    rotation: i32,
+) -> Expression<F> {
+    meta.query_advice(column, Rotation(rotation))
+}
+
+impl NodeConfig {
+    pub(crate) fn has_node<F: Field>(
+        &self,
+        meta: &mut VirtualCells<'_, F>,
+        rotation: i32,
+    ) -> Expression<F> {
+        sum::expr(
+            [self.is_branch, self.is_extension, self.is_leaf]
+                .map(|column| at(meta, column, rotation)),
+        )
+    }
+
+    /// Whether the leaf of the key is in the trie
+    pub(crate) fn exists<F: Field>(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F> {
+        at(meta, self.is_leaf, 0) - at(meta, self.is_other_leaf, 0)
+    }
+
+    pub(crate) fn configure<F: Field>(
+        meta: &mut ConstraintSystem<F>,
+        ctx: &NodeContext<F>,
+    ) -> Self {
+        let config = Self {
+            is_branch: meta.advice_column(),
+            is_extension: meta.advice_column(),
+            is_leaf: meta.advice_column(),
+            is_other_leaf: meta.advice_column(),
+            is_other_extension: meta.advice_column(),
+            len: meta.advice_column(),
+            is_long1: meta.advice_column(),
+            is_long2: meta.advice_column(),
+            path_odd: meta.advice_column(),
+            child_is_empty: meta.advice_column(),
+            path_len: meta.advice_column(),
+            hash: meta.advice_column_in(SecondPhase),
+            child: meta.advice_column_in(SecondPhase),
+            nib_acc: meta.advice_column_in(SecondPhase),
+            path_rlc: meta.advice_column_in(SecondPhase),
+            values: [(); 4].map(|_| meta.advice_column_in(SecondPhase)),
+            key_inv: meta.advice_column_in(SecondPhase),
+            children: meta.advice_column(),
+            other_child: meta.advice_column_in(SecondPhase),
+            other_nibble: meta.advice_column(),
+            byte: meta.advice_column(),
+            hi: meta.advice_column(),
+            lo: meta.advice_column(),
+            ge80: meta.advice_column(),
+            active: meta.advice_column(),
+            is_end: meta.advice_column(),
+            is_start: meta.advice_column(),
+            rem: meta.advice_column(),
+            is_hdr: meta.advice_column(),
+            item: meta.advice_column(),
+            is_pre: meta.advice_column(),
+            is_path: meta.advice_column(),
+            is_post: meta.advice_column(),
+            is_flag: meta.advice_column(),
+            count: meta.advice_column(),
+            other_item: meta.advice_column(),
+            path_count: meta.advice_column(),
+            field: [(); 4].map(|_| meta.advice_column()),
+            sub_start: meta.advice_column(),
+            sub_rem: meta.advice_column(),
+            inv: meta.advice_column(),
+            inv2: meta.advice_column(),
+            rlc: meta.advice_column_in(SecondPhase),
+            pre_rlc: meta.advice_column_in(SecondPhase),
+            post_rlc: meta.advice_column_in(SecondPhase),
+            acc: meta.advice_column_in(SecondPhase),
+            other_acc: meta.advice_column_in(SecondPhase),
+            path_acc: meta.advice_column_in(SecondPhase),
+        };
+        config.configure_constraints(meta, ctx);
+        config
+    }
+
+    fn configure_constraints<F: Field>(
+        &self,
+        meta: &mut ConstraintSystem<F>,
+        ctx: &NodeContext<F>,
+    ) {
+        let c = *self;
+        let r_k = ctx.challenges.keccak_input();
+        let r_w = ctx.challenges.evm_word();
+        let q_level = |meta: &mut VirtualCells<'_, F>| {
+            meta.query_fixed(ctx.q_enable, Rotation::cur()) * at(meta, ctx.is_level, 0)
+        };
+
+        meta.create_gate("mpt node constants are the same in a level", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            for column in [
+                c.is_branch,
+                c.is_extension,
+                c.is_leaf,
+                c.is_other_leaf,
+                c.is_other_extension,
+                c.len,
+                c.is_long1,
+                c.is_long2,
+                c.path_odd,
+                c.child_is_empty,
+                c.path_len,
+                c.hash,
+                c.child,
+                c.nib_acc,
+                c.path_rlc,
+                c.key_inv,
+                c.children,
+                c.other_child,
+                c.other_nibble,
+            ]
+            .into_iter()
+            .chain(c.values)
+            {
+                cb.require_equal("node constant", at(meta, column, 1), at(meta, column, 0));
+            }
+            cb.gate(q_level(meta) * not::expr(at(meta, ctx.is_last, 0)))
+        });
+
+        meta.create_gate("mpt node byte", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let has_node = c.has_node(meta, 0);
+            let active = at(meta, c.active, 0);
+            let is_field = sum::expr(c.field.map(|column| at(meta, column, 0)));
+
+            for column in [
+                c.is_branch,
+                c.is_extension,
+                c.is_leaf,
+                c.is_other_leaf,
+                c.is_other_extension,
+                c.is_long1,
+                c.is_long2,
+                c.path_odd,
+                c.child_is_empty,
+                c.active,
+                c.is_end,
+                c.is_start,
+                c.is_hdr,
+                c.is_pre,
+                c.is_path,
+                c.is_post,
+                c.is_flag,
+                c.sub_start,
+            ]
+            .into_iter()
+            .chain(c.field)
+            {
+                cb.require_boolean("node flag is boolean", at(meta, column, 0));
+            }
+            cb.require_boolean("at most one node kind", has_node);
+            cb.require_boolean("at most one field", is_field.clone());
+            cb.require_zero(
+                "other leaf is a leaf",
+                at(meta, c.is_other_leaf, 0) * not::expr(at(meta, c.is_leaf, 0)),
+            );
+            cb.require_zero(
+                "other extension is an extension",
+                at(meta, c.is_other_extension, 0) * not::expr(at(meta, c.is_extension, 0)),
+            );
+            cb.require_zero(
+                "at most one long header form",
+                at(meta, c.is_long1, 0) * at(meta, c.is_long2, 0),
+            );
+            cb.require_zero(
+                "fields are in the value of a leaf",
+                is_field.clone() * not::expr(at(meta, c.is_path, 0) * at(meta, c.is_leaf, 0)),
+            );
+            cb.require_zero(
+                "field units start on field rows",
+                at(meta, c.sub_start, 0) * not::expr(is_field),
+            );
+            cb.require_zero(
+                "storage leaves only have the value field",
+                at(meta, ctx.is_storage, 0) * sum::expr([0, 2, 3].map(|i| at(meta, c.field[i], 0))),
+            );
+
+            // The node ends on the last active row, where the next row is
+            // inactive or in the next level.
+            let is_last = at(meta, ctx.is_last, 0);
+            cb.require_equal(
+                "is_end is the last active row",
+                at(meta, c.is_end, 0),
+                active.clone()
+                    * (is_last.clone() + not::expr(is_last) * not::expr(at(meta, c.active, 1))),
+            );
+
+            cb.condition(not::expr(active.clone()), |cb| {
+                for column in [
+                    c.byte, c.is_hdr, c.is_start, c.is_pre, c.is_path, c.is_post, c.is_flag,
+                ] {
+                    cb.require_zero("inactive rows are empty", at(meta, column, 0));
+                }
+            });
+            cb.require_equal(
+                "active rows are in a unit",
+                active.clone(),
+                sum::expr(
+                    [c.is_hdr, c.is_pre, c.is_path, c.is_post].map(|column| at(meta, column, 0)),
+                ),
+            );
+
+            cb.gate(q_level(meta))
+        });
+
+        meta.create_gate("mpt node header", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let has_node = c.has_node(meta, 0);
+            let is_branch = at(meta, c.is_branch, 0);
+            let byte = at(meta, c.byte, 0);
+            let len = at(meta, c.len, 0);
+            let rem = at(meta, c.rem, 0);
+            let is_long1 = at(meta, c.is_long1, 0);
+            let is_long2 = at(meta, c.is_long2, 0);
+            let is_short = 1.expr() - is_long1.clone() - is_long2.clone();
+
+            cb.require_equal(
+                "first row is active with a node",
+                at(meta, c.active, 0),
+                has_node.clone(),
+            );
+            cb.require_zero(
+                "no node has no bytes",
+                not::expr(has_node.clone()) * len.clone(),
+            );
+            cb.require_equal(
+                "rlc starts with the first byte",
+                at(meta, c.rlc, 0),
+                byte.clone(),
+            );
+            for column in [
+                c.item,
+                c.count,
+                c.other_item,
+                c.acc,
+                c.other_acc,
+                c.pre_rlc,
+                c.post_rlc,
+                c.path_acc,
+                c.path_count,
+                c.is_flag,
+            ] {
+                cb.require_zero("accumulators start at 0", at(meta, column, 0));
+            }
+            cb.require_equal(
+                "header starts the node",
+                at(meta, c.is_hdr, 0),
+                has_node.clone(),
+            );
+            cb.require_equal(
+                "header starts a unit",
+                at(meta, c.is_start, 0),
+                has_node.clone(),
+            );
+
+            cb.condition(has_node, |cb| {
+                cb.require_zero(
+                    "branch headers are long",
+                    is_branch.clone() * is_short.clone(),
+                );
+                cb.require_zero(
+                    "extension and leaf headers are short or long1",
+                    not::expr(is_branch) * is_long2.clone(),
+                );
+            });
+            cb.require_zero(
+                "short header",
+                is_short.clone() * (byte.clone() - (0xbf.expr() + len.clone())),
+            );
+            cb.require_zero("short header has 1 byte", is_short * rem.clone());
+            cb.require_zero(
+                "long1 header",
+                is_long1.clone() * (byte.clone() - 0xf8.expr()),
+            );
+            cb.require_zero(
+                "long1 header has 2 bytes",
+                is_long1.clone() * (rem.clone() - 1.expr()),
+            );
+            cb.require_zero(
+                "long1 header length",
+                is_long1 * (at(meta, c.byte, 1) + 2.expr() - len.clone()),
+            );
+            cb.require_zero("long2 header", is_long2.clone() * (byte - 0xf9.expr()));
+            cb.require_zero(
+                "long2 header has 3 bytes",
+                is_long2.clone() * (rem - 2.expr()),
+            );
+            cb.require_zero(
+                "long2 header length",
+                is_long2.clone()
+                    * (at(meta, c.byte, 1) * 256.expr() + at(meta, c.byte, 2) + 3.expr() - len),
+            );
+            cb.require_zero(
+                "long2 header length is canonical",
+                is_long2 * (at(meta, c.byte, 1) - 1.expr()) * (at(meta, c.byte, 1) - 2.expr()),
+            );
+
+            cb.gate(q_level(meta) * at(meta, ctx.is_first, 0))
+        });
+
+        meta.create_gate("mpt node units", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let active = at(meta, c.active, 0);
+            let byte = at(meta, c.byte, 0);
+            let is_start = at(meta, c.is_start, 0);
+            let is_branch = at(meta, c.is_branch, 0);
+            let is_pre = at(meta, c.is_pre, 0);
+            let is_path = at(meta, c.is_path, 0);
+            let is_post = at(meta, c.is_post, 0);
+
+            cb.require_zero(
+                "active rows are contiguous",
+                active.clone() * not::expr(at(meta, c.active, -1)),
+            );
+            cb.require_equal(
+                "rlc of the node bytes",
+                at(meta, c.rlc, 0),
+                select::expr(
+                    active.clone(),
+                    at(meta, c.rlc, -1) * r_k.clone() + byte.clone(),
+                    at(meta, c.rlc, -1),
+                ),
+            );
+            cb.require_equal(
+                "rlc of the items before the path item",
+                at(meta, c.pre_rlc, 0),
+                select::expr(
+                    is_pre.clone(),
+                    at(meta, c.pre_rlc, -1) * r_k.clone() + byte.clone(),
+                    at(meta, c.pre_rlc, -1),
+                ),
+            );
+            cb.require_equal(
+                "rlc of the items after the path item",
+                at(meta, c.post_rlc, 0),
+                select::expr(
+                    is_post.clone(),
+                    at(meta, c.post_rlc, -1) * r_k.clone() + byte.clone(),
+                    at(meta, c.post_rlc, -1),
+                ),
+            );
+
+            cb.condition(active, |cb| {
+                cb.require_zero(
+                    "previous unit is complete when a unit starts",
+                    is_start.clone() * at(meta, c.rem, -1),
+                );
+                cb.require_zero(
+                    "rem decreases in a unit",
+                    not::expr(is_start.clone())
+                        * (at(meta, c.rem, 0) + 1.expr() - at(meta, c.rem, -1)),
+                );
+                cb.require_equal(
+                    "item index increases when a unit starts",
+                    at(meta, c.item, 0),
+                    at(meta, c.item, -1) + is_start.clone(),
+                );
+                cb.require_equal(
+                    "header is the first unit",
+                    at(meta, c.is_hdr, 0),
+                    at(meta, c.is_hdr, -1) * not::expr(is_start.clone()),
+                );
+                cb.require_equal(
+                    "count of branch children",
+                    at(meta, c.count, 0) * 32.expr(),
+                    at(meta, c.count, -1) * 32.expr()
+                        + is_branch.clone() * is_start.clone() * (byte.clone() - 0x80.expr()),
+                );
+                cb.require_equal(
+                    "items after the path item",
+                    is_start.clone() * is_post.clone(),
+                    is_start.clone() * (at(meta, c.is_path, -1) + at(meta, c.is_post, -1)),
+                );
+                for column in [c.is_pre, c.is_path, c.is_post] {
+                    cb.require_zero(
+                        "item kind is the same in an item",
+                        not::expr(is_start.clone()) * (at(meta, column, 0) - at(meta, column, -1)),
+                    );
+                }
+                cb.require_zero(
+                    "path item index",
+                    is_path.clone()
+                        * is_start.clone()
+                        * (at(meta, c.item, 0)
+                            - select::expr(
+                                is_branch.clone(),
+                                at(meta, ctx.nibble, 0) + 1.expr(),
+                                2.expr(),
+                            )),
+                );
+            });
+
+            cb.gate(q_level(meta) * not::expr(at(meta, ctx.is_first, 0)))
+        });
+
+        meta.create_gate("mpt branch and extension items", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let byte = at(meta, c.byte, 0);
+            let is_start = at(meta, c.is_start, 0);
+            let is_path = at(meta, c.is_path, 0);
+            let is_branch = at(meta, c.is_branch, 0);
+            let is_extension = at(meta, c.is_extension, 0);
+            let acc = at(meta, c.acc, 0);
+            let acc_prev = at(meta, c.acc, -1);
+
+            cb.condition(is_branch * is_start.clone(), |cb| {
+                cb.require_zero(
+                    "branch item is empty or a hash",
+                    (byte.clone() - 0x80.expr()) * (byte.clone() - 0xa0.expr()),
+                );
+                cb.require_equal(
+                    "branch item length",
+                    at(meta, c.rem, 0),
+                    byte.clone() - 0x80.expr(),
+                );
+                cb.require_equal(
+                    "branch child is empty",
+                    at(meta, c.child_is_empty, 0) * 32.expr(),
+                    is_path.clone() * (0xa0.expr() - byte.clone()),
+                );
+            });
+            cb.condition(
+                is_extension.clone() * is_path.clone() * is_start.clone(),
+                |cb| {
+                    cb.require_equal("extension child is a hash", byte.clone(), 0xa0.expr());
+                    cb.require_equal("extension child length", at(meta, c.rem, 0), 32.expr());
+                },
+            );
+            cb.require_zero(
+                "extension child is not empty",
+                is_extension * at(meta, c.child_is_empty, 0),
+            );
+
+            // The hash of the child in the path item
+            let is_inner = at(meta, c.is_branch, 0) + at(meta, c.is_extension, 0);
+            cb.condition(is_inner, |cb| {
+                cb.require_zero(
+                    "child starts at 0",
+                    is_path.clone() * is_start.clone() * acc.clone(),
+                );
+                cb.require_zero(
+                    "child hash bytes",
+                    is_path.clone()
+                        * not::expr(is_start.clone())
+                        * (acc.clone() - acc_prev.clone() * r_w.clone() - byte.clone()),
+                );
+                cb.require_zero(
+                    "child is carried after the path item",
+                    not::expr(is_path.clone()) * (acc.clone() - acc_prev.clone()),
+                );
+            });
+
+            // The last child of a branch in another item than the path item,
+            // which is the only one when the branch has 2 children.
+            let is_other = at(meta, c.is_pre, 0) + at(meta, c.is_post, 0);
+            let other_acc = at(meta, c.other_acc, 0);
+            let other_acc_prev = at(meta, c.other_acc, -1);
+            let other_item = at(meta, c.other_item, 0);
+            let other_item_prev = at(meta, c.other_item, -1);
+            cb.condition(at(meta, c.is_branch, 0), |cb| {
+                let is_hash = |byte: Expression<F>| byte - 0x80.expr();
+                let is_empty = |byte: Expression<F>| 0xa0.expr() - byte;
+                cb.require_zero(
+                    "other child starts at 0",
+                    is_other.clone()
+                        * is_start.clone()
+                        * (other_acc.clone() * 32.expr()
+                            - is_empty(byte.clone()) * other_acc_prev.clone()),
+                );
+                cb.require_zero(
+                    "other child item",
+                    is_other.clone()
+                        * is_start.clone()
+                        * (other_item.clone() * 32.expr()
+                            - is_empty(byte.clone()) * other_item_prev.clone()
+                            - is_hash(byte.clone()) * at(meta, c.item, 0)),
+                );
+                cb.require_zero(
+                    "other child hash bytes",
+                    is_other.clone()
+                        * not::expr(is_start.clone())
+                        * (other_acc.clone() - other_acc_prev.clone() * r_w.clone() - byte.clone()),
+                );
+                cb.require_zero(
+                    "other child item is carried in the item",
+                    is_other.clone()
+                        * not::expr(is_start.clone())
+                        * (other_item.clone() - other_item_prev.clone()),
+                );
+                cb.require_zero(
+                    "other child is carried out of the other items",
+                    not::expr(is_other.clone()) * (other_acc - other_acc_prev),
+                );
+                cb.require_zero(
+                    "other child item is carried out of the other items",
+                    not::expr(is_other) * (other_item - other_item_prev),
+                );
+            });
+
+            cb.gate(q_level(meta) * not::expr(at(meta, ctx.is_first, 0)))
+        });
+
+        meta.create_gate("mpt extension and leaf path", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let not_branch = not::expr(at(meta, c.is_branch, 0));
+            let is_pre = at(meta, c.is_pre, 0);
+            let is_start = at(meta, c.is_start, 0);
+            let is_flag = at(meta, c.is_flag, 0);
+            let ge80 = at(meta, c.ge80, 0);
+            let rem = at(meta, c.rem, 0);
+            let path_odd = at(meta, c.path_odd, 0);
+            let hi = at(meta, c.hi, 0);
+            let lo = at(meta, c.lo, 0);
+            let nib_acc = at(meta, c.nib_acc, 0);
+            let path_acc = at(meta, c.path_acc, 0);
+            let path_acc_prev = at(meta, c.path_acc, -1);
+            let path_count = at(meta, c.path_count, 0);
+            let path_count_prev = at(meta, c.path_count, -1);
+
+            // The flag byte is the single byte of the path item, or the first
+            // byte of its string.
+            cb.require_equal(
+                "path flag row",
+                is_flag.clone(),
+                not_branch.clone()
+                    * is_pre.clone()
+                    * (is_start.clone() * not::expr(ge80.clone())
+                        + not::expr(is_start.clone())
+                            * at(meta, c.is_start, -1)
+                            * at(meta, c.ge80, -1)),
+            );
+            cb.condition(
+                not_branch.clone() * is_pre.clone() * is_start.clone(),
+                |cb| {
+                    cb.require_zero(
+                        "path string length",
+                        ge80.clone() * (rem.clone() + 0x80.expr() - at(meta, c.byte, 0)),
+                    );
+                    cb.require_zero(
+                        "path string starts with the flag",
+                        ge80.clone() * not::expr(at(meta, c.is_flag, 1)),
+                    );
+                    cb.require_zero("single byte path", not::expr(ge80) * rem.clone());
+                },
+            );
+            cb.condition(is_flag.clone(), |cb| {
+                cb.require_zero(
+                    "path string has more than 1 byte",
+                    not::expr(is_start.clone()) * (rem * at(meta, c.inv, 0) - 1.expr()),
+                );
+                cb.require_equal(
+                    "path flag",
+                    hi.clone(),
+                    at(meta, c.is_leaf, 0) * 2.expr() + path_odd.clone(),
+                );
+                cb.require_zero("even path flag", not::expr(path_odd.clone()) * lo.clone());
+                cb.require_equal(
+                    "path starts with the key prefix",
+                    path_acc.clone(),
+                    nib_acc.clone()
+                        + path_odd.clone() * (nib_acc * (r_k.clone() - 1.expr()) + lo.clone()),
+                );
+                cb.require_equal(
+                    "path count of the flag",
+                    path_count.clone(),
+                    path_odd.clone(),
+                );
+                cb.require_zero(
+                    "extension path is not empty",
+                    at(meta, c.is_extension, 0) * is_start.clone() * not::expr(path_odd),
+                );
+            });
+            let is_data = not_branch * is_pre * not::expr(is_start) * not::expr(is_flag.clone());
+            cb.condition(is_data.clone(), |cb| {
+                cb.require_equal(
+                    "path nibbles",
+                    path_acc.clone(),
+                    path_acc_prev.clone() * r_k.clone() * r_k.clone() + hi * r_k.clone() + lo,
+                );
+                cb.require_equal(
+                    "path count of the nibbles",
+                    path_count.clone(),
+                    path_count_prev.clone() + 2.expr(),
+                );
+            });
+            cb.condition(1.expr() - is_flag - is_data, |cb| {
+                cb.require_equal("path is carried", path_acc, path_acc_prev);
+                cb.require_equal("path count is carried", path_count, path_count_prev);
+            });
+
+            cb.gate(q_level(meta) * not::expr(at(meta, ctx.is_first, 0)))
+        });
+
+        meta.create_gate("mpt leaf value item", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let is_storage = at(meta, ctx.is_storage, 0);
+            let byte = at(meta, c.byte, 0);
+            let ge80 = at(meta, c.ge80, 0);
+            let rem = at(meta, c.rem, 0);
+            let is_field = |meta: &mut VirtualCells<'_, F>, rotation| {
+                sum::expr(c.field.map(|column| at(meta, column, rotation)))
+            };
+
+            // Storage value: the string of the RLP encoding of the value.
+            cb.condition(is_storage.clone() * ge80.clone(), |cb| {
+                cb.require_equal(
+                    "storage value string length",
+                    rem.clone(),
+                    byte.clone() - 0x80.expr(),
+                );
+                cb.require_zero("storage value string header", is_field(meta, 0));
+                cb.require_equal("storage value field", at(meta, c.field[1], 1), 1.expr());
+                cb.require_equal(
+                    "storage value field starts",
+                    at(meta, c.sub_start, 1),
+                    1.expr(),
+                );
+                cb.require_equal(
+                    "storage value encoding length",
+                    at(meta, c.byte, 1),
+                    byte.clone() - 1.expr(),
+                );
+            });
+            cb.condition(is_storage.clone() * not::expr(ge80), |cb| {
+                cb.require_zero("single byte storage value", rem.clone());
+                cb.require_equal("storage value field", at(meta, c.field[1], 0), 1.expr());
+                cb.require_equal(
+                    "storage value field starts",
+                    at(meta, c.sub_start, 0),
+                    1.expr(),
+                );
+            });
+
+            // Account: the string of the RLP encoding of the list of its fields,
+            // which is always more than 55 bytes long.
+            cb.condition(not::expr(is_storage), |cb| {
+                cb.require_equal("account string header", byte, 0xb8.expr());
+                cb.require_equal("account string length", rem, at(meta, c.byte, 1) + 1.expr());
+                cb.require_equal("account list header", at(meta, c.byte, 2), 0xf8.expr());
+                cb.require_equal(
+                    "account list length",
+                    at(meta, c.byte, 3) + 2.expr(),
+                    at(meta, c.byte, 1),
+                );
+                for rotation in 0..4 {
+                    cb.require_zero("account headers", is_field(meta, rotation));
+                }
+                cb.require_equal("nonce field", at(meta, c.field[0], 4), 1.expr());
+                cb.require_equal("nonce field starts", at(meta, c.sub_start, 4), 1.expr());
+            });
+
+            cb.gate(and::expr([
+                q_level(meta),
+                not::expr(at(meta, ctx.is_first, 0)),
+                at(meta, c.is_leaf, 0),
+                at(meta, c.is_path, 0),
+                at(meta, c.is_start, 0),
+            ]))
+        });
+
+        meta.create_gate("mpt leaf value fields", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let byte = at(meta, c.byte, 0);
+            let ge80 = at(meta, c.ge80, 0);
+            let sub_start = at(meta, c.sub_start, 0);
+            let sub_rem = at(meta, c.sub_rem, 0);
+            let sub_rem_prev = at(meta, c.sub_rem, -1);
+            let field = c.field.map(|column| at(meta, column, 0));
+            let field_prev = c.field.map(|column| at(meta, column, -1));
+            let is_field = sum::expr(field.clone());
+            let is_field_prev = sum::expr(field_prev.clone());
+            let is_word = field[0].clone() + field[1].clone();
+            let acc = at(meta, c.acc, 0);
+
+            // Transitions from a field row in the value item
+            cb.condition(
+                at(meta, c.is_path, 0) * not::expr(at(meta, c.is_start, 0)) * is_field_prev,
+                |cb| {
+                    cb.require_equal("fields are contiguous", is_field.clone(), 1.expr());
+                    cb.require_zero(
+                        "previous field is complete when a field starts",
+                        sub_start.clone() * sub_rem_prev.clone(),
+                    );
+                    cb.require_zero(
+                        "sub_rem decreases in a field",
+                        not::expr(sub_start.clone())
+                            * (sub_rem.clone() + 1.expr() - sub_rem_prev.clone()),
+                    );
+                    for i in 0..4 {
+                        let shifted = if i == 0 {
+                            0.expr()
+                        } else {
+                            field_prev[i - 1].clone()
+                        };
+                        cb.require_equal(
+                            "fields are in order",
+                            field[i].clone(),
+                            select::expr(sub_start.clone(), shifted, field_prev[i].clone()),
+                        );
+                    }
+                },
+            );
+
+            // Field headers
+            cb.condition(sub_start.clone() * not::expr(ge80.clone()), |cb| {
+                cb.require_zero("single byte field", sub_rem.clone());
+                cb.require_equal("single byte field value", acc.clone(), byte.clone());
+                cb.require_equal(
+                    "single byte field is not 0",
+                    byte.clone() * at(meta, c.inv, 0),
+                    1.expr(),
+                );
+            });
+            cb.condition(sub_start.clone() * ge80.clone(), |cb| {
+                cb.require_equal(
+                    "field string length",
+                    sub_rem.clone(),
+                    byte.clone() - 0x80.expr(),
+                );
+                cb.require_zero("field string value starts at 0", acc.clone());
+                cb.require_zero(
+                    "hash fields are 32 bytes",
+                    (field[2].clone() + field[3].clone()) * (byte.clone() - 0xa0.expr()),
+                );
+                cb.require_zero(
+                    "storage value is not empty",
+                    at(meta, ctx.is_storage, 0)
+                        * ((byte.clone() - 0x80.expr()) * at(meta, c.inv, 0) - 1.expr()),
+                );
+                cb.require_zero(
+                    "single byte below 0x80 is not a string",
+                    is_word.clone()
+                        * not::expr(at(meta, c.ge80, 1))
+                        * ((sub_rem.clone() - 1.expr()) * at(meta, c.inv2, 0) - 1.expr()),
+                );
+            });
+
+            // Field bytes
+            cb.condition(is_field * not::expr(sub_start.clone()), |cb| {
+                cb.require_equal(
+                    "field value",
+                    acc.clone(),
+                    at(meta, c.acc, -1)
+                        * (field[0].clone() * 256.expr()
+                            + not::expr(field[0].clone()) * r_w.clone())
+                        + byte.clone(),
+                );
+            });
+            cb.require_zero(
+                "field value has no leading zero",
+                at(meta, c.sub_start, -1)
+                    * not::expr(sub_start)
+                    * is_word
+                    * not::expr(ge80)
+                    * (byte * at(meta, c.inv, 0) - 1.expr()),
+            );
+
+            cb.gate(q_level(meta) * not::expr(at(meta, ctx.is_first, 0)))
+        });
+
+        meta.create_gate("mpt leaf field values", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let field = c.field.map(|column| at(meta, column, 0));
+            let is_field = sum::expr(field.clone());
+            let is_field_next = sum::expr(c.field.map(|column| at(meta, column, 1)));
+            let is_field_end = is_field
+                * (not::expr(is_field_next.clone()) + is_field_next * at(meta, c.sub_start, 1));
+            let acc = at(meta, c.acc, 0);
+            cb.require_zero(
+                "field value on its last row",
+                is_field_end
+                    * sum::expr(
+                        field.iter().zip(c.values).map(|(field, value)| {
+                            field.clone() * (at(meta, value, 0) - acc.clone())
+                        }),
+                    ),
+            );
+            cb.gate(q_level(meta))
+        });
+
+        meta.create_gate("mpt node end", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let is_branch = at(meta, c.is_branch, 0);
+            let is_extension = at(meta, c.is_extension, 0);
+            let is_leaf = at(meta, c.is_leaf, 0);
+            let is_storage = at(meta, ctx.is_storage, 0);
+
+            cb.require_equal(
+                "node length",
+                at(meta, ctx.index, 0) + 1.expr(),
+                at(meta, c.len, 0),
+            );
+            cb.require_zero("last unit is complete", at(meta, c.rem, 0));
+            cb.require_zero("node has items", at(meta, c.is_hdr, 0));
+            cb.require_zero("node has the path item", at(meta, c.is_pre, 0));
+            cb.require_equal(
+                "number of items",
+                at(meta, c.item, 0),
+                select::expr(is_branch.clone(), BRANCH_ITEMS.expr(), 2.expr()),
+            );
+            cb.require_zero(
+                "branch value is empty",
+                is_branch.clone() * (at(meta, c.byte, 0) - 0x80.expr()),
+            );
+            cb.require_zero(
+                "child hash",
+                (is_branch.clone() + is_extension) * (at(meta, c.child, 0) - at(meta, c.acc, 0)),
+            );
+            cb.condition(is_branch.clone(), |cb| {
+                cb.require_equal(
+                    "number of children",
+                    at(meta, c.children, 0),
+                    at(meta, c.count, 0),
+                );
+                cb.require_equal(
+                    "other child",
+                    at(meta, c.other_child, 0),
+                    at(meta, c.other_acc, 0),
+                );
+                cb.require_equal(
+                    "nibble of the other child",
+                    at(meta, c.other_nibble, 0) + 1.expr(),
+                    at(meta, c.other_item, 0),
+                );
+            });
+            cb.condition(not::expr(is_branch), |cb| {
+                cb.require_equal("path", at(meta, c.path_rlc, 0), at(meta, c.path_acc, 0));
+                cb.require_equal(
+                    "path length",
+                    at(meta, c.path_len, 0),
+                    at(meta, c.path_count, 0),
+                );
+            });
+            cb.condition(is_leaf, |cb| {
+                cb.require_zero("last field is complete", at(meta, c.sub_rem, 0));
+                cb.require_equal(
+                    "leaf value has all its fields",
+                    select::expr(is_storage, at(meta, c.field[1], 0), at(meta, c.field[3], 0)),
+                    1.expr(),
+                );
+            });
+
+            cb.gate(q_level(meta) * at(meta, c.is_end, 0))
+        });
+
+        meta.lookup_any("mpt node byte", |meta| {
+            let q_enable = meta.query_fixed(ctx.q_enable, Rotation::cur());
+            [c.byte, c.hi, c.lo, c.ge80]
+                .into_iter()
+                .zip(ctx.byte_table)
+                .map(|(column, table)| {
+                    (
+                        q_enable.clone() * at(meta, column, 0),
+                        meta.query_fixed(table, Rotation::cur()),
+                    )
+                })
+                .collect()
+        });
+
+        meta.lookup_any("mpt node header length", |meta| {
+            let enable = q_level(meta) * at(meta, ctx.is_first, 0);
+            let len = at(meta, c.len, 0);
+            let is_long1 = at(meta, c.is_long1, 0);
+            let is_short = 1.expr() - is_long1.clone() - at(meta, c.is_long2, 0);
+            // A short header is used for lists up to 55 bytes, long1 above.
+            vec![(
+                enable * (is_short * (56.expr() - len.clone()) + is_long1 * (len - 58.expr())),
+                meta.query_fixed(ctx.byte_table[0], Rotation::cur()),
+            )]
+        });
+
+        meta.lookup_any("mpt branch has at least 2 children", |meta| {
+            let enable = q_level(meta) * at(meta, c.is_end, 0) * at(meta, c.is_branch, 0);
+            vec![(
+                enable * (at(meta, c.count, 0) - 2.expr()),
+                meta.query_fixed(ctx.byte_table[0], Rotation::cur()),
+            )]
+        });
+
+        meta.lookup_any("mpt nonce has at most 8 bytes", |meta| {
+            let enable = q_level(meta) * at(meta, c.field[0], 0) * at(meta, c.sub_start, 0);
+            vec![(
+                enable * (8.expr() - at(meta, c.sub_rem, 0)),
+                meta.query_fixed(ctx.byte_table[0], Rotation::cur()),
+            )]
+        });
+
+        meta.lookup_any("mpt node hash", |meta| {
+            let enable = q_level(meta) * at(meta, c.is_end, 0);
+            let table = ctx.keccak_table.columns();
+            let mut constraints = vec![(enable.clone(), at(meta, table[0], 0))];
+            for (column, table) in [c.rlc, c.len, c.hash]
+                .into_iter()
+                .zip(table.into_iter().skip(1))
+            {
+                constraints.push((enable.clone() * at(meta, column, 0), at(meta, table, 0)));
+            }
+            constraints
+        });
+    }
+
+    /// Assigns the node of a level, or no node, to the rows from `offset` to
+    /// `offset + height`. The path of another leaf or extension is compared to
+    /// `key_rlc`, the path of the key to the end of the node.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn assign<F: Field>(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        height: usize,
+        node: Option<&NodeWitness>,
+        nib_acc: Value<F>,
+        key_rlc: Value<F>,
+        challenges: &Challenges<Value<F>>,
+    ) -> Result<(), Error> {
+        let rows = node.map(parse).unwrap_or_default();
+        let kind = node.map(|node| node.kind);
+        let len = rows.len();
+        let bytes = node.map(|node| node.bytes.clone()).unwrap_or_default();
+
+        let flag = |value: bool| Value::known(if value { F::one() } else { F::zero() });
+        let constants = [
+            (self.is_branch, kind == Some(NodeKind::Branch)),
+            (
+                self.is_extension,
+                kind.map_or(false, NodeKind::is_extension),
+            ),
+            (
+                self.is_leaf,
+                matches!(kind, Some(NodeKind::Leaf | NodeKind::OtherLeaf)),
+            ),
+            (self.is_other_leaf, kind == Some(NodeKind::OtherLeaf)),
+            (
+                self.is_other_extension,
+                kind == Some(NodeKind::OtherExtension),
+            ),
+            (self.is_long1, header_len(len) == 2),
+            (self.is_long2, header_len(len) == 3),
+            (
+                self.child_is_empty,
+                node.map_or(false, |node| {
+                    node.kind == NodeKind::Branch && child_is_empty(&node.bytes, node.nibble)
+                }),
+            ),
+        ];
+        let path = node
+            .and_then(|node| (node.kind != NodeKind::Branch).then(|| path_nibbles(&node.bytes)));
+        let path_odd = path.as_ref().map_or(false, |path| path.len() % 2 == 1);
+        let path_len = path.as_ref().map_or(0, |path| path.len() as u64);
+
+        // Accumulators in the second phase
+        let acc_rows = challenges
+            .keccak_input()
+            .zip(challenges.evm_word())
+            .zip(nib_acc)
+            .map(|((r_k, r_w), nib_acc)| accumulate(node, &rows, r_k, r_w, nib_acc));
+        let hash = challenges.evm_word().map(|r_w| {
+            if node.is_some() {
+                let mut keccak = Keccak::default();
+                keccak.update(&bytes);
+                rlc_word(&keccak.digest(), r_w)
+            } else {
+                F::zero()
+            }
+        });
+        let child = acc_rows
+            .as_ref()
+            .map(|acc| acc.last().map_or(F::zero(), |row| row.acc));
+        let other_child = acc_rows
+            .as_ref()
+            .map(|acc| acc.last().map_or(F::zero(), |row| row.other_acc));
+        let last_row = rows.last().cloned().unwrap_or_default();
+        let other_nibble = last_row.other_item.saturating_sub(1);
+        let path_rlc = acc_rows
+            .as_ref()
+            .map(|acc| acc.last().map_or(F::zero(), |row| row.path_acc));
+        let values = acc_rows.as_ref().map(|acc| {
+            let mut values = [F::zero(); 4];
+            for (i, row) in rows.iter().enumerate() {
+                let is_field_end = rows.get(i + 1).map_or(true, |next| {
+                    next.field.iter().all(|field| !field) || next.sub_start
+                });
+                if let Some(k) = row.field.iter().position(|field| *field) {
+                    if is_field_end {
+                        values[k] = acc[i].acc;
+                    }
+                }
+            }
+            values
+        });
+        let key_inv = path_rlc.zip(key_rlc).map(|(path_rlc, key_rlc)| {
+            if matches!(kind, Some(NodeKind::OtherLeaf | NodeKind::OtherExtension)) {
+                (path_rlc - key_rlc).invert().unwrap_or(F::zero())
+            } else {
+                F::zero()
+            }
+        });
+
+        for row in 0..height {
+            let offset = offset + row;
+            for (column, value) in constants {
+                region.assign_advice(|| "mpt node constant", column, offset, || flag(value))?;
+            }
+            region.assign_advice(
+                || "mpt node path odd",
+                self.path_odd,
+                offset,
+                || flag(path_odd),
+            )?;
+            for (column, value) in [
+                (self.len, len as u64),
+                (self.path_len, path_len),
+                (self.children, last_row.count),
+                (self.other_nibble, other_nibble),
+            ] {
+                region.assign_advice(
+                    || "mpt node constant",
+                    column,
+                    offset,
+                    || Value::known(F::from(value)),
+                )?;
+            }
+            for (column, value) in [
+                (self.hash, hash),
+                (self.child, child),
+                (self.nib_acc, nib_acc),
+                (self.path_rlc, path_rlc),
+                (self.key_inv, key_inv),
+                (self.other_child, other_child),
+            ]
+            .into_iter()
+            .chain(
+                self.values
+                    .into_iter()
+                    .enumerate()
+                    .map(|(k, column)| (column, values.map(|values| values[k]))),
+            ) {
+                region.assign_advice(|| "mpt node constant", column, offset, || value)?;
+            }
+
+            // Rows after the node carry the accumulators of its last row.
+            let (node_row, acc_index) = match rows.get(row) {
+                Some(node_row) => (node_row.clone(), row),
+                None => {
+                    let last = &last_row;
+                    (
+                        NodeRow {
+                            rem: last.rem,
+                            item: last.item,
+                            count: last.count,
+                            other_item: last.other_item,
+                            path_count: last.path_count,
+                            sub_rem: last.sub_rem,
+                            ..Default::default()
+                        },
+                        len.saturating_sub(1),
+                    )
+                }
+            };
+            self.assign_row(region, offset, &node_row)?;
+            let acc_row = acc_rows
+                .as_ref()
+                .map(|acc| acc.get(acc_index).cloned().unwrap_or_default());
+            for (column, value) in [
+                (self.rlc, acc_row.as_ref().map(|row| row.rlc)),
+                (self.pre_rlc, acc_row.as_ref().map(|row| row.pre_rlc)),
+                (self.post_rlc, acc_row.as_ref().map(|row| row.post_rlc)),
+                (self.acc, acc_row.as_ref().map(|row| row.acc)),
+                (self.other_acc, acc_row.as_ref().map(|row| row.other_acc)),
+                (self.path_acc, acc_row.as_ref().map(|row| row.path_acc)),
+            ] {
+                region.assign_advice(|| "mpt node accumulator", column, offset, || value)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Assigns a byte to the byte columns, which are also used by the key
+    /// rows of the MPT circuit.
+    pub(crate) fn assign_byte<F: Field>(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        byte: u8,
+    ) -> Result<(), Error> {
+        for (column, value) in [
+            (self.byte, byte),
+            (self.hi, byte >> 4),
+            (self.lo, byte & 0xf),
+            (self.ge80, (byte >= 0x80) as u8),
+        ] {
+            region.assign_advice(
+                || "mpt byte",
+                column,
+                offset,
+                || Value::known(F::from(value as u64)),
+            )?;
+        }
+        Ok(())
+    }
+
+    fn assign_row<F: Field>(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        row: &NodeRow,
+    ) -> Result<(), Error> {
+        self.assign_byte(region, offset, row.byte)?;
+        for (column, value) in [
+            (self.active, row.active as u64),
+            (self.is_end, row.is_end as u64),
+            (self.is_start, row.is_start as u64),
+            (self.rem, row.rem),
+            (self.is_hdr, row.is_hdr as u64),
+            (self.item, row.item),
+            (self.is_pre, row.is_pre as u64),
+            (self.is_path, row.is_path as u64),
+            (self.is_post, row.is_post as u64),
+            (self.is_flag, row.is_flag as u64),
+            (self.count, row.count),
+            (self.other_item, row.other_item),
+            (self.path_count, row.path_count),
+            (self.sub_start, row.sub_start as u64),
+            (self.sub_rem, row.sub_rem),
+        ]
+        .into_iter()
+        .chain(
+            self.field
+                .into_iter()
+                .zip(row.field)
+                .map(|(column, field)| (column, field as u64)),
+        ) {
+            region.assign_advice(
+                || "mpt node row",
+                column,
+                offset,
+                || Value::known(F::from(value)),
+            )?;
+        }
+        let inverse = |value: F| value.invert().unwrap_or(F::zero());
+        region.assign_advice(
+            || "mpt node inv",
+            self.inv,
+            offset,
+            || Value::known(inverse(F::from(row.inv))),
+        )?;
+        region.assign_advice(
+            || "mpt node inv2",
+            self.inv2,
+            offset,
+            || {
+                Value::known(
+                    row.inv2
+                        .map_or(F::zero(), |value| inverse(F::from(value) - F::one())),
+                )
+            },
+        )
+    }
+
+    /// Assigns the empty rows of the node columns, where nothing is parsed.
+    pub(crate) fn assign_empty<F: Field>(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+    ) -> Result<(), Error> {
+        self.assign(
+            region,
+            offset,
+            1,
+            None,
+            Value::known(F::zero()),
+            Value::known(F::zero()),
+            &Challenges::mock(Value::known(F::zero()), Value::known(F::zero())),
+        )
+    }
+}
+
+/// The accumulators of a byte row of a node, which depend on the challenges.
+#[derive(Clone, Debug, Default)]
+struct AccRow<F> {
+    rlc: F,
+    pre_rlc: F,
+    post_rlc: F,
+    acc: F,
+    other_acc: F,
+    path_acc: F,
+}
+
+/// Returns the RLC of a word given by its big-endian bytes.
+pub(crate) fn rlc_word<F: Field>(be_bytes: &[u8], r_w: F) -> F {
+    crate::evm_circuit::util::RandomLinearCombination::<F, 32>::random_linear_combine(
+        Word::from_big_endian(be_bytes).to_le_bytes(),
+        r_w,
+    )
+}
+
+/// Computes the accumulators of the rows the same way they are constrained.
+fn accumulate<F: Field>(
+    node: Option<&NodeWitness>,
+    rows: &[NodeRow],
+    r_k: F,
+    r_w: F,
+    nib_acc: F,
+) -> Vec<AccRow<F>> {
+    let node = match node {
+        Some(node) => node,
+        None => return vec![],
+    };
+    let is_branch = node.kind == NodeKind::Branch;
+    let is_inner = is_branch || node.kind.is_extension();
+    let path_odd = node.kind != NodeKind::Branch && path_nibbles(&node.bytes).len() % 2 == 1;
+    let mut acc_rows: Vec<AccRow<F>> = vec![];
+    for (i, row) in rows.iter().enumerate() {
+        let byte = F::from(row.byte as u64);
+        let acc_row = match acc_rows.last() {
+            None => AccRow {
+                rlc: byte,
+                ..Default::default()
+            },
+            Some(prev) => {
+                let (hi, lo) = (
+                    F::from((row.byte >> 4) as u64),
+                    F::from((row.byte & 0xf) as u64),
+                );
+                let acc = if is_inner {
+                    match (row.is_path, row.is_start) {
+                        (true, true) => F::zero(),
+                        (true, false) => prev.acc * r_w + byte,
+                        _ => prev.acc,
+                    }
+                } else if row.sub_start {
+                    if row.byte < 0x80 {
+                        byte
+                    } else {
+                        F::zero()
+                    }
+                } else if row.field.iter().any(|field| *field) {
+                    let base = if row.field[0] { F::from(256) } else { r_w };
+                    prev.acc * base + byte
+                } else {
+                    prev.acc
+                };
+                let is_other = is_branch && (row.is_pre || row.is_post);
+                let other_acc = match (is_other, row.is_start) {
+                    (true, true) if row.byte == 0xa0 => F::zero(),
+                    (true, false) => prev.other_acc * r_w + byte,
+                    _ => prev.other_acc,
+                };
+                let is_data =
+                    node.kind != NodeKind::Branch && row.is_pre && !row.is_start && !row.is_flag;
+                let path_acc = if row.is_flag {
+                    if path_odd {
+                        nib_acc * r_k + lo
+                    } else {
+                        nib_acc
+                    }
+                } else if is_data {
+                    prev.path_acc * r_k * r_k + hi * r_k + lo
+                } else {
+                    prev.path_acc
+                };
+                AccRow {
+                    rlc: prev.rlc * r_k + byte,
+                    pre_rlc: if row.is_pre {
+                        prev.pre_rlc * r_k + byte
+                    } else {
+                        prev.pre_rlc
+                    },
+                    post_rlc: if row.is_post {
+                        prev.post_rlc * r_k + byte
+                    } else {
+                        prev.post_rlc
+                    },
+                    acc,
+                    other_acc,
+                    path_acc,
+                }
+            }
+        };
+        debug_assert!(i == acc_rows.len());
+        acc_rows.push(acc_row);
+    }
+    acc_rows
+}
+
+/// Returns the length of the RLP item starting at `bytes[0]`.
+fn item_len(bytes: &[u8]) -> usize {
+    match bytes[0] {
+        0..=0x7f => 1,
+        0x80..=0xb7 => 1 + (bytes[0] - 0x80) as usize,
+        0xb8 => 2 + bytes[1] as usize,
+        _ => unreachable!("trie node items are short strings"),
+    }
+}
+
+/// Returns the length of the list header of a node of `len` bytes.
+fn header_len(len: usize) -> usize {
+    match len {
+        0..=56 => 1,
+        57..=257 => 2,
+        _ => 3,
+    }
+}
+
+/// Returns the bytes of the items of a node.
+fn items(bytes: &[u8]) -> Vec<&[u8]> {
+    let mut items = vec![];
+    let mut rest = &bytes[header_len(bytes.len())..];
+    while !rest.is_empty() {
+        let len = item_len(rest);
+        items.push(&rest[..len]);
+        rest = &rest[len..];
+    }
+    items
+}
+
+/// Returns the kind of a node visited to find the key whose remaining
+/// nibbles are `path`.
+pub(crate) fn node_kind(bytes: &[u8], path: &[u8]) -> NodeKind {
+    let items = items(bytes);
+    if items.len() as u64 == BRANCH_ITEMS {
+        return NodeKind::Branch;
+    }
+    let flag = if items[0].len() == 1 {
+        items[0][0]
+    } else {
+        items[0][1]
+    };
+    if flag & 0x20 == 0 {
+        if path.starts_with(&path_nibbles(bytes)) {
+            NodeKind::Extension
+        } else {
+            NodeKind::OtherExtension
+        }
+    } else if path_nibbles(bytes) == path {
+        NodeKind::Leaf
+    } else {
+        NodeKind::OtherLeaf
+    }
+}
+
+fn child_is_empty(bytes: &[u8], nibble: u8) -> bool {
+    items(bytes)[nibble as usize] == [0x80]
+}
+
+/// Returns the nibbles of the path of an extension or leaf node.
+pub(crate) fn path_nibbles(bytes: &[u8]) -> Vec<u8> {
+    let item = items(bytes)[0];
+    let path = if item.len() == 1 { item } else { &item[1..] };
+    let mut nibbles = vec![];
+    if path[0] & 0x10 != 0 {
+        nibbles.push(path[0] & 0xf);
+    }
+    for byte in &path[1..] {
+        nibbles.extend([byte >> 4, byte & 0xf]);
+    }
+    nibbles
+}
+
+/// Computes the rows of a node the same way they are constrained.
+fn parse(node: &NodeWitness) -> Vec<NodeRow> {
+    let bytes = &node.bytes;
+    let is_branch = node.kind == NodeKind::Branch;
+    let path_item = if is_branch { node.nibble as u64 + 1 } else { 2 };
+    let mut rows: Vec<NodeRow> = bytes
+        .iter()
+        .map(|byte| NodeRow {
+            byte: *byte,
+            active: true,
+            ..Default::default()
+        })
+        .collect();
+
+    // Units: the list header and the items
+    let header_len = header_len(bytes.len());
+    let mut units = vec![(0, header_len)];
+    let mut offset = header_len;
+    for item in items(bytes) {
+        units.push((offset, item.len()));
+        offset += item.len();
+    }
+    let mut count = 0;
+    let mut other_item = 0;
+    for (item, (start, len)) in units.into_iter().enumerate() {
+        let item = item as u64;
+        if is_branch && item > 0 && bytes[start] == 0xa0 {
+            count += 1;
+            if item != path_item {
+                other_item = item;
+            }
+        }
+        for i in start..start + len {
+            let row = &mut rows[i];
+            row.is_start = i == start;
+            row.rem = (start + len - 1 - i) as u64;
+            row.item = item;
+            row.is_hdr = item == 0;
+            row.is_pre = item > 0 && item < path_item;
+            row.is_path = item == path_item;
+            row.is_post = item > path_item;
+            row.count = count;
+            row.other_item = other_item;
+        }
+        if item == 1 && !is_branch {
+            // The path of an extension or a leaf
+            let is_string = len > 1;
+            let flag = start + is_string as usize;
+            if is_string {
+                rows[flag].inv = rows[flag].rem;
+            }
+            let path_odd = bytes[flag] & 0x10 != 0;
+            rows[flag].is_flag = true;
+            rows[flag].path_count = path_odd as u64;
+            for i in flag + 1..start + len {
+                rows[i].path_count = rows[i - 1].path_count + 2;
+            }
+            for i in start + len..rows.len() {
+                rows[i].path_count = rows[start + len - 1].path_count;
+            }
+        }
+        if item == 2 && !is_branch && !node.kind.is_extension() {
+            parse_value(&mut rows[start..start + len], node.is_storage);
+        }
+    }
+    if let Some(last) = rows.last_mut() {
+        last.is_end = true;
+    }
+    rows
+}
+
+/// Computes the fields of the value item of a leaf.
+fn parse_value(rows: &mut [NodeRow], is_storage: bool) {
+    let start = if is_storage {
+        if rows[0].byte < 0x80 {
+            0
+        } else {
+            1
+        }
+    } else {
+        4
+    };
+    let mut field = if is_storage { 1 } else { 0 };
+    let mut i = start;
+    while i < rows.len() {
+        let header = rows[i].byte;
+        let data_len = if header < 0x80 {
+            0
+        } else {
+            (header - 0x80) as usize
+        };
+        rows[i].sub_start = true;
+        rows[i].sub_rem = data_len as u64;
+        rows[i].field[field] = true;
+        if header < 0x80 {
+            rows[i].inv = header as u64;
+        } else if is_storage {
+            rows[i].inv = data_len as u64;
+        }
+        rows[i].inv2 = Some(data_len as u64);
+        for j in 1..=data_len {
+            rows[i + j].field[field] = true;
+            rows[i + j].sub_rem = (data_len - j) as u64;
+        }
+        if data_len > 0 && field < 2 && rows[i + 1].byte < 0x80 {
+            rows[i + 1].inv = rows[i + 1].byte as u64;
+        }
+        i += 1 + data_len;
+        field += 1;
+    }
+}
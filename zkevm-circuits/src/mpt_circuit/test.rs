@@ -0,0 +1,238 @@
+use super::MptCircuit;
+use crate::{
+    table::AccountFieldTag,
+    util::log2_ceil,
+    witness::{MptUpdates, Rw},
+};
+use eth_types::{address, Address, Word};
+use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
+
+fn account(
+    rw_counter: usize,
+    address: Address,
+    field_tag: AccountFieldTag,
+    values: (u64, u64),
+) -> Rw {
+    Rw::Account {
+        rw_counter,
+        is_write: values.0 != values.1,
+        account_address: address,
+        field_tag,
+        value_prev: Word::from(values.0),
+        value: Word::from(values.1),
+    }
+}
+
+fn storage(rw_counter: usize, address: Address, storage_key: u64, values: (u64, u64)) -> Rw {
+    Rw::AccountStorage {
+        rw_counter,
+        is_write: values.0 != values.1,
+        account_address: address,
+        storage_key: Word::from(storage_key),
+        value_prev: Word::from(values.0),
+        value: Word::from(values.1),
+        tx_id: 1,
+        committed_value: Word::from(values.0),
+    }
+}
+
+fn verify(rows: &[Rw], success: bool) {
+    let updates = MptUpdates::new(rows, Word::zero(), &[]).unwrap();
+    let k = log2_ceil(64 + MptCircuit::<Fr>::num_rows(&updates)).max(9);
+    let circuit = MptCircuit::<Fr>::new(updates);
+    let prover = MockProver::<Fr>::run(k, &circuit, vec![]).unwrap();
+    let result = prover.verify_par();
+    if success {
+        if let Err(errors) = &result {
+            for error in errors.iter().take(10) {
+                log::error!("{}", error);
+            }
+        }
+        assert_eq!(result, Ok(()));
+    } else {
+        assert!(result.is_err());
+    }
+}
+
+#[test]
+fn mpt_circuit_empty() {
+    verify(&[], true);
+}
+
+#[test]
+fn mpt_circuit_account_creation() {
+    let rows = [account(
+        1,
+        address!("0x00000000000000000000000000000000000000aa"),
+        AccountFieldTag::Balance,
+        (0, 100),
+    )];
+    verify(&rows, true);
+}
+
+#[test]
+fn mpt_circuit_account_creation_splits_leaf() {
+    // The paths of 0xaa and 0xbb diverge at the first nibble.
+    let existing = address!("0x00000000000000000000000000000000000000aa");
+    let created = address!("0x00000000000000000000000000000000000000bb");
+    let rows = [
+        account(1, existing, AccountFieldTag::Balance, (1000, 1000)),
+        account(2, created, AccountFieldTag::Balance, (0, 100)),
+    ];
+    verify(&rows, true);
+}
+
+#[test]
+fn mpt_circuit_account_creation_splits_extension() {
+    // The paths of 0x05 and 0x0f share the nibbles 4 and 2, which the path of
+    // 0x08 leaves after the 4 and the path of 0xaa at the root.
+    let existing = [
+        address!("0x0000000000000000000000000000000000000005"),
+        address!("0x000000000000000000000000000000000000000f"),
+    ];
+    for created in [
+        address!("0x0000000000000000000000000000000000000008"),
+        address!("0x00000000000000000000000000000000000000aa"),
+    ] {
+        let rows = [
+            account(1, existing[0], AccountFieldTag::Balance, (1, 1)),
+            account(2, existing[1], AccountFieldTag::Balance, (1, 1)),
+            account(3, created, AccountFieldTag::Nonce, (0, 1)),
+        ];
+        verify(&rows, true);
+    }
+}
+
+#[test]
+fn mpt_circuit_account_deletion() {
+    let emptied = address!("0x00000000000000000000000000000000000000aa");
+    let other = address!("0x00000000000000000000000000000000000000bb");
+    let rows = [
+        account(1, other, AccountFieldTag::Balance, (1000, 1000)),
+        account(2, emptied, AccountFieldTag::Balance, (100, 0)),
+    ];
+    verify(&rows, true);
+
+    // Deleting 0x08 collapses the branch under the extension of the nibble 4
+    // into an extension of the nibbles 4 and 2.
+    let rows = [
+        account(
+            1,
+            address!("0x0000000000000000000000000000000000000005"),
+            AccountFieldTag::Balance,
+            (1, 1),
+        ),
+        account(
+            2,
+            address!("0x0000000000000000000000000000000000000008"),
+            AccountFieldTag::Balance,
+            (7, 0),
+        ),
+        account(
+            3,
+            address!("0x000000000000000000000000000000000000000f"),
+            AccountFieldTag::Balance,
+            (1, 1),
+        ),
+    ];
+    verify(&rows, true);
+}
+
+#[test]
+fn mpt_circuit_account_updates() {
+    let sender = address!("0x00000000000000000000000000000000000000aa");
+    let receiver = address!("0x00000000000000000000000000000000000000bb");
+    let rows = [
+        account(1, sender, AccountFieldTag::Nonce, (0, 1)),
+        account(2, sender, AccountFieldTag::Balance, (1000, 900)),
+        account(3, receiver, AccountFieldTag::Balance, (500, 600)),
+    ];
+    verify(&rows, true);
+}
+
+#[test]
+fn mpt_circuit_account_does_not_exist() {
+    let existing = address!("0x00000000000000000000000000000000000000aa");
+    let missing = address!("0x00000000000000000000000000000000000000bb");
+    verify(
+        &[account(1, missing, AccountFieldTag::NonExisting, (0, 0))],
+        true,
+    );
+    verify(
+        &[
+            account(1, existing, AccountFieldTag::Balance, (1000, 1000)),
+            account(2, missing, AccountFieldTag::NonExisting, (0, 0)),
+        ],
+        true,
+    );
+    // The path of 0x08 leaves the extension of the nibbles 4 and 2 at the root.
+    verify(
+        &[
+            account(
+                1,
+                address!("0x0000000000000000000000000000000000000005"),
+                AccountFieldTag::Balance,
+                (1, 1),
+            ),
+            account(
+                2,
+                address!("0x0000000000000000000000000000000000000008"),
+                AccountFieldTag::NonExisting,
+                (0, 0),
+            ),
+            account(
+                3,
+                address!("0x000000000000000000000000000000000000000f"),
+                AccountFieldTag::Balance,
+                (1, 1),
+            ),
+        ],
+        true,
+    );
+}
+
+#[test]
+fn mpt_circuit_storage_updates() {
+    let contract = address!("0x00000000000000000000000000000000000000cc");
+    let rows = [
+        account(1, contract, AccountFieldTag::Balance, (1, 1)),
+        storage(2, contract, 1, (0, 0x1234)),
+        storage(3, contract, 2, (0, 0)),
+    ];
+    verify(&rows, true);
+}
+
+#[test]
+fn mpt_circuit_storage_creation_and_deletion() {
+    // The paths of the slots 1 and 2 diverge at the first nibble, and the
+    // paths of the slots 6 and 8 after a common one.
+    let contract = address!("0x00000000000000000000000000000000000000cc");
+    for (existing, updated) in [(1, 2), (6, 8)] {
+        for values in [(0, 0x1234), (0x1234, 0)] {
+            let rows = [
+                account(1, contract, AccountFieldTag::Balance, (1, 1)),
+                storage(2, contract, existing, (5, 5)),
+                storage(3, contract, updated, values),
+            ];
+            verify(&rows, true);
+        }
+    }
+}
+
+#[test]
+fn mpt_circuit_storage_of_created_account() {
+    let contract = address!("0x00000000000000000000000000000000000000cc");
+    verify(&[storage(1, contract, 1, (0, 5))], true);
+}
+
+#[test]
+fn mpt_circuit_stale_value() {
+    // The second nonce update starts from the value before the first one.
+    let sender = address!("0x00000000000000000000000000000000000000aa");
+    let rows = [
+        account(1, sender, AccountFieldTag::Nonce, (0, 1)),
+        account(2, sender, AccountFieldTag::Balance, (1000, 1000)),
+        account(3, sender, AccountFieldTag::Nonce, (0, 2)),
+    ];
+    verify(&rows, false);
+}
@@ -1,36 +1,210 @@
 //! Public Input Circuit implementation
 
+mod receipts;
+
 use std::marker::PhantomData;
 
+use eth_types::evm_types::Hardfork;
 use eth_types::geth_types::BlockConstants;
 use eth_types::sign_types::SignData;
-use eth_types::H256;
 use eth_types::{
     geth_types::Transaction, Address, BigEndianHash, Field, ToBigEndian, ToLittleEndian, ToScalar,
     Word,
 };
-use halo2_proofs::plonk::Instance;
+use eth_types::{Bytes, H256, H64};
+use ethers_core::types::Bloom;
+use ethers_core::utils::{keccak256, rlp::RlpStream};
+use halo2_proofs::plonk::{Expression, Instance, SecondPhase};
 
+use crate::bloom_circuit::LOGS_BLOOM_WORDS;
 use crate::table::BlockTable;
+use crate::table::KeccakTable;
+use crate::table::RwTable;
 use crate::table::TxFieldTag;
 use crate::table::TxTable;
 use crate::tx_circuit::TX_LEN;
 use crate::util::{random_linear_combine_word as rlc, Challenges, SubCircuit, SubCircuitConfig};
 use crate::witness;
 use gadgets::is_zero::IsZeroChip;
-use gadgets::util::{not, or, Expr};
+use gadgets::util::{not, or, select, sum, Expr};
 use halo2_proofs::{
     circuit::{AssignedCell, Layouter, Region, SimpleFloorPlanner, Value},
     plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Fixed, Selector},
     poly::Rotation,
 };
+use log::error;
+use receipts::ReceiptsConfig;
 
 /// Fixed by the spec
-const BLOCK_LEN: usize = 7 + 256;
-const EXTRA_LEN: usize = 2;
+const BLOCK_LEN: usize = 8 + LOGS_BLOOM_WORDS + 256;
+/// Maximum length of the RLP encoding of a Shanghai block header: the list
+/// prefix (3), the parent and uncles hashes (2 * 33), the coinbase (21), the
+/// state, txs and receipts roots (3 * 33), the logs bloom (259), the
+/// difficulty (33), the number, gas limit, gas used and timestamp (4 * 9), the
+/// extra data (33), the mix hash (33), the nonce (9), the base fee (33) and the
+/// withdrawals root (33). The header of a London block is 33 bytes shorter.
+const MAX_HEADER_RLP_LEN: usize = 658;
+/// Maximum length of the extra data of a block header
+const MAX_EXTRA_DATA_LEN: usize = 32;
+/// Offset in the header RLP of the parent hash bytes, after the list prefix (3)
+/// and the string prefix (1).
+const HEADER_PARENT_HASH_OFFSET: usize = 4;
+/// Offset in the header RLP of the coinbase bytes, after the list prefix (3),
+/// the parent and uncles hashes (2 * 33) and the string prefix (1).
+const HEADER_COINBASE_OFFSET: usize = 70;
+/// Offset in the header RLP of the state root bytes, after the list prefix
+/// (3), the parent and uncles hashes (2 * 33), the coinbase (21) and the
+/// string prefix (1).
+const HEADER_STATE_ROOT_OFFSET: usize = 91;
+/// Offset in the header RLP of the transactions root bytes, after the list
+/// prefix (3), the parent and uncles hashes (2 * 33), the coinbase (21), the
+/// state root (33) and the string prefix (1).
+const HEADER_TXS_ROOT_OFFSET: usize = 124;
+/// Offset in the header RLP of the receipts root bytes, after the transactions
+/// root (32) and the string prefix (1).
+const HEADER_RECEIPTS_ROOT_OFFSET: usize = 157;
+/// Offset in the header RLP of the logs bloom bytes, after the receipts root
+/// (32) and the string prefix (3).
+const HEADER_LOGS_BLOOM_OFFSET: usize = 192;
+/// Offset in the header RLP of the fields of variable length, which start
+/// after the logs bloom (256) with the prefix of the difficulty.
+const HEADER_TAIL_OFFSET: usize = 448;
+/// Maximum number of fields of variable length in the header RLP: the
+/// difficulty, the number, the gas limit, the gas used, the timestamp, the
+/// extra data, the mix hash, the nonce, the base fee and, from Shanghai, the
+/// withdrawals root.
+const HEADER_TAIL_FIELDS: usize = 10;
+/// Bytes of the header RLP which are fixed by its layout: the prefix of the
+/// list, whose length is 2 bytes long, and the prefixes of the fields of fixed
+/// length.
+const HEADER_CONST_BYTES: [(usize, u8); 10] = [
+    (0, 0xf9),
+    (HEADER_PARENT_HASH_OFFSET - 1, 0xa0),
+    (HEADER_PARENT_HASH_OFFSET + 32, 0xa0),
+    (HEADER_COINBASE_OFFSET - 1, 0x94),
+    (HEADER_STATE_ROOT_OFFSET - 1, 0xa0),
+    (HEADER_TXS_ROOT_OFFSET - 1, 0xa0),
+    (HEADER_RECEIPTS_ROOT_OFFSET - 1, 0xa0),
+    (HEADER_LOGS_BLOOM_OFFSET - 3, 0xb9),
+    (HEADER_LOGS_BLOOM_OFFSET - 2, 0x01),
+    (HEADER_LOGS_BLOOM_OFFSET - 1, 0x00),
+];
+/// Offset in the extra values of the header RLP length
+const HEADER_RLP_LEN_OFFSET: usize = 5;
+/// Offset in the extra values of the randomness of the RLC encoding
+const RANDOMNESS_OFFSET: usize = 6;
+/// Offset in the extra values of the transactions RLP length
+const TXS_RLP_LEN_OFFSET: usize = 7;
+/// Offset in the extra values of the block hash bytes
+const BLOCK_HASH_BYTES_OFFSET: usize = 8;
+/// Offset in the extra values of the transactions root bytes
+const TXS_ROOT_BYTES_OFFSET: usize = BLOCK_HASH_BYTES_OFFSET + 32;
+/// Offset in the extra values of the receipts root bytes
+const RECEIPTS_ROOT_BYTES_OFFSET: usize = TXS_ROOT_BYTES_OFFSET + 32;
+/// Offset in the extra values of the header RLP bytes
+const HEADER_RLP_BYTES_OFFSET: usize = RECEIPTS_ROOT_BYTES_OFFSET + 32;
+const EXTRA_LEN: usize = HEADER_RLP_BYTES_OFFSET + MAX_HEADER_RLP_LEN;
+/// Maximum length of the RLP encoding of a signed transaction, without its
+/// call data and with an empty access list: the type (1), the list prefix (4),
+/// the chain id (9), the nonce (9), the gas tip and fee caps (2 * 33), the gas
+/// (9), the callee (21), the value (33), the call data prefix (4), the access
+/// list (1), the signature v (9), r and s (2 * 33).
+const MAX_TX_RLP_LEN: usize = 232;
+/// Maximum length added to the RLP encoding of a signed transaction by an
+/// entry of its access list: a storage key (33), or an address (21) together
+/// with the prefixes of its item and of its storage keys (2 * 4), which leaves
+/// room for the longer prefix of a non-empty access list (3).
+const MAX_ACCESS_LIST_ENTRY_RLP_LEN: usize = 33;
 const ZERO_BYTE_GAS_COST: u64 = 4;
 const NONZERO_BYTE_GAS_COST: u64 = 16;
 
+/// Field of the RLP encoding of a signed transaction, by the way its value is
+/// bound to the tx table
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum TxRlpField {
+    /// Field which isn't in the tx table: the chain id and the signature
+    Unchecked,
+    /// Field whose RLC encoding is the value of the tag in the tx table
+    Rlc(TxFieldTag),
+    /// Callee address, which is empty for the creation transactions
+    To,
+    /// Call data, whose bytes and length are in the tx table
+    Data,
+    /// Access list, whose number of addresses and of storage keys are in the
+    /// tx table, as well as each entry
+    AccessList,
+}
+
+impl TxRlpField {
+    /// Tag of the field value in the tx table
+    fn tag(&self) -> TxFieldTag {
+        match self {
+            Self::Unchecked => TxFieldTag::Null,
+            Self::Rlc(tag) => *tag,
+            Self::To => TxFieldTag::CalleeAddress,
+            Self::Data => TxFieldTag::CallDataLength,
+            Self::AccessList => TxFieldTag::AccessListAddressesLen,
+        }
+    }
+
+    /// Kind of the field, as in the transactions RLP schema table
+    fn kind(&self) -> u64 {
+        match self {
+            Self::Unchecked => 0,
+            Self::Rlc(_) => 1,
+            Self::To => 2,
+            Self::Data => 3,
+            Self::AccessList => 4,
+        }
+    }
+}
+
+/// Fields of the RLP encoding of the signed transactions, indexed by the
+/// transaction type: legacy, EIP-2930 and EIP-1559.
+const TX_RLP_FIELDS: [&[TxRlpField]; 3] = {
+    use TxRlpField::{AccessList, Data, Rlc, To, Unchecked};
+    [
+        &[
+            Rlc(TxFieldTag::Nonce),
+            Rlc(TxFieldTag::GasPrice),
+            Rlc(TxFieldTag::Gas),
+            To,
+            Rlc(TxFieldTag::Value),
+            Data,
+            Unchecked,
+            Unchecked,
+            Unchecked,
+        ],
+        &[
+            Unchecked,
+            Rlc(TxFieldTag::Nonce),
+            Rlc(TxFieldTag::GasPrice),
+            Rlc(TxFieldTag::Gas),
+            To,
+            Rlc(TxFieldTag::Value),
+            Data,
+            AccessList,
+            Unchecked,
+            Unchecked,
+            Unchecked,
+        ],
+        &[
+            Unchecked,
+            Rlc(TxFieldTag::Nonce),
+            Rlc(TxFieldTag::GasTipCap),
+            Rlc(TxFieldTag::GasFeeCap),
+            Rlc(TxFieldTag::Gas),
+            To,
+            Rlc(TxFieldTag::Value),
+            Data,
+            AccessList,
+            Unchecked,
+            Unchecked,
+            Unchecked,
+        ],
+    ]
+};
+
 /// Values of the block table (as in the spec)
 #[derive(Clone, Default, Debug)]
 pub struct BlockValues {
@@ -41,6 +215,8 @@ pub struct BlockValues {
     difficulty: Word,
     base_fee: Word, // NOTE: BaseFee was added by EIP-1559 and is ignored in legacy headers.
     chain_id: u64,
+    block_reward: Word,
+    logs_bloom: Vec<Word>,
     history_hashes: Vec<H256>,
 }
 
@@ -50,21 +226,29 @@ pub struct TxValues {
     nonce: Word,
     gas: Word, //gas limit
     gas_price: Word,
+    gas_fee_cap: Word,
+    gas_tip_cap: Word,
     from_addr: Address,
     to_addr: Address,
     is_create: u64,
     value: Word,
     call_data_len: u64,
     call_data_gas_cost: u64,
+    access_list_addresses_len: u64,
+    access_list_storage_keys_len: u64,
     tx_sign_hash: [u8; 32],
 }
 
 /// Extra values (not contained in block or tx tables)
 #[derive(Default, Debug, Clone)]
 pub struct ExtraValues {
-    // block_hash: H256,
     state_root: H256,
     prev_state_root: H256,
+    receipts_root: H256,
+    block_hash: H256,
+    transactions_root: H256,
+    header_rlp: Vec<u8>,
+    txs_rlp: Vec<u8>,
 }
 
 /// PublicData contains all the values that the PiCircuit recieves as input
@@ -81,11 +265,135 @@ pub struct PublicData {
     pub state_root: H256,
     /// Previous block root
     pub prev_state_root: H256,
+    /// Root of the transactions of the block header, which must be the
+    /// commitment to the transactions returned by [`txs_commitment`] for the
+    /// block to be proven.
+    pub transactions_root: H256,
+    /// Root of the receipts of the block header, which must be the
+    /// commitment to the receipts returned by [`receipts_commitment`] for the
+    /// block to be proven.
+    pub receipts_root: H256,
+    /// Receipts of the transactions, from the TxReceipt and TxLog rows of the
+    /// rw table
+    pub receipts: Vec<witness::Receipt>,
     /// Constants related to Ethereum block
     pub block_constants: BlockConstants,
+    /// Reward paid to the coinbase at the end of the block
+    pub block_reward: Word,
+    /// Logs bloom of the block
+    pub logs_bloom: Bloom,
+    /// Hash of the uncles of the block
+    pub uncles_hash: H256,
+    /// Gas used by the transactions of the block
+    pub gas_used: Word,
+    /// Extra data of the block header
+    pub extra_data: Bytes,
+    /// Mix hash of the block header
+    pub mix_hash: H256,
+    /// Nonce of the block header
+    pub nonce: H64,
+    /// Root of the withdrawals trie of the block header, only present from
+    /// Shanghai
+    pub withdrawals_root: H256,
+    /// Hardfork of the block, which fixes the fields of its header
+    pub hardfork: Hardfork,
 }
 
 impl PublicData {
+    /// Returns the public data of a witness block
+    pub fn new<F: Field>(block: &witness::Block<F>) -> Self {
+        Self {
+            chain_id: block.context.chain_id,
+            history_hashes: block.context.history_hashes.clone(),
+            transactions: block.eth_block.transactions.clone(),
+            state_root: block.eth_block.state_root,
+            prev_state_root: H256::from_uint(&block.prev_state_root),
+            transactions_root: block.eth_block.transactions_root,
+            receipts_root: block.eth_block.receipts_root,
+            receipts: block.rws.receipts(),
+            block_constants: BlockConstants {
+                coinbase: block.context.coinbase,
+                timestamp: block.context.timestamp,
+                number: block.context.number.as_u64().into(),
+                difficulty: block.context.difficulty,
+                gas_limit: block.context.gas_limit.into(),
+                base_fee: block.context.base_fee,
+            },
+            block_reward: block.context.block_reward,
+            logs_bloom: block.context.logs_bloom,
+            uncles_hash: block.eth_block.uncles_hash,
+            gas_used: block.eth_block.gas_used,
+            extra_data: block.eth_block.extra_data.clone(),
+            mix_hash: block.eth_block.mix_hash.unwrap_or_default(),
+            nonce: block.eth_block.nonce.unwrap_or_default(),
+            withdrawals_root: block
+                .eth_block
+                .other
+                .get_deserialized("withdrawalsRoot")
+                .and_then(Result::ok)
+                .unwrap_or(*witness::EMPTY_TRIE_ROOT),
+            hardfork: block.circuits_params.hardfork,
+        }
+    }
+
+    /// Returns the RLP encoding of the block header, whose parent hash is the
+    /// latest of the history hashes
+    pub fn header_rlp(&self) -> Vec<u8> {
+        let parent_hash = self.history_hashes.last().copied().unwrap_or_default();
+        let is_shanghai = self.hardfork >= Hardfork::Shanghai;
+        let mut stream = RlpStream::new_list(16 + is_shanghai as usize);
+        stream
+            .append(&H256::from(parent_hash.to_be_bytes()).as_bytes())
+            .append(&self.uncles_hash.as_bytes())
+            .append(&self.block_constants.coinbase.as_bytes())
+            .append(&self.state_root.as_bytes())
+            .append(&self.transactions_root.as_bytes())
+            .append(&self.receipts_root.as_bytes())
+            .append(&self.logs_bloom.as_bytes())
+            .append(&self.block_constants.difficulty)
+            .append(&self.block_constants.number.as_u64())
+            .append(&self.block_constants.gas_limit)
+            .append(&self.gas_used)
+            .append(&self.block_constants.timestamp)
+            .append(&self.extra_data.to_vec())
+            .append(&self.mix_hash.as_bytes())
+            .append(&self.nonce.as_bytes())
+            .append(&self.block_constants.base_fee);
+        if is_shanghai {
+            stream.append(&self.withdrawals_root.as_bytes());
+        }
+        stream.out().to_vec()
+    }
+
+    /// Returns the commitment to the signed transactions of the block, see
+    /// [`txs_commitment`]
+    pub fn txs_commitment(&self) -> H256 {
+        txs_commitment(self.chain_id.as_u64(), &self.transactions)
+    }
+
+    /// Returns the concatenated RLP encodings of the signed transactions,
+    /// whose hash is the transactions commitment
+    pub fn txs_rlp(&self) -> Vec<u8> {
+        txs_rlp(self.chain_id.as_u64(), &self.transactions)
+    }
+
+    /// Returns the commitment to the receipts of the block, see
+    /// [`receipts_commitment`]
+    pub fn receipts_commitment(&self) -> H256 {
+        receipts_commitment(&self.receipts)
+    }
+
+    /// Returns the encoding of the receipts, whose hash is the receipts
+    /// commitment
+    pub fn receipts_bytes(&self) -> Vec<u8> {
+        receipts::receipts_bytes(&self.receipts)
+    }
+
+    /// Returns the block hash, which is the hash of the header RLP
+    pub fn block_hash(&self) -> H256 {
+        H256(keccak256(self.header_rlp()))
+    }
+
     /// Returns struct with values for the block table
     pub fn get_block_table_values(&self) -> BlockValues {
         let history_hashes = [
@@ -104,6 +412,13 @@ impl PublicData {
             difficulty: self.block_constants.difficulty,
             base_fee: self.block_constants.base_fee,
             chain_id: self.chain_id.as_u64(),
+            block_reward: self.block_reward,
+            logs_bloom: self
+                .logs_bloom
+                .as_bytes()
+                .chunks(32)
+                .map(Word::from_big_endian)
+                .collect(),
             history_hashes,
         }
     }
@@ -124,6 +439,8 @@ impl PublicData {
             tx_vals.push(TxValues {
                 nonce: tx.nonce,
                 gas_price: tx.gas_price,
+                gas_fee_cap: tx.gas_fee_cap,
+                gas_tip_cap: tx.gas_tip_cap,
                 gas: tx.gas_limit,
                 from_addr: tx.from,
                 to_addr: tx.to.unwrap_or_else(Address::zero),
@@ -137,6 +454,8 @@ impl PublicData {
                         NONZERO_BYTE_GAS_COST
                     }
                 }),
+                access_list_addresses_len: tx.access_list_addresses_len(),
+                access_list_storage_keys_len: tx.access_list_storage_keys_len(),
                 tx_sign_hash: msg_hash_le,
             });
         }
@@ -145,10 +464,15 @@ impl PublicData {
 
     /// Returns struct with the extra values
     pub fn get_extra_values(&self) -> ExtraValues {
+        let header_rlp = self.header_rlp();
         ExtraValues {
-            // block_hash: self.hash.unwrap_or_else(H256::zero),
             state_root: self.state_root,
             prev_state_root: self.prev_state_root,
+            receipts_root: self.receipts_root,
+            block_hash: H256(keccak256(&header_rlp)),
+            transactions_root: self.txs_commitment(),
+            header_rlp,
+            txs_rlp: self.txs_rlp(),
         }
     }
 
@@ -157,6 +481,30 @@ impl PublicData {
     }
 }
 
+/// Returns the commitment to the signed transactions `txs` of a block of
+/// `chain_id`, which must be the transactions root of its header: the hash of
+/// their concatenated RLP encodings, which is decoded in the circuit, instead
+/// of the root of the trie of Ethereum blocks.
+pub fn txs_commitment(chain_id: u64, txs: &[eth_types::Transaction]) -> H256 {
+    H256(keccak256(txs_rlp(chain_id, txs)))
+}
+
+/// Returns the concatenated RLP encodings of the signed transactions `txs` of
+/// a block of `chain_id`
+fn txs_rlp(chain_id: u64, txs: &[eth_types::Transaction]) -> Vec<u8> {
+    txs.iter()
+        .flat_map(|tx| Transaction::from(tx).rlp_signed(chain_id).to_vec())
+        .collect()
+}
+
+/// Returns the commitment to the `receipts` of a block, which must be the
+/// receipts root of its header: the hash of their encoding, which is built in
+/// the circuit from the TxReceipt and TxLog rows of the rw table, instead of
+/// the root of the trie of Ethereum blocks.
+pub fn receipts_commitment(receipts: &[witness::Receipt]) -> H256 {
+    H256(keccak256(receipts::receipts_bytes(receipts)))
+}
+
 /// Config for PiCircuit
 #[derive(Clone, Debug)]
 pub struct PiCircuitConfig<F: Field> {
@@ -183,16 +531,86 @@ pub struct PiCircuitConfig<F: Field> {
     q_not_end: Selector,
     q_end: Selector,
 
-    pi: Column<Instance>, // rpi_rand, rpi_rlc, chain_ID, state_root, prev_state_root
+    q_hash_byte: Selector,
+    q_hash_first: Selector,
+    q_header_byte: Selector,
+    q_header_first: Selector,
+    q_header_last: Selector,
+    q_txs_root_byte: Selector,
+    q_receipts_root_byte: Selector,
+    header_is_byte: Column<Advice>,
+    header_len_acc: Column<Advice>,
+    bytes_rlc_acc: Column<Advice>,
+
+    q_header_const: Selector,
+    header_const: Column<Fixed>,
+    q_randomness_first: Selector,
+    q_randomness: Selector,
+    randomness: Column<Advice>,
+    q_header_field: Selector,
+    q_header_field_rev: Selector,
+    q_header_field_first: Selector,
+    header_acc_rlc: Column<Advice>,
+    header_acc_int: Column<Advice>,
+    q_header_tail_first: Selector,
+    q_header_tail: Selector,
+    header_is_prefix: Column<Advice>,
+    header_is_short: Column<Advice>,
+    header_tail_tag: Column<Advice>,
+    header_tail_rem: Column<Advice>,
+    header_field_tag: Column<Fixed>,
+
+    q_txs_rlp_first: Selector,
+    q_txs_rlp: Selector,
+    q_txs_rlp_len: Selector,
+    txs_rlp_tx_id: Column<Advice>,
+    txs_rlp_tx_type: Column<Advice>,
+    txs_rlp_is_type: Column<Advice>,
+    txs_rlp_is_list_prefix: Column<Advice>,
+    txs_rlp_is_list_len: Column<Advice>,
+    txs_rlp_is_str_len: Column<Advice>,
+    txs_rlp_is_long: Column<Advice>,
+    txs_rlp_index: Column<Advice>,
+    txs_rlp_field_end: Column<Advice>,
+    txs_rlp_tag: Column<Advice>,
+    txs_rlp_is_rlc: Column<Advice>,
+    txs_rlp_is_to: Column<Advice>,
+    txs_rlp_is_data: Column<Advice>,
+    txs_rlp_is_access_list: Column<Advice>,
+    txs_rlp_is_last_field: Column<Advice>,
+    txs_rlp_al_is_list_prefix: Column<Advice>,
+    txs_rlp_al_is_list_len: Column<Advice>,
+    txs_rlp_al_is_elem_prefix: Column<Advice>,
+    txs_rlp_al_is_elem: Column<Advice>,
+    txs_rlp_al_is_elem_last: Column<Advice>,
+    txs_rlp_al_is_keys: Column<Advice>,
+    txs_rlp_al_rem: Column<Advice>,
+    txs_rlp_al_item_rem: Column<Advice>,
+    txs_rlp_al_keys_rem: Column<Advice>,
+    txs_rlp_al_keys_count: Column<Advice>,
+    txs_rlp_al_addresses_len: Column<Advice>,
+    txs_rlp_al_address: Column<Advice>,
+    // tx_type, field + 1, tag, kind and is_last_field of each field in
+    // `TX_RLP_FIELDS`
+    txs_rlp_fields: [Column<Fixed>; 5],
+
+    // rpi_rand, rpi_rlc, chain_ID, state_root, prev_state_root, receipts_root, block_hash,
+    // transactions_root
+    pi: Column<Instance>,
+
+    // Encoding of the receipts, hashed into the receipts root
+    receipts: ReceiptsConfig<F>,
 
     _marker: PhantomData<F>,
     // External tables
     block_table: BlockTable,
     tx_table: TxTable,
+    rw_table: RwTable,
+    keccak_table: KeccakTable,
 }
 
 /// Circuit configuration arguments
-pub struct PiCircuitConfigArgs {
+pub struct PiCircuitConfigArgs<F: Field> {
     /// Max number of supported transactions
     pub max_txs: usize,
     /// Max number of supported calldata bytes
@@ -201,10 +619,16 @@ pub struct PiCircuitConfigArgs {
     pub tx_table: TxTable,
     /// BlockTable
     pub block_table: BlockTable,
+    /// RwTable
+    pub rw_table: RwTable,
+    /// KeccakTable
+    pub keccak_table: KeccakTable,
+    /// Challenges
+    pub challenges: Challenges<Expression<F>>,
 }
 
 impl<F: Field> SubCircuitConfig<F> for PiCircuitConfig<F> {
-    type ConfigArgs = PiCircuitConfigArgs;
+    type ConfigArgs = PiCircuitConfigArgs<F>;
 
     /// Return a new PiCircuitConfig
     fn new(
@@ -214,6 +638,9 @@ impl<F: Field> SubCircuitConfig<F> for PiCircuitConfig<F> {
             max_calldata,
             block_table,
             tx_table,
+            rw_table,
+            keccak_table,
+            challenges,
         }: Self::ConfigArgs,
     ) -> Self {
         let q_block_table = meta.selector();
@@ -243,11 +670,74 @@ impl<F: Field> SubCircuitConfig<F> for PiCircuitConfig<F> {
         let q_not_end = meta.selector();
         let q_end = meta.selector();
 
+        let q_hash_byte = meta.selector();
+        let q_hash_first = meta.selector();
+        let q_header_byte = meta.selector();
+        let q_header_first = meta.selector();
+        let q_header_last = meta.complex_selector();
+        let q_txs_root_byte = meta.selector();
+        let q_receipts_root_byte = meta.selector();
+        let header_is_byte = meta.advice_column();
+        let header_len_acc = meta.advice_column();
+        let bytes_rlc_acc = meta.advice_column_in(SecondPhase);
+        let q_header_const = meta.selector();
+        let header_const = meta.fixed_column();
+        let q_randomness_first = meta.selector();
+        let q_randomness = meta.selector();
+        let randomness = meta.advice_column();
+        let q_header_field = meta.selector();
+        let q_header_field_rev = meta.selector();
+        let q_header_field_first = meta.selector();
+        let header_acc_rlc = meta.advice_column();
+        let header_acc_int = meta.advice_column();
+        let q_header_tail_first = meta.complex_selector();
+        let q_header_tail = meta.complex_selector();
+        let header_is_prefix = meta.advice_column();
+        let header_is_short = meta.advice_column();
+        let header_tail_tag = meta.advice_column();
+        let header_tail_rem = meta.advice_column();
+        let header_field_tag = meta.fixed_column();
+        let q_txs_rlp_first = meta.complex_selector();
+        let q_txs_rlp = meta.complex_selector();
+        let q_txs_rlp_len = meta.complex_selector();
+        let txs_rlp_tx_id = meta.advice_column();
+        let txs_rlp_tx_type = meta.advice_column();
+        let txs_rlp_is_type = meta.advice_column();
+        let txs_rlp_is_list_prefix = meta.advice_column();
+        let txs_rlp_is_list_len = meta.advice_column();
+        let txs_rlp_is_str_len = meta.advice_column();
+        let txs_rlp_is_long = meta.advice_column();
+        let txs_rlp_index = meta.advice_column();
+        let txs_rlp_field_end = meta.advice_column();
+        let txs_rlp_tag = meta.advice_column();
+        let txs_rlp_is_rlc = meta.advice_column();
+        let txs_rlp_is_to = meta.advice_column();
+        let txs_rlp_is_data = meta.advice_column();
+        let txs_rlp_is_access_list = meta.advice_column();
+        let txs_rlp_is_last_field = meta.advice_column();
+        let txs_rlp_al_is_list_prefix = meta.advice_column();
+        let txs_rlp_al_is_list_len = meta.advice_column();
+        let txs_rlp_al_is_elem_prefix = meta.advice_column();
+        let txs_rlp_al_is_elem = meta.advice_column();
+        let txs_rlp_al_is_elem_last = meta.advice_column();
+        let txs_rlp_al_is_keys = meta.advice_column();
+        let txs_rlp_al_rem = meta.advice_column();
+        let txs_rlp_al_item_rem = meta.advice_column();
+        let txs_rlp_al_keys_rem = meta.advice_column();
+        let txs_rlp_al_keys_count = meta.advice_column();
+        let txs_rlp_al_addresses_len = meta.advice_column();
+        let txs_rlp_al_address = meta.advice_column();
+        let txs_rlp_fields = [(); 5].map(|_| meta.fixed_column());
+
         let pi = meta.instance_column();
 
         meta.enable_equality(raw_public_inputs);
         meta.enable_equality(rpi_rlc_acc);
         meta.enable_equality(rand_rpi);
+        meta.enable_equality(header_acc_rlc);
+        meta.enable_equality(header_acc_int);
+        meta.enable_equality(header_len_acc);
+        meta.enable_equality(bytes_rlc_acc);
         meta.enable_equality(pi);
 
         // 0.0 rpi_rlc_acc[0] == RLC(raw_public_inputs, rand_rpi)
@@ -519,6 +1009,1111 @@ impl<F: Field> SubCircuitConfig<F> for PiCircuitConfig<F> {
             ]
         });
 
+        // 1.0 Block hash, transactions root and receipts root bytes -> RLC accumulated
+        // from the most significant byte, as the output in the keccak table
+        meta.create_gate("hash bytes rlc", |meta| {
+            let q_hash_byte = meta.query_selector(q_hash_byte);
+            let q_hash_first = meta.query_selector(q_hash_first);
+            let byte = meta.query_advice(raw_public_inputs, Rotation::cur());
+            let rlc_acc = meta.query_advice(bytes_rlc_acc, Rotation::cur());
+            let rlc_acc_prev = meta.query_advice(bytes_rlc_acc, Rotation::prev());
+
+            vec![
+                q_hash_byte
+                    * (rlc_acc
+                        - select::expr(
+                            q_hash_first,
+                            byte.clone(),
+                            rlc_acc_prev * challenges.evm_word() + byte,
+                        )),
+            ]
+        });
+
+        // 1.1 Header and transactions RLP bytes -> RLC accumulated from the first
+        // byte, as the input in the keccak table, followed by zero padding
+        meta.create_gate("rlp bytes rlc", |meta| {
+            let q_rlp_byte = meta.query_selector(q_header_byte)
+                + meta.query_selector(q_txs_rlp_first)
+                + meta.query_selector(q_txs_rlp);
+            let q_rlp_first =
+                meta.query_selector(q_header_first) + meta.query_selector(q_txs_rlp_first);
+            let byte = meta.query_advice(raw_public_inputs, Rotation::cur());
+            let is_byte = meta.query_advice(header_is_byte, Rotation::cur());
+            let is_byte_prev = meta.query_advice(header_is_byte, Rotation::prev());
+            let len_acc = meta.query_advice(header_len_acc, Rotation::cur());
+            let len_acc_prev = meta.query_advice(header_len_acc, Rotation::prev());
+            let rlc_acc = meta.query_advice(bytes_rlc_acc, Rotation::cur());
+            let rlc_acc_prev = meta.query_advice(bytes_rlc_acc, Rotation::prev());
+
+            let is_byte_diff = is_byte_prev - is_byte.clone();
+
+            vec![
+                // is_byte is boolean
+                q_rlp_byte.clone() * is_byte.clone() * (1.expr() - is_byte.clone()),
+                // is_byte can only transit from 1 to 0
+                q_rlp_byte.clone()
+                    * not::expr(q_rlp_first.clone())
+                    * is_byte_diff.clone()
+                    * (1.expr() - is_byte_diff),
+                // the padding is zero
+                q_rlp_byte.clone() * not::expr(is_byte.clone()) * byte.clone(),
+                q_rlp_byte.clone()
+                    * (len_acc
+                        - select::expr(
+                            q_rlp_first.clone(),
+                            is_byte.clone(),
+                            len_acc_prev + is_byte.clone(),
+                        )),
+                q_rlp_byte
+                    * (rlc_acc
+                        - select::expr(
+                            q_rlp_first,
+                            is_byte.clone() * byte.clone(),
+                            select::expr(
+                                is_byte,
+                                rlc_acc_prev.clone() * challenges.keccak_input() + byte,
+                                rlc_acc_prev,
+                            ),
+                        )),
+            ]
+        });
+
+        // Rotations from the last header RLP byte row to the rows of the header RLP
+        // length and of the last block hash byte.
+        let header_last = (HEADER_RLP_BYTES_OFFSET + MAX_HEADER_RLP_LEN - 1) as i32;
+        let header_rlp_len_rotation = Rotation(HEADER_RLP_LEN_OFFSET as i32 - header_last);
+        let block_hash_rotation = Rotation((BLOCK_HASH_BYTES_OFFSET + 31) as i32 - header_last);
+
+        // 1.2 header RLP length == number of header RLP bytes
+        meta.create_gate("header rlp length", |meta| {
+            let q_header_last = meta.query_selector(q_header_last);
+            let len_acc = meta.query_advice(header_len_acc, Rotation::cur());
+            let header_rlp_len = meta.query_advice(raw_public_inputs, header_rlp_len_rotation);
+
+            vec![q_header_last * (len_acc - header_rlp_len)]
+        });
+
+        // 1.3 block hash == keccak(header RLP)
+        meta.lookup_any("block hash in keccak table", |meta| {
+            let q_header_last = meta.query_selector(q_header_last);
+            let input = [
+                q_header_last.clone(),
+                q_header_last.clone() * meta.query_advice(bytes_rlc_acc, Rotation::cur()),
+                q_header_last.clone() * meta.query_advice(header_len_acc, Rotation::cur()),
+                q_header_last * meta.query_advice(bytes_rlc_acc, block_hash_rotation),
+            ];
+            let table = [
+                keccak_table.is_enabled,
+                keccak_table.input_rlc,
+                keccak_table.input_len,
+                keccak_table.output_rlc,
+            ]
+            .map(|column| meta.query_advice(column, Rotation::cur()));
+
+            input.into_iter().zip(table).collect()
+        });
+
+        // 1.4 transactions and receipts root bytes == their bytes in the header RLP
+        let header_txs_root_rotation = Rotation(
+            (HEADER_RLP_BYTES_OFFSET + HEADER_TXS_ROOT_OFFSET) as i32
+                - TXS_ROOT_BYTES_OFFSET as i32,
+        );
+        let header_receipts_root_rotation = Rotation(
+            (HEADER_RLP_BYTES_OFFSET + HEADER_RECEIPTS_ROOT_OFFSET) as i32
+                - RECEIPTS_ROOT_BYTES_OFFSET as i32,
+        );
+        meta.create_gate("transactions and receipts roots in header rlp", |meta| {
+            let q_txs_root_byte = meta.query_selector(q_txs_root_byte);
+            let q_receipts_root_byte = meta.query_selector(q_receipts_root_byte);
+            let byte = meta.query_advice(raw_public_inputs, Rotation::cur());
+            let header_txs_root_byte =
+                meta.query_advice(raw_public_inputs, header_txs_root_rotation);
+            let header_receipts_root_byte =
+                meta.query_advice(raw_public_inputs, header_receipts_root_rotation);
+
+            vec![
+                q_txs_root_byte * (byte.clone() - header_txs_root_byte),
+                q_receipts_root_byte * (byte - header_receipts_root_byte),
+            ]
+        });
+
+        // 1.5 Header RLP bytes fixed by its layout, and length of the list in its
+        // prefix == header RLP length - 3
+        let header_first_rlp_len_rotation =
+            Rotation(HEADER_RLP_LEN_OFFSET as i32 - HEADER_RLP_BYTES_OFFSET as i32);
+        meta.create_gate("header rlp layout", |meta| {
+            let q_header_const = meta.query_selector(q_header_const);
+            let q_header_first = meta.query_selector(q_header_first);
+            let byte = meta.query_advice(raw_public_inputs, Rotation::cur());
+            let list_len = meta.query_advice(raw_public_inputs, Rotation(1)) * 256.expr()
+                + meta.query_advice(raw_public_inputs, Rotation(2));
+            let header_rlp_len =
+                meta.query_advice(raw_public_inputs, header_first_rlp_len_rotation);
+
+            vec![
+                q_header_const * (byte - meta.query_fixed(header_const, Rotation::cur())),
+                q_header_first * (list_len + 3.expr() - header_rlp_len),
+            ]
+        });
+
+        // 1.6 The randomness of the RLC encoding used by the header fields
+        // accumulators is the one in the extra values
+        meta.create_gate("randomness", |meta| {
+            let q_randomness_first = meta.query_selector(q_randomness_first);
+            let q_randomness = meta.query_selector(q_randomness);
+            let randomness_cur = meta.query_advice(randomness, Rotation::cur());
+
+            vec![
+                q_randomness_first
+                    * (randomness_cur.clone()
+                        - meta.query_advice(raw_public_inputs, Rotation::cur())),
+                q_randomness * (randomness_cur - meta.query_advice(randomness, Rotation::prev())),
+            ]
+        });
+
+        // 1.7 Header fields of fixed length -> accumulated in the RLC encoding and in
+        // base 256, from the most significant byte, or only in the RLC encoding from
+        // the last byte for the hashes. The accumulators are copied to the cells of
+        // the field values.
+        meta.create_gate("header fields of fixed length", |meta| {
+            let q_header_field = meta.query_selector(q_header_field);
+            let q_header_field_rev = meta.query_selector(q_header_field_rev);
+            let q_header_field_first = meta.query_selector(q_header_field_first);
+            let byte = meta.query_advice(raw_public_inputs, Rotation::cur());
+            let randomness = meta.query_advice(randomness, Rotation::cur());
+            let acc_rlc = meta.query_advice(header_acc_rlc, Rotation::cur());
+            let acc_int = meta.query_advice(header_acc_int, Rotation::cur());
+
+            vec![
+                q_header_field.clone()
+                    * (acc_rlc.clone()
+                        - select::expr(
+                            q_header_field_first.clone(),
+                            byte.clone(),
+                            meta.query_advice(header_acc_rlc, Rotation::prev())
+                                * randomness.clone()
+                                + byte.clone(),
+                        )),
+                q_header_field
+                    * (acc_int
+                        - select::expr(
+                            q_header_field_first.clone(),
+                            byte.clone(),
+                            meta.query_advice(header_acc_int, Rotation::prev()) * 256.expr()
+                                + byte.clone(),
+                        )),
+                q_header_field_rev
+                    * (acc_rlc
+                        - select::expr(
+                            q_header_field_first,
+                            byte.clone(),
+                            meta.query_advice(header_acc_rlc, Rotation::next()) * randomness + byte,
+                        )),
+            ]
+        });
+
+        // 1.8 Header fields of variable length -> each one is a prefix row followed
+        // by `rem` bytes, or a single byte below 0x80, accumulated in the RLC
+        // encoding and in base 256 as the fields of fixed length.
+        meta.create_gate("header fields of variable length", |meta| {
+            let q_header_tail_first = meta.query_selector(q_header_tail_first);
+            let q_header_tail = meta.query_selector(q_header_tail);
+            let byte = meta.query_advice(raw_public_inputs, Rotation::cur());
+            let randomness = meta.query_advice(randomness, Rotation::cur());
+            let is_byte = meta.query_advice(header_is_byte, Rotation::cur());
+            let is_byte_next = meta.query_advice(header_is_byte, Rotation::next());
+            let is_prefix = meta.query_advice(header_is_prefix, Rotation::cur());
+            let is_short = meta.query_advice(header_is_short, Rotation::cur());
+            let tag = meta.query_advice(header_tail_tag, Rotation::cur());
+            let tag_prev = meta.query_advice(header_tail_tag, Rotation::prev());
+            let rem = meta.query_advice(header_tail_rem, Rotation::cur());
+            let rem_prev = meta.query_advice(header_tail_rem, Rotation::prev());
+            let acc_rlc = meta.query_advice(header_acc_rlc, Rotation::cur());
+            let acc_rlc_prev = meta.query_advice(header_acc_rlc, Rotation::prev());
+            let acc_int = meta.query_advice(header_acc_int, Rotation::cur());
+            let acc_int_prev = meta.query_advice(header_acc_int, Rotation::prev());
+
+            let q_tail = q_header_tail_first.clone() + q_header_tail.clone();
+            let is_prefix_byte = q_tail.clone() * is_byte.clone() * is_prefix.clone();
+            let is_next_prefix_byte = q_header_tail.clone() * is_byte.clone() * is_prefix.clone();
+            let is_payload_byte = q_header_tail * is_byte.clone() * not::expr(is_prefix.clone());
+            let is_last_byte = q_tail.clone() * is_byte.clone() * not::expr(is_byte_next);
+            let prefix_acc = is_short.clone() * byte.clone();
+
+            vec![
+                // the tail starts with the prefix of the first field
+                q_header_tail_first.clone() * not::expr(is_byte),
+                q_header_tail_first.clone() * not::expr(is_prefix.clone()),
+                q_header_tail_first * tag.clone(),
+                q_tail.clone() * is_prefix.clone() * not::expr(is_prefix.clone()),
+                q_tail.clone() * is_short.clone() * not::expr(is_short.clone()),
+                q_tail * is_short.clone() * not::expr(is_prefix),
+                // a prefix row holds the length of the field, or the field itself
+                is_prefix_byte.clone()
+                    * (rem.clone() - not::expr(is_short) * (byte.clone() - 0x80.expr())),
+                is_prefix_byte.clone() * (acc_rlc.clone() - prefix_acc.clone()),
+                is_prefix_byte * (acc_int.clone() - prefix_acc),
+                // a new field starts once the previous one is over
+                is_next_prefix_byte.clone() * rem_prev.clone(),
+                is_next_prefix_byte * (tag.clone() - tag_prev.clone() - 1.expr()),
+                // a payload row continues the field
+                is_payload_byte.clone() * (rem.clone() - rem_prev + 1.expr()),
+                is_payload_byte.clone() * (tag.clone() - tag_prev),
+                is_payload_byte.clone() * (acc_rlc - (acc_rlc_prev * randomness + byte.clone())),
+                is_payload_byte * (acc_int - (acc_int_prev * 256.expr() + byte)),
+                // the header RLP ends with the last field, which is the base fee
+                // before Shanghai and the withdrawals root from Shanghai
+                is_last_byte.clone() * rem,
+                is_last_byte
+                    * (tag.clone() - (HEADER_TAIL_FIELDS - 2).expr())
+                    * (tag - (HEADER_TAIL_FIELDS - 1).expr()),
+            ]
+        });
+
+        meta.lookup_any("header fields of variable length: rem in range", |meta| {
+            let q_tail =
+                meta.query_selector(q_header_tail_first) + meta.query_selector(q_header_tail);
+            let is_byte = meta.query_advice(header_is_byte, Rotation::cur());
+            let rem = meta.query_advice(header_tail_rem, Rotation::cur());
+
+            vec![(
+                q_tail * is_byte * rem,
+                meta.query_fixed(fixed_u16, Rotation::cur()),
+            )]
+        });
+
+        // The prefix of a field is either a single byte up to 0x7f, or the
+        // prefix of a string up to 32 bytes long, which is in [0x80, 0xa0] given
+        // that its length `rem` is in range.
+        meta.lookup_any(
+            "header fields of variable length: prefix in range",
+            |meta| {
+                let q_tail =
+                    meta.query_selector(q_header_tail_first) + meta.query_selector(q_header_tail);
+                let byte = meta.query_advice(raw_public_inputs, Rotation::cur());
+                let is_byte = meta.query_advice(header_is_byte, Rotation::cur());
+                let is_prefix = meta.query_advice(header_is_prefix, Rotation::cur());
+                let is_short = meta.query_advice(header_is_short, Rotation::cur());
+
+                vec![(
+                    q_tail
+                        * is_byte
+                        * is_prefix
+                        * (0xa0.expr() - byte - (0xa0 - 0x7f).expr() * is_short),
+                    meta.query_fixed(fixed_u16, Rotation::cur()),
+                )]
+            },
+        );
+
+        // The accumulators of the last byte of each field are in the row tagged by
+        // the field in `header_field_tag`, from where they are copied to the cells
+        // of the field values.
+        meta.lookup_any("header fields of variable length: values", |meta| {
+            let q_tail =
+                meta.query_selector(q_header_tail_first) + meta.query_selector(q_header_tail);
+            let is_byte = meta.query_advice(header_is_byte, Rotation::cur());
+            let is_byte_next = meta.query_advice(header_is_byte, Rotation::next());
+            let is_prefix_next = meta.query_advice(header_is_prefix, Rotation::next());
+            // is_byte can only transit from 1 to 0, so this is 0 in the padding
+            let is_field_last = q_tail * (is_byte - is_byte_next * not::expr(is_prefix_next));
+
+            let input = [
+                is_field_last.clone()
+                    * (meta.query_advice(header_tail_tag, Rotation::cur()) + 1.expr()),
+                is_field_last.clone() * meta.query_advice(header_acc_rlc, Rotation::cur()),
+                is_field_last * meta.query_advice(header_acc_int, Rotation::cur()),
+            ];
+            let table = [
+                meta.query_fixed(header_field_tag, Rotation::cur()),
+                meta.query_advice(header_acc_rlc, Rotation::cur()),
+                meta.query_advice(header_acc_int, Rotation::cur()),
+            ];
+
+            input.into_iter().zip(table).collect()
+        });
+
+        // 2.0 Transactions RLP bytes -> decoded as the signed transactions, whose
+        // fields are looked up in the tx table. Each byte is the type of a typed
+        // transaction, the prefix of its list or a byte of the list length, or else
+        // the prefix of a field, a byte of the field length or a byte of the field
+        // payload. The fields of each transaction are the ones of its type in
+        // `TX_RLP_FIELDS`, which end exactly where the next transaction starts, so
+        // the length in the list prefix isn't needed to delimit them and isn't
+        // checked. The columns of the header fields of variable length are
+        // reused: `header_is_prefix` flags the prefix of a field, `header_is_short`
+        // a field which is a single byte below 0x80, `header_tail_tag` holds the
+        // index of the field and `header_tail_rem` the number of bytes left in the
+        // field or in the length. The payload of an access list is decoded by the
+        // "transactions rlp: access lists" gate.
+        meta.create_gate("transactions rlp", |meta| {
+            let q_first = meta.query_selector(q_txs_rlp_first);
+            let q_not_first = meta.query_selector(q_txs_rlp);
+            let q_rlp = q_first.clone() + q_not_first.clone();
+            let byte = meta.query_advice(raw_public_inputs, Rotation::cur());
+            let randomness = meta.query_advice(randomness, Rotation::cur());
+
+            let is_byte = meta.query_advice(header_is_byte, Rotation::cur());
+            let is_type = meta.query_advice(txs_rlp_is_type, Rotation::cur());
+            let is_list_prefix = meta.query_advice(txs_rlp_is_list_prefix, Rotation::cur());
+            let is_list_len = meta.query_advice(txs_rlp_is_list_len, Rotation::cur());
+            let is_prefix = meta.query_advice(header_is_prefix, Rotation::cur());
+            let is_str_len = meta.query_advice(txs_rlp_is_str_len, Rotation::cur());
+            let is_short = meta.query_advice(header_is_short, Rotation::cur());
+            let is_long = meta.query_advice(txs_rlp_is_long, Rotation::cur());
+            let al_is_list_prefix = meta.query_advice(txs_rlp_al_is_list_prefix, Rotation::cur());
+            let rem = meta.query_advice(header_tail_rem, Rotation::cur());
+            let tx_id = meta.query_advice(txs_rlp_tx_id, Rotation::cur());
+            let tx_type = meta.query_advice(txs_rlp_tx_type, Rotation::cur());
+            let field = meta.query_advice(header_tail_tag, Rotation::cur());
+            let index = meta.query_advice(txs_rlp_index, Rotation::cur());
+            let acc_rlc = meta.query_advice(header_acc_rlc, Rotation::cur());
+            let acc_int = meta.query_advice(header_acc_int, Rotation::cur());
+            let field_end = meta.query_advice(txs_rlp_field_end, Rotation::cur());
+            let is_data = meta.query_advice(txs_rlp_is_data, Rotation::cur());
+            let is_access_list = meta.query_advice(txs_rlp_is_access_list, Rotation::cur());
+            let is_last_field = meta.query_advice(txs_rlp_is_last_field, Rotation::cur());
+            let field_flags = [
+                meta.query_advice(txs_rlp_is_rlc, Rotation::cur()),
+                meta.query_advice(txs_rlp_is_to, Rotation::cur()),
+                is_data.clone(),
+                is_access_list.clone(),
+            ];
+
+            let is_type_prev = meta.query_advice(txs_rlp_is_type, Rotation::prev());
+            let is_list_prefix_prev = meta.query_advice(txs_rlp_is_list_prefix, Rotation::prev());
+            let is_list_len_prev = meta.query_advice(txs_rlp_is_list_len, Rotation::prev());
+            let is_prefix_prev = meta.query_advice(header_is_prefix, Rotation::prev());
+            let is_str_len_prev = meta.query_advice(txs_rlp_is_str_len, Rotation::prev());
+            let is_long_prev = meta.query_advice(txs_rlp_is_long, Rotation::prev());
+            let rem_prev = meta.query_advice(header_tail_rem, Rotation::prev());
+            let tx_id_prev = meta.query_advice(txs_rlp_tx_id, Rotation::prev());
+            let tx_type_prev = meta.query_advice(txs_rlp_tx_type, Rotation::prev());
+            let field_prev = meta.query_advice(header_tail_tag, Rotation::prev());
+            let index_prev = meta.query_advice(txs_rlp_index, Rotation::prev());
+            let acc_rlc_prev = meta.query_advice(header_acc_rlc, Rotation::prev());
+            let acc_int_prev = meta.query_advice(header_acc_int, Rotation::prev());
+            let is_last_field_prev = meta.query_advice(txs_rlp_is_last_field, Rotation::prev());
+            let is_payload_prev = meta.query_advice(header_is_byte, Rotation::prev())
+                - is_type_prev.clone()
+                - is_list_prefix_prev.clone()
+                - is_list_len_prev.clone()
+                - is_prefix_prev.clone()
+                - is_str_len_prev.clone();
+
+            let is_byte_next = meta.query_advice(header_is_byte, Rotation::next());
+            let is_type_next = meta.query_advice(txs_rlp_is_type, Rotation::next());
+            let is_list_prefix_next = meta.query_advice(txs_rlp_is_list_prefix, Rotation::next());
+            let is_prefix_next = meta.query_advice(header_is_prefix, Rotation::next());
+
+            let is_kind = is_type.clone()
+                + is_list_prefix.clone()
+                + is_list_len.clone()
+                + is_prefix.clone()
+                + is_str_len.clone();
+            let is_payload = is_byte.clone() - is_kind.clone();
+            let is_len = is_list_len.clone() + is_str_len.clone();
+            let is_start = is_type.clone()
+                + is_list_prefix.clone()
+                + is_prefix.clone()
+                + is_payload.clone() * is_str_len_prev.clone();
+            let is_continuation =
+                is_len.clone() + is_payload.clone() * not::expr(is_str_len_prev.clone());
+            let is_new_tx =
+                is_type.clone() + is_list_prefix.clone() * not::expr(is_type_prev.clone());
+            // a field can end on its prefix, unless it's followed by a length, or on
+            // a byte of its payload
+            let is_item = is_prefix.clone() + is_payload.clone();
+            let is_item_prev =
+                is_prefix_prev.clone() * not::expr(is_long_prev.clone()) + is_payload_prev;
+            let is_last_byte = is_byte.clone() - is_byte_next.clone();
+
+            let mut constraints = vec![];
+            for flag in [
+                is_type.clone(),
+                is_list_prefix.clone(),
+                is_list_len.clone(),
+                is_prefix.clone(),
+                is_str_len.clone(),
+                is_short.clone(),
+                is_long.clone(),
+                field_end.clone(),
+                is_last_field.clone(),
+                is_kind.clone(),
+                sum::expr(field_flags.clone()),
+            ]
+            .into_iter()
+            .chain(field_flags.clone())
+            {
+                constraints.push(q_rlp.clone() * flag.clone() * not::expr(flag));
+            }
+
+            constraints.extend([
+                // the padding has no kind, and only the prefixes can be short or long
+                q_rlp.clone() * not::expr(is_byte.clone()) * is_kind,
+                q_rlp.clone() * is_short.clone() * not::expr(is_prefix.clone()),
+                q_rlp.clone()
+                    * is_long.clone()
+                    * not::expr(is_prefix.clone() + is_list_prefix.clone() + al_is_list_prefix),
+                q_rlp.clone() * is_short.clone() * is_long.clone(),
+                // the type of a typed transaction is 1 or 2
+                q_rlp.clone() * is_type.clone() * (tx_type.clone() - byte.clone()),
+                q_rlp.clone()
+                    * is_type.clone()
+                    * (byte.clone() - 1.expr())
+                    * (byte.clone() - 2.expr()),
+                q_rlp.clone() * is_type.clone() * rem.clone(),
+                // a list prefix holds the number of bytes of its length
+                q_rlp.clone()
+                    * is_list_prefix.clone()
+                    * (rem.clone() - is_long.clone() * (byte.clone() - 0xf7.expr())),
+                q_rlp.clone() * is_list_prefix.clone() * acc_int.clone(),
+                // a field prefix holds the length of the field, the number of bytes of
+                // its length, or the field itself, and the access list is a list
+                q_rlp.clone()
+                    * is_prefix.clone()
+                    * (rem.clone()
+                        - not::expr(is_short.clone())
+                            * (byte.clone()
+                                - 0x80.expr()
+                                - is_long.clone() * (0xb7 - 0x80).expr()
+                                - is_access_list.clone() * (0xc0 - 0x80).expr())),
+                q_rlp.clone() * is_access_list.clone() * is_short.clone(),
+                q_rlp.clone()
+                    * is_prefix.clone()
+                    * is_long
+                    * not::expr(is_data + is_access_list.clone()),
+                q_rlp.clone()
+                    * is_prefix.clone()
+                    * (acc_rlc.clone() - is_short.clone() * byte.clone()),
+                q_rlp.clone()
+                    * is_prefix.clone()
+                    * (acc_int.clone() - is_short.clone() * byte.clone()),
+                q_rlp.clone() * is_prefix.clone() * (index.clone() - is_short),
+                // a length is accumulated in base 256
+                q_rlp.clone() * is_len.clone() * index.clone(),
+                q_not_first.clone()
+                    * is_len.clone()
+                    * (acc_int.clone() - acc_int_prev.clone() * 256.expr() - byte.clone()),
+                // a payload other than an access list is accumulated in the RLC encoding
+                // and in base 256, from the first byte after the prefix or after the
+                // length
+                q_not_first.clone()
+                    * is_payload.clone()
+                    * not::expr(is_access_list.clone())
+                    * (acc_rlc
+                        - not::expr(is_str_len_prev.clone()) * acc_rlc_prev * randomness
+                        - byte.clone()),
+                q_not_first.clone()
+                    * is_payload.clone()
+                    * not::expr(is_access_list.clone())
+                    * (acc_int
+                        - not::expr(is_str_len_prev.clone()) * acc_int_prev.clone() * 256.expr()
+                        - byte),
+                q_not_first.clone()
+                    * is_payload.clone()
+                    * not::expr(is_access_list)
+                    * (index - index_prev - 1.expr()),
+                // the type, the prefixes and the payload after a length start once the
+                // previous item is over, while the other bytes continue it
+                q_not_first.clone() * is_start * rem_prev.clone(),
+                q_not_first.clone() * is_continuation * (rem.clone() - rem_prev + 1.expr()),
+                q_not_first.clone()
+                    * is_payload.clone()
+                    * is_str_len_prev.clone()
+                    * (rem.clone() - acc_int_prev + 1.expr()),
+                // the kinds follow the RLP layout: a type is followed by a list
+                // prefix, a list prefix and its length by the first field, a long field
+                // prefix by its length, a length by its payload, and a field by the next
+                // one or by the next transaction after the last field
+                q_not_first.clone() * is_type_prev.clone() * not::expr(is_list_prefix.clone()),
+                q_not_first.clone()
+                    * (is_list_prefix_prev.clone() + is_list_len_prev.clone())
+                    * not::expr(is_list_len.clone() + is_prefix.clone()),
+                q_not_first.clone()
+                    * (is_list_prefix_prev + is_list_len_prev)
+                    * is_prefix.clone()
+                    * field.clone(),
+                q_not_first.clone() * is_prefix_prev * is_long_prev * not::expr(is_str_len.clone()),
+                q_not_first.clone()
+                    * is_str_len_prev
+                    * not::expr(is_str_len.clone() + is_payload.clone()),
+                q_not_first.clone() * is_item_prev.clone() * is_len,
+                q_not_first.clone()
+                    * is_item_prev.clone()
+                    * is_last_field_prev.clone()
+                    * is_prefix.clone(),
+                q_not_first.clone()
+                    * is_item_prev.clone()
+                    * not::expr(is_last_field_prev)
+                    * (is_type.clone() + is_list_prefix.clone()),
+                q_not_first.clone()
+                    * is_item_prev
+                    * is_prefix.clone()
+                    * (field.clone() - field_prev.clone() - 1.expr()),
+                q_not_first.clone()
+                    * (is_str_len.clone() + is_payload.clone())
+                    * (field - field_prev),
+                // the transactions are numbered from 1, and keep their type
+                q_not_first.clone()
+                    * is_byte.clone()
+                    * (tx_id.clone() - tx_id_prev - is_new_tx.clone()),
+                q_not_first.clone()
+                    * is_list_prefix.clone()
+                    * not::expr(is_type_prev)
+                    * tx_type.clone(),
+                q_not_first.clone()
+                    * (is_byte.clone() - is_new_tx)
+                    * (tx_type.clone() - tx_type_prev),
+                q_first.clone() * is_byte.clone() * not::expr(is_type + is_list_prefix.clone()),
+                q_first.clone() * is_byte.clone() * (tx_id - 1.expr()),
+                q_first * is_list_prefix * tx_type,
+                // the end of a field is the last byte of an item before a type, a
+                // prefix or the padding
+                q_rlp.clone()
+                    * (field_end
+                        - is_item.clone()
+                            * (not::expr(is_byte_next)
+                                + is_type_next
+                                + is_list_prefix_next
+                                + is_prefix_next)),
+                // the last byte ends the last field of a transaction
+                q_rlp.clone() * is_last_byte.clone() * not::expr(is_item),
+                q_rlp.clone() * is_last_byte.clone() * rem,
+                q_rlp.clone() * is_last_byte * not::expr(is_last_field.clone()),
+            ]);
+
+            // the fields keep their kind and tag, which are looked up on their prefix
+            for column in [
+                txs_rlp_tag,
+                txs_rlp_is_rlc,
+                txs_rlp_is_to,
+                txs_rlp_is_data,
+                txs_rlp_is_access_list,
+                txs_rlp_is_last_field,
+            ] {
+                constraints.push(
+                    q_not_first.clone()
+                        * (is_str_len.clone() + is_payload.clone())
+                        * (meta.query_advice(column, Rotation::cur())
+                            - meta.query_advice(column, Rotation::prev())),
+                );
+            }
+
+            constraints
+        });
+
+        meta.lookup_any("transactions rlp: rem in range", |meta| {
+            let q_rlp = meta.query_selector(q_txs_rlp_first) + meta.query_selector(q_txs_rlp);
+            let is_byte = meta.query_advice(header_is_byte, Rotation::cur());
+            let rem = meta.query_advice(header_tail_rem, Rotation::cur());
+
+            vec![(
+                q_rlp * is_byte * rem,
+                meta.query_fixed(fixed_u16, Rotation::cur()),
+            )]
+        });
+
+        // The prefixes are in the ranges of their kind, checked as `(byte - min) *
+        // 256 + (max - byte)` in range: a short list in [0xc0, 0xf7], a long list in
+        // [0xf8, 0xff], a single byte in [0x00, 0x7f], a short string in [0x80,
+        // 0xb7] and a long string in [0xb8, 0xbf]. The lists are the transactions,
+        // the access lists and their items and storage keys.
+        meta.lookup_any("transactions rlp: prefix in range", |meta| {
+            let q_rlp = meta.query_selector(q_txs_rlp_first) + meta.query_selector(q_txs_rlp);
+            let byte = meta.query_advice(raw_public_inputs, Rotation::cur());
+            let is_list_prefix = meta.query_advice(txs_rlp_is_list_prefix, Rotation::cur());
+            let al_is_list_prefix = meta.query_advice(txs_rlp_al_is_list_prefix, Rotation::cur());
+            let is_prefix = meta.query_advice(header_is_prefix, Rotation::cur());
+            let is_short = meta.query_advice(header_is_short, Rotation::cur());
+            let is_long = meta.query_advice(txs_rlp_is_long, Rotation::cur());
+            let is_access_list = meta.query_advice(txs_rlp_is_access_list, Rotation::cur());
+            let in_range = |min: u64, max: u64| {
+                (byte.clone() - min.expr()) * 256.expr() + max.expr() - byte.clone()
+            };
+
+            vec![(
+                q_rlp
+                    * ((is_list_prefix
+                        + al_is_list_prefix
+                        + is_prefix.clone() * is_access_list.clone())
+                        * select::expr(
+                            is_long.clone(),
+                            in_range(0xf8, 0xff),
+                            in_range(0xc0, 0xf7),
+                        )
+                        + is_prefix
+                            * not::expr(is_access_list)
+                            * (is_short.clone() * in_range(0x00, 0x7f)
+                                + is_long.clone() * in_range(0xb8, 0xbf)
+                                + not::expr(is_short + is_long) * in_range(0x80, 0xb7))),
+                meta.query_fixed(fixed_u16, Rotation::cur()),
+            )]
+        });
+
+        // The prefix of a field has the tag and the kind of the field of its index
+        // in `TX_RLP_FIELDS` for the transaction type.
+        meta.lookup_any("transactions rlp: fields", |meta| {
+            let q_rlp = meta.query_selector(q_txs_rlp_first) + meta.query_selector(q_txs_rlp);
+            let is_prefix = meta.query_advice(header_is_prefix, Rotation::cur());
+            let condition = q_rlp * is_prefix;
+            let kind = meta.query_advice(txs_rlp_is_rlc, Rotation::cur())
+                + meta.query_advice(txs_rlp_is_to, Rotation::cur()) * 2.expr()
+                + meta.query_advice(txs_rlp_is_data, Rotation::cur()) * 3.expr()
+                + meta.query_advice(txs_rlp_is_access_list, Rotation::cur()) * 4.expr();
+
+            let input = [
+                meta.query_advice(txs_rlp_tx_type, Rotation::cur()),
+                meta.query_advice(header_tail_tag, Rotation::cur()) + 1.expr(),
+                meta.query_advice(txs_rlp_tag, Rotation::cur()),
+                kind,
+                meta.query_advice(txs_rlp_is_last_field, Rotation::cur()),
+            ]
+            .map(|value| condition.clone() * value);
+            let table = txs_rlp_fields.map(|column| meta.query_fixed(column, Rotation::cur()));
+
+            input.into_iter().zip(table).collect()
+        });
+
+        // The value of a field is in the tx table: its RLC encoding, the callee
+        // address, the call data length or the number of access list addresses,
+        // counted in the index of the access list payload.
+        meta.lookup_any("transactions rlp: values in tx table", |meta| {
+            let q_rlp = meta.query_selector(q_txs_rlp_first) + meta.query_selector(q_txs_rlp);
+            let field_end = meta.query_advice(txs_rlp_field_end, Rotation::cur());
+            let is_rlc = meta.query_advice(txs_rlp_is_rlc, Rotation::cur());
+            let is_to = meta.query_advice(txs_rlp_is_to, Rotation::cur());
+            let is_data = meta.query_advice(txs_rlp_is_data, Rotation::cur());
+            let is_access_list = meta.query_advice(txs_rlp_is_access_list, Rotation::cur());
+            let condition = q_rlp * field_end;
+            let value = is_rlc.clone() * meta.query_advice(header_acc_rlc, Rotation::cur())
+                + is_to.clone() * meta.query_advice(header_acc_int, Rotation::cur())
+                + (is_data.clone() + is_access_list.clone())
+                    * meta.query_advice(txs_rlp_index, Rotation::cur());
+
+            let input = [
+                condition.clone()
+                    * (is_rlc + is_to + is_data + is_access_list)
+                    * meta.query_advice(txs_rlp_tx_id, Rotation::cur()),
+                condition.clone() * meta.query_advice(txs_rlp_tag, Rotation::cur()),
+                condition * value,
+            ];
+            let table = [
+                meta.query_advice(tx_table.tx_id, Rotation::cur()),
+                meta.query_fixed(tx_table.tag, Rotation::cur()),
+                meta.query_advice(tx_table.value, Rotation::cur()),
+            ];
+
+            input.into_iter().zip(table).collect()
+        });
+
+        // The tx table values implied by a field: `IsCreate` when the callee
+        // address is empty, and the number of access list storage keys.
+        meta.lookup_any("transactions rlp: implied values in tx table", |meta| {
+            let q_rlp = meta.query_selector(q_txs_rlp_first) + meta.query_selector(q_txs_rlp);
+            let field_end = meta.query_advice(txs_rlp_field_end, Rotation::cur());
+            let is_to = meta.query_advice(txs_rlp_is_to, Rotation::cur());
+            let is_access_list = meta.query_advice(txs_rlp_is_access_list, Rotation::cur());
+            let condition = q_rlp * field_end;
+            // the callee address is either 20 bytes long or empty
+            let is_create = 1.expr()
+                - meta.query_advice(txs_rlp_index, Rotation::cur())
+                    * Expression::Constant(F::from(20).invert().unwrap());
+
+            let input = [
+                condition.clone()
+                    * (is_to.clone() + is_access_list.clone())
+                    * meta.query_advice(txs_rlp_tx_id, Rotation::cur()),
+                condition.clone()
+                    * (is_to.clone() * TxFieldTag::IsCreate.expr()
+                        + is_access_list.clone() * TxFieldTag::AccessListStorageKeysLen.expr()),
+                condition.clone() * is_to * is_create
+                    + condition
+                        * is_access_list
+                        * meta.query_advice(txs_rlp_al_keys_count, Rotation::cur()),
+            ];
+            let table = [
+                meta.query_advice(tx_table.tx_id, Rotation::cur()),
+                meta.query_fixed(tx_table.tag, Rotation::cur()),
+                meta.query_advice(tx_table.value, Rotation::cur()),
+            ];
+
+            input.into_iter().zip(table).collect()
+        });
+
+        // The bytes of the call data are in the tx table, where the index of the
+        // first one is 0.
+        meta.lookup_any("transactions rlp: call data in tx table", |meta| {
+            let q_rlp = meta.query_selector(q_txs_rlp_first) + meta.query_selector(q_txs_rlp);
+            let is_byte = meta.query_advice(header_is_byte, Rotation::cur());
+            let is_kind = meta.query_advice(txs_rlp_is_type, Rotation::cur())
+                + meta.query_advice(txs_rlp_is_list_prefix, Rotation::cur())
+                + meta.query_advice(txs_rlp_is_list_len, Rotation::cur())
+                + meta.query_advice(header_is_prefix, Rotation::cur())
+                + meta.query_advice(txs_rlp_is_str_len, Rotation::cur());
+            let is_short = meta.query_advice(header_is_short, Rotation::cur());
+            let is_data = meta.query_advice(txs_rlp_is_data, Rotation::cur());
+            // a payload byte or a single byte below 0x80
+            let condition = q_rlp * is_data * (is_byte - is_kind + is_short);
+
+            let input = [
+                condition.clone() * meta.query_advice(txs_rlp_tx_id, Rotation::cur()),
+                condition.clone() * TxFieldTag::CallData.expr(),
+                condition.clone() * (meta.query_advice(txs_rlp_index, Rotation::cur()) - 1.expr()),
+                condition * meta.query_advice(raw_public_inputs, Rotation::cur()),
+            ];
+            let table = [
+                meta.query_advice(tx_table.tx_id, Rotation::cur()),
+                meta.query_fixed(tx_table.tag, Rotation::cur()),
+                meta.query_advice(tx_table.index, Rotation::cur()),
+                meta.query_advice(tx_table.value, Rotation::cur()),
+            ];
+
+            input.into_iter().zip(table).collect()
+        });
+
+        // 2.1 Access list payloads -> decoded as their items, each one the list of
+        // an address and of the list of its storage keys. The entries are looked up
+        // in the tx table in the order they're warmed up: the addresses from index
+        // 0, followed by the storage keys together with the address of their item.
+        // Each byte is a list prefix or a byte of a list length, of an item or of
+        // its storage keys when `is_keys`, or else the prefix of an address or of a
+        // storage key or one of their bytes, with `is_elem_last` on the last one.
+        // `al_rem` holds the number of bytes left in the length, the address or the
+        // storage key, and `item_rem` and `keys_rem` the number of bytes left in
+        // the item and in its storage keys. `txs_rlp_index` counts the addresses
+        // and `keys_count` the storage keys, while `addresses_len` holds the number
+        // of addresses of the whole access list.
+        meta.create_gate("transactions rlp: access lists", |meta| {
+            let q_not_first = meta.query_selector(q_txs_rlp);
+            let q_rlp = meta.query_selector(q_txs_rlp_first) + q_not_first.clone();
+            let byte = meta.query_advice(raw_public_inputs, Rotation::cur());
+            let randomness = meta.query_advice(randomness, Rotation::cur());
+
+            let is_prefix = meta.query_advice(header_is_prefix, Rotation::cur());
+            let is_str_len = meta.query_advice(txs_rlp_is_str_len, Rotation::cur());
+            let is_payload = meta.query_advice(header_is_byte, Rotation::cur())
+                - meta.query_advice(txs_rlp_is_type, Rotation::cur())
+                - meta.query_advice(txs_rlp_is_list_prefix, Rotation::cur())
+                - meta.query_advice(txs_rlp_is_list_len, Rotation::cur())
+                - is_prefix.clone()
+                - is_str_len.clone();
+            let is_long = meta.query_advice(txs_rlp_is_long, Rotation::cur());
+            let is_access_list = meta.query_advice(txs_rlp_is_access_list, Rotation::cur());
+            let field_end = meta.query_advice(txs_rlp_field_end, Rotation::cur());
+            let index = meta.query_advice(txs_rlp_index, Rotation::cur());
+            let acc_rlc = meta.query_advice(header_acc_rlc, Rotation::cur());
+            let acc_int = meta.query_advice(header_acc_int, Rotation::cur());
+            let is_list_prefix = meta.query_advice(txs_rlp_al_is_list_prefix, Rotation::cur());
+            let is_list_len = meta.query_advice(txs_rlp_al_is_list_len, Rotation::cur());
+            let is_elem_prefix = meta.query_advice(txs_rlp_al_is_elem_prefix, Rotation::cur());
+            let is_elem = meta.query_advice(txs_rlp_al_is_elem, Rotation::cur());
+            let is_elem_last = meta.query_advice(txs_rlp_al_is_elem_last, Rotation::cur());
+            let is_keys = meta.query_advice(txs_rlp_al_is_keys, Rotation::cur());
+            let rem = meta.query_advice(txs_rlp_al_rem, Rotation::cur());
+            let item_rem = meta.query_advice(txs_rlp_al_item_rem, Rotation::cur());
+            let keys_rem = meta.query_advice(txs_rlp_al_keys_rem, Rotation::cur());
+            let keys_count = meta.query_advice(txs_rlp_al_keys_count, Rotation::cur());
+            let addresses_len = meta.query_advice(txs_rlp_al_addresses_len, Rotation::cur());
+            let address = meta.query_advice(txs_rlp_al_address, Rotation::cur());
+
+            let is_prefix_prev = meta.query_advice(header_is_prefix, Rotation::prev());
+            let is_str_len_prev = meta.query_advice(txs_rlp_is_str_len, Rotation::prev());
+            let index_prev = meta.query_advice(txs_rlp_index, Rotation::prev());
+            let acc_rlc_prev = meta.query_advice(header_acc_rlc, Rotation::prev());
+            let acc_int_prev = meta.query_advice(header_acc_int, Rotation::prev());
+            let is_list_prev = meta.query_advice(txs_rlp_al_is_list_prefix, Rotation::prev())
+                + meta.query_advice(txs_rlp_al_is_list_len, Rotation::prev());
+            let is_elem_prefix_prev =
+                meta.query_advice(txs_rlp_al_is_elem_prefix, Rotation::prev());
+            let is_elem_prev = meta.query_advice(txs_rlp_al_is_elem, Rotation::prev());
+            let is_keys_prev = meta.query_advice(txs_rlp_al_is_keys, Rotation::prev());
+            let rem_prev = meta.query_advice(txs_rlp_al_rem, Rotation::prev());
+            let item_rem_prev = meta.query_advice(txs_rlp_al_item_rem, Rotation::prev());
+            let keys_rem_prev = meta.query_advice(txs_rlp_al_keys_rem, Rotation::prev());
+            let keys_count_prev = meta.query_advice(txs_rlp_al_keys_count, Rotation::prev());
+            let addresses_len_prev = meta.query_advice(txs_rlp_al_addresses_len, Rotation::prev());
+            let address_prev = meta.query_advice(txs_rlp_al_address, Rotation::prev());
+
+            let is_elem_next = meta.query_advice(txs_rlp_al_is_elem, Rotation::next());
+
+            let is_al = is_list_prefix.clone()
+                + is_list_len.clone()
+                + is_elem_prefix.clone()
+                + is_elem.clone();
+            let not_keys = not::expr(is_keys.clone());
+            let not_keys_prev = not::expr(is_keys_prev.clone());
+            let is_item_prefix = is_list_prefix.clone() * not_keys.clone();
+            let is_item_len = is_list_len.clone() * not_keys.clone();
+            let is_address_prefix = is_elem_prefix.clone() * not_keys.clone();
+            let is_address = is_elem.clone() * not_keys.clone();
+            let is_keys_prefix = is_list_prefix.clone() * is_keys.clone();
+            let is_keys_len = is_list_len.clone() * is_keys.clone();
+            let is_key_prefix = is_elem_prefix.clone() * is_keys.clone();
+            let is_key = is_elem.clone() * is_keys.clone();
+            let is_keys_continuation =
+                is_keys.clone() * (is_list_len.clone() + is_elem_prefix.clone() + is_elem.clone());
+            let is_elem_any_prev = is_elem_prefix_prev.clone() + is_elem_prev.clone();
+
+            let mut constraints = vec![];
+            for flag in [
+                is_list_prefix.clone(),
+                is_list_len.clone(),
+                is_elem_prefix.clone(),
+                is_elem.clone(),
+                is_elem_last.clone(),
+                is_keys.clone(),
+                is_al.clone(),
+            ] {
+                constraints.push(q_rlp.clone() * flag.clone() * not::expr(flag));
+            }
+
+            constraints.extend([
+                // the payload of an access list is decoded here, and nothing else
+                q_rlp.clone() * (is_al.clone() - is_access_list.clone() * is_payload.clone()),
+                q_rlp.clone() * is_keys.clone() * not::expr(is_al.clone()),
+                q_rlp.clone() * not::expr(is_al.clone()) * rem.clone(),
+                q_rlp.clone() * not::expr(is_al.clone()) * item_rem.clone(),
+                q_rlp.clone() * not::expr(is_al.clone()) * keys_rem.clone(),
+                // the last byte of an address or a storage key is flagged, and the
+                // other ones are followed by the next byte
+                q_rlp.clone() * is_elem_last.clone() * not::expr(is_elem.clone()),
+                q_rlp.clone() * is_elem_last.clone() * rem.clone(),
+                q_rlp.clone()
+                    * is_elem.clone()
+                    * not::expr(is_elem_last.clone())
+                    * not::expr(is_elem_next),
+                // the kinds follow the RLP layout: an item starts the payload or
+                // follows the storage keys of the previous one, its prefix and length
+                // are followed by the address prefix and the address, whose last byte
+                // is followed by the prefix of the storage keys, whose prefix and
+                // length are followed by the first storage key prefix, and a storage
+                // key by the next one or by the next item
+                q_not_first.clone()
+                    * is_item_prefix.clone()
+                    * not::expr(
+                        is_keys_prev.clone() * not::expr(is_elem_prefix_prev.clone())
+                            + is_prefix_prev
+                            + is_str_len_prev,
+                    ),
+                q_not_first.clone()
+                    * is_item_prefix.clone()
+                    * (rem_prev.clone() + item_rem_prev.clone() + keys_rem_prev.clone()),
+                q_not_first.clone()
+                    * is_item_len.clone()
+                    * not::expr(is_list_prev.clone() * not_keys_prev.clone()),
+                q_not_first.clone()
+                    * is_address_prefix.clone()
+                    * not::expr(is_list_prev.clone() * not_keys_prev.clone()),
+                q_not_first.clone() * is_address_prefix * rem_prev.clone(),
+                q_not_first.clone()
+                    * is_address
+                    * not::expr(is_elem_any_prev.clone() * not_keys_prev.clone()),
+                q_not_first.clone()
+                    * is_keys_prefix.clone()
+                    * not::expr(is_elem_prev * not_keys_prev),
+                q_not_first.clone() * is_keys_prefix.clone() * rem_prev.clone(),
+                q_not_first.clone()
+                    * is_keys_len.clone()
+                    * not::expr(is_list_prev * is_keys_prev.clone()),
+                q_not_first.clone()
+                    * is_key_prefix.clone()
+                    * not::expr(is_keys_prev.clone() * not::expr(is_elem_prefix_prev)),
+                q_not_first.clone() * is_key_prefix.clone() * rem_prev.clone(),
+                q_not_first.clone() * is_key * not::expr(is_elem_any_prev * is_keys_prev),
+                // a list prefix holds the number of bytes of its length, and the
+                // prefix of an address or a storage key the number of their bytes
+                q_rlp.clone()
+                    * is_list_prefix.clone()
+                    * (rem.clone() - is_long.clone() * (byte.clone() - 0xf7.expr())),
+                q_not_first.clone()
+                    * (is_list_len.clone() + is_elem.clone())
+                    * (rem.clone() - rem_prev + 1.expr()),
+                q_rlp.clone()
+                    * is_elem_prefix.clone()
+                    * (rem.clone() - 20.expr() - is_keys.clone() * 12.expr()),
+                q_rlp.clone()
+                    * is_elem_prefix.clone()
+                    * (byte.clone() - 0x94.expr() - is_keys.clone() * 12.expr()),
+                // the bytes left in an item and in its storage keys are given by their
+                // short prefix or by their length, and decrease over their payload
+                q_rlp.clone()
+                    * is_item_prefix.clone()
+                    * (item_rem.clone()
+                        - not::expr(is_long.clone()) * (byte.clone() - 0xc0.expr())),
+                q_rlp.clone() * is_item_len * (item_rem.clone() - acc_int.clone()),
+                q_not_first.clone()
+                    * (is_elem_prefix.clone()
+                        + is_elem.clone()
+                        + is_keys.clone() * (is_list_prefix.clone() + is_list_len.clone()))
+                    * (item_rem.clone() - item_rem_prev + 1.expr()),
+                q_rlp.clone()
+                    * is_keys_prefix.clone()
+                    * (keys_rem.clone() - not::expr(is_long) * (byte.clone() - 0xc0.expr())),
+                q_rlp.clone() * is_keys_len * (keys_rem.clone() - acc_int.clone()),
+                q_not_first.clone()
+                    * is_keys.clone()
+                    * (is_elem_prefix.clone() + is_elem.clone())
+                    * (keys_rem.clone() - keys_rem_prev + 1.expr()),
+                q_rlp.clone() * not_keys.clone() * keys_rem.clone(),
+                // the length, the address and the storage key are accumulated in base
+                // 256, and the storage key in the RLC encoding
+                q_rlp.clone() * (is_list_prefix.clone() + is_elem_prefix.clone()) * acc_int.clone(),
+                q_not_first.clone()
+                    * (is_list_len.clone() + is_elem.clone())
+                    * (acc_int - acc_int_prev.clone() * 256.expr() - byte.clone()),
+                q_rlp.clone()
+                    * (is_list_prefix.clone() + is_list_len + is_elem_prefix.clone())
+                    * acc_rlc.clone(),
+                q_not_first.clone()
+                    * is_elem.clone()
+                    * (acc_rlc.clone() - acc_rlc_prev * randomness - byte),
+                // the addresses and the storage keys are counted from the start of the
+                // access list
+                q_not_first.clone() * is_al.clone() * (index.clone() - index_prev - is_item_prefix),
+                q_not_first.clone()
+                    * is_al.clone()
+                    * (keys_count.clone() - keys_count_prev - is_key_prefix),
+                q_rlp.clone() * (is_prefix + is_str_len.clone()) * keys_count,
+                q_not_first.clone()
+                    * is_access_list.clone()
+                    * (is_str_len + is_payload)
+                    * (addresses_len.clone() - addresses_len_prev),
+                q_rlp.clone() * is_access_list * field_end.clone() * (addresses_len - index),
+                // the storage keys keep the address of their item
+                q_not_first.clone() * is_keys_prefix * (address.clone() - acc_int_prev),
+                q_not_first * is_keys_continuation * (address - address_prev),
+                // an access list ends with the last storage key of its last item
+                q_rlp.clone() * field_end.clone() * is_al.clone() * (rem + item_rem + keys_rem),
+                q_rlp * field_end * is_al * (not_keys + is_elem_prefix),
+            ]);
+
+            constraints
+        });
+
+        for (name, column) in [
+            ("transactions rlp: access list rem in range", txs_rlp_al_rem),
+            (
+                "transactions rlp: access list item rem in range",
+                txs_rlp_al_item_rem,
+            ),
+            (
+                "transactions rlp: access list keys rem in range",
+                txs_rlp_al_keys_rem,
+            ),
+        ] {
+            meta.lookup_any(name, |meta| {
+                let q_rlp = meta.query_selector(q_txs_rlp_first) + meta.query_selector(q_txs_rlp);
+
+                vec![(
+                    q_rlp * meta.query_advice(column, Rotation::cur()),
+                    meta.query_fixed(fixed_u16, Rotation::cur()),
+                )]
+            });
+        }
+
+        // The last byte of an address is the address of its entry in the tx
+        // table, whose index is the number of previous addresses.
+        meta.lookup_any(
+            "transactions rlp: access list addresses in tx table",
+            |meta| {
+                let q_rlp = meta.query_selector(q_txs_rlp_first) + meta.query_selector(q_txs_rlp);
+                let condition = q_rlp
+                    * meta.query_advice(txs_rlp_al_is_elem_last, Rotation::cur())
+                    * not::expr(meta.query_advice(txs_rlp_al_is_keys, Rotation::cur()));
+
+                let input = [
+                    condition.clone() * meta.query_advice(txs_rlp_tx_id, Rotation::cur()),
+                    condition.clone() * TxFieldTag::AccessListAddress.expr(),
+                    condition.clone()
+                        * (meta.query_advice(txs_rlp_index, Rotation::cur()) - 1.expr()),
+                    condition * meta.query_advice(header_acc_int, Rotation::cur()),
+                ];
+                let table = [
+                    meta.query_advice(tx_table.tx_id, Rotation::cur()),
+                    meta.query_fixed(tx_table.tag, Rotation::cur()),
+                    meta.query_advice(tx_table.index, Rotation::cur()),
+                    meta.query_advice(tx_table.value, Rotation::cur()),
+                ];
+
+                input.into_iter().zip(table).collect()
+            },
+        );
+
+        // The last byte of a storage key is the storage key of its entry in the tx
+        // table, after the addresses and the previous storage keys, whose address
+        // is the one of its item.
+        for (name, tag, value) in [
+            (
+                "transactions rlp: access list storage keys in tx table",
+                TxFieldTag::AccessListStorageKey,
+                header_acc_rlc,
+            ),
+            (
+                "transactions rlp: access list storage key addresses in tx table",
+                TxFieldTag::AccessListAddress,
+                txs_rlp_al_address,
+            ),
+        ] {
+            meta.lookup_any(name, |meta| {
+                let q_rlp = meta.query_selector(q_txs_rlp_first) + meta.query_selector(q_txs_rlp);
+                let condition = q_rlp
+                    * meta.query_advice(txs_rlp_al_is_elem_last, Rotation::cur())
+                    * meta.query_advice(txs_rlp_al_is_keys, Rotation::cur());
+                let index = meta.query_advice(txs_rlp_al_addresses_len, Rotation::cur())
+                    + meta.query_advice(txs_rlp_al_keys_count, Rotation::cur())
+                    - 1.expr();
+
+                let input = [
+                    condition.clone() * meta.query_advice(txs_rlp_tx_id, Rotation::cur()),
+                    condition.clone() * tag.expr(),
+                    condition.clone() * index,
+                    condition * meta.query_advice(value, Rotation::cur()),
+                ];
+                let table = [
+                    meta.query_advice(tx_table.tx_id, Rotation::cur()),
+                    meta.query_fixed(tx_table.tag, Rotation::cur()),
+                    meta.query_advice(tx_table.index, Rotation::cur()),
+                    meta.query_advice(tx_table.value, Rotation::cur()),
+                ];
+
+                input.into_iter().zip(table).collect()
+            });
+        }
+
+        // 2.2 transactions root == keccak(transactions RLP), whose length and RLC
+        // encoding are copied from the last transactions RLP byte row
+        let txs_root_rotation =
+            Rotation(TXS_ROOT_BYTES_OFFSET as i32 + 31 - TXS_RLP_LEN_OFFSET as i32);
+        meta.lookup_any("transactions root in keccak table", |meta| {
+            let q_txs_rlp_len = meta.query_selector(q_txs_rlp_len);
+            let input = [
+                q_txs_rlp_len.clone(),
+                q_txs_rlp_len.clone() * meta.query_advice(bytes_rlc_acc, Rotation::cur()),
+                q_txs_rlp_len.clone() * meta.query_advice(raw_public_inputs, Rotation::cur()),
+                q_txs_rlp_len * meta.query_advice(bytes_rlc_acc, txs_root_rotation),
+            ];
+            let table = [
+                keccak_table.is_enabled,
+                keccak_table.input_rlc,
+                keccak_table.input_len,
+                keccak_table.output_rlc,
+            ]
+            .map(|column| meta.query_advice(column, Rotation::cur()));
+
+            input.into_iter().zip(table).collect()
+        });
+
+        // 2.3 receipts root == keccak(receipts encoding), built from the
+        // TxReceipt and TxLog rows of the rw table
+        let receipts = ReceiptsConfig::configure(
+            meta,
+            max_txs,
+            rw_table,
+            tx_table.clone(),
+            keccak_table.clone(),
+            &challenges,
+        );
+
         Self {
             max_txs,
             max_calldata,
@@ -539,7 +2134,68 @@ impl<F: Field> SubCircuitConfig<F> for PiCircuitConfig<F> {
             rand_rpi,
             q_not_end,
             q_end,
+            q_hash_byte,
+            q_hash_first,
+            q_header_byte,
+            q_header_first,
+            q_header_last,
+            q_txs_root_byte,
+            q_receipts_root_byte,
+            header_is_byte,
+            header_len_acc,
+            bytes_rlc_acc,
+            q_header_const,
+            header_const,
+            q_randomness_first,
+            q_randomness,
+            randomness,
+            q_header_field,
+            q_header_field_rev,
+            q_header_field_first,
+            header_acc_rlc,
+            header_acc_int,
+            q_header_tail_first,
+            q_header_tail,
+            header_is_prefix,
+            header_is_short,
+            header_tail_tag,
+            header_tail_rem,
+            header_field_tag,
+            q_txs_rlp_first,
+            q_txs_rlp,
+            q_txs_rlp_len,
+            txs_rlp_tx_id,
+            txs_rlp_tx_type,
+            txs_rlp_is_type,
+            txs_rlp_is_list_prefix,
+            txs_rlp_is_list_len,
+            txs_rlp_is_str_len,
+            txs_rlp_is_long,
+            txs_rlp_index,
+            txs_rlp_field_end,
+            txs_rlp_tag,
+            txs_rlp_is_rlc,
+            txs_rlp_is_to,
+            txs_rlp_is_data,
+            txs_rlp_is_access_list,
+            txs_rlp_is_last_field,
+            txs_rlp_al_is_list_prefix,
+            txs_rlp_al_is_list_len,
+            txs_rlp_al_is_elem_prefix,
+            txs_rlp_al_is_elem,
+            txs_rlp_al_is_elem_last,
+            txs_rlp_al_is_keys,
+            txs_rlp_al_rem,
+            txs_rlp_al_item_rem,
+            txs_rlp_al_keys_rem,
+            txs_rlp_al_keys_count,
+            txs_rlp_al_addresses_len,
+            txs_rlp_al_address,
+            txs_rlp_fields,
             pi,
+            receipts,
+            rw_table,
+            keccak_table,
             _marker: PhantomData,
         }
     }
@@ -548,9 +2204,14 @@ impl<F: Field> SubCircuitConfig<F> for PiCircuitConfig<F> {
 impl<F: Field> PiCircuitConfig<F> {
     /// Return the number of rows in the circuit
     #[inline]
-    fn circuit_len(&self) -> usize {
+    fn circuit_len(&self, max_access_list: usize) -> usize {
         // +1 empty row in block table, +1 empty row in tx_table
-        BLOCK_LEN + 1 + EXTRA_LEN + 3 * (TX_LEN * self.max_txs + 1) + self.max_calldata
+        BLOCK_LEN
+            + 1
+            + EXTRA_LEN
+            + 3 * (TX_LEN * self.max_txs + 1)
+            + self.max_calldata
+            + txs_rlp_len(self.max_txs, self.max_calldata, max_access_list)
     }
 
     fn assign_tx_empty_row(&self, region: &mut Region<'_, F>, offset: usize) -> Result<(), Error> {
@@ -604,6 +2265,45 @@ impl<F: Field> PiCircuitConfig<F> {
         )?;
         Ok(())
     }
+
+    /// Assigns a tx_table row of an access list entry, which isn't in the
+    /// raw_public_inputs column
+    fn assign_tx_access_list_row(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        tx_id: usize,
+        tag: TxFieldTag,
+        index: usize,
+        tx_value: F,
+    ) -> Result<(), Error> {
+        region.assign_advice(
+            || "tx_id",
+            self.tx_table.tx_id,
+            offset,
+            || Value::known(F::from(tx_id as u64)),
+        )?;
+        region.assign_fixed(
+            || "tag",
+            self.tx_table.tag,
+            offset,
+            || Value::known(F::from(tag as u64)),
+        )?;
+        region.assign_advice(
+            || "index",
+            self.tx_table.index,
+            offset,
+            || Value::known(F::from(index as u64)),
+        )?;
+        region.assign_advice(
+            || "tx_value",
+            self.tx_table.value,
+            offset,
+            || Value::known(tx_value),
+        )?;
+        Ok(())
+    }
+
     /// Assigns a tx_table row and stores the values in a vec for the
     /// raw_public_inputs column
     #[allow(clippy::too_many_arguments)]
@@ -804,14 +2504,16 @@ impl<F: Field> PiCircuitConfig<F> {
 
     /// Assigns the values for block table in the block_table column
     /// and in the raw_public_inputs column. A copy is also stored in
-    /// a vector for computing RLC(raw_public_inputs)
+    /// a vector for computing RLC(raw_public_inputs). Returns the cells
+    /// of the raw_public_inputs column.
     fn assign_block_table(
         &self,
         region: &mut Region<'_, F>,
         block_values: BlockValues,
         randomness: F,
         raw_pi_vals: &mut [F],
-    ) -> Result<AssignedCell<F, F>, Error> {
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        let mut cells = Vec::with_capacity(BLOCK_LEN + 1);
         let mut offset = 0;
         for i in 0..BLOCK_LEN + 1 {
             self.q_block_table.enable(region, offset + i)?;
@@ -824,12 +2526,12 @@ impl<F: Field> PiCircuitConfig<F> {
             offset,
             || Value::known(F::zero()),
         )?;
-        region.assign_advice(
+        cells.push(region.assign_advice(
             || "zero",
             self.raw_public_inputs,
             offset,
             || Value::known(F::zero()),
-        )?;
+        )?);
         raw_pi_vals[offset] = F::zero();
         offset += 1;
 
@@ -841,12 +2543,12 @@ impl<F: Field> PiCircuitConfig<F> {
             offset,
             || Value::known(coinbase),
         )?;
-        region.assign_advice(
+        cells.push(region.assign_advice(
             || "coinbase",
             self.raw_public_inputs,
             offset,
             || Value::known(coinbase),
-        )?;
+        )?);
         raw_pi_vals[offset] = coinbase;
         offset += 1;
 
@@ -858,12 +2560,12 @@ impl<F: Field> PiCircuitConfig<F> {
             offset,
             || Value::known(gas_limit),
         )?;
-        region.assign_advice(
+        cells.push(region.assign_advice(
             || "gas_limit",
             self.raw_public_inputs,
             offset,
             || Value::known(gas_limit),
-        )?;
+        )?);
         raw_pi_vals[offset] = gas_limit;
         offset += 1;
 
@@ -875,12 +2577,12 @@ impl<F: Field> PiCircuitConfig<F> {
             offset,
             || Value::known(number),
         )?;
-        region.assign_advice(
+        cells.push(region.assign_advice(
             || "number",
             self.raw_public_inputs,
             offset,
             || Value::known(number),
-        )?;
+        )?);
         raw_pi_vals[offset] = number;
         offset += 1;
 
@@ -892,12 +2594,12 @@ impl<F: Field> PiCircuitConfig<F> {
             offset,
             || Value::known(timestamp),
         )?;
-        region.assign_advice(
+        cells.push(region.assign_advice(
             || "timestamp",
             self.raw_public_inputs,
             offset,
             || Value::known(timestamp),
-        )?;
+        )?);
         raw_pi_vals[offset] = timestamp;
         offset += 1;
 
@@ -909,12 +2611,12 @@ impl<F: Field> PiCircuitConfig<F> {
             offset,
             || Value::known(difficulty),
         )?;
-        region.assign_advice(
+        cells.push(region.assign_advice(
             || "difficulty",
             self.raw_public_inputs,
             offset,
             || Value::known(difficulty),
-        )?;
+        )?);
         raw_pi_vals[offset] = difficulty;
         offset += 1;
 
@@ -926,12 +2628,12 @@ impl<F: Field> PiCircuitConfig<F> {
             offset,
             || Value::known(base_fee),
         )?;
-        region.assign_advice(
+        cells.push(region.assign_advice(
             || "base_fee",
             self.raw_public_inputs,
             offset,
             || Value::known(base_fee),
-        )?;
+        )?);
         raw_pi_vals[offset] = base_fee;
         offset += 1;
 
@@ -943,15 +2645,50 @@ impl<F: Field> PiCircuitConfig<F> {
             offset,
             || Value::known(chain_id),
         )?;
-        let chain_id_cell = region.assign_advice(
+        cells.push(region.assign_advice(
             || "chain_id",
             self.raw_public_inputs,
             offset,
             || Value::known(chain_id),
-        )?;
+        )?);
         raw_pi_vals[offset] = chain_id;
         offset += 1;
 
+        // block_reward
+        let block_reward = rlc(block_values.block_reward.to_le_bytes(), randomness);
+        region.assign_advice(
+            || "block_reward",
+            self.block_table.value,
+            offset,
+            || Value::known(block_reward),
+        )?;
+        cells.push(region.assign_advice(
+            || "block_reward",
+            self.raw_public_inputs,
+            offset,
+            || Value::known(block_reward),
+        )?);
+        raw_pi_vals[offset] = block_reward;
+        offset += 1;
+
+        for logs_bloom_word in block_values.logs_bloom {
+            let logs_bloom_word = rlc(logs_bloom_word.to_le_bytes(), randomness);
+            region.assign_advice(
+                || "logs_bloom",
+                self.block_table.value,
+                offset,
+                || Value::known(logs_bloom_word),
+            )?;
+            cells.push(region.assign_advice(
+                || "logs_bloom",
+                self.raw_public_inputs,
+                offset,
+                || Value::known(logs_bloom_word),
+            )?);
+            raw_pi_vals[offset] = logs_bloom_word;
+            offset += 1;
+        }
+
         for prev_hash in block_values.history_hashes {
             let prev_hash = rlc(prev_hash.to_fixed_bytes(), randomness);
             region.assign_advice(
@@ -960,42 +2697,48 @@ impl<F: Field> PiCircuitConfig<F> {
                 offset,
                 || Value::known(prev_hash),
             )?;
-            region.assign_advice(
+            cells.push(region.assign_advice(
                 || "prev_hash",
                 self.raw_public_inputs,
                 offset,
                 || Value::known(prev_hash),
-            )?;
+            )?);
             raw_pi_vals[offset] = prev_hash;
             offset += 1;
         }
 
-        Ok(chain_id_cell)
+        Ok(cells)
     }
 
     /// Assigns the extra fields (not in block or tx tables):
     ///   - state root
     ///   - previous block state root
+    ///   - receipts root
+    ///   - block hash
+    ///   - transactions root
+    ///   - header RLP length
+    ///   - randomness of the RLC encoding
+    ///   - block hash bytes
+    ///   - transactions root bytes
+    ///   - receipts root bytes
+    ///   - header RLP bytes, padded with zeros
     /// to the raw_public_inputs column and stores a copy in a
-    /// vector for computing RLC(raw_public_inputs).
+    /// vector for computing RLC(raw_public_inputs). The fields of the header
+    /// RLP are copied to their cells in the block table and in the extra
+    /// values. The RLC encoding of the receipts root bytes is returned after
+    /// the value cells.
+    #[allow(clippy::too_many_arguments)]
     fn assign_extra_fields(
         &self,
         region: &mut Region<'_, F>,
         extra: ExtraValues,
+        block_cells: &[AssignedCell<F, F>],
+        max_access_list: usize,
         randomness: F,
+        challenges: &Challenges<Value<F>>,
         raw_pi_vals: &mut [F],
-    ) -> Result<[AssignedCell<F, F>; 2], Error> {
+    ) -> Result<[AssignedCell<F, F>; 6], Error> {
         let mut offset = BLOCK_LEN + 1;
-        // block hash
-        // let block_hash = rlc(extra.block_hash.to_fixed_bytes(), randomness);
-        // region.assign_advice(
-        //     || "block.hash",
-        //     self.raw_public_inputs,
-        //     offset,
-        //     || Ok(block_hash),
-        // )?;
-        // raw_pi_vals[offset] = block_hash;
-        // offset += 1;
 
         // block state root
         let state_root = rlc(extra.state_root.to_fixed_bytes(), randomness);
@@ -1017,28 +2760,707 @@ impl<F: Field> PiCircuitConfig<F> {
             || Value::known(prev_state_root),
         )?;
         raw_pi_vals[offset] = prev_state_root;
-        Ok([state_root_cell, prev_state_root_cell])
-    }
+        offset += 1;
 
-    /// Assign `rpi_rlc_acc` and `rand_rpi` columns
-    #[allow(clippy::type_complexity)]
-    fn assign_rlc_pi(
-        &self,
-        region: &mut Region<'_, F>,
-        rand_rpi: F,
-        raw_pi_vals: Vec<F>,
-    ) -> Result<(AssignedCell<F, F>, AssignedCell<F, F>), Error> {
-        let circuit_len = self.circuit_len();
-        assert_eq!(circuit_len, raw_pi_vals.len());
+        // block receipts root
+        let receipts_root = rlc(extra.receipts_root.to_fixed_bytes(), randomness);
+        let receipts_root_cell = region.assign_advice(
+            || "receipts.root",
+            self.raw_public_inputs,
+            offset,
+            || Value::known(receipts_root),
+        )?;
+        raw_pi_vals[offset] = receipts_root;
+        offset += 1;
 
-        // Last row
-        let offset = circuit_len - 1;
-        let mut rpi_rlc_acc = raw_pi_vals[offset];
-        region.assign_advice(
-            || "rpi_rlc_acc",
-            self.rpi_rlc_acc,
+        // block hash
+        let block_hash = rlc(extra.block_hash.to_fixed_bytes(), randomness);
+        let block_hash_cell = region.assign_advice(
+            || "block.hash",
+            self.raw_public_inputs,
             offset,
-            || Value::known(rpi_rlc_acc),
+            || Value::known(block_hash),
+        )?;
+        raw_pi_vals[offset] = block_hash;
+        offset += 1;
+
+        // transactions root
+        let transactions_root = rlc(extra.transactions_root.to_fixed_bytes(), randomness);
+        let transactions_root_cell = region.assign_advice(
+            || "transactions.root",
+            self.raw_public_inputs,
+            offset,
+            || Value::known(transactions_root),
+        )?;
+        raw_pi_vals[offset] = transactions_root;
+        offset += 1;
+
+        // header RLP length
+        let header_rlp_len = F::from(extra.header_rlp.len() as u64);
+        region.assign_advice(
+            || "header_rlp.len",
+            self.raw_public_inputs,
+            offset,
+            || Value::known(header_rlp_len),
+        )?;
+        raw_pi_vals[offset] = header_rlp_len;
+        offset += 1;
+
+        // randomness of the RLC encoding, repeated in the randomness column
+        // down to the last transactions RLP byte
+        self.q_randomness_first.enable(region, offset)?;
+        region.assign_advice(
+            || "randomness",
+            self.raw_public_inputs,
+            offset,
+            || Value::known(randomness),
+        )?;
+        raw_pi_vals[offset] = randomness;
+        for offset in offset..self.circuit_len(max_access_list) {
+            if offset > BLOCK_LEN + 1 + RANDOMNESS_OFFSET {
+                self.q_randomness.enable(region, offset)?;
+            }
+            region.assign_advice(
+                || "randomness",
+                self.randomness,
+                offset,
+                || Value::known(randomness),
+            )?;
+        }
+
+        // the transactions RLP length and its RLC encoding are assigned with the
+        // transactions RLP bytes
+        for offset in BLOCK_LEN + 1..BLOCK_LEN + 1 + BLOCK_HASH_BYTES_OFFSET {
+            let columns = if offset == BLOCK_LEN + 1 + TXS_RLP_LEN_OFFSET {
+                vec![self.header_is_byte, self.header_len_acc]
+            } else {
+                vec![self.header_is_byte, self.header_len_acc, self.bytes_rlc_acc]
+            };
+            for column in columns {
+                region.assign_advice(
+                    || "header rlp padding",
+                    column,
+                    offset,
+                    || Value::known(F::zero()),
+                )?;
+            }
+        }
+
+        // block hash bytes
+        let mut rlc_acc = Value::known(F::zero());
+        let block_hash_accs = field_accs(extra.block_hash.as_bytes(), true, randomness);
+        let mut block_hash_acc_cell = None;
+        for (i, byte) in extra.block_hash.as_bytes().iter().enumerate() {
+            let offset = BLOCK_LEN + 1 + BLOCK_HASH_BYTES_OFFSET + i;
+            let byte = F::from(*byte as u64);
+            rlc_acc = rlc_acc * challenges.evm_word() + Value::known(byte);
+
+            self.q_hash_byte.enable(region, offset)?;
+            if i == 0 {
+                self.q_hash_first.enable(region, offset)?;
+            }
+            self.q_header_field_rev.enable(region, offset)?;
+            if i == 31 {
+                self.q_header_field_first.enable(region, offset)?;
+            }
+            region.assign_advice(
+                || "block.hash byte",
+                self.raw_public_inputs,
+                offset,
+                || Value::known(byte),
+            )?;
+            region.assign_advice(|| "block.hash rlc", self.bytes_rlc_acc, offset, || rlc_acc)?;
+            let acc_cell = region.assign_advice(
+                || "block.hash acc",
+                self.header_acc_rlc,
+                offset,
+                || Value::known(block_hash_accs[i].0),
+            )?;
+            if i == 0 {
+                block_hash_acc_cell = Some(acc_cell);
+            }
+            for column in [self.header_is_byte, self.header_len_acc] {
+                region.assign_advice(
+                    || "header rlp padding",
+                    column,
+                    offset,
+                    || Value::known(F::zero()),
+                )?;
+            }
+            raw_pi_vals[offset] = byte;
+        }
+        region.constrain_equal(
+            block_hash_acc_cell.expect("block hash has 32 bytes").cell(),
+            block_hash_cell.cell(),
+        )?;
+
+        // transactions root bytes
+        let mut rlc_acc = Value::known(F::zero());
+        for (i, byte) in extra.transactions_root.as_bytes().iter().enumerate() {
+            let offset = BLOCK_LEN + 1 + TXS_ROOT_BYTES_OFFSET + i;
+            let byte = F::from(*byte as u64);
+            rlc_acc = rlc_acc * challenges.evm_word() + Value::known(byte);
+
+            self.q_hash_byte.enable(region, offset)?;
+            if i == 0 {
+                self.q_hash_first.enable(region, offset)?;
+            }
+            self.q_txs_root_byte.enable(region, offset)?;
+            region.assign_advice(
+                || "transactions.root byte",
+                self.raw_public_inputs,
+                offset,
+                || Value::known(byte),
+            )?;
+            region.assign_advice(
+                || "transactions.root rlc",
+                self.bytes_rlc_acc,
+                offset,
+                || rlc_acc,
+            )?;
+            for column in [
+                self.header_is_byte,
+                self.header_len_acc,
+                self.header_acc_rlc,
+            ] {
+                region.assign_advice(
+                    || "header rlp padding",
+                    column,
+                    offset,
+                    || Value::known(F::zero()),
+                )?;
+            }
+            raw_pi_vals[offset] = byte;
+        }
+
+        // receipts root bytes, whose RLC encoding is copied to the receipts
+        // rows as the hash of their encoding
+        let mut rlc_acc = Value::known(F::zero());
+        let mut receipts_hash_cell = None;
+        for (i, byte) in extra.receipts_root.as_bytes().iter().enumerate() {
+            let offset = BLOCK_LEN + 1 + RECEIPTS_ROOT_BYTES_OFFSET + i;
+            let byte = F::from(*byte as u64);
+            rlc_acc = rlc_acc * challenges.evm_word() + Value::known(byte);
+
+            self.q_hash_byte.enable(region, offset)?;
+            if i == 0 {
+                self.q_hash_first.enable(region, offset)?;
+            }
+            self.q_receipts_root_byte.enable(region, offset)?;
+            region.assign_advice(
+                || "receipts.root byte",
+                self.raw_public_inputs,
+                offset,
+                || Value::known(byte),
+            )?;
+            receipts_hash_cell = Some(region.assign_advice(
+                || "receipts.root rlc",
+                self.bytes_rlc_acc,
+                offset,
+                || rlc_acc,
+            )?);
+            for column in [
+                self.header_is_byte,
+                self.header_len_acc,
+                self.header_acc_rlc,
+            ] {
+                region.assign_advice(
+                    || "header rlp padding",
+                    column,
+                    offset,
+                    || Value::known(F::zero()),
+                )?;
+            }
+            raw_pi_vals[offset] = byte;
+        }
+
+        // header RLP fields of fixed length and their value cells: the
+        // coinbase is copied from its accumulator in base 256, the hashes from
+        // their RLC accumulated from the last byte, and the logs bloom words
+        // from their RLC accumulated from the first byte
+        let header_offset = BLOCK_LEN + 1 + HEADER_RLP_BYTES_OFFSET;
+        let mut header_accs = vec![(F::zero(), F::zero()); MAX_HEADER_RLP_LEN];
+        let mut header_copies = vec![];
+        let mut fixed_fields = vec![
+            (HEADER_PARENT_HASH_OFFSET, &block_cells[BLOCK_LEN]),
+            (HEADER_COINBASE_OFFSET, &block_cells[1]),
+            (HEADER_STATE_ROOT_OFFSET, &state_root_cell),
+            (HEADER_TXS_ROOT_OFFSET, &transactions_root_cell),
+            (HEADER_RECEIPTS_ROOT_OFFSET, &receipts_root_cell),
+        ];
+        fixed_fields.extend(
+            (0..LOGS_BLOOM_WORDS).map(|i| (HEADER_LOGS_BLOOM_OFFSET + 32 * i, &block_cells[9 + i])),
+        );
+        for (field_offset, value_cell) in fixed_fields {
+            let is_int = field_offset == HEADER_COINBASE_OFFSET;
+            let is_reversed = !is_int && field_offset < HEADER_LOGS_BLOOM_OFFSET;
+            let len = if is_int { 20 } else { 32 };
+            let bytes = &extra.header_rlp[field_offset..field_offset + len];
+            for (i, accs) in field_accs(bytes, is_reversed, randomness)
+                .into_iter()
+                .enumerate()
+            {
+                let offset = header_offset + field_offset + i;
+                if is_reversed {
+                    self.q_header_field_rev.enable(region, offset)?;
+                } else {
+                    self.q_header_field.enable(region, offset)?;
+                }
+                header_accs[field_offset + i] = accs;
+            }
+            let (acc_first, acc_last) = if is_reversed {
+                (field_offset + len - 1, field_offset)
+            } else {
+                (field_offset, field_offset + len - 1)
+            };
+            self.q_header_field_first
+                .enable(region, header_offset + acc_first)?;
+            header_copies.push((acc_last, is_int, value_cell.cell()));
+        }
+        for (offset, byte) in HEADER_CONST_BYTES {
+            self.q_header_const.enable(region, header_offset + offset)?;
+            region.assign_fixed(
+                || "header_rlp const byte",
+                self.header_const,
+                header_offset + offset,
+                || Value::known(F::from(byte as u64)),
+            )?;
+        }
+
+        // header RLP fields of variable length, as (is_prefix, is_short, tag,
+        // rem) for each byte, together with the accumulators of the last byte
+        // of each field
+        let mut tail_rows = vec![];
+        let mut tail_fields = vec![];
+        let mut i = HEADER_TAIL_OFFSET;
+        while i < extra.header_rlp.len() {
+            let prefix = extra.header_rlp[i];
+            let (is_short, len) = if prefix < 0x80 {
+                (true, 0)
+            } else {
+                (false, (prefix - 0x80) as usize)
+            };
+            let tag = tail_fields.len();
+            let prefix_acc = F::from(is_short as u64 * prefix as u64);
+            header_accs[i] = (prefix_acc, prefix_acc);
+            tail_rows.push((true, is_short, tag, len));
+            let payload = &extra.header_rlp[i + 1..i + 1 + len];
+            for (j, (acc_rlc, acc_int)) in field_accs(payload, false, randomness)
+                .into_iter()
+                .enumerate()
+            {
+                header_accs[i + 1 + j] = (acc_rlc, acc_int);
+                tail_rows.push((false, false, tag, len - j - 1));
+            }
+            tail_fields.push(header_accs[i + len]);
+            i += 1 + len;
+        }
+        assert!((HEADER_TAIL_FIELDS - 1..=HEADER_TAIL_FIELDS).contains(&tail_fields.len()));
+
+        // header RLP bytes
+        let mut rlc_acc = Value::known(F::zero());
+        let mut header_acc_cells = vec![];
+        for i in 0..MAX_HEADER_RLP_LEN {
+            let offset = header_offset + i;
+            let is_byte = i < extra.header_rlp.len();
+            let byte = F::from(extra.header_rlp.get(i).copied().unwrap_or_default() as u64);
+            if is_byte {
+                rlc_acc = rlc_acc * challenges.keccak_input() + Value::known(byte);
+            }
+
+            self.q_header_byte.enable(region, offset)?;
+            if i == 0 {
+                self.q_header_first.enable(region, offset)?;
+            }
+            if i == MAX_HEADER_RLP_LEN - 1 {
+                self.q_header_last.enable(region, offset)?;
+            }
+            if i == HEADER_TAIL_OFFSET {
+                self.q_header_tail_first.enable(region, offset)?;
+            } else if i > HEADER_TAIL_OFFSET {
+                self.q_header_tail.enable(region, offset)?;
+            }
+            region.assign_advice(
+                || "header_rlp byte",
+                self.raw_public_inputs,
+                offset,
+                || Value::known(byte),
+            )?;
+            region.assign_advice(
+                || "header_rlp is_byte",
+                self.header_is_byte,
+                offset,
+                || Value::known(F::from(is_byte as u64)),
+            )?;
+            region.assign_advice(
+                || "header_rlp len",
+                self.header_len_acc,
+                offset,
+                || Value::known(F::from((i + 1).min(extra.header_rlp.len()) as u64)),
+            )?;
+            region.assign_advice(|| "header_rlp rlc", self.bytes_rlc_acc, offset, || rlc_acc)?;
+            raw_pi_vals[offset] = byte;
+
+            let (acc_rlc, acc_int) = header_accs[i];
+            header_acc_cells.push((
+                region.assign_advice(
+                    || "header_rlp acc_rlc",
+                    self.header_acc_rlc,
+                    offset,
+                    || Value::known(acc_rlc),
+                )?,
+                region.assign_advice(
+                    || "header_rlp acc_int",
+                    self.header_acc_int,
+                    offset,
+                    || Value::known(acc_int),
+                )?,
+            ));
+            let (is_prefix, is_short, tag, rem) = i
+                .checked_sub(HEADER_TAIL_OFFSET)
+                .and_then(|j| tail_rows.get(j).copied())
+                .unwrap_or_default();
+            for (name, column, value) in [
+                (
+                    "header_rlp is_prefix",
+                    self.header_is_prefix,
+                    is_prefix as usize,
+                ),
+                (
+                    "header_rlp is_short",
+                    self.header_is_short,
+                    is_short as usize,
+                ),
+                ("header_rlp tag", self.header_tail_tag, tag),
+                ("header_rlp rem", self.header_tail_rem, rem),
+            ] {
+                region.assign_advice(
+                    || name,
+                    column,
+                    offset,
+                    || Value::known(F::from(value as u64)),
+                )?;
+            }
+        }
+        for column in [self.header_is_byte, self.header_is_prefix] {
+            region.assign_advice(
+                || "header rlp padding",
+                column,
+                header_offset + MAX_HEADER_RLP_LEN,
+                || Value::known(F::zero()),
+            )?;
+        }
+        for (i, is_int, value_cell) in header_copies {
+            let (acc_rlc_cell, acc_int_cell) = &header_acc_cells[i];
+            let acc_cell = if is_int { acc_int_cell } else { acc_rlc_cell };
+            region.constrain_equal(acc_cell.cell(), value_cell)?;
+        }
+
+        // The accumulators of the fields of variable length are in the first rows
+        // after the zero row of the block table, tagged by the field, from where
+        // the ones of the difficulty, number, gas limit, timestamp and base fee
+        // are copied to their cells in the block table.
+        let mut tail_field_cells = vec![];
+        for (tag, (acc_rlc, acc_int)) in tail_fields.into_iter().enumerate() {
+            let offset = 1 + tag;
+            region.assign_fixed(
+                || "header field tag",
+                self.header_field_tag,
+                offset,
+                || Value::known(F::from((tag + 1) as u64)),
+            )?;
+            tail_field_cells.push((
+                region.assign_advice(
+                    || "header field acc_rlc",
+                    self.header_acc_rlc,
+                    offset,
+                    || Value::known(acc_rlc),
+                )?,
+                region.assign_advice(
+                    || "header field acc_int",
+                    self.header_acc_int,
+                    offset,
+                    || Value::known(acc_int),
+                )?,
+            ));
+        }
+        for (tag, is_int, block_offset) in [
+            (0, false, 5), // difficulty
+            (1, true, 3),  // number
+            (2, true, 2),  // gas limit
+            (4, true, 4),  // timestamp
+            (8, false, 6), // base fee
+        ] {
+            let (acc_rlc_cell, acc_int_cell) = &tail_field_cells[tag];
+            let acc_cell = if is_int { acc_int_cell } else { acc_rlc_cell };
+            region.constrain_equal(acc_cell.cell(), block_cells[block_offset].cell())?;
+        }
+
+        Ok([
+            state_root_cell,
+            prev_state_root_cell,
+            receipts_root_cell,
+            block_hash_cell,
+            transactions_root_cell,
+            receipts_hash_cell.expect("receipts root has 32 bytes"),
+        ])
+    }
+
+    /// Assigns the transactions RLP bytes, decoded in the rows of the
+    /// "transactions rlp" gate, between two padding rows queried by the gates,
+    /// and their length and RLC encoding in the extra values, copied from the
+    /// last row.
+    #[allow(clippy::too_many_arguments)]
+    fn assign_txs_rlp(
+        &self,
+        region: &mut Region<'_, F>,
+        txs_rlp: &[u8],
+        max_access_list: usize,
+        randomness: F,
+        challenges: &Challenges<Value<F>>,
+        raw_pi_vals: &mut [F],
+    ) -> Result<(), Error> {
+        let txs_rlp_len = txs_rlp_len(self.max_txs, self.max_calldata, max_access_list);
+        let txs_rlp_offset = self.circuit_len(max_access_list) - txs_rlp_len;
+
+        let padding = TxRlpRow::default();
+        let mut last_cells = self.assign_txs_rlp_row(
+            region,
+            txs_rlp_offset - 1,
+            &padding,
+            false,
+            0,
+            Value::known(F::zero()),
+        )?;
+        self.assign_txs_rlp_row(
+            region,
+            txs_rlp_offset + txs_rlp_len,
+            &padding,
+            false,
+            0,
+            Value::known(F::zero()),
+        )?;
+
+        let rows = txs_rlp_rows(txs_rlp, randomness);
+        let mut rlc_acc = Value::known(F::zero());
+        for i in 0..txs_rlp_len {
+            let offset = txs_rlp_offset + i;
+            let is_byte = i < txs_rlp.len();
+            let byte = F::from(txs_rlp.get(i).copied().unwrap_or_default() as u64);
+            if is_byte {
+                rlc_acc = rlc_acc * challenges.keccak_input() + Value::known(byte);
+            }
+
+            if i == 0 {
+                self.q_txs_rlp_first.enable(region, offset)?;
+            } else {
+                self.q_txs_rlp.enable(region, offset)?;
+            }
+            region.assign_advice(
+                || "txs_rlp byte",
+                self.raw_public_inputs,
+                offset,
+                || Value::known(byte),
+            )?;
+            raw_pi_vals[offset] = byte;
+            last_cells = self.assign_txs_rlp_row(
+                region,
+                offset,
+                rows.get(i).unwrap_or(&padding),
+                is_byte,
+                (i + 1).min(txs_rlp.len()),
+                rlc_acc,
+            )?;
+        }
+
+        // transactions RLP length and RLC encoding, looked up in the keccak
+        // table with the transactions root
+        let offset = BLOCK_LEN + 1 + TXS_RLP_LEN_OFFSET;
+        let txs_rlp_len = F::from(txs_rlp.len() as u64);
+        self.q_txs_rlp_len.enable(region, offset)?;
+        let len_cell = region.assign_advice(
+            || "txs_rlp.len",
+            self.raw_public_inputs,
+            offset,
+            || Value::known(txs_rlp_len),
+        )?;
+        raw_pi_vals[offset] = txs_rlp_len;
+        let rlc_cell =
+            region.assign_advice(|| "txs_rlp rlc", self.bytes_rlc_acc, offset, || rlc_acc)?;
+        region.constrain_equal(len_cell.cell(), last_cells.0.cell())?;
+        region.constrain_equal(rlc_cell.cell(), last_cells.1.cell())?;
+
+        Ok(())
+    }
+
+    /// Assigns a row of the transactions RLP bytes, and returns the cells of
+    /// the length and of the RLC encoding accumulated up to the row
+    #[allow(clippy::type_complexity)]
+    fn assign_txs_rlp_row(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        row: &TxRlpRow<F>,
+        is_byte: bool,
+        len: usize,
+        rlc_acc: Value<F>,
+    ) -> Result<(AssignedCell<F, F>, AssignedCell<F, F>), Error> {
+        let kind = row.field.map_or(0, |field| field.kind());
+        let tag = row.field.map_or(TxFieldTag::Null, |field| field.tag());
+        let len_cell = region.assign_advice(
+            || "txs_rlp len",
+            self.header_len_acc,
+            offset,
+            || Value::known(F::from(len as u64)),
+        )?;
+        for (name, column, value) in [
+            ("txs_rlp is_byte", self.header_is_byte, is_byte as u64),
+            ("txs_rlp tx_id", self.txs_rlp_tx_id, row.tx_id as u64),
+            ("txs_rlp tx_type", self.txs_rlp_tx_type, row.tx_type as u64),
+            ("txs_rlp is_type", self.txs_rlp_is_type, row.is_type as u64),
+            (
+                "txs_rlp is_list_prefix",
+                self.txs_rlp_is_list_prefix,
+                row.is_list_prefix as u64,
+            ),
+            (
+                "txs_rlp is_list_len",
+                self.txs_rlp_is_list_len,
+                row.is_list_len as u64,
+            ),
+            (
+                "txs_rlp is_prefix",
+                self.header_is_prefix,
+                row.is_prefix as u64,
+            ),
+            (
+                "txs_rlp is_str_len",
+                self.txs_rlp_is_str_len,
+                row.is_str_len as u64,
+            ),
+            (
+                "txs_rlp is_short",
+                self.header_is_short,
+                row.is_short as u64,
+            ),
+            ("txs_rlp is_long", self.txs_rlp_is_long, row.is_long as u64),
+            (
+                "txs_rlp field",
+                self.header_tail_tag,
+                row.field_index as u64,
+            ),
+            ("txs_rlp rem", self.header_tail_rem, row.rem as u64),
+            ("txs_rlp index", self.txs_rlp_index, row.index as u64),
+            (
+                "txs_rlp field_end",
+                self.txs_rlp_field_end,
+                row.field_end as u64,
+            ),
+            ("txs_rlp tag", self.txs_rlp_tag, tag as u64),
+            ("txs_rlp is_rlc", self.txs_rlp_is_rlc, (kind == 1) as u64),
+            ("txs_rlp is_to", self.txs_rlp_is_to, (kind == 2) as u64),
+            ("txs_rlp is_data", self.txs_rlp_is_data, (kind == 3) as u64),
+            (
+                "txs_rlp is_access_list",
+                self.txs_rlp_is_access_list,
+                (kind == 4) as u64,
+            ),
+            (
+                "txs_rlp is_last_field",
+                self.txs_rlp_is_last_field,
+                row.is_last_field as u64,
+            ),
+            (
+                "txs_rlp al_is_list_prefix",
+                self.txs_rlp_al_is_list_prefix,
+                row.al_is_list_prefix as u64,
+            ),
+            (
+                "txs_rlp al_is_list_len",
+                self.txs_rlp_al_is_list_len,
+                row.al_is_list_len as u64,
+            ),
+            (
+                "txs_rlp al_is_elem_prefix",
+                self.txs_rlp_al_is_elem_prefix,
+                row.al_is_elem_prefix as u64,
+            ),
+            (
+                "txs_rlp al_is_elem",
+                self.txs_rlp_al_is_elem,
+                row.al_is_elem as u64,
+            ),
+            (
+                "txs_rlp al_is_elem_last",
+                self.txs_rlp_al_is_elem_last,
+                row.al_is_elem_last as u64,
+            ),
+            (
+                "txs_rlp al_is_keys",
+                self.txs_rlp_al_is_keys,
+                row.al_is_keys as u64,
+            ),
+            ("txs_rlp al_rem", self.txs_rlp_al_rem, row.al_rem as u64),
+            (
+                "txs_rlp al_item_rem",
+                self.txs_rlp_al_item_rem,
+                row.al_item_rem as u64,
+            ),
+            (
+                "txs_rlp al_keys_rem",
+                self.txs_rlp_al_keys_rem,
+                row.al_keys_rem as u64,
+            ),
+            (
+                "txs_rlp al_keys_count",
+                self.txs_rlp_al_keys_count,
+                row.al_keys_count as u64,
+            ),
+            (
+                "txs_rlp al_addresses_len",
+                self.txs_rlp_al_addresses_len,
+                row.al_addresses_len as u64,
+            ),
+        ] {
+            region.assign_advice(|| name, column, offset, || Value::known(F::from(value)))?;
+        }
+        let rlc_cell =
+            region.assign_advice(|| "txs_rlp rlc", self.bytes_rlc_acc, offset, || rlc_acc)?;
+        for (name, column, value) in [
+            ("txs_rlp acc_rlc", self.header_acc_rlc, row.acc_rlc),
+            ("txs_rlp acc_int", self.header_acc_int, row.acc_int),
+            (
+                "txs_rlp al_address",
+                self.txs_rlp_al_address,
+                row.al_address,
+            ),
+        ] {
+            region.assign_advice(|| name, column, offset, || Value::known(value))?;
+        }
+
+        Ok((len_cell, rlc_cell))
+    }
+
+    /// Assign `rpi_rlc_acc` and `rand_rpi` columns
+    #[allow(clippy::type_complexity)]
+    fn assign_rlc_pi(
+        &self,
+        region: &mut Region<'_, F>,
+        max_access_list: usize,
+        rand_rpi: F,
+        raw_pi_vals: Vec<F>,
+    ) -> Result<(AssignedCell<F, F>, AssignedCell<F, F>), Error> {
+        let circuit_len = self.circuit_len(max_access_list);
+        assert_eq!(circuit_len, raw_pi_vals.len());
+
+        // Last row
+        let offset = circuit_len - 1;
+        let mut rpi_rlc_acc = raw_pi_vals[offset];
+        region.assign_advice(
+            || "rpi_rlc_acc",
+            self.rpi_rlc_acc,
+            offset,
+            || Value::known(rpi_rlc_acc),
         )?;
         region.assign_advice(
             || "rand_rpi",
@@ -1088,6 +3510,8 @@ impl<F: Field> PiCircuitConfig<F> {
 pub struct PiCircuit<F: Field> {
     max_txs: usize,
     max_calldata: usize,
+    max_access_list: usize,
+    max_receipts_len: usize,
     /// Randomness for RLC encdoing
     pub randomness: F,
     /// Randomness for PI encoding
@@ -1101,6 +3525,8 @@ impl<F: Field> PiCircuit<F> {
     pub fn new(
         max_txs: usize,
         max_calldata: usize,
+        max_access_list: usize,
+        max_receipts_len: usize,
         randomness: impl Into<F>,
         rand_rpi: impl Into<F>,
         public_data: PublicData,
@@ -1108,6 +3534,8 @@ impl<F: Field> PiCircuit<F> {
         Self {
             max_txs,
             max_calldata,
+            max_access_list,
+            max_receipts_len,
             randomness: randomness.into(),
             rand_rpi: rand_rpi.into(),
             public_data,
@@ -1119,37 +3547,33 @@ impl<F: Field> SubCircuit<F> for PiCircuit<F> {
     type Config = PiCircuitConfig<F>;
 
     fn new_from_block(block: &witness::Block<F>) -> Self {
-        let public_data = PublicData {
-            chain_id: block.context.chain_id,
-            history_hashes: block.context.history_hashes.clone(),
-            transactions: block.eth_block.transactions.clone(),
-            state_root: block.eth_block.state_root,
-            prev_state_root: H256::from_uint(&block.prev_state_root),
-            block_constants: BlockConstants {
-                coinbase: block.context.coinbase,
-                timestamp: block.context.timestamp,
-                number: block.context.number.as_u64().into(),
-                difficulty: block.context.difficulty,
-                gas_limit: block.context.gas_limit.into(),
-                base_fee: block.context.base_fee,
-            },
-        };
         PiCircuit::new(
             block.circuits_params.max_txs,
             block.circuits_params.max_calldata,
+            block.circuits_params.max_access_list,
+            block.circuits_params.max_receipts_len,
             block.randomness,
             block.randomness + F::from_u128(1),
-            public_data,
+            PublicData::new(block),
         )
     }
 
     /// Return the minimum number of rows required to prove the block
     fn min_num_rows_block(block: &witness::Block<F>) -> usize {
-        BLOCK_LEN
+        let max_txs = block.circuits_params.max_txs;
+        let max_calldata = block.circuits_params.max_calldata;
+        let max_access_list = block.circuits_params.max_access_list;
+        let max_receipts_len = block.circuits_params.max_receipts_len;
+        // +1 padding row after the transactions RLP bytes, and +3 padding and
+        // last rows around the receipts bytes, which are in their own columns
+        (BLOCK_LEN
             + 1
             + EXTRA_LEN
-            + 3 * (TX_LEN * block.circuits_params.max_txs + 1)
-            + block.circuits_params.max_calldata
+            + 3 * (TX_LEN * max_txs + 1)
+            + max_calldata
+            + txs_rlp_len(max_txs, max_calldata, max_access_list)
+            + 1)
+        .max(max_receipts_len + 3)
     }
 
     /// Compute the public inputs for this circuit.
@@ -1157,12 +3581,18 @@ impl<F: Field> SubCircuit<F> for PiCircuit<F> {
         let rlc_rpi_col = raw_public_inputs_col::<F>(
             self.max_txs,
             self.max_calldata,
+            self.max_access_list,
             &self.public_data,
             self.randomness,
         );
         assert_eq!(
             rlc_rpi_col.len(),
-            BLOCK_LEN + 1 + EXTRA_LEN + 3 * (TX_LEN * self.max_txs + 1) + self.max_calldata
+            BLOCK_LEN
+                + 1
+                + EXTRA_LEN
+                + 3 * (TX_LEN * self.max_txs + 1)
+                + self.max_calldata
+                + txs_rlp_len(self.max_txs, self.max_calldata, self.max_access_list)
         );
 
         // Computation of raw_pulic_inputs
@@ -1171,11 +3601,6 @@ impl<F: Field> SubCircuit<F> for PiCircuit<F> {
             .rev()
             .fold(F::zero(), |acc, val| acc * self.rand_rpi + val);
 
-        // let block_hash = public_data
-        //     .eth_block
-        //     .hash
-        //     .unwrap_or_else(H256::zero)
-        //     .to_fixed_bytes();
         let public_inputs = vec![
             self.rand_rpi,
             rlc_rpi,
@@ -1188,6 +3613,18 @@ impl<F: Field> SubCircuit<F> for PiCircuit<F> {
                 self.public_data.prev_state_root.to_fixed_bytes(),
                 self.randomness,
             ),
+            rlc(
+                self.public_data.receipts_root.to_fixed_bytes(),
+                self.randomness,
+            ),
+            rlc(
+                self.public_data.block_hash().to_fixed_bytes(),
+                self.randomness,
+            ),
+            rlc(
+                self.public_data.transactions_root.to_fixed_bytes(),
+                self.randomness,
+            ),
         ];
 
         vec![public_inputs]
@@ -1197,9 +3634,56 @@ impl<F: Field> SubCircuit<F> for PiCircuit<F> {
     fn synthesize_sub(
         &self,
         config: &Self::Config,
-        _challenges: &Challenges<Value<F>>,
+        challenges: &Challenges<Value<F>>,
         layouter: &mut impl Layouter<F>,
     ) -> Result<(), Error> {
+        let access_list_len: usize = self
+            .public_data
+            .txs()
+            .iter()
+            .map(|tx| tx.access_list_entries().len())
+            .sum();
+        if access_list_len > self.max_access_list {
+            error!(
+                "access list entries = {} > max_access_list = {}",
+                access_list_len, self.max_access_list
+            );
+            return Err(Error::Synthesis);
+        }
+        let extra_data_len = self.public_data.extra_data.len();
+        if extra_data_len > MAX_EXTRA_DATA_LEN {
+            error!(
+                "extra data length = {} > max extra data length = {}",
+                extra_data_len, MAX_EXTRA_DATA_LEN
+            );
+            return Err(Error::Synthesis);
+        }
+        let header_rlp_len = self.public_data.header_rlp().len();
+        if header_rlp_len > MAX_HEADER_RLP_LEN {
+            error!(
+                "header rlp length = {} > max header rlp length = {}",
+                header_rlp_len, MAX_HEADER_RLP_LEN
+            );
+            return Err(Error::Synthesis);
+        }
+        let rlp_len = self.public_data.txs_rlp().len();
+        let max_rlp_len = txs_rlp_len(self.max_txs, self.max_calldata, self.max_access_list);
+        if rlp_len > max_rlp_len {
+            error!(
+                "transactions rlp length = {} > max transactions rlp length = {}",
+                rlp_len, max_rlp_len
+            );
+            return Err(Error::Synthesis);
+        }
+        let receipts_len = self.public_data.receipts_bytes().len();
+        if receipts_len > self.max_receipts_len {
+            error!(
+                "receipts length = {} > max receipts length = {}",
+                receipts_len, self.max_receipts_len
+            );
+            return Err(Error::Synthesis);
+        }
+
         layouter.assign_region(
             || "fixed u16 table",
             |mut region| {
@@ -1215,29 +3699,81 @@ impl<F: Field> SubCircuit<F> for PiCircuit<F> {
                 Ok(())
             },
         )?;
+        layouter.assign_region(
+            || "transactions rlp fields table",
+            |mut region| {
+                // all-zero row for the disabled lookups
+                for column in config.txs_rlp_fields {
+                    region.assign_fixed(
+                        || "transactions rlp fields zero row",
+                        column,
+                        0,
+                        || Value::known(F::zero()),
+                    )?;
+                }
+                let mut offset = 1;
+                for (tx_type, fields) in TX_RLP_FIELDS.iter().enumerate() {
+                    for (field_index, field) in fields.iter().enumerate() {
+                        for (column, value) in config.txs_rlp_fields.into_iter().zip([
+                            tx_type as u64,
+                            field_index as u64 + 1,
+                            field.tag() as u64,
+                            field.kind(),
+                            (field_index == fields.len() - 1) as u64,
+                        ]) {
+                            region.assign_fixed(
+                                || format!("transactions rlp field {}", offset),
+                                column,
+                                offset,
+                                || Value::known(F::from(value)),
+                            )?;
+                        }
+                        offset += 1;
+                    }
+                }
+
+                Ok(())
+            },
+        )?;
+        let mut receipts_hash_cell = None;
         let pi_cells = layouter.assign_region(
             || "region 0",
             |mut region| {
-                let circuit_len = config.circuit_len();
+                let circuit_len = config.circuit_len(self.max_access_list);
                 let mut raw_pi_vals = vec![F::zero(); circuit_len];
 
                 // Assign block table
                 let block_values = self.public_data.get_block_table_values();
-                let chain_id = config.assign_block_table(
+                let block_cells = config.assign_block_table(
                     &mut region,
                     block_values,
                     self.randomness,
                     &mut raw_pi_vals,
                 )?;
+                // chain_id is after the zero row, coinbase, gas_limit, number,
+                // timestamp, difficulty and base_fee
+                let chain_id = block_cells[7].clone();
 
                 // Assign extra fields
                 let extra_vals = self.public_data.get_extra_values();
-                let [state_root, prev_state_root] = config.assign_extra_fields(
+                let txs_rlp = extra_vals.txs_rlp.clone();
+                let [
+                    state_root,
+                    prev_state_root,
+                    receipts_root,
+                    block_hash,
+                    transactions_root,
+                    receipts_hash,
+                ] = config.assign_extra_fields(
                     &mut region,
                     extra_vals,
+                    &block_cells,
+                    self.max_access_list,
                     self.randomness,
+                    challenges,
                     &mut raw_pi_vals,
                 )?;
+                receipts_hash_cell = Some(receipts_hash);
 
                 let mut offset = 0;
                 // Assign Tx table
@@ -1270,6 +3806,14 @@ impl<F: Field> SubCircuit<F> for PiCircuit<F> {
                             TxFieldTag::GasPrice,
                             rlc(tx.gas_price.to_le_bytes(), self.randomness),
                         ),
+                        (
+                            TxFieldTag::GasFeeCap,
+                            rlc(tx.gas_fee_cap.to_le_bytes(), self.randomness),
+                        ),
+                        (
+                            TxFieldTag::GasTipCap,
+                            rlc(tx.gas_tip_cap.to_le_bytes(), self.randomness),
+                        ),
                         (
                             TxFieldTag::CallerAddress,
                             tx.from_addr.to_scalar().expect("tx.from too big"),
@@ -1285,6 +3829,14 @@ impl<F: Field> SubCircuit<F> for PiCircuit<F> {
                         ),
                         (TxFieldTag::CallDataLength, F::from(tx.call_data_len)),
                         (TxFieldTag::CallDataGasCost, F::from(tx.call_data_gas_cost)),
+                        (
+                            TxFieldTag::AccessListAddressesLen,
+                            F::from(tx.access_list_addresses_len),
+                        ),
+                        (
+                            TxFieldTag::AccessListStorageKeysLen,
+                            F::from(tx.access_list_storage_keys_len),
+                        ),
                         (
                             TxFieldTag::TxSignHash,
                             rlc(tx.tx_sign_hash, self.randomness),
@@ -1367,9 +3919,70 @@ impl<F: Field> SubCircuit<F> for PiCircuit<F> {
                 let tx_table_len = TX_LEN * self.max_txs + 1;
                 config.assign_tx_empty_row(&mut region, tx_table_len + offset)?;
 
+                // Tx table access lists, which aren't public inputs but are
+                // looked up by the transactions RLP
+                offset += tx_table_len + 1;
+                let mut access_list_count = 0;
+                for (i, tx) in txs.iter().enumerate() {
+                    for (index, (address, key)) in tx.access_list_entries().into_iter().enumerate()
+                    {
+                        for (tag, value) in [
+                            (
+                                TxFieldTag::AccessListAddress,
+                                address.to_scalar().expect("address too big"),
+                            ),
+                            (
+                                TxFieldTag::AccessListStorageKey,
+                                rlc(key.unwrap_or_default().to_le_bytes(), self.randomness),
+                            ),
+                        ] {
+                            config.assign_tx_access_list_row(
+                                &mut region,
+                                offset,
+                                i + 1,
+                                tag,
+                                index,
+                                value,
+                            )?;
+                            offset += 1;
+                        }
+                        access_list_count += 1;
+                    }
+                }
+                for _ in access_list_count..self.max_access_list {
+                    for tag in [
+                        TxFieldTag::AccessListAddress,
+                        TxFieldTag::AccessListStorageKey,
+                    ] {
+                        config.assign_tx_access_list_row(
+                            &mut region,
+                            offset,
+                            0, // tx_id
+                            tag,
+                            0,
+                            F::zero(),
+                        )?;
+                        offset += 1;
+                    }
+                }
+
+                // Assign transactions RLP
+                config.assign_txs_rlp(
+                    &mut region,
+                    &txs_rlp,
+                    self.max_access_list,
+                    self.randomness,
+                    challenges,
+                    &mut raw_pi_vals,
+                )?;
+
                 // rpi_rlc and rand_rpi cols
-                let (rpi_rand, rpi_rlc) =
-                    config.assign_rlc_pi(&mut region, self.rand_rpi, raw_pi_vals)?;
+                let (rpi_rand, rpi_rlc) = config.assign_rlc_pi(
+                    &mut region,
+                    self.max_access_list,
+                    self.rand_rpi,
+                    raw_pi_vals,
+                )?;
 
                 Ok(vec![
                     rpi_rand,
@@ -1377,6 +3990,9 @@ impl<F: Field> SubCircuit<F> for PiCircuit<F> {
                     chain_id,
                     state_root,
                     prev_state_root,
+                    receipts_root,
+                    block_hash,
+                    transactions_root,
                 ])
             },
         )?;
@@ -1386,6 +4002,15 @@ impl<F: Field> SubCircuit<F> for PiCircuit<F> {
             layouter.constrain_instance(pi_cell.cell(), config.pi, i)?;
         }
 
+        // Assign the receipts encoding, whose hash is the receipts root
+        config.receipts.assign(
+            layouter,
+            &self.public_data.receipts,
+            self.max_receipts_len,
+            challenges,
+            &receipts_hash_cell.expect("receipts root is assigned"),
+        )?;
+
         Ok(())
     }
 }
@@ -1417,6 +4042,10 @@ impl<F: Field, const MAX_TXS: usize, const MAX_CALLDATA: usize> Circuit<F>
     fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
         let block_table = BlockTable::construct(meta);
         let tx_table = TxTable::construct(meta);
+        let rw_table = RwTable::construct(meta);
+        let keccak_table = KeccakTable::construct(meta);
+        let challenges = Challenges::construct(meta);
+        let challenge_exprs = challenges.exprs(meta);
         (
             PiCircuitConfig::new(
                 meta,
@@ -1425,9 +4054,12 @@ impl<F: Field, const MAX_TXS: usize, const MAX_CALLDATA: usize> Circuit<F>
                     max_calldata: MAX_CALLDATA,
                     block_table,
                     tx_table,
+                    rw_table,
+                    keccak_table,
+                    challenges: challenge_exprs,
                 },
             ),
-            Challenges::construct(meta),
+            challenges,
         )
     }
 
@@ -1437,14 +4069,352 @@ impl<F: Field, const MAX_TXS: usize, const MAX_CALLDATA: usize> Circuit<F>
         mut layouter: impl Layouter<F>,
     ) -> Result<(), Error> {
         let challenges = challenges.values(&mut layouter);
+        let rws = receipts::receipts_rws(&self.0.public_data.receipts);
+        config
+            .rw_table
+            .load(&mut layouter, &rws, rws.len() + 1, &challenges)?;
+        config.keccak_table.dev_load(
+            &mut layouter,
+            &[
+                self.0.public_data.header_rlp(),
+                self.0.public_data.txs_rlp(),
+                self.0.public_data.receipts_bytes(),
+            ],
+            &challenges,
+        )?;
         self.0.synthesize_sub(&config, &challenges, &mut layouter)
     }
 }
 
+/// Row of the transactions RLP bytes, as in the "transactions rlp" gate
+#[derive(Clone, Copy, Debug, Default)]
+struct TxRlpRow<F> {
+    tx_id: usize,
+    tx_type: usize,
+    is_type: bool,
+    is_list_prefix: bool,
+    is_list_len: bool,
+    is_prefix: bool,
+    is_str_len: bool,
+    is_short: bool,
+    is_long: bool,
+    field_index: usize,
+    field: Option<TxRlpField>,
+    is_last_field: bool,
+    rem: usize,
+    index: usize,
+    acc_rlc: F,
+    acc_int: F,
+    field_end: bool,
+    al_is_list_prefix: bool,
+    al_is_list_len: bool,
+    al_is_elem_prefix: bool,
+    al_is_elem: bool,
+    al_is_elem_last: bool,
+    al_is_keys: bool,
+    al_rem: usize,
+    al_item_rem: usize,
+    al_keys_rem: usize,
+    al_keys_count: usize,
+    al_addresses_len: usize,
+    al_address: F,
+}
+
+/// Decodes the transactions RLP bytes in the rows of the "transactions rlp"
+/// gate, with the accumulators of the RLC encoding with `randomness`.
+fn txs_rlp_rows<F: Field>(txs_rlp: &[u8], randomness: F) -> Vec<TxRlpRow<F>> {
+    let mut rows = vec![];
+    let mut i = 0;
+    let mut tx_id = 0;
+    while i < txs_rlp.len() {
+        tx_id += 1;
+        let tx_type = if txs_rlp[i] < 0xc0 {
+            txs_rlp[i] as usize
+        } else {
+            0
+        };
+        let tx = TxRlpRow {
+            tx_id,
+            tx_type,
+            ..Default::default()
+        };
+        if tx_type != 0 {
+            rows.push(TxRlpRow {
+                is_type: true,
+                ..tx
+            });
+            i += 1;
+        }
+
+        // list prefix, followed by the length of the list when it's long
+        let len_len = txs_rlp[i].saturating_sub(0xf7) as usize;
+        rows.push(TxRlpRow {
+            is_list_prefix: true,
+            is_long: len_len > 0,
+            rem: len_len,
+            ..tx
+        });
+        for (j, (_, acc_int)) in field_accs(&txs_rlp[i + 1..i + 1 + len_len], false, randomness)
+            .into_iter()
+            .enumerate()
+        {
+            rows.push(TxRlpRow {
+                is_list_len: true,
+                rem: len_len - j - 1,
+                acc_int,
+                ..tx
+            });
+        }
+        i += 1 + len_len;
+
+        // fields, each one a prefix followed by the length of the field when
+        // it's long and by the field payload
+        let fields = TX_RLP_FIELDS[tx_type];
+        for (field_index, field) in fields.iter().enumerate() {
+            let row = TxRlpRow {
+                field_index,
+                field: Some(*field),
+                is_last_field: field_index == fields.len() - 1,
+                ..tx
+            };
+            let prefix = txs_rlp[i];
+            let (is_short, len_len, len) = if *field == TxRlpField::AccessList {
+                let (len_len, len) = rlp_list_len(&txs_rlp[i..]);
+                (false, len_len, len)
+            } else if prefix < 0x80 {
+                (true, 0, 0)
+            } else if prefix < 0xb8 {
+                (false, 0, (prefix - 0x80) as usize)
+            } else {
+                let len_len = (prefix - 0xb7) as usize;
+                let len = txs_rlp[i + 1..i + 1 + len_len]
+                    .iter()
+                    .fold(0, |len, byte| len * 256 + *byte as usize);
+                (false, len_len, len)
+            };
+            // the payload of an access list is decoded in its own rows, and the
+            // number of its addresses is in all the rows of the field
+            let payload = &txs_rlp[i + 1 + len_len..i + 1 + len_len + len];
+            let al_rows = if *field == TxRlpField::AccessList {
+                access_list_rows(payload, row, randomness)
+            } else {
+                vec![]
+            };
+            let row = TxRlpRow {
+                al_addresses_len: al_rows.first().map_or(0, |row| row.al_addresses_len),
+                ..row
+            };
+            let prefix_acc = F::from(is_short as u64 * prefix as u64);
+            rows.push(TxRlpRow {
+                is_prefix: true,
+                is_short,
+                is_long: len_len > 0,
+                rem: if len_len > 0 { len_len } else { len },
+                index: is_short as usize,
+                acc_rlc: prefix_acc,
+                acc_int: prefix_acc,
+                ..row
+            });
+            for (j, (_, acc_int)) in field_accs(&txs_rlp[i + 1..i + 1 + len_len], false, randomness)
+                .into_iter()
+                .enumerate()
+            {
+                rows.push(TxRlpRow {
+                    is_str_len: true,
+                    rem: len_len - j - 1,
+                    acc_int,
+                    ..row
+                });
+            }
+            i += 1 + len_len;
+            if *field == TxRlpField::AccessList {
+                rows.extend(al_rows);
+            } else {
+                for (j, (acc_rlc, acc_int)) in field_accs(payload, false, randomness)
+                    .into_iter()
+                    .enumerate()
+                {
+                    rows.push(TxRlpRow {
+                        rem: len - j - 1,
+                        index: j + 1,
+                        acc_rlc,
+                        acc_int,
+                        ..row
+                    });
+                }
+            }
+            i += len;
+            rows.last_mut().expect("a field has a prefix").field_end = true;
+        }
+    }
+    rows
+}
+
+/// Returns the number of bytes of the length of the RLP list at the start of
+/// `bytes`, and the length of its payload.
+fn rlp_list_len(bytes: &[u8]) -> (usize, usize) {
+    if bytes[0] < 0xf8 {
+        (0, (bytes[0] - 0xc0) as usize)
+    } else {
+        let len_len = (bytes[0] - 0xf7) as usize;
+        let len = bytes[1..1 + len_len]
+            .iter()
+            .fold(0, |len, byte| len * 256 + *byte as usize);
+        (len_len, len)
+    }
+}
+
+/// Decodes the payload of an access list in the rows of the "transactions
+/// rlp: access lists" gate, from the `row` of its field.
+fn access_list_rows<F: Field>(payload: &[u8], row: TxRlpRow<F>, randomness: F) -> Vec<TxRlpRow<F>> {
+    // rows of the prefix and of the length of a list, with the length of the
+    // list accumulated up to each row
+    fn list_rows<F: Field>(bytes: &[u8], row: TxRlpRow<F>) -> Vec<(TxRlpRow<F>, usize)> {
+        let (len_len, len) = rlp_list_len(bytes);
+        let mut rows = vec![(
+            TxRlpRow {
+                is_long: len_len > 0,
+                al_is_list_prefix: true,
+                al_rem: len_len,
+                ..row
+            },
+            if len_len > 0 { 0 } else { len },
+        )];
+        let mut acc = 0;
+        for (j, byte) in bytes[1..1 + len_len].iter().enumerate() {
+            acc = acc * 256 + *byte as usize;
+            rows.push((
+                TxRlpRow {
+                    al_is_list_len: true,
+                    al_rem: len_len - j - 1,
+                    acc_int: F::from(acc as u64),
+                    ..row
+                },
+                acc,
+            ));
+        }
+        rows
+    }
+
+    // rows of the prefix and of the bytes of an address or a storage key
+    fn elem_rows<F: Field>(bytes: &[u8], row: TxRlpRow<F>, randomness: F) -> Vec<TxRlpRow<F>> {
+        let len = (bytes[0] - 0x80) as usize;
+        let mut rows = vec![TxRlpRow {
+            al_is_elem_prefix: true,
+            al_rem: len,
+            ..row
+        }];
+        for (j, (acc_rlc, acc_int)) in field_accs(&bytes[1..1 + len], false, randomness)
+            .into_iter()
+            .enumerate()
+        {
+            rows.push(TxRlpRow {
+                al_is_elem: true,
+                al_is_elem_last: j == len - 1,
+                al_rem: len - j - 1,
+                acc_rlc,
+                acc_int,
+                ..row
+            });
+        }
+        rows
+    }
+
+    // each byte is in a row, so that `rows.len()` is the position in the payload
+    let mut rows: Vec<TxRlpRow<F>> = vec![];
+    let mut index = 0;
+    let mut keys_count = 0;
+    while rows.len() < payload.len() {
+        index += 1;
+        let item = TxRlpRow {
+            index,
+            al_keys_count: keys_count,
+            ..row
+        };
+        let item_rows = list_rows(&payload[rows.len()..], item);
+        let item_end = rows.len() + item_rows.len() + item_rows.last().map_or(0, |row| row.1);
+        let item_start = rows.len() + item_rows.len();
+        rows.extend(item_rows.into_iter().map(|(row, len)| TxRlpRow {
+            al_item_rem: len,
+            ..row
+        }));
+
+        let address_rows = elem_rows(&payload[rows.len()..], item, randomness);
+        let address = address_rows.last().map_or(F::zero(), |row| row.acc_int);
+        rows.extend(address_rows);
+
+        let keys = TxRlpRow {
+            al_is_keys: true,
+            al_address: address,
+            ..item
+        };
+        let keys_rows = list_rows(&payload[rows.len()..], keys);
+        let keys_end = rows.len() + keys_rows.len() + keys_rows.last().map_or(0, |row| row.1);
+        let keys_start = rows.len() + keys_rows.len();
+        rows.extend(keys_rows.into_iter().map(|(row, len)| TxRlpRow {
+            al_keys_rem: len,
+            ..row
+        }));
+        while rows.len() < keys_end {
+            keys_count += 1;
+            let key = TxRlpRow {
+                al_keys_count: keys_count,
+                ..keys
+            };
+            rows.extend(elem_rows(&payload[rows.len()..], key, randomness));
+        }
+
+        // the bytes left in the item and in its storage keys, after their
+        // prefix and length
+        for (position, row) in rows.iter_mut().enumerate().skip(item_start) {
+            row.al_item_rem = item_end - position - 1;
+        }
+        for (position, row) in rows.iter_mut().enumerate().skip(keys_start) {
+            row.al_keys_rem = keys_end - position - 1;
+        }
+    }
+
+    for (position, row) in rows.iter_mut().enumerate() {
+        row.rem = payload.len() - position - 1;
+        row.al_addresses_len = index;
+    }
+    rows
+}
+
+/// Returns the number of rows of the transactions RLP bytes, which is enough
+/// for `max_txs` transactions with `max_calldata` bytes of call data and
+/// `max_access_list` access list entries in total
+fn txs_rlp_len(max_txs: usize, max_calldata: usize, max_access_list: usize) -> usize {
+    max_txs * MAX_TX_RLP_LEN + max_calldata + max_access_list * MAX_ACCESS_LIST_ENTRY_RLP_LEN
+}
+
+/// Returns the accumulators of the RLC encoding with `randomness` and of the
+/// base 256 encoding of `bytes`, from the first byte, or from the last one when
+/// `is_reversed`.
+fn field_accs<F: Field>(bytes: &[u8], is_reversed: bool, randomness: F) -> Vec<(F, F)> {
+    let mut bytes = bytes.to_vec();
+    if is_reversed {
+        bytes.reverse();
+    }
+    let mut accs: Vec<_> = bytes
+        .into_iter()
+        .scan((F::zero(), F::zero()), |(acc_rlc, acc_int), byte| {
+            *acc_rlc = *acc_rlc * randomness + F::from(byte as u64);
+            *acc_int = *acc_int * F::from(256) + F::from(byte as u64);
+            Some((*acc_rlc, *acc_int))
+        })
+        .collect();
+    if is_reversed {
+        accs.reverse();
+    }
+    accs
+}
+
 /// Compute the raw_public_inputs column from the verifier's perspective.
 fn raw_public_inputs_col<F: Field>(
     max_txs: usize,
     max_calldata: usize,
+    max_access_list: usize,
     public_data: &PublicData,
     randomness: F, // For RLC encoding
 ) -> Vec<F> {
@@ -1453,8 +4423,9 @@ fn raw_public_inputs_col<F: Field>(
     let txs = public_data.get_tx_table_values();
 
     let mut offset = 0;
+    let txs_rlp_offset = BLOCK_LEN + 1 + EXTRA_LEN + 3 * (TX_LEN * max_txs + 1) + max_calldata;
     let mut result =
-        vec![F::zero(); BLOCK_LEN + 1 + EXTRA_LEN + 3 * (TX_LEN * max_txs + 1) + max_calldata];
+        vec![F::zero(); txs_rlp_offset + txs_rlp_len(max_txs, max_calldata, max_access_list)];
 
     //  Insert Block Values
     // zero row
@@ -1481,6 +4452,14 @@ fn raw_public_inputs_col<F: Field>(
     // chain_id
     result[offset] = F::from(block.chain_id);
     offset += 1;
+    // block_reward
+    result[offset] = rlc(block.block_reward.to_le_bytes(), randomness);
+    offset += 1;
+    // logs_bloom
+    for logs_bloom_word in block.logs_bloom {
+        result[offset] = rlc(logs_bloom_word.to_le_bytes(), randomness);
+        offset += 1;
+    }
     // Previous block hashes
     for prev_hash in block.history_hashes {
         result[offset] = rlc(prev_hash.to_fixed_bytes(), randomness);
@@ -1492,6 +4471,37 @@ fn raw_public_inputs_col<F: Field>(
     result[BLOCK_LEN + 1] = rlc(extra.state_root.to_fixed_bytes(), randomness);
     // parent block hash
     result[BLOCK_LEN + 2] = rlc(extra.prev_state_root.to_fixed_bytes(), randomness);
+    // receipts root
+    result[BLOCK_LEN + 3] = rlc(extra.receipts_root.to_fixed_bytes(), randomness);
+    // block hash
+    result[BLOCK_LEN + 4] = rlc(extra.block_hash.to_fixed_bytes(), randomness);
+    // transactions root
+    result[BLOCK_LEN + 5] = rlc(extra.transactions_root.to_fixed_bytes(), randomness);
+    // header RLP length
+    result[BLOCK_LEN + 1 + HEADER_RLP_LEN_OFFSET] = F::from(extra.header_rlp.len() as u64);
+    // randomness
+    result[BLOCK_LEN + 1 + RANDOMNESS_OFFSET] = randomness;
+    // transactions RLP length
+    result[BLOCK_LEN + 1 + TXS_RLP_LEN_OFFSET] = F::from(extra.txs_rlp.len() as u64);
+    // block hash bytes
+    for (i, byte) in extra.block_hash.as_bytes().iter().enumerate() {
+        result[BLOCK_LEN + 1 + BLOCK_HASH_BYTES_OFFSET + i] = F::from(*byte as u64);
+    }
+    // transactions root bytes
+    for (i, byte) in extra.transactions_root.as_bytes().iter().enumerate() {
+        result[BLOCK_LEN + 1 + TXS_ROOT_BYTES_OFFSET + i] = F::from(*byte as u64);
+    }
+    // receipts root bytes
+    for (i, byte) in extra.receipts_root.as_bytes().iter().enumerate() {
+        result[BLOCK_LEN + 1 + RECEIPTS_ROOT_BYTES_OFFSET + i] = F::from(*byte as u64);
+    }
+    // header RLP bytes, padded with zeros
+    for (value, byte) in result[BLOCK_LEN + 1 + HEADER_RLP_BYTES_OFFSET..BLOCK_LEN + 1 + EXTRA_LEN]
+        .iter_mut()
+        .zip(&extra.header_rlp)
+    {
+        *value = F::from(*byte as u64);
+    }
 
     // Insert Tx table
     offset = 0;
@@ -1518,12 +4528,16 @@ fn raw_public_inputs_col<F: Field>(
             rlc(tx.nonce.to_le_bytes(), randomness),
             rlc(tx.gas.to_le_bytes(), randomness),
             rlc(tx.gas_price.to_le_bytes(), randomness),
+            rlc(tx.gas_fee_cap.to_le_bytes(), randomness),
+            rlc(tx.gas_tip_cap.to_le_bytes(), randomness),
             tx.from_addr.to_scalar().expect("tx.from too big"),
             tx.to_addr.to_scalar().expect("tx.to too big"),
             F::from(tx.is_create),
             rlc(tx.value.to_le_bytes(), randomness),
             F::from(tx.call_data_len),
             F::from(tx.call_data_gas_cost),
+            F::from(tx.access_list_addresses_len),
+            F::from(tx.access_list_storage_keys_len),
             rlc(tx.tx_sign_hash, randomness),
         ] {
             result[id_offset + offset] = F::from((i + 1) as u64);
@@ -1548,6 +4562,11 @@ fn raw_public_inputs_col<F: Field>(
         offset += 1;
     }
 
+    // Insert transactions RLP bytes, padded with zeros
+    for (value, byte) in result[txs_rlp_offset..].iter_mut().zip(&extra.txs_rlp) {
+        *value = F::from(*byte as u64);
+    }
+
     result
 }
 
@@ -1556,16 +4575,21 @@ mod pi_circuit_test {
     use super::*;
 
     use crate::test_util::rand_tx;
+    use eth_types::{AccessList, AccessListItem};
     use halo2_proofs::{
         dev::{MockProver, VerifyFailure},
         halo2curves::bn256::Fr,
     };
+    use mock::{AddrOrWallet, MOCK_ACCOUNTS, MOCK_CHAIN_ID};
     use pretty_assertions::assert_eq;
     use rand::SeedableRng;
     use rand_chacha::ChaCha20Rng;
 
+    const MAX_RECEIPTS_LEN: usize = 512;
+
     fn run<F: Field, const MAX_TXS: usize, const MAX_CALLDATA: usize>(
         k: u32,
+        max_access_list: usize,
         public_data: PublicData,
     ) -> Result<(), Vec<VerifyFailure>> {
         let mut rng = ChaCha20Rng::seed_from_u64(2);
@@ -1575,6 +4599,8 @@ mod pi_circuit_test {
         let circuit = PiTestCircuit::<F, MAX_TXS, MAX_CALLDATA>(PiCircuit::new(
             MAX_TXS,
             MAX_CALLDATA,
+            max_access_list,
+            MAX_RECEIPTS_LEN,
             randomness,
             rand_rpi,
             public_data,
@@ -1588,20 +4614,41 @@ mod pi_circuit_test {
         prover.verify()
     }
 
+    /// Sets a receipt without logs for each transaction of `public_data`, and
+    /// the receipts root to their commitment.
+    fn set_receipts(public_data: &mut PublicData) {
+        public_data.receipts = (1..=public_data.transactions.len())
+            .map(|tx_id| witness::Receipt {
+                tx_id,
+                status: true,
+                cumulative_gas_used: 21_000 * tx_id as u64,
+                ..Default::default()
+            })
+            .collect();
+        public_data.receipts_root = public_data.receipts_commitment();
+    }
+
     #[test]
     fn test_default_pi() {
         const MAX_TXS: usize = 2;
         const MAX_CALLDATA: usize = 8;
-        let public_data = PublicData::default();
+        const MAX_ACCESS_LIST: usize = 0;
+        let mut public_data = PublicData::default();
+        public_data.transactions_root = public_data.txs_commitment();
+        set_receipts(&mut public_data);
 
         let k = 17;
-        assert_eq!(run::<Fr, MAX_TXS, MAX_CALLDATA>(k, public_data), Ok(()));
+        assert_eq!(
+            run::<Fr, MAX_TXS, MAX_CALLDATA>(k, MAX_ACCESS_LIST, public_data),
+            Ok(())
+        );
     }
 
     #[test]
     fn test_simple_pi() {
         const MAX_TXS: usize = 8;
         const MAX_CALLDATA: usize = 200;
+        const MAX_ACCESS_LIST: usize = 0;
 
         let mut rng = ChaCha20Rng::seed_from_u64(2);
 
@@ -1614,8 +4661,347 @@ mod pi_circuit_test {
             let eth_tx = eth_types::Transaction::from(&rand_tx(&mut rng, chain_id, i & 2 == 0));
             public_data.transactions.push(eth_tx);
         }
+        public_data.transactions_root = public_data.txs_commitment();
+        set_receipts(&mut public_data);
+
+        let k = 17;
+        assert_eq!(
+            run::<Fr, MAX_TXS, MAX_CALLDATA>(k, MAX_ACCESS_LIST, public_data),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_header_pi() {
+        const MAX_TXS: usize = 2;
+        const MAX_CALLDATA: usize = 8;
+        const MAX_ACCESS_LIST: usize = 0;
+
+        let mut public_data = PublicData::default();
+        public_data.history_hashes = vec![Word::from(0xbeef)];
+        public_data.block_constants.number = 0xcafeu64.into();
+        public_data.block_constants.gas_limit = Word::from(30_000_000);
+        public_data.block_constants.base_fee = Word::from(1_000_000_000);
+        public_data.gas_used = Word::from(21_000);
+        public_data.extra_data = Bytes::from(vec![0xff; 32]);
+        public_data.nonce = H64::from_low_u64_be(0x42);
+        public_data.transactions_root = public_data.txs_commitment();
+        set_receipts(&mut public_data);
+        assert_eq!(public_data.header_rlp().len(), 541);
+
+        let k = 17;
+        assert_eq!(
+            run::<Fr, MAX_TXS, MAX_CALLDATA>(k, MAX_ACCESS_LIST, public_data),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_max_header_pi() {
+        const MAX_TXS: usize = 2;
+        const MAX_CALLDATA: usize = 8;
+        const MAX_ACCESS_LIST: usize = 0;
+
+        // a Shanghai header whose fields of variable length are all as long as
+        // they can be
+        let mut public_data = PublicData::default();
+        public_data.hardfork = Hardfork::Shanghai;
+        public_data.block_constants.difficulty = Word::MAX;
+        public_data.block_constants.number = u64::MAX.into();
+        public_data.block_constants.gas_limit = Word::from(u64::MAX);
+        public_data.block_constants.timestamp = Word::from(u64::MAX);
+        public_data.block_constants.base_fee = Word::MAX;
+        public_data.gas_used = Word::from(u64::MAX);
+        public_data.extra_data = Bytes::from(vec![0xff; MAX_EXTRA_DATA_LEN]);
+        public_data.mix_hash = H256::repeat_byte(0xff);
+        public_data.nonce = H64::repeat_byte(0xff);
+        public_data.withdrawals_root = H256::repeat_byte(0xff);
+        public_data.transactions_root = public_data.txs_commitment();
+        set_receipts(&mut public_data);
+        assert_eq!(public_data.header_rlp().len(), MAX_HEADER_RLP_LEN);
+
+        let k = 17;
+        assert_eq!(
+            run::<Fr, MAX_TXS, MAX_CALLDATA>(k, MAX_ACCESS_LIST, public_data),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_extra_data_over_capacity_pi() {
+        const MAX_TXS: usize = 2;
+        const MAX_CALLDATA: usize = 8;
+        const MAX_ACCESS_LIST: usize = 0;
+
+        let mut public_data = PublicData::default();
+        public_data.extra_data = Bytes::from(vec![0xff; MAX_EXTRA_DATA_LEN + 1]);
+        public_data.transactions_root = public_data.txs_commitment();
+        set_receipts(&mut public_data);
+
+        let circuit = PiTestCircuit::<Fr, MAX_TXS, MAX_CALLDATA>(PiCircuit::new(
+            MAX_TXS,
+            MAX_CALLDATA,
+            MAX_ACCESS_LIST,
+            MAX_RECEIPTS_LEN,
+            Fr::one(),
+            Fr::one(),
+            public_data,
+        ));
+        let public_inputs = circuit.0.instance();
+
+        let k = 17;
+        assert!(matches!(
+            MockProver::run(k, &circuit, public_inputs),
+            Err(Error::Synthesis)
+        ));
+    }
+
+    #[test]
+    fn test_txs_commitment_pi() {
+        const MAX_TXS: usize = 4;
+        const MAX_CALLDATA: usize = 20;
+        const MAX_ACCESS_LIST: usize = 0;
+
+        let mut rng = ChaCha20Rng::seed_from_u64(2);
+
+        let mut public_data = PublicData::default();
+        let chain_id = 1337u64;
+        public_data.chain_id = Word::from(chain_id);
+        for i in 0..2 {
+            let eth_tx = eth_types::Transaction::from(&rand_tx(&mut rng, chain_id, i == 0));
+            public_data.transactions.push(eth_tx);
+        }
 
+        let txs_hash = public_data.txs_commitment();
+        assert_eq!(txs_hash, H256(keccak256(public_data.txs_rlp())));
+
+        // the transactions root of the header must be the commitment
         let k = 17;
-        assert_eq!(run::<Fr, MAX_TXS, MAX_CALLDATA>(k, public_data), Ok(()));
+        set_receipts(&mut public_data);
+        public_data.transactions_root = H256::from_low_u64_be(0xbad);
+        assert!(run::<Fr, MAX_TXS, MAX_CALLDATA>(k, MAX_ACCESS_LIST, public_data.clone()).is_err());
+
+        public_data.transactions_root = txs_hash;
+        let header_rlp = public_data.header_rlp();
+        assert_eq!(
+            &header_rlp[HEADER_TXS_ROOT_OFFSET..HEADER_TXS_ROOT_OFFSET + 32],
+            txs_hash.as_bytes()
+        );
+
+        assert_eq!(
+            run::<Fr, MAX_TXS, MAX_CALLDATA>(k, MAX_ACCESS_LIST, public_data),
+            Ok(())
+        );
+    }
+
+    /// Returns the public data of a block of 2 random transactions, the first
+    /// one emitting the `logs` and the second one failing.
+    fn receipts_public_data(logs: Vec<witness::ReceiptLog>) -> PublicData {
+        let mut rng = ChaCha20Rng::seed_from_u64(2);
+
+        let mut public_data = PublicData::default();
+        let chain_id = 1337u64;
+        public_data.chain_id = Word::from(chain_id);
+        for i in 0..2 {
+            let eth_tx = eth_types::Transaction::from(&rand_tx(&mut rng, chain_id, i == 0));
+            public_data.transactions.push(eth_tx);
+        }
+        public_data.transactions_root = public_data.txs_commitment();
+        public_data.receipts = vec![
+            witness::Receipt {
+                tx_id: 1,
+                status: true,
+                cumulative_gas_used: 50_000,
+                log_length: logs.len() as u64,
+                logs,
+            },
+            witness::Receipt {
+                tx_id: 2,
+                status: false,
+                cumulative_gas_used: 71_000,
+                ..Default::default()
+            },
+        ];
+        public_data.receipts_root = public_data.receipts_commitment();
+        public_data
+    }
+
+    #[test]
+    fn test_receipts_pi() {
+        const MAX_TXS: usize = 4;
+        const MAX_CALLDATA: usize = 20;
+        const MAX_ACCESS_LIST: usize = 0;
+
+        // a log with topics and data, and a log with neither
+        let mut public_data = receipts_public_data(vec![
+            witness::ReceiptLog {
+                address: MOCK_ACCOUNTS[0],
+                topics: vec![Word::from(0xcafe), Word::MAX],
+                data: vec![0, 1, 0xff],
+            },
+            witness::ReceiptLog {
+                address: MOCK_ACCOUNTS[1],
+                ..Default::default()
+            },
+        ]);
+        let receipts_hash = public_data.receipts_commitment();
+        assert_eq!(receipts_hash, H256(keccak256(public_data.receipts_bytes())));
+        let header_rlp = public_data.header_rlp();
+        assert_eq!(
+            &header_rlp[HEADER_RECEIPTS_ROOT_OFFSET..HEADER_RECEIPTS_ROOT_OFFSET + 32],
+            receipts_hash.as_bytes()
+        );
+
+        let k = 17;
+        assert_eq!(
+            run::<Fr, MAX_TXS, MAX_CALLDATA>(k, MAX_ACCESS_LIST, public_data.clone()),
+            Ok(())
+        );
+
+        // the receipts root of the header must be the commitment
+        public_data.receipts_root = H256::from_low_u64_be(0xbad);
+        assert!(run::<Fr, MAX_TXS, MAX_CALLDATA>(k, MAX_ACCESS_LIST, public_data.clone()).is_err());
+
+        // and must commit to the receipt of every transaction
+        public_data.receipts.pop();
+        public_data.receipts_root = public_data.receipts_commitment();
+        assert!(run::<Fr, MAX_TXS, MAX_CALLDATA>(k, MAX_ACCESS_LIST, public_data).is_err());
+    }
+
+    #[test]
+    fn test_receipts_over_capacity_pi() {
+        const MAX_TXS: usize = 2;
+        const MAX_CALLDATA: usize = 20;
+        const MAX_ACCESS_LIST: usize = 0;
+
+        let public_data = receipts_public_data(vec![witness::ReceiptLog {
+            address: MOCK_ACCOUNTS[0],
+            topics: vec![],
+            data: vec![0xff; MAX_RECEIPTS_LEN],
+        }]);
+        assert!(public_data.receipts_bytes().len() > MAX_RECEIPTS_LEN);
+
+        let circuit = PiTestCircuit::<Fr, MAX_TXS, MAX_CALLDATA>(PiCircuit::new(
+            MAX_TXS,
+            MAX_CALLDATA,
+            MAX_ACCESS_LIST,
+            MAX_RECEIPTS_LEN,
+            Fr::one(),
+            Fr::one(),
+            public_data,
+        ));
+        let public_inputs = circuit.0.instance();
+
+        let k = 17;
+        assert!(matches!(
+            MockProver::run(k, &circuit, public_inputs),
+            Err(Error::Synthesis)
+        ));
+    }
+
+    /// Returns a transaction of `tx_type` signed by a random wallet with the
+    /// items of `access_list`.
+    fn access_list_tx(
+        rng: &mut ChaCha20Rng,
+        tx_type: u64,
+        access_list: Vec<AccessListItem>,
+    ) -> eth_types::Transaction {
+        mock::MockTransaction::default()
+            .from(AddrOrWallet::random(rng))
+            .to(MOCK_ACCOUNTS[0])
+            .transaction_type(tx_type)
+            .access_list(AccessList(access_list))
+            .build()
+            .into()
+    }
+
+    #[test]
+    fn test_access_list_pi() {
+        const MAX_TXS: usize = 4;
+        const MAX_CALLDATA: usize = 20;
+        const MAX_ACCESS_LIST: usize = 16;
+
+        let mut rng = ChaCha20Rng::seed_from_u64(2);
+
+        let mut public_data = PublicData::default();
+        public_data.chain_id = *MOCK_CHAIN_ID;
+        // an empty access list, an item without storage keys, and items whose
+        // storage keys, the items themselves and the access list are long lists
+        for (tx_type, access_list) in [
+            (1, vec![]),
+            (
+                1,
+                vec![AccessListItem {
+                    address: MOCK_ACCOUNTS[1],
+                    storage_keys: vec![],
+                }],
+            ),
+            (
+                2,
+                vec![
+                    AccessListItem {
+                        address: MOCK_ACCOUNTS[2],
+                        storage_keys: vec![H256::from_low_u64_be(1)],
+                    },
+                    AccessListItem {
+                        address: MOCK_ACCOUNTS[3],
+                        storage_keys: (2..5).map(H256::from_low_u64_be).collect(),
+                    },
+                ],
+            ),
+            (2, vec![]),
+        ] {
+            let tx = access_list_tx(&mut rng, tx_type, access_list);
+            public_data.transactions.push(tx);
+        }
+        public_data.transactions_root = public_data.txs_commitment();
+        set_receipts(&mut public_data);
+
+        let k = 17;
+        assert_eq!(
+            run::<Fr, MAX_TXS, MAX_CALLDATA>(k, MAX_ACCESS_LIST, public_data),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_access_list_over_capacity_pi() {
+        const MAX_TXS: usize = 1;
+        const MAX_CALLDATA: usize = 8;
+        const MAX_ACCESS_LIST: usize = 2;
+
+        let mut rng = ChaCha20Rng::seed_from_u64(2);
+
+        let mut public_data = PublicData::default();
+        public_data.chain_id = *MOCK_CHAIN_ID;
+        let tx = access_list_tx(
+            &mut rng,
+            1,
+            vec![AccessListItem {
+                address: MOCK_ACCOUNTS[1],
+                storage_keys: vec![H256::from_low_u64_be(1), H256::from_low_u64_be(2)],
+            }],
+        );
+        public_data.transactions.push(tx);
+        set_receipts(&mut public_data);
+
+        let circuit = PiTestCircuit::<Fr, MAX_TXS, MAX_CALLDATA>(PiCircuit::new(
+            MAX_TXS,
+            MAX_CALLDATA,
+            MAX_ACCESS_LIST,
+            MAX_RECEIPTS_LEN,
+            Fr::random(&mut rng),
+            Fr::random(&mut rng),
+            public_data,
+        ));
+        let public_inputs = circuit.0.instance();
+
+        // The 3 entries of the access list don't fit in the 2 rows of the
+        // tx table, which is an error instead of a panic.
+        let k = 17;
+        assert!(matches!(
+            MockProver::run(k, &circuit, public_inputs),
+            Err(Error::Synthesis)
+        ));
     }
 }
@@ -0,0 +1,874 @@
+//! Rows of the PI circuit which encode the receipts of the block from the
+//! TxReceipt and TxLog rows of the rw table, and hash their encoding through
+//! the keccak table into the receipts root of the header.
+//!
+//! The receipt of each tx is encoded as its status (1 byte), its cumulative
+//! gas used (8 bytes) and its number of logs (8 bytes), followed by each of its
+//! logs, encoded as its address (20 bytes), its number of topics (1 byte),
+//! each of its topics (32 bytes), the length of its data (8 bytes) and each
+//! byte of its data. Numbers and topics are big-endian.
+//!
+//! There is a row per byte of the encoding, followed by padding rows up to the
+//! capacity and by a last row, which holds the length and RLC encoding of the
+//! bytes, looked up in the keccak table together with the receipts root.
+//! The value of each field is accumulated through its bytes, and looked up in
+//! the rw table at its last byte. The fields of each receipt follow each other
+//! by the number of logs, topics and data bytes they hold, and the receipts
+//! follow each other by tx id from 1, up to the last tx of the tx table.
+
+use eth_types::{Field, ToBigEndian};
+use gadgets::util::{not, select, sum, Expr};
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, SecondPhase, Selector},
+    poly::Rotation,
+};
+use std::marker::PhantomData;
+use strum::{EnumCount, IntoEnumIterator};
+use strum_macros::{EnumCount, EnumIter};
+
+#[cfg(any(feature = "test", test))]
+use crate::witness::Rw;
+use crate::{
+    evm_circuit::util::constraint_builder::BaseConstraintBuilder,
+    table::{
+        KeccakTable, LookupTable, RwTable, RwTableTag, TxFieldTag, TxLogFieldTag,
+        TxReceiptFieldTag, TxTable,
+    },
+    util::{build_tx_log_expression, Challenges},
+    witness::Receipt,
+};
+#[cfg(any(feature = "test", test))]
+use eth_types::{ToWord, Word};
+
+/// Field of the encoding of a receipt
+#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter, EnumCount)]
+enum ReceiptField {
+    Status,
+    CumulativeGasUsed,
+    LogLength,
+    Address,
+    TopicLength,
+    Topic,
+    DataLength,
+    Data,
+}
+
+impl ReceiptField {
+    /// Number of bytes of the field
+    fn width(&self) -> u64 {
+        match self {
+            Self::Status | Self::TopicLength | Self::Data => 1,
+            Self::CumulativeGasUsed | Self::LogLength | Self::DataLength => 8,
+            Self::Address => 20,
+            Self::Topic => 32,
+        }
+    }
+
+    /// Field tag of the TxReceipt rw of the field, or 0 for a TxLog rw
+    fn receipt_field_tag(&self) -> u64 {
+        match self {
+            Self::Status => TxReceiptFieldTag::PostStateOrStatus as u64,
+            Self::CumulativeGasUsed => TxReceiptFieldTag::CumulativeGasUsed as u64,
+            Self::LogLength => TxReceiptFieldTag::LogLength as u64,
+            _ => 0,
+        }
+    }
+
+    /// Field tag of the TxLog rw of the field, or 0 for a TxReceipt rw
+    fn log_field_tag(&self) -> u64 {
+        match self {
+            Self::Address => TxLogFieldTag::Address as u64,
+            Self::TopicLength => TxLogFieldTag::TopicLength as u64,
+            Self::Topic => TxLogFieldTag::Topic as u64,
+            Self::DataLength => TxLogFieldTag::DataLength as u64,
+            Self::Data => TxLogFieldTag::Data as u64,
+            _ => 0,
+        }
+    }
+}
+
+/// Row of a byte of the encoding of the receipts
+#[derive(Clone, Copy, Debug)]
+struct ReceiptRow {
+    field: ReceiptField,
+    tx_id: usize,
+    log_id: usize,
+    /// Topic index, or data byte index
+    index: usize,
+    /// Number of bytes of the field after this one
+    rem: u64,
+    /// Number of logs of the receipt after the current one
+    logs_rem: u64,
+    /// Number of topics, or data bytes, of the log after the current one
+    items_rem: u64,
+    byte: u8,
+}
+
+/// Returns the rows of the bytes of the encoding of `receipts`
+fn receipts_rows(receipts: &[Receipt]) -> Vec<ReceiptRow> {
+    let mut fields = vec![];
+    for receipt in receipts {
+        let tx_id = receipt.tx_id;
+        fields.push((
+            ReceiptField::Status,
+            tx_id,
+            0,
+            0,
+            vec![receipt.status as u8],
+        ));
+        fields.push((
+            ReceiptField::CumulativeGasUsed,
+            tx_id,
+            0,
+            0,
+            receipt.cumulative_gas_used.to_be_bytes().to_vec(),
+        ));
+        fields.push((
+            ReceiptField::LogLength,
+            tx_id,
+            0,
+            0,
+            receipt.log_length.to_be_bytes().to_vec(),
+        ));
+        for (i, log) in receipt.logs.iter().enumerate() {
+            let log_id = i + 1;
+            fields.push((
+                ReceiptField::Address,
+                tx_id,
+                log_id,
+                0,
+                log.address.as_bytes().to_vec(),
+            ));
+            fields.push((
+                ReceiptField::TopicLength,
+                tx_id,
+                log_id,
+                0,
+                vec![log.topics.len() as u8],
+            ));
+            for (index, topic) in log.topics.iter().enumerate() {
+                fields.push((
+                    ReceiptField::Topic,
+                    tx_id,
+                    log_id,
+                    index,
+                    topic.to_be_bytes().to_vec(),
+                ));
+            }
+            fields.push((
+                ReceiptField::DataLength,
+                tx_id,
+                log_id,
+                0,
+                (log.data.len() as u64).to_be_bytes().to_vec(),
+            ));
+            for (index, byte) in log.data.iter().enumerate() {
+                fields.push((ReceiptField::Data, tx_id, log_id, index, vec![*byte]));
+            }
+        }
+    }
+
+    let mut rows = vec![];
+    let (mut logs_rem, mut items_rem) = (0u64, 0u64);
+    for (field, tx_id, log_id, index, bytes) in fields {
+        for (i, byte) in bytes.iter().enumerate() {
+            let rem = (bytes.len() - 1 - i) as u64;
+            let value = || {
+                bytes
+                    .iter()
+                    .fold(0u64, |acc, byte| (acc << 8) | *byte as u64)
+            };
+            match field {
+                ReceiptField::LogLength if rem == 0 => logs_rem = value(),
+                ReceiptField::Address if i == 0 => logs_rem = logs_rem.wrapping_sub(1),
+                ReceiptField::TopicLength | ReceiptField::DataLength if rem == 0 => {
+                    items_rem = value()
+                }
+                ReceiptField::Topic | ReceiptField::Data if i == 0 => {
+                    items_rem = items_rem.wrapping_sub(1)
+                }
+                _ => {}
+            }
+            rows.push(ReceiptRow {
+                field,
+                tx_id,
+                log_id,
+                index,
+                rem,
+                logs_rem,
+                items_rem,
+                byte: *byte,
+            });
+        }
+    }
+    rows
+}
+
+/// Returns the encoding of `receipts`, whose hash is the receipts root
+pub(super) fn receipts_bytes(receipts: &[Receipt]) -> Vec<u8> {
+    receipts_rows(receipts).iter().map(|row| row.byte).collect()
+}
+
+/// Returns the TxReceipt and TxLog writes of `receipts`, as written at the end
+/// of each tx and by its LOG steps
+#[cfg(any(feature = "test", test))]
+pub(super) fn receipts_rws(receipts: &[Receipt]) -> Vec<Rw> {
+    let mut rws = vec![];
+    for receipt in receipts {
+        let tx_id = receipt.tx_id;
+        for (log_id, log) in receipt.logs.iter().enumerate() {
+            let log_id = log_id as u64 + 1;
+            let fields = [
+                (TxLogFieldTag::Address, 0, log.address.to_word()),
+                (TxLogFieldTag::TopicLength, 0, log.topics.len().to_word()),
+                (TxLogFieldTag::DataLength, 0, log.data.len().to_word()),
+            ]
+            .into_iter()
+            .chain(
+                log.topics
+                    .iter()
+                    .enumerate()
+                    .map(|(index, topic)| (TxLogFieldTag::Topic, index, *topic)),
+            )
+            .chain(
+                log.data
+                    .iter()
+                    .enumerate()
+                    .map(|(index, byte)| (TxLogFieldTag::Data, index, Word::from(*byte))),
+            );
+            for (field_tag, index, value) in fields {
+                rws.push(Rw::TxLog {
+                    rw_counter: rws.len() + 1,
+                    is_write: true,
+                    tx_id,
+                    log_id,
+                    field_tag,
+                    index,
+                    value,
+                });
+            }
+        }
+        for (field_tag, value) in [
+            (TxReceiptFieldTag::PostStateOrStatus, receipt.status as u64),
+            (TxReceiptFieldTag::LogLength, receipt.log_length),
+            (
+                TxReceiptFieldTag::CumulativeGasUsed,
+                receipt.cumulative_gas_used,
+            ),
+        ] {
+            rws.push(Rw::TxReceipt {
+                rw_counter: rws.len() + 1,
+                is_write: true,
+                tx_id,
+                field_tag,
+                value,
+            });
+        }
+    }
+    rws
+}
+
+/// Config of the rows of the encoding of the receipts
+#[derive(Clone, Debug)]
+pub(super) struct ReceiptsConfig<F> {
+    /// Max number of supported transactions
+    max_txs: usize,
+    q_enable: Selector,
+    q_first: Selector,
+    q_last: Selector,
+    /// Whether the row holds a byte of the encoding, or is padding
+    is_byte: Column<Advice>,
+    byte: Column<Advice>,
+    /// Whether the byte is in each field, as in `ReceiptField`
+    fields: [Column<Advice>; ReceiptField::COUNT],
+    tx_id: Column<Advice>,
+    log_id: Column<Advice>,
+    index: Column<Advice>,
+    rem: Column<Advice>,
+    rem_inv: Column<Advice>,
+    /// Whether the byte is the last one of its field
+    is_last: Column<Advice>,
+    logs_rem: Column<Advice>,
+    logs_rem_inv: Column<Advice>,
+    logs_done: Column<Advice>,
+    items_rem: Column<Advice>,
+    items_rem_inv: Column<Advice>,
+    items_done: Column<Advice>,
+    /// Inverse of the difference of the last tx id and the max number of txs
+    tx_id_diff_inv: Column<Advice>,
+    /// Value of the field accumulated from its first byte, in base 256 or, for
+    /// the topics, in the RLC encoding of the rw table
+    value_acc: Column<Advice>,
+    len_acc: Column<Advice>,
+    rlc_acc: Column<Advice>,
+    /// RLC encoding of the receipts root, copied from its bytes
+    hash_rlc: Column<Advice>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> ReceiptsConfig<F> {
+    pub(super) fn configure(
+        meta: &mut ConstraintSystem<F>,
+        max_txs: usize,
+        rw_table: RwTable,
+        tx_table: TxTable,
+        keccak_table: KeccakTable,
+        challenges: &Challenges<Expression<F>>,
+    ) -> Self {
+        let q_enable = meta.complex_selector();
+        let q_first = meta.complex_selector();
+        let q_last = meta.complex_selector();
+        let is_byte = meta.advice_column();
+        let byte = meta.advice_column();
+        let fields = [(); ReceiptField::COUNT].map(|_| meta.advice_column());
+        let tx_id = meta.advice_column();
+        let log_id = meta.advice_column();
+        let index = meta.advice_column();
+        let rem = meta.advice_column();
+        let rem_inv = meta.advice_column();
+        let is_last = meta.advice_column();
+        let logs_rem = meta.advice_column();
+        let logs_rem_inv = meta.advice_column();
+        let logs_done = meta.advice_column();
+        let items_rem = meta.advice_column();
+        let items_rem_inv = meta.advice_column();
+        let items_done = meta.advice_column();
+        let tx_id_diff_inv = meta.advice_column();
+        let value_acc = meta.advice_column_in(SecondPhase);
+        let len_acc = meta.advice_column();
+        let rlc_acc = meta.advice_column_in(SecondPhase);
+        let hash_rlc = meta.advice_column_in(SecondPhase);
+
+        meta.enable_equality(hash_rlc);
+
+        meta.create_gate("receipts bytes", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            let q_first = meta.query_selector(q_first);
+            let q_last = meta.query_selector(q_last);
+            let [is_byte_prev, is_byte, is_byte_next] =
+                [Rotation::prev(), Rotation::cur(), Rotation::next()]
+                    .map(|rotation| meta.query_advice(is_byte, rotation));
+            let [fields_prev, fields, fields_next] =
+                [Rotation::prev(), Rotation::cur(), Rotation::next()]
+                    .map(|rotation| fields.map(|column| meta.query_advice(column, rotation)));
+            let field = |field: ReceiptField| fields[field as usize].clone();
+            let field_prev = |field: ReceiptField| fields_prev[field as usize].clone();
+            let byte = meta.query_advice(byte, Rotation::cur());
+            let is_first_field = q_first.clone() + meta.query_advice(is_last, Rotation::prev());
+            let is_last = meta.query_advice(is_last, Rotation::cur());
+            let [tx_id_prev, tx_id] = [Rotation::prev(), Rotation::cur()]
+                .map(|rotation| meta.query_advice(tx_id, rotation));
+            let [log_id_prev, log_id] = [Rotation::prev(), Rotation::cur()]
+                .map(|rotation| meta.query_advice(log_id, rotation));
+            let [index_prev, index] = [Rotation::prev(), Rotation::cur()]
+                .map(|rotation| meta.query_advice(index, rotation));
+            let [rem_prev, rem] = [Rotation::prev(), Rotation::cur()]
+                .map(|rotation| meta.query_advice(rem, rotation));
+            let [logs_rem_prev, logs_rem] = [Rotation::prev(), Rotation::cur()]
+                .map(|rotation| meta.query_advice(logs_rem, rotation));
+            let [items_rem_prev, items_rem] = [Rotation::prev(), Rotation::cur()]
+                .map(|rotation| meta.query_advice(items_rem, rotation));
+            let [value_acc_prev, value_acc] = [Rotation::prev(), Rotation::cur()]
+                .map(|rotation| meta.query_advice(value_acc, rotation));
+            let [len_acc_prev, len_acc] = [Rotation::prev(), Rotation::cur()]
+                .map(|rotation| meta.query_advice(len_acc, rotation));
+            let [rlc_acc_prev, rlc_acc] = [Rotation::prev(), Rotation::cur()]
+                .map(|rotation| meta.query_advice(rlc_acc, rotation));
+            let logs_done = meta.query_advice(logs_done, Rotation::cur());
+            let items_done = meta.query_advice(items_done, Rotation::cur());
+
+            cb.require_boolean("is_byte is boolean", is_byte.clone());
+            cb.condition(not::expr(q_first.clone()), |cb| {
+                cb.require_boolean(
+                    "is_byte can only transit from 1 to 0",
+                    is_byte_prev - is_byte.clone(),
+                );
+            });
+            cb.condition(q_last, |cb| {
+                cb.require_zero("the last row is padding", is_byte.clone());
+            });
+            for field in fields.iter() {
+                cb.require_boolean("field is boolean", field.clone());
+            }
+            cb.require_equal(
+                "a byte is in exactly one field",
+                sum::expr(&fields),
+                is_byte.clone(),
+            );
+            cb.require_equal(
+                "len_acc counts the bytes",
+                len_acc,
+                len_acc_prev + is_byte.clone(),
+            );
+            cb.require_equal(
+                "rlc_acc accumulates the bytes from the first one",
+                rlc_acc,
+                select::expr(
+                    is_byte.clone(),
+                    rlc_acc_prev.clone() * challenges.keccak_input() + byte.clone(),
+                    rlc_acc_prev,
+                ),
+            );
+            cb.require_equal(
+                "tx_id increases at each receipt",
+                tx_id,
+                tx_id_prev + is_first_field.clone() * field(ReceiptField::Status),
+            );
+            cb.require_equal(
+                "is_last is 1 at the last byte of a field and 0 otherwise",
+                is_last.clone(),
+                is_byte.clone()
+                    * (1.expr() - rem.clone() * meta.query_advice(rem_inv, Rotation::cur())),
+            );
+            cb.require_zero("is_last is 0 if rem != 0", rem.clone() * is_last.clone());
+
+            cb.condition(is_byte.clone(), |cb| {
+                let is_topic = field(ReceiptField::Topic);
+                let is_data = field(ReceiptField::Data);
+                let width = sum::expr(ReceiptField::iter().map(|f| field(f) * f.width().expr()));
+
+                cb.condition(q_first.clone(), |cb| {
+                    cb.require_equal(
+                        "the first byte is the status of a receipt",
+                        field(ReceiptField::Status),
+                        1.expr(),
+                    );
+                });
+                cb.require_equal(
+                    "rem counts down the bytes of the field",
+                    rem.clone(),
+                    select::expr(
+                        is_first_field.clone(),
+                        width - 1.expr(),
+                        rem_prev - 1.expr(),
+                    ),
+                );
+                cb.require_equal(
+                    "value_acc accumulates the bytes of the field from the first one",
+                    value_acc.clone(),
+                    select::expr(
+                        is_first_field.clone(),
+                        byte.clone(),
+                        value_acc_prev
+                            * select::expr(is_topic.clone(), challenges.evm_word(), 256.expr())
+                            + byte,
+                    ),
+                );
+                cb.require_equal(
+                    "log_id counts the logs of the receipt",
+                    log_id,
+                    select::expr(
+                        field(ReceiptField::Status),
+                        0.expr(),
+                        log_id_prev + is_first_field.clone() * field(ReceiptField::Address),
+                    ),
+                );
+                cb.require_equal(
+                    "index counts the topics and data bytes of the log",
+                    index,
+                    (is_topic.clone() + is_data.clone())
+                        * select::expr(
+                            is_first_field.clone(),
+                            (is_topic.clone() * field_prev(ReceiptField::Topic)
+                                + is_data.clone() * field_prev(ReceiptField::Data))
+                                * (index_prev.clone() + 1.expr()),
+                            index_prev,
+                        ),
+                );
+                cb.require_equal(
+                    "logs_rem counts down the logs of the receipt",
+                    logs_rem.clone(),
+                    select::expr(
+                        field(ReceiptField::LogLength) * is_last.clone(),
+                        value_acc.clone(),
+                        logs_rem_prev - is_first_field.clone() * field(ReceiptField::Address),
+                    ),
+                );
+                cb.require_equal(
+                    "items_rem counts down the topics or data bytes of the log",
+                    items_rem.clone(),
+                    select::expr(
+                        (field(ReceiptField::TopicLength) + field(ReceiptField::DataLength))
+                            * is_last.clone(),
+                        value_acc,
+                        items_rem_prev - is_first_field * (is_topic + is_data),
+                    ),
+                );
+                for (name, value, value_inv, is_zero) in [
+                    (
+                        "logs_done is 1 if logs_rem == 0 and 0 otherwise",
+                        logs_rem,
+                        logs_rem_inv,
+                        logs_done.clone(),
+                    ),
+                    (
+                        "items_done is 1 if items_rem == 0 and 0 otherwise",
+                        items_rem,
+                        items_rem_inv,
+                        items_done.clone(),
+                    ),
+                ] {
+                    let value_inv = meta.query_advice(value_inv, Rotation::cur());
+                    cb.require_equal(name, is_zero.clone(), 1.expr() - value.clone() * value_inv);
+                    cb.require_zero(name, value * is_zero);
+                }
+
+                // Bytes of a field are followed by bytes of the same field
+                cb.condition(not::expr(is_last.clone()), |cb| {
+                    cb.require_equal("a field isn't cut", is_byte_next.clone(), 1.expr());
+                    for (field, field_next) in fields.iter().zip(fields_next.iter()) {
+                        cb.require_equal(
+                            "field is the same up to its last byte",
+                            field_next.clone(),
+                            field.clone(),
+                        );
+                    }
+                });
+            });
+
+            // The last byte of a field is followed by the first one of the next
+            // field, or by padding at the end of a receipt
+            cb.condition(is_last, |cb| {
+                let is_data_items = field(ReceiptField::DataLength) + field(ReceiptField::Data);
+                let is_logs_end =
+                    field(ReceiptField::LogLength) + is_data_items.clone() * items_done.clone();
+                let is_items = field(ReceiptField::TopicLength) + field(ReceiptField::Topic);
+                let expected = [
+                    is_logs_end.clone() * logs_done.clone(),
+                    field(ReceiptField::Status),
+                    field(ReceiptField::CumulativeGasUsed),
+                    is_logs_end * not::expr(logs_done.clone()),
+                    field(ReceiptField::Address),
+                    is_items.clone() * not::expr(items_done.clone()),
+                    is_items * items_done.clone(),
+                    is_data_items * not::expr(items_done),
+                ];
+                cb.condition(is_byte_next.clone(), |cb| {
+                    for (field_next, expected) in fields_next.iter().zip(expected.iter()) {
+                        cb.require_equal(
+                            "next field follows the field",
+                            field_next.clone(),
+                            expected.clone(),
+                        );
+                    }
+                });
+                cb.condition(not::expr(is_byte_next), |cb| {
+                    cb.require_equal(
+                        "the last byte ends a receipt",
+                        expected[ReceiptField::Status as usize].clone(),
+                        1.expr(),
+                    );
+                });
+            });
+
+            cb.gate(meta.query_selector(q_enable))
+        });
+
+        meta.create_gate("receipts last row", |meta| {
+            let q_last = meta.query_selector(q_last);
+            let tx_id_diff = meta.query_advice(tx_id, Rotation::cur()) - max_txs.expr();
+            let tx_id_diff_inv = meta.query_advice(tx_id_diff_inv, Rotation::cur());
+
+            vec![q_last * tx_id_diff.clone() * (1.expr() - tx_id_diff * tx_id_diff_inv)]
+        });
+
+        meta.lookup_any("receipts: field in rw table", |meta| {
+            let cond = meta.query_selector(q_enable) * meta.query_advice(is_last, Rotation::cur());
+            let fields = fields.map(|column| meta.query_advice(column, Rotation::cur()));
+            let is_receipt = sum::expr(
+                ReceiptField::iter()
+                    .filter(|field| field.receipt_field_tag() != 0)
+                    .map(|field| fields[field as usize].clone()),
+            );
+            let [receipt_field_tag, log_field_tag] =
+                [ReceiptField::receipt_field_tag, ReceiptField::log_field_tag].map(|field_tag| {
+                    sum::expr(
+                        ReceiptField::iter()
+                            .map(|field| fields[field as usize].clone() * field_tag(&field).expr()),
+                    )
+                });
+            vec![
+                1.expr(),
+                select::expr(
+                    is_receipt,
+                    RwTableTag::TxReceipt.expr(),
+                    RwTableTag::TxLog.expr(),
+                ),
+                meta.query_advice(tx_id, Rotation::cur()),
+                build_tx_log_expression(
+                    meta.query_advice(index, Rotation::cur()),
+                    log_field_tag,
+                    meta.query_advice(log_id, Rotation::cur()),
+                ),
+                receipt_field_tag,
+                0.expr(),
+                meta.query_advice(value_acc, Rotation::cur()),
+                0.expr(),
+                0.expr(),
+                0.expr(),
+            ]
+            .into_iter()
+            // The rw counter isn't looked up
+            .zip(rw_table.table_exprs(meta).into_iter().skip(1))
+            .map(|(arg, table)| (cond.clone() * arg, table))
+            .collect()
+        });
+
+        meta.lookup_any("receipts: tx after the last one is padding", |meta| {
+            let tx_id_diff = meta.query_advice(tx_id, Rotation::cur()) - max_txs.expr();
+            let tx_id_diff_inv = meta.query_advice(tx_id_diff_inv, Rotation::cur());
+            let cond = meta.query_selector(q_last) * tx_id_diff * tx_id_diff_inv;
+            let input = [
+                meta.query_advice(tx_id, Rotation::cur()) + 1.expr(),
+                TxFieldTag::CallerAddress.expr(),
+                0.expr(),
+                0.expr(),
+            ];
+            let table = [
+                meta.query_advice(tx_table.tx_id, Rotation::cur()),
+                meta.query_fixed(tx_table.tag, Rotation::cur()),
+                meta.query_advice(tx_table.index, Rotation::cur()),
+                meta.query_advice(tx_table.value, Rotation::cur()),
+            ];
+
+            input
+                .into_iter()
+                .zip(table)
+                .map(|(arg, table)| (cond.clone() * arg, table))
+                .collect()
+        });
+
+        meta.lookup_any("receipts: root in keccak table", |meta| {
+            let cond = meta.query_selector(q_last);
+            vec![
+                1.expr(),
+                meta.query_advice(rlc_acc, Rotation::cur()),
+                meta.query_advice(len_acc, Rotation::cur()),
+                meta.query_advice(hash_rlc, Rotation::cur()),
+            ]
+            .into_iter()
+            .zip(keccak_table.table_exprs(meta).into_iter())
+            .map(|(arg, table)| (cond.clone() * arg, table))
+            .collect()
+        });
+
+        Self {
+            max_txs,
+            q_enable,
+            q_first,
+            q_last,
+            is_byte,
+            byte,
+            fields,
+            tx_id,
+            log_id,
+            index,
+            rem,
+            rem_inv,
+            is_last,
+            logs_rem,
+            logs_rem_inv,
+            logs_done,
+            items_rem,
+            items_rem_inv,
+            items_done,
+            tx_id_diff_inv,
+            value_acc,
+            len_acc,
+            rlc_acc,
+            hash_rlc,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Assigns the rows of the encoding of `receipts` between two padding rows
+    /// queried by the gates, padded up to `max_receipts_len` bytes, and copies
+    /// the RLC encoding of the receipts root from `hash_cell`.
+    pub(super) fn assign(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        receipts: &[Receipt],
+        max_receipts_len: usize,
+        challenges: &Challenges<Value<F>>,
+        hash_cell: &AssignedCell<F, F>,
+    ) -> Result<(), Error> {
+        let rows = receipts_rows(receipts);
+        assert!(rows.len() <= max_receipts_len);
+        let last_tx_id = rows.last().map(|row| row.tx_id).unwrap_or_default();
+
+        layouter.assign_region(
+            || "receipts",
+            |mut region| {
+                let mut value_acc = Value::known(F::zero());
+                let mut rlc_acc = Value::known(F::zero());
+                let mut is_last_prev = true;
+                for offset in 0..max_receipts_len + 3 {
+                    let row = offset
+                        .checked_sub(1)
+                        .and_then(|i| rows.get(i))
+                        .filter(|_| offset <= max_receipts_len);
+                    let is_padding = offset == 0 || offset == max_receipts_len + 2;
+                    if !is_padding {
+                        self.q_enable.enable(&mut region, offset)?;
+                    }
+                    if offset == 1 {
+                        self.q_first.enable(&mut region, offset)?;
+                    }
+                    if offset == max_receipts_len + 1 {
+                        self.q_last.enable(&mut region, offset)?;
+                    }
+
+                    let byte = row.map(|row| row.byte).unwrap_or_default();
+                    if let Some(row) = row {
+                        let byte = Value::known(F::from(byte as u64));
+                        let base = if row.field == ReceiptField::Topic {
+                            challenges.evm_word()
+                        } else {
+                            Value::known(F::from(256))
+                        };
+                        value_acc = if is_last_prev {
+                            byte
+                        } else {
+                            value_acc * base + byte
+                        };
+                        rlc_acc = rlc_acc * challenges.keccak_input() + byte;
+                        is_last_prev = row.rem == 0;
+                    }
+                    let tx_id = if is_padding {
+                        0
+                    } else {
+                        row.map(|row| row.tx_id).unwrap_or(last_tx_id)
+                    };
+                    let len = if is_padding {
+                        0
+                    } else {
+                        offset.min(rows.len())
+                    };
+
+                    let mut values = vec![
+                        ("is_byte", self.is_byte, row.is_some() as u64),
+                        ("byte", self.byte, byte as u64),
+                        ("tx_id", self.tx_id, tx_id as u64),
+                        (
+                            "log_id",
+                            self.log_id,
+                            row.map(|row| row.log_id).unwrap_or_default() as u64,
+                        ),
+                        (
+                            "index",
+                            self.index,
+                            row.map(|row| row.index).unwrap_or_default() as u64,
+                        ),
+                        ("rem", self.rem, row.map(|row| row.rem).unwrap_or_default()),
+                        (
+                            "is_last",
+                            self.is_last,
+                            row.map(|row| row.rem == 0).unwrap_or_default() as u64,
+                        ),
+                        (
+                            "logs_rem",
+                            self.logs_rem,
+                            row.map(|row| row.logs_rem).unwrap_or_default(),
+                        ),
+                        (
+                            "logs_done",
+                            self.logs_done,
+                            row.map(|row| row.logs_rem == 0).unwrap_or_default() as u64,
+                        ),
+                        (
+                            "items_rem",
+                            self.items_rem,
+                            row.map(|row| row.items_rem).unwrap_or_default(),
+                        ),
+                        (
+                            "items_done",
+                            self.items_done,
+                            row.map(|row| row.items_rem == 0).unwrap_or_default() as u64,
+                        ),
+                        ("len_acc", self.len_acc, len as u64),
+                    ];
+                    for field in ReceiptField::iter() {
+                        let is_field = row.map(|row| row.field == field).unwrap_or_default();
+                        values.push(("field", self.fields[field as usize], is_field as u64));
+                    }
+                    for (name, column, value) in values {
+                        region.assign_advice(
+                            || format!("receipts {} {}", name, offset),
+                            column,
+                            offset,
+                            || Value::known(F::from(value)),
+                        )?;
+                    }
+
+                    let tx_id_diff = F::from(tx_id as u64) - F::from(self.max_txs as u64);
+                    for (name, column, value) in [
+                        ("rem_inv", self.rem_inv, row.map(|row| row.rem)),
+                        (
+                            "logs_rem_inv",
+                            self.logs_rem_inv,
+                            row.map(|row| row.logs_rem),
+                        ),
+                        (
+                            "items_rem_inv",
+                            self.items_rem_inv,
+                            row.map(|row| row.items_rem),
+                        ),
+                    ] {
+                        let value = F::from(value.unwrap_or_default());
+                        region.assign_advice(
+                            || format!("receipts {} {}", name, offset),
+                            column,
+                            offset,
+                            || Value::known(value.invert().unwrap_or(F::zero())),
+                        )?;
+                    }
+                    region.assign_advice(
+                        || format!("receipts tx_id_diff_inv {}", offset),
+                        self.tx_id_diff_inv,
+                        offset,
+                        || Value::known(tx_id_diff.invert().unwrap_or(F::zero())),
+                    )?;
+
+                    let (value_acc, rlc_acc) = if is_padding {
+                        (Value::known(F::zero()), Value::known(F::zero()))
+                    } else if row.is_some() {
+                        (value_acc, rlc_acc)
+                    } else {
+                        (Value::known(F::zero()), rlc_acc)
+                    };
+                    region.assign_advice(
+                        || format!("receipts value_acc {}", offset),
+                        self.value_acc,
+                        offset,
+                        || value_acc,
+                    )?;
+                    region.assign_advice(
+                        || format!("receipts rlc_acc {}", offset),
+                        self.rlc_acc,
+                        offset,
+                        || rlc_acc,
+                    )?;
+                    if offset == max_receipts_len + 1 {
+                        hash_cell.copy_advice(
+                            || "receipts hash_rlc",
+                            &mut region,
+                            self.hash_rlc,
+                            offset,
+                        )?;
+                    } else {
+                        region.assign_advice(
+                            || format!("receipts hash_rlc {}", offset),
+                            self.hash_rlc,
+                            offset,
+                            || Value::known(F::zero()),
+                        )?;
+                    }
+                }
+
+                Ok(())
+            },
+        )
+    }
+}
@@ -0,0 +1,491 @@
+//! The Precompile circuit fills the precompile table, from which the EVM
+//! circuit looks up the calls to the precompiled contracts which are verified
+//! neither by copying the input (identity) nor by the sig table (ecrecover).
+//!
+//! The circuit doesn't verify any precompiled contract by itself: the calls
+//! are verified by the [`PrecompileVerifier`] plugged into it, which assigns
+//! the cells of each row in its own regions and returns them to be copied
+//! into the table.  As the table isn't assigned anywhere else, a call to a
+//! precompiled contract which the verifier doesn't support is left out of the
+//! table, and its lookup from the EVM circuit fails.  The default verifier
+//! `()` supports none, so the table only holds the all-zero row.
+
+use bus_mapping::{circuit_input_builder::PrecompileEvent, precompile::PrecompileCalls};
+use eth_types::Field;
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{ConstraintSystem, Error, Expression},
+};
+use itertools::Itertools;
+use std::{fmt::Debug, marker::PhantomData};
+
+use crate::{
+    table::{DynamicTableColumns, PrecompileTable},
+    util::{Challenges, SubCircuit, SubCircuitConfig},
+    witness,
+};
+
+/// Number of columns of the precompile table
+pub const PRECOMPILE_TABLE_WIDTH: usize = 7;
+
+/// Return whether the EVM circuit looks up the call from the precompile table.
+pub fn is_precompile_table_call(event: &PrecompileEvent) -> bool {
+    match event.precompile {
+        PrecompileCalls::Identity => false,
+        // A call to ecrecover which runs out of gas needs no lookup.
+        PrecompileCalls::ECRecover => event.is_success && event.ecrecover_sign_data().is_none(),
+        _ => true,
+    }
+}
+
+/// Sub-circuit plugged into the [`PrecompileCircuit`] to verify the calls to
+/// some precompiled contracts.
+pub trait PrecompileVerifier<F: Field>: Clone + Debug + Default {
+    /// Configuration of the verifier.
+    type Config: Clone + Debug;
+
+    /// Configure the verifier.
+    fn configure(
+        meta: &mut ConstraintSystem<F>,
+        challenges: Challenges<Expression<F>>,
+    ) -> Self::Config;
+
+    /// Return whether the calls to `precompile` are verified.
+    fn verifies(&self, precompile: PrecompileCalls) -> bool;
+
+    /// Return the minimum number of rows required to verify `events`.
+    fn min_num_rows(&self, events: &[PrecompileEvent]) -> usize;
+
+    /// Verify `events`, which are all calls to the precompiled contracts this
+    /// verifier supports, and return for each of them the cells of its
+    /// precompile table row, in the order of [`PrecompileTable`]'s columns:
+    /// the address, the input length and RLC, the output length and RLC, the
+    /// required gas and the validity of the input.  The output is the one of
+    /// the precompiled contract for the input, even when the call runs out of
+    /// gas.  The cells are copied into the table, so they must belong to
+    /// columns with equality enabled.
+    fn assign(
+        &self,
+        config: &Self::Config,
+        layouter: &mut impl Layouter<F>,
+        events: &[PrecompileEvent],
+        challenges: &Challenges<Value<F>>,
+    ) -> Result<Vec<[AssignedCell<F, F>; PRECOMPILE_TABLE_WIDTH]>, Error>;
+}
+
+/// The verifier which supports no precompiled contract.
+impl<F: Field> PrecompileVerifier<F> for () {
+    type Config = ();
+
+    fn configure(_: &mut ConstraintSystem<F>, _: Challenges<Expression<F>>) -> Self::Config {}
+
+    fn verifies(&self, _: PrecompileCalls) -> bool {
+        false
+    }
+
+    fn min_num_rows(&self, _: &[PrecompileEvent]) -> usize {
+        0
+    }
+
+    fn assign(
+        &self,
+        _: &Self::Config,
+        _: &mut impl Layouter<F>,
+        _: &[PrecompileEvent],
+        _: &Challenges<Value<F>>,
+    ) -> Result<Vec<[AssignedCell<F, F>; PRECOMPILE_TABLE_WIDTH]>, Error> {
+        Ok(vec![])
+    }
+}
+
+/// Config for PrecompileCircuit
+#[derive(Clone, Debug)]
+pub struct PrecompileCircuitConfig<F: Field, V: PrecompileVerifier<F>> {
+    /// PrecompileTable
+    pub precompile_table: PrecompileTable,
+    /// Config of the verifier
+    pub verifier: V::Config,
+    _marker: PhantomData<F>,
+}
+
+/// Circuit configuration arguments
+pub struct PrecompileCircuitConfigArgs<F: Field> {
+    /// PrecompileTable
+    pub precompile_table: PrecompileTable,
+    /// Challenges
+    pub challenges: Challenges<Expression<F>>,
+}
+
+impl<F: Field, V: PrecompileVerifier<F>> SubCircuitConfig<F> for PrecompileCircuitConfig<F, V> {
+    type ConfigArgs = PrecompileCircuitConfigArgs<F>;
+
+    /// Return a new PrecompileCircuitConfig
+    fn new(
+        meta: &mut ConstraintSystem<F>,
+        Self::ConfigArgs {
+            precompile_table,
+            challenges,
+        }: Self::ConfigArgs,
+    ) -> Self {
+        for column in precompile_table.columns() {
+            meta.enable_equality(column);
+        }
+        let verifier = V::configure(meta, challenges);
+
+        Self {
+            precompile_table,
+            verifier,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F: Field, V: PrecompileVerifier<F>> PrecompileCircuitConfig<F, V> {
+    /// Assign the all-zero row followed by the rows verified by `verifier`.
+    pub fn assign(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        verifier: &V,
+        events: &[PrecompileEvent],
+        challenges: &Challenges<Value<F>>,
+    ) -> Result<(), Error> {
+        let rows = verifier.assign(&self.verifier, layouter, events, challenges)?;
+        if rows.len() != events.len() {
+            log::error!(
+                "precompile verifier returned {} rows for {} calls",
+                rows.len(),
+                events.len()
+            );
+            return Err(Error::Synthesis);
+        }
+
+        layouter.assign_region(
+            || "precompile table",
+            |mut region| {
+                let columns = self.precompile_table.columns();
+                for column in columns.iter() {
+                    region.assign_advice(
+                        || "precompile table all-zero row",
+                        *column,
+                        0,
+                        || Value::known(F::zero()),
+                    )?;
+                }
+                for (offset, cells) in rows.iter().enumerate() {
+                    for (column, cell) in columns.iter().zip_eq(cells) {
+                        cell.copy_advice(
+                            || format!("precompile table row {}", offset + 1),
+                            &mut region,
+                            *column,
+                            offset + 1,
+                        )?;
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+/// Precompile Circuit which fills the precompile table with the calls
+/// verified by `V`
+#[derive(Clone, Debug)]
+pub struct PrecompileCircuit<F: Field, V: PrecompileVerifier<F> = ()> {
+    /// Calls looked up from the precompile table which `verifier` supports
+    pub events: Vec<PrecompileEvent>,
+    /// Verifier of the calls
+    pub verifier: V,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field, V: PrecompileVerifier<F>> PrecompileCircuit<F, V> {
+    /// Return a new PrecompileCircuit verifying the calls among `events`
+    /// which are looked up from the precompile table and supported by
+    /// `verifier`.
+    pub fn new(events: &[PrecompileEvent], verifier: V) -> Self {
+        let events = events
+            .iter()
+            .filter(|event| is_precompile_table_call(event) && verifier.verifies(event.precompile))
+            .cloned()
+            .collect();
+        Self {
+            events,
+            verifier,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F: Field, V: PrecompileVerifier<F>> Default for PrecompileCircuit<F, V> {
+    fn default() -> Self {
+        Self::new(&[], V::default())
+    }
+}
+
+impl<F: Field, V: PrecompileVerifier<F>> SubCircuit<F> for PrecompileCircuit<F, V> {
+    type Config = PrecompileCircuitConfig<F, V>;
+
+    fn new_from_block(block: &witness::Block<F>) -> Self {
+        Self::new(&block.precompile_events, V::default())
+    }
+
+    /// Return the minimum number of rows required to prove the block
+    fn min_num_rows_block(block: &witness::Block<F>) -> usize {
+        let circuit = Self::new_from_block(block);
+        std::cmp::max(
+            circuit.events.len() + 1,
+            circuit.verifier.min_num_rows(&circuit.events),
+        )
+    }
+
+    /// Make the assignments to the PrecompileCircuit
+    fn synthesize_sub(
+        &self,
+        config: &Self::Config,
+        challenges: &Challenges<Value<F>>,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<(), Error> {
+        config.assign(layouter, &self.verifier, &self.events, challenges)
+    }
+}
+
+/// Dev helpers
+#[cfg(any(feature = "test", test))]
+pub mod dev {
+    use super::*;
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        plonk::{Challenge, Circuit},
+    };
+
+    impl<F: Field, V: PrecompileVerifier<F>> Circuit<F> for PrecompileCircuit<F, V> {
+        type Config = (PrecompileCircuitConfig<F, V>, Challenges<Challenge>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let precompile_table = PrecompileTable::construct(meta);
+            let challenges = Challenges::construct(meta);
+            let challenge_exprs = challenges.exprs(meta);
+
+            (
+                PrecompileCircuitConfig::new(
+                    meta,
+                    PrecompileCircuitConfigArgs {
+                        precompile_table,
+                        challenges: challenge_exprs,
+                    },
+                ),
+                challenges,
+            )
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let challenge_values = config.1.values(&mut layouter);
+            self.synthesize_sub(&config.0, &challenge_values, &mut layouter)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::table::LookupTable;
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::{MockProver, VerifyFailure},
+        halo2curves::bn256::Fr,
+        plonk::{Advice, Challenge, Circuit, Column, Fixed},
+        poly::Rotation,
+    };
+
+    /// Verifier of sha256 which trusts the witness
+    #[derive(Clone, Debug, Default)]
+    struct TrustedSha256Verifier;
+
+    impl<F: Field> PrecompileVerifier<F> for TrustedSha256Verifier {
+        type Config = [Column<Advice>; PRECOMPILE_TABLE_WIDTH];
+
+        fn configure(meta: &mut ConstraintSystem<F>, _: Challenges<Expression<F>>) -> Self::Config {
+            let table = PrecompileTable::construct(meta);
+            for column in table.columns() {
+                meta.enable_equality(column);
+            }
+            table.columns().try_into().unwrap()
+        }
+
+        fn verifies(&self, precompile: PrecompileCalls) -> bool {
+            precompile == PrecompileCalls::Sha256
+        }
+
+        fn min_num_rows(&self, events: &[PrecompileEvent]) -> usize {
+            events.len()
+        }
+
+        fn assign(
+            &self,
+            config: &Self::Config,
+            layouter: &mut impl Layouter<F>,
+            events: &[PrecompileEvent],
+            challenges: &Challenges<Value<F>>,
+        ) -> Result<Vec<[AssignedCell<F, F>; PRECOMPILE_TABLE_WIDTH]>, Error> {
+            layouter.assign_region(
+                || "trusted sha256",
+                |mut region| {
+                    events
+                        .iter()
+                        .enumerate()
+                        .map(|(offset, event)| {
+                            let row = PrecompileTable::assignment(event, challenges);
+                            let cells = config
+                                .iter()
+                                .zip(row)
+                                .map(|(column, value)| {
+                                    region.assign_advice(|| "sha256", *column, offset, || value)
+                                })
+                                .collect::<Result<Vec<_>, Error>>()?;
+                            Ok(cells.try_into().unwrap())
+                        })
+                        .collect()
+                },
+            )
+        }
+    }
+
+    /// Circuit looking up the rows of `expected` from the precompile table
+    /// filled by the Precompile circuit.
+    struct LookupCircuit<V: PrecompileVerifier<Fr>> {
+        precompile: PrecompileCircuit<Fr, V>,
+        expected: Vec<PrecompileEvent>,
+    }
+
+    impl<V: PrecompileVerifier<Fr>> Circuit<Fr> for LookupCircuit<V> {
+        type Config = (
+            PrecompileCircuitConfig<Fr, V>,
+            Column<Fixed>,
+            [Column<Advice>; PRECOMPILE_TABLE_WIDTH],
+            Challenges<Challenge>,
+        );
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                precompile: PrecompileCircuit::default(),
+                expected: vec![],
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let (config, challenges) = PrecompileCircuit::<Fr, V>::configure(meta);
+            let q_lookup = meta.fixed_column();
+            let expected: [Column<Advice>; PRECOMPILE_TABLE_WIDTH] =
+                PrecompileTable::construct(meta)
+                    .columns()
+                    .try_into()
+                    .unwrap();
+            meta.lookup_any("expected precompile row", |meta| {
+                let q_lookup = meta.query_fixed(q_lookup, Rotation::cur());
+                let table = config.precompile_table.table_exprs(meta);
+                expected
+                    .iter()
+                    .zip(table)
+                    .map(|(column, table)| {
+                        (
+                            q_lookup.clone() * meta.query_advice(*column, Rotation::cur()),
+                            table,
+                        )
+                    })
+                    .collect()
+            });
+            (config, q_lookup, expected, challenges)
+        }
+
+        fn synthesize(
+            &self,
+            (config, q_lookup, expected, challenges): Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let challenges = challenges.values(&mut layouter);
+            layouter.assign_region(
+                || "expected precompile rows",
+                |mut region| {
+                    for (offset, event) in self.expected.iter().enumerate() {
+                        region.assign_fixed(
+                            || "q_lookup",
+                            q_lookup,
+                            offset,
+                            || Value::known(Fr::from(1)),
+                        )?;
+                        let row = PrecompileTable::assignment(event, &challenges);
+                        for (column, value) in expected.iter().zip(row) {
+                            region.assign_advice(|| "expected", *column, offset, || value)?;
+                        }
+                    }
+                    Ok(())
+                },
+            )?;
+            self.precompile
+                .synthesize_sub(&config, &challenges, &mut layouter)
+        }
+    }
+
+    fn sha256_event(input: &[u8]) -> PrecompileEvent {
+        let precompile = PrecompileCalls::Sha256;
+        PrecompileEvent {
+            call_id: 1,
+            precompile,
+            input: input.to_vec(),
+            output: precompile.execute(input).unwrap(),
+            required_gas: precompile.required_gas(input),
+            is_valid: true,
+            is_success: true,
+        }
+    }
+
+    fn run<V: PrecompileVerifier<Fr>>(
+        events: Vec<PrecompileEvent>,
+        verifier: V,
+    ) -> Result<(), Vec<VerifyFailure>> {
+        let circuit = LookupCircuit {
+            precompile: PrecompileCircuit::new(&events, verifier),
+            expected: events,
+        };
+        let prover = MockProver::<Fr>::run(8, &circuit, vec![]).unwrap();
+        prover.verify()
+    }
+
+    #[test]
+    fn precompile_circuit_verified() {
+        let events = vec![sha256_event(b""), sha256_event(b"abc")];
+        assert_eq!(run(events, TrustedSha256Verifier), Ok(()));
+    }
+
+    #[test]
+    fn precompile_circuit_not_verified() {
+        // No verifier fills the table.
+        assert!(run(vec![sha256_event(b"abc")], ()).is_err());
+
+        // The verifier doesn't support bn256 add.
+        let precompile = PrecompileCalls::Bn256Add;
+        let input = [0u8; 128];
+        let event = PrecompileEvent {
+            call_id: 2,
+            precompile,
+            input: input.to_vec(),
+            output: precompile.execute(&input).unwrap(),
+            required_gas: precompile.required_gas(&input),
+            is_valid: true,
+            is_success: true,
+        };
+        let circuit = PrecompileCircuit::<Fr, _>::new(&[event.clone()], TrustedSha256Verifier);
+        assert!(circuit.events.is_empty());
+        assert!(run(vec![sha256_event(b"abc"), event], TrustedSha256Verifier).is_err());
+    }
+}
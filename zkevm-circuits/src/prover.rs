@@ -0,0 +1,162 @@
+//! Proving and verification of the circuits with the KZG commitment scheme on
+//! BN256 and the SHPLONK multi-opening argument.
+//!
+//! The functions are generic over the [`Circuit`], so they can be used for the
+//! [`SuperCircuit`](crate::super_circuit::SuperCircuit) as well as for each
+//! [`SubCircuit`](crate::util::SubCircuit) proved on its own.
+
+use halo2_proofs::{
+    halo2curves::bn256::{Bn256, Fr, G1Affine},
+    plonk::{self, Circuit, Error, ProvingKey, VerifyingKey},
+    poly::{
+        commitment::ParamsProver,
+        kzg::{
+            commitment::{KZGCommitmentScheme, ParamsKZG, ParamsVerifierKZG},
+            multiopen::{ProverSHPLONK, VerifierSHPLONK},
+            strategy::SingleStrategy,
+        },
+    },
+    transcript::{
+        Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
+    },
+};
+use rand::RngCore;
+
+mod key_cache;
+
+pub use key_cache::KeyCache;
+
+/// Generate the parameters of the KZG commitment scheme for circuits of
+/// `2^degree` rows.
+///
+/// The parameters generated from a random toxic waste are only suitable for
+/// testing; production parameters come from a trusted setup ceremony.
+pub fn gen_params(degree: u32, rng: impl RngCore) -> ParamsKZG<Bn256> {
+    ParamsKZG::<Bn256>::setup(degree, rng)
+}
+
+/// Returns the parameters needed to verify the proofs.
+pub fn verifier_params(params: &ParamsKZG<Bn256>) -> ParamsVerifierKZG<Bn256> {
+    params.verifier_params().clone()
+}
+
+/// Generate the verifying key of the circuit.  The witness of the circuit
+/// isn't used, so a circuit built from an empty block works as well, as long
+/// as it has the same configuration.
+pub fn gen_vk<C: Circuit<Fr>>(
+    params: &ParamsKZG<Bn256>,
+    circuit: &C,
+) -> Result<VerifyingKey<G1Affine>, Error> {
+    plonk::keygen_vk(params, circuit)
+}
+
+/// Generate the proving key of the circuit, which contains its verifying key.
+pub fn gen_pk<C: Circuit<Fr>>(
+    params: &ParamsKZG<Bn256>,
+    circuit: &C,
+) -> Result<ProvingKey<G1Affine>, Error> {
+    let vk = gen_vk(params, circuit)?;
+    plonk::keygen_pk(params, vk, circuit)
+}
+
+/// Create a proof of the circuit for the public inputs of its instance
+/// columns.
+pub fn create_proof<C: Circuit<Fr>>(
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    circuit: C,
+    instance: &[Vec<Fr>],
+    rng: impl RngCore,
+) -> Result<Vec<u8>, Error> {
+    let instance: Vec<&[Fr]> = instance.iter().map(|column| column.as_slice()).collect();
+    let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+    plonk::create_proof::<KZGCommitmentScheme<Bn256>, ProverSHPLONK<'_, Bn256>, _, _, _, _>(
+        params,
+        pk,
+        &[circuit],
+        &[&instance],
+        rng,
+        &mut transcript,
+    )?;
+
+    Ok(transcript.finalize())
+}
+
+/// Verify a proof created by [`create_proof`] for the public inputs of the
+/// instance columns.
+pub fn verify_proof(
+    params: &ParamsVerifierKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    proof: &[u8],
+    instance: &[Vec<Fr>],
+) -> Result<(), Error> {
+    let instance: Vec<&[Fr]> = instance.iter().map(|column| column.as_slice()).collect();
+    let mut transcript = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(proof);
+    plonk::verify_proof::<KZGCommitmentScheme<Bn256>, VerifierSHPLONK<'_, Bn256>, _, _, _>(
+        params,
+        vk,
+        SingleStrategy::new(params),
+        &[&instance],
+        &mut transcript,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{Advice, Column, ConstraintSystem, Instance},
+    };
+    use rand::SeedableRng;
+    use rand_xorshift::XorShiftRng;
+
+    /// Circuit exposing its witness as the public input.
+    #[derive(Clone, Default)]
+    pub(super) struct PublicWitnessCircuit(pub(super) Value<Fr>);
+
+    impl Circuit<Fr> for PublicWitnessCircuit {
+        type Config = (Column<Advice>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let advice = meta.advice_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(advice);
+            meta.enable_equality(instance);
+            (advice, instance)
+        }
+
+        fn synthesize(
+            &self,
+            (advice, instance): Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let cell = layouter.assign_region(
+                || "witness",
+                |mut region| region.assign_advice(|| "witness", advice, 0, || self.0),
+            )?;
+            layouter.constrain_instance(cell.cell(), instance, 0)
+        }
+    }
+
+    #[test]
+    fn prove_and_verify() {
+        let mut rng = XorShiftRng::seed_from_u64(1);
+        let params = gen_params(4, &mut rng);
+        let pk = gen_pk(&params, &PublicWitnessCircuit::default()).unwrap();
+
+        let circuit = PublicWitnessCircuit(Value::known(Fr::from(42)));
+        let proof = create_proof(&params, &pk, circuit, &[vec![Fr::from(42)]], &mut rng).unwrap();
+
+        let verifier_params = verifier_params(&params);
+        assert!(verify_proof(&verifier_params, pk.get_vk(), &proof, &[vec![Fr::from(42)]]).is_ok());
+        assert!(
+            verify_proof(&verifier_params, pk.get_vk(), &proof, &[vec![Fr::from(43)]]).is_err()
+        );
+    }
+}
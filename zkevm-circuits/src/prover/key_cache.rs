@@ -0,0 +1,505 @@
+//! Cache of the parameters and the circuit keys on disk.
+
+use super::{gen_params, gen_vk};
+use bus_mapping::circuit_input_builder::CircuitsParams;
+use halo2_proofs::{
+    circuit::{FloorPlanner, Value},
+    halo2curves::{
+        bn256::{Bn256, Fr, G1Affine},
+        group::ff::PrimeField,
+    },
+    plonk::{
+        self, Advice, Any, Assigned, Assignment, Challenge, Circuit, Column, ConstraintSystem,
+        Error, Fixed, Instance, ProvingKey, Selector, VerifyingKey,
+    },
+    poly::{commitment::Params, kzg::commitment::ParamsKZG},
+    SerdeFormat,
+};
+use rand::RngCore;
+use sha3::{Digest, Keccak256};
+use std::{
+    any::type_name,
+    env,
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+};
+
+/// Environment variable with the directory of the [`KeyCache::from_env`].
+const KEYS_DIR_ENV: &str = "KEYS_DIR";
+
+/// Format of the serialized keys.
+const KEY_FORMAT: SerdeFormat = SerdeFormat::RawBytes;
+
+/// Directory where the parameters and the keys are serialized, so that they
+/// are generated only once across runs.
+///
+/// The parameters are stored by degree, and the verifying and proving keys by
+/// circuit type, [`CircuitsParams`] and degree.  Each key is stored along with
+/// a hash of everything it commits to: the pinned description of the
+/// constraint system of its circuit, and the assignment of its fixed columns,
+/// selectors and copy constraints.  A key of a circuit whose configuration or
+/// fixed assignment has changed since, for instance the EVM circuit of another
+/// block, is detected as stale and generated again, so the circuits whose
+/// fixed columns depend on the witness can be cached as well.
+#[derive(Clone, Debug)]
+pub struct KeyCache {
+    dir: PathBuf,
+}
+
+impl KeyCache {
+    /// Cache in the directory `dir`, which is created on the first write.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Cache in the directory of the `KEYS_DIR` environment variable, or in
+    /// `zkevm-keys` under the temporary directory when it's not set.
+    pub fn from_env() -> Self {
+        Self::new(
+            env::var_os(KEYS_DIR_ENV)
+                .map(PathBuf::from)
+                .unwrap_or_else(|| env::temp_dir().join("zkevm-keys")),
+        )
+    }
+
+    /// Load the parameters for circuits of `2^degree` rows, generating them
+    /// from `rng` when they aren't cached yet.
+    pub fn load_params(&self, degree: u32, rng: impl RngCore) -> io::Result<ParamsKZG<Bn256>> {
+        let path = self.dir.join(format!("params_k{}.bin", degree));
+        if path.exists() {
+            return ParamsKZG::<Bn256>::read(&mut BufReader::new(File::open(path)?));
+        }
+
+        let params = gen_params(degree, rng);
+        write_file(&path, |writer| params.write(writer))?;
+        Ok(params)
+    }
+
+    /// Load the verifying key of the circuit, generating it when it isn't
+    /// cached yet or when the cached one is stale.  The `params` must be the
+    /// ones loaded from this cache.
+    pub fn load_vk<C: Circuit<Fr>>(
+        &self,
+        params: &ParamsKZG<Bn256>,
+        circuits_params: &CircuitsParams,
+        circuit: &C,
+    ) -> Result<VerifyingKey<G1Affine>, Error> {
+        let circuit_hash = circuit_hash(circuit)?;
+        self.load_vk_with_hash(params, circuits_params, circuit, &circuit_hash)
+    }
+
+    /// Load the proving key of the circuit, generating it from its verifying
+    /// key when it isn't cached yet or when the cached one is stale.  The
+    /// `params` must be the ones loaded from this cache.
+    pub fn load_pk<C: Circuit<Fr>>(
+        &self,
+        params: &ParamsKZG<Bn256>,
+        circuits_params: &CircuitsParams,
+        circuit: &C,
+    ) -> Result<ProvingKey<G1Affine>, Error> {
+        let circuit_hash = circuit_hash(circuit)?;
+        let path = self
+            .key_path::<C>(params.k(), circuits_params)
+            .with_extension("pk");
+        if let Some(pk) = read_key(&path, &circuit_hash, |reader| {
+            ProvingKey::read::<_, C>(reader, KEY_FORMAT)
+        })? {
+            return Ok(pk);
+        }
+
+        let vk = self.load_vk_with_hash(params, circuits_params, circuit, &circuit_hash)?;
+        log::info!("generating proving key {}", path.display());
+        let pk = plonk::keygen_pk(params, vk, circuit)?;
+        write_file(&path, |writer| {
+            writer.write_all(&circuit_hash)?;
+            pk.write(writer, KEY_FORMAT)
+        })?;
+        Ok(pk)
+    }
+
+    fn load_vk_with_hash<C: Circuit<Fr>>(
+        &self,
+        params: &ParamsKZG<Bn256>,
+        circuits_params: &CircuitsParams,
+        circuit: &C,
+        circuit_hash: &[u8],
+    ) -> Result<VerifyingKey<G1Affine>, Error> {
+        let path = self
+            .key_path::<C>(params.k(), circuits_params)
+            .with_extension("vk");
+        if let Some(vk) = read_key(&path, circuit_hash, |reader| {
+            VerifyingKey::read::<_, C>(reader, KEY_FORMAT)
+        })? {
+            return Ok(vk);
+        }
+
+        log::info!("generating verifying key {}", path.display());
+        let vk = gen_vk(params, circuit)?;
+        write_file(&path, |writer| {
+            writer.write_all(circuit_hash)?;
+            vk.write(writer, KEY_FORMAT)
+        })?;
+        Ok(vk)
+    }
+
+    /// The file name starts with the name of the circuit type to be readable,
+    /// and ends with a hash of everything the key depends on besides the
+    /// circuit hash stored in the file.  It has no extension, which is the
+    /// kind of key.
+    fn key_path<C: Circuit<Fr>>(&self, degree: u32, circuits_params: &CircuitsParams) -> PathBuf {
+        let circuit_type = type_name::<C>();
+        let circuit_name = circuit_type
+            .split('<')
+            .next()
+            .and_then(|path| path.rsplit("::").next())
+            .unwrap_or(circuit_type);
+        let hash = Keccak256::new()
+            .chain_update(circuit_type)
+            .chain_update(format!("{:?}", circuits_params))
+            .chain_update(degree.to_le_bytes())
+            .finalize();
+        let hash: String = hash[..8].iter().map(|b| format!("{:02x}", b)).collect();
+
+        self.dir
+            .join(format!("{}_k{}_{}", circuit_name, degree, hash))
+    }
+}
+
+/// Hash of everything the keys of the circuit commit to: the pinned
+/// description of its constraint system, and the assignment of its fixed
+/// columns, selectors and copy constraints, which is synthesized the same way
+/// as in the key generation.
+fn circuit_hash<C: Circuit<Fr>>(circuit: &C) -> Result<Vec<u8>, Error> {
+    let mut cs = ConstraintSystem::default();
+    let config = C::configure(&mut cs);
+    let mut hasher = FixedAssignmentHasher(Keccak256::new());
+    hasher.update(format!("{:?}", cs.pinned()));
+    C::FloorPlanner::synthesize(&mut hasher, circuit, config, cs.constants().clone())?;
+    Ok(hasher.0.finalize().to_vec())
+}
+
+/// Hasher of the assignments that don't depend on the witness, in the order
+/// the floor planner makes them.
+struct FixedAssignmentHasher(Keccak256);
+
+impl FixedAssignmentHasher {
+    fn update(&mut self, data: impl AsRef<[u8]>) {
+        self.0.update(data);
+    }
+
+    fn update_value(&mut self, value: Value<Assigned<Fr>>) {
+        value.map(|value| self.0.update(value.evaluate().to_repr()));
+    }
+}
+
+impl Assignment<Fr> for FixedAssignmentHasher {
+    fn enter_region<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn annotate_column<A, AR>(&mut self, _: A, _: Column<Any>)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+    }
+
+    fn exit_region(&mut self) {}
+
+    fn enable_selector<A, AR>(&mut self, _: A, selector: &Selector, row: usize) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.update(format!("selector {:?} {}", selector, row));
+        Ok(())
+    }
+
+    fn query_instance(&self, _: Column<Instance>, _: usize) -> Result<Value<Fr>, Error> {
+        Ok(Value::unknown())
+    }
+
+    fn assign_advice<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        _: Column<Advice>,
+        _: usize,
+        _: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<Fr>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        Ok(())
+    }
+
+    fn assign_fixed<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        column: Column<Fixed>,
+        row: usize,
+        to: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<Fr>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.update(format!("fixed {:?} {}", column, row));
+        self.update_value(to().map(|value| value.into()));
+        Ok(())
+    }
+
+    fn copy(
+        &mut self,
+        left_column: Column<Any>,
+        left_row: usize,
+        right_column: Column<Any>,
+        right_row: usize,
+    ) -> Result<(), Error> {
+        self.update(format!(
+            "copy {:?} {} {:?} {}",
+            left_column, left_row, right_column, right_row
+        ));
+        Ok(())
+    }
+
+    fn fill_from_row(
+        &mut self,
+        column: Column<Fixed>,
+        row: usize,
+        to: Value<Assigned<Fr>>,
+    ) -> Result<(), Error> {
+        self.update(format!("fill {:?} {}", column, row));
+        self.update_value(to);
+        Ok(())
+    }
+
+    fn get_challenge(&self, _: Challenge) -> Value<Fr> {
+        Value::unknown()
+    }
+
+    fn push_namespace<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn pop_namespace(&mut self, _: Option<String>) {}
+}
+
+/// Read the key at `path`, unless it doesn't exist or it was generated for a
+/// circuit with a different hash.
+fn read_key<K>(
+    path: &Path,
+    circuit_hash: &[u8],
+    read: impl FnOnce(&mut BufReader<File>) -> io::Result<K>,
+) -> io::Result<Option<K>> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err),
+    };
+    let mut reader = BufReader::new(file);
+
+    let mut stored_circuit_hash = vec![0; circuit_hash.len()];
+    reader.read_exact(&mut stored_circuit_hash)?;
+    if stored_circuit_hash != circuit_hash {
+        log::warn!("stale key {}", path.display());
+        return Ok(None);
+    }
+
+    read(&mut reader).map(Some)
+}
+
+/// Write the file through a temporary one, so that a concurrent reader never
+/// sees it partially written.
+fn write_file(
+    path: &Path,
+    write: impl FnOnce(&mut BufWriter<File>) -> io::Result<()>,
+) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(format!(".tmp{}", std::process::id()));
+    let mut writer = BufWriter::new(File::create(&tmp_path)?);
+    write(&mut writer)?;
+    writer.flush()?;
+    drop(writer);
+
+    fs::rename(tmp_path, path)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::prover::{create_proof, test::PublicWitnessCircuit, verifier_params, verify_proof};
+    use halo2_proofs::circuit::{Layouter, SimpleFloorPlanner};
+    use rand::SeedableRng;
+    use rand_xorshift::XorShiftRng;
+
+    fn vk_bytes(vk: &VerifyingKey<G1Affine>) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        vk.write(&mut bytes, KEY_FORMAT).unwrap();
+        bytes
+    }
+
+    fn pk_bytes(pk: &ProvingKey<G1Affine>) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        pk.write(&mut bytes, KEY_FORMAT).unwrap();
+        bytes
+    }
+
+    /// Circuit with a fixed column assigned from its witness, like the EVM
+    /// circuit with the tags of its fixed table detected from the block.
+    #[derive(Clone, Default)]
+    struct FixedWitnessCircuit(u64);
+
+    impl Circuit<Fr> for FixedWitnessCircuit {
+        type Config = Column<Fixed>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            meta.fixed_column()
+        }
+
+        fn synthesize(
+            &self,
+            fixed: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "fixed",
+                |mut region| {
+                    region.assign_fixed(|| "fixed", fixed, 0, || Value::known(Fr::from(self.0)))
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn reload_keys() {
+        let dir = env::temp_dir().join(format!("zkevm-key-cache-test-{}", std::process::id()));
+        let cache = KeyCache::new(&dir);
+        let circuits_params = CircuitsParams::default();
+        let circuit = PublicWitnessCircuit::default();
+
+        let params = cache.load_params(4, XorShiftRng::seed_from_u64(1)).unwrap();
+        let vk = cache.load_vk(&params, &circuits_params, &circuit).unwrap();
+        let pk = cache.load_pk(&params, &circuits_params, &circuit).unwrap();
+
+        // The parameters are read back instead of generated from the new rng.
+        let reloaded_params = cache.load_params(4, XorShiftRng::seed_from_u64(2)).unwrap();
+        let mut params_bytes = Vec::new();
+        params.write(&mut params_bytes).unwrap();
+        let mut reloaded_params_bytes = Vec::new();
+        reloaded_params.write(&mut reloaded_params_bytes).unwrap();
+        assert_eq!(params_bytes, reloaded_params_bytes);
+
+        // The proving key is read back instead of generated.
+        let pk_path = cache
+            .key_path::<PublicWitnessCircuit>(4, &circuits_params)
+            .with_extension("pk");
+        assert!(pk_path.exists());
+        let reloaded_pk = cache
+            .load_pk(&reloaded_params, &circuits_params, &circuit)
+            .unwrap();
+        assert_eq!(pk_bytes(&pk), pk_bytes(&reloaded_pk));
+        assert_eq!(vk_bytes(&vk), vk_bytes(reloaded_pk.get_vk()));
+
+        let mut rng = XorShiftRng::seed_from_u64(3);
+        let instance = vec![vec![Fr::from(42)]];
+        let circuit = PublicWitnessCircuit(Value::known(Fr::from(42)));
+        let proof =
+            create_proof(&reloaded_params, &reloaded_pk, circuit, &instance, &mut rng).unwrap();
+        assert!(verify_proof(
+            &verifier_params(&reloaded_params),
+            reloaded_pk.get_vk(),
+            &proof,
+            &instance
+        )
+        .is_ok());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn regenerate_stale_vk() {
+        let dir = env::temp_dir().join(format!("zkevm-key-cache-stale-{}", std::process::id()));
+        let cache = KeyCache::new(&dir);
+        let circuits_params = CircuitsParams::default();
+        let circuit = PublicWitnessCircuit::default();
+
+        let params = cache.load_params(4, XorShiftRng::seed_from_u64(1)).unwrap();
+        let vk = cache.load_vk(&params, &circuits_params, &circuit).unwrap();
+
+        // Overwrite the stored hash as if the constraint system had changed.
+        let path = cache
+            .key_path::<PublicWitnessCircuit>(4, &circuits_params)
+            .with_extension("vk");
+        let mut bytes = fs::read(&path).unwrap();
+        bytes[0] ^= 1;
+        fs::write(&path, bytes).unwrap();
+
+        let regenerated_vk = cache.load_vk(&params, &circuits_params, &circuit).unwrap();
+        assert_eq!(vk_bytes(&vk), vk_bytes(&regenerated_vk));
+        assert_eq!(
+            fs::read(&path).unwrap()[..32],
+            circuit_hash(&circuit).unwrap()[..]
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn regenerate_keys_of_other_fixed_assignment() {
+        let dir = env::temp_dir().join(format!("zkevm-key-cache-fixed-{}", std::process::id()));
+        let cache = KeyCache::new(&dir);
+        let circuits_params = CircuitsParams::default();
+
+        let params = cache.load_params(4, XorShiftRng::seed_from_u64(1)).unwrap();
+        let vk_1 = cache
+            .load_vk(&params, &circuits_params, &FixedWitnessCircuit(1))
+            .unwrap();
+        assert_eq!(
+            vk_bytes(&vk_1),
+            vk_bytes(&gen_vk(&params, &FixedWitnessCircuit(1)).unwrap())
+        );
+
+        // Same constraint system but another fixed column, so the cached keys
+        // of the first circuit are stale.
+        let vk_2 = cache
+            .load_vk(&params, &circuits_params, &FixedWitnessCircuit(2))
+            .unwrap();
+        assert_eq!(
+            vk_bytes(&vk_2),
+            vk_bytes(&gen_vk(&params, &FixedWitnessCircuit(2)).unwrap())
+        );
+        assert_ne!(vk_bytes(&vk_1), vk_bytes(&vk_2));
+
+        let pk_2 = cache
+            .load_pk(&params, &circuits_params, &FixedWitnessCircuit(2))
+            .unwrap();
+        let pk_1 = cache
+            .load_pk(&params, &circuits_params, &FixedWitnessCircuit(1))
+            .unwrap();
+        assert_eq!(vk_bytes(&vk_1), vk_bytes(pk_1.get_vk()));
+        assert_eq!(vk_bytes(&vk_2), vk_bytes(pk_2.get_vk()));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
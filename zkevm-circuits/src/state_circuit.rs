@@ -8,13 +8,13 @@ mod random_linear_combination;
 mod test;
 
 use crate::{
-    evm_circuit::param::N_BYTES_WORD,
+    evm_circuit::{param::N_BYTES_WORD, util::RandomLinearCombination},
     table::{LookupTable, MptTable, RwTable, RwTableTag},
     util::{Challenges, Expr, SubCircuit, SubCircuitConfig},
     witness::{self, MptUpdates, Rw, RwMap},
 };
 use constraint_builder::{ConstraintBuilder, Queries};
-use eth_types::{Address, Field};
+use eth_types::{Address, Field, ToLittleEndian};
 use gadgets::binary_number::{BinaryNumberChip, BinaryNumberConfig};
 use halo2_proofs::{
     circuit::{Layouter, Region, SimpleFloorPlanner, Value},
@@ -192,7 +192,12 @@ impl<F: Field> StateCircuitConfig<F> {
         let rows = rows.iter();
         let prev_rows = once(None).chain(rows.clone().map(Some));
 
-        let mut state_root = randomness.map(|_| F::zero());
+        let mut state_root = randomness.map(|randomness| {
+            RandomLinearCombination::random_linear_combine(
+                updates.old_root().to_le_bytes(),
+                randomness,
+            )
+        });
 
         for (offset, (row, prev_row)) in rows.zip(prev_rows).enumerate() {
             if offset >= padding_length {
@@ -359,6 +364,11 @@ impl<F: Field> StateCircuit<F> {
     pub fn new(rw_map: RwMap, n_rows: usize) -> Self {
         let rows = rw_map.table_assignments();
         let updates = MptUpdates::mock_from(&rows);
+        Self::new_with_updates(rows, updates, n_rows)
+    }
+
+    /// make a new state circuit from the rows and their MPT updates
+    pub(crate) fn new_with_updates(rows: Vec<Rw>, updates: MptUpdates, n_rows: usize) -> Self {
         Self {
             rows,
             updates,
@@ -370,12 +380,15 @@ impl<F: Field> StateCircuit<F> {
     }
 }
 
-#[cfg(any(feature = "test", test))]
 impl<F: Field> SubCircuit<F> for StateCircuit<F> {
     type Config = StateCircuitConfig<F>;
 
     fn new_from_block(block: &witness::Block<F>) -> Self {
-        Self::new(block.rws.clone(), block.circuits_params.max_rws)
+        Self::new_with_updates(
+            block.rws.table_assignments(),
+            block.mpt_updates.clone(),
+            block.circuits_params.max_rws,
+        )
     }
 
     /// Return the minimum number of rows required to prove the block
@@ -540,6 +553,11 @@ fn queries<F: Field>(meta: &mut VirtualCells<'_, F>, c: &StateCircuitConfig<F>)
                 + meta.query_advice(first_different_limb.bits[1], Rotation::cur())
                 + meta.query_advice(first_different_limb.bits[2], Rotation::cur()))
             + final_bits_sum.clone() * (1.expr() - final_bits_sum),
+        // this isn't binary either! only 0 if all bits are 0, i.e. the first different limb is
+        // the tag.
+        is_tag_unchanged: (0..5)
+            .map(|idx| meta.query_advice(first_different_limb.bits[idx], Rotation::cur()))
+            .fold(0.expr(), |acc, bit| acc + bit),
         address: MpiQueries::new(meta, c.sort_keys.address),
         storage_key: RlcQueries::new(meta, c.sort_keys.storage_key),
         initial_value: meta.query_advice(c.initial_value, Rotation::cur()),
@@ -584,7 +602,7 @@ mod state_circuit_stats {
         // and querying the step height for each possible execution state (only those
         // implemented will return a Some value).
         let mut meta = ConstraintSystem::<Fr>::default();
-        let circuit = EvmCircuit::configure(&mut meta);
+        let (circuit, _) = EvmCircuit::configure(&mut meta);
 
         let mut implemented_states = Vec::new();
         for state in ExecutionState::iter() {
@@ -6,7 +6,7 @@ use super::{
 use crate::util::Expr;
 use crate::{
     evm_circuit::{param::N_BYTES_WORD, util::not},
-    table::{AccountFieldTag, ProofType, RwTableTag},
+    table::{AccountFieldTag, ProofType, RwTableTag, TxReceiptFieldTag},
 };
 use eth_types::Field;
 use gadgets::binary_number::BinaryNumberConfig;
@@ -51,6 +51,7 @@ pub struct Queries<F: Field> {
     pub tag_bits: [Expression<F>; 4],
     pub id: MpiQueries<F, N_LIMBS_ID>,
     pub is_tag_and_id_unchanged: Expression<F>,
+    pub is_tag_unchanged: Expression<F>,
     pub address: MpiQueries<F, N_LIMBS_ACCOUNT_ADDRESS>,
     pub storage_key: RlcQueries<F, N_BYTES_WORD>,
     pub initial_value: Expression<F>,
@@ -131,6 +132,9 @@ impl<F: Field> ConstraintBuilder<F> {
         self.condition(q.tag_matches(RwTableTag::TxLog), |cb| {
             cb.build_tx_log_constraints(q)
         });
+        self.condition(q.tag_matches(RwTableTag::TxReceipt), |cb| {
+            cb.build_tx_receipt_constraints(q)
+        });
     }
 
     fn build_general_constraints(&mut self, q: &Queries<F>) {
@@ -360,13 +364,22 @@ impl<F: Field> ConstraintBuilder<F> {
     }
 
     fn build_account_destructed_constraints(&mut self, q: &Queries<F>) {
-        self.require_zero("id is 0 for AccountDestructed", q.id());
         self.require_zero("field_tag is 0 for AccountDestructed", q.field_tag());
         self.require_zero(
             "storage_key is 0 for AccountDestructed",
             q.rw_table.storage_key.clone(),
         );
-        // TODO: Missing constraints
+        self.require_boolean("AccountDestructed value is boolean", q.value());
+        self.require_zero(
+            "initial AccountDestructed value is false",
+            q.initial_value(),
+        );
+
+        self.require_equal(
+            "state_root is unchanged for AccountDestructed",
+            q.state_root(),
+            q.state_root_prev(),
+        );
     }
 
     fn build_call_context_constraints(&mut self, q: &Queries<F>) {
@@ -394,6 +407,12 @@ impl<F: Field> ConstraintBuilder<F> {
             1.expr(),
         );
         self.require_zero("initial TxLog value is 0", q.initial_value());
+        self.condition(q.is_tag_and_id_unchanged.clone(), |cb| {
+            cb.require_boolean(
+                "if previous row is also TxLog with unchanged tx id, log_id change is 0 or 1",
+                q.tx_log_id() - q.tx_log_id_prev(),
+            )
+        });
 
         self.require_equal(
             "state_root is unchanged for TxLog",
@@ -403,8 +422,37 @@ impl<F: Field> ConstraintBuilder<F> {
     }
 
     fn build_tx_receipt_constraints(&mut self, q: &Queries<F>) {
-        // TODO: implement TxReceipt constraints
-        self.require_equal("TxReceipt rows not implemented", 1.expr(), 0.expr());
+        // The cumulative gas used is not compared across tx_ids here: EndTx reads the
+        // CumulativeGasUsed of the previous tx and writes its own as that value plus
+        // the gas used, so write-once receipt fields are enough to make it
+        // monotonic.
+        self.require_zero("address is 0 for TxReceipt", q.rw_table.address.clone());
+        self.require_zero(
+            "storage_key is 0 for TxReceipt",
+            q.rw_table.storage_key.clone(),
+        );
+        self.require_in_set(
+            "field_tag in TxReceiptFieldTag range",
+            q.field_tag(),
+            set::<F, TxReceiptFieldTag>(),
+        );
+        self.require_zero("initial TxReceipt value is 0", q.initial_value());
+        self.require_zero(
+            "TxReceipt fields are written at most once",
+            q.not_first_access.clone() * q.is_write(),
+        );
+        // Not normalized, since it is only used to switch the constraint on and off.
+        self.condition(
+            (q.field_tag() - TxReceiptFieldTag::CumulativeGasUsed.expr())
+                * (q.field_tag() - TxReceiptFieldTag::LogLength.expr()),
+            |cb| cb.require_boolean("PostStateOrStatus value is boolean", q.value()),
+        );
+        self.condition(q.is_tag_unchanged.clone(), |cb| {
+            cb.require_boolean(
+                "if previous row is also TxReceipt, tx_id change is 0 or 1",
+                q.id_change(),
+            )
+        });
 
         self.require_equal(
             "state_root is unchanged for TxReceipt",
@@ -9,14 +9,19 @@
 //! The current implementation contains the following circuits:
 //!
 //! - [x] EVM Circuit
-//! - [ ] State Circuit
+//! - [x] State Circuit
 //! - [x] Tx Circuit
 //! - [x] Bytecode Circuit
 //! - [x] Copy Circuit
 //! - [x] Exponentiation Circuit
-//! - [ ] Keccak Circuit
-//! - [ ] MPT Circuit
+//! - [x] Keccak Circuit
+//! - [x] MPT Circuit
 //! - [x] PublicInputs Circuit
+//! - [x] Precompile Circuit
+//!
+//! The Precompile Circuit is built without a verifier, so the calls to the
+//! precompiled contracts looked up from the Precompile Table (all but identity
+//! and the ecrecover calls verified by the Sig Table) can't be proven yet.
 //!
 //! And the following shared tables, with the circuits that use them:
 //!
@@ -25,10 +30,10 @@
 //!   - [x] EVM Circuit
 //! - [x] Exponentiation Table
 //!   - [x] EVM Circuit
-//! - [ ] Rw Table
-//!   - [ ] State Circuit
-//!   - [ ] EVM Circuit
-//!   - [ ] Copy Circuit
+//! - [x] Rw Table
+//!   - [x] State Circuit
+//!   - [x] EVM Circuit
+//!   - [x] Copy Circuit
 //! - [x] Tx Table
 //!   - [x] Tx Circuit
 //!   - [x] EVM Circuit
@@ -42,15 +47,22 @@
 //!   - [ ] EVM Circuit
 //!   - [x] PublicInputs Circuit
 //! - [ ] MPT Table
-//!   - [ ] MPT Circuit
+//!   - [x] MPT Circuit
 //!   - [ ] State Circuit
 //! - [x] Keccak Table
-//!   - [ ] Keccak Circuit
-//!   - [ ] EVM Circuit
+//!   - [x] Keccak Circuit
+//!   - [x] EVM Circuit
 //!   - [x] Bytecode Circuit
 //!   - [x] Tx Circuit
-//!   - [ ] MPT Circuit
+//!   - [x] MPT Circuit
+//! - [x] Sig Table
+//!   - [x] Tx Circuit
+//!   - [x] EVM Circuit
+//! - [x] Precompile Table
+//!   - [x] Precompile Circuit
+//!   - [x] EVM Circuit
 
+use crate::bloom_circuit::{BloomCircuit, BloomCircuitConfig, BloomCircuitConfigArgs};
 use crate::bytecode_circuit::bytecode_unroller::{
     BytecodeCircuit, BytecodeCircuitConfig, BytecodeCircuitConfigArgs,
 };
@@ -60,25 +72,28 @@ use crate::exp_circuit::{ExpCircuit, ExpCircuitConfig};
 use crate::keccak_circuit::keccak_packed_multi::{
     KeccakCircuit, KeccakCircuitConfig, KeccakCircuitConfigArgs,
 };
-use crate::pi_circuit::{PiCircuit, PiCircuitConfig, PiCircuitConfigArgs};
+use crate::mpt_circuit::{MptCircuit, MptCircuitConfig, MptCircuitConfigArgs};
+use crate::pi_circuit::{receipts_commitment, PiCircuit, PiCircuitConfig, PiCircuitConfigArgs};
+use crate::precompile_circuit::{
+    PrecompileCircuit, PrecompileCircuitConfig, PrecompileCircuitConfigArgs,
+};
 use crate::state_circuit::{StateCircuit, StateCircuitConfig, StateCircuitConfigArgs};
 use crate::table::{
-    BlockTable, BytecodeTable, CopyTable, ExpTable, KeccakTable, MptTable, RwTable, TxTable,
+    BlockTable, BloomTable, BytecodeTable, CopyTable, ExpTable, KeccakTable, MptTable,
+    PrecompileTable, RwTable, SigTable, TxTable,
 };
 use crate::tx_circuit::{TxCircuit, TxCircuitConfig, TxCircuitConfigArgs};
 use crate::util::{log2_ceil, Challenges, SubCircuit, SubCircuitConfig};
-use crate::witness::{block_convert, Block, MptUpdates};
+use crate::witness::{block_convert, Block, RwMap};
 use bus_mapping::circuit_input_builder::{CircuitInputBuilder, CircuitsParams};
 use bus_mapping::mock::BlockData;
 use eth_types::geth_types::GethData;
-use eth_types::Field;
+use eth_types::{Field, Word, H256};
 use halo2_proofs::{
-    circuit::{Layouter, SimpleFloorPlanner, Value},
-    plonk::{Circuit, ConstraintSystem, Error, Expression},
+    circuit::{Layouter, SimpleFloorPlanner},
+    plonk::{Circuit, ConstraintSystem, Error},
 };
 
-use std::array;
-
 /// Mock randomness used for `SuperCircuit`.
 pub const MOCK_RANDOMNESS: u64 = 0x100;
 // TODO: Figure out if we can remove MAX_TXS, MAX_CALLDATA and MAX_RWS from the
@@ -93,15 +108,18 @@ pub struct SuperCircuitConfig<
     const MAX_RWS: usize,
 > {
     block_table: BlockTable,
-    mpt_table: MptTable,
     evm_circuit: EvmCircuitConfig<F>,
     state_circuit: StateCircuitConfig<F>,
+    mpt_circuit: MptCircuitConfig<F>,
     tx_circuit: TxCircuitConfig<F>,
     bytecode_circuit: BytecodeCircuitConfig<F>,
     copy_circuit: CopyCircuitConfig<F>,
     keccak_circuit: KeccakCircuitConfig<F>,
     pi_circuit: PiCircuitConfig<F>,
     exp_circuit: ExpCircuitConfig<F>,
+    bloom_circuit: BloomCircuitConfig<F>,
+    precompile_circuit: PrecompileCircuitConfig<F, ()>,
+    challenges: Challenges,
 }
 
 /// The Super Circuit contains all the zkEVM circuits
@@ -116,6 +134,8 @@ pub struct SuperCircuit<
     pub evm_circuit: EvmCircuit<F>,
     /// State Circuit
     pub state_circuit: StateCircuit<F>,
+    /// MPT Circuit
+    pub mpt_circuit: MptCircuit<F>,
     /// The transaction circuit that will be used in the `synthesize` step.
     pub tx_circuit: TxCircuit<F>,
     /// Public Input Circuit
@@ -128,6 +148,10 @@ pub struct SuperCircuit<
     pub exp_circuit: ExpCircuit<F>,
     /// Keccak Circuit
     pub keccak_circuit: KeccakCircuit<F>,
+    /// Bloom Circuit
+    pub bloom_circuit: BloomCircuit<F>,
+    /// Precompile Circuit
+    pub precompile_circuit: PrecompileCircuit<F>,
 }
 
 impl<F: Field, const MAX_TXS: usize, const MAX_CALLDATA: usize, const MAX_RWS: usize>
@@ -140,7 +164,9 @@ impl<F: Field, const MAX_TXS: usize, const MAX_CALLDATA: usize, const MAX_RWS: u
             let config = Self::configure(&mut cs);
             config.evm_circuit.get_num_rows_required(block)
         };
-        let num_rows_tx_circuit = TxCircuitConfig::<F>::get_num_rows_required(MAX_TXS);
+        let num_rows_tx_circuit = TxCircuitConfig::<F>::get_num_rows_required(
+            MAX_TXS + block.circuits_params.max_ecrecover,
+        );
         num_rows_evm_circuit.max(num_rows_tx_circuit)
     }
 }
@@ -165,21 +191,18 @@ impl<F: Field, const MAX_TXS: usize, const MAX_CALLDATA: usize, const MAX_RWS: u
         let copy_table = CopyTable::construct(meta, q_copy_table);
         let exp_table = ExpTable::construct(meta);
         let keccak_table = KeccakTable::construct(meta);
+        let precompile_table = PrecompileTable::construct(meta);
+        let bloom_table = BloomTable::construct(meta);
+        let sig_table = SigTable::construct(meta);
 
-        let power_of_randomness = array::from_fn(|i| {
-            Expression::Constant(F::from(MOCK_RANDOMNESS).pow(&[1 + i as u64, 0, 0, 0]))
-        });
-
-        let challenges = Challenges::mock(
-            power_of_randomness[0].clone(),
-            power_of_randomness[0].clone(),
-        );
+        let challenges = Challenges::construct(meta);
+        let challenge_exprs = challenges.exprs(meta);
 
         let keccak_circuit = KeccakCircuitConfig::new(
             meta,
             KeccakCircuitConfigArgs {
                 keccak_table: keccak_table.clone(),
-                challenges: challenges.clone(),
+                challenges: challenge_exprs.clone(),
             },
         );
 
@@ -190,6 +213,9 @@ impl<F: Field, const MAX_TXS: usize, const MAX_CALLDATA: usize, const MAX_RWS: u
                 max_calldata: MAX_CALLDATA,
                 block_table: block_table.clone(),
                 tx_table: tx_table.clone(),
+                rw_table,
+                keccak_table: keccak_table.clone(),
+                challenges: challenge_exprs.clone(),
             },
         );
         let tx_circuit = TxCircuitConfig::new(
@@ -197,7 +223,8 @@ impl<F: Field, const MAX_TXS: usize, const MAX_CALLDATA: usize, const MAX_RWS: u
             TxCircuitConfigArgs {
                 tx_table: tx_table.clone(),
                 keccak_table: keccak_table.clone(),
-                challenges: challenges.clone(),
+                sig_table,
+                challenges: challenge_exprs.clone(),
             },
         );
         let bytecode_circuit = BytecodeCircuitConfig::new(
@@ -205,7 +232,7 @@ impl<F: Field, const MAX_TXS: usize, const MAX_CALLDATA: usize, const MAX_RWS: u
             BytecodeCircuitConfigArgs {
                 bytecode_table: bytecode_table.clone(),
                 keccak_table: keccak_table.clone(),
-                challenges: challenges.clone(),
+                challenges: challenge_exprs.clone(),
             },
         );
         let copy_circuit = CopyCircuitConfig::new(
@@ -216,7 +243,15 @@ impl<F: Field, const MAX_TXS: usize, const MAX_CALLDATA: usize, const MAX_RWS: u
                 bytecode_table: bytecode_table.clone(),
                 copy_table,
                 q_enable: q_copy_table,
-                challenges: challenges.clone(),
+                challenges: challenge_exprs.clone(),
+            },
+        );
+        let mpt_circuit = MptCircuitConfig::new(
+            meta,
+            MptCircuitConfigArgs {
+                mpt_table,
+                keccak_table: keccak_table.clone(),
+                challenges: challenge_exprs.clone(),
             },
         );
         let state_circuit = StateCircuitConfig::new(
@@ -224,14 +259,31 @@ impl<F: Field, const MAX_TXS: usize, const MAX_CALLDATA: usize, const MAX_RWS: u
             StateCircuitConfigArgs {
                 rw_table,
                 mpt_table,
-                challenges,
+                challenges: challenge_exprs.clone(),
             },
         );
         let exp_circuit = ExpCircuitConfig::new(meta, exp_table);
+        let bloom_circuit = BloomCircuitConfig::new(
+            meta,
+            BloomCircuitConfigArgs {
+                bloom_table,
+                rw_table,
+                keccak_table: keccak_table.clone(),
+                block_table: block_table.clone(),
+                challenges: challenge_exprs.clone(),
+            },
+        );
+        let precompile_circuit = PrecompileCircuitConfig::new(
+            meta,
+            PrecompileCircuitConfigArgs {
+                precompile_table,
+                challenges: challenge_exprs.clone(),
+            },
+        );
         let evm_circuit = EvmCircuitConfig::new(
             meta,
             EvmCircuitConfigArgs {
-                power_of_randomness,
+                challenges: challenge_exprs,
                 tx_table,
                 rw_table,
                 bytecode_table,
@@ -239,20 +291,26 @@ impl<F: Field, const MAX_TXS: usize, const MAX_CALLDATA: usize, const MAX_RWS: u
                 copy_table,
                 keccak_table,
                 exp_table,
+                sig_table,
+                precompile_table,
+                bloom_table,
             },
         );
 
         Self::Config {
             block_table,
-            mpt_table,
             evm_circuit,
             state_circuit,
+            mpt_circuit,
             copy_circuit,
             tx_circuit,
             bytecode_circuit,
             keccak_circuit,
             pi_circuit,
             exp_circuit,
+            bloom_circuit,
+            precompile_circuit,
+            challenges,
         }
     }
 
@@ -262,21 +320,10 @@ impl<F: Field, const MAX_TXS: usize, const MAX_CALLDATA: usize, const MAX_RWS: u
         mut layouter: impl Layouter<F>,
     ) -> Result<(), Error> {
         let block = self.evm_circuit.block.as_ref().unwrap();
-        let challenges = Challenges::mock(
-            Value::known(block.randomness),
-            Value::known(block.randomness),
-        );
-        let rws = &self.state_circuit.rows;
-
+        let challenges = config.challenges.values(&mut layouter);
         config
             .block_table
-            .load(&mut layouter, &block.context, block.randomness)?;
-
-        config.mpt_table.load(
-            &mut layouter,
-            &MptUpdates::mock_from(rws),
-            Value::known(block.randomness),
-        )?;
+            .load(&mut layouter, &block.context, &challenges)?;
 
         self.keccak_circuit
             .synthesize_sub(&config.keccak_circuit, &challenges, &mut layouter)?;
@@ -289,10 +336,19 @@ impl<F: Field, const MAX_TXS: usize, const MAX_CALLDATA: usize, const MAX_RWS: u
             .synthesize_sub(&config.tx_circuit, &challenges, &mut layouter)?;
         self.state_circuit
             .synthesize_sub(&config.state_circuit, &challenges, &mut layouter)?;
+        self.mpt_circuit
+            .synthesize_sub(&config.mpt_circuit, &challenges, &mut layouter)?;
         self.copy_circuit
             .synthesize_sub(&config.copy_circuit, &challenges, &mut layouter)?;
         self.exp_circuit
             .synthesize_sub(&config.exp_circuit, &challenges, &mut layouter)?;
+        self.bloom_circuit
+            .synthesize_sub(&config.bloom_circuit, &challenges, &mut layouter)?;
+        self.precompile_circuit.synthesize_sub(
+            &config.precompile_circuit,
+            &challenges,
+            &mut layouter,
+        )?;
         self.evm_circuit
             .synthesize_sub(&config.evm_circuit, &challenges, &mut layouter)?;
         self.pi_circuit
@@ -313,6 +369,21 @@ impl<F: Field, const MAX_TXS: usize, const MAX_CALLDATA: usize, const MAX_RWS: u
     pub fn build(
         geth_data: GethData,
     ) -> Result<(u32, Self, Vec<Vec<F>>, CircuitInputBuilder), bus_mapping::Error> {
+        let builder = Self::handle_block(&geth_data);
+
+        let ret = Self::build_from_circuit_input_builder(&builder)?;
+        Ok((ret.0, ret.1, ret.2, builder))
+    }
+
+    /// Returns the receipts root of the header of the block of `geth_data`,
+    /// which is the commitment to the receipts of its execution, see
+    /// [`receipts_commitment`].
+    pub fn receipts_root(geth_data: &GethData) -> H256 {
+        let builder = Self::handle_block(geth_data);
+        receipts_commitment(&RwMap::from(&builder.block.container).receipts())
+    }
+
+    fn handle_block(geth_data: &GethData) -> CircuitInputBuilder {
         let block_data = BlockData::new_from_geth_data_with_params(
             geth_data.clone(),
             CircuitsParams {
@@ -321,15 +392,15 @@ impl<F: Field, const MAX_TXS: usize, const MAX_CALLDATA: usize, const MAX_RWS: u
                 max_rws: MAX_RWS,
                 max_bytecode: 512,
                 keccak_padding: None,
+                block_reward: Word::zero(),
+                ..Default::default()
             },
         );
         let mut builder = block_data.new_circuit_input_builder();
         builder
             .handle_block(&geth_data.eth_block, &geth_data.geth_traces)
             .expect("could not handle block tx");
-
-        let ret = Self::build_from_circuit_input_builder(&builder)?;
-        Ok((ret.0, ret.1, ret.2, builder))
+        builder
     }
 
     /// From CircuitInputBuilder, generate a SuperCircuit instance with all of
@@ -350,22 +421,28 @@ impl<F: Field, const MAX_TXS: usize, const MAX_CALLDATA: usize, const MAX_RWS: u
 
         let evm_circuit = EvmCircuit::new_from_block(&block);
         let state_circuit = StateCircuit::new_from_block(&block);
+        let mpt_circuit = MptCircuit::new_from_block(&block);
         let tx_circuit = TxCircuit::new_from_block(&block);
         let pi_circuit = PiCircuit::new_from_block(&block);
         let bytecode_circuit = BytecodeCircuit::new_from_block(&block);
         let copy_circuit = CopyCircuit::new_from_block(&block);
         let exp_circuit = ExpCircuit::new_from_block(&block);
         let keccak_circuit = KeccakCircuit::new_from_block(&block);
+        let bloom_circuit = BloomCircuit::new_from_block(&block);
+        let precompile_circuit = PrecompileCircuit::new_from_block(&block);
 
         let circuit = SuperCircuit::<_, MAX_TXS, MAX_CALLDATA, MAX_RWS> {
             evm_circuit,
             state_circuit,
+            mpt_circuit,
             tx_circuit,
             pi_circuit,
             bytecode_circuit,
             copy_circuit,
             exp_circuit,
             keccak_circuit,
+            bloom_circuit,
+            precompile_circuit,
         };
 
         let instance = circuit.instance();
@@ -385,20 +462,27 @@ impl<F: Field, const MAX_TXS: usize, const MAX_CALLDATA: usize, const MAX_RWS: u
     pub fn min_num_rows_block(block: &Block<F>) -> usize {
         let evm = EvmCircuit::min_num_rows_block(block);
         let state = StateCircuit::min_num_rows_block(block);
+        let mpt = MptCircuit::min_num_rows_block(block);
         let bytecode = BytecodeCircuit::min_num_rows_block(block);
         let copy = CopyCircuit::min_num_rows_block(block);
         let keccak = KeccakCircuit::min_num_rows_block(block);
         let tx = TxCircuit::min_num_rows_block(block);
         let exp = ExpCircuit::min_num_rows_block(block);
         let pi = PiCircuit::min_num_rows_block(block);
+        let bloom = BloomCircuit::min_num_rows_block(block);
+        let precompile = PrecompileCircuit::<F>::min_num_rows_block(block);
 
-        itertools::max([evm, state, bytecode, copy, keccak, tx, exp, pi]).unwrap()
+        itertools::max([
+            evm, state, mpt, bytecode, copy, keccak, tx, exp, pi, bloom, precompile,
+        ])
+        .unwrap()
     }
 }
 
 #[cfg(test)]
 mod super_circuit_tests {
     use super::*;
+    use crate::pi_circuit::txs_commitment;
     use ethers_signers::{LocalWallet, Signer};
     use halo2_proofs::dev::MockProver;
     use halo2_proofs::halo2curves::bn256::Fr;
@@ -420,8 +504,10 @@ mod super_circuit_tests {
     }
 
     fn test_super_circuit<const MAX_TXS: usize, const MAX_CALLDATA: usize, const MAX_RWS: usize>(
-        block: GethData,
+        mut block: GethData,
     ) {
+        block.eth_block.receipts_root =
+            SuperCircuit::<Fr, MAX_TXS, MAX_CALLDATA, MAX_RWS>::receipts_root(&block);
         let (k, circuit, instance, _) =
             SuperCircuit::<Fr, MAX_TXS, MAX_CALLDATA, MAX_RWS>::build(block).unwrap();
         let prover = MockProver::run(k, &circuit, instance).unwrap();
@@ -470,6 +556,7 @@ mod super_circuit_tests {
         .unwrap()
         .into();
         block.sign(&wallets);
+        block.eth_block.transactions_root = txs_commitment(chain_id, &block.eth_block.transactions);
         block
     }
 
@@ -515,6 +602,7 @@ mod super_circuit_tests {
         .unwrap()
         .into();
         block.sign(&wallets);
+        block.eth_block.transactions_root = txs_commitment(chain_id, &block.eth_block.transactions);
         block
     }
 
@@ -9,8 +9,11 @@ use crate::util::Challenges;
 use crate::witness::{
     Block, BlockContext, Bytecode, MptUpdateRow, MptUpdates, Rw, RwMap, RwRow, Transaction,
 };
-use bus_mapping::circuit_input_builder::{CopyDataType, CopyEvent, CopyStep, ExpEvent};
-use core::iter::once;
+use bus_mapping::circuit_input_builder::{
+    CopyDataType, CopyEvent, CopyStep, ExpEvent, PrecompileEvent,
+};
+use core::iter::{self, once};
+use eth_types::sign_types::{pk_bytes_le, pk_bytes_swap_endianness, SignData};
 use eth_types::{Field, ToLittleEndian, ToScalar, Word, U256};
 use gadgets::binary_number::{BinaryNumberChip, BinaryNumberConfig};
 use gadgets::util::{split_u256, split_u256_limb64};
@@ -66,8 +69,12 @@ pub enum TxFieldTag {
     Nonce,
     /// Gas
     Gas,
-    /// GasPrice
+    /// GasPrice, which is the effective gas price for EIP-1559 transactions
     GasPrice,
+    /// GasFeeCap (EIP-1559), which equals GasPrice for legacy transactions
+    GasFeeCap,
+    /// GasTipCap (EIP-1559), which equals GasPrice for legacy transactions
+    GasTipCap,
     /// CallerAddress
     CallerAddress,
     /// CalleeAddress
@@ -80,11 +87,23 @@ pub enum TxFieldTag {
     CallDataLength,
     /// Gas cost for transaction call data (4 for byte == 0, 16 otherwise)
     CallDataGasCost,
+    /// Number of addresses in the access list (EIP-2930)
+    AccessListAddressesLen,
+    /// Number of storage keys in the access list (EIP-2930)
+    AccessListStorageKeysLen,
     /// TxSignHash: Hash of the transaction without the signature, used for
     /// signing.
     TxSignHash,
     /// CallData
     CallData,
+    /// Address of an entry of the access list (EIP-2930), indexed by the
+    /// position of the entry: all the addresses, followed by all the storage
+    /// keys. The entries are bound to the signed transaction by the PI
+    /// circuit, which decodes its RLP encoding against them.
+    AccessListAddress,
+    /// Storage key of an entry of the access list (EIP-2930), which is zero
+    /// for the entries of the addresses
+    AccessListStorageKey,
 }
 impl_expr!(TxFieldTag);
 
@@ -98,7 +117,7 @@ pub struct TxTable {
     pub tx_id: Column<Advice>,
     /// Tag (TxContextFieldTag)
     pub tag: Column<Fixed>,
-    /// Index for Tag = CallData
+    /// Index for Tag = CallData, AccessListAddress and AccessListStorageKey
     pub index: Column<Advice>,
     /// Value
     pub value: Column<Advice>,
@@ -267,6 +286,10 @@ pub enum TxLogFieldTag {
     Topic,
     /// Data field
     Data,
+    /// Number of topics
+    TopicLength,
+    /// Length of the data
+    DataLength,
 }
 impl_expr!(TxLogFieldTag);
 
@@ -435,11 +458,11 @@ impl RwTable {
         layouter: &mut impl Layouter<F>,
         rws: &[Rw],
         n_rows: usize,
-        challenges: Value<F>,
+        challenges: &Challenges<Value<F>>,
     ) -> Result<(), Error> {
         layouter.assign_region(
             || "rw table",
-            |mut region| self.load_with_region(&mut region, rws, n_rows, challenges),
+            |mut region| self.load_with_region(&mut region, rws, n_rows, challenges.evm_word()),
         )
     }
 
@@ -459,6 +482,7 @@ impl RwTable {
 }
 
 /// The types of proofs in the MPT table
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ProofType {
     /// Nonce updated
     NonceChanged = AccountFieldTag::Nonce as isize,
@@ -501,7 +525,15 @@ impl DynamicTableColumns for MptTable {
 impl MptTable {
     /// Construct a new MptTable
     pub(crate) fn construct<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> Self {
-        Self([0; 7].map(|_| meta.advice_column()))
+        Self([
+            meta.advice_column(),               // Address
+            meta.advice_column_in(SecondPhase), // Storage key
+            meta.advice_column(),               // Proof type
+            meta.advice_column_in(SecondPhase), // New root
+            meta.advice_column_in(SecondPhase), // Old root
+            meta.advice_column_in(SecondPhase), // New value
+            meta.advice_column_in(SecondPhase), // Old value
+        ])
     }
 
     pub(crate) fn assign<F: Field>(
@@ -658,6 +690,12 @@ pub enum BlockContextFieldTag {
     /// Chain ID field.  Although this is not a field in the block header, we
     /// add it here for convenience.
     ChainId,
+    /// Block reward paid to the coinbase.  This is a parameter of the
+    /// circuits, not a field in the block header.
+    BlockReward,
+    /// Logs bloom field, split in words of 32 bytes indexed from the most
+    /// significant one.
+    LogsBloom,
 }
 impl_expr!(BlockContextFieldTag);
 
@@ -678,7 +716,7 @@ impl BlockTable {
         Self {
             tag: meta.advice_column(),
             index: meta.advice_column(),
-            value: meta.advice_column(),
+            value: meta.advice_column_in(SecondPhase),
         }
     }
 
@@ -687,7 +725,7 @@ impl BlockTable {
         &self,
         layouter: &mut impl Layouter<F>,
         block: &BlockContext,
-        randomness: F,
+        challenges: &Challenges<Value<F>>,
     ) -> Result<(), Error> {
         layouter.assign_region(
             || "block table",
@@ -704,13 +742,13 @@ impl BlockTable {
                 offset += 1;
 
                 let block_table_columns = self.columns();
-                for row in block.table_assignments(randomness) {
+                for row in block.table_assignments(challenges.evm_word()) {
                     for (column, value) in block_table_columns.iter().zip_eq(row) {
                         region.assign_advice(
                             || format!("block table row {}", offset),
                             *column,
                             offset,
-                            || Value::known(value),
+                            || value,
                         )?;
                     }
                     offset += 1;
@@ -914,8 +952,8 @@ impl CopyTable {
                 .map(|(value, _)| *value)
                 .collect::<Vec<u8>>();
             challenges
-                .evm_word()
-                .map(|evm_word_challenge| rlc::value(values.iter().rev(), evm_word_challenge))
+                .keccak_input()
+                .map(|keccak_input| rlc::value(values.iter().rev(), keccak_input))
         } else {
             Value::known(F::zero())
         };
@@ -996,7 +1034,7 @@ impl CopyTable {
                 if is_read_step {
                     Value::known(F::from(copy_step.value as u64))
                 } else {
-                    value_acc = value_acc * challenges.evm_word()
+                    value_acc = value_acc * challenges.keccak_input()
                         + Value::known(F::from(copy_step.value as u64));
                     value_acc
                 }
@@ -1311,3 +1349,312 @@ impl<F: Field> LookupTable<F> for ExpTable {
         ]
     }
 }
+
+/// Precompile Table, which holds the calls to the precompiled contracts
+/// verified by dedicated sub-circuits: for an input of a precompiled contract,
+/// the gas it requires, whether it's well formed and the output returned when
+/// the call succeeds.  It's filled by the
+/// [`PrecompileCircuit`](crate::precompile_circuit::PrecompileCircuit) only,
+/// from the rows verified by the sub-circuits plugged into it.
+#[derive(Clone, Copy, Debug)]
+pub struct PrecompileTable {
+    /// Address of the precompiled contract
+    pub address: Column<Advice>,
+    /// Length of the input bytes
+    pub input_length: Column<Advice>,
+    /// RLC of the input bytes
+    pub input_rlc: Column<Advice>,
+    /// Length of the output bytes
+    pub output_length: Column<Advice>,
+    /// RLC of the output bytes
+    pub output_rlc: Column<Advice>,
+    /// Gas required for the input
+    pub required_gas: Column<Advice>,
+    /// Whether the input is well formed
+    pub is_valid: Column<Advice>,
+}
+
+impl PrecompileTable {
+    /// Construct a new PrecompileTable
+    pub fn construct<F: Field>(meta: &mut ConstraintSystem<F>) -> Self {
+        Self {
+            address: meta.advice_column(),
+            input_length: meta.advice_column(),
+            input_rlc: meta.advice_column_in(SecondPhase),
+            output_length: meta.advice_column(),
+            output_rlc: meta.advice_column_in(SecondPhase),
+            required_gas: meta.advice_column(),
+            is_valid: meta.advice_column(),
+        }
+    }
+
+    /// Generate the precompile table assignment of a call to a precompiled
+    /// contract.
+    pub fn assignment<F: Field>(
+        event: &PrecompileEvent,
+        challenges: &Challenges<Value<F>>,
+    ) -> [Value<F>; 7] {
+        let [input_rlc, output_rlc] = [&event.input, &event.output].map(|bytes| {
+            challenges
+                .keccak_input()
+                .map(|challenge| rlc::value(bytes.iter().rev(), challenge))
+        });
+        [
+            Value::known(F::from(u64::from(event.precompile))),
+            Value::known(F::from(event.input.len() as u64)),
+            input_rlc,
+            Value::known(F::from(event.output.len() as u64)),
+            output_rlc,
+            Value::known(F::from(event.required_gas)),
+            Value::known(F::from(event.is_valid as u64)),
+        ]
+    }
+
+    /// Provide this function for the case that we want to consume a
+    /// precompile table but without running the sub-circuits verifying the
+    /// calls.
+    #[cfg(any(feature = "test", test))]
+    pub fn dev_load<F: Field>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        events: &[PrecompileEvent],
+        challenges: &Challenges<Value<F>>,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "precompile table",
+            |mut region| {
+                let mut offset = 0;
+                for column in self.columns() {
+                    region.assign_advice(
+                        || "precompile table all-zero row",
+                        column,
+                        offset,
+                        || Value::known(F::zero()),
+                    )?;
+                }
+                offset += 1;
+
+                let precompile_table_columns = self.columns();
+                for event in events {
+                    for (column, value) in precompile_table_columns
+                        .iter()
+                        .zip_eq(Self::assignment(event, challenges))
+                    {
+                        region.assign_advice(
+                            || format!("precompile table row {}", offset),
+                            *column,
+                            offset,
+                            || value,
+                        )?;
+                    }
+                    offset += 1;
+                }
+
+                Ok(())
+            },
+        )
+    }
+}
+
+impl DynamicTableColumns for PrecompileTable {
+    fn columns(&self) -> Vec<Column<Advice>> {
+        vec![
+            self.address,
+            self.input_length,
+            self.input_rlc,
+            self.output_length,
+            self.output_rlc,
+            self.required_gas,
+            self.is_valid,
+        ]
+    }
+}
+
+/// Bloom Table, which holds the address and topics of every log of the block
+/// accrued into the logs bloom by the Bloom circuit.
+#[derive(Clone, Copy, Debug)]
+pub struct BloomTable {
+    /// Whether the row holds a log item
+    pub is_enabled: Column<Advice>,
+    /// TxLogFieldTag of the item, either Address or Topic
+    pub field_tag: Column<Advice>,
+    /// Value of the item, as in the RwTable
+    pub value: Column<Advice>,
+}
+
+impl BloomTable {
+    /// Construct a new BloomTable
+    pub fn construct<F: Field>(meta: &mut ConstraintSystem<F>) -> Self {
+        Self {
+            is_enabled: meta.advice_column(),
+            field_tag: meta.advice_column(),
+            value: meta.advice_column_in(SecondPhase),
+        }
+    }
+
+    /// Generate the bloom table assignment of a log item, which is a TxLog
+    /// write of an address or a topic.
+    pub fn assignment<F: Field>(rw: &Rw, challenges: &Challenges<Value<F>>) -> [Value<F>; 3] {
+        let field_tag = match rw {
+            Rw::TxLog { field_tag, .. } => *field_tag,
+            _ => unreachable!("log items are TxLog rws"),
+        };
+        [
+            Value::known(F::one()),
+            Value::known(F::from(field_tag as u64)),
+            rw.table_assignment(challenges.evm_word()).value,
+        ]
+    }
+
+    /// Provide this function for the case that we want to consume a bloom
+    /// table but without running the full bloom circuit
+    pub fn dev_load<F: Field>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        rws: &RwMap,
+        challenges: &Challenges<Value<F>>,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "bloom table",
+            |mut region| {
+                let mut offset = 0;
+                for column in self.columns() {
+                    region.assign_advice(
+                        || "bloom table all-zero row",
+                        column,
+                        offset,
+                        || Value::known(F::zero()),
+                    )?;
+                }
+                offset += 1;
+
+                let bloom_table_columns = self.columns();
+                for rw in rws.logs_bloom_items() {
+                    for (column, value) in bloom_table_columns
+                        .iter()
+                        .zip_eq(Self::assignment(&rw, challenges))
+                    {
+                        region.assign_advice(
+                            || format!("bloom table row {}", offset),
+                            *column,
+                            offset,
+                            || value,
+                        )?;
+                    }
+                    offset += 1;
+                }
+
+                Ok(())
+            },
+        )
+    }
+}
+
+impl DynamicTableColumns for BloomTable {
+    fn columns(&self) -> Vec<Column<Advice>> {
+        vec![self.is_enabled, self.field_tag, self.value]
+    }
+}
+
+/// Sig Table, which holds the calls to ecrecover whose recovered address is
+/// verified by the SignVerify chip of the Tx circuit.
+#[derive(Clone, Copy, Debug)]
+pub struct SigTable {
+    /// RLC of the 128 input bytes of ecrecover, as `RLC(reversed(msg_hash || v
+    /// || r || s))`, where `v` is 27 plus the recovery id of the signature
+    pub input_rlc: Column<Advice>,
+    /// RLC of the 20 bytes of the recovered address
+    pub address_rlc: Column<Advice>,
+}
+
+impl SigTable {
+    /// Construct a new SigTable
+    pub fn construct<F: Field>(meta: &mut ConstraintSystem<F>) -> Self {
+        Self {
+            input_rlc: meta.advice_column_in(SecondPhase),
+            address_rlc: meta.advice_column_in(SecondPhase),
+        }
+    }
+
+    /// Generate the sig table assignment of the signature verified for a call
+    /// to ecrecover.
+    pub fn assignment<F: Field>(
+        sign_data: &SignData,
+        challenges: &Challenges<Value<F>>,
+    ) -> [Value<F>; 2] {
+        let (sig_r, sig_s) = sign_data.signature;
+        let input_le: Vec<u8> = iter::empty()
+            .chain(sig_s.to_bytes())
+            .chain(sig_r.to_bytes())
+            .chain([27 + sign_data.recovery_id()])
+            .chain([0; 31])
+            .chain(sign_data.msg_hash.to_bytes())
+            .collect();
+        let pk_be = pk_bytes_swap_endianness(&pk_bytes_le(&sign_data.pk));
+        let mut keccak = Keccak::default();
+        keccak.update(&pk_be);
+        let pk_hash = keccak.digest();
+
+        [
+            challenges
+                .keccak_input()
+                .map(|challenge| rlc::value(&input_le, challenge)),
+            challenges
+                .keccak_input()
+                .map(|challenge| rlc::value(pk_hash[12..].iter().rev(), challenge)),
+        ]
+    }
+
+    /// Provide this function for the case that we want to consume a sig table
+    /// but without running the full tx circuit
+    pub fn dev_load<F: Field>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        block: &Block<F>,
+        challenges: &Challenges<Value<F>>,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "sig table",
+            |mut region| {
+                let mut offset = 0;
+                for column in self.columns() {
+                    region.assign_advice(
+                        || "sig table all-zero row",
+                        column,
+                        offset,
+                        || Value::known(F::zero()),
+                    )?;
+                }
+                offset += 1;
+
+                let sig_table_columns = self.columns();
+                for sign_data in block
+                    .precompile_events
+                    .iter()
+                    .filter_map(|event| event.ecrecover_sign_data())
+                {
+                    for (column, value) in sig_table_columns
+                        .iter()
+                        .zip_eq(Self::assignment(&sign_data, challenges))
+                    {
+                        region.assign_advice(
+                            || format!("sig table row {}", offset),
+                            *column,
+                            offset,
+                            || value,
+                        )?;
+                    }
+                    offset += 1;
+                }
+
+                Ok(())
+            },
+        )
+    }
+}
+
+impl DynamicTableColumns for SigTable {
+    fn columns(&self) -> Vec<Column<Advice>> {
+        vec![self.input_rlc, self.address_rlc]
+    }
+}
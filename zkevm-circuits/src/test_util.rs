@@ -160,6 +160,8 @@ pub fn rand_tx<R: Rng + CryptoRng>(mut rng: R, chain_id: u64, has_calldata: bool
         to,
         gas_limit: tx.gas.unwrap(),
         gas_price: tx.gas_price.unwrap(),
+        gas_fee_cap: tx.gas_price.unwrap(),
+        gas_tip_cap: tx.gas_price.unwrap(),
         value: tx.value.unwrap(),
         call_data: tx.data.unwrap(),
         nonce: tx.nonce.unwrap(),
@@ -6,10 +6,10 @@
 
 pub mod sign_verify;
 
-use crate::table::{KeccakTable, TxFieldTag, TxTable};
+use crate::table::{KeccakTable, SigTable, TxFieldTag, TxTable};
 use crate::util::{random_linear_combine_word as rlc, Challenges, SubCircuit, SubCircuitConfig};
 use crate::witness;
-use bus_mapping::circuit_input_builder::keccak_inputs_tx_circuit;
+use bus_mapping::circuit_input_builder::{keccak_inputs_sign_verify, keccak_inputs_tx_circuit};
 use eth_types::{
     sign_types::SignData,
     {geth_types::Transaction, Address, Field, ToLittleEndian, ToScalar},
@@ -21,7 +21,7 @@ use halo2_proofs::{
 use itertools::Itertools;
 use log::error;
 use sign_verify::{AssignedSignatureVerify, SignVerifyChip, SignVerifyConfig};
-use std::marker::PhantomData;
+use std::{iter, marker::PhantomData};
 
 pub use halo2_proofs::halo2curves::{
     group::{
@@ -32,12 +32,14 @@ pub use halo2_proofs::halo2curves::{
     secp256k1::{self, Secp256k1Affine, Secp256k1Compressed},
 };
 
-/// Number of static fields per tx: [nonce, gas, gas_price,
-/// caller_address, callee_address, is_create, value, call_data_length,
-/// call_data_gas_cost, tx_sign_hash].
+/// Number of static fields per tx: [nonce, gas, gas_price, gas_fee_cap,
+/// gas_tip_cap, caller_address, callee_address, is_create, value,
+/// call_data_length, call_data_gas_cost, access_list_addresses_len,
+/// access_list_storage_keys_len, tx_sign_hash].
 /// Note that call data bytes are layed out in the TxTable after all the static
-/// fields arranged by txs.
-pub(crate) const TX_LEN: usize = 10;
+/// fields arranged by txs, followed by the entries of the access lists, which
+/// take two rows each.
+pub(crate) const TX_LEN: usize = 14;
 
 /// Config for TxCircuit
 #[derive(Clone, Debug)]
@@ -50,6 +52,7 @@ pub struct TxCircuitConfig<F: Field> {
     _marker: PhantomData<F>,
     // External tables
     keccak_table: KeccakTable,
+    sig_table: SigTable,
 }
 
 /// Circuit configuration arguments
@@ -58,6 +61,8 @@ pub struct TxCircuitConfigArgs<F: Field> {
     pub tx_table: TxTable,
     /// KeccakTable
     pub keccak_table: KeccakTable,
+    /// SigTable
+    pub sig_table: SigTable,
     /// Challenges
     pub challenges: Challenges<Expression<F>>,
 }
@@ -71,6 +76,7 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
         Self::ConfigArgs {
             tx_table,
             keccak_table,
+            sig_table,
             challenges,
         }: Self::ConfigArgs,
     ) -> Self {
@@ -79,6 +85,8 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
         let index = tx_table.index;
         let value = tx_table.value;
         meta.enable_equality(value);
+        meta.enable_equality(sig_table.input_rlc);
+        meta.enable_equality(sig_table.address_rlc);
 
         let sign_verify = SignVerifyConfig::new(meta, keccak_table.clone(), challenges);
 
@@ -89,6 +97,7 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
             value,
             sign_verify,
             keccak_table,
+            sig_table,
             _marker: PhantomData,
         }
     }
@@ -132,12 +141,13 @@ impl<F: Field> TxCircuitConfig<F> {
         region.assign_advice(|| "value", self.value, offset, || value)
     }
 
-    /// Get number of rows required.
-    pub fn get_num_rows_required(num_tx: usize) -> usize {
+    /// Get number of rows required to verify `num_verif` signatures, of
+    /// transactions or calls to ecrecover.
+    pub fn get_num_rows_required(num_verif: usize) -> usize {
         let num_rows_range_table = 1 << 18;
-        // Number of rows required to verify a transaction.
-        let num_rows_per_tx = 140436;
-        (num_tx * num_rows_per_tx).max(num_rows_range_table)
+        // Number of rows required to verify a signature.
+        let num_rows_per_verif = 140436 + 3 * 16 + 16 + 8 + 2 * 32 + 20 + 34 + 2;
+        (num_verif * num_rows_per_verif).max(num_rows_range_table)
     }
 }
 
@@ -148,31 +158,95 @@ pub struct TxCircuit<F: Field> {
     pub max_txs: usize,
     /// Max number of supported calldata bytes
     pub max_calldata: usize,
+    /// Max number of supported access list entries
+    pub max_access_list: usize,
+    /// Max number of supported calls to ecrecover
+    pub max_ecrecover: usize,
     /// SignVerify chip
     pub sign_verify: SignVerifyChip<F>,
     /// List of Transactions
     pub txs: Vec<Transaction>,
+    /// Signatures of the calls to ecrecover
+    pub ecrecovers: Vec<SignData>,
     /// Chain ID
     pub chain_id: u64,
 }
 
 impl<F: Field> TxCircuit<F> {
     /// Return a new TxCircuit
-    pub fn new(max_txs: usize, max_calldata: usize, chain_id: u64, txs: Vec<Transaction>) -> Self {
+    pub fn new(
+        max_txs: usize,
+        max_calldata: usize,
+        max_access_list: usize,
+        max_ecrecover: usize,
+        chain_id: u64,
+        txs: Vec<Transaction>,
+        ecrecovers: Vec<SignData>,
+    ) -> Self {
         TxCircuit::<F> {
             max_txs,
             max_calldata,
-            sign_verify: SignVerifyChip::new(max_txs),
+            max_access_list,
+            max_ecrecover,
+            sign_verify: SignVerifyChip::new(max_txs + max_ecrecover),
             txs,
+            ecrecovers,
             chain_id,
         }
     }
 
     /// Return the minimum number of rows required to prove an input of a
     /// particular size.
-    pub fn min_num_rows(txs_len: usize, call_data_len: usize) -> usize {
-        let tx_table_len = txs_len * TX_LEN + call_data_len;
-        std::cmp::max(tx_table_len, SignVerifyChip::<F>::min_num_rows(txs_len))
+    pub fn min_num_rows(
+        txs_len: usize,
+        call_data_len: usize,
+        access_list_len: usize,
+        ecrecovers_len: usize,
+    ) -> usize {
+        let tx_table_len = txs_len * TX_LEN + call_data_len + 2 * access_list_len;
+        std::cmp::max(
+            tx_table_len,
+            SignVerifyChip::<F>::min_num_rows(txs_len + ecrecovers_len),
+        )
+    }
+
+    /// Assign the sig table from the verifications of the calls to ecrecover,
+    /// whose padding rows are zero.
+    fn assign_sig_table(
+        &self,
+        config: &TxCircuitConfig<F>,
+        layouter: &mut impl Layouter<F>,
+        assigned_sig_verifs: &[AssignedSignatureVerify<F>],
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "sig table",
+            |mut region| {
+                let sig_table_columns = [config.sig_table.input_rlc, config.sig_table.address_rlc];
+                for column in sig_table_columns {
+                    region.assign_advice(
+                        || "sig table all-zero row",
+                        column,
+                        0,
+                        || Value::known(F::zero()),
+                    )?;
+                }
+                for (offset, assigned_sig_verif) in (1..).zip(assigned_sig_verifs) {
+                    for (column, assigned) in sig_table_columns.into_iter().zip([
+                        &assigned_sig_verif.ecrecover_input_rlc,
+                        &assigned_sig_verif.address_rlc,
+                    ]) {
+                        let assigned_cell = region.assign_advice(
+                            || format!("sig table row {}", offset),
+                            column,
+                            offset,
+                            || assigned.value().copied(),
+                        )?;
+                        region.constrain_equal(assigned_cell.cell(), assigned.cell())?;
+                    }
+                }
+                Ok(())
+            },
+        )
     }
 
     fn assign_tx_table(
@@ -222,6 +296,18 @@ impl<F: Field> TxCircuit<F> {
                                 .evm_word()
                                 .map(|challenge| rlc(tx.gas_price.to_le_bytes(), challenge)),
                         ),
+                        (
+                            TxFieldTag::GasFeeCap,
+                            challenges
+                                .evm_word()
+                                .map(|challenge| rlc(tx.gas_fee_cap.to_le_bytes(), challenge)),
+                        ),
+                        (
+                            TxFieldTag::GasTipCap,
+                            challenges
+                                .evm_word()
+                                .map(|challenge| rlc(tx.gas_tip_cap.to_le_bytes(), challenge)),
+                        ),
                         (
                             TxFieldTag::CallerAddress,
                             Value::known(tx.from.to_scalar().expect("tx.from too big")),
@@ -258,6 +344,14 @@ impl<F: Field> TxCircuit<F> {
                                     .fold(0, |acc, byte| acc + if *byte == 0 { 4 } else { 16 }),
                             )),
                         ),
+                        (
+                            TxFieldTag::AccessListAddressesLen,
+                            Value::known(F::from(tx.access_list_addresses_len())),
+                        ),
+                        (
+                            TxFieldTag::AccessListStorageKeysLen,
+                            Value::known(F::from(tx.access_list_storage_keys_len())),
+                        ),
                         (
                             TxFieldTag::TxSignHash,
                             assigned_sig_verif.msg_hash_rlc.value().copied(),
@@ -311,6 +405,48 @@ impl<F: Field> TxCircuit<F> {
                     )?;
                     offset += 1;
                 }
+
+                // Assign the entries of the access lists, each one in a row with
+                // its address and a row with its storage key, as the PI circuit
+                // does where it decodes them from the signed transactions.
+                let mut access_list_count = 0;
+                for (i, tx) in self.txs.iter().enumerate() {
+                    for (index, (address, key)) in tx.access_list_entries().into_iter().enumerate()
+                    {
+                        for (tag, value) in [
+                            (
+                                TxFieldTag::AccessListAddress,
+                                Value::known(address.to_scalar().expect("address too big")),
+                            ),
+                            (
+                                TxFieldTag::AccessListStorageKey,
+                                challenges.evm_word().map(|challenge| {
+                                    rlc(key.unwrap_or_default().to_le_bytes(), challenge)
+                                }),
+                            ),
+                        ] {
+                            config.assign_row(&mut region, offset, i + 1, tag, index, value)?;
+                            offset += 1;
+                        }
+                        access_list_count += 1;
+                    }
+                }
+                for _ in access_list_count..self.max_access_list {
+                    for tag in [
+                        TxFieldTag::AccessListAddress,
+                        TxFieldTag::AccessListStorageKey,
+                    ] {
+                        config.assign_row(
+                            &mut region,
+                            offset,
+                            0, // tx_id
+                            tag,
+                            0,
+                            Value::known(F::zero()),
+                        )?;
+                        offset += 1;
+                    }
+                }
                 Ok(())
             },
         )
@@ -324,6 +460,8 @@ impl<F: Field> SubCircuit<F> for TxCircuit<F> {
         Self::new(
             block.circuits_params.max_txs,
             block.circuits_params.max_calldata,
+            block.circuits_params.max_access_list,
+            block.circuits_params.max_ecrecover,
             block.context.chain_id.as_u64(),
             block
                 .eth_block
@@ -331,6 +469,11 @@ impl<F: Field> SubCircuit<F> for TxCircuit<F> {
                 .iter()
                 .map(|tx| tx.into())
                 .collect(),
+            block
+                .precompile_events
+                .iter()
+                .filter_map(|event| event.ecrecover_sign_data())
+                .collect(),
         )
     }
 
@@ -339,6 +482,16 @@ impl<F: Field> SubCircuit<F> for TxCircuit<F> {
         Self::min_num_rows(
             block.txs.len(),
             block.txs.iter().map(|tx| tx.call_data.len()).sum(),
+            block
+                .txs
+                .iter()
+                .map(|tx| (tx.access_list_addresses_len + tx.access_list_storage_keys_len) as usize)
+                .sum(),
+            block
+                .precompile_events
+                .iter()
+                .filter(|event| event.ecrecover_sign_data().is_some())
+                .count(),
         )
     }
 
@@ -350,7 +503,27 @@ impl<F: Field> SubCircuit<F> for TxCircuit<F> {
         layouter: &mut impl Layouter<F>,
     ) -> Result<(), Error> {
         assert!(self.txs.len() <= self.max_txs);
-        let sign_datas: Vec<SignData> = self
+        if self.ecrecovers.len() > self.max_ecrecover {
+            error!(
+                "ecrecovers.len() = {} > max_ecrecover = {}",
+                self.ecrecovers.len(),
+                self.max_ecrecover
+            );
+            return Err(Error::Synthesis);
+        }
+        let access_list_len: usize = self
+            .txs
+            .iter()
+            .map(|tx| tx.access_list_entries().len())
+            .sum();
+        if access_list_len > self.max_access_list {
+            error!(
+                "access list entries = {} > max_access_list = {}",
+                access_list_len, self.max_access_list
+            );
+            return Err(Error::Synthesis);
+        }
+        let tx_sign_datas: Vec<SignData> = self
             .txs
             .iter()
             .map(|tx| {
@@ -360,12 +533,25 @@ impl<F: Field> SubCircuit<F> for TxCircuit<F> {
                 })
             })
             .try_collect()?;
+        // The first max_txs verifications are the ones of the txs, followed by
+        // the ones of the calls to ecrecover.
+        let sign_datas = iter::empty()
+            .chain(
+                tx_sign_datas
+                    .into_iter()
+                    .map(Some)
+                    .pad_using(self.max_txs, |_| None),
+            )
+            .chain(self.ecrecovers.iter().cloned().map(Some))
+            .collect_vec();
 
         config.load_aux_tables(layouter)?;
-        let assigned_sig_verifs =
+        let mut assigned_sig_verifs =
             self.sign_verify
                 .assign(&config.sign_verify, layouter, &sign_datas, challenges)?;
+        let assigned_ecrecover_verifs = assigned_sig_verifs.split_off(self.max_txs);
         self.assign_tx_table(config, challenges, layouter, assigned_sig_verifs)?;
+        self.assign_sig_table(config, layouter, &assigned_ecrecover_verifs)?;
         Ok(())
     }
 }
@@ -382,6 +568,7 @@ impl<F: Field> Circuit<F> for TxCircuit<F> {
     fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
         let tx_table = TxTable::construct(meta);
         let keccak_table = KeccakTable::construct(meta);
+        let sig_table = SigTable::construct(meta);
         let challenges = Challenges::construct(meta);
 
         let config = {
@@ -391,6 +578,7 @@ impl<F: Field> Circuit<F> for TxCircuit<F> {
                 TxCircuitConfigArgs {
                     tx_table,
                     keccak_table,
+                    sig_table,
                     challenges,
                 },
             )
@@ -406,14 +594,15 @@ impl<F: Field> Circuit<F> for TxCircuit<F> {
     ) -> Result<(), Error> {
         let challenges = challenges.values(&mut layouter);
 
-        config.keccak_table.dev_load(
-            &mut layouter,
-            &keccak_inputs_tx_circuit(&self.txs[..], self.chain_id).map_err(|e| {
+        let mut keccak_inputs =
+            keccak_inputs_tx_circuit(&self.txs[..], self.chain_id).map_err(|e| {
                 error!("keccak_inputs_tx_circuit error: {:?}", e);
                 Error::Synthesis
-            })?,
-            &challenges,
-        )?;
+            })?;
+        keccak_inputs.extend(keccak_inputs_sign_verify(&self.ecrecovers));
+        config
+            .keccak_table
+            .dev_load(&mut layouter, &keccak_inputs, &challenges)?;
         self.synthesize_sub(&config, &challenges, &mut layouter)
     }
 }
@@ -422,13 +611,17 @@ impl<F: Field> Circuit<F> for TxCircuit<F> {
 mod tx_circuit_tests {
     use super::*;
     use crate::util::log2_ceil;
-    use eth_types::address;
+    use eth_types::{
+        address, precompile::ecrecover_sign_data, AccessList, AccessListItem, Word, H256,
+    };
     use halo2_proofs::{
         dev::{MockProver, VerifyFailure},
         halo2curves::bn256::Fr,
     };
     use mock::AddrOrWallet;
     use pretty_assertions::assert_eq;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
 
     const NUM_BLINDING_ROWS: usize = 64;
 
@@ -438,9 +631,37 @@ mod tx_circuit_tests {
         max_txs: usize,
         max_calldata: usize,
     ) -> Result<(), Vec<VerifyFailure>> {
-        let k = log2_ceil(NUM_BLINDING_ROWS + TxCircuit::<Fr>::min_num_rows(max_txs, max_calldata));
+        run_with_params::<F>(txs, vec![], chain_id, max_txs, max_calldata, 0, 0)
+    }
+
+    fn run_with_params<F: Field>(
+        txs: Vec<Transaction>,
+        ecrecovers: Vec<SignData>,
+        chain_id: u64,
+        max_txs: usize,
+        max_calldata: usize,
+        max_access_list: usize,
+        max_ecrecover: usize,
+    ) -> Result<(), Vec<VerifyFailure>> {
+        let k = log2_ceil(
+            NUM_BLINDING_ROWS
+                + TxCircuit::<Fr>::min_num_rows(
+                    max_txs,
+                    max_calldata,
+                    max_access_list,
+                    max_ecrecover,
+                ),
+        );
         // SignVerifyChip -> ECDSAChip -> MainGate instance column
-        let circuit = TxCircuit::<F>::new(max_txs, max_calldata, chain_id, txs);
+        let circuit = TxCircuit::<F>::new(
+            max_txs,
+            max_calldata,
+            max_access_list,
+            max_ecrecover,
+            chain_id,
+            txs,
+            ecrecovers,
+        );
 
         let prover = match MockProver::run(k, &circuit, vec![vec![]]) {
             Ok(prover) => prover,
@@ -493,6 +714,131 @@ mod tx_circuit_tests {
         assert_eq!(run::<Fr>(vec![tx], chain_id, MAX_TXS, MAX_CALLDATA), Ok(()));
     }
 
+    #[test]
+    fn tx_circuit_1tx_eip1559() {
+        const MAX_TXS: usize = 1;
+        const MAX_CALLDATA: usize = 32;
+
+        let mut rng = ChaCha20Rng::seed_from_u64(2);
+        let tx = mock::MockTransaction::default()
+            .from(AddrOrWallet::random(&mut rng))
+            .to(mock::MOCK_ACCOUNTS[0])
+            .transaction_type(2)
+            .max_fee_per_gas(Word::from(2000))
+            .max_priority_fee_per_gas(Word::from(1000))
+            .input(b"hello".to_vec().into())
+            .build();
+
+        assert_eq!(
+            run::<Fr>(
+                vec![tx.into()],
+                mock::MOCK_CHAIN_ID.as_u64(),
+                MAX_TXS,
+                MAX_CALLDATA
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn tx_circuit_1tx_1ecrecover_2max_ecrecover() {
+        const MAX_TXS: usize = 1;
+        const MAX_CALLDATA: usize = 32;
+        const MAX_ECRECOVER: usize = 2;
+
+        let input = hex::decode(
+            "456e9aea5e197a1f1af7a3e85a3212fa4049a3ba34c2289b4c860fc0b0c64ef3\
+             000000000000000000000000000000000000000000000000000000000000001c\
+             9242685bf161793cc25603c231bc2f568eb630ea16aa137d2664ac8038825608\
+             4f8ae3bd7535248d0bd448298cc2e2071e56992d0774dc340c368ae950852ada",
+        )
+        .unwrap();
+        let ecrecover = ecrecover_sign_data(&input).unwrap();
+        let tx: Transaction = mock::CORRECT_MOCK_TXS[0].clone().into();
+
+        assert_eq!(
+            run_with_params::<Fr>(
+                vec![tx],
+                vec![ecrecover],
+                mock::MOCK_CHAIN_ID.as_u64(),
+                MAX_TXS,
+                MAX_CALLDATA,
+                0,
+                MAX_ECRECOVER
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn tx_circuit_1tx_access_list() {
+        const MAX_TXS: usize = 1;
+        const MAX_CALLDATA: usize = 32;
+        const MAX_ACCESS_LIST: usize = 4;
+
+        let mut rng = ChaCha20Rng::seed_from_u64(2);
+        let tx = mock::MockTransaction::default()
+            .from(AddrOrWallet::random(&mut rng))
+            .to(mock::MOCK_ACCOUNTS[0])
+            .transaction_type(1)
+            .access_list(AccessList(vec![AccessListItem {
+                address: mock::MOCK_ACCOUNTS[1],
+                storage_keys: vec![H256::from_low_u64_be(1), H256::from_low_u64_be(2)],
+            }]))
+            .build();
+
+        assert_eq!(
+            run_with_params::<Fr>(
+                vec![tx.into()],
+                vec![],
+                mock::MOCK_CHAIN_ID.as_u64(),
+                MAX_TXS,
+                MAX_CALLDATA,
+                MAX_ACCESS_LIST,
+                0
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn tx_circuit_1tx_access_list_over_capacity() {
+        const MAX_TXS: usize = 1;
+        const MAX_CALLDATA: usize = 32;
+        const MAX_ACCESS_LIST: usize = 2;
+
+        let mut rng = ChaCha20Rng::seed_from_u64(2);
+        let tx: Transaction = mock::MockTransaction::default()
+            .from(AddrOrWallet::random(&mut rng))
+            .to(mock::MOCK_ACCOUNTS[0])
+            .transaction_type(1)
+            .access_list(AccessList(vec![AccessListItem {
+                address: mock::MOCK_ACCOUNTS[1],
+                storage_keys: vec![H256::from_low_u64_be(1), H256::from_low_u64_be(2)],
+            }]))
+            .build()
+            .into();
+        let circuit = TxCircuit::<Fr>::new(
+            MAX_TXS,
+            MAX_CALLDATA,
+            MAX_ACCESS_LIST,
+            0,
+            mock::MOCK_CHAIN_ID.as_u64(),
+            vec![tx],
+            vec![],
+        );
+        let k = log2_ceil(
+            NUM_BLINDING_ROWS + TxCircuit::<Fr>::min_num_rows(MAX_TXS, MAX_CALLDATA, 3, 0),
+        );
+
+        // The 3 entries of the access list don't fit in the 2 rows of the
+        // tx table, which is an error instead of a panic.
+        assert!(matches!(
+            MockProver::run(k, &circuit, vec![vec![]]),
+            Err(Error::Synthesis)
+        ));
+    }
+
     #[test]
     fn tx_circuit_bad_address() {
         const MAX_TXS: usize = 1;
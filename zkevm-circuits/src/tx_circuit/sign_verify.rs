@@ -10,7 +10,7 @@ use crate::{
     util::{Challenges, Expr},
 };
 use ecc::{maingate, EccConfig, GeneralEccChip};
-use ecdsa::ecdsa::{AssignedEcdsaSig, AssignedPublicKey, EcdsaChip};
+use ecdsa::ecdsa::{AssignedEcdsaSig, AssignedPublicKey};
 use eth_types::sign_types::{pk_bytes_le, pk_bytes_swap_endianness, SignData};
 use eth_types::{self, Field};
 use halo2_proofs::{
@@ -39,7 +39,9 @@ use num::Integer;
 use std::{iter, marker::PhantomData};
 
 /// Auxiliary Gadget to verify a that a message hash is signed by the public
-/// key corresponding to an Ethereum Address.
+/// key corresponding to an Ethereum Address.  The verifications also bind the
+/// RLCs of the ecrecover input, including the recovery id, and the address,
+/// for the calls to ecrecover.
 #[derive(Clone, Debug)]
 pub struct SignVerifyChip<F: Field> {
     /// Aux generator for EccChip
@@ -80,11 +82,14 @@ impl<F: Field> SignVerifyChip<F> {
         // --all-features -- --nocapture`
         // The value rows_range_chip_table has been optained by patching the halo2
         // library to report the number of rows used in the range chip table
-        // region. TODO: Figure out a way to get these numbers automatically.
+        // region. The rows added by the byte decomposition of the signature and of
+        // the y coordinate of R, the recovery id and the RLCs of the ecrecover input
+        // and address are estimated from the number of bytes. TODO: Figure out a way
+        // to get these numbers automatically.
         let rows_range_chip_table = 295188;
         let rows_ecc_chip_aux = 226;
-        let rows_ecdsa_chip_verification = 140360;
-        let rows_signature_address_verify = 76;
+        let rows_ecdsa_chip_verification = 140360 + 3 * 16 + 16 + 8;
+        let rows_signature_address_verify = 76 + 2 * 32 + 20 + 34 + 2;
         std::cmp::max(
             rows_range_chip_table,
             (rows_ecc_chip_aux + rows_ecdsa_chip_verification + rows_signature_address_verify)
@@ -305,12 +310,20 @@ pub(crate) struct AssignedECDSA<F: Field> {
     pk_x_le: [AssignedValue<F>; 32],
     pk_y_le: [AssignedValue<F>; 32],
     msg_hash_le: [AssignedValue<F>; 32],
+    sig_r_le: [AssignedValue<F>; 32],
+    sig_s_le: [AssignedValue<F>; 32],
+    recovery_id: AssignedValue<F>,
 }
 
 #[derive(Debug)]
 pub(crate) struct AssignedSignatureVerify<F: Field> {
     pub(crate) address: AssignedValue<F>,
     pub(crate) msg_hash_rlc: AssignedValue<F>,
+    /// RLC of the input of a call to ecrecover, whose `v` is 27 plus the
+    /// recovery id, which is zero for padding.
+    pub(crate) ecrecover_input_rlc: AssignedValue<F>,
+    /// RLC of the address bytes, which is zero for padding.
+    pub(crate) address_rlc: AssignedValue<F>,
 }
 
 // Return an array of bytes that corresponds to the little endian representation
@@ -344,7 +357,6 @@ struct ChipsRef<'a, F: Field, const NUMBER_OF_LIMBS: usize, const BIT_LEN_LIMB:
     range_chip: &'a RangeChip<F>,
     ecc_chip: &'a GeneralEccChip<Secp256k1Affine, F, NUMBER_OF_LIMBS, BIT_LEN_LIMB>,
     scalar_chip: &'a IntegerChip<secp256k1::Fq, F, NUMBER_OF_LIMBS, BIT_LEN_LIMB>,
-    ecdsa_chip: &'a EcdsaChip<Secp256k1Affine, F, NUMBER_OF_LIMBS, BIT_LEN_LIMB>,
 }
 
 impl<F: Field> SignVerifyChip<F> {
@@ -372,11 +384,10 @@ impl<F: Field> SignVerifyChip<F> {
         let (sig_r, sig_s) = signature;
 
         let ChipsRef {
-            main_gate: _,
+            main_gate,
             range_chip,
             ecc_chip,
             scalar_chip,
-            ecdsa_chip,
         } = chips;
 
         let integer_r = ecc_chip.new_unassigned_scalar(Value::known(*sig_r));
@@ -385,6 +396,8 @@ impl<F: Field> SignVerifyChip<F> {
 
         let r_assigned = scalar_chip.assign_integer(ctx, integer_r, Range::Remainder)?;
         let s_assigned = scalar_chip.assign_integer(ctx, integer_s, Range::Remainder)?;
+        let sig_r_le = integer_to_bytes_le(ctx, range_chip, &r_assigned)?;
+        let sig_s_le = integer_to_bytes_le(ctx, range_chip, &s_assigned)?;
         let sig = AssignedEcdsaSig {
             r: r_assigned,
             s: s_assigned,
@@ -403,8 +416,29 @@ impl<F: Field> SignVerifyChip<F> {
         let pk_y = pk_assigned.point.y();
         let pk_y_le = integer_to_bytes_le(ctx, range_chip, pk_y)?;
 
-        // Ref. spec SignVerifyChip 4. Verify the ECDSA signature
-        ecdsa_chip.verify(ctx, &sig, &pk_assigned, &msg_hash)?;
+        // Ref. spec SignVerifyChip 4. Verify the ECDSA signature as
+        // `EcdsaChip::verify` does, keeping the point `R = u1 * G + u2 * pk` whose
+        // x coordinate is `r`, and whose y coordinate parity is the recovery id
+        let base_chip = ecc_chip.base_field_chip();
+        scalar_chip.assert_not_zero(ctx, &sig.r)?;
+        scalar_chip.assert_not_zero(ctx, &sig.s)?;
+        let (s_inv, _) = scalar_chip.invert(ctx, &sig.s)?;
+        let u1 = scalar_chip.mul(ctx, &msg_hash, &s_inv)?;
+        let u2 = scalar_chip.mul(ctx, &sig.r, &s_inv)?;
+        let generator = ecc_chip.assign_constant(ctx, Secp256k1Affine::generator())?;
+        let g1 = ecc_chip.mul(ctx, &generator, &u1, self.window_size)?;
+        let g2 = ecc_chip.mul(ctx, &pk_assigned.point, &u2, self.window_size)?;
+        let sig_point = ecc_chip.add(ctx, &g1, &g2)?;
+        let sig_point_x = base_chip.reduce(ctx, sig_point.x())?;
+        let sig_point_x = scalar_chip.reduce_external(ctx, &sig_point_x)?;
+        scalar_chip.assert_strict_equal(ctx, &sig_point_x, &sig.r)?;
+
+        let sig_point_y = base_chip.reduce(ctx, sig_point.y())?;
+        base_chip.assert_in_field(ctx, &sig_point_y)?;
+        let sig_point_y_le = integer_to_bytes_le(ctx, range_chip, &sig_point_y)?;
+        let recovery_id = main_gate
+            .to_bits(ctx, &sig_point_y_le[0], 8)?
+            .swap_remove(0);
 
         // TODO: Update once halo2wrong suports the following methods:
         // - `IntegerChip::assign_integer_from_bytes_le`
@@ -414,6 +448,9 @@ impl<F: Field> SignVerifyChip<F> {
             pk_x_le,
             pk_y_le,
             msg_hash_le,
+            sig_r_le,
+            sig_s_le,
+            recovery_id,
         })
     }
 
@@ -508,15 +545,15 @@ impl<F: Field> SignVerifyChip<F> {
 
         let pk_le = pk_bytes_le(&sign_data.pk);
         let pk_be = pk_bytes_swap_endianness(&pk_le);
-        let pk_hash = (!padding)
+        let pk_hash_bytes = (!padding)
             .then(|| {
                 let mut keccak = Keccak::default();
                 keccak.update(&pk_be);
                 let hash: [_; 32] = keccak.digest().try_into().expect("vec to array of size 32");
                 hash
             })
-            .unwrap_or_default()
-            .map(|byte| Value::known(F::from(byte as u64)));
+            .unwrap_or_default();
+        let pk_hash = pk_hash_bytes.map(|byte| Value::known(F::from(byte as u64)));
         let pk_hash_hi = pk_hash[..12].to_vec();
         // Ref. spec SignVerifyChip 2. Verify that the first 20 bytes of the
         // pub_key_hash equal the address
@@ -530,46 +567,93 @@ impl<F: Field> SignVerifyChip<F> {
                 .zip(powers_of_256.into_iter().rev())
                 .map(|(byte, coeff)| maingate::Term::Unassigned(*byte, coeff))
                 .collect_vec();
-            let (address, pk_hash_lo) =
-                main_gate.decompose(ctx, &terms, F::zero(), |_, _| Ok(()))?;
-
-            (
-                address,
-                pk_hash_lo
-                    .into_iter()
-                    .zip(pk_hash[12..].iter())
-                    .map(|(assigned, byte)| Term::assigned(assigned.cell(), *byte))
-                    .collect_vec(),
-            )
+            main_gate.decompose(ctx, &terms, F::zero(), |_, _| Ok(()))?
         };
         let is_address_zero = main_gate.is_zero(ctx, &address)?;
 
+        // The bytes are set to zero for padding, so that the RLCs of the padding
+        // verifications are zero.
+        let zero = main_gate.assign_constant(ctx, F::zero())?;
+        let select_bytes_le =
+            |ctx: &mut RegionCtx<F>, assigned: &[AssignedValue<F>], bytes_le: Vec<u8>| {
+                assigned
+                    .iter()
+                    .zip(bytes_le)
+                    .map(|(assigned, byte)| {
+                        let selected = main_gate.select(ctx, &zero, assigned, &is_address_zero)?;
+                        let byte = if padding { 0 } else { byte };
+                        Ok(Term::assigned(
+                            selected.cell(),
+                            Value::known(F::from(byte as u64)),
+                        ))
+                    })
+                    .collect::<Result<Vec<_>, Error>>()
+            };
+        let msg_hash_le = select_bytes_le(
+            ctx,
+            &assigned_ecdsa.msg_hash_le,
+            sign_data.msg_hash.to_bytes().to_vec(),
+        )?;
+        let sig_r_le = select_bytes_le(
+            ctx,
+            &assigned_ecdsa.sig_r_le,
+            sign_data.signature.0.to_bytes().to_vec(),
+        )?;
+        let sig_s_le = select_bytes_le(
+            ctx,
+            &assigned_ecdsa.sig_s_le,
+            sign_data.signature.1.to_bytes().to_vec(),
+        )?;
+        let address_le = select_bytes_le(
+            ctx,
+            &pk_hash_lo.iter().rev().cloned().collect_vec(),
+            pk_hash_bytes[12..].iter().rev().copied().collect(),
+        )?;
+        let v = main_gate.add_constant(ctx, &assigned_ecdsa.recovery_id, F::from(27))?;
+        let v = main_gate.select(ctx, &zero, &v, &is_address_zero)?;
+
         // Ref. spec SignVerifyChip 3. Verify that the signed message in the ecdsa_chip
         // with RLC encoding corresponds to msg_hash_rlc
-        let msg_hash_rlc = {
-            let zero = main_gate.assign_constant(ctx, F::zero())?;
-            let assigned_msg_hash_le = assigned_ecdsa
-                .msg_hash_le
-                .iter()
-                .map(|byte| main_gate.select(ctx, &zero, byte, &is_address_zero))
-                .collect::<Result<Vec<_>, _>>()?;
-            let msg_hash_le = (!padding)
-                .then(|| sign_data.msg_hash.to_bytes())
-                .unwrap_or_default()
-                .map(|byte| Value::known(F::from(byte as u64)));
-            self.assign_rlc_le(
-                config,
-                ctx,
-                chips,
-                "msg_hash",
-                config.q_rlc_evm_word,
-                challenges.evm_word(),
-                assigned_msg_hash_le
-                    .iter()
-                    .zip(msg_hash_le)
-                    .map(|(assigned, byte)| Term::assigned(assigned.cell(), byte)),
-            )?
-        };
+        let msg_hash_rlc = self.assign_rlc_le(
+            config,
+            ctx,
+            chips,
+            "msg_hash",
+            config.q_rlc_evm_word,
+            challenges.evm_word(),
+            msg_hash_le.iter().cloned(),
+        )?;
+
+        // Ref. spec SignVerifyChip 5. Verify that the input of a call to ecrecover
+        // (msg_hash, v, r, s) with RLC encoding, where v is 27 plus the recovery id,
+        // corresponds to ecrecover_input_rlc, and that the recovered address with RLC
+        // encoding corresponds to address_rlc
+        let ecrecover_input_rlc = self.assign_rlc_le(
+            config,
+            ctx,
+            chips,
+            "ecrecover_input",
+            config.q_rlc_keccak_input,
+            challenges.keccak_input(),
+            iter::empty()
+                .chain(sig_s_le)
+                .chain(sig_r_le)
+                .chain(iter::once(Term::assigned(v.cell(), v.value().copied())))
+                .chain(
+                    iter::repeat_with(|| Term::assigned(zero.cell(), Value::known(F::zero())))
+                        .take(31),
+                )
+                .chain(msg_hash_le),
+        )?;
+        let address_rlc = self.assign_rlc_le(
+            config,
+            ctx,
+            chips,
+            "address",
+            config.q_rlc_keccak_input,
+            challenges.keccak_input(),
+            address_le,
+        )?;
 
         let pk_rlc = {
             let assigned_pk_le = iter::empty()
@@ -600,7 +684,13 @@ impl<F: Field> SignVerifyChip<F> {
             config.q_rlc_evm_word,
             challenges.evm_word(),
             iter::empty()
-                .chain(pk_hash_lo.into_iter().rev())
+                .chain(
+                    pk_hash_lo
+                        .iter()
+                        .zip(&pk_hash[12..])
+                        .map(|(assigned, byte)| Term::assigned(assigned.cell(), *byte))
+                        .rev(),
+                )
                 .chain(pk_hash_hi.into_iter().rev().map(Term::unassigned)),
         )?;
 
@@ -609,14 +699,18 @@ impl<F: Field> SignVerifyChip<F> {
         Ok(AssignedSignatureVerify {
             address,
             msg_hash_rlc,
+            ecrecover_input_rlc,
+            address_rlc,
         })
     }
 
+    /// Assign the verifications of the signatures, where the `None` signatures
+    /// and the verifications after the last signature are padding.
     pub(crate) fn assign(
         &self,
         config: &SignVerifyConfig,
         layouter: &mut impl Layouter<F>,
-        signatures: &[SignData],
+        signatures: &[Option<SignData>],
         challenges: &Challenges<Value<F>>,
     ) -> Result<Vec<AssignedSignatureVerify<F>>, Error> {
         if signatures.len() > self.max_verif {
@@ -645,14 +739,11 @@ impl<F: Field> SignVerifyChip<F> {
             },
         )?;
 
-        let ecdsa_chip = EcdsaChip::new(ecc_chip.clone());
-
         let chips = ChipsRef {
             main_gate: &main_gate,
             range_chip: &range_chip,
             ecc_chip: &ecc_chip,
             scalar_chip,
-            ecdsa_chip: &ecdsa_chip,
         };
 
         let assigned_ecdsas = layouter.assign_region(
@@ -661,12 +752,8 @@ impl<F: Field> SignVerifyChip<F> {
                 let mut assigned_ecdsas = Vec::new();
                 let mut ctx = RegionCtx::new(region, 0);
                 for i in 0..self.max_verif {
-                    let signature = if i < signatures.len() {
-                        signatures[i].clone()
-                    } else {
-                        // padding (enabled when address == 0)
-                        SignData::default()
-                    };
+                    // padding (enabled when address == 0)
+                    let signature = signatures.get(i).cloned().flatten().unwrap_or_default();
                     let assigned_ecdsa = self.assign_ecdsa(&mut ctx, &chips, &signature)?;
                     assigned_ecdsas.push(assigned_ecdsa);
                 }
@@ -681,7 +768,8 @@ impl<F: Field> SignVerifyChip<F> {
                 let mut assigned_sig_verifs = Vec::new();
                 let mut ctx = RegionCtx::new(region, 0);
                 for (i, assigned_ecdsa) in assigned_ecdsas.iter().enumerate() {
-                    let sign_data = signatures.get(i); // None when padding (enabled when address == 0)
+                    // None when padding (enabled when address == 0)
+                    let sign_data = signatures.get(i).and_then(Option::as_ref);
                     let assigned_sig_verif = self.assign_signature_verify(
                         config,
                         &mut ctx,
@@ -710,11 +798,11 @@ mod sign_verify_tests {
     use super::*;
     use crate::util::Challenges;
     use bus_mapping::circuit_input_builder::keccak_inputs_sign_verify;
-    use eth_types::sign_types::sign;
+    use eth_types::{precompile::ecrecover_sign_data, sign_types::sign};
     use halo2_proofs::arithmetic::Field as HaloField;
     use halo2_proofs::{
         circuit::SimpleFloorPlanner,
-        dev::MockProver,
+        dev::{MockProver, VerifyFailure},
         halo2curves::{
             bn256::Fr,
             group::{Curve, Group},
@@ -729,6 +817,7 @@ mod sign_verify_tests {
     #[derive(Clone, Debug)]
     struct TestCircuitSignVerifyConfig {
         sign_verify: SignVerifyConfig,
+        ecrecover_input_rlc: Column<Advice>,
         challenges: Challenges,
     }
 
@@ -736,6 +825,8 @@ mod sign_verify_tests {
         pub(crate) fn new<F: Field>(meta: &mut ConstraintSystem<F>) -> Self {
             let keccak_table = KeccakTable::construct(meta);
             let challenges = Challenges::construct(meta);
+            let ecrecover_input_rlc = meta.advice_column_in(SecondPhase);
+            meta.enable_equality(ecrecover_input_rlc);
 
             let sign_verify = {
                 let challenges = challenges.exprs(meta);
@@ -744,6 +835,7 @@ mod sign_verify_tests {
 
             TestCircuitSignVerifyConfig {
                 sign_verify,
+                ecrecover_input_rlc,
                 challenges,
             }
         }
@@ -753,6 +845,9 @@ mod sign_verify_tests {
     struct TestCircuitSignVerify<F: Field> {
         sign_verify: SignVerifyChip<F>,
         signatures: Vec<SignData>,
+        /// Inputs of ecrecover whose RLCs are constrained to be the ones of the
+        /// verifications of the first signatures
+        ecrecover_inputs: Vec<Vec<u8>>,
     }
 
     impl<F: Field> Circuit<F> for TestCircuitSignVerify<F> {
@@ -774,12 +869,38 @@ mod sign_verify_tests {
         ) -> Result<(), Error> {
             let challenges = config.challenges.values(&mut layouter);
 
-            self.sign_verify.assign(
+            let assigned_sig_verifs = self.sign_verify.assign(
                 &config.sign_verify,
                 &mut layouter,
-                &self.signatures,
+                &self.signatures.iter().cloned().map(Some).collect_vec(),
                 &challenges,
             )?;
+            layouter.assign_region(
+                || "ecrecover inputs",
+                |mut region| {
+                    for (offset, (input, assigned_sig_verif)) in self
+                        .ecrecover_inputs
+                        .iter()
+                        .zip(&assigned_sig_verifs)
+                        .enumerate()
+                    {
+                        let input_rlc = challenges
+                            .keccak_input()
+                            .map(|challenge| rlc::value(input.iter().rev(), challenge));
+                        let assigned = region.assign_advice(
+                            || format!("ecrecover input rlc {}", offset),
+                            config.ecrecover_input_rlc,
+                            offset,
+                            || input_rlc,
+                        )?;
+                        region.constrain_equal(
+                            assigned.cell(),
+                            assigned_sig_verif.ecrecover_input_rlc.cell(),
+                        )?;
+                    }
+                    Ok(())
+                },
+            )?;
             config.sign_verify.keccak_table.dev_load(
                 &mut layouter,
                 &keccak_inputs_sign_verify(&self.signatures),
@@ -791,6 +912,18 @@ mod sign_verify_tests {
     }
 
     fn run<F: Field>(k: u32, max_verif: usize, signatures: Vec<SignData>) {
+        assert_eq!(
+            run_with_ecrecover_inputs::<F>(k, max_verif, signatures, vec![]),
+            Ok(())
+        );
+    }
+
+    fn run_with_ecrecover_inputs<F: Field>(
+        k: u32,
+        max_verif: usize,
+        signatures: Vec<SignData>,
+        ecrecover_inputs: Vec<Vec<u8>>,
+    ) -> Result<(), Vec<VerifyFailure>> {
         let mut rng = XorShiftRng::seed_from_u64(2);
         let aux_generator =
             <Secp256k1Affine as CurveAffine>::CurveExt::random(&mut rng).to_affine();
@@ -804,13 +937,14 @@ mod sign_verify_tests {
                 _marker: PhantomData,
             },
             signatures,
+            ecrecover_inputs,
         };
 
         let prover = match MockProver::run(k, &circuit, vec![vec![]]) {
             Ok(prover) => prover,
             Err(e) => panic!("{:#?}", e),
         };
-        assert_eq!(prover.verify(), Ok(()));
+        prover.verify()
     }
 
     // Generate a test key pair
@@ -867,4 +1001,29 @@ mod sign_verify_tests {
         let k = 19;
         run::<Fr>(k, MAX_VERIF, signatures);
     }
+
+    #[test]
+    fn sign_verify_ecrecover() {
+        let input = hex::decode(
+            "456e9aea5e197a1f1af7a3e85a3212fa4049a3ba34c2289b4c860fc0b0c64ef3\
+             000000000000000000000000000000000000000000000000000000000000001c\
+             9242685bf161793cc25603c231bc2f568eb630ea16aa137d2664ac8038825608\
+             4f8ae3bd7535248d0bd448298cc2e2071e56992d0774dc340c368ae950852ada",
+        )
+        .unwrap();
+        let sign_data = ecrecover_sign_data(&input).unwrap();
+        assert_eq!(
+            run_with_ecrecover_inputs::<Fr>(19, 1, vec![sign_data], vec![input.clone()]),
+            Ok(())
+        );
+
+        // Both public keys verify the signature, but the one recovered with the
+        // other `v` isn't bound to the input.
+        let mut input_wrong_v = input.clone();
+        input_wrong_v[63] = 0x1b;
+        let wrong_sign_data = ecrecover_sign_data(&input_wrong_v).unwrap();
+        assert!(
+            run_with_ecrecover_inputs::<Fr>(19, 1, vec![wrong_sign_data], vec![input]).is_err()
+        );
+    }
 }
@@ -95,6 +95,12 @@ impl<T: Clone> Challenges<T> {
         self.keccak_input.clone()
     }
 
+    /// Returns the challenges indexed by the order in which they are
+    /// allocated in the `ConstraintSystem`.
+    pub fn indexed(&self) -> [&T; 2] {
+        [&self.evm_word, &self.keccak_input]
+    }
+
     pub(crate) fn mock(evm_word: T, keccak_input: T) -> Self {
         Self {
             evm_word,
@@ -114,6 +120,17 @@ impl<F: Field> Challenges<Expression<F>> {
         .try_into()
         .unwrap()
     }
+
+    /// Returns powers of randomness for keccak input RLC encoding
+    pub fn keccak_powers_of_randomness<const S: usize>(&self) -> [Expression<F>; S] {
+        std::iter::successors(self.keccak_input.clone().into(), |power| {
+            (self.keccak_input.clone() * power.clone()).into()
+        })
+        .take(S)
+        .collect::<Vec<_>>()
+        .try_into()
+        .unwrap()
+    }
 }
 
 pub(crate) fn build_tx_log_address(index: u64, field_tag: TxLogFieldTag, log_id: u64) -> Address {
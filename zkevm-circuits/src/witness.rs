@@ -9,7 +9,10 @@ pub use bytecode::Bytecode;
 mod call;
 pub use call::Call;
 mod mpt;
+pub(crate) use mpt::{MptProof, MptUpdateWitness, EMPTY_CODE_HASH, EMPTY_TRIE_ROOT};
 pub use mpt::{MptUpdate, MptUpdateRow, MptUpdates};
+mod receipt;
+pub use receipt::{Receipt, ReceiptLog};
 mod rw;
 pub use rw::{Rw, RwMap, RwRow};
 mod step;
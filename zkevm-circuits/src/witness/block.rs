@@ -1,13 +1,19 @@
 use std::collections::HashMap;
 
-use crate::{evm_circuit::util::RandomLinearCombination, table::BlockContextFieldTag};
+use crate::{
+    evm_circuit::util::RandomLinearCombination, pi_circuit::PublicData, table::BlockContextFieldTag,
+};
 use bus_mapping::{
-    circuit_input_builder::{self, CircuitsParams, CopyEvent, ExpEvent},
+    circuit_input_builder::{self, CircuitsParams, CopyEvent, ExpEvent, PrecompileEvent},
     Error,
 };
 use eth_types::{Address, Field, ToLittleEndian, ToScalar, Word};
+use ethers_core::types::Bloom;
+use halo2_proofs::circuit::Value;
 
-use super::{step::step_convert, tx::tx_convert, Bytecode, ExecStep, RwMap, Transaction};
+use super::{
+    step::step_convert, tx::tx_convert, Bytecode, ExecStep, MptUpdates, RwMap, Transaction,
+};
 
 // TODO: Remove fields that are duplicated in`eth_block`
 /// Block is the struct used by all circuits, which contains all the needed
@@ -18,6 +24,9 @@ pub struct Block<F> {
     pub randomness: F,
     /// Transactions in the block
     pub txs: Vec<Transaction>,
+    /// BlockReward step paying the block reward to the coinbase after the last
+    /// transaction.
+    pub block_reward: ExecStep,
     /// EndBlock step that is repeated after the last transaction and before
     /// reaching the last EVM row.
     pub end_block_not_last: ExecStep,
@@ -33,6 +42,8 @@ pub struct Block<F> {
     pub copy_events: Vec<CopyEvent>,
     /// Exponentiation traces for the exponentiation circuit's table.
     pub exp_events: Vec<ExpEvent>,
+    /// Calls to the precompiled contracts for the precompile table.
+    pub precompile_events: Vec<PrecompileEvent>,
     // TODO: Rename to `max_evm_rows`, maybe move to CircuitsParams
     /// Pad evm circuit to make selectors fixed, so vk/pk can be universal.
     /// When 0, the EVM circuit contains as many rows for all steps + 1 row
@@ -50,6 +61,8 @@ pub struct Block<F> {
     pub keccak_inputs: Vec<Vec<u8>>,
     /// Original Block from geth
     pub eth_block: eth_types::Block<eth_types::Transaction>,
+    /// State trie updates of the block, proven by the MPT circuit
+    pub mpt_updates: MptUpdates,
 }
 
 /// Block context for execution
@@ -71,56 +84,86 @@ pub struct BlockContext {
     pub history_hashes: Vec<Word>,
     /// The chain id
     pub chain_id: Word,
+    /// The reward paid to the coinbase at the end of the block
+    pub block_reward: Word,
+    /// The logs bloom of the block header
+    pub logs_bloom: Bloom,
 }
 
 impl BlockContext {
+    /// The logs bloom split in words of 32 bytes, from the most significant
+    /// one
+    pub fn logs_bloom_words(&self) -> Vec<Word> {
+        self.logs_bloom
+            .as_bytes()
+            .chunks(32)
+            .map(Word::from_big_endian)
+            .collect()
+    }
+
     /// Assignments for block table
-    pub fn table_assignments<F: Field>(&self, randomness: F) -> Vec<[F; 3]> {
+    pub fn table_assignments<F: Field>(&self, randomness: Value<F>) -> Vec<[Value<F>; 3]> {
         [
             vec![
                 [
-                    F::from(BlockContextFieldTag::Coinbase as u64),
-                    F::zero(),
-                    self.coinbase.to_scalar().unwrap(),
+                    Value::known(F::from(BlockContextFieldTag::Coinbase as u64)),
+                    Value::known(F::zero()),
+                    Value::known(self.coinbase.to_scalar().unwrap()),
+                ],
+                [
+                    Value::known(F::from(BlockContextFieldTag::Timestamp as u64)),
+                    Value::known(F::zero()),
+                    Value::known(self.timestamp.to_scalar().unwrap()),
                 ],
                 [
-                    F::from(BlockContextFieldTag::Timestamp as u64),
-                    F::zero(),
-                    self.timestamp.to_scalar().unwrap(),
+                    Value::known(F::from(BlockContextFieldTag::Number as u64)),
+                    Value::known(F::zero()),
+                    Value::known(self.number.to_scalar().unwrap()),
                 ],
                 [
-                    F::from(BlockContextFieldTag::Number as u64),
-                    F::zero(),
-                    self.number.to_scalar().unwrap(),
+                    Value::known(F::from(BlockContextFieldTag::Difficulty as u64)),
+                    Value::known(F::zero()),
+                    randomness.map(|randomness| {
+                        RandomLinearCombination::random_linear_combine(
+                            self.difficulty.to_le_bytes(),
+                            randomness,
+                        )
+                    }),
                 ],
                 [
-                    F::from(BlockContextFieldTag::Difficulty as u64),
-                    F::zero(),
-                    RandomLinearCombination::random_linear_combine(
-                        self.difficulty.to_le_bytes(),
-                        randomness,
-                    ),
+                    Value::known(F::from(BlockContextFieldTag::GasLimit as u64)),
+                    Value::known(F::zero()),
+                    Value::known(F::from(self.gas_limit)),
                 ],
                 [
-                    F::from(BlockContextFieldTag::GasLimit as u64),
-                    F::zero(),
-                    F::from(self.gas_limit),
+                    Value::known(F::from(BlockContextFieldTag::BaseFee as u64)),
+                    Value::known(F::zero()),
+                    randomness.map(|randomness| {
+                        RandomLinearCombination::random_linear_combine(
+                            self.base_fee.to_le_bytes(),
+                            randomness,
+                        )
+                    }),
                 ],
                 [
-                    F::from(BlockContextFieldTag::BaseFee as u64),
-                    F::zero(),
-                    RandomLinearCombination::random_linear_combine(
-                        self.base_fee.to_le_bytes(),
-                        randomness,
-                    ),
+                    Value::known(F::from(BlockContextFieldTag::ChainId as u64)),
+                    Value::known(F::zero()),
+                    randomness.map(|randomness| {
+                        RandomLinearCombination::random_linear_combine(
+                            self.chain_id.to_le_bytes(),
+                            randomness,
+                        )
+                    }),
                 ],
                 [
-                    F::from(BlockContextFieldTag::ChainId as u64),
-                    F::zero(),
-                    RandomLinearCombination::random_linear_combine(
-                        self.chain_id.to_le_bytes(),
-                        randomness,
-                    ),
+                    Value::known(F::from(BlockContextFieldTag::BlockReward as u64)),
+                    Value::known(F::zero()),
+                    randomness.map(|randomness| {
+                        RandomLinearCombination::random_linear_combine(
+                            self.block_reward.to_le_bytes(),
+                            randomness,
+                        )
+                    }),
                 ],
             ],
             {
@@ -130,16 +173,34 @@ impl BlockContext {
                     .enumerate()
                     .map(|(idx, hash)| {
                         [
-                            F::from(BlockContextFieldTag::BlockHash as u64),
-                            (self.number - len_history + idx).to_scalar().unwrap(),
-                            RandomLinearCombination::random_linear_combine(
-                                hash.to_le_bytes(),
-                                randomness,
-                            ),
+                            Value::known(F::from(BlockContextFieldTag::BlockHash as u64)),
+                            Value::known((self.number - len_history + idx).to_scalar().unwrap()),
+                            randomness.map(|randomness| {
+                                RandomLinearCombination::random_linear_combine(
+                                    hash.to_le_bytes(),
+                                    randomness,
+                                )
+                            }),
                         ]
                     })
                     .collect()
             },
+            self.logs_bloom_words()
+                .iter()
+                .enumerate()
+                .map(|(idx, word)| {
+                    [
+                        Value::known(F::from(BlockContextFieldTag::LogsBloom as u64)),
+                        Value::known(F::from(idx as u64)),
+                        randomness.map(|randomness| {
+                            RandomLinearCombination::random_linear_combine(
+                                word.to_le_bytes(),
+                                randomness,
+                            )
+                        }),
+                    ]
+                })
+                .collect(),
         ]
         .concat()
     }
@@ -156,6 +217,8 @@ impl From<&circuit_input_builder::Block> for BlockContext {
             base_fee: block.base_fee,
             history_hashes: block.history_hashes.clone(),
             chain_id: block.chain_id,
+            block_reward: block.circuits_params.block_reward,
+            logs_bloom: block.eth_block.logs_bloom.unwrap_or_default(),
         }
     }
 }
@@ -165,17 +228,27 @@ pub fn block_convert<F: Field>(
     block: &circuit_input_builder::Block,
     code_db: &bus_mapping::state_db::CodeDB,
 ) -> Result<Block<F>, Error> {
-    Ok(Block {
+    let rws = RwMap::from(&block.container);
+    let mpt_updates = MptUpdates::new(
+        &rws.table_assignments(),
+        block.prev_state_root,
+        &[&block.state_proofs[..], &block.post_state_proofs[..]].concat(),
+    )?;
+    let mut keccak_inputs = circuit_input_builder::keccak_inputs(block, code_db)?;
+    keccak_inputs.extend(mpt_updates.keccak_inputs());
+
+    let mut block = Block {
         randomness: F::from(0xcafeu64),
         // randomness: F::from(0x100), // Special value to reveal elements after RLC
         context: block.into(),
-        rws: RwMap::from(&block.container),
+        rws,
         txs: block
             .txs()
             .iter()
             .enumerate()
             .map(|(idx, tx)| tx_convert(tx, idx + 1))
             .collect(),
+        block_reward: step_convert(&block.block_steps.block_reward),
         end_block_not_last: step_convert(&block.block_steps.end_block_not_last),
         end_block_last: step_convert(&block.block_steps.end_block_last),
         bytecodes: code_db
@@ -188,12 +261,21 @@ pub fn block_convert<F: Field>(
             .collect(),
         copy_events: block.copy_events.clone(),
         exp_events: block.exp_events.clone(),
+        precompile_events: block.precompile_events.clone(),
         sha3_inputs: block.sha3_inputs.clone(),
         circuits_params: block.circuits_params.clone(),
         evm_circuit_pad_to: <usize>::default(),
         exp_circuit_pad_to: <usize>::default(),
         prev_state_root: block.prev_state_root,
-        keccak_inputs: circuit_input_builder::keccak_inputs(block, code_db)?,
+        keccak_inputs,
         eth_block: block.eth_block.clone(),
-    })
+        mpt_updates,
+    };
+    // PI Circuit
+    let public_data = PublicData::new(&block);
+    block.keccak_inputs.push(public_data.header_rlp());
+    block.keccak_inputs.push(public_data.txs_rlp());
+    block.keccak_inputs.push(public_data.receipts_bytes());
+
+    Ok(block)
 }
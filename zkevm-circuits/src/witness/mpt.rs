@@ -1,9 +1,17 @@
 use crate::evm_circuit::{util::RandomLinearCombination, witness::Rw};
 use crate::table::{AccountFieldTag, ProofType};
-use eth_types::{Address, Field, ToLittleEndian, ToScalar, Word};
+use bus_mapping::Error;
+use eth_types::{
+    Address, EIP1186ProofResponse, Field, Hash, ToBigEndian, ToLittleEndian, ToScalar, Word,
+};
 use halo2_proofs::circuit::Value;
 use itertools::Itertools;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+
+mod trie;
+
+use trie::{drifted_node, encode_storage_value, key_path, Account, Trie};
+pub(crate) use trie::{EMPTY_CODE_HASH, EMPTY_TRIE_ROOT};
 
 /// An MPT update whose validility is proved by the MptCircuit
 #[derive(Debug, Clone, Copy)]
@@ -17,7 +25,11 @@ pub struct MptUpdate {
 
 impl MptUpdate {
     fn proof_type<F: Field>(&self) -> F {
-        let proof_type = match self.key {
+        F::from(self.proof_type_tag() as u64)
+    }
+
+    pub(crate) fn proof_type_tag(&self) -> ProofType {
+        match self.key {
             Key::AccountStorage { .. } => {
                 if self.old_value.is_zero() && self.new_value.is_zero() {
                     ProofType::StorageDoesNotExist
@@ -26,14 +38,59 @@ impl MptUpdate {
                 }
             }
             Key::Account { field_tag, .. } => field_tag.into(),
-        };
-        F::from(proof_type as u64)
+        }
+    }
+
+    pub(crate) fn address(&self) -> Address {
+        match self.key {
+            Key::Account { address, .. } | Key::AccountStorage { address, .. } => address,
+        }
+    }
+
+    pub(crate) fn storage_key(&self) -> Option<Word> {
+        match self.key {
+            Key::Account { .. } => None,
+            Key::AccountStorage { storage_key, .. } => Some(storage_key),
+        }
     }
 }
 
+/// The encoded trie nodes visited to find a key, from the root to the node
+/// holding its value or proving its absence, before and after an update.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct MptProof {
+    pub(crate) old: Vec<Vec<u8>>,
+    pub(crate) new: Vec<Vec<u8>>,
+    /// The node drifting below the branch inserted or deleted by the update,
+    /// when it splits the last node of the shorter side or collapses into it
+    pub(crate) drifted: Option<Vec<u8>>,
+}
+
+impl MptProof {
+    fn new(old: Vec<Vec<u8>>, new: Vec<Vec<u8>>) -> Result<Self, Error> {
+        let drifted = drifted_node(&old, &new)?;
+        Ok(Self { old, new, drifted })
+    }
+}
+
+/// The witness of an MPT update: the proof of the account in the state trie
+/// and, for storage updates, the proof of the slot in the storage trie of the
+/// account.
+#[derive(Clone, Debug)]
+pub(crate) struct MptUpdateWitness {
+    pub(crate) update: MptUpdate,
+    pub(crate) account_proof: MptProof,
+    pub(crate) storage_proof: Option<MptProof>,
+}
+
 /// All the MPT updates in the MptCircuit, accessible by their key
 #[derive(Default, Clone, Debug)]
-pub struct MptUpdates(HashMap<Key, MptUpdate>);
+pub struct MptUpdates {
+    updates: HashMap<Key, MptUpdate>,
+    witnesses: Vec<MptUpdateWitness>,
+    old_root: Word,
+    new_root: Word,
+}
 
 /// The field element encoding of an MPT update, which is used by the MptTable
 #[derive(Debug, Clone, Copy)]
@@ -41,11 +98,58 @@ pub struct MptUpdateRow<F>(pub(crate) [F; 7]);
 
 impl MptUpdates {
     pub(crate) fn get(&self, row: &Rw) -> Option<MptUpdate> {
-        key(row).map(|key| *self.0.get(&key).expect("missing key in mpt updates"))
+        key(row).map(|key| *self.updates.get(&key).expect("missing key in mpt updates"))
+    }
+
+    /// The state root before the updates.
+    pub(crate) fn old_root(&self) -> Word {
+        self.old_root
+    }
+
+    /// The state root after the updates.
+    pub(crate) fn new_root(&self) -> Word {
+        self.new_root
+    }
+
+    /// The updates in the order they are applied, with their proofs. This is
+    /// empty for mock updates.
+    pub(crate) fn witnesses(&self) -> &[MptUpdateWitness] {
+        &self.witnesses
+    }
+
+    /// The inputs hashed by the MptCircuit: the trie nodes, the keys of the
+    /// tries and the encodings whose hashes are the empty trie root and the
+    /// empty code hash.
+    pub(crate) fn keccak_inputs(&self) -> Vec<Vec<u8>> {
+        let nodes = self.witnesses.iter().flat_map(|witness| {
+            let proofs = std::iter::once(&witness.account_proof).chain(&witness.storage_proof);
+            proofs.flat_map(|proof| {
+                proof
+                    .old
+                    .iter()
+                    .chain(proof.new.iter())
+                    .chain(proof.drifted.iter())
+                    .cloned()
+            })
+        });
+        let keys = self.witnesses.iter().flat_map(|witness| {
+            let address = witness.update.address().as_bytes().to_vec();
+            let storage_key = witness
+                .update
+                .storage_key()
+                .map(|key| key.to_be_bytes().to_vec());
+            std::iter::once(address).chain(storage_key)
+        });
+        vec![vec![0x80], vec![]]
+            .into_iter()
+            .chain(nodes)
+            .chain(keys)
+            .unique()
+            .collect()
     }
 
     pub(crate) fn mock_from(rows: &[Rw]) -> Self {
-        let map: HashMap<_, _> = rows
+        let updates: HashMap<_, _> = rows
             .iter()
             .group_by(|row| key(row))
             .into_iter()
@@ -66,37 +170,141 @@ impl MptUpdates {
                 )
             })
             .collect();
-        MptUpdates(map)
+        MptUpdates {
+            old_root: Word::zero(),
+            new_root: Word::from(updates.len()),
+            updates,
+            witnesses: vec![],
+        }
+    }
+
+    /// Computes the updates of the rows, with their roots and proofs, from the
+    /// state proofs of the accounts and storage slots accessed by the block,
+    /// which are made against `prev_state_root`, and against the state after
+    /// the block for the nodes merged by its deletions. Without proofs, as for
+    /// the blocks built from mock data, the initial state is the one
+    /// holding the initial values of the rows instead.
+    pub(crate) fn new(
+        rows: &[Rw],
+        prev_state_root: Word,
+        proofs: &[EIP1186ProofResponse],
+    ) -> Result<Self, Error> {
+        let mut trie = Trie::default();
+        let mut root = if proofs.is_empty() {
+            mock_state(&mut trie, rows)?
+        } else {
+            for proof in proofs {
+                trie.add_proof(&proof.account_proof);
+                for storage_proof in proof.storage_proof.iter() {
+                    trie.add_proof(&storage_proof.proof);
+                }
+            }
+            Hash::from_uint(&prev_state_root)
+        };
+
+        let old_root = Word::from_big_endian(root.as_bytes());
+        let mut updates = HashMap::new();
+        let mut witnesses = vec![];
+        for (key, mut rows) in &rows.iter().group_by(|row| key(row)) {
+            let key = match key {
+                Some(key) => key,
+                None => continue,
+            };
+            let first = rows.next().unwrap();
+            let last = rows.last().unwrap_or(first);
+            let (new_root, account_proof, storage_proof) = match key {
+                Key::Account { address, field_tag } => {
+                    let (new_root, proof) =
+                        update_account(&mut trie, root, address, |_, account| {
+                            match field_tag {
+                                AccountFieldTag::Nonce => account.nonce = value(last),
+                                AccountFieldTag::Balance => account.balance = value(last),
+                                AccountFieldTag::CodeHash => {
+                                    account.code_hash = Hash::from_uint(&value(last))
+                                }
+                                AccountFieldTag::NonExisting => (),
+                            };
+                            Ok(())
+                        })?;
+                    (new_root, proof, None)
+                }
+                Key::AccountStorage {
+                    address,
+                    storage_key,
+                    ..
+                } => {
+                    let path = key_path(&storage_key.to_be_bytes());
+                    let mut storage_proof = None;
+                    let (new_root, account_proof) =
+                        update_account(&mut trie, root, address, |trie, account| {
+                            let (_, old) = trie.get(account.storage_root, &path)?;
+                            account.storage_root = trie.set(
+                                account.storage_root,
+                                &path,
+                                encode_storage_value(value(last)),
+                            )?;
+                            let (_, new) = trie.get(account.storage_root, &path)?;
+                            storage_proof = Some(MptProof::new(old, new)?);
+                            Ok(())
+                        })?;
+                    (new_root, account_proof, storage_proof)
+                }
+            };
+            let update = MptUpdate {
+                key,
+                old_value: value_prev(first),
+                new_value: value(last),
+                old_root: Word::from_big_endian(root.as_bytes()),
+                new_root: Word::from_big_endian(new_root.as_bytes()),
+            };
+            updates.insert(key, update);
+            witnesses.push(MptUpdateWitness {
+                update,
+                account_proof,
+                storage_proof,
+            });
+            root = new_root;
+        }
+
+        Ok(Self {
+            updates,
+            witnesses,
+            old_root,
+            new_root: Word::from_big_endian(root.as_bytes()),
+        })
     }
 
     pub(crate) fn table_assignments<F: Field>(
         &self,
         randomness: Value<F>,
     ) -> Vec<MptUpdateRow<Value<F>>> {
-        self.0
+        self.updates
             .values()
-            .map(|update| {
-                let (new_root, old_root) = randomness
-                    .map(|randomness| update.root_assignments(randomness))
-                    .unzip();
-                let (new_value, old_value) = randomness
-                    .map(|randomness| update.value_assignments(randomness))
-                    .unzip();
-                MptUpdateRow([
-                    Value::known(update.key.address()),
-                    randomness.map(|randomness| update.key.storage_key(randomness)),
-                    Value::known(update.proof_type()),
-                    new_root,
-                    old_root,
-                    new_value,
-                    old_value,
-                ])
-            })
+            .map(|update| update.table_row(randomness))
             .collect()
     }
 }
 
 impl MptUpdate {
+    /// The row of the update in the MptTable
+    pub(crate) fn table_row<F: Field>(&self, randomness: Value<F>) -> MptUpdateRow<Value<F>> {
+        let (new_root, old_root) = randomness
+            .map(|randomness| self.root_assignments(randomness))
+            .unzip();
+        let (new_value, old_value) = randomness
+            .map(|randomness| self.value_assignments(randomness))
+            .unzip();
+        MptUpdateRow([
+            Value::known(self.key.address()),
+            randomness.map(|randomness| self.key.storage_key(randomness)),
+            Value::known(self.proof_type()),
+            new_root,
+            old_root,
+            new_value,
+            old_value,
+        ])
+    }
+
     pub(crate) fn value_assignments<F: Field>(&self, word_randomness: F) -> (F, F) {
         let assign = |x: Word| match self.key {
             Key::Account {
@@ -165,6 +373,79 @@ impl<F> MptUpdateRow<F> {
     }
 }
 
+/// Updates the account of `address` in the state trie of `root`, returning
+/// the new root and the proof of the account. Accounts are only in the trie
+/// when they differ from the empty account: an update emptying an account
+/// deletes it.
+fn update_account(
+    trie: &mut Trie,
+    root: Hash,
+    address: Address,
+    update: impl FnOnce(&mut Trie, &mut Account) -> Result<(), Error>,
+) -> Result<(Hash, MptProof), Error> {
+    let path = key_path(address.as_bytes());
+    let (leaf, old) = trie.get(root, &path)?;
+    let old_account = leaf.as_deref().map(Account::decode).transpose()?;
+    let mut account = old_account.unwrap_or_default();
+    update(trie, &mut account)?;
+    let new_leaf =
+        (account != Account::default() || old_account == Some(account)).then(|| account.encode());
+    let new_root = trie.set(root, &path, new_leaf)?;
+    let (_, new) = trie.get(new_root, &path)?;
+    Ok((new_root, MptProof::new(old, new)?))
+}
+
+/// Adds to the trie a state holding the initial values of the rows, returning
+/// its root.
+fn mock_state(trie: &mut Trie, rows: &[Rw]) -> Result<Hash, Error> {
+    let mut accounts: BTreeMap<Address, Account> = BTreeMap::new();
+    let mut storage: BTreeMap<(Address, Word), Word> = BTreeMap::new();
+    for (key, mut rows) in &rows.iter().group_by(|row| key(row)) {
+        let initial_value = value_prev(rows.next().unwrap());
+        match key {
+            Some(Key::Account { address, field_tag }) => {
+                let account = accounts.entry(address).or_default();
+                match field_tag {
+                    AccountFieldTag::Nonce => account.nonce = initial_value,
+                    AccountFieldTag::Balance => account.balance = initial_value,
+                    AccountFieldTag::CodeHash if !initial_value.is_zero() => {
+                        account.code_hash = Hash::from_uint(&initial_value)
+                    }
+                    _ => (),
+                }
+            }
+            Some(Key::AccountStorage {
+                address,
+                storage_key,
+                ..
+            }) => {
+                // The rows are sorted by transaction first, so the value of the first one is
+                // kept.
+                storage
+                    .entry((address, storage_key))
+                    .or_insert(initial_value);
+            }
+            None => (),
+        }
+    }
+
+    let mut root = *EMPTY_TRIE_ROOT;
+    for ((address, storage_key), value) in storage {
+        let account = accounts.entry(address).or_default();
+        account.storage_root = trie.set(
+            account.storage_root,
+            &key_path(&storage_key.to_be_bytes()),
+            encode_storage_value(value),
+        )?;
+    }
+    for (address, account) in accounts {
+        if account != Account::default() {
+            root = trie.set(root, &key_path(address.as_bytes()), Some(account.encode()))?;
+        }
+    }
+    Ok(root)
+}
+
 fn key(row: &Rw) -> Option<Key> {
     match row {
         Rw::Account {
@@ -0,0 +1,600 @@
+//! A partial Merkle Patricia Trie, made of the nodes returned by
+//! `eth_getProof`, which is used to compute the roots and the proofs of the
+//! state updates of a block.
+
+use bus_mapping::Error;
+use eth_types::{Hash, Word};
+use ethers_core::utils::{
+    keccak256,
+    rlp::{Rlp, RlpStream},
+};
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+
+lazy_static! {
+    /// Root of the empty trie, which is the hash of the RLP encoding of the
+    /// empty string.
+    pub(crate) static ref EMPTY_TRIE_ROOT: Hash = Hash(keccak256([0x80]));
+    /// Code hash of the accounts without code.
+    pub(crate) static ref EMPTY_CODE_HASH: Hash = Hash(*keccak256::EMPTY_HASH);
+}
+
+/// Returns the 64 nibbles of the path of a key in a secure trie, where the
+/// keys are hashed.
+pub(crate) fn key_path(key: &[u8]) -> Vec<u8> {
+    keccak256(key)
+        .iter()
+        .flat_map(|byte| [byte >> 4, byte & 0xf])
+        .collect()
+}
+
+/// A decoded trie node. Children are referenced by their hash, as nodes
+/// shorter than 32 bytes which are embedded in their parent are not
+/// supported.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Node {
+    Branch(Box<[Option<Hash>; 16]>),
+    Extension(Vec<u8>, Hash),
+    Leaf(Vec<u8>, Vec<u8>),
+}
+
+impl Node {
+    pub(crate) fn decode(bytes: &[u8]) -> Result<Self, Error> {
+        let invalid = |_| Error::InternalError("invalid trie node encoding");
+        let rlp = Rlp::new(bytes);
+        match rlp.item_count().map_err(invalid)? {
+            17 => {
+                let mut children = [None; 16];
+                for (index, child) in children.iter_mut().enumerate() {
+                    *child = child_hash(&rlp.at(index).map_err(invalid)?)?;
+                }
+                if !rlp
+                    .at(16)
+                    .and_then(|value| value.data())
+                    .map_err(invalid)?
+                    .is_empty()
+                {
+                    return Err(Error::InternalError(
+                        "branch nodes with a value are not supported",
+                    ));
+                }
+                Ok(Self::Branch(Box::new(children)))
+            }
+            2 => {
+                let (path, is_leaf) =
+                    decode_compact(rlp.at(0).and_then(|path| path.data()).map_err(invalid)?)?;
+                let item = rlp.at(1).map_err(invalid)?;
+                if is_leaf {
+                    Ok(Self::Leaf(path, item.data().map_err(invalid)?.to_vec()))
+                } else {
+                    child_hash(&item)?
+                        .map(|child| Self::Extension(path, child))
+                        .ok_or(Error::InternalError("extension node without child"))
+                }
+            }
+            _ => Err(Error::InternalError("invalid trie node encoding")),
+        }
+    }
+
+    pub(crate) fn encode(&self) -> Vec<u8> {
+        match self {
+            Self::Branch(children) => {
+                let mut stream = RlpStream::new_list(17);
+                for child in children.iter() {
+                    match child {
+                        Some(child) => stream.append(&child.as_bytes()),
+                        None => stream.append_empty_data(),
+                    };
+                }
+                stream.append_empty_data();
+                stream.out().to_vec()
+            }
+            Self::Extension(path, child) => {
+                let mut stream = RlpStream::new_list(2);
+                stream.append(&encode_compact(path, false));
+                stream.append(&child.as_bytes());
+                stream.out().to_vec()
+            }
+            Self::Leaf(path, value) => {
+                let mut stream = RlpStream::new_list(2);
+                stream.append(&encode_compact(path, true));
+                stream.append(value);
+                stream.out().to_vec()
+            }
+        }
+    }
+}
+
+fn child_hash(item: &Rlp) -> Result<Option<Hash>, Error> {
+    if item.is_list() {
+        return Err(Error::InternalError(
+            "embedded trie nodes are not supported",
+        ));
+    }
+    let data = item
+        .data()
+        .map_err(|_| Error::InternalError("invalid trie node encoding"))?;
+    match data.len() {
+        0 => Ok(None),
+        32 => Ok(Some(Hash::from_slice(data))),
+        _ => Err(Error::InternalError("invalid trie node child")),
+    }
+}
+
+/// Decodes the hex-prefix encoding of a path, returning the nibbles and
+/// whether the path belongs to a leaf.
+fn decode_compact(bytes: &[u8]) -> Result<(Vec<u8>, bool), Error> {
+    let flag = *bytes
+        .first()
+        .ok_or(Error::InternalError("invalid trie node path"))?;
+    let (is_leaf, is_odd) = match flag >> 4 {
+        0 => (false, false),
+        1 => (false, true),
+        2 => (true, false),
+        3 => (true, true),
+        _ => return Err(Error::InternalError("invalid trie node path")),
+    };
+    let mut path = if is_odd { vec![flag & 0xf] } else { vec![] };
+    path.extend(bytes[1..].iter().flat_map(|byte| [byte >> 4, byte & 0xf]));
+    Ok((path, is_leaf))
+}
+
+/// Returns the hex-prefix encoding of a path.
+pub(crate) fn encode_compact(path: &[u8], is_leaf: bool) -> Vec<u8> {
+    let flag = if is_leaf { 0x20 } else { 0 };
+    let (first, rest) = if path.len() % 2 == 1 {
+        (flag | 0x10 | path[0], &path[1..])
+    } else {
+        (flag, path)
+    };
+    std::iter::once(first)
+        .chain(rest.chunks(2).map(|pair| pair[0] << 4 | pair[1]))
+        .collect()
+}
+
+/// The nodes of a set of tries, stored by their hash. Different roots can be
+/// used to query the state trie and the storage tries sharing the same nodes.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Trie {
+    nodes: HashMap<Hash, Vec<u8>>,
+}
+
+impl Trie {
+    /// Adds the nodes of a proof.
+    pub(crate) fn add_proof<T: AsRef<[u8]>>(&mut self, proof: &[T]) {
+        for node in proof {
+            let node = node.as_ref().to_vec();
+            self.nodes.insert(Hash(keccak256(&node)), node);
+        }
+    }
+
+    fn node(&self, hash: &Hash) -> Result<Node, Error> {
+        let bytes = self.nodes.get(hash).ok_or(Error::InternalError(
+            "trie node missing from the state proofs",
+        ))?;
+        Node::decode(bytes)
+    }
+
+    fn insert(&mut self, node: Node) -> Result<Hash, Error> {
+        let bytes = node.encode();
+        if bytes.len() < 32 {
+            return Err(Error::InternalError(
+                "embedded trie nodes are not supported",
+            ));
+        }
+        let hash = Hash(keccak256(&bytes));
+        self.nodes.insert(hash, bytes);
+        Ok(hash)
+    }
+
+    /// Returns the value of the path in the trie of `root`, together with the
+    /// encoded nodes visited from the root to find it.
+    pub(crate) fn get(
+        &self,
+        root: Hash,
+        path: &[u8],
+    ) -> Result<(Option<Vec<u8>>, Vec<Vec<u8>>), Error> {
+        let mut proof = vec![];
+        if root == *EMPTY_TRIE_ROOT {
+            return Ok((None, proof));
+        }
+        let (mut hash, mut path) = (root, path);
+        loop {
+            let node = self.node(&hash)?;
+            proof.push(self.nodes[&hash].clone());
+            match node {
+                Node::Branch(children) => match children[path[0] as usize] {
+                    Some(child) => (hash, path) = (child, &path[1..]),
+                    None => return Ok((None, proof)),
+                },
+                Node::Extension(extension, child) => {
+                    if !path.starts_with(&extension) {
+                        return Ok((None, proof));
+                    }
+                    (hash, path) = (child, &path[extension.len()..]);
+                }
+                Node::Leaf(leaf, value) => return Ok(((leaf == path).then_some(value), proof)),
+            }
+        }
+    }
+
+    /// Sets the value of the path in the trie of `root`, removing it when the
+    /// value is `None`, and returns the new root.
+    pub(crate) fn set(
+        &mut self,
+        root: Hash,
+        path: &[u8],
+        value: Option<Vec<u8>>,
+    ) -> Result<Hash, Error> {
+        let root = (root != *EMPTY_TRIE_ROOT).then_some(root);
+        Ok(self.set_at(root, path, value)?.unwrap_or(*EMPTY_TRIE_ROOT))
+    }
+
+    fn set_at(
+        &mut self,
+        hash: Option<Hash>,
+        path: &[u8],
+        value: Option<Vec<u8>>,
+    ) -> Result<Option<Hash>, Error> {
+        let node = match hash {
+            Some(hash) => self.node(&hash)?,
+            None => {
+                return value
+                    .map(|value| self.insert(Node::Leaf(path.to_vec(), value)))
+                    .transpose()
+            }
+        };
+        match node {
+            Node::Leaf(leaf, _) if leaf == path => value
+                .map(|value| self.insert(Node::Leaf(leaf, value)))
+                .transpose(),
+            Node::Leaf(leaf, leaf_value) => match value {
+                Some(value) => self
+                    .split(Node::Leaf(leaf, leaf_value), path, value)
+                    .map(Some),
+                None => Ok(hash),
+            },
+            Node::Extension(extension, child) if path.starts_with(&extension) => {
+                let child = self
+                    .set_at(Some(child), &path[extension.len()..], value)?
+                    .expect("branch has at least one child left");
+                self.prepend(extension, child).map(Some)
+            }
+            Node::Extension(extension, child) => match value {
+                Some(value) => self
+                    .split(Node::Extension(extension, child), path, value)
+                    .map(Some),
+                None => Ok(hash),
+            },
+            Node::Branch(mut children) => {
+                let index = path[0] as usize;
+                children[index] = self.set_at(children[index], &path[1..], value)?;
+                let mut remaining = children
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(index, child)| child.map(|child| (index, child)));
+                match (remaining.next(), remaining.next()) {
+                    (None, _) => Ok(None),
+                    (Some((index, child)), None) => {
+                        self.prepend(vec![index as u8], child).map(Some)
+                    }
+                    _ => self.insert(Node::Branch(children)).map(Some),
+                }
+            }
+        }
+    }
+
+    /// Returns the hash of the node made by prepending a path to the node of
+    /// `hash`, merging it into the node when it is not a branch.
+    fn prepend(&mut self, path: Vec<u8>, hash: Hash) -> Result<Hash, Error> {
+        let node = match self.node(&hash) {
+            Ok(node) => node,
+            Err(error) => self.moved_node(&hash).ok_or(error)?,
+        };
+        let node = match node {
+            Node::Branch(_) => Node::Extension(path, hash),
+            Node::Extension(extension, child) => Node::Extension([path, extension].concat(), child),
+            Node::Leaf(leaf, value) => Node::Leaf([path, leaf].concat(), value),
+        };
+        self.insert(node)
+    }
+
+    /// Returns the node of `hash` moved up by a deletion, which is missing from
+    /// the proofs of the deleted key, from the node it is merged into in the
+    /// proofs against the state after the deletion: a leaf or extension with
+    /// the end of its path, or a branch child of an extension, whose children
+    /// are not needed to prepend a path to it.
+    fn moved_node(&self, hash: &Hash) -> Option<Node> {
+        let matches = |node: &Node| Hash(keccak256(node.encode())) == *hash;
+        self.nodes
+            .values()
+            .filter_map(|bytes| Node::decode(bytes).ok())
+            .find_map(|node| match node {
+                Node::Extension(_, child) if child == *hash => {
+                    Some(Node::Branch(Box::new([None; 16])))
+                }
+                Node::Extension(path, child) => (1..path.len())
+                    .map(|start| Node::Extension(path[start..].to_vec(), child))
+                    .find(matches),
+                Node::Leaf(path, value) => (1..path.len())
+                    .map(|start| Node::Leaf(path[start..].to_vec(), value.clone()))
+                    .find(matches),
+                Node::Branch(_) => None,
+            })
+    }
+
+    /// Inserts a leaf next to a leaf or extension node whose path diverges
+    /// from it, under a new branch.
+    fn split(&mut self, node: Node, path: &[u8], value: Vec<u8>) -> Result<Hash, Error> {
+        let node_path = match &node {
+            Node::Extension(path, _) | Node::Leaf(path, _) => path.clone(),
+            Node::Branch(_) => unreachable!("branches are never split"),
+        };
+        let common = node_path
+            .iter()
+            .zip(path)
+            .take_while(|(a, b)| a == b)
+            .count();
+        let mut children = [None; 16];
+        children[node_path[common] as usize] = Some(match node {
+            Node::Extension(_, child) if node_path.len() == common + 1 => child,
+            Node::Extension(_, child) => {
+                self.insert(Node::Extension(node_path[common + 1..].to_vec(), child))?
+            }
+            Node::Leaf(_, leaf_value) => {
+                self.insert(Node::Leaf(node_path[common + 1..].to_vec(), leaf_value))?
+            }
+            Node::Branch(_) => unreachable!(),
+        });
+        children[path[common] as usize] =
+            Some(self.insert(Node::Leaf(path[common + 1..].to_vec(), value))?);
+        let branch = self.insert(Node::Branch(Box::new(children)))?;
+        if common == 0 {
+            Ok(branch)
+        } else {
+            self.insert(Node::Extension(path[..common].to_vec(), branch))
+        }
+    }
+}
+
+/// Returns the node drifting from the last node of the shorter of the proofs
+/// of a key before and after an update, when the update splits it under a new
+/// branch or collapses the branch back into it: the node with the rest of its
+/// path after the branch. An extension of the nibble of the branch only has no
+/// drifted node, its child being the child of the branch.
+pub(crate) fn drifted_node(old: &[Vec<u8>], new: &[Vec<u8>]) -> Result<Option<Vec<u8>>, Error> {
+    let (short, long) = if old.len() < new.len() {
+        (old, new)
+    } else {
+        (new, old)
+    };
+    let index = match short.len().checked_sub(1) {
+        Some(index) if long.len() > short.len() => index,
+        _ => return Ok(None),
+    };
+    let common = match Node::decode(&long[index])? {
+        Node::Extension(path, _) => path.len(),
+        _ => 0,
+    };
+    let rest = |path: &[u8]| {
+        path.get(common + 1..)
+            .map(<[u8]>::to_vec)
+            .ok_or(Error::InternalError(
+                "split node is shorter than the branch",
+            ))
+    };
+    let drifted = match Node::decode(&short[index])? {
+        Node::Leaf(path, value) => Some(Node::Leaf(rest(&path)?, value)),
+        Node::Extension(path, _) if path.len() == common + 1 => None,
+        Node::Extension(path, child) => Some(Node::Extension(rest(&path)?, child)),
+        Node::Branch(_) => None,
+    };
+    Ok(drifted.map(|node| node.encode()))
+}
+
+/// The fields of an account leaf of the state trie.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Account {
+    pub(crate) nonce: Word,
+    pub(crate) balance: Word,
+    pub(crate) storage_root: Hash,
+    pub(crate) code_hash: Hash,
+}
+
+impl Default for Account {
+    fn default() -> Self {
+        Self {
+            nonce: Word::zero(),
+            balance: Word::zero(),
+            storage_root: *EMPTY_TRIE_ROOT,
+            code_hash: *EMPTY_CODE_HASH,
+        }
+    }
+}
+
+impl Account {
+    pub(crate) fn decode(bytes: &[u8]) -> Result<Self, Error> {
+        let invalid = |_| Error::InternalError("invalid account encoding");
+        let rlp = Rlp::new(bytes);
+        let hash = |index| -> Result<Hash, Error> {
+            let data = rlp
+                .at(index)
+                .and_then(|item| item.data())
+                .map_err(invalid)?;
+            (data.len() == 32)
+                .then(|| Hash::from_slice(data))
+                .ok_or(Error::InternalError("invalid account encoding"))
+        };
+        Ok(Self {
+            nonce: rlp.val_at(0).map_err(invalid)?,
+            balance: rlp.val_at(1).map_err(invalid)?,
+            storage_root: hash(2)?,
+            code_hash: hash(3)?,
+        })
+    }
+
+    pub(crate) fn encode(&self) -> Vec<u8> {
+        let mut stream = RlpStream::new_list(4);
+        stream
+            .append(&self.nonce)
+            .append(&self.balance)
+            .append(&self.storage_root.as_bytes())
+            .append(&self.code_hash.as_bytes());
+        stream.out().to_vec()
+    }
+}
+
+/// Returns the leaf value of a storage slot, which is absent for zero.
+pub(crate) fn encode_storage_value(value: Word) -> Option<Vec<u8>> {
+    (!value.is_zero()).then(|| ethers_core::utils::rlp::encode(&value).to_vec())
+}
+
+#[cfg(test)]
+mod trie_tests {
+    use super::*;
+    use eth_types::Address;
+
+    fn storage_path(key: u64) -> Vec<u8> {
+        let mut bytes = [0u8; 32];
+        Word::from(key).to_big_endian(&mut bytes);
+        key_path(&bytes)
+    }
+
+    fn build(entries: &[(u64, u64)]) -> (Trie, Hash) {
+        let mut trie = Trie::default();
+        let mut root = *EMPTY_TRIE_ROOT;
+        for (key, value) in entries {
+            root = trie
+                .set(
+                    root,
+                    &storage_path(*key),
+                    encode_storage_value(Word::from(*value)),
+                )
+                .unwrap();
+        }
+        (trie, root)
+    }
+
+    #[test]
+    fn compact_encoding() {
+        for (path, is_leaf) in [(vec![], true), (vec![1], false), (vec![1, 2, 3, 4], true)] {
+            assert_eq!(
+                decode_compact(&encode_compact(&path, is_leaf)).unwrap(),
+                (path, is_leaf)
+            );
+        }
+        assert_eq!(encode_compact(&[1, 2, 3], true), vec![0x31, 0x23]);
+        assert_eq!(encode_compact(&[1, 2], false), vec![0x00, 0x12]);
+    }
+
+    #[test]
+    fn root_is_independent_of_insertion_order() {
+        let entries: Vec<_> = (1..40).map(|key| (key, key * 1000)).collect();
+        let (_, root) = build(&entries);
+        let (_, reversed_root) = build(&entries.iter().rev().cloned().collect::<Vec<_>>());
+        assert_eq!(root, reversed_root);
+        assert_ne!(root, *EMPTY_TRIE_ROOT);
+    }
+
+    #[test]
+    fn delete_restores_root() {
+        let entries: Vec<_> = (1..20).map(|key| (key, key + 7)).collect();
+        let (mut trie, root) = build(&entries);
+        let (_, smaller_root) = build(&entries[1..]);
+        let deleted_root = trie.set(root, &storage_path(1), None).unwrap();
+        assert_eq!(deleted_root, smaller_root);
+
+        let (mut trie, root) = build(&[(3, 4)]);
+        assert_eq!(
+            trie.set(root, &storage_path(3), None).unwrap(),
+            *EMPTY_TRIE_ROOT
+        );
+    }
+
+    #[test]
+    fn drifted_node_of_split() {
+        // Slots 1 and 2 diverge at the first nibble, slots 6 and 8 after a
+        // common one.
+        for (existing, inserted) in [(1, 2), (6, 8)] {
+            let (mut trie, root) = build(&[(existing, 1)]);
+            let new_root = trie
+                .set(
+                    root,
+                    &storage_path(inserted),
+                    encode_storage_value(Word::one()),
+                )
+                .unwrap();
+            let (_, old) = trie.get(root, &storage_path(inserted)).unwrap();
+            let (_, new) = trie.get(new_root, &storage_path(inserted)).unwrap();
+            let (_, mut moved) = trie.get(new_root, &storage_path(existing)).unwrap();
+            assert_eq!(drifted_node(&old, &new).unwrap(), moved.pop());
+            assert_eq!(
+                drifted_node(&new, &old).unwrap(),
+                drifted_node(&old, &new).unwrap()
+            );
+            assert_eq!(drifted_node(&new, &new).unwrap(), None);
+        }
+    }
+
+    #[test]
+    fn delete_from_proofs() {
+        // Deleting the slot 2 merges the leaf of the slot 1 into the root, and
+        // deleting the slot 1 merges the branch of the slots 6 and 8 into an
+        // extension.
+        for (entries, deleted) in [(vec![(1, 1), (2, 2)], 2), (vec![(1, 1), (6, 6), (8, 8)], 1)] {
+            let (mut trie, root) = build(&entries);
+            let new_root = trie.set(root, &storage_path(deleted), None).unwrap();
+            let remaining: Vec<_> = entries
+                .iter()
+                .filter(|(key, _)| *key != deleted)
+                .cloned()
+                .collect();
+            assert_eq!(new_root, build(&remaining).1);
+
+            let mut partial = Trie::default();
+            for root in [root, new_root] {
+                partial.add_proof(&trie.get(root, &storage_path(deleted)).unwrap().1);
+            }
+            assert_eq!(
+                partial.set(root, &storage_path(deleted), None).unwrap(),
+                new_root
+            );
+        }
+    }
+
+    #[test]
+    fn update_from_proof() {
+        let entries: Vec<_> = (1..30).map(|key| (key, key)).collect();
+        let (trie, root) = build(&entries);
+        let (value, proof) = trie.get(root, &storage_path(5)).unwrap();
+        assert_eq!(value, encode_storage_value(Word::from(5)));
+
+        let mut partial = Trie::default();
+        partial.add_proof(&proof);
+        let new_root = partial
+            .set(root, &storage_path(5), encode_storage_value(Word::from(6)))
+            .unwrap();
+        let mut expected: Vec<_> = entries.clone();
+        expected[4].1 = 6;
+        assert_eq!(new_root, build(&expected).1);
+    }
+
+    #[test]
+    fn account_encoding() {
+        let account = Account {
+            nonce: Word::from(3),
+            balance: Word::from(1_000_000_000u64),
+            ..Default::default()
+        };
+        assert_eq!(Account::decode(&account.encode()).unwrap(), account);
+
+        let mut trie = Trie::default();
+        let path = key_path(Address::repeat_byte(0xfe).as_bytes());
+        let root = trie
+            .set(*EMPTY_TRIE_ROOT, &path, Some(account.encode()))
+            .unwrap();
+        let (value, proof) = trie.get(root, &path).unwrap();
+        assert_eq!(value, Some(account.encode()));
+        assert_eq!(proof.len(), 1);
+    }
+}
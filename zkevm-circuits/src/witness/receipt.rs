@@ -0,0 +1,28 @@
+use eth_types::{Address, Word};
+
+/// Receipt of a transaction in a witness block, as written to the TxReceipt
+/// and TxLog rows of the rw table at the end of the transaction.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Receipt {
+    /// The transaction identifier in the block
+    pub tx_id: usize,
+    /// Whether the transaction succeeded
+    pub status: bool,
+    /// The gas used in the block up to and including this transaction
+    pub cumulative_gas_used: u64,
+    /// The number of logs emitted by the transaction
+    pub log_length: u64,
+    /// The logs emitted by the transaction, ordered by log id
+    pub logs: Vec<ReceiptLog>,
+}
+
+/// Log of a transaction receipt, as written to the TxLog rows of the rw table.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ReceiptLog {
+    /// The address of the contract which emitted the log
+    pub address: Address,
+    /// The topics of the log
+    pub topics: Vec<Word>,
+    /// The data of the log
+    pub data: Vec<u8>,
+}
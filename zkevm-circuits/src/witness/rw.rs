@@ -1,11 +1,12 @@
 #![allow(missing_docs)]
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use bus_mapping::operation::{self, AccountField, CallContextField, TxLogField, TxReceiptField};
 use eth_types::{Address, Field, ToAddress, ToLittleEndian, ToScalar, Word, U256};
 use halo2_proofs::circuit::Value;
 use itertools::Itertools;
 
+use super::{Receipt, ReceiptLog};
 use crate::util::build_tx_log_address;
 use crate::{
     evm_circuit::util::RandomLinearCombination,
@@ -67,6 +68,89 @@ impl RwMap {
         let padding = (1..=padding_length).map(|rw_counter| Rw::Start { rw_counter });
         (padding.chain(rows.into_iter()).collect(), padding_length)
     }
+
+    /// Collect the receipt of every tx from the TxReceipt writes, with its logs
+    /// from the TxLog writes, ordered by tx id
+    pub fn receipts(&self) -> Vec<Receipt> {
+        let mut receipts: BTreeMap<usize, Receipt> = BTreeMap::new();
+        for rw in self
+            .0
+            .get(&RwTableTag::TxReceipt)
+            .into_iter()
+            .flatten()
+            .filter(|rw| rw.is_write())
+        {
+            if let Rw::TxReceipt {
+                tx_id,
+                field_tag,
+                value,
+                ..
+            } = *rw
+            {
+                let receipt = receipts.entry(tx_id).or_insert_with(|| Receipt {
+                    tx_id,
+                    ..Default::default()
+                });
+                match field_tag {
+                    TxReceiptFieldTag::PostStateOrStatus => receipt.status = value == 1,
+                    TxReceiptFieldTag::CumulativeGasUsed => receipt.cumulative_gas_used = value,
+                    TxReceiptFieldTag::LogLength => receipt.log_length = value,
+                }
+            }
+        }
+
+        let mut logs: BTreeMap<(usize, u64), ReceiptLog> = BTreeMap::new();
+        for rw in self
+            .0
+            .get(&RwTableTag::TxLog)
+            .into_iter()
+            .flatten()
+            .sorted_by_key(|rw| (rw.id(), rw.address()))
+        {
+            if let Rw::TxLog {
+                tx_id,
+                log_id,
+                field_tag,
+                value,
+                ..
+            } = *rw
+            {
+                let log = logs.entry((tx_id, log_id)).or_default();
+                match field_tag {
+                    TxLogFieldTag::Address => log.address = value.to_address(),
+                    TxLogFieldTag::Topic => log.topics.push(value),
+                    TxLogFieldTag::Data => log.data.push(value.byte(0)),
+                    TxLogFieldTag::TopicLength | TxLogFieldTag::DataLength => {}
+                }
+            }
+        }
+        for ((tx_id, _), log) in logs {
+            if let Some(receipt) = receipts.get_mut(&tx_id) {
+                receipt.logs.push(log);
+            }
+        }
+
+        receipts.into_values().collect()
+    }
+    /// Collect the TxLog writes of the address and topics of every log, which
+    /// are the items accrued into the logs bloom, ordered by rw counter
+    pub fn logs_bloom_items(&self) -> Vec<Rw> {
+        self.0
+            .get(&RwTableTag::TxLog)
+            .into_iter()
+            .flatten()
+            .filter(|rw| {
+                matches!(
+                    rw,
+                    Rw::TxLog {
+                        field_tag: TxLogFieldTag::Address | TxLogFieldTag::Topic,
+                        ..
+                    }
+                )
+            })
+            .cloned()
+            .collect()
+    }
     /// Build Rws for assignment
     pub fn table_assignments(&self) -> Vec<Rw> {
         let mut rows: Vec<Rw> = self.0.values().flatten().cloned().collect();
@@ -252,6 +336,17 @@ impl Rw {
         }
     }
 
+    pub(crate) fn account_destructed_value_pair(&self) -> (bool, bool) {
+        match self {
+            Self::AccountDestructed {
+                is_destructed,
+                is_destructed_prev,
+                ..
+            } => (*is_destructed, *is_destructed_prev),
+            _ => unreachable!(),
+        }
+    }
+
     pub(crate) fn tx_refund_value_pair(&self) -> (u64, u64) {
         match self {
             Self::TxRefund {
@@ -435,12 +530,13 @@ impl Rw {
             | Self::TxAccessListAccount { tx_id, .. }
             | Self::TxAccessListAccountStorage { tx_id, .. }
             | Self::TxRefund { tx_id, .. }
+            | Self::AccountDestructed { tx_id, .. }
             | Self::TxLog { tx_id, .. }
             | Self::TxReceipt { tx_id, .. } => Some(*tx_id),
             Self::CallContext { call_id, .. }
             | Self::Stack { call_id, .. }
             | Self::Memory { call_id, .. } => Some(*call_id),
-            Self::Start { .. } | Self::Account { .. } | Self::AccountDestructed { .. } => None,
+            Self::Start { .. } | Self::Account { .. } => None,
         }
     }
 
@@ -818,6 +914,8 @@ impl From<&operation::OperationContainer> for RwMap {
                         TxLogField::Address => TxLogFieldTag::Address,
                         TxLogField::Topic => TxLogFieldTag::Topic,
                         TxLogField::Data => TxLogFieldTag::Data,
+                        TxLogField::TopicLength => TxLogFieldTag::TopicLength,
+                        TxLogField::DataLength => TxLogFieldTag::DataLength,
                     },
                     index: op.op().index,
                     value: op.op().value,
@@ -1,5 +1,8 @@
 use bus_mapping::circuit_input_builder;
-use eth_types::{Address, Field, ToLittleEndian, ToScalar, ToWord, Word};
+use eth_types::{
+    geth_types::access_list_entries, AccessList, Address, Field, ToLittleEndian, ToScalar, ToWord,
+    Word,
+};
 use halo2_proofs::circuit::Value;
 
 use crate::{
@@ -19,9 +22,13 @@ pub struct Transaction {
     pub gas: u64,
     /// The gas price
     pub gas_price: Word,
+    /// The gas fee cap
+    pub gas_fee_cap: Word,
+    /// The gas tip cap
+    pub gas_tip_cap: Word,
     /// The caller address
     pub caller_address: Address,
-    /// The callee address
+    /// The callee address, which is zero for a creation transaction
     pub callee_address: Address,
     /// Whether it's a create transaction
     pub is_create: bool,
@@ -33,6 +40,12 @@ pub struct Transaction {
     pub call_data_length: usize,
     /// The gas cost for transaction call data
     pub call_data_gas_cost: u64,
+    /// The number of addresses in the access list
+    pub access_list_addresses_len: u64,
+    /// The number of storage keys in the access list
+    pub access_list_storage_keys_len: u64,
+    /// The access list
+    pub access_list: AccessList,
     /// The calls made in the transaction
     pub calls: Vec<Call>,
     /// The steps executioned in the transaction
@@ -70,6 +83,28 @@ impl Transaction {
                         )
                     }),
                 ],
+                [
+                    Value::known(F::from(self.id as u64)),
+                    Value::known(F::from(TxContextFieldTag::GasFeeCap as u64)),
+                    Value::known(F::zero()),
+                    challenges.evm_word().map(|evm_word| {
+                        RandomLinearCombination::random_linear_combine(
+                            self.gas_fee_cap.to_le_bytes(),
+                            evm_word,
+                        )
+                    }),
+                ],
+                [
+                    Value::known(F::from(self.id as u64)),
+                    Value::known(F::from(TxContextFieldTag::GasTipCap as u64)),
+                    Value::known(F::zero()),
+                    challenges.evm_word().map(|evm_word| {
+                        RandomLinearCombination::random_linear_combine(
+                            self.gas_tip_cap.to_le_bytes(),
+                            evm_word,
+                        )
+                    }),
+                ],
                 [
                     Value::known(F::from(self.id as u64)),
                     Value::known(F::from(TxContextFieldTag::CallerAddress as u64)),
@@ -111,6 +146,18 @@ impl Transaction {
                     Value::known(F::zero()),
                     Value::known(F::from(self.call_data_gas_cost)),
                 ],
+                [
+                    Value::known(F::from(self.id as u64)),
+                    Value::known(F::from(TxContextFieldTag::AccessListAddressesLen as u64)),
+                    Value::known(F::zero()),
+                    Value::known(F::from(self.access_list_addresses_len)),
+                ],
+                [
+                    Value::known(F::from(self.id as u64)),
+                    Value::known(F::from(TxContextFieldTag::AccessListStorageKeysLen as u64)),
+                    Value::known(F::zero()),
+                    Value::known(F::from(self.access_list_storage_keys_len)),
+                ],
             ],
             self.call_data
                 .iter()
@@ -124,6 +171,31 @@ impl Transaction {
                     ]
                 })
                 .collect(),
+            access_list_entries(&self.access_list)
+                .into_iter()
+                .enumerate()
+                .flat_map(|(idx, (address, key))| {
+                    [
+                        [
+                            Value::known(F::from(self.id as u64)),
+                            Value::known(F::from(TxContextFieldTag::AccessListAddress as u64)),
+                            Value::known(F::from(idx as u64)),
+                            Value::known(address.to_scalar().unwrap()),
+                        ],
+                        [
+                            Value::known(F::from(self.id as u64)),
+                            Value::known(F::from(TxContextFieldTag::AccessListStorageKey as u64)),
+                            Value::known(F::from(idx as u64)),
+                            challenges.evm_word().map(|evm_word| {
+                                RandomLinearCombination::random_linear_combine(
+                                    key.unwrap_or_default().to_le_bytes(),
+                                    evm_word,
+                                )
+                            }),
+                        ],
+                    ]
+                })
+                .collect(),
         ]
         .concat()
     }
@@ -135,8 +207,14 @@ pub(super) fn tx_convert(tx: &circuit_input_builder::Transaction, id: usize) ->
         nonce: tx.nonce,
         gas: tx.gas,
         gas_price: tx.gas_price,
+        gas_fee_cap: tx.gas_fee_cap,
+        gas_tip_cap: tx.gas_tip_cap,
         caller_address: tx.from,
-        callee_address: tx.to,
+        callee_address: if tx.is_create() {
+            Address::zero()
+        } else {
+            tx.to
+        },
         is_create: tx.is_create(),
         value: tx.value,
         call_data: tx.input.clone(),
@@ -145,6 +223,14 @@ pub(super) fn tx_convert(tx: &circuit_input_builder::Transaction, id: usize) ->
             .input
             .iter()
             .fold(0, |acc, byte| acc + if *byte == 0 { 4 } else { 16 }),
+        access_list_addresses_len: tx.access_list.0.len() as u64,
+        access_list_storage_keys_len: tx
+            .access_list
+            .0
+            .iter()
+            .map(|item| item.storage_keys.len() as u64)
+            .sum(),
+        access_list: tx.access_list.clone(),
         calls: tx
             .calls()
             .iter()
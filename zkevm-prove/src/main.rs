@@ -0,0 +1,439 @@
+//! Prove a block with the zkEVM circuits from a JSON file with its witness.
+
+use anyhow::{anyhow, bail, Context, Result};
+use bus_mapping::{
+    circuit_input_builder::{CircuitInputBuilder, CircuitsParams},
+    mock::BlockData,
+};
+use clap::{ArgEnum, Args, Parser, Subcommand};
+use env_logger::Env;
+use eth_types::{evm_types::Hardfork, geth_types::GethData, Word};
+use halo2_proofs::{
+    dev::MockProver,
+    halo2curves::{
+        bn256::{Bn256, Fr},
+        group::ff::PrimeField,
+    },
+    plonk::{Circuit, ConstraintSystem},
+    poly::{commitment::Params, kzg::commitment::ParamsKZG},
+};
+use log::{error, info};
+use rand::rngs::OsRng;
+use std::{
+    fs::{self, File},
+    io::BufReader,
+    path::{Path, PathBuf},
+};
+use zkevm_circuits::{prover, super_circuit::SuperCircuit};
+
+/// Run the SuperCircuit with the capacity of `--max-txs` and `--max-calldata`
+/// among the listed ones, since its configuration depends on them.
+macro_rules! dispatch_super_circuit {
+    (
+        $command:expr,
+        $args:expr,
+        $builder:expr,
+        [$(($max_txs:literal, $max_calldata:literal)),* $(,)?]
+    ) => {
+        match ($args.max_txs, $args.max_calldata) {
+            $(
+                ($max_txs, $max_calldata) => {
+                    run_super_circuit::<$max_txs, $max_calldata>($command, $args, $builder)
+                }
+            )*
+            (max_txs, max_calldata) => bail!(
+                "the super circuit doesn't support {} txs with {} bytes of calldata, only {}",
+                max_txs,
+                max_calldata,
+                [$(format!("{} txs with {} bytes", $max_txs, $max_calldata)),*].join(", ")
+            ),
+        }
+    };
+}
+
+/// Prove a block with the zkEVM circuits
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct Cli {
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Check the witness of the block with the MockProver
+    Mock(CircuitArgs),
+    /// Prove the block, writing the proof and its public inputs
+    Prove(ProofArgs),
+    /// Verify a proof of the block against its public inputs
+    Verify(ProofArgs),
+}
+
+#[derive(Args, Debug)]
+struct CircuitArgs {
+    /// JSON file with the serialized `GethData` of the block: the block with
+    /// its transactions, their traces, the accounts they touch and the history
+    /// hashes
+    witness: PathBuf,
+
+    /// Circuit to build from the block, the sub-circuits need the
+    /// `sub-circuits` feature
+    #[clap(long, arg_enum, default_value = "super")]
+    circuit: CircuitKind,
+
+    /// Hardfork of the block
+    #[clap(long, default_value = "london")]
+    hardfork: Hardfork,
+
+    /// Reward in wei paid to the coinbase, none when it's 0
+    #[clap(long, default_value = "0", parse(try_from_str = Word::from_dec_str))]
+    block_reward: Word,
+
+    /// Degree of the circuit, computed from the block when it's not set
+    #[clap(long)]
+    degree: Option<u32>,
+
+    /// Maximum number of txs of the block.  The super circuit supports 4 txs
+    /// with 512 bytes of calldata, 16 with 4096, 64 with 32768, 256 with 131072
+    /// and 1024 with 1048576.
+    #[clap(long, default_value = "4")]
+    max_txs: usize,
+
+    /// Maximum number of bytes of calldata of all the txs of the block
+    #[clap(long, default_value = "512")]
+    max_calldata: usize,
+
+    /// Maximum number of rw operations of the block
+    #[clap(long, default_value = "5888")]
+    max_rws: usize,
+
+    /// Maximum number of addresses and storage keys of the access lists of all
+    /// the txs of the block
+    #[clap(long, default_value = "64")]
+    max_access_list: usize,
+
+    /// Maximum number of bytes of all the bytecodes run by the block
+    #[clap(long, default_value = "5000")]
+    max_bytecode: usize,
+
+    /// Maximum number of calls to the ecrecover precompile of the block
+    #[clap(long, default_value = "0")]
+    max_ecrecover: usize,
+
+    /// Maximum number of bytes of the encoding of the receipts of all the txs
+    /// of the block
+    #[clap(long, default_value = "2048")]
+    max_receipts_len: usize,
+}
+
+#[derive(Args, Debug)]
+struct ProofArgs {
+    #[clap(flatten)]
+    circuit: CircuitArgs,
+
+    /// File of the proof
+    #[clap(long)]
+    proof: PathBuf,
+
+    /// JSON file of the public inputs of the proof, as hex field elements by
+    /// instance column
+    #[clap(long)]
+    instances: PathBuf,
+
+    /// File of the KZG parameters from a trusted setup, of a degree at least
+    /// the one of the circuit
+    #[clap(long)]
+    params: PathBuf,
+}
+
+#[derive(ArgEnum, Clone, Copy, Debug)]
+enum CircuitKind {
+    Super,
+    Evm,
+    State,
+    Tx,
+    Bytecode,
+    Copy,
+    Exp,
+    Keccak,
+}
+
+fn main() -> Result<()> {
+    env_logger::Builder::from_env(Env::default().default_filter_or("info")).init();
+
+    let cli = Cli::parse();
+    let args = match &cli.command {
+        Command::Mock(args) => args,
+        Command::Prove(args) | Command::Verify(args) => &args.circuit,
+    };
+
+    let geth_data: GethData = serde_json::from_slice(
+        &fs::read(&args.witness)
+            .with_context(|| format!("failed to read {}", args.witness.display()))?,
+    )
+    .context("failed to parse the witness")?;
+    let circuits_params = CircuitsParams {
+        max_rws: args.max_rws,
+        max_txs: args.max_txs,
+        max_calldata: args.max_calldata,
+        max_access_list: args.max_access_list,
+        max_bytecode: args.max_bytecode,
+        max_ecrecover: args.max_ecrecover,
+        max_receipts_len: args.max_receipts_len,
+        keccak_padding: None,
+        block_reward: args.block_reward,
+        hardfork: args.hardfork,
+    };
+    let mut builder = BlockData::new_from_geth_data_with_params(geth_data.clone(), circuits_params)
+        .new_circuit_input_builder();
+    builder
+        .handle_block(&geth_data.eth_block, &geth_data.geth_traces)
+        .map_err(|err| anyhow!("failed to handle the block: {}", err))?;
+
+    match args.circuit {
+        CircuitKind::Super => dispatch_super_circuit!(
+            &cli.command,
+            args,
+            &builder,
+            [
+                (4, 512),
+                (16, 4096),
+                (64, 32768),
+                (256, 131072),
+                (1024, 1048576)
+            ]
+        ),
+        kind => sub_circuit::run(&cli.command, args, kind, &builder),
+    }
+}
+
+fn run_super_circuit<const MAX_TXS: usize, const MAX_CALLDATA: usize>(
+    command: &Command,
+    args: &CircuitArgs,
+    builder: &CircuitInputBuilder,
+) -> Result<()> {
+    // `MAX_RWS` is only the capacity of the blocks built by `SuperCircuit::build`,
+    // this one is already built with `--max-rws`.
+    let (degree, circuit, instance) =
+        SuperCircuit::<Fr, MAX_TXS, MAX_CALLDATA, 0>::build_from_circuit_input_builder(builder)
+            .map_err(|err| anyhow!("failed to build the super circuit: {}", err))?;
+    run(command, args.degree.unwrap_or(degree), circuit, instance)
+}
+
+/// The sub-circuits can only be built on their own with the test harnesses of
+/// zkevm-circuits, which load the tables they look up into themselves.
+#[cfg(feature = "sub-circuits")]
+mod sub_circuit {
+    use super::{CircuitArgs, CircuitKind, Command};
+    use anyhow::{anyhow, Result};
+    use bus_mapping::circuit_input_builder::CircuitInputBuilder;
+    use halo2_proofs::{halo2curves::bn256::Fr, plonk::Circuit};
+    use zkevm_circuits::{
+        bytecode_circuit::bytecode_unroller::BytecodeCircuit,
+        copy_circuit::CopyCircuit,
+        evm_circuit::test::{get_test_cicuit_from_block, get_test_degree},
+        exp_circuit::ExpCircuit,
+        keccak_circuit::keccak_packed_multi::KeccakCircuit,
+        state_circuit::StateCircuit,
+        tx_circuit::TxCircuit,
+        util::{log2_ceil, SubCircuit},
+        witness::{block_convert, Block},
+    };
+
+    /// Rows reserved for the blinding factors when the degree of a sub-circuit
+    /// is computed from the block.
+    const NUM_BLINDING_ROWS: usize = 64;
+
+    pub(super) fn run(
+        command: &Command,
+        args: &CircuitArgs,
+        kind: CircuitKind,
+        builder: &CircuitInputBuilder,
+    ) -> Result<()> {
+        let block = block_convert(&builder.block, &builder.code_db)
+            .map_err(|err| anyhow!("failed to convert the block: {}", err))?;
+
+        match kind {
+            CircuitKind::Super => unreachable!("the super circuit isn't a sub-circuit"),
+            CircuitKind::Evm => {
+                let degree = args.degree.unwrap_or_else(|| get_test_degree(&block));
+                let circuit = get_test_cicuit_from_block(block);
+                let instance = circuit.instance();
+                super::run(command, degree, circuit, instance)
+            }
+            CircuitKind::State => run_sub_circuit::<StateCircuit<Fr>>(command, args, &block),
+            CircuitKind::Tx => run_sub_circuit::<TxCircuit<Fr>>(command, args, &block),
+            CircuitKind::Bytecode => run_sub_circuit::<BytecodeCircuit<Fr>>(command, args, &block),
+            CircuitKind::Copy => run_sub_circuit::<CopyCircuit<Fr>>(command, args, &block),
+            CircuitKind::Exp => run_sub_circuit::<ExpCircuit<Fr>>(command, args, &block),
+            CircuitKind::Keccak => run_sub_circuit::<KeccakCircuit<Fr>>(command, args, &block),
+        }
+    }
+
+    fn run_sub_circuit<C: SubCircuit<Fr> + Circuit<Fr>>(
+        command: &Command,
+        args: &CircuitArgs,
+        block: &Block<Fr>,
+    ) -> Result<()> {
+        let degree = args
+            .degree
+            .unwrap_or_else(|| log2_ceil(C::min_num_rows_block(block) + NUM_BLINDING_ROWS));
+        let circuit = C::new_from_block(block);
+        let instance = circuit.instance();
+        super::run(command, degree, circuit, instance)
+    }
+}
+
+#[cfg(not(feature = "sub-circuits"))]
+mod sub_circuit {
+    use super::{CircuitArgs, CircuitKind, Command};
+    use anyhow::{bail, Result};
+    use bus_mapping::circuit_input_builder::CircuitInputBuilder;
+
+    pub(super) fn run(
+        _: &Command,
+        _: &CircuitArgs,
+        kind: CircuitKind,
+        _: &CircuitInputBuilder,
+    ) -> Result<()> {
+        bail!(
+            "the {:?} circuit can only be run with the `sub-circuits` feature",
+            kind
+        )
+    }
+}
+
+fn run<C: Circuit<Fr>>(
+    command: &Command,
+    degree: u32,
+    circuit: C,
+    mut instance: Vec<Vec<Fr>>,
+) -> Result<()> {
+    // Some circuits leave out their instance columns without public inputs.
+    let mut cs = ConstraintSystem::default();
+    C::configure(&mut cs);
+    instance.resize(cs.num_instance_columns(), vec![]);
+    info!("circuit of degree {}", degree);
+
+    match command {
+        Command::Mock(_) => {
+            let mock_prover = MockProver::<Fr>::run(degree, &circuit, instance)
+                .map_err(|err| anyhow!("failed to synthesize the circuit: {:?}", err))?;
+            if let Err(failures) = mock_prover.verify_par() {
+                for failure in &failures {
+                    error!("{}", failure);
+                }
+                bail!("{} constraints are not satisfied", failures.len());
+            }
+            info!("the witness satisfies the circuit");
+        }
+        Command::Prove(args) => {
+            let params = load_params(&args.params, degree)?;
+            let pk = prover::gen_pk(&params, &circuit)
+                .map_err(|err| anyhow!("failed to generate the proving key: {:?}", err))?;
+            let proof = prover::create_proof(&params, &pk, circuit, &instance, OsRng)
+                .map_err(|err| anyhow!("failed to create the proof: {:?}", err))?;
+
+            fs::write(&args.proof, proof)
+                .with_context(|| format!("failed to write {}", args.proof.display()))?;
+            fs::write(
+                &args.instances,
+                serde_json::to_string_pretty(&encode_instance(&instance))?,
+            )
+            .with_context(|| format!("failed to write {}", args.instances.display()))?;
+            info!("proof written to {}", args.proof.display());
+        }
+        Command::Verify(args) => {
+            let proof = fs::read(&args.proof)
+                .with_context(|| format!("failed to read {}", args.proof.display()))?;
+            let instance = decode_instance(serde_json::from_slice(
+                &fs::read(&args.instances)
+                    .with_context(|| format!("failed to read {}", args.instances.display()))?,
+            )?)?;
+
+            // The fixed columns of some circuits depend on the block, so the
+            // verifying key is generated from it instead of being cached.
+            let params = load_params(&args.params, degree)?;
+            let vk = prover::gen_vk(&params, &circuit)
+                .map_err(|err| anyhow!("failed to generate the verifying key: {:?}", err))?;
+            prover::verify_proof(&prover::verifier_params(&params), &vk, &proof, &instance)
+                .map_err(|err| anyhow!("the proof is invalid: {:?}", err))?;
+            info!("the proof is valid");
+        }
+    }
+
+    Ok(())
+}
+
+/// Load the parameters of a trusted setup, downsized to the degree of the
+/// circuit.
+fn load_params(path: &Path, degree: u32) -> Result<ParamsKZG<Bn256>> {
+    let file = File::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+    let mut params = ParamsKZG::<Bn256>::read(&mut BufReader::new(file))
+        .with_context(|| format!("failed to read the params {}", path.display()))?;
+    if params.k() < degree {
+        bail!(
+            "the params {} are for circuits of degree up to {}, not {}",
+            path.display(),
+            params.k(),
+            degree
+        );
+    }
+    params.downsize(degree);
+    Ok(params)
+}
+
+/// Encode the field elements as big-endian hex strings.
+fn encode_instance(instance: &[Vec<Fr>]) -> Vec<Vec<String>> {
+    instance
+        .iter()
+        .map(|column| {
+            column
+                .iter()
+                .map(|value| {
+                    let mut bytes = value.to_repr();
+                    bytes.reverse();
+                    format!("0x{}", hex::encode(bytes))
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn decode_instance(instance: Vec<Vec<String>>) -> Result<Vec<Vec<Fr>>> {
+    instance
+        .iter()
+        .map(|column| {
+            column
+                .iter()
+                .map(|value| {
+                    let mut bytes = [0; 32];
+                    hex::decode_to_slice(value.trim_start_matches("0x"), &mut bytes)
+                        .with_context(|| format!("invalid public input {}", value))?;
+                    bytes.reverse();
+                    Option::from(Fr::from_repr(bytes))
+                        .ok_or_else(|| anyhow!("public input {} is not a field element", value))
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn instance_encoding() {
+        let instance = vec![vec![Fr::from(1), -Fr::from(1)], vec![]];
+        let encoded = encode_instance(&instance);
+        assert_eq!(
+            encoded[0][0],
+            "0x0000000000000000000000000000000000000000000000000000000000000001"
+        );
+        assert_eq!(decode_instance(encoded).unwrap(), instance);
+
+        let modulus = "0x30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001";
+        assert!(decode_instance(vec![vec![modulus.to_string()]]).is_err());
+    }
+}